@@ -0,0 +1,378 @@
+use std::path::Path;
+use image::{DynamicImage, Rgb32FImage, RgbImage};
+use crate::gamut;
+use crate::spectrum::{Spectrum, NBR_OF_SAMPLES_MAX};
+
+/// The file names of the arrays written by [Accumulation::export].
+const RGB_SUM_FILE_NAME: &str = "rgb_sum.npy";
+const SAMPLE_COUNT_FILE_NAME: &str = "sample_count.npy";
+const SPECTRAL_SUM_FILE_NAME: &str = "spectral_sum.npy";
+const WAVELENGTHS_FILE_NAME: &str = "wavelengths.npy";
+/// The relative errors shown at the cold and the hot end of the noise heatmap, the colors are 
+/// spread logarithmically in between. 
+const HEATMAP_LOWEST_ERROR: f32 = 0.001;
+const HEATMAP_HIGHEST_ERROR: f32 = 0.1;
+/// The colors of the noise heatmap from the lowest to the highest error. 
+const HEATMAP_COLORS: [[f32; 3]; 4] = [[0.0, 0.0, 0.4], [0.0, 0.7, 0.3], [1.0, 0.9, 0.0], [0.9, 0.0, 0.0]];
+/// The color of pixels with fewer than two samples, whose noise is unknown. 
+const HEATMAP_UNKNOWN_COLOR: [u8; 3] = [128, 128, 128];
+
+/// The raw accumulation state of a render: the unfiltered sums of all samples per pixel, the
+/// number of samples per pixel and optionally the sums of the spectra the samples were converted
+/// from. Dividing a sum by the sample count yields the estimate of the pixel. In contrast to the
+/// displayed image, the sums are not affected by the firefly filter or the gamut compression.
+#[derive(Clone)]
+pub struct Accumulation {
+    width: u32,
+    height: u32,
+    /// r, g, b per pixel, row by row.
+    rgb_sums: Vec<f32>,
+    sample_counts: Vec<u32>,
+    /// The sums of the luminances of the samples and of their squares per pixel, from which the 
+    /// variance of the pixels is estimated, see [Accumulation::relative_errors]. 
+    luminance_sums: Vec<f64>,
+    luminance_square_sums: Vec<f64>,
+    /// The sampled wavelengths and one intensity per wavelength and pixel, row by row. Only
+    /// present if spectra are recorded.
+    spectral_sums: Option<(Vec<f32>, Vec<f32>)>,
+}
+
+impl Accumulation {
+    /// Creates an empty accumulation for an image of the given size. If an example spectrum is
+    /// given, the spectra of all samples are recorded as well, using its wavelengths.
+    pub fn new(width: u32, height: u32, example_spectrum: Option<&Spectrum>) -> Self {
+        let nbr_of_pixels = (width * height) as usize;
+        let spectral_sums = example_spectrum.map(|spectrum| {
+            (spectrum.get_wavelengths(), vec![0.0; nbr_of_pixels * spectrum.get_nbr_of_samples()])
+        });
+
+        Accumulation {
+            width,
+            height,
+            rgb_sums: vec![0.0; nbr_of_pixels * 3],
+            sample_counts: vec![0; nbr_of_pixels],
+            luminance_sums: vec![0.0; nbr_of_pixels],
+            luminance_square_sums: vec![0.0; nbr_of_pixels],
+            spectral_sums,
+        }
+    }
+
+    /// Returns true if the spectra of the samples are recorded.
+    pub fn records_spectra(&self) -> bool {
+        self.spectral_sums.is_some()
+    }
+
+    /// Adds one sample to each pixel of the rectangle starting at x, y with the given width. The
+    /// rgb values hold r, g, b per pixel, row by row. If spectra are recorded, the spectra of the
+    /// samples have to be given in the same order.
+    pub fn add_samples(&mut self, x: u32, y: u32, width: u32, rgb: &[f32], spectra: Option<&[Spectrum]>) {
+        for (i, pixel) in rgb.chunks_exact(3).enumerate() {
+            let pixel_index = ((y + i as u32 / width) * self.width + x + i as u32 % width) as usize;
+            for (sum, value) in self.rgb_sums[pixel_index * 3..pixel_index * 3 + 3].iter_mut().zip(pixel) {
+                *sum += value;
+            }
+            self.sample_counts[pixel_index] += 1;
+            let luminance = gamut::luminance((pixel[0], pixel[1], pixel[2])) as f64;
+            self.luminance_sums[pixel_index] += luminance;
+            self.luminance_square_sums[pixel_index] += luminance * luminance;
+
+            if let (Some((wavelengths, sums)), Some(spectra)) = (&mut self.spectral_sums, spectra) {
+                let nbr_of_samples = wavelengths.len();
+                let pixel_sums = &mut sums[pixel_index * nbr_of_samples..(pixel_index + 1) * nbr_of_samples];
+                for (sum, (_, intensity)) in pixel_sums.iter_mut().zip(spectra[i].iter()) {
+                    *sum += intensity;
+                }
+            }
+        }
+    }
+
+    /// Returns the relative standard error of the luminance of every pixel, row by row: the 
+    /// standard deviation of the mean of its samples divided by the mean. It halves with every 
+    /// quadrupling of the samples. None for pixels with fewer than two samples. Black pixels 
+    /// without any variance have no error. 
+    pub fn relative_errors(&self) -> Vec<Option<f32>> {
+        self.sample_counts.iter().zip(&self.luminance_sums).zip(&self.luminance_square_sums)
+            .map(|((&count, &sum), &square_sum)| {
+                if count < 2 {
+                    return None;
+                }
+                let n = count as f64;
+                let mean = sum / n;
+                let variance = ((square_sum / n - mean * mean) * n / (n - 1.0)).max(0.0);
+                if variance == 0.0 {
+                    return Some(0.0);
+                }
+                Some(((variance / n).sqrt() / mean.abs().max(f64::MIN_POSITIVE)) as f32)
+            }).collect()
+    }
+
+    /// Returns the relative error which the given share of the pixels with a known error stays 
+    /// below, e.g. 0.95 for all but the noisiest 5 percent. None if no error is known yet. 
+    pub fn error_quantile(&self, quantile: f32) -> Option<f32> {
+        let mut errors: Vec<f32> = self.relative_errors().into_iter().flatten().collect();
+        if errors.is_empty() {
+            return None;
+        }
+        let index = ((errors.len() - 1) as f32 * quantile.clamp(0.0, 1.0)).round() as usize;
+        Some(*errors.select_nth_unstable_by(index, f32::total_cmp).1)
+    }
+
+    /// Renders the [relative errors](Accumulation::relative_errors) as an image: dark blue where 
+    /// the error is below [HEATMAP_LOWEST_ERROR], through green and yellow to red where it is above 
+    /// [HEATMAP_HIGHEST_ERROR]. Pixels with an unknown error are gray. 
+    pub fn noise_heatmap(&self) -> DynamicImage {
+        let (lowest, highest) = (HEATMAP_LOWEST_ERROR.log10(), HEATMAP_HIGHEST_ERROR.log10());
+        let pixels = self.relative_errors().into_iter().flat_map(|error| {
+            let Some(error) = error else {
+                return HEATMAP_UNKNOWN_COLOR;
+            };
+            let t = ((error.max(f32::MIN_POSITIVE).log10() - lowest) / (highest - lowest)).clamp(0.0, 1.0);
+            let position = t * (HEATMAP_COLORS.len() - 1) as f32;
+            let index = (position as usize).min(HEATMAP_COLORS.len() - 2);
+            let fraction = position - index as f32;
+            let (from, to) = (HEATMAP_COLORS[index], HEATMAP_COLORS[index + 1]);
+            [0, 1, 2].map(|c| ((from[c] + (to[c] - from[c]) * fraction) * 255.0) as u8)
+        }).collect();
+        RgbImage::from_raw(self.width, self.height, pixels).unwrap().into()
+    }
+
+    /// Writes the accumulation into the given directory as NumPy .npy files (format version 1.0,
+    /// little endian, C order):
+    /// - rgb_sum.npy: float32, shape (height, width, 3)
+    /// - sample_count.npy: uint32, shape (height, width)
+    /// - spectral_sum.npy: float32, shape (height, width, samples), if spectra are recorded
+    /// - wavelengths.npy: float32 in nanometers, shape (samples,), if spectra are recorded
+    pub fn export(&self, directory: &Path) -> Result<(), AccumulationError> {
+        let (width, height) = (self.width as usize, self.height as usize);
+
+        write_npy(&directory.join(RGB_SUM_FILE_NAME), "<f4", &[height, width, 3],
+                  &to_le_bytes(&self.rgb_sums, |v| v.to_le_bytes()))?;
+        write_npy(&directory.join(SAMPLE_COUNT_FILE_NAME), "<u4", &[height, width],
+                  &to_le_bytes(&self.sample_counts, |v| v.to_le_bytes()))?;
+        if let Some((wavelengths, sums)) = &self.spectral_sums {
+            write_npy(&directory.join(SPECTRAL_SUM_FILE_NAME), "<f4", &[height, width, wavelengths.len()],
+                      &to_le_bytes(sums, |v| v.to_le_bytes()))?;
+            write_npy(&directory.join(WAVELENGTHS_FILE_NAME), "<f4", &[wavelengths.len()],
+                      &to_le_bytes(wavelengths, |v| v.to_le_bytes()))?;
+        }
+        Ok(())
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the average linear r, g, b values of the pixel at x, y and the number of its 
+    /// samples. None if the pixel is outside the image or has no samples yet. 
+    pub fn pixel_rgb(&self, x: u32, y: u32) -> Option<((f32, f32, f32), u32)> {
+        let index = self.pixel_index(x, y)?;
+        let count = self.sample_counts[index];
+        if count == 0 {
+            return None;
+        }
+        let sums = &self.rgb_sums[index * 3..index * 3 + 3];
+        Some(((sums[0] / count as f32, sums[1] / count as f32, sums[2] / count as f32), count))
+    }
+
+    /// Returns the average spectrum of the samples of the pixel at x, y, sampled at the recorded 
+    /// wavelengths. None if spectra are not recorded, the pixel is outside the image or has no 
+    /// samples yet. 
+    pub fn pixel_spectrum(&self, x: u32, y: u32) -> Option<Spectrum> {
+        let (wavelengths, sums) = self.spectral_sums.as_ref()?;
+        let index = self.pixel_index(x, y)?;
+        let count = self.sample_counts[index];
+        if count == 0 || wavelengths.len() < 2 {
+            return None;
+        }
+        let mut intensities = [0.0; NBR_OF_SAMPLES_MAX];
+        let pixel_sums = &sums[index * wavelengths.len()..(index + 1) * wavelengths.len()];
+        for (intensity, sum) in intensities.iter_mut().zip(pixel_sums) {
+            *intensity = sum / count as f32;
+        }
+        Some(Spectrum::new_from_list(&intensities, wavelengths[0], wavelengths[wavelengths.len() - 1], wavelengths.len()))
+    }
+
+    /// Returns the index of the pixel at x, y, None if it is outside the image. 
+    fn pixel_index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+
+    /// Returns the average intensity of every pixel at the sampled wavelength with the given 
+    /// index, row by row, as a float image with the same value in r, g and b. None if spectra are 
+    /// not recorded or the index is out of range. 
+    pub fn spectral_band_image(&self, index: usize) -> Option<DynamicImage> {
+        let (wavelengths, sums) = self.spectral_sums.as_ref()?;
+        if index >= wavelengths.len() {
+            return None;
+        }
+        let averages = sums.chunks_exact(wavelengths.len()).zip(&self.sample_counts).flat_map(|(pixel, &count)| {
+            let average = if count > 0 { pixel[index] / count as f32 } else { 0.0 };
+            [average; 3]
+        }).collect();
+        Some(Rgb32FImage::from_raw(self.width, self.height, averages).unwrap().into())
+    }
+
+    /// Writes one grayscale OpenEXR image per sampled wavelength into the given directory, see 
+    /// [Accumulation::spectral_band_image]. The files are named after the index and the 
+    /// wavelength of their band, e.g. band_007_550.0nm.exr. Does nothing if spectra are not 
+    /// recorded. 
+    pub fn export_spectral_bands(&self, directory: &Path) -> Result<(), AccumulationError> {
+        let Some((wavelengths, _)) = &self.spectral_sums else {
+            return Ok(());
+        };
+        for (index, wavelength) in wavelengths.iter().enumerate() {
+            let path = directory.join(format!("band_{index:03}_{wavelength:.1}nm.exr"));
+            if let Some(image) = self.spectral_band_image(index) {
+                image.save(&path)
+                    .map_err(|e| AccumulationError {error: format!("Could not write {}: {e}", path.display())})?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the average spectrum of every pixel as a hyperspectral cube in the ENVI format, 
+    /// which remote sensing and colorimetry tools read: the float32 intensities, little endian 
+    /// and band interleaved by pixel, go to the given path, usually ending in .dat, the header 
+    /// with the size and the wavelengths next to it with the extension .hdr. Does nothing if 
+    /// spectra are not recorded. 
+    pub fn export_envi(&self, path: &Path) -> Result<(), AccumulationError> {
+        let Some((wavelengths, sums)) = &self.spectral_sums else {
+            return Ok(());
+        };
+        let averages: Vec<f32> = sums.chunks_exact(wavelengths.len()).zip(&self.sample_counts)
+            .flat_map(|(pixel, &count)| {
+                let scale = if count > 0 { 1.0 / count as f32 } else { 0.0 };
+                pixel.iter().map(move |sum| sum * scale)
+            }).collect();
+        let header = format!("ENVI\n\
+            description = {{Spectral radiance in W/sr/m^2/nm rendered by eframe_raytracing}}\n\
+            samples = {}\n\
+            lines = {}\n\
+            bands = {}\n\
+            header offset = 0\n\
+            file type = ENVI Standard\n\
+            data type = 4\n\
+            interleave = bip\n\
+            byte order = 0\n\
+            wavelength units = Nanometers\n\
+            wavelength = {{{}}}\n",
+            self.width, self.height, wavelengths.len(),
+            wavelengths.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(", "));
+
+        let header_path = path.with_extension("hdr");
+        std::fs::write(path, to_le_bytes(&averages, |v| v.to_le_bytes()))
+            .map_err(|e| AccumulationError {error: format!("Could not write {}: {e}", path.display())})?;
+        std::fs::write(&header_path, header)
+            .map_err(|e| AccumulationError {error: format!("Could not write {}: {e}", header_path.display())})
+    }
+}
+
+fn to_le_bytes<T: Copy>(values: &[T], convert: fn(T) -> [u8; 4]) -> Vec<u8> {
+    values.iter().flat_map(|v| convert(*v)).collect()
+}
+
+/// Writes an array in the NumPy .npy format version 1.0. The header is padded with spaces so that
+/// the data starts at a multiple of 64 bytes, as the format demands.
+fn write_npy(path: &Path, descr: &str, shape: &[usize], data: &[u8]) -> Result<(), AccumulationError> {
+    let shape = match shape {
+        [length] => format!("({length},)"),
+        _ => format!("({})", shape.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+    };
+    let mut header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    //magic string, version and header length take 10 bytes, the header ends in a newline
+    let padding = (64 - (10 + header.len() + 1) % 64) % 64;
+    header += &" ".repeat(padding);
+    header.push('\n');
+
+    let mut content = Vec::with_capacity(10 + header.len() + data.len());
+    content.extend_from_slice(b"\x93NUMPY\x01\x00");
+    content.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    content.extend_from_slice(header.as_bytes());
+    content.extend_from_slice(data);
+
+    std::fs::write(path, content)
+        .map_err(|e| AccumulationError {error: format!("Could not write {}: {e}", path.display())})
+}
+
+/// An error type used when exporting an [Accumulation]. Specific details of the error are given
+/// in the error String.
+#[derive(Debug)]
+pub struct AccumulationError {
+    pub error: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Reads a .npy file written by [write_npy] and returns its header and its data, checking the 
+    /// parts of the format which do not depend on the array. 
+    fn read_npy(path: &Path) -> (String, Vec<u8>) {
+        let content = std::fs::read(path).unwrap();
+        assert_eq!(&content[..8], b"\x93NUMPY\x01\x00", "{} does not start with the magic string and version 1.0!", path.display());
+        let header_length = u16::from_le_bytes([content[8], content[9]]) as usize;
+        assert_eq!((10 + header_length) % 64, 0, "The data of {} is not aligned to 64 bytes!", path.display());
+        let header = String::from_utf8(content[10..10 + header_length].to_vec()).unwrap();
+        assert!(header.ends_with('\n'), "The header of {} does not end in a newline!", path.display());
+        (header.trim_end().to_string(), content[10 + header_length..].to_vec())
+    }
+
+    fn to_f32s(data: &[u8]) -> Vec<f32> {
+        data.chunks_exact(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect()
+    }
+
+    #[test]
+    fn test_npy_header() {
+        let directory = std::env::temp_dir().join(format!("test_npy_header_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        for shape in [vec![7], vec![2, 3], vec![480, 640, 3], vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]] {
+            let length: usize = shape.iter().product();
+            let path = directory.join("array.npy");
+            write_npy(&path, "<u4", &shape, &vec![1; length * 4]).unwrap();
+            let (header, data) = read_npy(&path);
+
+            let expected_shape = match shape.as_slice() {
+                [length] => format!("({length},)"),
+                _ => format!("({})", shape.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+            };
+            assert_eq!(header, format!("{{'descr': '<u4', 'fortran_order': False, 'shape': {expected_shape}, }}"));
+            assert_eq!(data.len(), length * 4);
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_export() {
+        let directory = std::env::temp_dir().join(format!("test_accumulation_export_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let spectrum = Spectrum::new_singular_reflectance_factor(400.0, 700.0, 4, 0.5);
+        let mut accumulation = Accumulation::new(3, 2, Some(&spectrum));
+        //two samples in the right column, one in the pixel in the lower left
+        accumulation.add_samples(2, 0, 1, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], Some(&[spectrum, spectrum]));
+        accumulation.add_samples(2, 0, 1, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], Some(&[spectrum, spectrum]));
+        accumulation.add_samples(0, 1, 1, &[0.5, 0.25, 0.125], Some(&[spectrum]));
+        accumulation.export(&directory).unwrap();
+
+        let (header, data) = read_npy(&directory.join(RGB_SUM_FILE_NAME));
+        assert_eq!(header, "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3, 3), }");
+        assert_eq!(to_f32s(&data), [0.0, 0.0, 0.0,    0.0, 0.0, 0.0,   2.0, 4.0, 6.0,
+                                    0.5, 0.25, 0.125, 0.0, 0.0, 0.0,   8.0, 10.0, 12.0]);
+
+        let (header, data) = read_npy(&directory.join(SAMPLE_COUNT_FILE_NAME));
+        assert_eq!(header, "{'descr': '<u4', 'fortran_order': False, 'shape': (2, 3), }");
+        let counts: Vec<u32> = data.chunks_exact(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())).collect();
+        assert_eq!(counts, [0, 0, 2, 1, 0, 2]);
+
+        let (header, data) = read_npy(&directory.join(SPECTRAL_SUM_FILE_NAME));
+        assert_eq!(header, "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3, 4), }");
+        let sums = to_f32s(&data);
+        assert_eq!(sums[2 * 4..3 * 4], [1.0; 4]);
+        assert_eq!(sums[3 * 4..4 * 4], [0.5; 4]);
+
+        let (header, data) = read_npy(&directory.join(WAVELENGTHS_FILE_NAME));
+        assert_eq!(header, "{'descr': '<f4', 'fortran_order': False, 'shape': (4,), }");
+        assert_eq!(to_f32s(&data), spectrum.get_wavelengths());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
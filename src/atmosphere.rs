@@ -0,0 +1,215 @@
+use std::f64::consts::PI;
+use nalgebra::Vector3;
+use crate::radiometry::{SpectralIrradiance, SpectralRadiance, SpectralReflectance};
+use crate::spectrum::Spectrum;
+
+/// The radius of the planet and of the top of its atmosphere in meters, those of the earth.
+const PLANET_RADIUS: f64 = 6_360_000.0;
+const ATMOSPHERE_RADIUS: f64 = 6_420_000.0;
+/// The heights over which the density of air molecules and of aerosols falls to 1/e.
+const RAYLEIGH_SCALE_HEIGHT: f64 = 8_000.0;
+const MIE_SCALE_HEIGHT: f64 = 1_200.0;
+/// The Rayleigh scattering coefficient of air at sea level at 550nm in 1/m. It grows with the 
+/// inverse fourth power of the wavelength, which makes the sky blue and sunsets red.
+const RAYLEIGH_COEFFICIENT_550NM: f64 = 13.3e-6;
+/// The Mie scattering coefficient of the aerosols of a clear day at sea level in 1/m. Aerosols 
+/// are large compared to the wavelength, their scattering hardly depends on it. 
+const MIE_SCATTERING_COEFFICIENT: f64 = 21e-6;
+/// Aerosols absorb a tenth of the light they scatter on top.
+const MIE_EXTINCTION_FACTOR: f64 = 1.1;
+/// The asymmetry of Mie scattering, the strong forward scattering causes the glow around the sun.
+const MIE_ASYMMETRY: f64 = 0.76;
+/// The angular radius of the sun disc as seen from the earth in radians.
+const SUN_ANGULAR_RADIUS: f64 = 0.00465;
+/// The number of points along a view ray at which the scattered light is gathered and the number 
+/// of points along the way of the sunlight to each of them.
+const VIEW_SAMPLES: usize = 16;
+const SUN_SAMPLES: usize = 8;
+
+/// A planetary atmosphere lit by the sun. The sky seen by rays leaving the scene is the sunlight 
+/// scattered once by air molecules (Rayleigh scattering) and aerosols (Mie scattering), integrated 
+/// separately for every wavelength. The scene itself is a small spot on the ground of the planet, 
+/// its extent is neglected. The sun also lights the scene directly, dimmed by the air in its way.
+#[derive(Clone, Debug)]
+pub struct Atmosphere {
+    /// The direction towards the sun, normalized.
+    sun_direction: Vector3<f32>,
+    /// The irradiance of the sun at the top of the atmosphere, perpendicular to its light.
+    sun_irradiance: SpectralIrradiance,
+    /// The irradiance of the sun arriving at the scene, perpendicular to its light.
+    ground_sun_irradiance: SpectralIrradiance,
+    /// The Rayleigh scattering coefficient of each wavelength at sea level in 1/m.
+    rayleigh_coefficients: Spectrum,
+    /// The factor of the aerosol density of a clear day.
+    turbidity: f64,
+    /// The height of the scene above sea level in meters.
+    altitude: f64,
+}
+
+impl Atmosphere {
+    pub fn get_sun_direction(&self) -> &Vector3<f32> {
+        &self.sun_direction
+    }
+
+    /// Multiplies the irradiance of the sun with the given factor.
+    pub fn scale_emission(&mut self, factor: f32) {
+        self.sun_irradiance *= factor;
+        self.ground_sun_irradiance *= factor;
+    }
+
+    /// The irradiance of the sun arriving at the scene, perpendicular to its light. Zero once the
+    /// sun has set.
+    pub fn get_ground_sun_irradiance(&self) -> &SpectralIrradiance {
+        &self.ground_sun_irradiance
+    }
+
+    /// Returns the radiance of the sky arriving at the scene from the given direction: the 
+    /// sunlight scattered towards the scene along the view ray, plus the sun disc itself. Below 
+    /// the horizon, only the light scattered by the air between the scene and the ground is seen.
+    pub fn sky_radiance(&self, direction: &Vector3<f32>) -> SpectralRadiance {
+        let direction: Vector3<f64> = direction.normalize().cast();
+        let sun_direction: Vector3<f64> = self.sun_direction.cast();
+        let origin = self.origin();
+        
+        let ground_distance = sphere_entry_distance(&origin, &direction, PLANET_RADIUS);
+        let view_distance = ground_distance
+            .unwrap_or_else(|| sphere_exit_distance(&origin, &direction, ATMOSPHERE_RADIUS));
+        let segment = view_distance / VIEW_SAMPLES as f64;
+        
+        //the optical depths are measured in meters of air at sea level density
+        let mut rayleigh_depth = 0.0;
+        let mut mie_depth = 0.0;
+        let mut rayleigh_sum = Spectrum::new_equal_size_empty_spectrum(&self.rayleigh_coefficients);
+        let mut mie_sum = Spectrum::new_equal_size_empty_spectrum(&self.rayleigh_coefficients);
+        for i in 0..VIEW_SAMPLES {
+            let point = origin + direction * ((i as f64 + 0.5) * segment);
+            let height = point.magnitude() - PLANET_RADIUS;
+            let rayleigh_density = (-height / RAYLEIGH_SCALE_HEIGHT).exp() * segment;
+            let mie_density = (-height / MIE_SCALE_HEIGHT).exp() * segment * self.turbidity;
+            rayleigh_depth += rayleigh_density;
+            mie_depth += mie_density;
+            
+            //points in the shadow of the planet receive no sunlight
+            if sphere_entry_distance(&point, &sun_direction, PLANET_RADIUS).is_some() {
+                continue;
+            }
+            let (sun_rayleigh_depth, sun_mie_depth) = self.optical_depth_to_space(&point, &sun_direction, SUN_SAMPLES);
+            let attenuation = self.attenuation(rayleigh_depth + sun_rayleigh_depth, mie_depth + sun_mie_depth);
+            for (index, (_, attenuation)) in attenuation.iter().enumerate() {
+                rayleigh_sum[index] += attenuation * rayleigh_density as f32;
+                mie_sum[index] += attenuation * mie_density as f32;
+            }
+        }
+        
+        let cos_theta = direction.dot(&sun_direction);
+        let rayleigh_phase = 3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta);
+        let g = MIE_ASYMMETRY;
+        let mie_phase = 3.0 / (8.0 * PI) * (1.0 - g * g) * (1.0 + cos_theta * cos_theta)
+            / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5));
+        
+        let mut radiance = &rayleigh_sum * &self.rayleigh_coefficients;
+        radiance *= rayleigh_phase as f32;
+        mie_sum *= (MIE_SCATTERING_COEFFICIENT * mie_phase) as f32;
+        radiance += &mie_sum;
+        
+        //the sun disc spreads the irradiance of the sun over its solid angle
+        if ground_distance.is_none() && cos_theta > SUN_ANGULAR_RADIUS.cos() {
+            let (rayleigh_depth, mie_depth) = self.optical_depth_to_space(&origin, &direction, VIEW_SAMPLES);
+            let mut sun = self.attenuation(rayleigh_depth, mie_depth);
+            sun *= (1.0 / (PI * SUN_ANGULAR_RADIUS * SUN_ANGULAR_RADIUS)) as f32;
+            radiance += &sun;
+        }
+        
+        radiance *= &*self.sun_irradiance;
+        SpectralRadiance::new(radiance)
+    }
+
+    /// The share of each wavelength passing the atmosphere from the scene into the given 
+    /// direction, zero if the ground is in the way. 
+    fn transmittance_to_space(&self, direction: &Vector3<f64>) -> SpectralReflectance {
+        let origin = self.origin();
+        if sphere_entry_distance(&origin, direction, PLANET_RADIUS).is_some() {
+            return SpectralReflectance::zero(&self.rayleigh_coefficients);
+        }
+        let (rayleigh_depth, mie_depth) = self.optical_depth_to_space(&origin, direction, VIEW_SAMPLES);
+        SpectralReflectance::new(self.attenuation(rayleigh_depth, mie_depth))
+    }
+
+    /// The optical depths of air molecules and aerosols from the given point to the top of the 
+    /// atmosphere into the given direction, integrated with the given number of samples.
+    fn optical_depth_to_space(&self, point: &Vector3<f64>, direction: &Vector3<f64>, samples: usize) -> (f64, f64) {
+        let segment = sphere_exit_distance(point, direction, ATMOSPHERE_RADIUS) / samples as f64;
+        let mut rayleigh_depth = 0.0;
+        let mut mie_depth = 0.0;
+        for i in 0..samples {
+            let height = (point + direction * ((i as f64 + 0.5) * segment)).magnitude() - PLANET_RADIUS;
+            rayleigh_depth += (-height / RAYLEIGH_SCALE_HEIGHT).exp() * segment;
+            mie_depth += (-height / MIE_SCALE_HEIGHT).exp() * segment * self.turbidity;
+        }
+        (rayleigh_depth, mie_depth)
+    }
+
+    /// The share of each wavelength passing the given optical depths of air molecules and aerosols.
+    fn attenuation(&self, rayleigh_depth: f64, mie_depth: f64) -> Spectrum {
+        let mut attenuation = self.rayleigh_coefficients;
+        let mie_optical_depth = MIE_SCATTERING_COEFFICIENT * MIE_EXTINCTION_FACTOR * mie_depth;
+        for value in attenuation.get_intensities_slice() {
+            *value = (-(*value as f64 * rayleigh_depth + mie_optical_depth)).exp() as f32;
+        }
+        attenuation
+    }
+
+    /// The position of the scene relative to the center of the planet.
+    fn origin(&self) -> Vector3<f64> {
+        Vector3::new(0.0, PLANET_RADIUS + self.altitude, 0.0)
+    }
+}
+
+impl Atmosphere {
+    /// Creates the atmosphere of the earth lit by a sun at the given elevation above the horizon 
+    /// and azimuth, counted from the z axis towards the x axis. The sun spectrum is its irradiance 
+    /// at the top of the atmosphere. The turbidity scales the aerosols of a clear day, the altitude 
+    /// of the scene above sea level is given in meters. 
+    pub fn new(sun_elevation_deg: f32, sun_azimuth_deg: f32, sun_spectrum: &Spectrum, turbidity: f32, altitude: f32) -> Self {
+        let (elevation, azimuth) = (sun_elevation_deg.to_radians(), sun_azimuth_deg.to_radians());
+        let sun_direction = Vector3::new(elevation.cos() * azimuth.sin(), elevation.sin(), elevation.cos() * azimuth.cos());
+        
+        let sun_spectrum = *sun_spectrum;
+        let mut rayleigh_coefficients = sun_spectrum;
+        for (value, wavelength) in rayleigh_coefficients.get_intensities_slice().iter_mut().zip(sun_spectrum.get_wavelengths()) {
+            *value = (RAYLEIGH_COEFFICIENT_550NM * (550.0 / wavelength as f64).powi(4)) as f32;
+        }
+        
+        let mut atmosphere = Atmosphere {
+            sun_direction,
+            sun_irradiance: SpectralIrradiance::new(sun_spectrum),
+            ground_sun_irradiance: SpectralIrradiance::new(sun_spectrum),
+            rayleigh_coefficients,
+            turbidity: turbidity as f64,
+            altitude: altitude as f64,
+        };
+        atmosphere.ground_sun_irradiance *= &atmosphere.transmittance_to_space(&sun_direction.cast());
+        atmosphere
+    }
+}
+
+/// Returns the distance along the ray from a point outside of the sphere around the center of 
+/// the planet to where it enters the sphere, None if it misses the sphere.
+fn sphere_entry_distance(origin: &Vector3<f64>, direction: &Vector3<f64>, radius: f64) -> Option<f64> {
+    let b = origin.dot(direction);
+    let c = origin.magnitude_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    (distance > 0.0).then_some(distance)
+}
+
+/// Returns the distance along the ray from a point inside of the sphere around the center of the 
+/// planet to where it leaves the sphere.
+fn sphere_exit_distance(origin: &Vector3<f64>, direction: &Vector3<f64>, radius: f64) -> f64 {
+    let b = origin.dot(direction);
+    let c = origin.magnitude_squared() - radius * radius;
+    -b + (b * b - c).max(0.0).sqrt()
+}
@@ -0,0 +1,116 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::gamut;
+
+/// The default bloom lets only light brighter than white glow, faintly and close to its source.
+pub const BLOOM_THRESHOLD_DEFAULT: f32 = 1.0;
+pub const BLOOM_INTENSITY_DEFAULT: f32 = 0.1;
+pub const BLOOM_RADIUS_DEFAULT: f32 = 0.01;
+
+/// The number of box blurs the glow is blurred with, three already come close to a gaussian.
+const BOX_BLUR_PASSES: usize = 3;
+/// The widths of the layers of the glow relative to the radius and their weights. The light
+/// scattered in lenses and eyes falls off slowly, so a tight glow is combined with a faint wide
+/// haze.
+const GLOW_LAYERS: [(f32, f32); 3] = [(1.0, 0.6), (4.0, 0.3), (16.0, 0.1)];
+
+/// A post effect spreading the light of very bright pixels over their surroundings, like the
+/// glare of a lens or an eye. Without it, the brightest emitters are flat discs clipped to white,
+/// with it they glow. The glow is taken from the linear colors before they are exposed and tone
+/// mapped.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bloom {
+    /// The luminance above which pixels glow, only the light above it is spread.
+    pub threshold: f32,
+    /// The share of the light above the threshold which is added around the pixel as glow.
+    pub intensity: f32,
+    /// The width of the tight glow as a fraction of the larger side of the image, the wide haze
+    /// reaches further.
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: BLOOM_THRESHOLD_DEFAULT,
+            intensity: BLOOM_INTENSITY_DEFAULT,
+            radius: BLOOM_RADIUS_DEFAULT,
+        }
+    }
+}
+
+impl Bloom {
+    /// Returns the colors of an image with the glow of its bright pixels added. The data holds the
+    /// r, g, b, a values of every pixel, the alpha is kept, so the glow does not reach into
+    /// transparent pixels.
+    pub fn apply(&self, width: u32, height: u32, data: &[f32]) -> Vec<f32> {
+        let (width, height) = (width as usize, height as usize);
+        //only the light above the threshold glows, keeping its hue
+        let bright = data.chunks_exact(4).map(|pixel| {
+            let luminance = gamut::luminance((pixel[0], pixel[1], pixel[2]));
+            if luminance <= self.threshold {
+                return [0.0; 3];
+            }
+            let scale = (luminance - self.threshold) / luminance * self.intensity;
+            [pixel[0] * scale, pixel[1] * scale, pixel[2] * scale]
+        }).collect::<Vec<[f32; 3]>>();
+
+        let mut result = data.to_vec();
+        let radius = self.radius * width.max(height) as f32;
+        for (scale, weight) in GLOW_LAYERS {
+            let glow = gaussian_blur(&bright, width, height, radius * scale);
+            for (pixel, glow) in result.chunks_exact_mut(4).zip(glow) {
+                for channel in 0..3 {
+                    pixel[channel] += glow[channel] * weight;
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Approximates a gaussian blur with the given standard deviation in pixels by repeated box
+/// blurs, first along the rows, then along the columns. Light blurred past the border is lost.
+fn gaussian_blur(data: &[[f32; 3]], width: usize, height: usize, sigma: f32) -> Vec<[f32; 3]> {
+    //n box blurs of radius r have a variance of about n * r² / 3
+    let radius = (sigma * (3.0 / BOX_BLUR_PASSES as f32).sqrt()).round() as usize;
+    if radius == 0 || data.is_empty() {
+        return data.to_vec();
+    }
+    let mut blurred = data.to_vec();
+    for _ in 0..BOX_BLUR_PASSES {
+        blurred = box_blur_rows(&blurred, width, radius);
+    }
+    blurred = transpose(&blurred, width, height);
+    for _ in 0..BOX_BLUR_PASSES {
+        blurred = box_blur_rows(&blurred, height, radius);
+    }
+    transpose(&blurred, height, width)
+}
+
+/// Averages every pixel with the pixels up to the radius to its left and right via running sums.
+fn box_blur_rows(data: &[[f32; 3]], width: usize, radius: usize) -> Vec<[f32; 3]> {
+    let mut blurred = vec![[0.0; 3]; data.len()];
+    let normalization = 1.0 / (2 * radius + 1) as f32;
+    blurred.par_chunks_mut(width).zip(data.par_chunks(width)).for_each(|(blurred_row, row)| {
+        let mut sum = [0.0; 3];
+        for pixel in &row[..radius.min(width)] {
+            (0..3).for_each(|c| sum[c] += pixel[c]);
+        }
+        for (x, blurred_pixel) in blurred_row.iter_mut().enumerate() {
+            if let Some(entering) = row.get(x + radius) {
+                (0..3).for_each(|c| sum[c] += entering[c]);
+            }
+            *blurred_pixel = sum.map(|s| s * normalization);
+            if let Some(leaving) = x.checked_sub(radius).map(|i| row[i]) {
+                (0..3).for_each(|c| sum[c] -= leaving[c]);
+            }
+        }
+    });
+    blurred
+}
+
+/// Swaps the rows and columns of an image of the given width and height.
+fn transpose(data: &[[f32; 3]], width: usize, height: usize) -> Vec<[f32; 3]> {
+    (0..width).flat_map(|x| (0..height).map(move |y| data[y * width + x])).collect()
+}
@@ -0,0 +1,45 @@
+//! Shading models which are not built into the renderer. A material with a [Bsdf] is shaded by it
+//! instead of by its reflectance, metallicness and roughness: the light of the light sources and
+//! the sun is weighted by [Bsdf::evaluate] and paths are continued into the directions drawn by
+//! [Bsdf::sample]. This allows crates depending on the renderer to add their own spectral shading
+//! models, e.g. thin film interference or measured materials, without touching the shaders.
+
+use std::fmt::Debug;
+use nalgebra::Vector3;
+use crate::spectrum::Spectrum;
+
+/// A direction drawn by [Bsdf::sample].
+#[derive(Clone, Debug)]
+pub struct BsdfSample {
+    /// The direction the path continues in, pointing away from the surface and of unit length.
+    pub direction: Vector3<f32>,
+    /// The value of the BSDF for the drawn direction in 1/sr, see [Bsdf::evaluate]. For specular
+    /// samples, the share of the light reflected into the direction instead.
+    pub value: Spectrum,
+    /// The probability density of drawing the direction in 1/sr, unused for specular samples.
+    pub pdf: f32,
+    /// Whether the direction is the only one light arrives from, like the reflection of a mirror.
+    /// Such a direction is never found by [Bsdf::evaluate] and counts as a specular bounce.
+    pub specular: bool,
+}
+
+/// The scattering of light at a surface. All directions are given in world space, point away from
+/// the surface and are of unit length: the outgoing direction towards the origin of the ray
+/// hitting the surface and the incoming direction towards where the light comes from. The normal
+/// faces the side of the surface which was hit. Spectra have as many samples and the same range
+/// as the given example spectrum, which is the spectrum the ray carries.
+pub trait Bsdf: Send + Sync + Debug {
+    /// Draws an incoming direction for the given outgoing direction using two random values in
+    /// range \[0; 1). Returns None if the path ends here, i.e. the light is absorbed.
+    fn sample(&self, outgoing: &Vector3<f32>, normal: &Vector3<f32>, random_x: f32, random_y: f32,
+              example_spectrum: &Spectrum) -> Option<BsdfSample>;
+
+    /// Returns the share of the irradiance arriving from the incoming direction which leaves the
+    /// surface as radiance into the outgoing direction, in 1/sr.
+    fn evaluate(&self, outgoing: &Vector3<f32>, incoming: &Vector3<f32>, normal: &Vector3<f32>,
+                example_spectrum: &Spectrum) -> Spectrum;
+
+    /// Returns the probability density of [Bsdf::sample] drawing the incoming direction for the
+    /// outgoing direction, in 1/sr.
+    fn pdf(&self, outgoing: &Vector3<f32>, incoming: &Vector3<f32>, normal: &Vector3<f32>) -> f32;
+}
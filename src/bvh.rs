@@ -0,0 +1,338 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use nalgebra::{Point3, Vector3};
+
+/// The maximum number of objects in a leaf of the hierarchy.
+const MAX_LEAF_SIZE: usize = 2;
+/// The maximum depth of the hierarchy supported by the traversal. Median splits halve the objects
+/// with every level, so this is never reached.
+const MAX_DEPTH: usize = 64;
+/// How much worse the hierarchy may become through refitting, measured by the summed surface
+/// area of its nodes relative to a fresh build, before it is rebuilt instead.
+const REBUILD_COST_FACTOR: f32 = 1.5;
+
+#[derive(Clone)]
+struct BvhNode {
+    min: Point3<f32>,
+    max: Point3<f32>,
+    /// For inner nodes the index of the left child, the right child follows it directly. For
+    /// leaves the index of the first object in [Bvh::indices].
+    first: u32,
+    /// The number of objects in a leaf, 0 for inner nodes.
+    count: u32,
+}
+
+/// A bounding volume hierarchy over the objects of a scene. Rays only test the objects whose
+/// bounding boxes they hit, which turns the linear search over all objects into a logarithmic
+/// one. <br/>
+/// If objects only move, the hierarchy does not have to be rebuilt: [Bvh::refit] recalculates the
+/// bounds of the nodes in place, keeping the structure. See [Bvh::update].
+#[derive(Clone)]
+pub struct Bvh {
+    /// The nodes, every child has a larger index than its parent. The root is the first node.
+    nodes: Vec<BvhNode>,
+    /// The indices of the objects in the order the leaves reference them.
+    indices: Vec<u32>,
+    /// The cost of the hierarchy right after it was built, see [Bvh::cost].
+    built_cost: f32,
+}
+
+impl Bvh {
+    /// Builds the hierarchy over the objects with the given bounding boxes. The objects are split
+    /// at the median of their centers along the longest axis until they fit into a leaf.
+    pub fn build(bounds: &[(Point3<f32>, Point3<f32>)]) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * bounds.len()),
+            indices: (0..bounds.len() as u32).collect(),
+            built_cost: 0.0,
+        };
+        if !bounds.is_empty() {
+            bvh.nodes.push(BvhNode {min: Point3::origin(), max: Point3::origin(), first: 0, count: bounds.len() as u32});
+            bvh.subdivide(0, bounds);
+            bvh.refit(bounds);
+            bvh.built_cost = bvh.cost();
+        }
+        bvh
+    }
+
+    /// Returns a hierarchy over the objects with the given bounding boxes. If the previous
+    /// hierarchy was built for the same number of objects, it is refit to their new bounds,
+    /// otherwise or if refitting degraded it too much a new one is built. The previous hierarchy is
+    /// copied if it is still in use elsewhere, e.g. by a running render.
+    pub fn update(previous: Option<Arc<Bvh>>, bounds: &[(Point3<f32>, Point3<f32>)]) -> Arc<Bvh> {
+        if let Some(mut bvh) = previous.filter(|bvh| bvh.indices.len() == bounds.len()) {
+            let refit = Arc::make_mut(&mut bvh);
+            refit.refit(bounds);
+            if refit.cost() <= refit.built_cost * REBUILD_COST_FACTOR {
+                log::debug!("Refit the BVH over {} objects", bounds.len());
+                return bvh;
+            }
+        }
+        log::debug!("Built the BVH over {} objects", bounds.len());
+        Arc::new(Bvh::build(bounds))
+    }
+
+    /// Recalculates the bounds of all nodes from the given bounding boxes of the objects, which
+    /// have to be in the same order as when the hierarchy was built.
+    pub fn refit(&mut self, bounds: &[(Point3<f32>, Point3<f32>)]) {
+        //children come after their parents, so they are always refit first
+        for i in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[i];
+            let (min, max) = if node.count > 0 {
+                let objects = &self.indices[node.first as usize..(node.first + node.count) as usize];
+                objects.iter().map(|o| bounds[*o as usize]).reduce(union).unwrap()
+            } else {
+                let (left, right) = (&self.nodes[node.first as usize], &self.nodes[node.first as usize + 1]);
+                union((left.min, left.max), (right.min, right.max))
+            };
+            self.nodes[i].min = min;
+            self.nodes[i].max = max;
+        }
+    }
+
+    /// Visits the objects whose bounding boxes the ray hits closer than the maximum distance,
+    /// nearer nodes first. The visitor can lower the maximum distance, e.g. to the closest hit
+    /// found so far, which skips all nodes beyond it, or break to stop the traversal.
+    pub fn traverse(&self, origin: &Point3<f32>, direction: &Vector3<f32>, mut max_distance: f32,
+                    mut visit: impl FnMut(usize) -> ControlFlow<(), f32>) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let inverse_direction = direction.map(|d| 1.0 / d);
+        let mut stack = [0u32; MAX_DEPTH];
+        let mut stack_size = 1;
+
+        while stack_size > 0 {
+            stack_size -= 1;
+            let node = &self.nodes[stack[stack_size] as usize];
+            if entry_distance(node, origin, &inverse_direction, max_distance).is_none() {
+                continue;
+            }
+
+            if node.count > 0 {
+                for object in &self.indices[node.first as usize..(node.first + node.count) as usize] {
+                    match visit(*object as usize) {
+                        ControlFlow::Continue(distance) => max_distance = max_distance.min(distance),
+                        ControlFlow::Break(()) => return,
+                    }
+                }
+                continue;
+            }
+
+            //the nearer child is pushed last, so that it is visited first
+            let left = node.first;
+            let right = node.first + 1;
+            let left_distance = entry_distance(&self.nodes[left as usize], origin, &inverse_direction, max_distance);
+            let right_distance = entry_distance(&self.nodes[right as usize], origin, &inverse_direction, max_distance);
+            let mut children = [(left, left_distance), (right, right_distance)];
+            if left_distance.unwrap_or(f32::INFINITY) < right_distance.unwrap_or(f32::INFINITY) {
+                children.swap(0, 1);
+            }
+            for (child, distance) in children {
+                if distance.is_some() {
+                    stack[stack_size] = child;
+                    stack_size += 1;
+                }
+            }
+        }
+    }
+
+    /// Splits the objects of the given leaf among two new children, recursively.
+    fn subdivide(&mut self, node_index: usize, bounds: &[(Point3<f32>, Point3<f32>)]) {
+        let (first, count) = (self.nodes[node_index].first as usize, self.nodes[node_index].count as usize);
+        if count <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        let objects = &mut self.indices[first..first + count];
+        let center = |object: &u32| {
+            let (min, max) = bounds[*object as usize];
+            nalgebra::center(&min, &max)
+        };
+        let (centers_min, centers_max) = objects.iter()
+            .map(|o| (center(o), center(o)))
+            .reduce(union)
+            .unwrap();
+        let extent = centers_max - centers_min;
+        let axis = extent.imax();
+        if extent[axis].is_nan() || extent[axis] <= 0.0 {
+            return;     //all centers coincide, splitting does not separate anything
+        }
+        objects.sort_unstable_by(|a, b| center(a)[axis].total_cmp(&center(b)[axis]));
+
+        let left_count = count / 2;
+        let left = self.nodes.len();
+        for (child_first, child_count) in [(first, left_count), (first + left_count, count - left_count)] {
+            self.nodes.push(BvhNode {
+                min: Point3::origin(),
+                max: Point3::origin(),
+                first: child_first as u32,
+                count: child_count as u32,
+            });
+        }
+        self.nodes[node_index].first = left as u32;
+        self.nodes[node_index].count = 0;
+
+        self.subdivide(left, bounds);
+        self.subdivide(left + 1, bounds);
+    }
+
+    /// The summed surface area of all nodes relative to the surface area of the root, which is
+    /// proportional to the expected number of nodes a random ray hitting the scene has to test.
+    fn cost(&self) -> f32 {
+        let root_area = surface_area(&self.nodes[0]);
+        if root_area.is_nan() || root_area <= 0.0 {
+            return 0.0;
+        }
+        self.nodes.iter().map(surface_area).sum::<f32>() / root_area
+    }
+}
+
+/// Returns the distance at which the ray enters the bounds of the node, None if it misses them
+/// or enters them beyond the maximum distance.
+fn entry_distance(node: &BvhNode, origin: &Point3<f32>, inverse_direction: &Vector3<f32>, max_distance: f32) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = max_distance;
+    for i in 0..3 {
+        let t1 = (node.min[i] - origin[i]) * inverse_direction[i];
+        let t2 = (node.max[i] - origin[i]) * inverse_direction[i];
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    }
+    (t_min <= t_max).then_some(t_min)
+}
+
+fn union(a: (Point3<f32>, Point3<f32>), b: (Point3<f32>, Point3<f32>)) -> (Point3<f32>, Point3<f32>) {
+    (a.0.inf(&b.0), a.1.sup(&b.1))
+}
+
+fn surface_area(node: &BvhNode) -> f32 {
+    let extent = node.max - node.min;
+    2.0 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A small linear congruential generator, so that the scenes are the same on every run.
+    struct Random(u64);
+
+    impl Random {
+        /// Returns the next number in range \[low; high).
+        fn next(&mut self, low: f32, high: f32) -> f32 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            low + (self.0 >> 40) as f32 / (1u64 << 24) as f32 * (high - low)
+        }
+
+        fn point(&mut self, low: f32, high: f32) -> Point3<f32> {
+            Point3::new(self.next(low, high), self.next(low, high), self.next(low, high))
+        }
+    }
+
+    /// Boxes of different sizes scattered over a cube of 20 units.
+    fn random_bounds(random: &mut Random, count: usize) -> Vec<(Point3<f32>, Point3<f32>)> {
+        (0..count).map(|_| {
+            let min = random.point(-10.0, 10.0);
+            (min, min + random.point(0.1, 3.0).coords)
+        }).collect()
+    }
+
+    /// The distance at which the ray enters the box, tested against every box one by one.
+    fn box_entry(bounds: (Point3<f32>, Point3<f32>), origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        let node = BvhNode {min: bounds.0, max: bounds.1, first: 0, count: 1};
+        entry_distance(&node, origin, &direction.map(|d| 1.0 / d), f32::INFINITY)
+    }
+
+    /// Checks that the hierarchy visits every box the rays hit and finds the same closest one as 
+    /// a brute force search.
+    fn assert_matches_brute_force(bvh: &Bvh, bounds: &[(Point3<f32>, Point3<f32>)], random: &mut Random) {
+        for _ in 0..200 {
+            let origin = random.point(-15.0, 15.0);
+            let direction = random.point(-1.0, 1.0).coords.normalize();
+            let hit: Vec<usize> = (0..bounds.len())
+                .filter(|i| box_entry(bounds[*i], &origin, &direction).is_some())
+                .collect();
+            let closest = hit.iter().map(|i| box_entry(bounds[*i], &origin, &direction).unwrap())
+                .fold(f32::INFINITY, f32::min);
+
+            let mut visited = vec![false; bounds.len()];
+            bvh.traverse(&origin, &direction, f32::INFINITY, |i| {
+                visited[i] = true;
+                ControlFlow::Continue(f32::INFINITY)
+            });
+            assert!(hit.iter().all(|i| visited[*i]), "A box hit by the ray was not visited!");
+
+            let mut closest_found = f32::INFINITY;
+            bvh.traverse(&origin, &direction, f32::INFINITY, |i| {
+                if let Some(t) = box_entry(bounds[i], &origin, &direction) {
+                    closest_found = closest_found.min(t);
+                }
+                ControlFlow::Continue(closest_found)
+            });
+            assert_eq!(closest_found, closest, "The closest box differs from the brute force search!");
+        }
+    }
+
+    #[test]
+    fn test_traverse_matches_brute_force() {
+        let mut random = Random(1);
+        for count in [0, 1, 2, 3, 17, 200] {
+            let bounds = random_bounds(&mut random, count);
+            assert_matches_brute_force(&Bvh::build(&bounds), &bounds, &mut random);
+        }
+    }
+
+    #[test]
+    fn test_traverse_stops_on_break() {
+        let mut random = Random(2);
+        let bounds = random_bounds(&mut random, 50);
+        let bvh = Bvh::build(&bounds);
+        let mut visits = 0;
+        bvh.traverse(&Point3::new(-20.0, 0.0, 0.0), &Vector3::x(), f32::INFINITY, |_| {
+            visits += 1;
+            ControlFlow::Break(())
+        });
+        assert!(visits <= 1, "The traversal went on after the visitor broke it!");
+    }
+
+    #[test]
+    fn test_refit_matches_brute_force() {
+        let mut random = Random(3);
+        let bounds = random_bounds(&mut random, 100);
+        let mut bvh = Bvh::build(&bounds);
+        //every box moves a little, as when an object is dragged
+        let moved: Vec<_> = bounds.iter().map(|(min, max)| {
+            let offset = random.point(-2.0, 2.0).coords;
+            (min + offset, max + offset)
+        }).collect();
+        bvh.refit(&moved);
+        assert_matches_brute_force(&bvh, &moved, &mut random);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut random = Random(4);
+        let bounds = random_bounds(&mut random, 100);
+        let previous = Arc::new(Bvh::build(&bounds));
+
+        //a hierarchy still used elsewhere is copied, not changed
+        let moved: Vec<_> = bounds.iter().map(|(min, max)| (min + Vector3::x(), max + Vector3::x())).collect();
+        let refit = Bvh::update(Some(previous.clone()), &moved);
+        assert!(!Arc::ptr_eq(&previous, &refit));
+        assert_matches_brute_force(&previous, &bounds, &mut random);
+        assert_matches_brute_force(&refit, &moved, &mut random);
+
+        //scrambling the boxes degrades a refit hierarchy so much that it is built again
+        let scrambled = random_bounds(&mut random, 100);
+        let rebuilt = Bvh::update(Some(previous.clone()), &scrambled);
+        assert_eq!(rebuilt.cost(), rebuilt.built_cost, "The degraded hierarchy was not built again!");
+        assert_matches_brute_force(&rebuilt, &scrambled, &mut random);
+
+        //a different number of objects always needs a new hierarchy
+        let fewer = random_bounds(&mut random, 10);
+        let rebuilt = Bvh::update(Some(previous), &fewer);
+        assert_eq!(rebuilt.indices.len(), 10);
+        assert_matches_brute_force(&rebuilt, &fewer, &mut random);
+    }
+}
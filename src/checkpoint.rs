@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::{info, warn};
+use crate::custom_image::{AovBuffers, CustomImage};
+use crate::platform::Instant;
+
+/// The checkpoint file of the renders of the app, in the working directory.
+pub const CHECKPOINT_FILE_NAME: &str = "render.checkpoint";
+/// How often a checkpoint is written while rendering, it is always written after the frame which
+/// exceeds the interval.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+/// Identifies checkpoint files and the version of their layout.
+const CHECKPOINT_MAGIC: &[u8; 8] = b"SRTCKPT2";
+
+/// The state of an unfinished render from which it can be continued: the scene file it renders,
+/// the number of frames already averaged into the image and the image itself with the number of
+/// samples of every pixel and, if recorded, its arbitrary output variables. These are saved as
+/// well, so that the normals, depths and albedos the denoiser is guided by keep matching the
+/// colors after resuming. The random values of a frame only depend on the seed of the scene and
+/// the frame number, so the resumed render continues exactly as if it had not been interrupted.
+/// <br>
+/// The file starts with [CHECKPOINT_MAGIC], followed by little endian values: the length of the
+/// scene in bytes as u32, the scene as UTF-8, the frames done, the width and the height as u32,
+/// the r, g, b, a values of the image as f32 and the sample counts as u32, both row by row. Then
+/// a u32 which is 1 if the arbitrary output variables follow and 0 if not, followed by the sums of
+/// [AovBuffers] in the order of its fields, the number of light passes and the length of the light
+/// colors as u32 before the light colors.
+pub struct Checkpoint {
+    pub scene: String,
+    pub frames_done: u32,
+    pub image: CustomImage,
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint of the given scene, frames and image into a temporary file first,
+    /// which then replaces the file at the given path, so that a crash while writing leaves the
+    /// previous checkpoint intact.
+    pub fn write(path: &Path, scene: &str, frames_done: u32, image: &CustomImage) -> Result<(), CheckpointError> {
+        let (width, height) = (image.get_width(), image.get_height());
+        let data = image.get_data();
+        let sample_counts = image.get_sample_counts();
+        let mut content = Vec::with_capacity(scene.len() + 24 + (data.len() + sample_counts.len()) * 4);
+        content.extend_from_slice(CHECKPOINT_MAGIC);
+        content.extend_from_slice(&(scene.len() as u32).to_le_bytes());
+        content.extend_from_slice(scene.as_bytes());
+        for value in [frames_done, width, height] {
+            content.extend_from_slice(&value.to_le_bytes());
+        }
+        push_f32s(&mut content, data);
+        push_u32s(&mut content, sample_counts);
+        content.extend_from_slice(&(image.get_aov_buffers().is_some() as u32).to_le_bytes());
+        if let Some(aovs) = image.get_aov_buffers() {
+            push_f32s(&mut content, &aovs.normals);
+            push_f32s(&mut content, &aovs.depths);
+            push_u32s(&mut content, &aovs.depth_hits);
+            push_f32s(&mut content, &aovs.albedos);
+            push_f32s(&mut content, &aovs.id_colors);
+            push_u32s(&mut content, &aovs.samples);
+            push_u32s(&mut content, &[aovs.nbr_of_light_passes as u32, aovs.light_colors.len() as u32]);
+            push_f32s(&mut content, &aovs.light_colors);
+        }
+
+        let temporary_path = path.with_extension("tmp");
+        std::fs::write(&temporary_path, content)
+            .and_then(|_| std::fs::rename(&temporary_path, path))
+            .map_err(|e| CheckpointError {error: format!("Could not write {}: {e}", path.display())})
+    }
+
+    /// Reads a checkpoint written by [Checkpoint::write].
+    pub fn read(path: &Path) -> Result<Checkpoint, CheckpointError> {
+        let content = std::fs::read(path)
+            .map_err(|e| CheckpointError {error: format!("Could not read {}: {e}", path.display())})?;
+        let invalid = || CheckpointError {error: format!("{} is no valid checkpoint!", path.display())};
+
+        let mut reader = ByteReader {content: &content, position: 0};
+        if reader.take(CHECKPOINT_MAGIC.len()).ok_or_else(invalid)? != CHECKPOINT_MAGIC {
+            return Err(invalid());
+        }
+        let scene_length = reader.u32().ok_or_else(invalid)? as usize;
+        let scene = String::from_utf8(reader.take(scene_length).ok_or_else(invalid)?.to_vec())
+            .map_err(|_| invalid())?;
+        let frames_done = reader.u32().ok_or_else(invalid)?;
+        let width = reader.u32().ok_or_else(invalid)?;
+        let height = reader.u32().ok_or_else(invalid)?;
+
+        //the image counts its values in u32, a corrupt header must not overflow the lengths
+        let nbr_of_pixels = width.checked_mul(height).filter(|pixels| pixels.checked_mul(4).is_some())
+            .ok_or_else(invalid)? as usize;
+        let length = |values_per_pixel: usize| nbr_of_pixels.checked_mul(values_per_pixel).ok_or_else(invalid);
+        let data = reader.f32s(length(4)?).ok_or_else(invalid)?;
+        let sample_counts = reader.u32s(nbr_of_pixels).ok_or_else(invalid)?;
+        let mut image = CustomImage::new_from_samples(width, height, data, sample_counts)
+            .map_err(|_| invalid())?;
+        if reader.u32().ok_or_else(invalid)? == 1 {
+            let mut aovs = AovBuffers {
+                normals: reader.f32s(length(3)?).ok_or_else(invalid)?,
+                depths: reader.f32s(nbr_of_pixels).ok_or_else(invalid)?,
+                depth_hits: reader.u32s(nbr_of_pixels).ok_or_else(invalid)?,
+                albedos: reader.f32s(length(3)?).ok_or_else(invalid)?,
+                id_colors: reader.f32s(length(3)?).ok_or_else(invalid)?,
+                samples: reader.u32s(nbr_of_pixels).ok_or_else(invalid)?,
+                light_colors: Vec::new(),
+                nbr_of_light_passes: reader.u32().ok_or_else(invalid)? as usize,
+            };
+            let light_colors_length = reader.u32().ok_or_else(invalid)? as usize;
+            aovs.light_colors = reader.f32s(light_colors_length).ok_or_else(invalid)?;
+            image.set_aov_buffers(aovs).map_err(|_| invalid())?;
+        }
+        Ok(Checkpoint {scene, frames_done, image})
+    }
+}
+
+/// Appends the values in little endian.
+fn push_u32s(content: &mut Vec<u8>, values: &[u32]) {
+    values.iter().for_each(|value| content.extend_from_slice(&value.to_le_bytes()));
+}
+
+/// Appends the values in little endian.
+fn push_f32s(content: &mut Vec<u8>, values: &[f32]) {
+    values.iter().for_each(|value| content.extend_from_slice(&value.to_le_bytes()));
+}
+
+/// Reads the values of a checkpoint file one after another.
+struct ByteReader<'a> {
+    content: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Returns the next bytes, None if the content ends before.
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.content.get(self.position..self.position.checked_add(length)?)?;
+        self.position += length;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u32s(&mut self, length: usize) -> Option<Vec<u32>> {
+        let bytes = self.take(length.checked_mul(4)?)?;
+        Some(bytes.chunks_exact(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())).collect())
+    }
+
+    fn f32s(&mut self, length: usize) -> Option<Vec<f32>> {
+        Some(self.u32s(length)?.into_iter().map(f32::from_bits).collect())
+    }
+}
+
+/// Writes the checkpoints of a running render every [CHECKPOINT_INTERVAL]. Errors are only logged,
+/// as they must not stop the render.
+pub struct CheckpointWriter {
+    path: PathBuf,
+    scene: String,
+    last_written: Instant,
+}
+
+impl CheckpointWriter {
+    /// Creates the writer for the render of the given scene file content.
+    pub fn new(path: PathBuf, scene: String) -> Self {
+        CheckpointWriter {path, scene, last_written: Instant::now()}
+    }
+
+    /// Writes a checkpoint of the image after the given number of frames, if the last one is
+    /// older than the interval.
+    pub fn frame_finished(&mut self, frames_done: u32, image: &CustomImage) {
+        if self.last_written.elapsed() >= CHECKPOINT_INTERVAL {
+            self.write(frames_done, image);
+        }
+    }
+
+    /// Writes a checkpoint of the image after the given number of frames.
+    pub fn write(&mut self, frames_done: u32, image: &CustomImage) {
+        match Checkpoint::write(&self.path, &self.scene, frames_done, image) {
+            Ok(()) => info!("Checkpoint after {frames_done} frames written to {}", self.path.display()),
+            Err(e) => warn!("Error writing the checkpoint: {}", e.error),
+        }
+        self.last_written = Instant::now();
+    }
+
+    /// Removes the checkpoint once the render is complete, as there is nothing left to resume.
+    pub fn remove(self) {
+        if self.path.exists() {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                warn!("Error removing the checkpoint {}: {e}", self.path.display());
+            }
+        }
+    }
+}
+
+/// An error type used when reading and writing checkpoints. Specific details of the error are
+/// given in the error String.
+#[derive(Debug)]
+pub struct CheckpointError {
+    pub error: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_keeps_aovs() {
+        let mut image = CustomImage::new(2, 1);
+        image.enable_aovs();
+        image.add_aov_sample(0, 0, &[0.0, 1.0, 0.0], 2.5, &[0.5, 0.25, 0.125], &[1.0, 0.0, 0.0]).unwrap();
+        image.add_aov_sample(1, 0, &[1.0, 0.0, 0.0], f32::INFINITY, &[0.0; 3], &[0.0; 3]).unwrap();
+        image.add_light_pass_sample(0, 0, &[0.25, 0.5, 0.75, 1.0, 1.0, 1.0]).unwrap();
+        let path = std::env::temp_dir().join(format!("test_checkpoint_keeps_aovs_{}.checkpoint", std::process::id()));
+        Checkpoint::write(&path, "scene", 3, &image).unwrap();
+        let checkpoint = Checkpoint::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        
+        let checkpoint = checkpoint.unwrap();
+        assert_eq!((checkpoint.scene.as_str(), checkpoint.frames_done), ("scene", 3));
+        let (expected, read) = (image.get_aov_buffers().unwrap(), checkpoint.image.get_aov_buffers().unwrap());
+        assert_eq!(read.normals, expected.normals);
+        assert_eq!((&read.depths, &read.depth_hits), (&expected.depths, &expected.depth_hits));
+        assert_eq!((&read.albedos, &read.id_colors, &read.samples), (&expected.albedos, &expected.id_colors, &expected.samples));
+        assert_eq!((&read.light_colors, read.nbr_of_light_passes), (&expected.light_colors, 2));
+    }
+
+    #[test]
+    fn test_truncated_checkpoint_is_refused() {
+        let mut image = CustomImage::new(3, 2);
+        image.enable_aovs();
+        image.add_light_pass_sample(1, 1, &[0.5, 0.5, 0.5]).unwrap();
+        let path = std::env::temp_dir().join(format!("test_truncated_checkpoint_is_refused_{}.checkpoint", std::process::id()));
+        Checkpoint::write(&path, "scene", 1, &image).unwrap();
+        let content = std::fs::read(&path).unwrap();
+        
+        let results: Vec<_> = (0..content.len()).map(|length| {
+            std::fs::write(&path, &content[..length]).unwrap();
+            Checkpoint::read(&path).is_err()
+        }).collect();
+        std::fs::remove_file(&path).unwrap();
+        assert!(results.iter().all(|&refused| refused), "A checkpoint cut off after {} bytes was read!", 
+                results.iter().position(|&refused| !refused).unwrap_or_default());
+    }
+
+    #[test]
+    fn test_oversized_checkpoint_header_is_refused() {
+        let path = std::env::temp_dir().join(format!("test_oversized_checkpoint_header_is_refused_{}.checkpoint", std::process::id()));
+        let results: Vec<_> = [(u32::MAX, u32::MAX), (65536, 65536), (32768, 32768), (u32::MAX, 1)].into_iter()
+            .map(|(width, height)| {
+                let mut content = CHECKPOINT_MAGIC.to_vec();
+                push_u32s(&mut content, &[0, 1, width, height]);
+                push_f32s(&mut content, &[1.0; 16]);
+                std::fs::write(&path, content).unwrap();
+                Checkpoint::read(&path).is_err()
+            }).collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(results, [true; 4], "A checkpoint with an oversized image was read!");
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+use crate::distributed;
+use crate::spectrum::Spectrum;
+use crate::spectrum_file;
+
+/// The usage text printed by the help subcommand and after invalid arguments.
+const USAGE: &str = "\
+Usage: eframe_raytracing [<subcommand> <arguments>]
+Without a subcommand the graphical user interface is started.
+
+Subcommands operating on spectrum files (.csv or .spectrum):
+  convert <input> <output> [--samples N]
+      Converts between CSV and the internal format, the formats are chosen by the file
+      extensions. When reading CSV, the spectrum is resampled to N samples.
+  resample <input> <output> --samples N [--range LOWEST HIGHEST]
+      Resamples the spectrum to N samples (a multiple of 8, at most 128), optionally covering
+      a new wavelength range in nanometers.
+  info <input>
+      Prints the radiance, the XYZ and linear sRGB color, the chromaticity and the correlated
+      color temperature of the spectrum.
+  blend <first> <second> <output> [--weight W]
+      Blends two spectra linearly, W is the share of the second spectrum (default 0.5). The
+      result uses the wavelengths and samples of the first spectrum.
+
+Other subcommands:
+  worker --token TOKEN [--bind ADDRESS] [--port N]
+      Waits for renders started on other machines and renders a share of their tiles, see the
+      render workers setting. Only renders for masters sending the same token. Listens on
+      ADDRESS (default 127.0.0.1, only reachable from this machine) and port N (default 7878).
+  help
+      Prints this text.";
+
+/// Runs the subcommand given by the command line arguments, excluding the executable name.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let (subcommand, args) = args.split_first()
+        .ok_or_else(|| CliError {error: USAGE.to_string()})?;
+    let args = Arguments::parse(args)?;
+
+    match subcommand.as_str() {
+        "convert" => {
+            let [input, output] = args.positional()?;
+            let spectrum = spectrum_file::read(Path::new(input), args.samples()?)?;
+            spectrum_file::write(Path::new(output), &spectrum)?;
+        }
+        "resample" => {
+            let [input, output] = args.positional()?;
+            let nbr_of_samples = args.samples()?
+                .ok_or_else(|| CliError {error: "resample requires --samples!".to_string()})?;
+            let mut spectrum = spectrum_file::read(Path::new(input), None)?;
+            match args.range()? {
+                Some((lowest, highest)) => {
+                    let samples: Vec<(f32, f32)> = spectrum.iter().collect();
+                    spectrum = Spectrum::new_from_samples(&samples, lowest, highest, nbr_of_samples);
+                }
+                None => spectrum.resample(nbr_of_samples),
+            }
+            spectrum_file::write(Path::new(output), &spectrum)?;
+        }
+        "info" => {
+            let [input] = args.positional()?;
+            let spectrum = spectrum_file::read(Path::new(input), None)?;
+            print_info(&spectrum);
+        }
+        "blend" => {
+            let [first, second, output] = args.positional()?;
+            let weight = args.weight()?;
+            let first = spectrum_file::read(Path::new(first), None)?;
+            let second = spectrum_file::read(Path::new(second), None)?;
+
+            //bring the second spectrum onto the samples of the first
+            let (lowest, highest) = first.get_range();
+            let samples: Vec<(f32, f32)> = second.iter().collect();
+            let mut second = Spectrum::new_from_samples(&samples, lowest, highest, first.get_nbr_of_samples());
+
+            let mut blended = first;
+            blended *= 1.0 - weight;
+            second *= weight;
+            blended += &second;
+            spectrum_file::write(Path::new(output), &blended)?;
+        }
+        "worker" => {
+            args.positional::<0>()?;
+            let token = args.token
+                .ok_or_else(|| CliError {error: "worker requires --token!".to_string()})?;
+            let bind_address = args.bind.unwrap_or(distributed::DEFAULT_WORKER_BIND_ADDRESS);
+            distributed::run_worker(bind_address, args.port()?, token)?;
+        }
+        "help" | "--help" | "-h" => println!("{USAGE}"),
+        _ => return Err(CliError {error: format!("Unknown subcommand '{subcommand}'!\n\n{USAGE}")}),
+    }
+
+    Ok(())
+}
+
+/// Prints the properties of the spectrum which can be derived by the spectrum module.
+fn print_info(spectrum: &Spectrum) {
+    let (lowest, highest) = spectrum.get_range();
+    let xyz = spectrum.get_xyz();
+    let (r, g, b) = spectrum.get_rgb_early();
+
+    println!("Range: {lowest}nm - {highest}nm, {} samples", spectrum.get_nbr_of_samples());
+    println!("Radiance: {}W/sr/m^2", spectrum.get_radiance());
+    println!("XYZ: {} {} {}", xyz.x, xyz.y, xyz.z);
+    println!("Linear sRGB: {r} {g} {b}");
+    match spectrum.get_xy_chromaticity() {
+        Some((x, y)) => println!("Chromaticity xy: {x} {y}"),
+        None => println!("Chromaticity xy: not visible"),
+    }
+    match spectrum.get_correlated_color_temperature() {
+        Some(temperature) => println!("Correlated color temperature: {temperature:.0}K"),
+        None => println!("Correlated color temperature: not visible"),
+    }
+}
+
+/// The arguments following a subcommand, split into positional arguments and options.
+struct Arguments<'a> {
+    positional: Vec<&'a str>,
+    samples: Option<&'a str>,
+    range: Option<(&'a str, &'a str)>,
+    weight: Option<&'a str>,
+    port: Option<&'a str>,
+    bind: Option<&'a str>,
+    token: Option<&'a str>,
+}
+
+impl<'a> Arguments<'a> {
+    fn parse(args: &'a [String]) -> Result<Self, CliError> {
+        let missing_value = |option: &str| CliError {error: format!("{option} requires a value!")};
+        let mut arguments = Arguments { positional: Vec::new(), samples: None, range: None, weight: None, port: None, 
+                                      bind: None, token: None };
+
+        let mut iter = args.iter().map(String::as_str);
+        while let Some(arg) = iter.next() {
+            match arg {
+                "--samples" => arguments.samples = Some(iter.next().ok_or_else(|| missing_value(arg))?),
+                "--weight" => arguments.weight = Some(iter.next().ok_or_else(|| missing_value(arg))?),
+                "--port" => arguments.port = Some(iter.next().ok_or_else(|| missing_value(arg))?),
+                "--bind" => arguments.bind = Some(iter.next().ok_or_else(|| missing_value(arg))?),
+                "--token" => arguments.token = Some(iter.next().ok_or_else(|| missing_value(arg))?),
+                "--range" => {
+                    let lowest = iter.next().ok_or_else(|| missing_value(arg))?;
+                    let highest = iter.next().ok_or_else(|| missing_value(arg))?;
+                    arguments.range = Some((lowest, highest));
+                }
+                _ if arg.starts_with("--") => return Err(CliError {error: format!("Unknown option '{arg}'!")}),
+                _ => arguments.positional.push(arg),
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    /// Returns the positional arguments, failing if there are not exactly N of them.
+    fn positional<const N: usize>(&self) -> Result<[&'a str; N], CliError> {
+        self.positional.clone().try_into().map_err(|_| CliError {
+            error: format!("Expected {N} file arguments, got {}!\n\n{USAGE}", self.positional.len())
+        })
+    }
+
+    fn samples(&self) -> Result<Option<usize>, CliError> {
+        self.samples.map(|samples| {
+            let samples = samples.parse::<usize>()
+                .map_err(|_| CliError {error: format!("'{samples}' is not a valid number of samples!")})?;
+            Ok(spectrum_file::check_nbr_of_samples(samples)?)
+        }).transpose()
+    }
+
+    fn range(&self) -> Result<Option<(f32, f32)>, CliError> {
+        self.range.map(|(lowest, highest)| {
+            let parse = |value: &str| value.parse::<f32>()
+                .map_err(|_| CliError {error: format!("'{value}' is not a valid wavelength!")});
+            let (lowest, highest) = (parse(lowest)?, parse(highest)?);
+            if lowest >= highest {
+                return Err(CliError {error: "The lowest wavelength must be smaller than the highest!".to_string()});
+            }
+            Ok((lowest, highest))
+        }).transpose()
+    }
+
+    fn weight(&self) -> Result<f32, CliError> {
+        match self.weight {
+            Some(weight) => weight.parse::<f32>()
+                .map_err(|_| CliError {error: format!("'{weight}' is not a valid weight!")}),
+            None => Ok(0.5),
+        }
+    }
+
+    fn port(&self) -> Result<u16, CliError> {
+        match self.port {
+            Some(port) => port.parse::<u16>()
+                .map_err(|_| CliError {error: format!("'{port}' is not a valid port!")}),
+            None => Ok(distributed::DEFAULT_WORKER_PORT),
+        }
+    }
+}
+
+/// An error type used by the command line interface. The error String is printed to the user.
+#[derive(Debug)]
+pub struct CliError {
+    pub error: String,
+}
+
+impl From<spectrum_file::SpectrumFileError> for CliError {
+    fn from(value: spectrum_file::SpectrumFileError) -> Self {
+        CliError {error: value.error}
+    }
+}
+
+impl From<distributed::DistributedError> for CliError {
+    fn from(value: distributed::DistributedError) -> Self {
+        CliError {error: value.error}
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use nalgebra::{point, vector, Point3, Rotation3};
+use crate::{SpectrumEffectType, UIAtmosphere, UIBackground, UICamera, UICsgPart, UILight, UIMaterial, UIMedium, UIObject, 
+            UIObjectType, UISpectrum};
+use crate::atmosphere::Atmosphere;
+use crate::medium::Medium;
+use crate::radiometry::{SpectralIntensity, SpectralRadiance, SpectralReemission, SpectralReflectance};
+use crate::shader::{Aabb, Background, Camera, LensModel, Light, Material, MaterialMix, ThickLens, MATERIAL_MIX_DEPTH_MAX};
+use crate::spectrum::Spectrum;
+
+impl From<&UISpectrum> for Spectrum {
+    fn from(value: &UISpectrum) -> Self {
+        let mut inner_spectrum = value.spectrum;
+        if value.spectrum_effect_type == SpectrumEffectType::Reflective {
+            inner_spectrum.min1();
+        }
+        inner_spectrum
+    }
+}
+
+impl From<&UIObject> for Aabb {
+    fn from(value: &UIObject) -> Self {
+        let pos = point![value.pos_x, value.pos_y, value.pos_z];
+        let mut aabb = aabb_from_type(&pos, &value.ui_object_type, (&*value.material.borrow()).into());
+        if let Some(spectrum) = &value.emission {
+            aabb.set_emission(SpectralRadiance::new(spectrum.borrow().spectrum), 
+                              value.emission_visible_to_camera, value.emission_visible_in_reflections);
+        }
+        aabb.set_texture(value.texture.clone());
+        aabb.set_object_id(value.id);
+        aabb.set_id_color(value.id_color());
+        aabb.set_visibility(value.visible_to_camera, value.visible_in_reflections, value.casts_shadows);
+        aabb
+    }
+}
+
+/// Creates the object of the given type at the given position. The parts of CSG objects are 
+/// placed relative to it. 
+fn aabb_from_type(pos: &Point3<f32>, object_type: &UIObjectType, material: Material) -> Aabb {
+    match *object_type {
+        UIObjectType::PlainBox(x_length, y_length, z_length) => {
+            Aabb::new_box(pos, x_length, y_length, z_length, material)
+        }
+        UIObjectType::Sphere(radius) => {
+            Aabb::new_sphere(pos, radius, material)
+        }
+        UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, z_rotation) => {
+            let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
+            Aabb::new_rotated_box(pos, x_length, y_length, z_length, rotation, material)
+        }
+        UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+            let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
+            Aabb::new_cylinder(pos, radius, height, rotation, material)
+        }
+        UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+            let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
+            Aabb::new_ellipsoid(pos, vector![x_radius, y_radius, z_radius], rotation, material)
+        }
+        UIObjectType::Disc(radius, normal_x, normal_y, normal_z) => {
+            Aabb::new_disc(pos, radius, &vector![normal_x, normal_y, normal_z], material)
+        }
+        UIObjectType::Quad(width, depth, normal_x, normal_y, normal_z) => {
+            Aabb::new_quad(pos, width, depth, &vector![normal_x, normal_y, normal_z], material)
+        }
+        UIObjectType::Heightfield(ref heightfield) => {
+            Aabb::new_heightfield(pos, heightfield.width, heightfield.depth, heightfield.height, 
+                                  heightfield.grid.clone(), material)
+        }
+        UIObjectType::Csg(ref csg) => {
+            let part = |part: &UICsgPart| {
+                let part_pos = pos + vector![part.offset_x, part.offset_y, part.offset_z];
+                aabb_from_type(&part_pos, &part.ui_object_type, material.clone())
+            };
+            Aabb::new_csg(csg.operation, part(&csg.first), part(&csg.second), material)
+        }
+    }
+}
+
+impl From<&UILight> for Light {
+    fn from(value: &UILight) -> Self {
+        let mut light = Light::new(point![value.pos_x, value.pos_y, value.pos_z], 
+                                   SpectralIntensity::new(value.spectrum.borrow().spectrum));
+        light.set_link(value.link_mode, value.linked_objects.iter().copied().collect());
+        light
+    }
+}
+
+impl From<&UICamera> for Camera {
+    fn from(ui_camera: &UICamera) -> Self {
+        let mut camera = Camera::new(
+            point![
+                    ui_camera.pos_x, 
+                    ui_camera.pos_y, 
+                    ui_camera.pos_z
+                ],
+            vector![
+                    ui_camera.dir_x, 
+                    ui_camera.dir_y, 
+                    ui_camera.dir_z
+                ],
+            vector![
+                ui_camera.up_x,
+                ui_camera.up_y,
+                ui_camera.up_z,
+            ],
+            ui_camera.fov_deg_y,
+            if ui_camera.thick_lens {
+                LensModel::ThickLens(ThickLens {
+                    aperture_radius: ui_camera.aperture_radius,
+                    focus_distance: ui_camera.focus_distance,
+                    thickness: ui_camera.lens_thickness,
+                    refractive_index: ui_camera.lens_refractive_index,
+                    abbe_number: ui_camera.lens_abbe_number,
+                    nbr_of_wavelength_bands: ui_camera.lens_wavelength_bands,
+                })
+            } else {
+                LensModel::Pinhole
+            });
+        camera.near_clip = ui_camera.near_clip.unwrap_or(0.0);
+        camera.far_clip = ui_camera.far_clip.unwrap_or(f32::INFINITY);
+        camera
+    }
+}
+
+impl From<&UIMaterial> for Material {
+    fn from(value: &UIMaterial) -> Self {
+        material_from_ui(value, 0)
+    }
+}
+
+/// Converts the material, following mixes up to [MATERIAL_MIX_DEPTH_MAX] levels deep. The UI does 
+/// not allow cyclic mixes, this only keeps a cycle from overflowing the stack should one occur 
+/// anyway. 
+fn material_from_ui(value: &UIMaterial, depth: usize) -> Material {
+    Material {
+        mix: value.mix.as_ref().filter(|_| depth < MATERIAL_MIX_DEPTH_MAX).map(|mix| Arc::new(MaterialMix {
+            first: material_from_ui(&mix.first.borrow(), depth + 1),
+            second: material_from_ui(&mix.second.borrow(), depth + 1),
+            weight: mix.weight,
+        })),
+        reflective_spectrum: SpectralReflectance::new((&*value.spectrum.borrow()).into()),
+        metallicness: value.metallicness,
+        roughness: value.roughness,
+        bitangent_roughness: value.bitangent_roughness.unwrap_or(value.roughness),
+        tangent_rotation: value.tangent_rotation,
+        pattern: value.pattern.as_ref().map(|pattern| 
+            (pattern.pattern, SpectralReflectance::new((&*pattern.spectrum.borrow()).into()))),
+        fluorescence: value.fluorescence.as_ref().map(|fluorescence| Arc::new(SpectralReemission::new(
+            &fluorescence.excitation.borrow().spectrum, 
+            &fluorescence.emission.borrow().spectrum, 
+            fluorescence.quantum_yield))),
+        shadow_catcher: value.shadow_catcher,
+        bsdf: None,
+    }
+}
+
+impl From<&UIBackground> for Background {
+    fn from(value: &UIBackground) -> Self {
+        match value {
+            UIBackground::Black => Background::Black,
+            UIBackground::Spectrum(spectrum) => Background::Uniform(Arc::new(SpectralRadiance::new(spectrum.borrow().spectrum))),
+            UIBackground::Gradient {top, bottom} => Background::Gradient {
+                top: Arc::new(SpectralRadiance::new(top.borrow().spectrum)),
+                bottom: Arc::new(SpectralRadiance::new(bottom.borrow().spectrum)),
+            },
+            UIBackground::Transparent => Background::Transparent,
+        }
+    }
+}
+
+impl From<&UIAtmosphere> for Atmosphere {
+    fn from(value: &UIAtmosphere) -> Self {
+        Atmosphere::new(value.sun_elevation_deg, value.sun_azimuth_deg, &value.sun_spectrum.borrow().spectrum, 
+                        value.turbidity, value.altitude)
+    }
+}
+
+impl From<&UIMedium> for Medium {
+    fn from(value: &UIMedium) -> Self {
+        Medium::new(&value.absorption.borrow().spectrum, &value.scattering.borrow().spectrum, value.density, 
+                    value.in_scattering)
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// The boolean operations which combine two objects into a single one (constructive solid
+/// geometry).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CsgOperation {
+    /// Everything inside either of the two objects.
+    Union,
+    /// Only what is inside both objects.
+    Intersection,
+    /// Everything inside the first object which is not inside the second one, for example a box
+    /// with a spherical hole.
+    Difference,
+}
+
+impl CsgOperation {
+    pub const ALL: [CsgOperation; 3] = [CsgOperation::Union, CsgOperation::Intersection, CsgOperation::Difference];
+
+    /// Returns whether a point inside or outside of the two objects is inside the combined object.
+    pub fn contains(&self, in_first: bool, in_second: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_first || in_second,
+            CsgOperation::Intersection => in_first && in_second,
+            CsgOperation::Difference => in_first && !in_second,
+        }
+    }
+}
+
+impl Display for CsgOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsgOperation::Union => write!(f, "Union"),
+            CsgOperation::Intersection => write!(f, "Intersection"),
+            CsgOperation::Difference => write!(f, "Difference"),
+        }
+    }
+}
+
+/// A point at which a ray enters or leaves a solid. The surface identifies the primitive the ray
+/// passes through there, so that its normal can be calculated later. If flipped, the normal of
+/// the surface points into the solid instead of out of it, which happens where the surface of a
+/// subtracted object bounds a difference.
+#[derive(Clone, Copy)]
+pub struct Boundary<S> {
+    pub t: f32,
+    pub surface: S,
+    pub flipped: bool,
+}
+
+/// Combines the stretches of a ray inside two solids into the stretches inside the solid created
+/// by the operation. Each stretch is given by the boundary where the ray enters it and the one
+/// where it leaves it, the stretches of each solid have to be sorted and must not overlap. The
+/// result is sorted and does not overlap either.
+pub fn combine<S: Copy>(operation: CsgOperation, first: &[(Boundary<S>, Boundary<S>)],
+                        second: &[(Boundary<S>, Boundary<S>)]) -> Vec<(Boundary<S>, Boundary<S>)> {
+    //every boundary of either solid is a point at which the ray may enter or leave the result
+    let mut events: Vec<(Boundary<S>, bool)> = first.iter().flat_map(|(enter, leave)| [(*enter, true), (*leave, true)])
+        .chain(second.iter().flat_map(|(enter, leave)| [(*enter, false), (*leave, false)]))
+        .collect();
+    events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+    let mut combined = Vec::new();
+    let (mut in_first, mut in_second) = (false, false);
+    let mut entered: Option<Boundary<S>> = None;
+    for (mut boundary, of_first) in events {
+        if of_first {
+            in_first = !in_first;
+        } else {
+            in_second = !in_second;
+            if operation == CsgOperation::Difference {
+                boundary.flipped = !boundary.flipped;
+            }
+        }
+
+        match (entered, operation.contains(in_first, in_second)) {
+            (None, true) => entered = Some(boundary),
+            (Some(enter), false) => {
+                combined.push((enter, boundary));
+                entered = None;
+            }
+            _ => (),
+        }
+    }
+    combined
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The stretch from enter to leave through the surface with the given name. 
+    fn stretch(enter: f32, leave: f32, surface: char) -> (Boundary<char>, Boundary<char>) {
+        (Boundary {t: enter, surface, flipped: false}, Boundary {t: leave, surface, flipped: false})
+    }
+
+    /// The t, surface and flip of a boundary.
+    type BoundaryValues = (f32, char, bool);
+
+    /// The values of the entry and the exit of each stretch.
+    fn simplify(stretches: &[(Boundary<char>, Boundary<char>)]) -> Vec<(BoundaryValues, BoundaryValues)> {
+        stretches.iter()
+            .map(|(enter, leave)| ((enter.t, enter.surface, enter.flipped), (leave.t, leave.surface, leave.flipped)))
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping_solids() {
+        let first = [stretch(1.0, 3.0, 'a')];
+        let second = [stretch(2.0, 4.0, 'b')];
+        assert_eq!(simplify(&combine(CsgOperation::Union, &first, &second)),
+                   vec![((1.0, 'a', false), (4.0, 'b', false))]);
+        assert_eq!(simplify(&combine(CsgOperation::Intersection, &first, &second)),
+                   vec![((2.0, 'b', false), (3.0, 'a', false))]);
+        //the ray leaves the difference where it enters the subtracted solid, seeing its inside
+        assert_eq!(simplify(&combine(CsgOperation::Difference, &first, &second)),
+                   vec![((1.0, 'a', false), (2.0, 'b', true))]);
+    }
+
+    #[test]
+    fn test_separate_solids() {
+        let first = [stretch(1.0, 2.0, 'a'), stretch(5.0, 6.0, 'a')];
+        let second = [stretch(3.0, 4.0, 'b')];
+        assert_eq!(simplify(&combine(CsgOperation::Union, &first, &second)),
+                   vec![((1.0, 'a', false), (2.0, 'a', false)), ((3.0, 'b', false), (4.0, 'b', false)), 
+                        ((5.0, 'a', false), (6.0, 'a', false))]);
+        assert!(combine(CsgOperation::Intersection, &first, &second).is_empty());
+        assert_eq!(simplify(&combine(CsgOperation::Difference, &first, &second)), simplify(&first));
+    }
+
+    #[test]
+    fn test_hole_through_solid() {
+        //a box with a hole drilled through, the ray passes through the walls on both sides
+        let first = [stretch(1.0, 5.0, 'a')];
+        let second = [stretch(2.0, 3.0, 'b')];
+        assert_eq!(simplify(&combine(CsgOperation::Difference, &first, &second)),
+                   vec![((1.0, 'a', false), (2.0, 'b', true)), ((3.0, 'b', true), (5.0, 'a', false))]);
+        assert_eq!(simplify(&combine(CsgOperation::Intersection, &first, &second)), simplify(&second));
+        assert_eq!(simplify(&combine(CsgOperation::Union, &first, &second)), simplify(&first));
+    }
+
+    #[test]
+    fn test_ray_starting_inside() {
+        //the ray starts inside the first solid, which it entered behind its origin
+        let first = [stretch(-1.0, 2.0, 'a')];
+        let second = [stretch(1.0, 3.0, 'b')];
+        assert_eq!(simplify(&combine(CsgOperation::Union, &first, &second)),
+                   vec![((-1.0, 'a', false), (3.0, 'b', false))]);
+        assert_eq!(simplify(&combine(CsgOperation::Intersection, &first, &second)),
+                   vec![((1.0, 'b', false), (2.0, 'a', false))]);
+        assert_eq!(simplify(&combine(CsgOperation::Difference, &first, &second)),
+                   vec![((-1.0, 'a', false), (1.0, 'b', true))]);
+    }
+
+    #[test]
+    fn test_flipped_boundaries_of_nested_difference() {
+        //a difference subtracted again flips the surfaces of the inner subtracted solid back
+        let inner = combine(CsgOperation::Difference, &[stretch(2.0, 6.0, 'b')], &[stretch(3.0, 4.0, 'c')]);
+        let outer = combine(CsgOperation::Difference, &[stretch(1.0, 8.0, 'a')], &inner);
+        assert_eq!(simplify(&outer),
+                   vec![((1.0, 'a', false), (2.0, 'b', true)), ((3.0, 'c', false), (4.0, 'c', false)), 
+                        ((6.0, 'b', true), (8.0, 'a', false))]);
+    }
+}
@@ -1,15 +1,209 @@
-use image::{DynamicImage, RgbaImage};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use image::{DynamicImage, ImageBuffer, Rgb32FImage, Rgba, Rgba32FImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use crate::bloom::Bloom;
+use crate::gamut;
+use crate::post_effects::PostEffect;
+use crate::sampler;
 
 const NBR_DATA_POINTS_PER_PIXEL: usize = 4;
+/// Added to the luminance accumulated so far before the outlier rejection threshold is derived 
+/// from it. Without it, a pixel which happens to be black after the first frames could never 
+/// brighten again. 
+const OUTLIER_REJECTION_LUMINANCE_FLOOR: f32 = 0.1;
+/// Colors darker than this are written as black into Radiance files, their exponent would not 
+/// fit into a byte. 
+const RGBE_DARKEST_COLOR: f32 = 1e-32;
+/// The linear value the [ToneMapper::Filmic] curve maps to white, and the factor the colors are 
+/// scaled by before, as proposed by John Hable. 
+const FILMIC_WHITE_POINT: f32 = 11.2;
+const FILMIC_EXPOSURE_BIAS: f32 = 2.0;
+
+/// Strategies to suppress fireflies, single very bright samples, usually from rare indirect light 
+/// paths, which would otherwise leave hot pixels that take very long to average out. Both 
+/// strategies trade a little energy (bias) for much less noise.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FireflyFilter {
+    /// Samples are blended in as they are.
+    Off,
+    /// Samples brighter than the given luminance are darkened to it, keeping their hue.
+    Clamp(f32),
+    /// Samples brighter than the given factor times the luminance accumulated so far are 
+    /// darkened to that limit. Adapts to the brightness of each pixel. 
+    OutlierRejection(f32),
+}
+
+impl Display for FireflyFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FireflyFilter::Off => write!(f, "Off"),
+            FireflyFilter::Clamp(_) => write!(f, "Clamp"),
+            FireflyFilter::OutlierRejection(_) => write!(f, "Outlier rejection"),
+        }
+    }
+}
+
+/// How the linear colors of a [CustomImage] are encoded into 8 and 16 bit images. The float 
+/// formats always keep the linear colors. 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TransferFunction {
+    /// The sRGB curve displays and image viewers expect, so that the image appears as bright as 
+    /// intended. 
+    Srgb,
+    /// The linear values are only clamped and scaled, for further processing of the raw values.
+    Linear,
+}
+
+impl TransferFunction {
+    /// Encodes a linear value in range \[0; 1] for storing it in an integer image. 
+    pub fn encode(&self, value: f32) -> f32 {
+        match self {
+            TransferFunction::Srgb if value <= 0.0031308 => value * 12.92,
+            TransferFunction::Srgb => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+            TransferFunction::Linear => value,
+        }
+    }
+    
+    /// Decodes a value in range \[0; 1] read from an integer image back into a linear value, the 
+    /// inverse of [encode](TransferFunction::encode). 
+    pub fn decode(&self, value: f32) -> f32 {
+        match self {
+            TransferFunction::Srgb if value <= 0.04045 => value / 12.92,
+            TransferFunction::Srgb => ((value + 0.055) / 1.055).powf(2.4),
+            TransferFunction::Linear => value,
+        }
+    }
+}
+
+/// How the linear colors of a [CustomImage] are compressed into the displayable range \[0; 1] 
+/// before they are encoded into 8 and 16 bit images. Without it, bright emissive objects and 
+/// highlights clip to white. The float formats always keep the uncompressed colors. 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ToneMapper {
+    /// Colors above 1 are clipped. 
+    Clip,
+    /// Compresses the luminance with l / (1 + l), keeping the hue. Gentle, but flattens the 
+    /// contrast of bright images. 
+    Reinhard,
+    /// The fit of the ACES filmic curve by Krzysztof Narkowicz, with a toe and a soft shoulder 
+    /// which also desaturates very bright colors. 
+    Aces,
+    /// The filmic curve of John Hable from Uncharted 2, with a longer shoulder than ACES. 
+    Filmic,
+}
+
+impl ToneMapper {
+    /// Maps a linear color into range \[0; 1]. 
+    pub fn apply(&self, color: [f32; 3]) -> [f32; 3] {
+        let color = color.map(|c| c.max(0.0));
+        match self {
+            ToneMapper::Clip => color.map(|c| c.min(1.0)),
+            ToneMapper::Reinhard => {
+                let luminance = 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+                color.map(|c| (c / (1.0 + luminance)).min(1.0))
+            }
+            ToneMapper::Aces => color.map(|c| {
+                ((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14)).clamp(0.0, 1.0)
+            }),
+            ToneMapper::Filmic => {
+                let white_scale = 1.0 / hable_curve(FILMIC_WHITE_POINT);
+                color.map(|c| (hable_curve(c * FILMIC_EXPOSURE_BIAS) * white_scale).min(1.0))
+            }
+        }
+    }
+}
+
+impl Display for ToneMapper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToneMapper::Clip => write!(f, "Clip"),
+            ToneMapper::Reinhard => write!(f, "Reinhard"),
+            ToneMapper::Aces => write!(f, "ACES"),
+            ToneMapper::Filmic => write!(f, "Filmic"),
+        }
+    }
+}
+
+/// The curve of the [ToneMapper::Filmic] tone mapper, without its white point normalization. 
+fn hable_curve(x: f32) -> f32 {
+    const SHOULDER_STRENGTH: f32 = 0.15;
+    const LINEAR_STRENGTH: f32 = 0.5;
+    const LINEAR_ANGLE: f32 = 0.1;
+    const TOE_STRENGTH: f32 = 0.2;
+    const TOE_NUMERATOR: f32 = 0.02;
+    const TOE_DENOMINATOR: f32 = 0.3;
+    ((x * (SHOULDER_STRENGTH * x + LINEAR_ANGLE * LINEAR_STRENGTH) + TOE_STRENGTH * TOE_NUMERATOR)
+        / (x * (SHOULDER_STRENGTH * x + LINEAR_STRENGTH) + TOE_STRENGTH * TOE_DENOMINATOR))
+        - TOE_NUMERATOR / TOE_DENOMINATOR
+}
 
 /// CustomImage is a struct which is supposed to hold images whose values are stored in f32 for each
 /// channel. Additionally, pixel blending support is included to ease layering multiple images over
-/// each other.
+/// each other. <br/>
+/// The colors are stored premultiplied by the alpha, so that blending pixels of different opacity 
+/// weights each color by its opacity. <br/>
+/// Besides the colors, the image can hold the shading normals, the distances, the albedos and the 
+/// object id colors of what the pixels see first in separate buffers, see 
+/// [enable_aovs](CustomImage::enable_aovs). 
 #[derive(Clone)]
 pub struct CustomImage {
     width: u32,
     height: u32,
     data: Vec<f32>,
+    /// The number of samples averaged into each pixel by [add_sample](CustomImage::add_sample).
+    sample_counts: Vec<u32>,
+    firefly_filter: FireflyFilter,
+    transfer_function: TransferFunction,
+    tone_mapper: ToneMapper,
+    /// The exposure in stops the colors are scaled by before tone mapping. 
+    exposure: f32,
+    /// The glow added around bright pixels before the colors are exposed, None for no glow. 
+    bloom: Option<Bloom>,
+    /// The stylistic effects applied after tone mapping, in order. 
+    post_effects: Vec<PostEffect>,
+    /// The arbitrary output variables besides the colors, if they are recorded. 
+    aovs: Option<AovBuffers>,
+}
+
+/// The sums of the arbitrary output variables of the samples of the pixels of a [CustomImage], 
+/// all row by row. 
+#[derive(Clone)]
+pub struct AovBuffers {
+    /// The sums of the normals, x, y, z per pixel. 
+    pub normals: Vec<f32>,
+    /// The sums of the distances of the samples which hit something and their number per pixel. 
+    pub depths: Vec<f32>,
+    pub depth_hits: Vec<u32>,
+    /// The sums of the albedos and of the object id colors, r, g, b per pixel, and the number of 
+    /// samples added per pixel. 
+    pub albedos: Vec<f32>,
+    pub id_colors: Vec<f32>,
+    pub samples: Vec<u32>,
+    /// The sums of the r, g, b values each light source contributes, all lights of a pixel after 
+    /// each other, and the number of lights. Allocated by the first sample with light passes. 
+    pub light_colors: Vec<f32>,
+    pub nbr_of_light_passes: usize,
+}
+
+impl AovBuffers {
+    /// Adds the sums of a pixel of other buffers with the same light passes to the pixel at the 
+    /// given index. 
+    fn add_sums_of(&mut self, index: usize, other: &AovBuffers, other_index: usize) {
+        let add = |sums: &mut [f32], other_sums: &[f32], stride: usize| {
+            let other_pixel = &other_sums[other_index * stride..(other_index + 1) * stride];
+            sums[index * stride..(index + 1) * stride].iter_mut().zip(other_pixel).for_each(|(sum, other)| *sum += other);
+        };
+        add(&mut self.normals, &other.normals, 3);
+        add(&mut self.depths, &other.depths, 1);
+        add(&mut self.albedos, &other.albedos, 3);
+        add(&mut self.id_colors, &other.id_colors, 3);
+        if !self.light_colors.is_empty() && !other.light_colors.is_empty() {
+            add(&mut self.light_colors, &other.light_colors, self.nbr_of_light_passes * 3);
+        }
+        self.depth_hits[index] += other.depth_hits[other_index];
+        self.samples[index] += other.samples[other_index];
+    }
 }
 
 impl CustomImage {
@@ -17,17 +211,41 @@ impl CustomImage {
     /// black in standard interpretation. The length of the data is width * height * 4 (r, g, b, a). 
     pub fn new(width: u32, height: u32) -> CustomImage {
         let data = vec![0.0; (width * height * 4) as usize];
+        let sample_counts = vec![0; (width * height) as usize];
         
-        CustomImage {width, height, data}
+        CustomImage {width, height, data, sample_counts, firefly_filter: FireflyFilter::Off, 
+                     transfer_function: TransferFunction::Srgb, tone_mapper: ToneMapper::Clip, 
+                     exposure: 0.0, bloom: None, 
+                     post_effects: Vec::new(), aovs: None}
     }
     
     /// Generates a new CustomImage from a given width, height and data vec. Will return a 
-    /// CustomImageError if the length of the data does not match the width and height. 
+    /// CustomImageError if the length of the data does not match the width and height. Every pixel 
+    /// of the data counts as one sample. 
     pub fn new_from_data(width: u32, height: u32, data: Vec<f32>) -> Result<CustomImage, CustomImageError> {
         if width * height * 4 != data.len() as u32 {
             return Err(CustomImageError{error: "Data length does not match given width and height!".to_string()});
         }
-        Ok(CustomImage { width, height, data })
+        let sample_counts = vec![1; (width * height) as usize];
+        Ok(CustomImage { width, height, data, sample_counts, firefly_filter: FireflyFilter::Off, 
+                         transfer_function: TransferFunction::Srgb, tone_mapper: ToneMapper::Clip, 
+                         exposure: 0.0, bloom: None, 
+                         post_effects: Vec::new(), aovs: None })
+    }
+    
+    /// Generates a new CustomImage from the data and sample counts of another one, see 
+    /// [get_data](CustomImage::get_data) and [get_sample_counts](CustomImage::get_sample_counts), 
+    /// so that further samples are averaged in as if the image had never been taken apart. Will 
+    /// return a CustomImageError if the lengths do not match the width and height. 
+    pub fn new_from_samples(width: u32, height: u32, data: Vec<f32>, sample_counts: Vec<u32>) 
+        -> Result<CustomImage, CustomImageError> {
+        if width * height * 4 != data.len() as u32 || width * height != sample_counts.len() as u32 {
+            return Err(CustomImageError{error: "Data length does not match given width and height!".to_string()});
+        }
+        Ok(CustomImage { width, height, data, sample_counts, firefly_filter: FireflyFilter::Off, 
+                         transfer_function: TransferFunction::Srgb, tone_mapper: ToneMapper::Clip, 
+                         exposure: 0.0, bloom: None, 
+                         post_effects: Vec::new(), aovs: None })
     }
     
     /// Takes a row of Pixels and blends each pixel with the corresponding row in the data. The 
@@ -54,7 +272,8 @@ impl CustomImage {
     }
 
     /// Blends a single Pixel at the given position with the old data. The new Pixel is multiplied 
-    /// by new_weight_factor and the old Pixel by 1 - new_weight_factor, then added together. <br/>
+    /// by new_weight_factor and the old Pixel by 1 - new_weight_factor, then added together. 
+    /// Beforehand, the new Pixel is darkened according to the [FireflyFilter] of the image. <br/>
     /// Returns a CustomImageError if x or y are out of bounds. 
     pub fn blend_pixel(&mut self, x: usize, y: usize, pixel: &Pixel, new_weight_factor: f32)    //TODO SIMD optimisation?
         -> Result<(), CustomImageError> {
@@ -70,6 +289,25 @@ impl CustomImage {
 
         let old_factor = 1.0 - new_weight_factor;
         let index = y * row_length + x * pixel_size;
+        
+        //the first blended pixel has no history to be compared to
+        let max_luminance = match self.firefly_filter {
+            FireflyFilter::Off => f32::INFINITY,
+            FireflyFilter::Clamp(max_luminance) => max_luminance,
+            FireflyFilter::OutlierRejection(_) if new_weight_factor >= 1.0 => f32::INFINITY,
+            FireflyFilter::OutlierRejection(factor) => {
+                let old_luminance = gamut::luminance((self.data[index], self.data[index + 1], self.data[index + 2]));
+                factor * (old_luminance.max(0.0) + OUTLIER_REJECTION_LUMINANCE_FLOOR)
+            }
+        };
+        let luminance = gamut::luminance((pixel.r, pixel.g, pixel.b));
+        let pixel = if luminance > max_luminance {
+            let scale = max_luminance / luminance;
+            &Pixel {r: pixel.r * scale, g: pixel.g * scale, b: pixel.b * scale, a: pixel.a}
+        } else {
+            pixel
+        };
+        
         self.data[index] = self.data[index] * old_factor + pixel.r * new_weight_factor;
         self.data[index + 1] = self.data[index + 1] * old_factor + pixel.g * new_weight_factor;
         self.data[index + 2] = self.data[index + 2] * old_factor + pixel.b * new_weight_factor;
@@ -78,6 +316,41 @@ impl CustomImage {
         Ok(())
     }
     
+    /// Averages a new sample into the Pixel at the given position. Each pixel keeps track of how 
+    /// many samples it holds, so that the result is the true average of all its samples, even if 
+    /// the pixels hold different numbers of samples, e.g. after an aborted frame. <br/>
+    /// Returns a CustomImageError if x or y are out of bounds. 
+    pub fn add_sample(&mut self, x: usize, y: usize, pixel: &Pixel) -> Result<(), CustomImageError> {
+        if x >= self.width as usize || y >= self.height as usize {
+            return Err(CustomImageError {error: 
+            format!("{x} or {y} out of bounds for width {} or height {}!", self.width, self.height)});
+        }
+        
+        let index = y * self.width as usize + x;
+        self.sample_counts[index] += 1;
+        self.blend_pixel(x, y, pixel, 1.0 / self.sample_counts[index] as f32)
+    }
+    
+    /// Returns the number of samples averaged into the Pixel at the given position, 0 if the 
+    /// position is out of bounds. 
+    pub fn get_sample_count(&self, x: usize, y: usize) -> u32 {
+        if x >= self.width as usize || y >= self.height as usize {
+            return 0;
+        }
+        self.sample_counts[y * self.width as usize + x]
+    }
+    
+    /// Returns the r, g, b, a values of all pixels row by row, the colors premultiplied by the alpha.
+    pub fn get_data(&self) -> &[f32] {
+        &self.data
+    }
+    
+    /// Returns the number of samples of all pixels row by row, see 
+    /// [get_sample_count](CustomImage::get_sample_count). 
+    pub fn get_sample_counts(&self) -> &[u32] {
+        &self.sample_counts
+    }
+    
     /// Returns the images width. 
     pub fn get_width(&self) -> u32 {
         self.width
@@ -87,14 +360,427 @@ impl CustomImage {
     pub fn get_height(&self) -> u32 {
         self.height
     }
+    
+    /// Sets how fireflies are suppressed when blending new pixels into the image. 
+    pub fn set_firefly_filter(&mut self, firefly_filter: FireflyFilter) {
+        self.firefly_filter = firefly_filter;
+    }
+    
+    /// Sets how the colors are encoded when the image is converted into an 8 or 16 bit image, 
+    /// sRGB unless set otherwise. 
+    pub fn set_transfer_function(&mut self, transfer_function: TransferFunction) {
+        self.transfer_function = transfer_function;
+    }
+    
+    /// Sets how the colors are compressed into range \[0; 1] when the image is converted into an 
+    /// 8 or 16 bit image, clipped unless set otherwise. 
+    pub fn set_tone_mapper(&mut self, tone_mapper: ToneMapper) {
+        self.tone_mapper = tone_mapper;
+    }
+    
+    /// Sets the exposure in stops (EV) applied before tone mapping when the image is converted 
+    /// into an 8 or 16 bit image: every stop doubles the brightness, negative stops darken. 
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+    
+    /// Sets the glow added around bright pixels when the image is converted into an 8 or 16 bit 
+    /// image, None for no glow. 
+    pub fn set_bloom(&mut self, bloom: Option<Bloom>) {
+        self.bloom = bloom;
+    }
+    
+    /// Sets the post effects applied in order after tone mapping when the image is converted into 
+    /// an 8 or 16 bit image. 
+    pub fn set_post_effects(&mut self, post_effects: Vec<PostEffect>) {
+        self.post_effects = post_effects;
+    }
+    
+    /// Returns the r, g, b, a values of the pixels as they are displayed: with the glow of the 
+    /// bloom, no longer premultiplied by the alpha, exposed, tone mapped into range \[0; 1] and 
+    /// run through the post effects, but not yet encoded by the transfer function. 
+    fn display_colors(&self) -> Vec<[f32; 4]> {
+        let bloomed = self.bloom.map(|bloom| bloom.apply(self.width, self.height, &self.data));
+        let data = bloomed.as_deref().unwrap_or(&self.data);
+        let scale = self.exposure.exp2();
+        let mut colors = data.chunks_exact(NBR_DATA_POINTS_PER_PIXEL).map(|pixel| {
+            let [r, g, b] = self.tone_mapper.apply(unmultiplied_color(pixel).map(|c| c * scale));
+            [r, g, b, pixel[3].clamp(0.0, 1.0)]
+        }).collect::<Vec<[f32; 4]>>();
+        for effect in &self.post_effects {
+            effect.apply(self.width, self.height, &mut colors);
+        }
+        colors
+    }
+    
+    /// Starts recording the arbitrary output variables of the samples added by 
+    /// [add_aov_sample](CustomImage::add_aov_sample). Samples recorded so far are kept. 
+    pub fn enable_aovs(&mut self) {
+        if self.aovs.is_none() {
+            let nbr_of_pixels = (self.width * self.height) as usize;
+            self.aovs = Some(AovBuffers {
+                normals: vec![0.0; nbr_of_pixels * 3],
+                depths: vec![0.0; nbr_of_pixels],
+                depth_hits: vec![0; nbr_of_pixels],
+                albedos: vec![0.0; nbr_of_pixels * 3],
+                id_colors: vec![0.0; nbr_of_pixels * 3],
+                samples: vec![0; nbr_of_pixels],
+                light_colors: Vec::new(),
+                nbr_of_light_passes: 0,
+            });
+        }
+    }
+    
+    /// Returns the sums of the arbitrary output variables, None if they are not recorded. 
+    pub fn get_aov_buffers(&self) -> Option<&AovBuffers> {
+        self.aovs.as_ref()
+    }
+    
+    /// Replaces the arbitrary output variables by the given sums, e.g. ones returned by 
+    /// [get_aov_buffers](CustomImage::get_aov_buffers), and records them from now on. Will return 
+    /// a CustomImageError if the lengths of the buffers do not match the width and height. 
+    pub fn set_aov_buffers(&mut self, aovs: AovBuffers) -> Result<(), CustomImageError> {
+        let nbr_of_pixels = (self.width * self.height) as usize;
+        let light_colors_length = if aovs.light_colors.is_empty() { 0 } else { nbr_of_pixels * aovs.nbr_of_light_passes * 3 };
+        if aovs.normals.len() != nbr_of_pixels * 3 || aovs.depths.len() != nbr_of_pixels 
+            || aovs.depth_hits.len() != nbr_of_pixels || aovs.albedos.len() != nbr_of_pixels * 3 
+            || aovs.id_colors.len() != nbr_of_pixels * 3 || aovs.samples.len() != nbr_of_pixels 
+            || aovs.light_colors.len() != light_colors_length {
+            return Err(CustomImageError{error: "AOV length does not match given width and height!".to_string()});
+        }
+        self.aovs = Some(aovs);
+        Ok(())
+    }
+    
+    /// Returns true if the arbitrary output variables of the samples are recorded. 
+    pub fn records_aovs(&self) -> bool {
+        self.aovs.is_some()
+    }
+    
+    /// Adds the shading normal, the distance, the albedo and the object id color of the first hit 
+    /// of a sample to the Pixel at the given position. An infinite distance marks a sample which 
+    /// hit nothing. Does nothing if the arbitrary output variables are not recorded. <br/>
+    /// Returns a CustomImageError if x or y are out of bounds. 
+    pub fn add_aov_sample(&mut self, x: usize, y: usize, normal: &[f32], depth: f32, albedo: &[f32], 
+                          id_color: &[f32]) -> Result<(), CustomImageError> {
+        if x >= self.width as usize || y >= self.height as usize {
+            return Err(CustomImageError {error: 
+            format!("{x} or {y} out of bounds for width {} or height {}!", self.width, self.height)});
+        }
+        if let Some(aovs) = &mut self.aovs {
+            let index = y * self.width as usize + x;
+            for (sum, value) in aovs.normals[index * 3..index * 3 + 3].iter_mut().zip(normal) {
+                *sum += value;
+            }
+            if depth.is_finite() {
+                aovs.depths[index] += depth;
+                aovs.depth_hits[index] += 1;
+            }
+            for (sum, value) in aovs.albedos[index * 3..index * 3 + 3].iter_mut().zip(albedo) {
+                *sum += value;
+            }
+            for (sum, value) in aovs.id_colors[index * 3..index * 3 + 3].iter_mut().zip(id_color) {
+                *sum += value;
+            }
+            aovs.samples[index] += 1;
+        }
+        Ok(())
+    }
+    
+    /// Returns the average normals of the pixels as a float image, x, y, z in range \[-1; 1] as 
+    /// r, g, b, as external denoisers expect them. Pixels which saw nothing are zero. None if the 
+    /// arbitrary output variables are not recorded. 
+    pub fn get_normal_image(&self) -> Option<DynamicImage> {
+        let aovs = self.aovs.as_ref()?;
+        let averages = aovs.normals.chunks_exact(3).flat_map(|sum| {
+            let length = (sum[0] * sum[0] + sum[1] * sum[1] + sum[2] * sum[2]).sqrt();
+            let scale = if length > 0.0 { 1.0 / length } else { 0.0 };
+            [sum[0] * scale, sum[1] * scale, sum[2] * scale]
+        }).collect();
+        Some(Rgb32FImage::from_raw(self.width, self.height, averages).unwrap().into())
+    }
+    
+    /// Returns the average distance from the camera to what the pixels see first as a float 
+    /// image, the same value in r, g and b. Pixels which saw nothing are infinitely far away. 
+    /// None if the arbitrary output variables are not recorded. 
+    pub fn get_depth_image(&self) -> Option<DynamicImage> {
+        let aovs = self.aovs.as_ref()?;
+        let averages = aovs.depths.iter().zip(&aovs.depth_hits).flat_map(|(&sum, &hits)| {
+            let depth = if hits > 0 { sum / hits as f32 } else { f32::INFINITY };
+            [depth; 3]
+        }).collect();
+        Some(Rgb32FImage::from_raw(self.width, self.height, averages).unwrap().into())
+    }
+    
+    /// Adds the r, g, b values each light source contributed to a sample to the light passes of the 
+    /// Pixel at the given position, see [get_light_pass_images](CustomImage::get_light_pass_images). 
+    /// Must be called along with [add_aov_sample](CustomImage::add_aov_sample), which counts the 
+    /// sample. Does nothing if the arbitrary output variables are not recorded or the number of 
+    /// lights differs from the previous samples. <br/>
+    /// Returns a CustomImageError if x or y are out of bounds. 
+    pub fn add_light_pass_sample(&mut self, x: usize, y: usize, light_colors: &[f32]) -> Result<(), CustomImageError> {
+        if x >= self.width as usize || y >= self.height as usize {
+            return Err(CustomImageError {error: 
+            format!("{x} or {y} out of bounds for width {} or height {}!", self.width, self.height)});
+        }
+        let nbr_of_pixels = (self.width * self.height) as usize;
+        if let Some(aovs) = &mut self.aovs {
+            if aovs.light_colors.is_empty() && !light_colors.is_empty() {
+                aovs.nbr_of_light_passes = light_colors.len() / 3;
+                aovs.light_colors = vec![0.0; nbr_of_pixels * light_colors.len()];
+            }
+            let stride = aovs.nbr_of_light_passes * 3;
+            if light_colors.len() == stride {
+                let index = (y * self.width as usize + x) * stride;
+                for (sum, value) in aovs.light_colors[index..index + stride].iter_mut().zip(light_colors) {
+                    *sum += value;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Returns the average reflectance of what the pixels see first as a linear RGB float image, 
+    /// the albedo external denoisers expect along with the normals. Samples which saw nothing 
+    /// count as black. None if the arbitrary output variables are not recorded. 
+    pub fn get_albedo_image(&self) -> Option<DynamicImage> {
+        let aovs = self.aovs.as_ref()?;
+        Some(self.average_aov_image(&aovs.albedos, &aovs.samples))
+    }
+    
+    /// Returns the object id colors of what the pixels see first as a float image, see 
+    /// [shader::FirstHit::id_color](crate::shader::FirstHit::id_color). Where several objects 
+    /// share a pixel, their colors are mixed by their coverage, and samples which saw nothing 
+    /// count as black. None if the arbitrary output variables are not recorded. 
+    pub fn get_id_image(&self) -> Option<DynamicImage> {
+        let aovs = self.aovs.as_ref()?;
+        Some(self.average_aov_image(&aovs.id_colors, &aovs.samples))
+    }
+    
+    /// Returns the light passes as linear RGB float images, one per light source in the order of 
+    /// the lights of the uniforms, see 
+    /// [spectral_ray_generation_shader](crate::shader::spectral_ray_generation_shader). Each holds 
+    /// the light of its light source sampled at the hits of the paths, so that the lighting can be 
+    /// rebalanced by scaling and summing them. Empty if no light passes are recorded. 
+    pub fn get_light_pass_images(&self) -> Vec<DynamicImage> {
+        let Some(aovs) = &self.aovs else {
+            return Vec::new();
+        };
+        let stride = aovs.nbr_of_light_passes * 3;
+        (0..aovs.nbr_of_light_passes).map(|light| {
+            let sums: Vec<f32> = aovs.light_colors.chunks_exact(stride)
+                .flat_map(|pixel| pixel[light * 3..light * 3 + 3].to_vec())
+                .collect();
+            self.average_aov_image(&sums, &aovs.samples)
+        }).collect()
+    }
+    
+    /// Divides sums of r, g, b per pixel by the number of samples of each pixel. 
+    fn average_aov_image(&self, sums: &[f32], samples: &[u32]) -> DynamicImage {
+        let averages = sums.chunks_exact(3).zip(samples).flat_map(|(sum, &samples)| {
+            let scale = if samples > 0 { 1.0 / samples as f32 } else { 0.0 };
+            [sum[0] * scale, sum[1] * scale, sum[2] * scale]
+        }).collect();
+        Rgb32FImage::from_raw(self.width, self.height, averages).unwrap().into()
+    }
+}
+
+/// Encodes a normal image of [CustomImage::get_normal_image] as an 8 bit image for viewing and 
+/// compositing, mapping each component from \[-1; 1] to \[0; 255]. 
+pub fn encode_normal_image(normals: &DynamicImage) -> DynamicImage {
+    let mut encoded = normals.to_rgb32f();
+    encoded.pixels_mut().flat_map(|pixel| pixel.0.iter_mut()).for_each(|value| *value = *value * 0.5 + 0.5);
+    DynamicImage::from(encoded).to_rgb8().into()
+}
+
+/// Encodes a linear color image such as [CustomImage::get_albedo_image] as an 8 bit image for 
+/// viewing and compositing, with the given transfer function. 
+pub fn encode_color_image(colors: &DynamicImage, transfer_function: TransferFunction) -> DynamicImage {
+    let mut encoded = colors.to_rgb32f();
+    encoded.pixels_mut().flat_map(|pixel| pixel.0.iter_mut())
+        .for_each(|value| *value = transfer_function.encode(value.clamp(0.0, 1.0)));
+    DynamicImage::from(encoded).to_rgb8().into()
+}
+
+/// Encodes a depth image of [CustomImage::get_depth_image] as an 8 bit image for viewing and 
+/// compositing: white at the near distance and closer, black at the far distance and beyond. 
+pub fn encode_depth_image(depths: &DynamicImage, near: f32, far: f32) -> DynamicImage {
+    let mut encoded = depths.to_rgb32f();
+    let range = (far - near).max(f32::MIN_POSITIVE);
+    encoded.pixels_mut().flat_map(|pixel| pixel.0.iter_mut())
+        .for_each(|value| *value = 1.0 - ((*value - near) / range).clamp(0.0, 1.0));
+    DynamicImage::from(encoded).to_rgb8().into()
+}
+
+impl CustomImage {
+    /// Brings every pixel into the sRGB gamut via [gamut::compress] instead of leaving the out of 
+    /// gamut colors to be hard clipped during conversion. The colors are compressed without the 
+    /// premultiplied alpha. 
+    pub fn compress_gamut(&mut self) {
+        for pixel in self.data.chunks_exact_mut(NBR_DATA_POINTS_PER_PIXEL) {
+            let alpha = pixel[3];
+            if alpha <= 0.0 {
+                continue;
+            }
+            let (r, g, b) = gamut::compress((pixel[0] / alpha, pixel[1] / alpha, pixel[2] / alpha));
+            pixel[0] = r * alpha;
+            pixel[1] = g * alpha;
+            pixel[2] = b * alpha;
+        }
+    }
+    
+    /// Removes the buffers of the arbitrary output variables, leaving only the colors. 
+    pub fn without_aovs(mut self) -> CustomImage {
+        self.aovs = None;
+        self
+    }
+    
+    /// Returns the image resampled to the given size, keeping the settings of the conversion. 
+    /// Where the image is larger, every pixel of the result combines the samples of the pixels it 
+    /// covers, so that a supersampled render is averaged down with all of its samples. Where it is 
+    /// smaller, every pixel of the result takes the pixel it lies on, so that a low resolution 
+    /// preview is enlarged without blurring. The arbitrary output variables are resampled alike. 
+    pub fn resized(&self, width: u32, height: u32) -> CustomImage {
+        if (width, height) == (self.width, self.height) {
+            return self.clone();
+        }
+        //the source pixels covered by a pixel of the result along one axis, at least one
+        let covered = |position: u32, size: u32, source_size: u32| {
+            let start = (position as u64 * source_size as u64 / size as u64) as usize;
+            let end = ((position as u64 + 1) * source_size as u64).div_ceil(size as u64) as usize;
+            start..end.max(start + 1).min(source_size as usize)
+        };
+        let nbr_of_pixels = (width * height) as usize;
+        let mut data = vec![0.0; nbr_of_pixels * NBR_DATA_POINTS_PER_PIXEL];
+        let mut sample_counts = vec![0; nbr_of_pixels];
+        let mut aovs = self.aovs.as_ref().map(|aovs| AovBuffers {
+            normals: vec![0.0; nbr_of_pixels * 3],
+            depths: vec![0.0; nbr_of_pixels],
+            depth_hits: vec![0; nbr_of_pixels],
+            albedos: vec![0.0; nbr_of_pixels * 3],
+            id_colors: vec![0.0; nbr_of_pixels * 3],
+            samples: vec![0; nbr_of_pixels],
+            light_colors: vec![0.0; if aovs.light_colors.is_empty() { 0 } else { nbr_of_pixels * aovs.nbr_of_light_passes * 3 }],
+            nbr_of_light_passes: aovs.nbr_of_light_passes,
+        });
+        
+        for y in 0..height {
+            for x in 0..width {
+                let index = (y * width + x) as usize;
+                let pixel = &mut data[index * NBR_DATA_POINTS_PER_PIXEL..(index + 1) * NBR_DATA_POINTS_PER_PIXEL];
+                for source_y in covered(y, height, self.height) {
+                    for source_x in covered(x, width, self.width) {
+                        let source = source_y * self.width as usize + source_x;
+                        //the colors are averages, weighted by their samples they combine into one
+                        let count = self.sample_counts[source];
+                        let source_pixel = &self.data[source * NBR_DATA_POINTS_PER_PIXEL..(source + 1) * NBR_DATA_POINTS_PER_PIXEL];
+                        for (value, source_value) in pixel.iter_mut().zip(source_pixel) {
+                            *value += source_value * count as f32;
+                        }
+                        sample_counts[index] += count;
+                        if let (Some(aovs), Some(source_aovs)) = (&mut aovs, &self.aovs) {
+                            aovs.add_sums_of(index, source_aovs, source);
+                        }
+                    }
+                }
+                if sample_counts[index] > 0 {
+                    pixel.iter_mut().for_each(|value| *value /= sample_counts[index] as f32);
+                }
+            }
+        }
+        
+        CustomImage { width, height, data, sample_counts, firefly_filter: self.firefly_filter, 
+                      transfer_function: self.transfer_function, tone_mapper: self.tone_mapper, 
+                      exposure: self.exposure, bloom: self.bloom, 
+                      post_effects: self.post_effects.clone(), aovs }
+    }
+    
+    /// Returns the colors and the alpha as a float image, for OpenEXR. The colors stay 
+    /// premultiplied by the alpha, as OpenEXR expects them. 
+    pub fn get_color_image(&self) -> DynamicImage {
+        Rgba32FImage::from_raw(self.width, self.height, self.data.clone()).unwrap().into()
+    }
+    
+    /// Converts the image into 16 bit RGBA, with the colors no longer premultiplied by the alpha. 
+    /// The colors are encoded like in the 8 bit conversion, but with triangular dither noise of 
+    /// one step added before quantizing them, so that smooth gradients of the float colors do not band. Pure 
+    /// black and white are kept exact. 
+    pub fn to_dithered_rgba16(&self) -> DynamicImage {
+        let data = self.display_colors().into_iter().enumerate().flat_map(|(i, color)| {
+            let (x, y) = (i as u32 % self.width, i as u32 / self.width);
+            let [r, g, b] = [0, 1, 2].map(|channel| {
+                let linear = color[channel];
+                if linear == 0.0 || linear == 1.0 {
+                    return (linear * u16::MAX as f32) as u16;
+                }
+                let value = self.transfer_function.encode(linear) * u16::MAX as f32;
+                //the difference of two uniform values is triangular noise in range (-1; 1)
+                let (first, second, _) = sampler::random_pcg3d(x, y, channel as u32);
+                (value + first - second).round().clamp(0.0, u16::MAX as f32) as u16
+            });
+            [r, g, b, (color[3] * u16::MAX as f32).round() as u16]
+        }).collect::<Vec<u16>>();
+        ImageBuffer::<Rgba<u16>, Vec<u16>>::from_raw(self.width, self.height, data).unwrap().into()
+    }
+    
+    /// Writes the colors as a Radiance RGBE file (.hdr), a lightweight HDR format which keeps the 
+    /// linear colors above 1 that the 8 bit formats clip, at a quarter of the size of the floats. 
+    /// The colors are written without the premultiplied alpha, in uncompressed scanlines from the 
+    /// top. 
+    pub fn save_radiance_hdr(&self, path: &Path) -> Result<(), CustomImageError> {
+        let header = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", self.height, self.width);
+        let mut bytes = header.into_bytes();
+        bytes.reserve(self.data.len());
+        for pixel in self.data.chunks_exact(NBR_DATA_POINTS_PER_PIXEL) {
+            bytes.extend(float_to_rgbe(unmultiplied_color(pixel)));
+        }
+        std::fs::write(path, bytes)
+            .map_err(|e| CustomImageError{error: format!("Error writing {}: {e}", path.display())})
+    }
+}
+
+/// Returns the r, g, b values of a pixel of the data no longer premultiplied by its alpha. 
+fn unmultiplied_color(pixel: &[f32]) -> [f32; 3] {
+    let alpha = pixel[3];
+    let unmultiply = if alpha > 0.0 { 1.0 / alpha } else { 0.0 };
+    [pixel[0] * unmultiply, pixel[1] * unmultiply, pixel[2] * unmultiply]
+}
+
+/// Encodes a linear color into the shared exponent format of Radiance files: three 8 bit 
+/// mantissas relative to the brightest channel and its exponent, offset by 128. Negative values 
+/// are written as 0. 
+fn float_to_rgbe(color: [f32; 3]) -> [u8; 4] {
+    let color = color.map(|c| c.max(0.0));
+    let brightest = color[0].max(color[1]).max(color[2]);
+    if brightest < RGBE_DARKEST_COLOR {
+        return [0; 4];
+    }
+    //the brightest channel is brought into range [128; 256), its exponent is stored 
+    let exponent = brightest.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2f32.powi(exponent);
+    let [r, g, b] = color.map(|c| (c * scale).min(255.0) as u8);
+    [r, g, b, (exponent + 128) as u8]
 }
 
 impl From<CustomImage> for DynamicImage {
+    /// Converts the image into 8 bit RGBA, see the conversion of a reference to the image. 
     fn from(value: CustomImage) -> Self {
-        let data_as_bytes = value.data.into_iter().map(|mut float| {
+        (&value).into()
+    }
+}
+
+impl From<&CustomImage> for DynamicImage {
+    /// Converts the image into 8 bit RGBA, with the colors bloomed, no longer premultiplied by the 
+    /// alpha, exposed, tone mapped, run through the post effects and encoded by the transfer 
+    /// function of the image. 
+    fn from(value: &CustomImage) -> Self {
+        let data_as_bytes = value.display_colors().into_iter().flat_map(|[r, g, b, a]| {
+            let [r, g, b] = [r, g, b].map(|channel| value.transfer_function.encode(channel));
+            [r, g, b, a]
+        }).map(|mut float| {
             float = float.clamp(0.0, 1.0);
             float *= 255.0;
-            float as u8
+            float.round() as u8
         }).collect::<Vec<u8>>();
         RgbaImage::from_raw(value.width, value.height, data_as_bytes).unwrap().into()
     }
@@ -116,3 +802,58 @@ pub struct Pixel {
     pub b: f32,
     pub a: f32,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Decodes a shared exponent color like Radiance does, with the mantissas centered in their step.
+    fn rgbe_to_float(rgbe: [u8; 4]) -> [f32; 3] {
+        if rgbe[3] == 0 {
+            return [0.0; 3];
+        }
+        let scale = 2f32.powi(rgbe[3] as i32 - 128 - 8);
+        [rgbe[0], rgbe[1], rgbe[2]].map(|mantissa| (mantissa as f32 + 0.5) * scale)
+    }
+
+    #[test]
+    fn test_float_to_rgbe() {
+        assert_eq!(float_to_rgbe([1.0, 0.5, 0.0]), [128, 64, 0, 129]);
+        assert_eq!(float_to_rgbe([0.0; 3]), [0; 4]);
+        assert_eq!(float_to_rgbe([-1.0, -2.0, -3.0]), [0; 4]);
+        assert_eq!(float_to_rgbe([-1.0, 2.0, 0.0]), [0, 128, 0, 130]);
+        assert_eq!(float_to_rgbe([1e-33, 0.0, 0.0]), [0; 4]);
+
+        for color in [[1.0, 1.0, 1.0], [0.999, 0.3, 0.01], [3.7, 12.5, 0.2], [1000.0, 1.0, 0.5],
+                      [1e-5, 3e-6, 7e-6], [0.1, 0.2, 0.3], [65504.0, 65504.0, 65504.0]] {
+            let rgbe = float_to_rgbe(color);
+            let brightest = color[0].max(color[1]).max(color[2]);
+            assert!(rgbe[0].max(rgbe[1]).max(rgbe[2]) >= 128, "The mantissas of {color:?} are not normalized: {rgbe:?}!");
+            for (expected, decoded) in color.iter().zip(rgbe_to_float(rgbe)) {
+                //every channel is quantized in steps of the brightest channel
+                assert!((expected - decoded).abs() <= brightest / 128.0,
+                        "{color:?} was encoded as {rgbe:?}, which decodes to {decoded} instead of {expected}!");
+            }
+        }
+    }
+
+    #[test]
+    fn test_radiance_hdr_round_trip() {
+        let data = vec![2.0, 1.0, 0.5, 1.0,    0.0, 0.0, 0.0, 1.0,
+                        0.25, 0.5, 0.75, 0.5,  1.0, 1.0, 1.0, 0.0];
+        let image = CustomImage::new_from_data(2, 2, data.clone()).unwrap();
+        let path = std::env::temp_dir().join(format!("test_radiance_hdr_round_trip_{}.hdr", std::process::id()));
+        image.save_radiance_hdr(&path).unwrap();
+        let read = image::open(&path).unwrap().to_rgb32f();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.dimensions(), (2, 2));
+        for (pixel, read) in data.chunks_exact(NBR_DATA_POINTS_PER_PIXEL).zip(read.pixels()) {
+            let expected = unmultiplied_color(pixel);
+            let brightest = expected[0].max(expected[1]).max(expected[2]);
+            for (expected, read) in expected.iter().zip(read.0) {
+                assert!((expected - read).abs() <= brightest / 128.0, "{expected} was read back as {read}!");
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use image::DynamicImage;
+use rayon::prelude::*;
+use crate::custom_image::CustomImage;
+use crate::gamut;
+use crate::loader::{LoadError, LoadProgress};
+
+/// The number of pixels in each direction the neighbors of a pixel are averaged over.
+const DENOISE_RADIUS: i32 = 6;
+/// The standard deviation in pixels of the falloff of the weights with the distance.
+const SPATIAL_SIGMA: f32 = 3.0;
+/// The power the cosine between two normals is raised to, the higher the sharper the edges
+/// between differently oriented surfaces stay.
+const NORMAL_POWER: i32 = 64;
+/// The difference between two albedos at which their weight has fallen to about 60%.
+const ALBEDO_SIGMA: f32 = 0.1;
+/// The difference between two depths relative to the depth of the pixel at which their weight has
+/// fallen to about 60%.
+const DEPTH_SIGMA: f32 = 0.05;
+/// The difference between two smoothed luminances relative to their mean at which their weight
+/// has fallen to about 60%. Keeps shadow edges, which the other guides do not see.
+const LUMINANCE_SIGMA: f32 = 0.5;
+/// Albedos are raised to at least this before the colors are divided by them, so that black
+/// surfaces do not blow up.
+const ALBEDO_MIN: f32 = 0.01;
+
+/// Removes the noise of a render that is still converging with an edge aware filter: every pixel
+/// becomes a weighted average of its neighbors, and neighbors which differ in the normal, the
+/// albedo or the depth of what they see, or whose smoothed brightness differs a lot, get little
+/// weight, so that edges and textures stay sharp while flat areas are smoothed. Before averaging,
+/// the colors are divided by the albedo, so that only the lighting is smoothed and the texture is
+/// multiplied back in sharply. <br/>
+/// The guides must be float images of the size of the image, as returned by
+/// [get_normal_image](CustomImage::get_normal_image), [get_albedo_image](CustomImage::get_albedo_image)
+/// and [get_depth_image](CustomImage::get_depth_image). The sample counts are kept. The progress
+/// is reported per row, and the filter stops early with [LoadError::Cancelled] if it is cancelled.
+pub fn denoise(image: &CustomImage, normals: &DynamicImage, albedos: &DynamicImage, depths: &DynamicImage,
+               progress: &LoadProgress) -> Result<CustomImage, LoadError> {
+    let (width, height) = (image.get_width() as usize, image.get_height() as usize);
+    let [normals, albedos, depths] = [normals, albedos, depths].map(|guide| guide.to_rgb32f());
+    if [&normals, &albedos, &depths].iter().any(|guide| guide.dimensions() != (image.get_width(), image.get_height())) {
+        return Err(LoadError::Failed("The normals, albedos and depths do not match the size of the image!".to_string()));
+    }
+    let (normals, albedos, depths) = (normals.as_raw(), albedos.as_raw(), depths.as_raw());
+    let albedo = |i: usize| [0, 1, 2].map(|c| albedos[i * 3 + c].max(ALBEDO_MIN));
+
+    //the texture is taken out of the colors, only the light arriving at the surfaces is averaged
+    let irradiance = image.get_data().chunks_exact(4).enumerate().map(|(i, pixel)| {
+        let albedo = albedo(i);
+        [pixel[0] / albedo[0], pixel[1] / albedo[1], pixel[2] / albedo[2], pixel[3]]
+    }).collect::<Vec<[f32; 4]>>();
+    let luminances = smoothed_luminances(&irradiance, width, height);
+
+    let spatial_weights = (-DENOISE_RADIUS..=DENOISE_RADIUS).flat_map(|dy| (-DENOISE_RADIUS..=DENOISE_RADIUS).map(move |dx| {
+        (-((dx * dx + dy * dy) as f32) / (2.0 * SPATIAL_SIGMA * SPATIAL_SIGMA)).exp()
+    })).collect::<Vec<f32>>();
+    let rows_done = AtomicUsize::new(0);
+
+    let rows = (0..height).into_par_iter().map(|y| {
+        if progress.is_cancelled() {
+            return Vec::new();
+        }
+        let mut row = Vec::with_capacity(width * 4);
+        for x in 0..width {
+            let p = y * width + x;
+            let normal_p = &normals[p * 3..p * 3 + 3];
+            let albedo_p = albedo(p);
+            let depth_p = depths[p * 3];
+            let mut sum = [0.0; 4];
+            let mut weight_sum = 0.0;
+            for dy in -DENOISE_RADIUS..=DENOISE_RADIUS {
+                let qy = y as i32 + dy;
+                if qy < 0 || qy >= height as i32 {
+                    continue;
+                }
+                for dx in -DENOISE_RADIUS..=DENOISE_RADIUS {
+                    let qx = x as i32 + dx;
+                    if qx < 0 || qx >= width as i32 {
+                        continue;
+                    }
+                    let q = qy as usize * width + qx as usize;
+                    let spatial = spatial_weights[((dy + DENOISE_RADIUS) * (2 * DENOISE_RADIUS + 1) + dx + DENOISE_RADIUS) as usize];
+                    let weight = spatial
+                        * normal_weight(normal_p, &normals[q * 3..q * 3 + 3])
+                        * albedo_weight(&albedo_p, &albedo(q))
+                        * depth_weight(depth_p, depths[q * 3])
+                        * luminance_weight(luminances[p], luminances[q]);
+                    if weight <= 0.0 {
+                        continue;
+                    }
+                    (0..4).for_each(|c| sum[c] += irradiance[q][c] * weight);
+                    weight_sum += weight;
+                }
+            }
+            //the pixel itself always has a weight of 1
+            let average = sum.map(|s| s / weight_sum);
+            row.extend([average[0] * albedo_p[0], average[1] * albedo_p[1], average[2] * albedo_p[2], average[3]]);
+        }
+        let done = rows_done.fetch_add(1, Ordering::Relaxed) + 1;
+        progress.set(done as f32 / height as f32);
+        row
+    }).collect::<Vec<Vec<f32>>>();
+
+    if progress.is_cancelled() {
+        return Err(LoadError::Cancelled);
+    }
+    CustomImage::new_from_samples(image.get_width(), image.get_height(), rows.concat(), image.get_sample_counts().to_vec())
+        .map_err(|e| LoadError::Failed(e.error))
+}
+
+/// Returns the luminance of every pixel averaged with its direct neighbors, so that the noise of
+/// single pixels does not decide the weights.
+fn smoothed_luminances(colors: &[[f32; 4]], width: usize, height: usize) -> Vec<f32> {
+    let luminances = colors.iter().map(|c| gamut::luminance((c[0], c[1], c[2]))).collect::<Vec<f32>>();
+    (0..width * height).map(|i| {
+        let (x, y) = (i % width, i / width);
+        let (mut sum, mut count) = (0.0, 0);
+        for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                sum += luminances[ny * width + nx];
+                count += 1;
+            }
+        }
+        sum / count as f32
+    }).collect()
+}
+
+/// The weight of a neighbor by how much its normal deviates. Pixels which saw nothing have no
+/// normal, they only match each other.
+fn normal_weight(normal_p: &[f32], normal_q: &[f32]) -> f32 {
+    let length_p = normal_p.iter().map(|n| n * n).sum::<f32>();
+    let length_q = normal_q.iter().map(|n| n * n).sum::<f32>();
+    match (length_p > 0.0, length_q > 0.0) {
+        (false, false) => 1.0,
+        (true, true) => {
+            let cosine = normal_p.iter().zip(normal_q).map(|(a, b)| a * b).sum::<f32>();
+            cosine.max(0.0).powi(NORMAL_POWER)
+        }
+        _ => 0.0,
+    }
+}
+
+/// The weight of a neighbor by how much its albedo differs.
+fn albedo_weight(albedo_p: &[f32; 3], albedo_q: &[f32; 3]) -> f32 {
+    let distance_squared = albedo_p.iter().zip(albedo_q).map(|(a, b)| (a - b) * (a - b)).sum::<f32>();
+    (-distance_squared / (2.0 * ALBEDO_SIGMA * ALBEDO_SIGMA)).exp()
+}
+
+/// The weight of a neighbor by how much further away or closer it is, relative to the depth.
+/// Pixels which saw nothing are infinitely far away, they only match each other.
+fn depth_weight(depth_p: f32, depth_q: f32) -> f32 {
+    match (depth_p.is_finite(), depth_q.is_finite()) {
+        (false, false) => 1.0,
+        (true, true) => {
+            let difference = (depth_p - depth_q) / (DEPTH_SIGMA * depth_p.max(f32::MIN_POSITIVE));
+            (-difference * difference / 2.0).exp()
+        }
+        _ => 0.0,
+    }
+}
+
+/// The weight of a neighbor by how much its smoothed luminance differs, relative to their mean.
+fn luminance_weight(luminance_p: f32, luminance_q: f32) -> f32 {
+    let mean = (luminance_p + luminance_q) / 2.0;
+    if mean <= 0.0 {
+        return 1.0;
+    }
+    let difference = (luminance_p - luminance_q) / (LUMINANCE_SIGMA * mean);
+    (-difference * difference / 2.0).exp()
+}
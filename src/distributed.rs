@@ -0,0 +1,454 @@
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::io::ErrorKind;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use log::{info, warn};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::{scene_file, shader, App};
+use crate::scene::{self, RenderBucket, TileAovs};
+use crate::shader::RaytracingUniforms;
+
+/// The port a worker listens on if none is given.
+pub const DEFAULT_WORKER_PORT: u16 = 7878;
+/// The address a worker listens on if none is given, only reachable from the same machine.
+pub const DEFAULT_WORKER_BIND_ADDRESS: &str = "127.0.0.1";
+/// How long the master tries to reach a worker before rendering without it.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the master waits for the next message of a worker before giving up on it. This
+/// includes the time the worker takes to prepare the scene and to render a single tile.
+const WORKER_TIMEOUT: Duration = Duration::from_secs(300);
+/// How often the master checks whether the render was paused or cancelled while it waits for a
+/// worker.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// The number of batches of tiles a worker is given ahead, so that it already has the next batch
+/// when it finishes one. A batch holds as many tiles as the worker has threads.
+const BATCHES_IN_FLIGHT: usize = 2;
+/// How long a worker waits for the scene of a master which just connected, so that a connection
+/// which never sends anything does not keep the worker from serving other masters.
+const SCENE_TIMEOUT: Duration = Duration::from_secs(30);
+/// The largest message accepted, guards against allocating absurd amounts of memory when talking
+/// to something that is not a worker. The buffer of a message only grows as its bytes arrive.
+const MAX_MESSAGE_LENGTH: u32 = 1 << 28;
+
+/// The messages the master sends to a worker.
+#[derive(Serialize, Deserialize)]
+enum MasterMessage {
+    /// The scene to be rendered as the content of a scene file, sent once per connection together
+    /// with the token the worker was started with.
+    Scene { token: String, scene: String },
+    /// Renders the tiles once with the random values of the given frame.
+    Tiles { frame: u32, tiles: Vec<RenderBucket> },
+}
+
+/// The messages a worker sends back to the master.
+#[derive(Serialize, Deserialize)]
+enum WorkerMessage {
+    /// The scene was prepared, the worker renders with the given number of threads.
+    Ready { threads: usize },
+    /// A rendered tile, the RGB values row by row, the alpha value of each pixel and the AOVs of 
+    /// the pixels.
+    Tile { bucket: RenderBucket, colors: Vec<f32>, alphas: Vec<f32>, aovs: TileAovs },
+    /// The worker could not do what it was asked to.
+    Failed(String),
+}
+
+/// Everything the render thread needs to connect to the workers. Connecting happens on the render
+/// thread, so that the UI does not freeze while the workers prepare the scene.
+pub struct WorkerSetup {
+    addresses: Vec<String>,
+    token: String,
+    scene: String,
+}
+
+impl WorkerSetup {
+    /// Takes the comma separated addresses of the workers, the token they were started with and the
+    /// content of the scene file to be rendered. Returns None if there are no addresses.
+    pub fn new(addresses: &str, token: &str, scene: String) -> Option<Self> {
+        let addresses: Vec<String> = addresses.split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!addresses.is_empty()).then_some(WorkerSetup {addresses, token: token.to_string(), scene})
+    }
+
+    /// Connects to all workers and gives them the scene. Workers which can not be reached are
+    /// left out with a warning.
+    pub fn connect(self) -> Vec<RemoteWorker> {
+        self.addresses.iter().filter_map(|address| {
+            match RemoteWorker::connect(address, &self.token, &self.scene) {
+                Ok(worker) => {
+                    info!("Rendering with worker {address} and its {} threads", worker.threads);
+                    Some(worker)
+                }
+                Err(e) => {
+                    warn!("Error connecting to worker {address}, rendering without it: {}", e.error);
+                    None
+                }
+            }
+        }).collect()
+    }
+}
+
+/// A connection to another instance running in worker mode, see [run_worker], which has prepared
+/// the scene and renders tiles on request.
+pub struct RemoteWorker {
+    address: String,
+    stream: TcpStream,
+    threads: usize,
+}
+
+impl RemoteWorker {
+    fn connect(address: &str, token: &str, scene: &str) -> Result<Self, DistributedError> {
+        let socket_address = address.to_socket_addrs()
+            .map_err(|e| DistributedError {error: format!("Invalid address: {e}")})?
+            .next()
+            .ok_or_else(|| DistributedError {error: "The address could not be resolved!".to_string()})?;
+        let mut stream = TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)
+            .map_err(|e| DistributedError {error: format!("Could not connect: {e}")})?;
+        stream.set_read_timeout(Some(WORKER_TIMEOUT))
+            .and_then(|_| stream.set_nodelay(true))
+            .map_err(|e| DistributedError {error: format!("Could not configure the connection: {e}")})?;
+
+        send(&mut stream, &MasterMessage::Scene {token: token.to_string(), scene: scene.to_string()})?;
+        match receive(&mut stream)? {
+            WorkerMessage::Ready {threads} => Ok(RemoteWorker {address: address.to_string(), stream, threads: threads.max(1)}),
+            WorkerMessage::Failed(error) => Err(DistributedError {error}),
+            WorkerMessage::Tile {..} => Err(DistributedError {error: "Received a tile before the scene was prepared!".to_string()}),
+        }
+    }
+
+    pub fn get_address(&self) -> &str {
+        &self.address
+    }
+
+    /// The number of threads the worker renders with, tiles are handed out in proportion to it.
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Has the worker render the tiles for the given frame. Every tile is handed to the callback
+    /// with its colors, alphas and AOVs as soon as it arrives, in whatever order the worker
+    /// finishes them. On an error, the tiles not handed to the callback yet are missing from the
+    /// frame. <br>
+    /// The tiles are handed out in batches. While the render is paused, no further batch is sent
+    /// and the call waits once the batches sent are done. Once the render is cancelled, the
+    /// connection is shut down without waiting for the tiles in progress, the worker can not be
+    /// used any further.
+    pub fn render_tiles(&mut self, frame: u32, tiles: &[RenderBucket], paused: &AtomicBool, cancelled: &AtomicBool,
+                        mut on_tile: impl FnMut(RenderBucket, Vec<f32>, Vec<f32>, TileAovs)) -> Result<(), DistributedError> {
+        let mut unsent = tiles.chunks(self.threads);
+        let mut remaining = Vec::new();
+        loop {
+            //keeps the worker busy as long as the render is neither paused nor cancelled
+            while remaining.len() <= (BATCHES_IN_FLIGHT - 1) * self.threads && !paused.load(Ordering::Relaxed) 
+                && !cancelled.load(Ordering::Relaxed) {
+                let Some(batch) = unsent.next() else {
+                    break;
+                };
+                send(&mut self.stream, &MasterMessage::Tiles {frame, tiles: batch.to_vec()})?;
+                remaining.extend_from_slice(batch);
+            }
+            if remaining.is_empty() {
+                if unsent.len() == 0 || cancelled.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                thread::sleep(POLL_INTERVAL);   //paused
+                continue;
+            }
+            
+            if !self.wait_for_message(cancelled)? {
+                let _ = self.stream.shutdown(Shutdown::Both);
+                return Ok(());
+            }
+            match receive(&mut self.stream)? {
+                WorkerMessage::Tile {bucket, colors, alphas, aovs} => {
+                    let pixels = (bucket.width * bucket.height) as usize;
+                    if !remaining.contains(&bucket) || colors.len() != pixels * 3 || alphas.len() != pixels 
+                        || aovs.len() != pixels || aovs.normals.len() != pixels * 3 
+                        || aovs.albedos.len() != pixels * 3 || aovs.id_colors.len() != pixels * 3 
+                        || aovs.light_colors.len() % (pixels * 3) != 0 {
+                        return Err(DistributedError {error: "Received a tile which was not asked for!".to_string()});
+                    }
+                    remaining.retain(|b| *b != bucket);
+                    on_tile(bucket, colors, alphas, aovs);
+                }
+                WorkerMessage::Failed(error) => return Err(DistributedError {error}),
+                WorkerMessage::Ready {..} => return Err(DistributedError {error: "Received an unexpected message!".to_string()}),
+            }
+        }
+    }
+
+    /// Waits until the worker begins to send its next message, for at most [WORKER_TIMEOUT].
+    /// Returns false as soon as the render is cancelled instead.
+    fn wait_for_message(&mut self, cancelled: &AtomicBool) -> Result<bool, DistributedError> {
+        let configure_error = |e| DistributedError {error: format!("Could not configure the connection: {e}")};
+        self.stream.set_read_timeout(Some(POLL_INTERVAL)).map_err(configure_error)?;
+        let mut waited = Duration::ZERO;
+        let result = loop {
+            if cancelled.load(Ordering::Relaxed) {
+                break Ok(false);
+            }
+            match self.stream.peek(&mut [0u8]) {
+                Ok(0) => break Err(DistributedError {error: "The worker closed the connection!".to_string()}),
+                Ok(_) => break Ok(true),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    waited += POLL_INTERVAL;
+                    if waited >= WORKER_TIMEOUT {
+                        break Err(DistributedError {error: "The worker did not answer in time!".to_string()});
+                    }
+                }
+                Err(e) => break Err(DistributedError {error: format!("Could not receive a message: {e}")}),
+            }
+        };
+        self.stream.set_read_timeout(Some(WORKER_TIMEOUT)).map_err(configure_error)?;
+        result
+    }
+}
+
+impl Drop for RemoteWorker {
+    fn drop(&mut self) {
+        //lets the worker know the render is over, so it can wait for the next one
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Runs this instance as a worker listening on the given address and port. Masters connect one
+/// after another, send the scene and then ask for tiles, which are rendered with all cores and sent
+/// back one by one. Textures and heightfield images are loaded from the paths given in the scene,
+/// so they have to exist at the same place on the worker. <br>
+/// Masters have to send the given token along with the scene, connections with another token are
+/// closed before the scene is prepared. Only returns if the address can not be listened on or the
+/// token is empty.
+pub fn run_worker(bind_address: &str, port: u16, token: &str) -> Result<(), DistributedError> {
+    if token.is_empty() {
+        return Err(DistributedError {error: "The token of a worker must not be empty!".to_string()});
+    }
+    let listener = TcpListener::bind((bind_address, port))
+        .map_err(|e| DistributedError {error: format!("Could not listen on {bind_address}:{port}: {e}")})?;
+    info!("Worker listening on {bind_address}:{port}");
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Error accepting a connection: {e}");
+                continue;
+            }
+        };
+        let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_else(|_| "unknown".to_string());
+        info!("Master {peer} connected");
+        match serve_master(stream, token) {
+            Ok(()) => info!("Master {peer} disconnected"),
+            Err(e) => warn!("Error serving master {peer}: {}", e.error),
+        }
+    }
+    Ok(())
+}
+
+/// Prepares the scene sent by the master and renders the tiles it asks for until it disconnects.
+/// Nothing is done for a master which does not send the token of the worker with its scene.
+fn serve_master(mut stream: TcpStream, token: &str) -> Result<(), DistributedError> {
+    stream.set_nodelay(true)
+        .and_then(|_| stream.set_read_timeout(Some(SCENE_TIMEOUT)))
+        .map_err(|e| DistributedError {error: format!("Could not configure the connection: {e}")})?;
+
+    let scene = match receive(&mut stream)? {
+        MasterMessage::Scene {token: received_token, scene} => {
+            if !tokens_match(&received_token, token) {
+                send(&mut stream, &WorkerMessage::Failed("The token does not match the one of the worker!".to_string()))?;
+                return Err(DistributedError {error: "The master sent a wrong token!".to_string()});
+            }
+            scene
+        }
+        MasterMessage::Tiles {..} => return Err(DistributedError {error: "Received tiles before a scene!".to_string()}),
+    };
+    //between frames, the master may take arbitrarily long
+    stream.set_read_timeout(None)
+        .map_err(|e| DistributedError {error: format!("Could not configure the connection: {e}")})?;
+    let (mut uniforms, width, height) = match prepare_scene(&scene) {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            send(&mut stream, &WorkerMessage::Failed(e.error.clone()))?;
+            return Err(e);
+        }
+    };
+    send(&mut stream, &WorkerMessage::Ready {threads: rayon::current_num_threads()})?;
+
+    let writer = Mutex::new(stream.try_clone()
+        .map_err(|e| DistributedError {error: format!("Could not configure the connection: {e}")})?);
+    loop {
+        let (frame, tiles) = match receive(&mut stream) {
+            Ok(MasterMessage::Tiles {frame, tiles}) => (frame, tiles),
+            Ok(MasterMessage::Scene {..}) => return Err(DistributedError {error: "Received a second scene!".to_string()}),
+            Err(_) => return Ok(()),   //the master closed the connection after its render
+        };
+        if frame != uniforms.frame_id {
+            uniforms.set_frame(frame);
+        }
+
+        tiles.into_par_iter().try_for_each(|bucket| {
+            let mut aovs = TileAovs::default();
+            let (colors, alphas) = scene::render_tile(bucket, width, height, &uniforms, None, Some(&mut aovs), 
+                                                      || ControlFlow::Continue(()));
+            shader::take_ray_statistics();
+            send(&mut writer.lock().unwrap(), &WorkerMessage::Tile {bucket, colors, alphas, aovs})
+        })?;
+    }
+}
+
+/// Builds the uniforms of the scene the same way the master does, including the irradiance cache
+/// of the first frame. Returns them together with the size of the image.
+fn prepare_scene(scene: &str) -> Result<(RaytracingUniforms, u32, u32), DistributedError> {
+    let mut app = App::new(None);
+    scene_file::from_ron(scene, &mut app.ui_values, &mut app.texture_cache)
+        .map_err(|e| DistributedError {error: e.error})?;
+    app.update_all_spectrum_sample_sizes(app.ui_values.spectrum_number_of_samples);
+    if !app.check_render_legality() {
+        return Err(DistributedError {error: "The scene is in an illegal state!".to_string()});
+    }
+
+    let (width, height) = app.ui_values.render_size();
+    let mut uniforms = app.build_uniforms();
+    uniforms.set_frame(0);
+    uniforms.prepare_irradiance_cache(&shader::Dimensions {width, height});
+    Ok((uniforms, width, height))
+}
+
+/// Compares the tokens in a time independent of where they differ, so that a wrong token can not
+/// be corrected character by character by timing the answers.
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+/// Writes the message as RON, preceded by its length in bytes.
+fn send(stream: &mut TcpStream, message: &impl Serialize) -> Result<(), DistributedError> {
+    let content = ron::to_string(message)
+        .map_err(|e| DistributedError {error: format!("Could not serialize a message: {e}")})?;
+    stream.write_all(&(content.len() as u32).to_le_bytes())
+        .and_then(|_| stream.write_all(content.as_bytes()))
+        .map_err(|e| DistributedError {error: format!("Could not send a message: {e}")})
+}
+
+/// Reads a message written by [send].
+fn receive<T: DeserializeOwned>(stream: &mut TcpStream) -> Result<T, DistributedError> {
+    let mut length = [0u8; 4];
+    stream.read_exact(&mut length)
+        .map_err(|e| DistributedError {error: format!("Could not receive a message: {e}")})?;
+    let length = u32::from_le_bytes(length);
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(DistributedError {error: format!("Received a message of {length} bytes, which is too large!")});
+    }
+
+    //the buffer grows with the bytes received instead of trusting the length up front
+    let mut content = Vec::new();
+    stream.take(length as u64).read_to_end(&mut content)
+        .map_err(|e| DistributedError {error: format!("Could not receive a message: {e}")})?;
+    if content.len() != length as usize {
+        return Err(DistributedError {error: "The connection was closed in the middle of a message!".to_string()});
+    }
+    let content = String::from_utf8(content)
+        .map_err(|e| DistributedError {error: format!("Received an invalid message: {e}")})?;
+    ron::from_str(&content)
+        .map_err(|e| DistributedError {error: format!("Received an invalid message: {e}")})
+}
+
+/// An error type used when talking to workers or masters. Specific details of the error are given
+/// in the error String.
+#[derive(Debug)]
+pub struct DistributedError {
+    pub error: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wrong_token_is_refused() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let worker = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_master(stream, "secret")
+        });
+        
+        //the scene is invalid, so the worker would fail with another error if it prepared it
+        let result = RemoteWorker::connect(&address, "guessed", "not a scene");
+        assert!(result.is_err_and(|e| e.error.contains("token")), "The wrong token was not refused!");
+        assert!(worker.join().unwrap().is_err());
+    }
+
+    /// Connects to a worker which accepts any scene and then receives the messages of the master 
+    /// without ever answering them. Returns the worker and the messages it received. 
+    fn connect_to_silent_worker() -> (RemoteWorker, thread::JoinHandle<Vec<MasterMessage>>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let worker = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut messages = vec![receive::<MasterMessage>(&mut stream).unwrap()];
+            send(&mut stream, &WorkerMessage::Ready {threads: 2}).unwrap();
+            while let Ok(message) = receive(&mut stream) {
+                messages.push(message);
+            }
+            messages
+        });
+        (RemoteWorker::connect(&address, "secret", "").unwrap(), worker)
+    }
+
+    #[test]
+    fn test_cancel_does_not_wait_for_worker() {
+        let (mut worker, messages) = connect_to_silent_worker();
+        let tiles = RenderBucket::split(256, 256);
+        let (paused, cancelled) = (AtomicBool::new(false), AtomicBool::new(false));
+        
+        let start = std::time::Instant::now();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(200));
+                cancelled.store(true, Ordering::Relaxed);
+            });
+            let result = worker.render_tiles(0, &tiles, &paused, &cancelled, |_, _, _, _| panic!("Received a tile!"));
+            assert!(result.is_ok());
+        });
+        assert!(start.elapsed() < Duration::from_secs(5), "Cancelling waited for the worker!");
+        
+        //only the batches in flight were handed out
+        let sent: usize = messages.join().unwrap().iter().map(|message| match message {
+            MasterMessage::Tiles {tiles, ..} => tiles.len(),
+            MasterMessage::Scene {..} => 0,
+        }).sum();
+        assert_eq!(sent, BATCHES_IN_FLIGHT * 2);
+    }
+
+    #[test]
+    fn test_paused_render_hands_out_no_tiles() {
+        let (mut worker, messages) = connect_to_silent_worker();
+        let tiles = RenderBucket::split(256, 256);
+        let (paused, cancelled) = (AtomicBool::new(true), AtomicBool::new(false));
+        
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(200));
+                cancelled.store(true, Ordering::Relaxed);
+            });
+            assert!(worker.render_tiles(0, &tiles, &paused, &cancelled, |_, _, _, _| panic!("Received a tile!")).is_ok());
+        });
+        drop(worker);
+        assert_eq!(messages.join().unwrap().len(), 1, "Tiles were handed out while paused!");
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("", "secret"));
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::spectrum::Spectrum;
+
+/// The default band of a false color render, green in the middle of the visible range.
+pub const FALSE_COLOR_WAVELENGTH_DEFAULT: f32 = 550.0;
+pub const FALSE_COLOR_BANDWIDTH_DEFAULT: f32 = 10.0;
+pub const FALSE_COLOR_MAXIMUM_DEFAULT: f32 = 1.0;
+
+/// The colors of the maps from the lowest to the highest intensity, spread evenly.
+const VIRIDIS_COLORS: [[f32; 3]; 5] = [[0.267, 0.005, 0.329], [0.229, 0.322, 0.546], [0.128, 0.567, 0.551],
+                                       [0.369, 0.789, 0.383], [0.993, 0.906, 0.144]];
+const INFERNO_COLORS: [[f32; 3]; 6] = [[0.001, 0.000, 0.014], [0.258, 0.039, 0.406], [0.578, 0.148, 0.404],
+                                       [0.865, 0.317, 0.226], [0.988, 0.645, 0.040], [0.988, 1.000, 0.645]];
+const GRAYSCALE_COLORS: [[f32; 3]; 2] = [[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]];
+
+/// The color maps a false color render can use.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FalseColorMap {
+    Viridis,
+    Inferno,
+    Grayscale,
+}
+
+impl FalseColorMap {
+    /// Returns the color of the map at the given position in range \[0; 1], interpolated linearly
+    /// between its colors.
+    pub fn color_at(&self, t: f32) -> (f32, f32, f32) {
+        let colors: &[[f32; 3]] = match self {
+            FalseColorMap::Viridis => &VIRIDIS_COLORS,
+            FalseColorMap::Inferno => &INFERNO_COLORS,
+            FalseColorMap::Grayscale => &GRAYSCALE_COLORS,
+        };
+        let position = t.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+        let index = (position as usize).min(colors.len() - 2);
+        let fraction = position - index as f32;
+        let (from, to) = (colors[index], colors[index + 1]);
+        let [r, g, b] = [0, 1, 2].map(|c| from[c] + (to[c] - from[c]) * fraction);
+        (r, g, b)
+    }
+}
+
+impl Display for FalseColorMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FalseColorMap::Viridis => write!(f, "Viridis"),
+            FalseColorMap::Inferno => write!(f, "Inferno"),
+            FalseColorMap::Grayscale => write!(f, "Grayscale"),
+        }
+    }
+}
+
+/// Converts spectra into colors by looking only at a narrow band of wavelengths: the average
+/// intensity within the band is mapped through a color map, so that the scene is seen as it
+/// appears at that wavelength, e.g. to compare how materials behave at 420 nm and 680 nm.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FalseColor {
+    /// The center of the band in nanometers.
+    pub wavelength: f32,
+    /// The width of the band in nanometers. If no sample of the spectra falls into the band, the
+    /// sample closest to its center is taken.
+    pub bandwidth: f32,
+    /// The intensity mapped to the end of the color map, lower intensities are spread linearly
+    /// over the map.
+    pub maximum: f32,
+    pub map: FalseColorMap,
+}
+
+impl Default for FalseColor {
+    fn default() -> Self {
+        FalseColor {
+            wavelength: FALSE_COLOR_WAVELENGTH_DEFAULT,
+            bandwidth: FALSE_COLOR_BANDWIDTH_DEFAULT,
+            maximum: FALSE_COLOR_MAXIMUM_DEFAULT,
+            map: FalseColorMap::Viridis,
+        }
+    }
+}
+
+impl FalseColor {
+    /// Returns the average intensity of the spectrum within the band.
+    pub fn band_intensity(&self, spectrum: &Spectrum) -> f32 {
+        let half_width = self.bandwidth / 2.0;
+        let (sum, count) = spectrum.iter()
+            .filter(|(wavelength, _)| (wavelength - self.wavelength).abs() <= half_width)
+            .fold((0.0, 0), |(sum, count), (_, intensity)| (sum + intensity, count + 1));
+        if count > 0 {
+            return sum / count as f32;
+        }
+        spectrum.iter()
+            .min_by(|(a, _), (b, _)| (a - self.wavelength).abs().total_cmp(&(b - self.wavelength).abs()))
+            .map_or(0.0, |(_, intensity)| intensity)
+    }
+
+    /// Converts the spectrum into the color of its band intensity on the color map.
+    pub fn to_rgb(&self, spectrum: &Spectrum) -> (f32, f32, f32) {
+        self.map.color_at(self.band_intensity(spectrum) / self.maximum.max(f32::MIN_POSITIVE))
+    }
+}
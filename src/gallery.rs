@@ -0,0 +1,507 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use eframe::egui;
+use image::DynamicImage;
+use crate::{RenderScale, UIAtmosphere, UIBackground, UICamera, UIColorResponse, UIFields, UIGroup, UILight, UIMaterial, UIMedium, UIObject, UIObjectType, UISpectrum};
+use crate::custom_image::FireflyFilter;
+use crate::heightfield::HeightfieldSource;
+use crate::hierarchy;
+use crate::light_selection::LightSelection;
+use crate::procedural::Pattern;
+use crate::sampler::SamplerType;
+use crate::shader::{MixWeight, MATERIAL_MIX_DEPTH_MAX};
+use crate::sensor::SensorResponse;
+use crate::spectrum::Spectrum;
+
+/// The number of renders kept in the gallery, older ones are dropped.
+const GALLERY_MAX_ENTRIES: usize = 20;
+/// The width of the preview images in the gallery in pixels.
+const THUMBNAIL_WIDTH: u32 = 160;
+/// The wavelengths at which a custom sensor response is sampled for the hash, in nanometers.
+const SENSOR_HASH_WAVELENGTHS: std::ops::RangeInclusive<u32> = 360..=830;
+
+/// An immutable copy of all settings and the whole scene a render was started with. The scene is
+/// copied deeply, later edits of spectra, materials or objects do not change the snapshot. The
+/// settings can be restored from it to reproduce the render exactly.
+pub struct RenderSnapshot {
+    width: u32,
+    height: u32,
+    render_scale: RenderScale,
+    nbr_of_iterations: u32,
+    nbr_of_ray_bounces: u32,
+    nbr_of_diffuse_bounces: u32,
+    nbr_of_specular_bounces: u32,
+    nbr_of_transmission_bounces: u32,
+    russian_roulette: bool,
+    sampler_type: SamplerType,
+    random_seed: u32,
+    light_selection: LightSelection,
+    light_samples: u32,
+    photon_count: u32,
+    photon_radius: f32,
+    irradiance_cache_quality: Option<f32>,
+    ray_offset: f32,
+    firefly_filter: FireflyFilter,
+    gamut_compression: bool,
+    color_response: UIColorResponse,
+    custom_sensor_response: Option<Arc<SensorResponse>>,
+    light_scale: f32,
+    spectrum_number_of_samples: usize,
+    scene: SceneCopy,
+    /// A hash over everything influencing the rendered image, see [RenderSnapshot::get_hash].
+    hash: u64,
+}
+
+impl RenderSnapshot {
+    /// Copies the settings and the scene of the UI values.
+    pub fn capture(ui_values: &UIFields) -> Self {
+        let mut snapshot = RenderSnapshot {
+            width: ui_values.width,
+            height: ui_values.height,
+            render_scale: ui_values.render_scale,
+            nbr_of_iterations: ui_values.nbr_of_iterations,
+            nbr_of_ray_bounces: ui_values.nbr_of_ray_bounces,
+            nbr_of_diffuse_bounces: ui_values.nbr_of_diffuse_bounces,
+            nbr_of_specular_bounces: ui_values.nbr_of_specular_bounces,
+            nbr_of_transmission_bounces: ui_values.nbr_of_transmission_bounces,
+            russian_roulette: ui_values.russian_roulette,
+            sampler_type: ui_values.sampler_type,
+            random_seed: ui_values.random_seed,
+            light_selection: ui_values.light_selection,
+            light_samples: ui_values.light_samples,
+            photon_count: ui_values.photon_count,
+            photon_radius: ui_values.photon_radius,
+            irradiance_cache_quality: ui_values.irradiance_cache_quality,
+            ray_offset: ui_values.ray_offset,
+            firefly_filter: ui_values.firefly_filter,
+            gamut_compression: ui_values.gamut_compression,
+            color_response: ui_values.color_response,
+            custom_sensor_response: ui_values.custom_sensor_response.clone(),
+            light_scale: ui_values.light_scale,
+            spectrum_number_of_samples: ui_values.spectrum_number_of_samples,
+            scene: SceneCopy::new(&ui_values.spectra, &ui_values.materials, &ui_values.ui_cameras,
+                                  ui_values.active_camera, &ui_values.ui_lights, &ui_values.ui_objects,
+                                  &ui_values.ui_groups, ui_values.medium.as_ref(), ui_values.atmosphere.as_ref(),
+                                  &ui_values.background),
+            hash: 0,
+        };
+        snapshot.hash = snapshot.calculate_hash();
+        snapshot
+    }
+
+    /// Overwrites the settings and the scene of the UI values with the snapshot. The scene is
+    /// copied again, so that the snapshot stays unchanged when the restored scene is edited.
+    /// Selections referring to the replaced spectra are cleared.
+    pub fn restore(&self, ui_values: &mut UIFields) {
+        ui_values.width = self.width;
+        ui_values.height = self.height;
+        ui_values.render_scale = self.render_scale;
+        ui_values.nbr_of_iterations = self.nbr_of_iterations;
+        ui_values.nbr_of_ray_bounces = self.nbr_of_ray_bounces;
+        ui_values.nbr_of_diffuse_bounces = self.nbr_of_diffuse_bounces;
+        ui_values.nbr_of_specular_bounces = self.nbr_of_specular_bounces;
+        ui_values.nbr_of_transmission_bounces = self.nbr_of_transmission_bounces;
+        ui_values.russian_roulette = self.russian_roulette;
+        ui_values.sampler_type = self.sampler_type;
+        ui_values.random_seed = self.random_seed;
+        ui_values.light_selection = self.light_selection;
+        ui_values.light_samples = self.light_samples;
+        ui_values.photon_count = self.photon_count;
+        ui_values.photon_radius = self.photon_radius;
+        ui_values.irradiance_cache_quality = self.irradiance_cache_quality;
+        ui_values.ray_offset = self.ray_offset;
+        ui_values.firefly_filter = self.firefly_filter;
+        ui_values.gamut_compression = self.gamut_compression;
+        ui_values.color_response = self.color_response;
+        ui_values.custom_sensor_response = self.custom_sensor_response.clone();
+        ui_values.light_scale = self.light_scale;
+        ui_values.spectrum_number_of_samples = self.spectrum_number_of_samples;
+
+        let scene = SceneCopy::new(&self.scene.spectra, &self.scene.materials, &self.scene.cameras,
+                                   self.scene.active_camera, &self.scene.lights, &self.scene.objects,
+                                   &self.scene.groups, self.scene.medium.as_ref(), self.scene.atmosphere.as_ref(),
+                                   &self.scene.background);
+        ui_values.spectra = scene.spectra;
+        ui_values.materials = scene.materials;
+        ui_values.ui_cameras = scene.cameras;
+        ui_values.active_camera = scene.active_camera;
+        ui_values.ui_lights = scene.lights;
+        ui_values.ui_objects = scene.objects;
+        ui_values.ui_groups = scene.groups;
+        ui_values.medium = scene.medium;
+        ui_values.atmosphere = scene.atmosphere;
+        ui_values.background = scene.background;
+        ui_values.selected_spectrum = None;
+        ui_values.compared_spectra.clear();
+    }
+
+    /// Returns a hash over everything influencing the rendered image: the settings, the active
+    /// camera, the visible lights and objects with their spectra and materials, the medium, the
+    /// atmosphere and the background. Names, hidden elements and the number of threads are left out. Two renders with
+    /// the same hash give the same image. The hash is only stable within a session.
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn get_nbr_of_iterations(&self) -> u32 {
+        self.nbr_of_iterations
+    }
+
+    fn calculate_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.width, self.height, self.nbr_of_iterations).hash(&mut hasher);
+        (self.nbr_of_ray_bounces, self.nbr_of_diffuse_bounces, self.nbr_of_specular_bounces).hash(&mut hasher);
+        self.nbr_of_transmission_bounces.hash(&mut hasher);
+        (self.russian_roulette, self.random_seed, self.light_samples, self.gamut_compression).hash(&mut hasher);
+        //the enums carry floats, their debug output is hashed instead
+        format!("{:?} {:?} {:?} {:?} {:?}", self.sampler_type, self.light_selection, self.firefly_filter,
+                self.color_response, self.render_scale).hash(&mut hasher);
+        if let Some(sensor) = &self.custom_sensor_response {
+            for wavelength in SENSOR_HASH_WAVELENGTHS {
+                let (r, g, b) = sensor.response_at(wavelength as f32);
+                hash_floats(&mut hasher, &[r, g, b]);
+            }
+        }
+        self.photon_count.hash(&mut hasher);
+        hash_floats(&mut hasher, &[self.light_scale, self.photon_radius, self.ray_offset]);
+        if let Some(quality) = self.irradiance_cache_quality {
+            hash_floats(&mut hasher, &[quality]);
+        }
+        self.spectrum_number_of_samples.hash(&mut hasher);
+
+        let camera = &self.scene.cameras[self.scene.active_camera];
+        hash_floats(&mut hasher, &[camera.pos_x, camera.pos_y, camera.pos_z, camera.dir_x, camera.dir_y,
+            camera.dir_z, camera.up_x, camera.up_y, camera.up_z, camera.fov_deg_y]);
+        camera.near_clip.map(f32::to_bits).hash(&mut hasher);
+        camera.far_clip.map(f32::to_bits).hash(&mut hasher);
+        if camera.thick_lens {
+            hash_floats(&mut hasher, &[camera.aperture_radius, camera.focus_distance, camera.lens_thickness,
+                camera.lens_refractive_index, camera.lens_abbe_number]);
+            camera.lens_wavelength_bands.hash(&mut hasher);
+        }
+
+        //groups only matter through the positions they give, which are hashed in world space
+        for light in self.scene.lights.iter().filter(|l| !l.hidden).map(hierarchy::world_light) {
+            hash_floats(&mut hasher, &[light.pos_x, light.pos_y, light.pos_z]);
+            hash_spectrum(&mut hasher, &light.spectrum.borrow().spectrum);
+            format!("{:?}", light.link_mode).hash(&mut hasher);
+            light.linked_objects.hash(&mut hasher);
+        }
+        for object in self.scene.objects.iter().filter(|o| !o.hidden).map(hierarchy::world_object) {
+            hash_floats(&mut hasher, &[object.pos_x, object.pos_y, object.pos_z]);
+            hash_object_type(&mut hasher, &object.ui_object_type);
+            (object.visible_to_camera, object.visible_in_reflections, object.casts_shadows).hash(&mut hasher);
+            hash_material(&mut hasher, &object.material.borrow(), 0);
+            object.texture.as_ref().map(|texture| texture.get_path()).hash(&mut hasher);
+            if let Some(emission) = &object.emission {
+                (object.emission_visible_to_camera, object.emission_visible_in_reflections).hash(&mut hasher);
+                hash_spectrum(&mut hasher, &emission.borrow().spectrum);
+            }
+        }
+        if let Some(medium) = &self.scene.medium {
+            hash_floats(&mut hasher, &[medium.density]);
+            medium.in_scattering.hash(&mut hasher);
+            hash_spectrum(&mut hasher, &medium.absorption.borrow().spectrum);
+            hash_spectrum(&mut hasher, &medium.scattering.borrow().spectrum);
+        }
+        if let Some(atmosphere) = &self.scene.atmosphere {
+            hash_floats(&mut hasher, &[atmosphere.sun_elevation_deg, atmosphere.sun_azimuth_deg, 
+                atmosphere.turbidity, atmosphere.altitude]);
+            hash_spectrum(&mut hasher, &atmosphere.sun_spectrum.borrow().spectrum);
+        }
+        self.scene.background.to_string().hash(&mut hasher);
+        match &self.scene.background {
+            UIBackground::Black | UIBackground::Transparent => {}
+            UIBackground::Spectrum(spectrum) => hash_spectrum(&mut hasher, &spectrum.borrow().spectrum),
+            UIBackground::Gradient {top, bottom} => {
+                hash_spectrum(&mut hasher, &top.borrow().spectrum);
+                hash_spectrum(&mut hasher, &bottom.borrow().spectrum);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+/// A deep copy of the spectra, materials, cameras, lights, objects, groups, medium, atmosphere and 
+/// background of a scene. Elements which shared a spectrum, material or group before share the copy of it.
+struct SceneCopy {
+    spectra: Vec<Rc<RefCell<UISpectrum>>>,
+    materials: Vec<Rc<RefCell<UIMaterial>>>,
+    cameras: Vec<UICamera>,
+    active_camera: usize,
+    lights: Vec<UILight>,
+    objects: Vec<UIObject>,
+    groups: Vec<Rc<RefCell<UIGroup>>>,
+    medium: Option<UIMedium>,
+    atmosphere: Option<UIAtmosphere>,
+    background: UIBackground,
+}
+
+impl SceneCopy {
+    #[allow(clippy::too_many_arguments)]
+    fn new(spectra: &[Rc<RefCell<UISpectrum>>], materials: &[Rc<RefCell<UIMaterial>>], cameras: &[UICamera],
+           active_camera: usize, lights: &[UILight], objects: &[UIObject], groups: &[Rc<RefCell<UIGroup>>],
+           medium: Option<&UIMedium>, atmosphere: Option<&UIAtmosphere>, background: &UIBackground) -> Self {
+        //the copies are looked up by the address of the original
+        let mut copied_spectra: HashMap<*const RefCell<UISpectrum>, Rc<RefCell<UISpectrum>>> = HashMap::new();
+        let mut copy_spectrum = |spectrum: &Rc<RefCell<UISpectrum>>| {
+            copied_spectra.entry(Rc::as_ptr(spectrum))
+                .or_insert_with(|| Rc::new(RefCell::new(spectrum.borrow().clone())))
+                .clone()
+        };
+        let spectra: Vec<_> = spectra.iter().map(&mut copy_spectrum).collect();
+
+        let mut copied_materials: HashMap<*const RefCell<UIMaterial>, Rc<RefCell<UIMaterial>>> = HashMap::new();
+        let mut copy_material = |material: &Rc<RefCell<UIMaterial>>| {
+            copied_materials.entry(Rc::as_ptr(material))
+                .or_insert_with(|| {
+                    let mut copy = material.borrow().clone();
+                    copy.spectrum = copy_spectrum(&copy.spectrum);
+                    if let Some(pattern) = &mut copy.pattern {
+                        pattern.spectrum = copy_spectrum(&pattern.spectrum);
+                    }
+                    if let Some(fluorescence) = &mut copy.fluorescence {
+                        fluorescence.excitation = copy_spectrum(&fluorescence.excitation);
+                        fluorescence.emission = copy_spectrum(&fluorescence.emission);
+                    }
+                    Rc::new(RefCell::new(copy))
+                })
+                .clone()
+        };
+        let materials: Vec<_> = materials.iter().map(&mut copy_material).collect();
+
+        let mut copied_groups = HashMap::new();
+        let groups: Vec<_> = groups.iter().map(|group| copy_group(group, &mut copied_groups)).collect();
+
+        let objects = objects.iter().map(|object| {
+            let mut copy = object.clone();
+            copy.material = copy_material(&object.material);
+            copy.group = object.group.as_ref().map(|group| copy_group(group, &mut copied_groups));
+            copy
+        }).collect::<Vec<_>>();
+        //mixes still hold the original materials, including ones which are no longer in the list
+        let mut unresolved: Vec<_> = materials.iter().chain(objects.iter().map(|o| &o.material)).cloned().collect();
+        let mut resolved = HashSet::new();
+        while let Some(copy) = unresolved.pop() {
+            if !resolved.insert(Rc::as_ptr(&copy)) {
+                continue;
+            }
+            if let Some(mix) = &mut copy.borrow_mut().mix {
+                mix.first = copy_material(&mix.first);
+                mix.second = copy_material(&mix.second);
+                unresolved.extend([mix.first.clone(), mix.second.clone()]);
+            }
+        }
+        //the material closure holds on to the spectrum closure, so emissions are copied afterwards
+        let objects = objects.into_iter().map(|mut object| {
+            object.emission = object.emission.as_ref().map(&mut copy_spectrum);
+            object
+        }).collect();
+        let lights = lights.iter().map(|light| {
+            let mut copy = light.clone();
+            copy.spectrum = copy_spectrum(&light.spectrum);
+            copy.group = light.group.as_ref().map(|group| copy_group(group, &mut copied_groups));
+            copy
+        }).collect();
+        let medium = medium.map(|medium| UIMedium {
+            absorption: copy_spectrum(&medium.absorption),
+            scattering: copy_spectrum(&medium.scattering),
+            ..medium.clone()
+        });
+        let atmosphere = atmosphere.map(|atmosphere| UIAtmosphere {
+            sun_spectrum: copy_spectrum(&atmosphere.sun_spectrum),
+            ..atmosphere.clone()
+        });
+        let background = match background {
+            UIBackground::Black => UIBackground::Black,
+            UIBackground::Spectrum(spectrum) => UIBackground::Spectrum(copy_spectrum(spectrum)),
+            UIBackground::Gradient {top, bottom} => UIBackground::Gradient {
+                top: copy_spectrum(top),
+                bottom: copy_spectrum(bottom),
+            },
+            UIBackground::Transparent => UIBackground::Transparent,
+        };
+
+        SceneCopy {
+            spectra,
+            materials,
+            cameras: cameras.to_vec(),
+            active_camera,
+            lights,
+            objects,
+            groups,
+            medium,
+            atmosphere,
+            background,
+        }
+    }
+}
+
+/// Copies the group together with its parents, unless it was copied before. Parents are copied 
+/// recursively, so this can not be a closure like the copies of spectra and materials.
+fn copy_group(group: &Rc<RefCell<UIGroup>>, copied_groups: &mut HashMap<*const RefCell<UIGroup>, Rc<RefCell<UIGroup>>>)
+        -> Rc<RefCell<UIGroup>> {
+    if let Some(copy) = copied_groups.get(&Rc::as_ptr(group)) {
+        return copy.clone();
+    }
+    let mut copy = group.borrow().clone();
+    copy.parent = copy.parent.as_ref().map(|parent| copy_group(parent, copied_groups));
+    let copy = Rc::new(RefCell::new(copy));
+    copied_groups.insert(Rc::as_ptr(group), copy.clone());
+    copy
+}
+
+/// A finished render kept in the gallery together with the settings it was rendered with.
+pub struct GalleryEntry {
+    /// The number of the render within the session, starting at 1.
+    pub number: u32,
+    pub image: DynamicImage,
+    pub snapshot: RenderSnapshot,
+    pub render_time: Duration,
+    thumbnail: Option<egui::TextureHandle>,
+}
+
+impl GalleryEntry {
+    /// Returns a small preview of the image, which is created on the first call.
+    pub fn get_thumbnail(&mut self, ctx: &egui::Context) -> &egui::TextureHandle {
+        let (image, number) = (&self.image, self.number);
+        self.thumbnail.get_or_insert_with(|| {
+            let height = (image.height() * THUMBNAIL_WIDTH / image.width().max(1)).max(1);
+            let preview = image.thumbnail(THUMBNAIL_WIDTH, height).to_rgba8();
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                [preview.width() as usize, preview.height() as usize], preview.as_raw());
+            ctx.load_texture(format!("gallery_{number}"), color_image, egui::TextureOptions::LINEAR)
+        })
+    }
+}
+
+/// The renders completed in this session, the latest first.
+#[derive(Default)]
+pub struct Gallery {
+    entries: VecDeque<GalleryEntry>,
+    renders: u32,
+}
+
+impl Gallery {
+    /// Adds a completed render. If the gallery is full, the oldest render is dropped.
+    pub fn add(&mut self, image: DynamicImage, snapshot: RenderSnapshot, render_time: Duration) {
+        self.renders += 1;
+        self.entries.push_front(GalleryEntry {
+            number: self.renders,
+            image,
+            snapshot,
+            render_time,
+            thumbnail: None,
+        });
+        self.entries.truncate(GALLERY_MAX_ENTRIES);
+    }
+
+    pub fn entries_mut(&mut self) -> impl Iterator<Item = &mut GalleryEntry> {
+        self.entries.iter_mut()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn hash_floats(hasher: &mut DefaultHasher, values: &[f32]) {
+    for value in values {
+        value.to_bits().hash(hasher);
+    }
+}
+
+fn hash_object_type(hasher: &mut DefaultHasher, object_type: &UIObjectType) {
+    match *object_type {
+        UIObjectType::PlainBox(x, y, z) => hash_floats(hasher, &[0.0, x, y, z]),
+        UIObjectType::Sphere(radius) => hash_floats(hasher, &[1.0, radius]),
+        UIObjectType::RotatedBox(x, y, z, x_rotation, y_rotation, z_rotation) => {
+            hash_floats(hasher, &[2.0, x, y, z, x_rotation, y_rotation, z_rotation])
+        }
+        UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+            hash_floats(hasher, &[3.0, radius, height, x_rotation, y_rotation, z_rotation])
+        }
+        UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+            hash_floats(hasher, &[8.0, x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation])
+        }
+        UIObjectType::Disc(radius, normal_x, normal_y, normal_z) => {
+            hash_floats(hasher, &[4.0, radius, normal_x, normal_y, normal_z])
+        }
+        UIObjectType::Quad(width, depth, normal_x, normal_y, normal_z) => {
+            hash_floats(hasher, &[5.0, width, depth, normal_x, normal_y, normal_z])
+        }
+        UIObjectType::Csg(ref csg) => {
+            hash_floats(hasher, &[6.0]);
+            csg.operation.to_string().hash(hasher);
+            for part in [&csg.first, &csg.second] {
+                hash_floats(hasher, &[part.offset_x, part.offset_y, part.offset_z]);
+                hash_object_type(hasher, &part.ui_object_type);
+            }
+        }
+        UIObjectType::Heightfield(ref heightfield) => {
+            hash_floats(hasher, &[7.0, heightfield.width, heightfield.depth, heightfield.height]);
+            match heightfield.source {
+                HeightfieldSource::Noise { seed, frequency, octaves } => (seed, frequency.to_bits(), octaves).hash(hasher),
+                HeightfieldSource::Image(ref path) => path.hash(hasher),
+            }
+        }
+    }
+}
+
+/// Hashes the material and, if it is a mix, its materials up to the depth the renderer follows. 
+fn hash_material(hasher: &mut DefaultHasher, material: &UIMaterial, depth: usize) {
+    material.shadow_catcher.hash(hasher);
+    if let Some(mix) = material.mix.as_ref().filter(|_| depth < MATERIAL_MIX_DEPTH_MAX) {
+        match mix.weight {
+            MixWeight::Constant(share) => hash_floats(hasher, &[0.0, share]),
+            MixWeight::Pattern(pattern) => {
+                hash_floats(hasher, &[1.0]);
+                hash_pattern(hasher, &pattern);
+            }
+            MixWeight::Fresnel(refractive_index) => hash_floats(hasher, &[2.0, refractive_index]),
+        }
+        hash_material(hasher, &mix.first.borrow(), depth + 1);
+        hash_material(hasher, &mix.second.borrow(), depth + 1);
+        return;
+    }
+    hash_floats(hasher, &[material.metallicness, material.roughness, material.tangent_rotation]);
+    material.bitangent_roughness.map(f32::to_bits).hash(hasher);
+    hash_spectrum(hasher, &material.spectrum.borrow().spectrum);
+    if let Some(pattern) = &material.pattern {
+        hash_pattern(hasher, &pattern.pattern);
+        hash_spectrum(hasher, &pattern.spectrum.borrow().spectrum);
+    }
+    if let Some(fluorescence) = &material.fluorescence {
+        hash_floats(hasher, &[fluorescence.quantum_yield]);
+        hash_spectrum(hasher, &fluorescence.excitation.borrow().spectrum);
+        hash_spectrum(hasher, &fluorescence.emission.borrow().spectrum);
+    }
+}
+
+fn hash_pattern(hasher: &mut DefaultHasher, pattern: &Pattern) {
+    match *pattern {
+        Pattern::Checker { size } => hash_floats(hasher, &[0.0, size]),
+        Pattern::Noise { size, octaves, seed } => {
+            hash_floats(hasher, &[1.0, size]);
+            (octaves, seed).hash(hasher);
+        }
+        Pattern::Gradient { axis, start, end } => {
+            hash_floats(hasher, &[2.0, start, end]);
+            axis.hash(hasher);
+        }
+    }
+}
+
+fn hash_spectrum(hasher: &mut DefaultHasher, spectrum: &Spectrum) {
+    for (wavelength, intensity) in spectrum.iter() {
+        hash_floats(hasher, &[wavelength, intensity]);
+    }
+}
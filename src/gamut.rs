@@ -0,0 +1,71 @@
+use crate::spectrum;
+use crate::spectrum::Spectrum;
+
+/// Components below this negative value are considered to be outside of the sRGB gamut, anything
+/// above is taken to be floating point imprecision.
+const OUT_OF_GAMUT_TOLERANCE: f32 = 1e-4;
+
+/// The chromaticity coordinates (x, y) of the red, green and blue primaries of sRGB.
+pub const SRGB_PRIMARIES_XY: [(f32, f32); 3] = [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)];
+
+/// The lowest and highest temperature in kelvin of the drawn blackbody (planckian) locus.
+const PLANCKIAN_LOCUS_TEMPERATURE_RANGE: (f32, f32) = (1000.0, 25000.0);
+const PLANCKIAN_LOCUS_NBR_OF_POINTS: usize = 40;
+
+/// Returns true if the given linear RGB color can not be displayed in sRGB, meaning at least one
+/// of its components is negative. Components above 1 are not considered out of gamut since they
+/// are merely too bright, which is a question of exposure instead of chromaticity.
+pub fn is_out_of_gamut(rgb: (f32, f32, f32)) -> bool {
+    rgb.0.min(rgb.1).min(rgb.2) < -OUT_OF_GAMUT_TOLERANCE
+}
+
+/// Brings the given linear RGB color into the displayable range by hard clipping each component
+/// to [0, 1]. This shifts the hue of out of gamut colors.
+pub fn clip(rgb: (f32, f32, f32)) -> (f32, f32, f32) {
+    (rgb.0.clamp(0.0, 1.0), rgb.1.clamp(0.0, 1.0), rgb.2.clamp(0.0, 1.0))
+}
+
+/// Brings the given linear RGB color into the sRGB gamut by desaturating it towards the grey of
+/// equal luminance, just far enough to lift the smallest component to 0. Unlike [clip], the hue
+/// and luminance are kept. Colors inside the gamut are returned unchanged.
+pub fn compress(rgb: (f32, f32, f32)) -> (f32, f32, f32) {
+    let min = rgb.0.min(rgb.1).min(rgb.2);
+    if min >= 0.0 {
+        return rgb;
+    }
+
+    let luminance = luminance(rgb);
+    if luminance <= 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let t = luminance / (luminance - min);
+    (
+        (luminance + (rgb.0 - luminance) * t).max(0.0),
+        (luminance + (rgb.1 - luminance) * t).max(0.0),
+        (luminance + (rgb.2 - luminance) * t).max(0.0),
+    )
+}
+
+/// Calculates the relative luminance of a linear sRGB color.
+pub fn luminance(rgb: (f32, f32, f32)) -> f32 {
+    0.2126 * rgb.0 + 0.7152 * rgb.1 + 0.0722 * rgb.2
+}
+
+/// Calculates the chromaticity coordinates (x, y) of black bodies between 1000 K and 25000 K,
+/// ordered by ascending temperature.
+pub fn planckian_locus_xy() -> Vec<(f32, f32)> {
+    let (lowest, highest) = PLANCKIAN_LOCUS_TEMPERATURE_RANGE;
+    (0..PLANCKIAN_LOCUS_NBR_OF_POINTS).filter_map(|i| {
+        //sampling evenly in reciprocal temperature spaces the points evenly along the curve
+        let t = i as f32 / (PLANCKIAN_LOCUS_NBR_OF_POINTS - 1) as f32;
+        let temperature = 1.0 / (1.0 / lowest + t * (1.0 / highest - 1.0 / lowest));
+        Spectrum::new_temperature_spectrum(
+            spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+            spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+            temperature,
+            spectrum::NBR_OF_SAMPLES_MAX,
+            1.0,
+        ).get_xy_chromaticity()
+    }).collect()
+}
@@ -0,0 +1,255 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use crate::procedural;
+
+/// The number of samples per side of grids generated from noise.
+const NOISE_GRID_RESOLUTION: usize = 128;
+/// The maximum number of samples per side of grids read from images, larger images are scaled
+/// down to keep the memory and the cost of exporting the mesh in check.
+const IMAGE_GRID_RESOLUTION_MAX: u32 = 1024;
+
+/// Where the heights of a heightfield come from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HeightfieldSource {
+    /// Fractal value noise. The frequency is the number of hills across the heightfield, every
+    /// further octave adds details of twice the frequency and half the amplitude.
+    Noise { seed: u32, frequency: f32, octaves: u32 },
+    /// A grayscale image seen from above, black being the lowest and white the highest point.
+    Image(PathBuf),
+}
+
+impl Display for HeightfieldSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeightfieldSource::Noise { .. } => write!(f, "Noise"),
+            HeightfieldSource::Image(path) => write!(f, "Image {}", path.display()),
+        }
+    }
+}
+
+/// A regular grid of heights in range \[0; 1]. The grid is laid out along the x and z axis, the
+/// first sample lying at the smallest x and z. Between the samples, each cell is split into two
+/// triangles.
+#[derive(Debug)]
+pub struct HeightGrid {
+    resolution_x: usize,
+    resolution_z: usize,
+    /// The heights row by row, x changing fastest.
+    heights: Vec<f32>,
+}
+
+impl HeightGrid {
+    /// Creates the grid described by the source, reading the image if it is one.
+    pub fn from_source(source: &HeightfieldSource) -> Result<Self, HeightfieldError> {
+        match source {
+            HeightfieldSource::Noise { seed, frequency, octaves } => Ok(Self::from_noise(*seed, *frequency, *octaves)),
+            HeightfieldSource::Image(path) => Self::from_image(path),
+        }
+    }
+
+    /// Reads the heights from the brightness of the pixels of the image. Color images are
+    /// converted to grayscale first.
+    pub fn from_image(path: &Path) -> Result<Self, HeightfieldError> {
+        let mut image = image::open(path)
+            .map_err(|e| HeightfieldError {error: format!("Could not read {}: {e}", path.display())})?;
+        if image.width() < 2 || image.height() < 2 {
+            return Err(HeightfieldError {error: "The image has to be at least 2 pixels wide and high!".to_string()});
+        }
+        //each axis is reduced on its own, keeping the aspect ratio could leave a single row of samples
+        if image.width().max(image.height()) > IMAGE_GRID_RESOLUTION_MAX {
+            image = image.resize_exact(image.width().min(IMAGE_GRID_RESOLUTION_MAX), 
+                                       image.height().min(IMAGE_GRID_RESOLUTION_MAX),
+                                       image::imageops::FilterType::Triangle);
+        }
+
+        let luma = image.to_luma32f();
+        Ok(HeightGrid {
+            resolution_x: luma.width() as usize,
+            resolution_z: luma.height() as usize,
+            heights: luma.pixels().map(|pixel| pixel.0[0].clamp(0.0, 1.0)).collect(),
+        })
+    }
+
+    /// Generates the heights from fractal value noise, normalized to fill the range \[0; 1].
+    pub fn from_noise(seed: u32, frequency: f32, octaves: u32) -> Self {
+        let mut heights = Vec::with_capacity(NOISE_GRID_RESOLUTION * NOISE_GRID_RESOLUTION);
+        for z in 0..NOISE_GRID_RESOLUTION {
+            for x in 0..NOISE_GRID_RESOLUTION {
+                let u = x as f32 / (NOISE_GRID_RESOLUTION - 1) as f32;
+                let v = z as f32 / (NOISE_GRID_RESOLUTION - 1) as f32;
+                heights.push(procedural::fractal_noise(seed, u * frequency, 0.0, v * frequency, octaves.max(1)));
+            }
+        }
+
+        let lowest = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let highest = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        if highest > lowest {
+            heights.iter_mut().for_each(|height| *height = (*height - lowest) / (highest - lowest));
+        }
+        HeightGrid {
+            resolution_x: NOISE_GRID_RESOLUTION,
+            resolution_z: NOISE_GRID_RESOLUTION,
+            heights,
+        }
+    }
+
+    /// Returns the number of samples along the x and the z axis.
+    pub fn get_resolution(&self) -> (usize, usize) {
+        (self.resolution_x, self.resolution_z)
+    }
+
+    pub fn get_height(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.resolution_x + x]
+    }
+
+    /// Returns the highest sample of the grid.
+    pub fn get_highest(&self) -> f32 {
+        self.heights.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// Returns the position of the sample, for a grid spanning the given width along x and depth
+    /// along z centered at the origin, and reaching up to the given height.
+    pub fn get_point(&self, x: usize, z: usize, dimensions: &Vector3<f32>) -> Point3<f32> {
+        Point3::new(
+            (x as f32 / (self.resolution_x - 1) as f32 - 0.5) * dimensions.x,
+            self.get_height(x, z) * dimensions.y,
+            (z as f32 / (self.resolution_z - 1) as f32 - 0.5) * dimensions.z,
+        )
+    }
+
+    /// Calculates the first intersection of a ray with the surface, for a grid with the given
+    /// dimensions (see [HeightGrid::get_point]). The ray is given in the local space of the grid,
+    /// together with the stretch of it inside the bounds of the grid. The cells below the ray are
+    /// walked through with a 2D DDA, testing only their triangles. Returns the length of the ray
+    /// and the normal of the triangle it hit, pointing upwards.
+    pub fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>, dimensions: &Vector3<f32>,
+                     t_enter: f32, t_exit: f32) -> Option<(f32, Vector3<f32>)> {
+        let cells_x = self.resolution_x - 1;
+        let cells_z = self.resolution_z - 1;
+        let cell_size_x = dimensions.x / cells_x as f32;
+        let cell_size_z = dimensions.z / cells_z as f32;
+        let to_grid = |t: f32| {
+            let point = origin + direction * t;
+            ((point.x / dimensions.x + 0.5) * cells_x as f32, (point.z / dimensions.z + 0.5) * cells_z as f32)
+        };
+
+        //the cell the ray enters the grid in
+        let (grid_x, grid_z) = to_grid(t_enter);
+        let mut cell_x = (grid_x.floor().max(0.0) as usize).min(cells_x - 1);
+        let mut cell_z = (grid_z.floor().max(0.0) as usize).min(cells_z - 1);
+
+        //the lengths of the ray at which it crosses the next cell border along x and z
+        let step_x = if direction.x > 0.0 { 1 } else { -1 };
+        let step_z = if direction.z > 0.0 { 1 } else { -1 };
+        let delta_x = (cell_size_x / direction.x).abs();
+        let delta_z = (cell_size_z / direction.z).abs();
+        let border_x = (cell_x as f32 + if step_x > 0 { 1.0 } else { 0.0 }) * cell_size_x - dimensions.x / 2.0;
+        let border_z = (cell_z as f32 + if step_z > 0 { 1.0 } else { 0.0 }) * cell_size_z - dimensions.z / 2.0;
+        let mut next_x = if direction.x == 0.0 { f32::INFINITY } else { (border_x - origin.x) / direction.x };
+        let mut next_z = if direction.z == 0.0 { f32::INFINITY } else { (border_z - origin.z) / direction.z };
+
+        let mut t_cell_enter = t_enter;
+        loop {
+            let t_cell_exit = next_x.min(next_z).min(t_exit);
+            if let Some(hit) = self.intersect_cell(origin, direction, dimensions, cell_x, cell_z, t_cell_enter, t_cell_exit) {
+                return Some(hit);
+            }
+            if t_cell_exit >= t_exit {
+                return None;
+            }
+
+            //step into the neighbouring cell the ray crosses into first
+            if next_x < next_z {
+                if (step_x < 0 && cell_x == 0) || (step_x > 0 && cell_x == cells_x - 1) {
+                    return None;
+                }
+                cell_x = cell_x.wrapping_add_signed(step_x);
+                next_x += delta_x;
+            } else {
+                if (step_z < 0 && cell_z == 0) || (step_z > 0 && cell_z == cells_z - 1) {
+                    return None;
+                }
+                cell_z = cell_z.wrapping_add_signed(step_z);
+                next_z += delta_z;
+            }
+            t_cell_enter = t_cell_exit;
+        }
+    }
+
+    /// Tests the ray against the two triangles of a cell, only accepting hits within the given
+    /// stretch of the ray above the cell.
+    #[allow(clippy::too_many_arguments)]
+    fn intersect_cell(&self, origin: &Point3<f32>, direction: &Vector3<f32>, dimensions: &Vector3<f32>,
+                      cell_x: usize, cell_z: usize, t_enter: f32, t_exit: f32) -> Option<(f32, Vector3<f32>)> {
+        let corner_00 = self.get_point(cell_x, cell_z, dimensions);
+        let corner_10 = self.get_point(cell_x + 1, cell_z, dimensions);
+        let corner_01 = self.get_point(cell_x, cell_z + 1, dimensions);
+        let corner_11 = self.get_point(cell_x + 1, cell_z + 1, dimensions);
+
+        //the ray passes above the cell if it stays higher than all of its corners
+        let highest = corner_00.y.max(corner_10.y).max(corner_01.y).max(corner_11.y);
+        if origin.y + direction.y * t_enter > highest && origin.y + direction.y * t_exit > highest {
+            return None;
+        }
+
+        //the rounding of the cell borders is tolerated, so that no ray slips between two cells
+        let tolerance = (t_exit - t_enter).abs() * 0.0001 + 0.00001;
+        [(corner_00, corner_01, corner_11), (corner_00, corner_11, corner_10)].iter()
+            .filter_map(|(a, b, c)| ray_triangle_intersection(origin, direction, a, b, c))
+            .filter(|(t, _)| *t >= t_enter - tolerance && *t <= t_exit + tolerance && *t >= 0.0)
+            .min_by(|first, second| first.0.total_cmp(&second.0))
+    }
+}
+
+/// An error type used when creating a heightfield. Specific details of the error are given in the
+/// error String.
+#[derive(Debug)]
+pub struct HeightfieldError {
+    pub error: String,
+}
+
+/// Calculates the intersection of a ray and a triangle with the Möller–Trumbore algorithm. Returns
+/// the length of the ray upon hitting the triangle together with the normal of the triangle,
+/// which points to the side from which the corners appear in counterclockwise order.
+fn ray_triangle_intersection(origin: &Point3<f32>, direction: &Vector3<f32>, a: &Point3<f32>, b: &Point3<f32>,
+                             c: &Point3<f32>) -> Option<(f32, Vector3<f32>)> {
+    let edge_1 = b - a;
+    let edge_2 = c - a;
+    let p = direction.cross(&edge_2);
+    let determinant = edge_1.dot(&p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let to_origin = origin - a;
+    let u = to_origin.dot(&p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = to_origin.cross(&edge_1);
+    let v = direction.dot(&q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge_2.dot(&q) * inverse_determinant;
+    Some((t, edge_1.cross(&edge_2).normalize()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wide_image_keeps_two_rows() {
+        let path = std::env::temp_dir().join("heightfield_test_wide_image.png");
+        image::GrayImage::from_fn(IMAGE_GRID_RESOLUTION_MAX * 2, 2, |x, _| image::Luma([(x % 256) as u8]))
+            .save(&path).unwrap();
+        let grid = HeightGrid::from_image(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(grid.unwrap().get_resolution(), (IMAGE_GRID_RESOLUTION_MAX as usize, 2));
+    }
+}
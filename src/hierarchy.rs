@@ -0,0 +1,118 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use nalgebra::{point, vector, Isometry3, Translation3, UnitQuaternion};
+use crate::{UICsg, UICsgPart, UIGroup, UILight, UIObject, UIObjectType};
+
+/// The deepest nesting of groups taken into account. The UI does not allow cyclic parents, this
+/// only keeps a cycle from hanging the application should one occur anyway.
+const MAX_GROUP_DEPTH: usize = 64;
+
+/// Returns the transformation from the space of the group into world space, which combines the
+/// transformations of the group and all of its parents. Without a group, this is the identity.
+pub fn group_transform(group: Option<&Rc<RefCell<UIGroup>>>) -> Isometry3<f32> {
+    let mut transform = Isometry3::identity();
+    let mut current = group.cloned();
+    for _ in 0..MAX_GROUP_DEPTH {
+        let Some(node) = current else {
+            break;
+        };
+        let node = node.borrow();
+        let rotation = UnitQuaternion::from_euler_angles(node.rot_x, node.rot_y, node.rot_z);
+        transform = Isometry3::from_parts(Translation3::new(node.pos_x, node.pos_y, node.pos_z), rotation) * transform;
+        current = node.parent.clone();
+    }
+    transform
+}
+
+/// Returns true if the candidate is the given group or one of the groups nested in it. Such a
+/// candidate can not become the parent of the group without creating a cycle.
+pub fn is_nested_in(candidate: &Rc<RefCell<UIGroup>>, group: &Rc<RefCell<UIGroup>>) -> bool {
+    let mut current = Some(candidate.clone());
+    for _ in 0..MAX_GROUP_DEPTH {
+        let Some(node) = current else {
+            return false;
+        };
+        if Rc::ptr_eq(&node, group) {
+            return true;
+        }
+        current = node.borrow().parent.clone();
+    }
+    true
+}
+
+/// Returns a copy of the object in world space: its position and orientation include the
+/// transformations of its groups and it belongs to no group anymore.
+pub fn world_object(object: &UIObject) -> UIObject {
+    let transform = group_transform(object.group.as_ref());
+    let position = transform * point![object.pos_x, object.pos_y, object.pos_z];
+    let mut copy = object.clone();
+    (copy.pos_x, copy.pos_y, copy.pos_z) = (position.x, position.y, position.z);
+    copy.ui_object_type = rotate_object_type(&object.ui_object_type, &transform.rotation);
+    copy.group = None;
+    copy
+}
+
+/// Returns a copy of the light in world space, see [world_object].
+pub fn world_light(light: &UILight) -> UILight {
+    let position = group_transform(light.group.as_ref()) * point![light.pos_x, light.pos_y, light.pos_z];
+    let mut copy = light.clone();
+    (copy.pos_x, copy.pos_y, copy.pos_z) = (position.x, position.y, position.z);
+    copy.group = None;
+    copy
+}
+
+/// Applies the rotation to the orientation of the object type. Plain boxes turn into rotated
+/// boxes. Quads keep facing along their rotated normal, but any twist around the normal is lost,
+/// and heightfields can not be rotated at all.
+fn rotate_object_type(object_type: &UIObjectType, rotation: &UnitQuaternion<f32>) -> UIObjectType {
+    if rotation.angle() == 0.0 {
+        return object_type.clone();
+    }
+    let rotate_euler_angles = |x: f32, y: f32, z: f32| {
+        (rotation * UnitQuaternion::from_euler_angles(x, y, z)).euler_angles()
+    };
+
+    match *object_type {
+        UIObjectType::PlainBox(x_length, y_length, z_length) => {
+            let (x_rotation, y_rotation, z_rotation) = rotation.euler_angles();
+            UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, z_rotation)
+        }
+        UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, z_rotation) => {
+            let (x_rotation, y_rotation, z_rotation) = rotate_euler_angles(x_rotation, y_rotation, z_rotation);
+            UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, z_rotation)
+        }
+        UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+            let (x_rotation, y_rotation, z_rotation) = rotate_euler_angles(x_rotation, y_rotation, z_rotation);
+            UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation)
+        }
+        UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+            let (x_rotation, y_rotation, z_rotation) = rotate_euler_angles(x_rotation, y_rotation, z_rotation);
+            UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation)
+        }
+        UIObjectType::Disc(radius, normal_x, normal_y, normal_z) => {
+            let normal = rotation * vector![normal_x, normal_y, normal_z];
+            UIObjectType::Disc(radius, normal.x, normal.y, normal.z)
+        }
+        UIObjectType::Quad(width, depth, normal_x, normal_y, normal_z) => {
+            let normal = rotation * vector![normal_x, normal_y, normal_z];
+            UIObjectType::Quad(width, depth, normal.x, normal.y, normal.z)
+        }
+        UIObjectType::Csg(ref csg) => {
+            let rotate_part = |part: &UICsgPart| {
+                let offset = rotation * vector![part.offset_x, part.offset_y, part.offset_z];
+                UICsgPart {
+                    offset_x: offset.x,
+                    offset_y: offset.y,
+                    offset_z: offset.z,
+                    ui_object_type: rotate_object_type(&part.ui_object_type, rotation),
+                }
+            };
+            UIObjectType::Csg(Box::new(UICsg {
+                operation: csg.operation,
+                first: rotate_part(&csg.first),
+                second: rotate_part(&csg.second),
+            }))
+        }
+        UIObjectType::Sphere(_) | UIObjectType::Heightfield(_) => object_type.clone(),
+    }
+}
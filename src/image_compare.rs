@@ -0,0 +1,306 @@
+use std::f32::consts::PI;
+use image::{DynamicImage, RgbImage};
+use rayon::prelude::*;
+use crate::custom_image::TransferFunction;
+use crate::loader::{LoadError, LoadProgress};
+
+/// The pixels per degree of visual angle FLIP assumes, a 0.7 m wide 4K monitor seen from 0.7 m.
+const FLIP_PIXELS_PER_DEGREE: f32 = 67.0;
+/// The terms (a, b) of the contrast sensitivity functions of the achromatic, the red-green and the
+/// blue-yellow channel, each a gaussian a * sqrt(pi / b) * exp(-pi² * x² / b) over the visual
+/// angle x in degrees.
+const FLIP_CSF_ACHROMATIC: [(f32, f32); 1] = [(1.0, 0.0047)];
+const FLIP_CSF_RED_GREEN: [(f32, f32); 1] = [(1.0, 0.0053)];
+const FLIP_CSF_BLUE_YELLOW: [(f32, f32); 2] = [(34.1, 0.04), (13.5, 0.025)];
+/// The widest term of the contrast sensitivity functions, which decides the size of their kernels.
+const FLIP_CSF_WIDEST: f32 = 0.04;
+/// The exponents the color and the feature differences are compressed with.
+const FLIP_COLOR_EXPONENT: f32 = 0.7;
+const FLIP_FEATURE_EXPONENT: f32 = 0.5;
+/// The color differences below this share of the largest one are mapped to the lower part of
+/// the error range up to the given point, the larger ones to the rest.
+const FLIP_COLOR_CUTOFF: f32 = 0.4;
+const FLIP_ERROR_CUTOFF: f32 = 0.95;
+/// The width in degrees of the gaussian the edges and points are detected with.
+const FLIP_FEATURE_WIDTH: f32 = 0.082;
+
+/// The standard deviation in pixels of the gaussian window of the SSIM and its radius.
+const SSIM_SIGMA: f32 = 1.5;
+const SSIM_RADIUS: i32 = 5;
+/// Keep the SSIM stable in flat dark areas, for values in range \[0; 1].
+const SSIM_C1: f32 = 0.01 * 0.01;
+const SSIM_C2: f32 = 0.03 * 0.03;
+
+/// The colors of the error heatmap from no to the largest error, the magma color map FLIP uses.
+const HEATMAP_COLORS: [[f32; 3]; 5] = [[0.001, 0.0, 0.014], [0.317, 0.072, 0.485], [0.716, 0.215, 0.475],
+                                       [0.987, 0.536, 0.382], [0.987, 0.991, 0.75]];
+
+/// How much an image differs from a reference.
+pub struct ImageDifference {
+    /// The root mean square error of the linear colors.
+    pub rmse: f32,
+    /// The mean structural similarity of the luma of the displayed colors, 1 for equal images.
+    pub ssim: f32,
+    /// The mean FLIP error of the displayed colors, 0 for equal images and up to 1.
+    pub flip: f32,
+    /// The FLIP error of every pixel, from black for none to light yellow for the largest.
+    pub heatmap: DynamicImage,
+}
+
+/// Measures how much an image differs from a reference of the same size with three metrics:
+/// - the root mean square error of the linear colors, which averages out for noise, so that a
+///   bias shows as an error remaining with more samples,
+/// - the structural similarity (SSIM) of Wang et al. of the luma of the colors clipped to \[0; 1],
+/// - the mean of the LDR variant of FLIP by Andersson et al., which approximates how noticeable
+///   the differences are to a viewer flipping between the images, of the colors clipped to
+///   \[0; 1]. Its per pixel error is returned as a heatmap.
+///
+/// Float images are taken as linear colors, integer images are decoded with the given transfer
+/// function. The progress is reported per metric, and the comparison stops early with
+/// [LoadError::Cancelled] if it is cancelled.
+pub fn compare(image: &DynamicImage, reference: &DynamicImage, transfer_function: TransferFunction,
+               progress: &LoadProgress) -> Result<ImageDifference, LoadError> {
+    if (image.width(), image.height()) != (reference.width(), reference.height()) {
+        return Err(LoadError::Failed(format!("The image ({}×{}) and the reference ({}×{}) differ in size!",
+            image.width(), image.height(), reference.width(), reference.height())));
+    }
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let colors = linear_colors(image, transfer_function);
+    let reference_colors = linear_colors(reference, transfer_function);
+
+    let squared_error = colors.iter().zip(&reference_colors)
+        .flat_map(|(a, b)| (0..3).map(move |c| ((a[c] - b[c]) as f64).powi(2)))
+        .sum::<f64>();
+    let rmse = (squared_error / (colors.len() * 3).max(1) as f64).sqrt() as f32;
+    progress.set(0.1);
+
+    let clipped = colors.iter().map(|color| color.map(|c| c.clamp(0.0, 1.0))).collect::<Vec<_>>();
+    let reference_clipped = reference_colors.iter().map(|color| color.map(|c| c.clamp(0.0, 1.0))).collect::<Vec<_>>();
+    let ssim = ssim(&clipped, &reference_clipped, width, height);
+    if progress.is_cancelled() {
+        return Err(LoadError::Cancelled);
+    }
+    progress.set(0.3);
+
+    let errors = flip(&clipped, &reference_clipped, width, height);
+    if progress.is_cancelled() {
+        return Err(LoadError::Cancelled);
+    }
+    let flip = (errors.iter().map(|&e| e as f64).sum::<f64>() / errors.len().max(1) as f64) as f32;
+    let heatmap = errors.iter().flat_map(|&error| heatmap_color(error)).collect();
+    progress.set(1.0);
+
+    Ok(ImageDifference {
+        rmse,
+        ssim,
+        flip,
+        heatmap: RgbImage::from_raw(width as u32, height as u32, heatmap).unwrap().into(),
+    })
+}
+
+/// Returns the linear r, g, b values of every pixel, row by row.
+fn linear_colors(image: &DynamicImage, transfer_function: TransferFunction) -> Vec<[f32; 3]> {
+    let float = matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_));
+    image.to_rgb32f().pixels().map(|pixel| {
+        if float { pixel.0 } else { pixel.0.map(|c| transfer_function.decode(c)) }
+    }).collect()
+}
+
+/// Returns the mean structural similarity of the luma of the sRGB encoded colors, with a gaussian
+/// window around every pixel.
+fn ssim(colors: &[[f32; 3]], reference: &[[f32; 3]], width: usize, height: usize) -> f32 {
+    let luma = |colors: &[[f32; 3]]| colors.iter().map(|color| {
+        let [r, g, b] = color.map(|c| TransferFunction::Srgb.encode(c));
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }).collect::<Vec<f32>>();
+    let (x, y) = (luma(colors), luma(reference));
+    let window = gaussian_kernel(SSIM_SIGMA, SSIM_RADIUS);
+    let blur = |values: Vec<f32>| convolve_separable(&values, width, height, &window, &window);
+    let mean_x = blur(x.clone());
+    let mean_y = blur(y.clone());
+    let mean_xx = blur(x.iter().map(|v| v * v).collect());
+    let mean_yy = blur(y.iter().map(|v| v * v).collect());
+    let mean_xy = blur(x.iter().zip(&y).map(|(a, b)| a * b).collect());
+
+    let sum = (0..x.len()).map(|i| {
+        let (mx, my) = (mean_x[i], mean_y[i]);
+        let variance_x = mean_xx[i] - mx * mx;
+        let variance_y = mean_yy[i] - my * my;
+        let covariance = mean_xy[i] - mx * my;
+        ((2.0 * mx * my + SSIM_C1) * (2.0 * covariance + SSIM_C2)
+            / ((mx * mx + my * my + SSIM_C1) * (variance_x + variance_y + SSIM_C2))) as f64
+    }).sum::<f64>();
+    (sum / x.len().max(1) as f64) as f32
+}
+
+/// Returns the LDR FLIP error of every pixel, row by row. The colors are filtered like the eye
+/// blurs them at the viewing distance and compared in a perceptual color space, and differences of
+/// the edges and points of the luminance increase the error.
+fn flip(colors: &[[f32; 3]], reference: &[[f32; 3]], width: usize, height: usize) -> Vec<f32> {
+    let white = rgb_to_xyz([1.0; 3]);
+    let max_color_difference = hyab(hunt_lab(xyz_to_lab(rgb_to_xyz([0.0, 1.0, 0.0]), white)),
+                                    hunt_lab(xyz_to_lab(rgb_to_xyz([0.0, 0.0, 1.0]), white))).powf(FLIP_COLOR_EXPONENT);
+
+    let prepare = |colors: &[[f32; 3]]| {
+        let opponent = colors.iter().map(|&color| xyz_to_ycxcz(rgb_to_xyz(color), white)).collect::<Vec<_>>();
+        let filtered = spatial_filter(&opponent, width, height);
+        let lab = filtered.iter().map(|&ycxcz| {
+            let rgb = xyz_to_rgb(ycxcz_to_xyz(ycxcz, white)).map(|c| c.clamp(0.0, 1.0));
+            hunt_lab(xyz_to_lab(rgb_to_xyz(rgb), white))
+        }).collect::<Vec<_>>();
+        //the features are detected on the unfiltered luminance relative to white
+        let luminance = opponent.iter().map(|ycxcz| (ycxcz[0] + 16.0) / 116.0).collect::<Vec<_>>();
+        (lab, features(&luminance, width, height))
+    };
+    let (lab, (edges, points)) = prepare(colors);
+    let (reference_lab, (reference_edges, reference_points)) = prepare(reference);
+
+    (0..lab.len()).into_par_iter().map(|i| {
+        let difference = hyab(lab[i], reference_lab[i]).powf(FLIP_COLOR_EXPONENT);
+        let color_error = if difference < FLIP_COLOR_CUTOFF * max_color_difference {
+            FLIP_ERROR_CUTOFF / (FLIP_COLOR_CUTOFF * max_color_difference) * difference
+        } else {
+            FLIP_ERROR_CUTOFF + (difference - FLIP_COLOR_CUTOFF * max_color_difference)
+                / (max_color_difference - FLIP_COLOR_CUTOFF * max_color_difference) * (1.0 - FLIP_ERROR_CUTOFF)
+        };
+        let feature_difference = (edges[i] - reference_edges[i]).abs().max((points[i] - reference_points[i]).abs());
+        let feature_error = (feature_difference / 2f32.sqrt()).powf(FLIP_FEATURE_EXPONENT);
+        color_error.powf(1.0 - feature_error)
+    }).collect()
+}
+
+/// Filters the channels of the colors in the YCxCz space with the contrast sensitivity functions
+/// of the eye, keeping only the detail visible at [FLIP_PIXELS_PER_DEGREE]. Every function is a
+/// sum of gaussians, so it is applied as a sum of separable blurs.
+fn spatial_filter(colors: &[[f32; 3]], width: usize, height: usize) -> Vec<[f32; 3]> {
+    let radius = (3.0 * (FLIP_CSF_WIDEST / (2.0 * PI * PI)).sqrt() * FLIP_PIXELS_PER_DEGREE).ceil() as i32;
+    let channels = [&FLIP_CSF_ACHROMATIC[..], &FLIP_CSF_RED_GREEN[..], &FLIP_CSF_BLUE_YELLOW[..]]
+        .into_par_iter().enumerate().map(|(channel, terms)| {
+        let values = colors.iter().map(|color| color[channel]).collect::<Vec<f32>>();
+        let mut filtered = vec![0.0; values.len()];
+        let mut normalization = 0.0;
+        for &(a, b) in terms {
+            let kernel = (-radius..=radius).map(|x| {
+                let degrees = x as f32 / FLIP_PIXELS_PER_DEGREE;
+                (-PI * PI * degrees * degrees / b).exp()
+            }).collect::<Vec<f32>>();
+            let weight = a * (PI / b).sqrt();
+            normalization += weight * kernel.iter().sum::<f32>().powi(2);
+            let blurred = convolve_separable(&values, width, height, &kernel, &kernel);
+            filtered.iter_mut().zip(blurred).for_each(|(f, v)| *f += weight * v);
+        }
+        filtered.iter().map(|f| f / normalization).collect::<Vec<f32>>()
+    }).collect::<Vec<_>>();
+    (0..colors.len()).map(|i| [channels[0][i], channels[1][i], channels[2][i]]).collect()
+}
+
+/// Returns the strength of the edges and of the points of the luminance of every pixel, from the
+/// first and second derivatives of a gaussian along both axes.
+fn features(luminance: &[f32], width: usize, height: usize) -> (Vec<f32>, Vec<f32>) {
+    let sigma = 0.5 * FLIP_FEATURE_WIDTH * FLIP_PIXELS_PER_DEGREE;
+    let radius = (3.0 * sigma).ceil() as i32;
+    let gaussian = gaussian_kernel(sigma, radius);
+    let edge = normalize_signed((-radius..=radius).zip(&gaussian).map(|(x, g)| -x as f32 * g).collect());
+    let point = normalize_signed((-radius..=radius).zip(&gaussian)
+        .map(|(x, g)| ((x * x) as f32 / (sigma * sigma) - 1.0) * g).collect());
+
+    let magnitude = |kernel: &[f32]| {
+        let along_x = convolve_separable(luminance, width, height, kernel, &gaussian);
+        let along_y = convolve_separable(luminance, width, height, &gaussian, kernel);
+        along_x.iter().zip(along_y).map(|(x, y)| (x * x + y * y).sqrt()).collect::<Vec<f32>>()
+    };
+    (magnitude(&edge), magnitude(&point))
+}
+
+/// Returns a gaussian with the given standard deviation over the pixels up to the radius,
+/// normalized to a sum of 1.
+fn gaussian_kernel(sigma: f32, radius: i32) -> Vec<f32> {
+    let kernel = (-radius..=radius).map(|x| (-((x * x) as f32) / (2.0 * sigma * sigma)).exp()).collect::<Vec<f32>>();
+    let sum = kernel.iter().sum::<f32>();
+    kernel.into_iter().map(|k| k / sum).collect()
+}
+
+/// Scales the positive weights of a kernel to a sum of 1 and the negative ones to a sum of -1.
+fn normalize_signed(kernel: Vec<f32>) -> Vec<f32> {
+    let positive = kernel.iter().filter(|&&k| k > 0.0).sum::<f32>();
+    let negative = -kernel.iter().filter(|&&k| k < 0.0).sum::<f32>();
+    kernel.into_iter().map(|k| if k > 0.0 { k / positive } else if k < 0.0 { k / negative } else { 0.0 }).collect()
+}
+
+/// Convolves the values of an image with one kernel along the rows and another along the
+/// columns. Both kernels are centered, pixels beyond the border repeat the border pixels.
+fn convolve_separable(values: &[f32], width: usize, height: usize, horizontal: &[f32], vertical: &[f32]) -> Vec<f32> {
+    let convolve = |values: &[f32], width: usize, kernel: &[f32]| {
+        let radius = (kernel.len() / 2) as isize;
+        let mut result = vec![0.0; values.len()];
+        result.par_chunks_mut(width).zip(values.par_chunks(width)).for_each(|(result_row, row)| {
+            for (x, result) in result_row.iter_mut().enumerate() {
+                *result = kernel.iter().enumerate().map(|(k, weight)| {
+                    let source = (x as isize + k as isize - radius).clamp(0, width as isize - 1) as usize;
+                    weight * row[source]
+                }).sum();
+            }
+        });
+        result
+    };
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let rows = convolve(values, width, horizontal);
+    let transposed = transpose(&rows, width, height);
+    transpose(&convolve(&transposed, height, vertical), height, width)
+}
+
+/// Swaps the rows and columns of an image of the given width and height.
+fn transpose(values: &[f32], width: usize, height: usize) -> Vec<f32> {
+    (0..width).flat_map(|x| (0..height).map(move |y| values[y * width + x])).collect()
+}
+
+fn rgb_to_xyz([r, g, b]: [f32; 3]) -> [f32; 3] {
+    [0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+     0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+     0.0193339 * r + 0.119192 * g + 0.9503041 * b]
+}
+
+fn xyz_to_rgb([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+     -0.969266 * x + 1.8760108 * y + 0.0415560 * z,
+     0.0556434 * x - 0.2040259 * y + 1.0572252 * z]
+}
+
+/// The linear opponent color space of FLIP: the lightness and two color differences.
+fn xyz_to_ycxcz([x, y, z]: [f32; 3], white: [f32; 3]) -> [f32; 3] {
+    let (x, y, z) = (x / white[0], y / white[1], z / white[2]);
+    [116.0 * y - 16.0, 500.0 * (x - y), 200.0 * (y - z)]
+}
+
+fn ycxcz_to_xyz([luminance, cx, cz]: [f32; 3], white: [f32; 3]) -> [f32; 3] {
+    let y = (luminance + 16.0) / 116.0;
+    [(cx / 500.0 + y) * white[0], y * white[1], (y - cz / 200.0) * white[2]]
+}
+
+fn xyz_to_lab(xyz: [f32; 3], white: [f32; 3]) -> [f32; 3] {
+    let delta = 6.0f32 / 29.0;
+    let f = |t: f32| if t > delta.powi(3) { t.cbrt() } else { t / (3.0 * delta * delta) + 4.0 / 29.0 };
+    let [fx, fy, fz] = [0, 1, 2].map(|c| f(xyz[c] / white[c]));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// The Hunt effect: colors appear less saturated the darker they are.
+fn hunt_lab([lightness, a, b]: [f32; 3]) -> [f32; 3] {
+    [lightness, 0.01 * lightness * a, 0.01 * lightness * b]
+}
+
+/// The HyAB distance, which suits large color differences better than the euclidean one.
+fn hyab(first: [f32; 3], second: [f32; 3]) -> f32 {
+    (first[0] - second[0]).abs() + ((first[1] - second[1]).powi(2) + (first[2] - second[2]).powi(2)).sqrt()
+}
+
+/// Maps an error in range \[0; 1] to the colors of the heatmap.
+fn heatmap_color(error: f32) -> [u8; 3] {
+    let position = error.clamp(0.0, 1.0) * (HEATMAP_COLORS.len() - 1) as f32;
+    let index = (position as usize).min(HEATMAP_COLORS.len() - 2);
+    let t = position - index as f32;
+    let (low, high) = (HEATMAP_COLORS[index], HEATMAP_COLORS[index + 1]);
+    [0, 1, 2].map(|c| ((low[c] + (high[c] - low[c]) * t) * 255.0).round() as u8)
+}
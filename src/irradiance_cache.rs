@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use nalgebra::{Point3, Vector3};
+use crate::radiometry::SpectralIrradiance;
+use crate::spectrum::Spectrum;
+
+/// The indirect irradiance arriving at one point of a surface, computed by tracing many rays into
+/// the hemisphere above it.
+pub struct IrradianceRecord {
+    pub position: Point3<f32>,
+    pub normal: Vector3<f32>,
+    pub irradiance: SpectralIrradiance,
+    /// The harmonic mean distance to the surfaces seen from the point. Close surfaces make the
+    /// irradiance change quickly, so the record is valid in a smaller area.
+    pub mean_distance: f32,
+}
+
+/// A cache of the indirect irradiance at sparse points of the surfaces, interpolated in between.
+/// The irradiance changes slowly across diffuse surfaces, so tracing the bounce rays at every
+/// hit is mostly wasted work. <br>
+/// A record is used at a point as long as the error estimate of Ward et al. stays below the
+/// maximum error: the distance to the record relative to its mean distance plus the difference
+/// of the normals. Each record is sorted into all cells of a grid it is valid in, the cells being
+/// at least half as large as the valid area, so that it touches at most 27 of them. The grids of
+/// the different cell sizes are kept in one map, looking up a point only takes one cell per size.
+/// <br>
+/// A Ray Tracing Solution for Diffuse Interreflection, Ward et al., 1988
+pub struct IrradianceCache {
+    max_error: f32,
+    records: Vec<IrradianceRecord>,
+    cells: HashMap<(i32, i32, i32, i32), Vec<usize>>,
+    levels: Vec<i32>,
+}
+
+impl IrradianceCache {
+    /// Creates an empty cache. The maximum error has to be positive.
+    pub fn new(max_error: f32) -> Self {
+        IrradianceCache { max_error, records: Vec::new(), cells: HashMap::new(), levels: Vec::new() }
+    }
+
+    /// Adds the records to the cache, sorting them into the grids.
+    pub fn insert(&mut self, records: Vec<IrradianceRecord>) {
+        for record in records {
+            let index = self.records.len();
+            let radius = record.mean_distance * self.max_error;
+            let level = level_of(radius / 2.0);
+            if !self.levels.contains(&level) {
+                self.levels.push(level);
+            }
+            let (_, x_min, y_min, z_min) = cell_of(&(record.position - Vector3::repeat(radius)), level);
+            let (_, x_max, y_max, z_max) = cell_of(&(record.position + Vector3::repeat(radius)), level);
+            for x in x_min..=x_max {
+                for y in y_min..=y_max {
+                    for z in z_min..=z_max {
+                        self.cells.entry((level, x, y, z)).or_default().push(index);
+                    }
+                }
+            }
+            self.records.push(record);
+        }
+    }
+
+    pub fn get_max_error(&self) -> f32 {
+        self.max_error
+    }
+
+    /// Whether a record lies well within its valid area at the given point of a surface with the 
+    /// given normal, so that no further record is needed there.
+    pub fn is_covered(&self, point: &Point3<f32>, normal: &Vector3<f32>) -> bool {
+        self.valid_records(point, normal).any(|(_, error)| error < self.max_error * 0.75)
+    }
+
+    /// Interpolates the indirect irradiance at the given point of a surface with the given normal
+    /// from all valid records, the closer ones weighted more. Returns None if no record is valid
+    /// here, the irradiance has to be computed then.
+    pub fn irradiance_at(&self, point: &Point3<f32>, normal: &Vector3<f32>, example_spectrum: &Spectrum)
+        -> Option<SpectralIrradiance> {
+        let mut irradiance = SpectralIrradiance::zero(example_spectrum);
+        let mut weight_sum = 0.0;
+        for (record, error) in self.valid_records(point, normal) {
+            //the weight falls off to 0 at the border of the valid area, avoiding visible seams
+            let weight = 1.0 - error / self.max_error;
+            let mut weighted = record.irradiance;
+            weighted *= weight;
+            irradiance += &weighted;
+            weight_sum += weight;
+        }
+        if weight_sum <= 0.0 {
+            return None;
+        }
+        irradiance *= 1.0 / weight_sum;
+        Some(irradiance)
+    }
+
+    /// Returns the records valid at the given point of a surface with the given normal, together 
+    /// with their error estimate there. 
+    fn valid_records<'a>(&'a self, point: &'a Point3<f32>, normal: &'a Vector3<f32>) 
+        -> impl Iterator<Item = (&'a IrradianceRecord, f32)> + 'a {
+        self.levels.iter()
+            .filter_map(|level| self.cells.get(&cell_of(point, *level)))
+            .flatten()
+            .map(|index| &self.records[*index])
+            .filter_map(move |record| {
+                let offset = point - record.position;
+                //records on a surface in front of the point see a different hemisphere
+                if offset.dot(&(record.normal + normal)) < -0.1 * record.mean_distance {
+                    return None;
+                }
+                let error = offset.magnitude() / record.mean_distance
+                    + (1.0 - normal.dot(&record.normal)).max(0.0).sqrt();
+                (error < self.max_error).then_some((record, error))
+            })
+    }
+}
+
+/// Returns the level of the grid whose cells are at least as large as the given radius. The cells
+/// of level l have an edge length of 2^l.
+fn level_of(radius: f32) -> i32 {
+    radius.max(f32::MIN_POSITIVE).log2().ceil() as i32
+}
+
+/// Returns the cell of the grid of the given level the point lies in.
+fn cell_of(point: &Point3<f32>, level: i32) -> (i32, i32, i32, i32) {
+    let size = 2.0f32.powi(level);
+    (level, (point.x / size).floor() as i32, (point.y / size).floor() as i32, (point.z / size).floor() as i32)
+}
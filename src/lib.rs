@@ -0,0 +1,40 @@
+//! The spectral renderer without its user interface. A [Scene](scene::Scene) of objects, lights
+//! and a camera is rendered with [Scene::render](scene::Scene::render) into a
+//! [CustomImage](custom_image::CustomImage) of linear RGB values with alpha, which can be converted
+//! into an image of the `image` crate. All light is handled as spectra (see [spectrum] and
+//! [radiometry]) and only converted into RGB when a sample is written into the image. <br>
+//! The lower level building blocks, such as the shaders, the bounding volume hierarchy or the
+//! per-frame [RaytracingUniforms](shader::RaytracingUniforms), are public as well, so that an
+//! application can drive the render loop itself, e.g. to display the image while it converges.
+
+pub mod atmosphere;
+pub mod bloom;
+pub mod bsdf;
+pub mod bvh;
+pub mod csg;
+pub mod custom_image;
+pub mod denoise;
+pub mod false_color;
+pub mod gamut;
+pub mod heightfield;
+pub mod image_compare;
+pub mod irradiance_cache;
+pub mod light_selection;
+pub mod loader;
+pub mod medium;
+#[cfg(feature = "oidn")]
+pub mod oidn;
+pub mod photon_map;
+pub mod platform;
+pub mod post_effects;
+pub mod primitive;
+pub mod procedural;
+pub mod profiler;
+pub mod radiometry;
+pub mod sampler;
+pub mod scene;
+pub mod sensor;
+pub mod shader;
+pub mod spectral_data;
+pub mod spectrum;
+pub mod texture;
@@ -0,0 +1,146 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::shader::Light;
+
+/// The strategies to choose which light sources are sampled for the direct light at a hit.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LightSelection {
+    /// Every light is sampled at every hit. Exact, but the cost grows with the number of lights.
+    All,
+    /// A fixed number of lights is drawn at random, all with the same probability.
+    Uniform,
+    /// A fixed number of lights is drawn at random, each with a probability proportional to its
+    /// emitted power. Bright lights are sampled more often, dim ones rarely.
+    Power,
+}
+
+impl Display for LightSelection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LightSelection::All => write!(f, "All lights"),
+            LightSelection::Uniform => write!(f, "Uniform random"),
+            LightSelection::Power => write!(f, "Power weighted"),
+        }
+    }
+}
+
+/// The probabilities with which the lights of a scene are drawn. The contribution of a drawn
+/// light has to be divided by its probability, which keeps the estimate unbiased.
+pub struct LightDistribution {
+    /// The cumulative probability up to and including each light, the last one being 1.
+    cdf: Vec<f32>,
+}
+
+impl LightDistribution {
+    /// Creates the distribution for the given lights. Power weighting falls back to uniform
+    /// probabilities if none of the lights emits anything.
+    pub fn new(lights: &[Light], selection: LightSelection) -> Self {
+        let mut weights: Vec<f32> = match selection {
+            LightSelection::Power => lights.iter().map(|l| l.get_power().max(0.0)).collect(),
+            LightSelection::All | LightSelection::Uniform => vec![1.0; lights.len()],
+        };
+        let mut total: f32 = weights.iter().sum();
+        if total <= 0.0 || !total.is_finite() {
+            weights.fill(1.0);
+            total = weights.len() as f32;
+        }
+
+        let mut sum = 0.0;
+        let mut cdf: Vec<f32> = weights.iter().map(|w| {
+            sum += w / total;
+            sum
+        }).collect();
+        //rounding must not leave a gap at the end
+        if let Some(last) = cdf.last_mut() {
+            *last = 1.0;
+        }
+        LightDistribution {cdf}
+    }
+
+    /// Draws a light with the given random value in \[0; 1) and returns its index together with
+    /// the probability it had to be drawn. None if there are no lights.
+    pub fn sample(&self, random: f32) -> Option<(usize, f32)> {
+        if self.cdf.is_empty() {
+            return None;
+        }
+        let index = self.cdf.partition_point(|c| *c <= random).min(self.cdf.len() - 1);
+        Some((index, self.probability(index)))
+    }
+
+    /// The probability of the light with the given index to be drawn.
+    pub fn probability(&self, index: usize) -> f32 {
+        let previous = if index == 0 { 0.0 } else { self.cdf[index - 1] };
+        self.cdf[index] - previous
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Point3;
+    use crate::radiometry::SpectralIntensity;
+    use crate::shader::F32_DELTA;
+    use crate::spectrum::{self, Spectrum};
+    use super::*;
+
+    /// Lights with flat spectra of the given intensities.
+    fn lights(intensities: &[f32]) -> Vec<Light> {
+        intensities.iter().map(|intensity| {
+            let spectrum = Spectrum::new_singular_reflectance_factor(spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+                                                                      spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+                                                                      32, *intensity);
+            Light::new(Point3::origin(), SpectralIntensity::new(spectrum))
+        }).collect()
+    }
+
+    /// The share of evenly spaced random values which draw each light.
+    fn sampled_shares(distribution: &LightDistribution, nbr_of_lights: usize) -> Vec<f32> {
+        const NBR_OF_SAMPLES: usize = 10000;
+        let mut counts = vec![0; nbr_of_lights];
+        for i in 0..NBR_OF_SAMPLES {
+            let (index, probability) = distribution.sample(i as f32 / NBR_OF_SAMPLES as f32).unwrap();
+            assert_eq!(probability, distribution.probability(index));
+            counts[index] += 1;
+        }
+        counts.iter().map(|count| *count as f32 / NBR_OF_SAMPLES as f32).collect()
+    }
+
+    #[test]
+    fn test_power_weighted_probabilities() {
+        let distribution = LightDistribution::new(&lights(&[1.0, 3.0, 0.0, 4.0]), LightSelection::Power);
+        let expected = [0.125, 0.375, 0.0, 0.5];
+        for (index, (expected, share)) in expected.iter().zip(sampled_shares(&distribution, 4)).enumerate() {
+            assert!((distribution.probability(index) - expected).abs() < F32_DELTA,
+                    "Light {index} has the probability {} instead of {expected}!", distribution.probability(index));
+            assert!((share - expected).abs() < 0.001, "Light {index} was drawn {share} of the time instead of {expected}!");
+        }
+    }
+
+    #[test]
+    fn test_uniform_probabilities() {
+        let intensities = [1.0, 3.0, 0.0, 4.0, 2.0];
+        for selection in [LightSelection::Uniform, LightSelection::All] {
+            let distribution = LightDistribution::new(&lights(&intensities), selection);
+            for (index, share) in sampled_shares(&distribution, intensities.len()).iter().enumerate() {
+                assert!((distribution.probability(index) - 0.2).abs() < F32_DELTA);
+                assert!((share - 0.2).abs() < 0.001, "Light {index} was drawn {share} of the time instead of 0.2!");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dark_lights_fall_back_to_uniform() {
+        let distribution = LightDistribution::new(&lights(&[0.0, 0.0]), LightSelection::Power);
+        assert!((distribution.probability(0) - 0.5).abs() < F32_DELTA);
+        assert!((distribution.probability(1) - 0.5).abs() < F32_DELTA);
+    }
+
+    #[test]
+    fn test_sample_bounds() {
+        assert!(LightDistribution::new(&[], LightSelection::Power).sample(0.5).is_none());
+        let distribution = LightDistribution::new(&lights(&[1.0, 1.0, 1.0]), LightSelection::Power);
+        assert_eq!(distribution.sample(0.0).unwrap().0, 0);
+        assert_eq!(distribution.sample(0.999_999).unwrap().0, 2);
+        //values of 1 occur through rounding and must still draw a light
+        assert_eq!(distribution.sample(1.0).unwrap().0, 2);
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
+use crate::platform;
+
+/// Shared state between a [BackgroundLoad] and the thread doing the work. The loading function
+/// reports its progress here and should regularly check whether it was cancelled.
+pub struct LoadProgress {
+    /// The progress from 0 to 1, stored as the bits of an f32 to allow atomic access.
+    progress: AtomicU32,
+    cancelled: AtomicBool,
+}
+
+impl LoadProgress {
+    fn new() -> Self {
+        LoadProgress {
+            progress: AtomicU32::new(0.0f32.to_bits()),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the progress of the loading process, values are clamped to [0, 1].
+    pub fn set(&self, progress: f32) {
+        self.progress.store(progress.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the progress of the loading process between 0 and 1.
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    /// Returns true if the user requested the loading process to stop. The loading function
+    /// should then return [LoadError::Cancelled] as soon as possible.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The reasons a [BackgroundLoad] can finish without a result.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The loading process was cancelled by the user.
+    Cancelled,
+    /// The asset could not be loaded, the String describes why.
+    Failed(String),
+}
+
+/// An asset import running on its own thread, so that even large files do not block the UI. The
+/// UI polls the load every frame, displays its progress and may cancel it.
+pub struct BackgroundLoad<T> {
+    name: String,
+    progress: Arc<LoadProgress>,
+    receiver: mpsc::Receiver<Result<T, LoadError>>,
+}
+
+impl<T: Send + 'static> BackgroundLoad<T> {
+    /// Starts the given loading function on a new thread. The name describes the asset to the
+    /// user.
+    pub fn start<F>(name: String, load: F) -> Self
+    where F: FnOnce(&LoadProgress) -> Result<T, LoadError> + Send + 'static
+    {
+        let progress = Arc::new(LoadProgress::new());
+        let (sender, receiver) = mpsc::channel();
+
+        let thread_progress = progress.clone();
+        platform::spawn(move || {
+            let result = load(&thread_progress);
+            //the receiving end is gone if the load was dropped, nobody is interested anymore
+            let _ = sender.send(result);
+        });
+
+        BackgroundLoad { name, progress, receiver }
+    }
+
+    /// Returns the result if the loading process has finished, otherwise None. A thread which
+    /// terminated without result (e.g. by panicking) is reported as [LoadError::Failed].
+    pub fn poll(&self) -> Option<Result<T, LoadError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err(LoadError::Failed("The loading thread terminated unexpectedly!".to_string())))
+            }
+        }
+    }
+
+    /// Requests the loading process to stop. The result will be [LoadError::Cancelled] unless the
+    /// load finishes before noticing.
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_progress(&self) -> f32 {
+        self.progress.get()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+}
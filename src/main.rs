@@ -1,38 +1,143 @@
 //#![windows_subsystem = "windows"] //<- completely disables std::in/out/err. Uncomment only for final versions
 
-mod shader;
-mod custom_image;
-mod spectrum;
-mod spectral_data;
+mod accumulation;
+mod checkpoint;
+mod cli;
+mod conversion;
+mod distributed;
+mod gallery;
+mod hierarchy;
+mod pbrt_export;
+mod progress;
+mod ray_dump;
+mod remote_control;
+mod render_estimate;
+mod scene_check;
+mod scene_file;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod spectrum_file;
 mod text_resources;
+mod tutorial;
 
 use std::cell::RefCell;
 use std::cmp::PartialEq;
 use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::{mpsc, Arc, Mutex};
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc::Receiver;
 use std::thread;
-use std::time::{Duration, Instant, UNIX_EPOCH};
+use std::time::Duration;
 use eframe::egui;
 use eframe::egui::{menu, Color32, ComboBox, IconData, Sense, TextEdit, TopBottomPanel, Ui, UiBuilder};
 use eframe::epaint::Vec2;
+use egui_dock::{DockArea, DockState, TabViewer};
 use image::DynamicImage;
-use log::{error, warn};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
 use nalgebra::Vector3;
-use threadpool::ThreadPool;
-use crate::shader::{PixelPos, RaytracingUniforms};
-use crate::spectrum::Spectrum;
+use rayon::prelude::*;
+use eframe_raytracing::{atmosphere, bloom, bvh, csg, custom_image, denoise, false_color, gamut, heightfield, image_compare, light_selection, loader, medium, platform, 
+                        post_effects, procedural, radiometry, sampler, scene, sensor, shader, spectral_data, spectrum, texture};
+#[cfg(feature = "profiling")]
+use eframe_raytracing::profiler;
+#[cfg(feature = "oidn")]
+use eframe_raytracing::oidn;
+use crate::accumulation::Accumulation;
+use crate::atmosphere::Atmosphere;
+use crate::bloom::Bloom;
+use crate::bvh::Bvh;
+use crate::checkpoint::{Checkpoint, CheckpointWriter, CHECKPOINT_FILE_NAME};
+use crate::csg::CsgOperation;
+use crate::custom_image::{FireflyFilter, ToneMapper, TransferFunction};
+use crate::false_color::{FalseColor, FalseColorMap};
+use crate::distributed::{RemoteWorker, WorkerSetup};
+use crate::gallery::{Gallery, RenderSnapshot};
+use crate::heightfield::{HeightGrid, HeightfieldSource};
+use crate::light_selection::LightSelection;
+use crate::platform::{Instant, UNIX_EPOCH};
+use crate::post_effects::PostEffect;
+use crate::loader::{BackgroundLoad, LoadError, LoadProgress};
+use crate::procedural::{Axis, Pattern};
+use crate::progress::{ProgressTracker, RenderProgress};
+use crate::remote_control::{RemoteCommand, RemoteControl, RemoteReply, RemoteStatus, DEFAULT_REMOTE_CONTROL_PORT};
+use crate::render_estimate::RenderEstimate;
+use crate::sampler::SamplerType;
+use crate::scene::{RenderBucket, RenderSettings, Scene, NBR_OF_ITERATIONS_DEFAULT, NBR_OF_SPECTRUM_SAMPLES_DEFAULT, 
+                   NEW_RAY_MAX_BOUNCES_DEFAULT, NEW_RAY_MAX_DIFFUSE_BOUNCES_DEFAULT, NEW_RAY_MAX_SPECULAR_BOUNCES_DEFAULT, NEW_RAY_MAX_TRANSMISSION_BOUNCES_DEFAULT, 
+                   PHOTON_RADIUS_DEFAULT, RAY_OFFSET_DEFAULT, TileAovs};
+use crate::scene_file::SceneWatch;
+use crate::sensor::SensorResponse;
+use crate::shader::{Background, LightLinkMode, MixWeight, PixelPos, RayStatistics, RaytracingUniforms};
+use crate::spectrum::{Observer, Spectrum};
 use crate::text_resources::*;
+use crate::texture::{Texture, TextureCache};
+use crate::tutorial::{Tutorial, TutorialProgress};
 
 const NBR_OF_THREADS_DEFAULT: usize = 20;
 const NBR_OF_THREADS_MAX: usize = 64;
-const NBR_OF_ITERATIONS_DEFAULT: u32 = 100;
-const NBR_OF_SPECTRUM_SAMPLES_DEFAULT: usize = 32;
-const NEW_RAY_MAX_BOUNCES_DEFAULT: u32 = 30;
 const NEW_RAY_MAX_BOUNCES_MAX: u32 = 100;
 const MAX_CHARS_IN_NAME_STRING: usize = 40;
+const FIREFLY_CLAMP_DEFAULT: f32 = 10.0;
+const FIREFLY_OUTLIER_FACTOR_DEFAULT: f32 = 8.0;
+/// The width in pixels of the probe render used to balance the lights.
+const LIGHT_BALANCE_PROBE_WIDTH: u32 = 64;
+const LIGHT_BALANCE_PROBE_FRAMES: u32 = 8;
+/// The average luminance the light balance aims for, the middle gray of photography.
+const LIGHT_BALANCE_TARGET_LUMINANCE: f32 = 0.18;
+const CROP_INSPECTION_SIZE: u32 = 512;
+/// The share of the pixels whose noise is reported below the heatmap, the rest being the noisiest. 
+const NOISE_ERROR_QUANTILE: f32 = 0.95;
+/// The relative error of the pixels at which an image counts as converged. 
+const NOISE_TARGET_ERROR: f32 = 0.01;
+/// The distances shown white and black in the depth view until the user changes them. 
+const DEPTH_NEAR_DEFAULT: f32 = 0.0;
+const DEPTH_FAR_DEFAULT: f32 = 20.0;
+/// The narrowest range in nanometers the spectra can be restricted to. 
+const SPECTRUM_RANGE_WIDTH_MIN: f32 = 10.0;
+/// The most stops the displayed image can be brightened or darkened by. 
+const EXPOSURE_MAX: f32 = 10.0;
+/// The key under which the tab layout is stored between sessions.
+const DOCK_STATE_STORAGE_KEY: &str = "dock_state";
+/// The key under which the render settings are stored between sessions, see [StoredSettings].
+const SETTINGS_STORAGE_KEY: &str = "render_settings";
+/// The colors the spectra in the spectrum comparison are drawn in, repeating if necessary.
+const COMPARISON_PLOT_COLORS: [Color32; 8] = [
+    Color32::from_rgb(31, 119, 180), Color32::from_rgb(255, 127, 14), Color32::from_rgb(44, 160, 44),
+    Color32::from_rgb(214, 39, 40), Color32::from_rgb(148, 103, 189), Color32::from_rgb(140, 86, 75),
+    Color32::from_rgb(227, 119, 194), Color32::from_rgb(23, 190, 207),
+];
+/// The largest width in points the heatmap of a comparison is shown with. 
+const COMPARISON_HEATMAP_WIDTH: f32 = 480.0;
+/// The height in points of the plot of the spectrum in the pixel probe. 
+const PIXEL_PROBE_PLOT_HEIGHT: f32 = 180.0;
+/// The highest relative power of the CIE D65 illuminant, at 460nm.
+const D65_RELATIVE_POWER_MAX: f32 = 117.812;
+/// More octaves of heightfield noise add details finer than the grid resolves.
+const HEIGHTFIELD_OCTAVES_MAX: u32 = 8;
+/// Every octave of pattern noise is evaluated at every hit, further ones are rarely visible.
+const PATTERN_NOISE_OCTAVES_MAX: u32 = 10;
+/// Every photon is kept in memory for a whole frame, each taking up about half a kilobyte.
+const PHOTON_COUNT_MAX: u32 = 1_000_000;
+const IRRADIANCE_CACHE_QUALITY_DEFAULT: f32 = 0.5;
+/// The clipping distances proposed when a clipping plane is enabled, the far one beyond the near 
+/// one. 
+const CAMERA_NEAR_CLIP_DEFAULT: f32 = 1.0;
+const CAMERA_FAR_CLIP_DEFAULT: f32 = 10.0;
+/// How often the render threads check whether a paused render was resumed. 
+const RENDER_PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// The number of iterations renders started by a change of the watched scene file are limited to, 
+/// so that the feedback arrives quickly. 
+const SCENE_WATCH_PREVIEW_ITERATIONS: u32 = 16;
+/// The smallest angle in degrees the view direction keeps to the up vector while navigating, 
+/// looking straight up or down would leave the right vector undefined. 
+const CAMERA_NAVIGATION_PITCH_MARGIN_DEG: f32 = 1.0;
+/// How many times faster the camera moves while shift is held during the navigation. 
+const CAMERA_NAVIGATION_FAST_FACTOR: f32 = 5.0;
 
 static COUNTER: AtomicU32 = AtomicU32::new(1);
 fn get_id() -> u32 { COUNTER.fetch_add(1, core::sync::atomic::Ordering::Relaxed) }
@@ -49,6 +154,16 @@ fn main() -> eframe::Result {
     std::env::set_var("RUST_LOG", "info");
     env_logger::init();
 
+    //with arguments, a command line subcommand is run instead of opening the window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = cli::run(&args) {
+            eprintln!("{}", e.error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     //Set up the window which will be opened
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -65,35 +180,204 @@ fn main() -> eframe::Result {
         Box::new(|cc| {
             //image support
             egui_extras::install_image_loaders(&cc.egui_ctx);
-            Ok(Box::new(App::new()))
+            Ok(Box::new(App::new(cc.storage)))
         })
     )
 }
 
-//TODO implement serialization and deserialization of settings via the serde crate
-
 /// Struct that forms the main data of the app. The struct contains data such as the generated 
 /// images or the values input into the UI. 
 struct App {
     ui_values: UIFields,
     image_actual: Option<DynamicImage>,
+    /// The linear colors of the current or last render as floats, for saving them as HDR. None 
+    /// while a render of the gallery is shown. 
+    image_float: Option<custom_image::CustomImage>,
     image_eframe_texture: Option<egui::TextureHandle>,
+    /// By how much the displayed texture is smaller than the actual image. Only Some if the image 
+    /// exceeds the maximum texture size of the graphics backend.
+    image_downsample_factor: Option<f32>,
+    /// The image pixel around which a full resolution crop is displayed, if any. 
+    image_crop_center: Option<(u32, u32)>,
+    /// The pixel last clicked in the image, whose spectrum is shown by the pixel probe while the 
+    /// cursor is not over the image, see [display_pixel_probe](App::display_pixel_probe). None 
+    /// while the probe is closed. 
+    probed_pixel: Option<(u32, u32)>,
+    image_crop_texture: Option<egui::TextureHandle>,
+    /// The shading normals the camera sees first in the current or last render as a float image, 
+    /// see [CustomImage::get_normal_image](custom_image::CustomImage::get_normal_image). 
+    image_normals: Option<DynamicImage>,
+    /// The distances from the camera to what it sees first in the current or last render as a 
+    /// float image, see [CustomImage::get_depth_image](custom_image::CustomImage::get_depth_image). 
+    image_depth: Option<DynamicImage>,
+    /// The reflectances the camera sees first in the current or last render as a float image, see 
+    /// [CustomImage::get_albedo_image](custom_image::CustomImage::get_albedo_image). 
+    image_albedo: Option<DynamicImage>,
+    /// The id colors of the objects the camera sees first in the current or last render as a 
+    /// float image, see [CustomImage::get_id_image](custom_image::CustomImage::get_id_image). 
+    image_ids: Option<DynamicImage>,
+    /// The light of every light source in the current or last render as float images, see 
+    /// [CustomImage::get_light_pass_images](custom_image::CustomImage::get_light_pass_images). 
+    /// Empty unless light passes are recorded. 
+    image_light_passes: Vec<DynamicImage>,
+    /// The names of the lights of the light passes, taken when the render was started. 
+    light_pass_names: Vec<String>,
+    /// The relative error which [NOISE_ERROR_QUANTILE] of the pixels stay below, only known while 
+    /// the noise heatmap is shown. 
+    noise_error: Option<f32>,
     actions: Arc<Mutex<Vec<AppActions>>>,
     currently_rendering: Arc<Mutex<bool>>,
+    /// Whether the render threads wait instead of starting new tiles. 
+    render_paused: Arc<AtomicBool>,
+    /// Whether the render threads stop after their current row, set when the render is aborted. 
+    render_cancelled: Arc<AtomicBool>,
+    active_buckets: Arc<Mutex<Vec<RenderBucket>>>,
+    /// The raw samples of the current or last render, shared with the render thread so that it 
+    /// can be exported at any time. 
+    accumulation: Arc<Mutex<Option<Accumulation>>>,
+    /// A sensor response which is currently imported in the background, if any.
+    sensor_response_load: Option<BackgroundLoad<SensorResponse>>,
+    /// A low resolution probe render measuring the average luminance of the scene, if any. 
+    light_balance_probe: Option<BackgroundLoad<f32>>,
+    /// The last frame being denoised in the background, if any. 
+    denoise_run: Option<BackgroundLoad<custom_image::CustomImage>>,
+    /// The last frame denoised by Open Image Denoise, shown instead of the frame while 
+    /// [show_oidn](UIFields::show_oidn) is set. Only ever Some with the `oidn` feature. 
+    image_oidn: Option<custom_image::CustomImage>,
+    /// The last frame being denoised by Open Image Denoise in the background, if any. 
+    oidn_run: Option<BackgroundLoad<custom_image::CustomImage>>,
+    /// The hash of the scene the live preview was last started with, see 
+    /// [update_live_preview](App::update_live_preview). None while the live preview is off. 
+    live_preview_hash: Option<u64>,
+    /// The comparison of the shown frame with a reference running in the background, if any. 
+    comparison_run: Option<BackgroundLoad<image_compare::ImageDifference>>,
+    /// The result of the last comparison with a reference and the texture of its heatmap, until 
+    /// its window is closed. 
+    comparison: Option<(image_compare::ImageDifference, egui::TextureHandle)>,
+    /// The average luminance measured by the last probe render, until the user applies or 
+    /// dismisses the proposed light scale. 
+    light_balance_result: Option<f32>,
+    /// A low resolution probe render timing the scene before the render is started, if any. 
+    render_estimate_probe: Option<BackgroundLoad<RenderEstimate>>,
+    /// The estimate of a render which was held back because it exceeds the memory or takes very 
+    /// long, until the user starts or dismisses it. 
+    render_estimate: Option<RenderEstimate>,
+    /// Paths which are currently traced and written to a file for debugging, if any. 
+    path_dump: Option<BackgroundLoad<()>>,
+    /// The hierarchy over the objects of the last built uniforms. It is refit instead of rebuilt 
+    /// as long as objects are only moved or resized. 
+    bvh: RefCell<Option<Arc<Bvh>>>,
+    /// The settings of the running render, added to the gallery together with the image once the 
+    /// render completes. 
+    render_snapshot: Option<RenderSnapshot>,
+    /// The renders completed in this session. 
+    gallery: Gallery,
+    /// The tutorial the user currently follows, if any. 
+    tutorial: Option<TutorialProgress>,
     rendering_since: Option<Instant>,
     app_to_render_channel: Option<mpsc::Sender<AppToRenderMessages>>,
+    /// The arrangement of the tabs in the main content window. It is stored per user when the app 
+    /// closes and restored on the next start. 
+    dock_state: DockState<UiTab>,
+    /// A tab which should be brought to the front the next time the tabs are displayed. 
+    tab_to_focus: Option<UiTab>,
+    /// The textures of the objects, each image file is only read once. 
+    texture_cache: TextureCache,
+    /// The scene file opened or saved last, if any. 
+    scene_path: Option<PathBuf>,
+    /// The scene file which is opened again and rendered whenever it changes on disk, if any. 
+    scene_watch: Option<SceneWatch>,
+    /// Whether a render of the changed watched scene starts once the running render has stopped.
+    scene_watch_render_pending: bool,
+    /// The server through which other programs control the app, if enabled. 
+    remote_control: Option<RemoteControl>,
+    /// The script of the scripting tab and the output of its last run. 
+    #[cfg(feature = "scripting")]
+    script_console: scripting::ScriptConsole,
 }
 
 impl App {
-    fn new() -> Self {
-        Self {
+    /// Creates the app. If a storage is given, the tab layout and the render settings of the last 
+    /// session are restored from it. 
+    fn new(storage: Option<&dyn eframe::Storage>) -> Self {
+        let dock_state = storage
+            .and_then(|storage| eframe::get_value::<DockState<UiTab>>(storage, DOCK_STATE_STORAGE_KEY))
+            .and_then(restore_dock_state)
+            .unwrap_or_else(default_dock_state);
+        
+        let mut app = Self {
             ui_values: UIFields::default(),
             image_actual: None,
+            image_float: None,
             image_eframe_texture: None,
+            image_downsample_factor: None,
+            image_crop_center: None,
+            probed_pixel: None,
+            image_crop_texture: None,
+            image_normals: None,
+            image_depth: None,
+            image_albedo: None,
+            image_ids: None,
+            image_light_passes: Vec::new(),
+            light_pass_names: Vec::new(),
+            noise_error: None,
             actions: Arc::new(Mutex::new(Vec::new())),
             currently_rendering: Arc::new(Mutex::new(false)),
+            render_paused: Arc::new(AtomicBool::new(false)),
+            render_cancelled: Arc::new(AtomicBool::new(false)),
+            active_buckets: Arc::new(Mutex::new(Vec::new())),
+            accumulation: Arc::new(Mutex::new(None)),
+            sensor_response_load: None,
+            light_balance_probe: None,
+            denoise_run: None,
+            image_oidn: None,
+            oidn_run: None,
+            live_preview_hash: None,
+            comparison_run: None,
+            comparison: None,
+            light_balance_result: None,
+            render_estimate_probe: None,
+            render_estimate: None,
+            path_dump: None,
+            bvh: RefCell::new(None),
+            render_snapshot: None,
+            gallery: Gallery::default(),
+            tutorial: None,
             rendering_since: None,
             app_to_render_channel: None,
+            dock_state,
+            tab_to_focus: None,
+            texture_cache: TextureCache::default(),
+            scene_path: None,
+            scene_watch: None,
+            scene_watch_render_pending: false,
+            remote_control: None,
+            #[cfg(feature = "scripting")]
+            script_console: scripting::ScriptConsole::default(),
+        };
+        
+        let settings = storage
+            .and_then(|storage| eframe::get_value::<StoredSettings>(storage, SETTINGS_STORAGE_KEY))
+            .filter(StoredSettings::is_valid);
+        if let Some(settings) = settings {
+            settings.apply(&mut app.ui_values);
+            if settings.spectrum_number_of_samples != app.ui_values.spectrum_number_of_samples {
+                app.ui_values.spectrum_number_of_samples = settings.spectrum_number_of_samples;
+                app.update_all_spectrum_sample_sizes(settings.spectrum_number_of_samples);
+            }
+        }
+        app
+    }
+    
+    /// Stores the render settings for the next session. Settings equal to the defaults are not 
+    /// stored, so that defaults depending on the machine, like the number of threads, stay up to 
+    /// date. 
+    fn store_settings(&self, storage: &mut dyn eframe::Storage) {
+        let settings = StoredSettings::from(&self.ui_values);
+        if settings == StoredSettings::from(&UIFields::default()) {
+            storage.set_string(SETTINGS_STORAGE_KEY, String::new());
+        } else {
+            eframe::set_value(storage, SETTINGS_STORAGE_KEY, &settings);
         }
     }
 
@@ -157,6 +441,24 @@ impl App {
         });
     }
     
+    /// Displays the selection of the render scale together with the size the image is rendered at. 
+    fn display_render_scale_selection(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Render scale:").on_hover_text(RENDER_SCALE_TOOLTIP);
+                ComboBox::new("render_scale", "")
+                    .selected_text(self.ui_values.render_scale.to_string())
+                    .show_ui(ui, |ui| {
+                        for scale in RenderScale::ALL {
+                            ui.selectable_value(&mut self.ui_values.render_scale, scale, scale.to_string());
+                        }
+                    });
+                let (width, height) = self.ui_values.render_size();
+                ui.label(format!("{width}×{height} pixels rendered"));
+            });
+        });
+    }
+    
     /// Shortcut function to display the text field managing the number of frames including label
     /// horizontally. 
     fn display_nbr_of_iterations_edit_field(&mut self, ui: &mut Ui) {
@@ -216,938 +518,2923 @@ impl App {
             });
         });
     }
-    
-    /// Shortcut function that generates and displays the time taken to render the image. 
-    fn display_frame_generation_time(&mut self, ui: &mut Ui) {
-        let (s, t) = match self.ui_values.frame_gen_time {
-            Some(duration) => {
-                let mut remaining_duration = Duration::ZERO;
 
-                let progress = self.ui_values.progress_bar_progress;
-                if !(progress == 0.0 || progress == 1.0) {
-                    let total_duration = duration.div_f32(progress);
-                    remaining_duration = total_duration.mul_f32(1.0 - progress);
+    /// Displays the separate depth limits for diffuse, specular and transmission bounces as well as the russian
+    /// roulette toggle, each in a horizontally aligned manner.
+    fn display_bounce_kind_limits_edit_fields(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Maximum diffuse bounces:").on_hover_text(MAX_DIFFUSE_BOUNCES_TOOLTIP);
+                ui.add(egui::Slider::new(&mut self.ui_values.nbr_of_diffuse_bounces, 0..=NEW_RAY_MAX_BOUNCES_MAX));
+                if ui.button(" - ").clicked() {
+                    self.ui_values.nbr_of_diffuse_bounces = self.ui_values.nbr_of_diffuse_bounces.saturating_sub(1);
                 }
-                
-                (format!("{:.3?}", duration), format!("{:.3?}", remaining_duration))
-            },
-            None => ("-".to_string(), "-".to_string()),
-        };
-
-        ui.label(format!("Time to generate image: {s}"));
-        ui.label(format!("Approximate time remaining: {t}"));
-    }
-    
-    /// Shortcut function to display various settings for the camera. The settings can be changed 
-    /// and the updated values will be used in the rendering process. 
-    fn display_camera_settings(&mut self, ui: &mut Ui) {
-        //camera position
-        ui.horizontal_top(|ui| {
-            let mut pos_x_string = self.ui_values.ui_camera.pos_x.to_string();
-            let mut pos_y_string = self.ui_values.ui_camera.pos_y.to_string();
-            let mut pos_z_string = self.ui_values.ui_camera.pos_z.to_string();
-            ui.label("Camera Position: (x:").on_hover_text(CAMERA_POSITION_TOOLTIP);
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_x_string));
-            ui.label("y:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_y_string));
-            ui.label("z:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_z_string));
-            ui.label(")");
-
-            if pos_x_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.pos_x = pos_x_string.parse::<f32>().unwrap();
-            }
-            if pos_y_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.pos_y = pos_y_string.parse::<f32>().unwrap();
-            }
-            if pos_z_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.pos_z = pos_z_string.parse::<f32>().unwrap();
-            }
+                if ui.button(" + ").clicked() {
+                    self.ui_values.nbr_of_diffuse_bounces += 1;
+                }
+            });
         });
-        
-        //camera direction
-        ui.horizontal_top(|ui| {
-            let mut dir_x_string = self.ui_values.ui_camera.dir_x.to_string();
-            let mut dir_y_string = self.ui_values.ui_camera.dir_y.to_string();
-            let mut dir_z_string = self.ui_values.ui_camera.dir_z.to_string();
-
-            ui.label("Camera Direction: (x:").on_hover_text(CAMERA_DIRECTION_TOOLTIP);
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dir_x_string));
-            ui.label("y:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dir_y_string));
-            ui.label("z:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dir_z_string));
-            ui.label(")");
-
-            if dir_x_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.dir_x = dir_x_string.parse::<f32>().unwrap();
-            }
-            if dir_y_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.dir_y = dir_y_string.parse::<f32>().unwrap();
-            }
-            if dir_z_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.dir_z = dir_z_string.parse::<f32>().unwrap();
-            }
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Maximum specular bounces:").on_hover_text(MAX_SPECULAR_BOUNCES_TOOLTIP);
+                ui.add(egui::Slider::new(&mut self.ui_values.nbr_of_specular_bounces, 0..=NEW_RAY_MAX_BOUNCES_MAX));
+                if ui.button(" - ").clicked() {
+                    self.ui_values.nbr_of_specular_bounces = self.ui_values.nbr_of_specular_bounces.saturating_sub(1);
+                }
+                if ui.button(" + ").clicked() {
+                    self.ui_values.nbr_of_specular_bounces += 1;
+                }
+            });
         });
-
-        //camera up direction
-        ui.horizontal_top(|ui| {
-            let mut up_x_string = self.ui_values.ui_camera.up_x.to_string();
-            let mut up_y_string = self.ui_values.ui_camera.up_y.to_string();
-            let mut up_z_string = self.ui_values.ui_camera.up_z.to_string();
-
-            ui.label("Camera Up: (x:").on_hover_text(CAMERA_UP_TOOLTIP);
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut up_x_string));
-            ui.label("y:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut up_y_string));
-            ui.label("z:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut up_z_string));
-            ui.label(")");
-
-            if up_x_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.up_x = up_x_string.parse::<f32>().unwrap();
-            }
-            if up_y_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.up_y = up_y_string.parse::<f32>().unwrap();
-            }
-            if up_z_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.up_z = up_z_string.parse::<f32>().unwrap();
-            }
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Maximum transmission bounces:").on_hover_text(MAX_TRANSMISSION_BOUNCES_TOOLTIP);
+                ui.add(egui::Slider::new(&mut self.ui_values.nbr_of_transmission_bounces, 0..=NEW_RAY_MAX_BOUNCES_MAX));
+                if ui.button(" - ").clicked() {
+                    self.ui_values.nbr_of_transmission_bounces = self.ui_values.nbr_of_transmission_bounces.saturating_sub(1);
+                }
+                if ui.button(" + ").clicked() {
+                    self.ui_values.nbr_of_transmission_bounces += 1;
+                }
+            });
         });
-        
-        //camera FOV
-        ui.horizontal_top(|ui| {
-            ui.label("Camera vertical FOV in degrees:").on_hover_text(CAMERA_FOV_TOOLTIP);
-            let mut fov_string = self.ui_values.ui_camera.fov_deg_y.to_string();
-
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut fov_string));
-
-            if fov_string.parse::<f32>().is_ok() {
-                self.ui_values.ui_camera.fov_deg_y = fov_string.parse::<f32>().unwrap();
-            }
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Russian roulette:").on_hover_text(RUSSIAN_ROULETTE_TOOLTIP);
+                ui.checkbox(&mut self.ui_values.russian_roulette, "");
+            });
         });
     }
     
-    /// Shortcut function to display various settings for a single Light object. The settings can 
-    /// be changed and the updated values will be used in the rendering process. 
-    fn display_light_source_settings(&mut self, ui: &mut Ui, index: usize) { 
-        let light = &mut self.ui_values.ui_lights[index];
-        
-        //name
-        ui.horizontal_top(|ui| {
-            let backup_name = &format!("Light Source #{index}");
-            display_name_with_edit(ui, &mut light.name, backup_name, &mut light.editing_name);
-            ui.add_space(100.0);
-            
-            let delete_button = egui::widgets::Button::new("Delete this light source").fill(Color32::LIGHT_RED);
-            if ui.add(delete_button).clicked() {
-                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteLight(index));
-            }
-        });
-        
-        //light position
-        ui.horizontal_top(|ui| {
-            let mut pos_x_string = light.pos_x.to_string();
-            let mut pos_y_string = light.pos_y.to_string();
-            let mut pos_z_string = light.pos_z.to_string();
-            ui.label("Light Position: (x:").on_hover_text(LIGHT_SOURCE_TOOLTIP);
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_x_string));
-            ui.label("y:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_y_string));
-            ui.label("z:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_z_string));
-            ui.label(")");
-
-            if pos_x_string.parse::<f32>().is_ok() {
-                light.pos_x = pos_x_string.parse::<f32>().unwrap();
-            }
-            if pos_y_string.parse::<f32>().is_ok() {
-                light.pos_y = pos_y_string.parse::<f32>().unwrap();
-            }
-            if pos_z_string.parse::<f32>().is_ok() {
-                light.pos_z = pos_z_string.parse::<f32>().unwrap();
-            }
+    /// Displays the selection of the strategy used to generate random numbers in the shaders as 
+    /// well as their seed. 
+    fn display_sampler_selection(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Sampler:").on_hover_text(SAMPLER_TOOLTIP);
+                ComboBox::new("sampler_type", "")
+                    .selected_text(self.ui_values.sampler_type.to_string())
+                    .show_ui(ui, |ui| {
+                        for sampler_type in [SamplerType::Pcg, SamplerType::Sobol] {
+                            ui.selectable_value(&mut self.ui_values.sampler_type, sampler_type, sampler_type.to_string());
+                        }
+                    }).response.on_hover_text(SAMPLER_TOOLTIP);
+                
+                ui.label("Seed:").on_hover_text(RANDOM_SEED_TOOLTIP);
+                let mut seed_string = self.ui_values.random_seed.to_string();
+                ui.add(TextEdit::singleline(&mut seed_string).desired_width(80.0));
+                if let Ok(seed) = seed_string.parse::<u32>() {
+                    self.ui_values.random_seed = seed;
+                } else if seed_string.is_empty() {
+                    self.ui_values.random_seed = 0;
+                }
+            });
         });
-
-        //light spectrum
-        ui.horizontal_top(|ui| {
-            let label_color = if !self.ui_values.spectra.contains(&light.spectrum) && is_time_even() {
-                Color32::RED
-            } else {
-                Color32::DARK_GRAY
-            };
-            ui.colored_label(label_color, "Spectrum").on_hover_text(LIGHT_SPECTRUM_TOOLTIP);
-
-            let borrow = light.spectrum.borrow();
-            let selected_text = borrow.to_string();
-            drop(borrow);
-            
-            Self::display_combobox_with_spectrum_list(
-                &mut self.ui_values.spectra,
-                ui, 
-                format!("light source {index} spectrum"),
-                selected_text,
-                LIGHT_SPECTRUM_TOOLTIP,
-                &mut light.spectrum,
-            )
+    }
+    
+    /// Displays the strategy choosing the lights sampled at every hit, together with the number 
+    /// of lights drawn if they are chosen at random. 
+    fn display_light_selection_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Light selection:").on_hover_text(LIGHT_SELECTION_TOOLTIP);
+                ComboBox::new("light_selection", "")
+                    .selected_text(self.ui_values.light_selection.to_string())
+                    .show_ui(ui, |ui| {
+                        for selection in [LightSelection::All, LightSelection::Uniform, LightSelection::Power] {
+                            ui.selectable_value(&mut self.ui_values.light_selection, selection, selection.to_string());
+                        }
+                    }).response.on_hover_text(LIGHT_SELECTION_TOOLTIP);
+                
+                if self.ui_values.light_selection != LightSelection::All {
+                    ui.label("Lights per hit:").on_hover_text(LIGHT_SAMPLES_TOOLTIP);
+                    ui.add(egui::DragValue::new(&mut self.ui_values.light_samples).range(1..=64));
+                }
+            });
         });
     }
-
-    /// Displays a [ComboBox] which lists all the available spectra. 
-    fn display_combobox_with_spectrum_list(spectra: &mut [Rc<RefCell<UISpectrum>>], ui: &mut Ui, id_salt: String,
-                                           selected_text: String, tool_tip: &str, current_spectrum: &mut Rc<RefCell<UISpectrum>>) {
-        ComboBox::new(id_salt, "")
-            .selected_text(selected_text)
-            .show_ui(ui, |ui| {
-                for spectrum in spectra {
-                    ui.selectable_value(current_spectrum, spectrum.clone(), spectrum.borrow().to_string());
+    
+    /// Displays the number of photons traced every frame for the caustics and the radius they 
+    /// are gathered in. 
+    fn display_caustics_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Caustic photons:").on_hover_text(PHOTON_COUNT_TOOLTIP);
+                ui.add(egui::DragValue::new(&mut self.ui_values.photon_count).range(0..=PHOTON_COUNT_MAX).speed(100))
+                    .on_hover_text(PHOTON_COUNT_TOOLTIP);
+                
+                if self.ui_values.photon_count > 0 {
+                    ui.label("Gather radius:").on_hover_text(PHOTON_RADIUS_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut self.ui_values.photon_radius, 0.001..=1.0).logarithmic(true))
+                        .on_hover_text(PHOTON_RADIUS_TOOLTIP);
                 }
-            }).response.on_hover_text(tool_tip);
+            });
+        });
     }
-
-    /// Displays a [ComboBox] which lists all the available materials. 
-    fn display_combobox_with_material_list(materials: &mut [Rc<RefCell<UIMaterial>>], ui: &mut Ui, id_salt: String,
-                                           selected_text: String, tool_tip: &str, current_material: &mut Rc<RefCell<UIMaterial>>) {
-        ComboBox::new(id_salt, "")
-            .selected_text(selected_text)
-            .show_ui(ui, |ui| {
-                for material in materials {
-                    ui.selectable_value(current_material, material.clone(), material.borrow().to_string());
+    
+    /// Displays whether the indirect diffuse light is interpolated from the irradiance cache, 
+    /// together with the quality of the cache. 
+    fn display_irradiance_cache_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Irradiance cache:").on_hover_text(IRRADIANCE_CACHE_TOOLTIP);
+                let mut enabled = self.ui_values.irradiance_cache_quality.is_some();
+                ui.checkbox(&mut enabled, "");
+                if !enabled {
+                    self.ui_values.irradiance_cache_quality = None;
+                } else if self.ui_values.irradiance_cache_quality.is_none() {
+                    self.ui_values.irradiance_cache_quality = Some(IRRADIANCE_CACHE_QUALITY_DEFAULT);
                 }
-        }).response.on_hover_text(tool_tip);
+                
+                if let Some(quality) = &mut self.ui_values.irradiance_cache_quality {
+                    ui.label("Quality:").on_hover_text(IRRADIANCE_CACHE_QUALITY_TOOLTIP);
+                    ui.add(egui::Slider::new(quality, 0.0..=1.0)).on_hover_text(IRRADIANCE_CACHE_QUALITY_TOOLTIP);
+                }
+            });
+        });
     }
     
-    /// Shortcut function to display the settings for a single Object in the scene. The settings 
-    /// can be changed and the updated values will be used in the rendering process. Each object is 
-    /// differentiated according to their type, and the respective settings will be displayed.
-    ///TODO the type changing logic in this function is a mess. 
-    fn display_objects_settings(&mut self, ui: &mut Ui, index: usize) {
-        let object = &mut self.ui_values.ui_objects[index];
-
-        //name
-        ui.horizontal_top(|ui| {
-            let backup_name = &format!("{object} #{index}");
-            display_name_with_edit(ui, &mut object.name, backup_name, &mut object.editing_name);
-            ui.add_space(30.0);
+    /// Displays the settings of the fog filling the scene: its density, whether light is scattered 
+    /// towards the camera and the spectra of its absorption and scattering. 
+    fn display_medium_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Fog:").on_hover_text(MEDIUM_TOOLTIP);
+                let mut enabled = self.ui_values.medium.is_some();
+                ui.add_enabled(enabled || !self.ui_values.spectra.is_empty(), egui::Checkbox::new(&mut enabled, ""))
+                    .on_disabled_hover_text(MEDIUM_DISABLED_TOOLTIP);
+                if !enabled {
+                    self.ui_values.medium = None;
+                } else if self.ui_values.medium.is_none() {
+                    let spectrum = self.get_first_reflective_spectrum_or_first_general();
+                    self.ui_values.medium = spectrum.map(|spectrum| UIMedium {
+                        absorption: spectrum.clone(),
+                        scattering: spectrum,
+                        density: 0.1,
+                        in_scattering: true,
+                    });
+                }
+                
+                if let Some(medium) = &mut self.ui_values.medium {
+                    ui.label("Density:").on_hover_text(MEDIUM_DENSITY_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut medium.density, 0.001..=10.0).logarithmic(true))
+                        .on_hover_text(MEDIUM_DENSITY_TOOLTIP);
+                    ui.label("Light beams:").on_hover_text(MEDIUM_IN_SCATTERING_TOOLTIP);
+                    ui.checkbox(&mut medium.in_scattering, "");
+                }
+            });
             
-            #[derive(PartialEq, Clone, Copy, Debug)]
-            enum Type {
-                PlainBox,
-                Sphere,
-                RotatedBox,
-            }
-            impl Display for Type {
-                fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-                    let s = match self {
-                        Type::PlainBox => "PlainBox",
-                        Type::Sphere => "Sphere",
-                        Type::RotatedBox => "RotatedBox",
-                    };
-                    write!(f, "{s}")
+            if let Some(medium) = &mut self.ui_values.medium {
+                for (spectrum, name, tool_tip) in [
+                    (&mut medium.absorption, "Absorption:", MEDIUM_ABSORPTION_TOOLTIP),
+                    (&mut medium.scattering, "Scattering:", MEDIUM_SCATTERING_TOOLTIP),
+                ] {
+                    ui.horizontal_top(|ui| {
+                        let label_color = if !self.ui_values.spectra.contains(spectrum) && is_time_even() {
+                            Color32::RED
+                        } else {
+                            Color32::DARK_GRAY
+                        };
+                        ui.colored_label(label_color, name).on_hover_text(tool_tip);
+                        
+                        let selected_text = spectrum.borrow().to_string();
+                        Self::display_combobox_with_spectrum_list(
+                            &mut self.ui_values.spectra,
+                            ui,
+                            format!("medium {name}"),
+                            selected_text,
+                            tool_tip,
+                            &mut *spectrum,
+                        )
+                    });
                 }
             }
-            let mut selected = match object.ui_object_type {
-                UIObjectType::PlainBox(_, _, _) => Type::PlainBox,
-                UIObjectType::Sphere(_) => Type::Sphere,
-                UIObjectType::RotatedBox(_, _, _, _, _, _) => Type::RotatedBox,
-            };
-            ComboBox::new(index, "Type")
-                .selected_text(format!("{}", selected))
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut selected, Type::PlainBox, "Plain Box").on_hover_text(OBJECT_TYPE_PLAIN_BOX_TOOLTIP);
-                    ui.selectable_value(&mut selected, Type::Sphere, "Sphere").on_hover_text(OBJECT_TYPE_SPHERE_TOOLTIP);
-                    ui.selectable_value(&mut selected, Type::RotatedBox, "Rotated Box").on_hover_text(OBJECT_TYPE_ROTATED_BOX_TOOLTIP);
-                }).response.on_hover_text(OBJECT_TYPE_TOOLTIP);
-            let same = selected == match object.ui_object_type {
-                UIObjectType::PlainBox(_, _, _) => Type::PlainBox,
-                UIObjectType::Sphere(_) => Type::Sphere,
-                UIObjectType::RotatedBox(_, _, _, _, _, _) => Type::RotatedBox,
+        });
+    }
+    
+    /// Displays the settings of the atmosphere around the scene: the position and the spectrum of 
+    /// the sun, the amount of aerosols and the altitude of the scene. 
+    fn display_atmosphere_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Atmosphere:").on_hover_text(ATMOSPHERE_TOOLTIP);
+                let mut enabled = self.ui_values.atmosphere.is_some();
+                ui.add_enabled(enabled || !self.ui_values.spectra.is_empty(), egui::Checkbox::new(&mut enabled, ""))
+                    .on_disabled_hover_text(ATMOSPHERE_DISABLED_TOOLTIP);
+                if !enabled {
+                    self.ui_values.atmosphere = None;
+                } else if self.ui_values.atmosphere.is_none() {
+                    let spectrum = self.ui_values.spectra.iter()
+                        .find(|s| s.borrow().spectrum_effect_type == SpectrumEffectType::Emissive)
+                        .or(self.ui_values.spectra.first())
+                        .cloned();
+                    self.ui_values.atmosphere = spectrum.map(|sun_spectrum| UIAtmosphere {
+                        sun_spectrum,
+                        sun_elevation_deg: 30.0,
+                        sun_azimuth_deg: 0.0,
+                        turbidity: 1.0,
+                        altitude: 0.0,
+                    });
+                }
+                
+                if let Some(atmosphere) = &mut self.ui_values.atmosphere {
+                    ui.label("Sun elevation:").on_hover_text(ATMOSPHERE_SUN_POSITION_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut atmosphere.sun_elevation_deg, -10.0..=90.0).suffix("°"));
+                    ui.label("azimuth:");
+                    ui.add(egui::Slider::new(&mut atmosphere.sun_azimuth_deg, 0.0..=360.0).suffix("°"));
+                }
+            });
+            
+            if let Some(atmosphere) = &mut self.ui_values.atmosphere {
+                ui.horizontal_top(|ui| {
+                    ui.label("Turbidity:").on_hover_text(ATMOSPHERE_TURBIDITY_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut atmosphere.turbidity, 0.0..=10.0));
+                    ui.label("Altitude:").on_hover_text(ATMOSPHERE_ALTITUDE_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut atmosphere.altitude, 0.0..=20000.0).suffix("m"));
+                });
+                ui.horizontal_top(|ui| {
+                    let label_color = if !self.ui_values.spectra.contains(&atmosphere.sun_spectrum) && is_time_even() {
+                        Color32::RED
+                    } else {
+                        Color32::DARK_GRAY
+                    };
+                    ui.colored_label(label_color, "Sun Spectrum:").on_hover_text(ATMOSPHERE_SUN_SPECTRUM_TOOLTIP);
+                    
+                    let selected_text = atmosphere.sun_spectrum.borrow().to_string();
+                    Self::display_combobox_with_spectrum_list(
+                        &mut self.ui_values.spectra,
+                        ui,
+                        "atmosphere sun spectrum".to_string(),
+                        selected_text,
+                        ATMOSPHERE_SUN_SPECTRUM_TOOLTIP,
+                        &mut atmosphere.sun_spectrum,
+                    )
+                });
+            }
+        });
+    }
+    
+    /// Displays the selection of what rays leaving the scene see if there is no atmosphere: black, 
+    /// a spectrum, a vertical gradient between two spectra or transparency. 
+    fn display_background_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Background:").on_hover_text(BACKGROUND_TOOLTIP);
+                let spectrum = self.ui_values.spectra.iter()
+                    .find(|s| s.borrow().spectrum_effect_type == SpectrumEffectType::Emissive)
+                    .or(self.ui_values.spectra.first())
+                    .cloned();
+                let mut candidates = vec![UIBackground::Black];
+                if let Some(spectrum) = spectrum {
+                    candidates.push(UIBackground::Spectrum(spectrum.clone()));
+                    candidates.push(UIBackground::Gradient {top: spectrum.clone(), bottom: spectrum});
+                }
+                candidates.push(UIBackground::Transparent);
+                
+                ComboBox::new("background", "")
+                    .selected_text(self.ui_values.background.to_string())
+                    .show_ui(ui, |ui| {
+                        for background in candidates {
+                            let selected = std::mem::discriminant(&self.ui_values.background) == std::mem::discriminant(&background);
+                            if ui.selectable_label(selected, background.to_string()).clicked() && !selected {
+                                self.ui_values.background = background;
+                            }
+                        }
+                    }).response.on_hover_text(BACKGROUND_TOOLTIP);
+            });
+            
+            let spectra: Vec<_> = match &mut self.ui_values.background {
+                UIBackground::Black | UIBackground::Transparent => Vec::new(),
+                UIBackground::Spectrum(spectrum) => vec![(spectrum, "Spectrum:", BACKGROUND_SPECTRUM_TOOLTIP)],
+                UIBackground::Gradient {top, bottom} => vec![
+                    (top, "Top:", BACKGROUND_GRADIENT_TOOLTIP),
+                    (bottom, "Bottom:", BACKGROUND_GRADIENT_TOOLTIP),
+                ],
+            };
+            for (spectrum, name, tool_tip) in spectra {
+                ui.horizontal_top(|ui| {
+                    let label_color = if !self.ui_values.spectra.contains(spectrum) && is_time_even() {
+                        Color32::RED
+                    } else {
+                        Color32::DARK_GRAY
+                    };
+                    ui.colored_label(label_color, name).on_hover_text(tool_tip);
+                    
+                    let selected_text = spectrum.borrow().to_string();
+                    Self::display_combobox_with_spectrum_list(
+                        &mut self.ui_values.spectra,
+                        ui,
+                        format!("background {name}"),
+                        selected_text,
+                        tool_tip,
+                        &mut *spectrum,
+                    )
+                });
+            }
+        });
+    }
+    
+    /// Displays the selection of the firefly filter together with its threshold. 
+    fn display_firefly_filter_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Firefly filter:").on_hover_text(FIREFLY_FILTER_TOOLTIP);
+                ComboBox::new("firefly_filter", "")
+                    .selected_text(self.ui_values.firefly_filter.to_string())
+                    .show_ui(ui, |ui| {
+                        for filter in [FireflyFilter::Off, 
+                                       FireflyFilter::Clamp(FIREFLY_CLAMP_DEFAULT), 
+                                       FireflyFilter::OutlierRejection(FIREFLY_OUTLIER_FACTOR_DEFAULT)] {
+                            let selected = std::mem::discriminant(&self.ui_values.firefly_filter) == std::mem::discriminant(&filter);
+                            if ui.selectable_label(selected, filter.to_string()).clicked() && !selected {
+                                self.ui_values.firefly_filter = filter;
+                            }
+                        }
+                    }).response.on_hover_text(FIREFLY_FILTER_TOOLTIP);
+                
+                match &mut self.ui_values.firefly_filter {
+                    FireflyFilter::Off => {}
+                    FireflyFilter::Clamp(max_luminance) => {
+                        ui.label("Maximum luminance:").on_hover_text(FIREFLY_CLAMP_TOOLTIP);
+                        ui.add(egui::Slider::new(max_luminance, 0.1..=100.0).logarithmic(true))
+                            .on_hover_text(FIREFLY_CLAMP_TOOLTIP);
+                    }
+                    FireflyFilter::OutlierRejection(factor) => {
+                        ui.label("Rejection factor:").on_hover_text(FIREFLY_OUTLIER_TOOLTIP);
+                        ui.add(egui::Slider::new(factor, 1.0..=100.0).logarithmic(true))
+                            .on_hover_text(FIREFLY_OUTLIER_TOOLTIP);
+                    }
+                }
+            });
+        });
+    }
+    
+    /// Displays the selection of how spectra are converted into RGB, either by the CIE observer, 
+    /// by the sensitivity curves of a camera sensor or by mapping a single band through a false 
+    /// color map. Custom sensor curves can be loaded from a CSV file. 
+    fn display_color_response_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Color response:").on_hover_text(COLOR_RESPONSE_TOOLTIP);
+                ComboBox::new("color_response", "")
+                    .selected_text(self.ui_values.color_response.to_string())
+                    .show_ui(ui, |ui| {
+                        for response in [UIColorResponse::CieObserver, 
+                                         UIColorResponse::CieObserver1931, 
+                                         UIColorResponse::TypicalCmos, 
+                                         UIColorResponse::CustomSensor, 
+                                         UIColorResponse::FalseColor(FalseColor::default())] {
+                            let selected = std::mem::discriminant(&self.ui_values.color_response) == std::mem::discriminant(&response);
+                            if ui.selectable_label(selected, response.to_string()).clicked() && !selected {
+                                self.ui_values.color_response = response;
+                            }
+                        }
+                    }).response.on_hover_text(COLOR_RESPONSE_TOOLTIP);
+
+                if self.ui_values.color_response == UIColorResponse::CustomSensor {
+                    let loaded = match &self.ui_values.custom_sensor_response {
+                        Some(sensor) => sensor.to_string(),
+                        None => "none loaded".to_string(),
+                    };
+                    ui.label(format!("({loaded})"));
+                    let load_button = egui::Button::new("Load CSV");
+                    if ui.add_enabled(self.sensor_response_load.is_none(), load_button)
+                        .on_hover_text(SENSOR_RESPONSE_CSV_TOOLTIP).clicked() {
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .pick_file();
+                        if let Some(path) = dialog {
+                            let name = format!("Sensor response {}", path.display());
+                            self.sensor_response_load = Some(BackgroundLoad::start(name, move |progress| {
+                                SensorResponse::new_from_csv_file(&path, progress)
+                            }));
+                        }
+                    }
+                }
+            });
+        });
+        if let UIColorResponse::FalseColor(false_color) = &mut self.ui_values.color_response {
+            ui.vertical_centered(|ui| {
+                ui.horizontal_top(|ui| {
+                    ui.label("Wavelength:").on_hover_text(FALSE_COLOR_WAVELENGTH_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut false_color.wavelength, 
+                                             self.ui_values.spectrum_lower_bound..=self.ui_values.spectrum_upper_bound)
+                        .suffix(" nm"))
+                        .on_hover_text(FALSE_COLOR_WAVELENGTH_TOOLTIP);
+                    ui.label("Bandwidth:").on_hover_text(FALSE_COLOR_BANDWIDTH_TOOLTIP);
+                    ui.add(egui::DragValue::new(&mut false_color.bandwidth).range(0.0..=100.0).speed(0.5).suffix(" nm"))
+                        .on_hover_text(FALSE_COLOR_BANDWIDTH_TOOLTIP);
+                });
+            });
+            ui.vertical_centered(|ui| {
+                ui.horizontal_top(|ui| {
+                    ui.label("Maximum:").on_hover_text(FALSE_COLOR_MAXIMUM_TOOLTIP);
+                    ui.add(egui::Slider::new(&mut false_color.maximum, 0.01..=100.0).logarithmic(true))
+                        .on_hover_text(FALSE_COLOR_MAXIMUM_TOOLTIP);
+                    ui.label("Map:").on_hover_text(FALSE_COLOR_MAP_TOOLTIP);
+                    ComboBox::new("false_color_map", "")
+                        .selected_text(false_color.map.to_string())
+                        .show_ui(ui, |ui| {
+                            for map in [FalseColorMap::Viridis, FalseColorMap::Inferno, FalseColorMap::Grayscale] {
+                                ui.selectable_value(&mut false_color.map, map, map.to_string());
+                            }
+                        }).response.on_hover_text(FALSE_COLOR_MAP_TOOLTIP);
+                });
+            });
+        }
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Gamut compression:").on_hover_text(GAMUT_COMPRESSION_TOOLTIP);
+                ui.checkbox(&mut self.ui_values.gamut_compression, "");
+            });
+        });
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Linear output:").on_hover_text(LINEAR_OUTPUT_TOOLTIP);
+                if ui.checkbox(&mut self.ui_values.linear_output, "").changed() {
+                    self.encode_last_frame(ui.ctx());
+                }
+            });
+        });
+    }
+    
+    /// Displays the multiplier applied to all light sources and emissive objects, together with 
+    /// buttons to propose a multiplier from a probe render and to reset it. 
+    fn display_light_scale_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Light scale:").on_hover_text(LIGHT_SCALE_TOOLTIP);
+                let mut scale_string = self.ui_values.light_scale.to_string();
+                ui.text_edit_singleline(&mut scale_string).on_hover_text(LIGHT_SCALE_TOOLTIP);
+                if let Ok(scale) = scale_string.parse::<f32>() {
+                    if scale.is_finite() && scale >= 0.0 {
+                        self.ui_values.light_scale = scale;
+                    }
+                }
+                
+                let probe_button = egui::Button::new("Auto-balance");
+                if ui.add_enabled(self.light_balance_probe.is_none(), probe_button)
+                    .on_hover_text(LIGHT_AUTO_BALANCE_TOOLTIP).clicked() {
+                    self.start_light_balance_probe();
+                }
+                if ui.button("Reset").on_hover_text(LIGHT_SCALE_RESET_TOOLTIP).clicked() {
+                    self.ui_values.light_scale = 1.0;
+                }
+            });
+        });
+    }
+    
+    /// Starts a quick, low resolution render of the scene in the background which measures its 
+    /// average luminance, see [probe_average_luminance]. 
+    fn start_light_balance_probe(&mut self) {
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+        if !self.check_render_legality() {
+            warn!("The scene can not be rendered in its current state, no probe render is started.");
+            return;
+        }
+        
+        let uniforms = self.build_uniforms();
+        let width = LIGHT_BALANCE_PROBE_WIDTH.min(self.ui_values.width).max(1);
+        let height = ((width as f32 * self.ui_values.height as f32 / self.ui_values.width as f32)
+            .round() as u32).max(1);
+        self.light_balance_result = None;
+        self.light_balance_probe = Some(BackgroundLoad::start("Light balance probe render".to_string(), 
+            move |progress| probe_average_luminance(uniforms, width, height, progress)));
+    }
+    
+    /// Displays the settings which rarely need to be touched, collapsed by default: the offset of 
+    /// new rays from the surfaces. 
+    fn display_advanced_settings(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Advanced").id_salt("advanced settings").show(ui, |ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Ray offset:").on_hover_text(RAY_OFFSET_TOOLTIP);
+                ui.add(egui::Slider::new(&mut self.ui_values.ray_offset, 0.0000001..=0.01).logarithmic(true))
+                    .on_hover_text(RAY_OFFSET_TOOLTIP);
+                if ui.button("Reset").on_hover_text(RAY_OFFSET_RESET_TOOLTIP).clicked() {
+                    self.ui_values.ray_offset = RAY_OFFSET_DEFAULT;
+                }
+            });
+        });
+    }
+    
+    /// Displays whether the remote control is enabled and the port it listens on. The port can only 
+    /// be changed while it is disabled. 
+    fn display_remote_control_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Remote Control:").on_hover_text(REMOTE_CONTROL_TOOLTIP);
+                let mut enabled = self.remote_control.is_some();
+                if ui.checkbox(&mut enabled, "").on_hover_text(REMOTE_CONTROL_TOOLTIP).changed() {
+                    self.remote_control = None;
+                    if enabled {
+                        match RemoteControl::start(self.ui_values.remote_control_port, ui.ctx().clone()) {
+                            Ok(remote_control) => self.remote_control = Some(remote_control),
+                            Err(e) => warn!("Error starting the remote control: {}", e.error),
+                        }
+                    }
+                }
+                ui.label("Port:");
+                ui.add_enabled(!enabled, egui::DragValue::new(&mut self.ui_values.remote_control_port).range(1..=u16::MAX));
+            });
+        });
+    }
+    
+    /// Answers the requests which arrived through the remote control since the last frame. 
+    fn poll_remote_control(&mut self) {
+        while let Some(request) = self.remote_control.as_ref().and_then(RemoteControl::poll) {
+            let reply = match &request.command {
+                RemoteCommand::Start {scene} => self.start_remote_render(scene.as_deref()),
+                RemoteCommand::Abort => match self.abort_render() {
+                    true => RemoteReply::Done,
+                    false => RemoteReply::Failed("No render is running!".to_string()),
+                },
+                RemoteCommand::Status => RemoteReply::Status(RemoteStatus {
+                    rendering: self.app_to_render_channel.is_some(),
+                    progress: self.ui_values.render_progress,
+                    elapsed: self.ui_values.frame_gen_time,
+                }),
+                RemoteCommand::Image => RemoteReply::Image(self.image_actual.clone()),
+            };
+            request.reply(reply);
+        }
+    }
+    
+    /// Starts a render requested through the remote control, of the given scene file content if 
+    /// any. The scene replaces the one in the UI, just like opening a scene file. 
+    fn start_remote_render(&mut self, scene: Option<&str>) -> RemoteReply {
+        if self.app_to_render_channel.is_some() {
+            return RemoteReply::Failed("A render is already running!".to_string());
+        }
+        if let Some(scene) = scene {
+            if let Err(e) = scene_file::from_ron(scene, &mut self.ui_values, &mut self.texture_cache) {
+                return RemoteReply::Failed(format!("Invalid scene: {}", e.error));
+            }
+        }
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+        if !self.check_render_legality() {
+            return RemoteReply::Failed("The scene can not be rendered in its current state!".to_string());
+        }
+        self.dispatch_render();
+        RemoteReply::Done
+    }
+    
+    /// Displays the number of paths to dump and a button to trace them and write them to a file. 
+    fn display_path_dump_settings(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.horizontal_top(|ui| {
+                ui.label("Debug paths:").on_hover_text(PATH_DUMP_TOOLTIP);
+                let mut count_string = self.ui_values.debug_path_count.to_string();
+                ui.text_edit_singleline(&mut count_string).on_hover_text(PATH_DUMP_TOOLTIP);
+                if let Ok(count) = count_string.parse::<u32>() {
+                    self.ui_values.debug_path_count = count.max(1);
+                }
+                
+                let dump_button = egui::Button::new("Dump to File");
+                if ui.add_enabled(self.path_dump.is_none(), dump_button).on_hover_text(PATH_DUMP_TOOLTIP).clicked() {
+                    self.start_path_dump();
+                }
+            });
+        });
+    }
+    
+    /// Asks for a file and starts tracing the debug paths in the background, see 
+    /// [ray_dump::dump_paths]. 
+    fn start_path_dump(&mut self) {
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+        if !self.check_render_legality() {
+            warn!("The scene can not be rendered in its current state, no paths are dumped.");
+            return;
+        }
+        let dialog = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .add_filter("CSV", &["csv"])
+            .set_file_name("paths.json")
+            .save_file();
+        let Some(path) = dialog else {
+            return;
+        };
+        
+        let uniforms = self.build_uniforms();
+        let (width, height) = (self.ui_values.width, self.ui_values.height);
+        let nbr_of_paths = self.ui_values.debug_path_count;
+        //in the same order as the objects of the uniforms
+        let object_names: Vec<String> = self.ui_values.ui_objects.iter()
+            .filter(|o| !o.hidden)
+            .map(|o| o.name.clone())
+            .collect();
+        let name = format!("Debug paths {}", path.display());
+        self.path_dump = Some(BackgroundLoad::start(name, move |progress| {
+            ray_dump::dump_paths(uniforms, width, height, nbr_of_paths, &object_names, &path, progress)
+        }));
+    }
+    
+    /// Displays the result of the last light balance probe and lets the user apply the proposed 
+    /// light scale. 
+    fn display_light_balance_result(&mut self, ctx: &egui::Context) {
+        let Some(average_luminance) = self.light_balance_result else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("Auto-balance lights")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Average luminance of the probe render: {average_luminance:.4}"));
+                if average_luminance > 0.0 {
+                    let factor = LIGHT_BALANCE_TARGET_LUMINANCE / average_luminance;
+                    ui.label(format!("Scaling all lights by {factor:.3} brings it to {LIGHT_BALANCE_TARGET_LUMINANCE}. \
+                        The new light scale would be {:.3}.", self.ui_values.light_scale * factor));
+                    apply = ui.button("Apply").clicked();
+                } else {
+                    ui.label("No light reaches the camera, the lights can not be balanced.");
+                }
+            });
+        
+        if apply {
+            self.ui_values.light_scale *= LIGHT_BALANCE_TARGET_LUMINANCE / average_luminance;
+        }
+        if apply || !open {
+            self.light_balance_result = None;
+        }
+    }
+    
+    /// Runs [scene_check::check_scene] on the current scene and lists the problems found, followed 
+    /// by a preview of the color every object reflects under every light source. 
+    fn display_scene_check(&mut self, ui: &mut Ui) {
+        let warnings = self.check_scene();
+        if warnings.is_empty() {
+            ui.label("Scene check: no problems found").on_hover_text(SCENE_CHECK_TOOLTIP);
+        } else {
+            egui::Frame::NONE.fill(Color32::from_rgb(255, 220, 160)).inner_margin(5.0).show(ui, |ui| {
+                ui.label(format!("Scene check: {} problem(s) found", warnings.len()))
+                    .on_hover_text(SCENE_CHECK_TOOLTIP);
+                for warning in warnings {
+                    ui.label(format!("• {warning}"));
+                }
+            });
+        }
+        
+        let pairs = scene_check::reflected_light(&self.ui_values.ui_objects, &self.ui_values.ui_lights);
+        if !pairs.is_empty() {
+            egui::CollapsingHeader::new("Reflected colors").show(ui, |ui| {
+                ui.label("The color of each object under each light source, ignoring geometry.")
+                    .on_hover_text(REFLECTED_COLORS_TOOLTIP);
+                egui::Grid::new("reflected_colors").striped(true).show(ui, |ui| {
+                    for pair in pairs {
+                        let (r, g, b) = pair.rgb;
+                        let color = Color32::from(egui::Rgba::from_rgb(r.max(0.0), g.max(0.0), b.max(0.0)));
+                        let (rect, response) = ui.allocate_exact_size(Vec2::new(16.0, 16.0), Sense::hover());
+                        ui.painter().rect_filled(rect, 2.0, color);
+                        response.on_hover_text(format!("{:.2}% of the light is reflected", pair.reflected_share * 100.0));
+                        ui.label(format!("{} under {}", pair.object_name, pair.emitter_name));
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+        ui.add_space(10.0);
+    }
+    
+    /// Replaces the scene with the one of the given tutorial and shows its first step. 
+    fn start_tutorial(&mut self, tutorial: Tutorial) {
+        self.tutorial = Some(TutorialProgress::start(tutorial, &mut self.ui_values));
+        //the spectra of the tutorial scene are generated from their types
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+    }
+    
+    /// Displays the current step of the tutorial in a floating window, from where the scene can 
+    /// be rendered and the next step applied. 
+    fn display_tutorial_window(&mut self, ctx: &egui::Context) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        let (step, nbr_of_steps) = tutorial.get_step();
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new(tutorial.get_tutorial().to_string())
+            .open(&mut open)
+            .default_width(350.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Step {} of {}: {}", step + 1, nbr_of_steps, tutorial.get_title()));
+                ui.separator();
+                ui.label(tutorial.get_text());
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let rendering = *self.currently_rendering.lock().unwrap();
+                    if ui.add_enabled(!rendering && self.check_render_legality(), egui::Button::new("Render"))
+                        .clicked() {
+                        action = Some(TutorialAction::Render);
+                    }
+                    if ui.add_enabled(!rendering && !tutorial.is_last_step(), egui::Button::new("Next step"))
+                        .on_hover_text(TUTORIAL_NEXT_STEP_TOOLTIP).clicked() {
+                        action = Some(TutorialAction::NextStep);
+                    }
+                    if ui.add_enabled(!rendering, egui::Button::new("Restart"))
+                        .on_hover_text(TUTORIAL_RESTART_TOOLTIP).clicked() {
+                        action = Some(TutorialAction::Restart);
+                    }
+                });
+            });
+        
+        match action {
+            Some(TutorialAction::Render) => {
+                self.dispatch_render();
+                self.tab_to_focus = Some(UiTab::Display);
+            }
+            Some(TutorialAction::NextStep) => {
+                if let Some(tutorial) = &mut self.tutorial {
+                    tutorial.next(&mut self.ui_values);
+                }
+            }
+            Some(TutorialAction::Restart) => {
+                let tutorial = tutorial.get_tutorial();
+                self.start_tutorial(tutorial);
+            }
+            None => (),
+        }
+        if !open {
+            self.tutorial = None;
+        }
+    }
+    
+    /// Checks the visible objects for degenerate geometry and other problems, as seen from the 
+    /// active camera. The objects and lights are checked in world space. 
+    fn check_scene(&self) -> Vec<String> {
+        let camera = &self.ui_values.ui_cameras[self.ui_values.active_camera];
+        let camera_position = nalgebra::point![camera.pos_x, camera.pos_y, camera.pos_z];
+        let objects: Vec<UIObject> = self.ui_values.ui_objects.iter().map(hierarchy::world_object).collect();
+        let lights: Vec<UILight> = self.ui_values.ui_lights.iter().map(hierarchy::world_light).collect();
+        scene_check::check_scene(&objects, &lights, camera_position)
+    }
+    
+    /// Shortcut function that generates and displays the time taken to render the image. 
+    /// Reports how noisy the image still is while the noise heatmap is shown, together with the 
+    /// frames it takes until [NOISE_ERROR_QUANTILE] of the pixels reach [NOISE_TARGET_ERROR]. The 
+    /// error of a pixel falls with the square root of its samples. 
+    fn display_noise_error(&self, ui: &mut Ui) {
+        let Some(error) = self.noise_error else {
+            return;
+        };
+        let progress = self.ui_values.render_progress;
+        let frames = progress.frames_done.saturating_sub(progress.frames_resumed);
+        let mut text = format!("{:.0}% of the pixels have a noise below {:.2}%", 
+                               NOISE_ERROR_QUANTILE * 100.0, error * 100.0);
+        if frames > 0 && error > NOISE_TARGET_ERROR {
+            let frames_needed = (frames as f32 * (error / NOISE_TARGET_ERROR).powi(2)).ceil() as u32;
+            text += &format!(", about {} more frames reach {:.0}%", 
+                             frames_needed.saturating_sub(frames), NOISE_TARGET_ERROR * 100.0);
+        }
+        ui.label(text).on_hover_text(DISPLAY_NOISE_ERROR_TOOLTIP);
+    }
+    
+    /// Shows the chain of post effects in a collapsible panel, where effects can be added, 
+    /// adjusted, reordered and removed. Returns whether the chain changed. 
+    fn display_post_effects(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        egui::CollapsingHeader::new(format!("Post effects ({})", self.ui_values.post_effects.len()))
+                .id_salt("post effects").show(ui, |ui| {
+            let mut move_up = None;
+            let mut remove = None;
+            for (i, effect) in self.ui_values.post_effects.iter_mut().enumerate() {
+                ui.horizontal_top(|ui| {
+                    ui.label(format!("{}. {effect}:", i + 1));
+                    let (slider, tooltip) = match effect {
+                        PostEffect::Vignette(strength) => 
+                            (egui::Slider::new(strength, 0.0..=1.0), POST_EFFECT_VIGNETTE_TOOLTIP),
+                        PostEffect::ChromaticFringe(amount) => 
+                            (egui::Slider::new(amount, 0.0..=0.05), POST_EFFECT_CHROMATIC_FRINGE_TOOLTIP),
+                        PostEffect::Contrast(contrast) => 
+                            (egui::Slider::new(contrast, 0.5..=2.0), POST_EFFECT_CONTRAST_TOOLTIP),
+                        PostEffect::Grain(iso) => 
+                            (egui::Slider::new(iso, 100.0..=25600.0).logarithmic(true).prefix("ISO "), 
+                             POST_EFFECT_GRAIN_TOOLTIP),
+                    };
+                    changed |= ui.add(slider).on_hover_text(tooltip).changed();
+                    if ui.add_enabled(i > 0, egui::Button::new("Move up")).clicked() {
+                        move_up = Some(i);
+                    }
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = move_up {
+                self.ui_values.post_effects.swap(i - 1, i);
+                changed = true;
+            }
+            if let Some(i) = remove {
+                self.ui_values.post_effects.remove(i);
+                changed = true;
+            }
+            ui.menu_button("Add effect", |ui| {
+                for effect in PostEffect::ALL {
+                    if ui.button(effect.to_string()).clicked() {
+                        self.ui_values.post_effects.push(effect);
+                        changed = true;
+                        ui.close_menu();
+                    }
+                }
+            }).response.on_hover_text(POST_EFFECTS_TOOLTIP);
+        });
+        changed
+    }
+    
+    /// Shows the rays shot by the current or last render in a collapsible panel. 
+    fn display_render_statistics(&self, ui: &mut Ui) {
+        let statistics = self.ui_values.render_statistics;
+        egui::CollapsingHeader::new("Statistics").id_salt("render statistics").show(ui, |ui| {
+            egui::Grid::new("render_statistics_grid").show(ui, |ui| {
+                ui.label("Camera rays").on_hover_text(STATISTICS_CAMERA_RAYS_TOOLTIP);
+                ui.label(statistics.camera_rays.to_string());
+                ui.end_row();
+                ui.label("Bounce rays").on_hover_text(STATISTICS_BOUNCE_RAYS_TOOLTIP);
+                ui.label(statistics.bounce_rays.to_string());
+                ui.end_row();
+                ui.label("Shadow rays").on_hover_text(STATISTICS_SHADOW_RAYS_TOOLTIP);
+                ui.label(statistics.shadow_rays.to_string());
+                ui.end_row();
+                ui.label("Photon rays").on_hover_text(STATISTICS_PHOTON_RAYS_TOOLTIP);
+                ui.label(statistics.photon_rays.to_string());
+                ui.end_row();
+                ui.label("Average bounces").on_hover_text(STATISTICS_AVERAGE_BOUNCES_TOOLTIP);
+                ui.label(format!("{:.2}", statistics.average_bounces()));
+                ui.end_row();
+                ui.label("Rays per second").on_hover_text(STATISTICS_RAYS_PER_SECOND_TOOLTIP);
+                let rays_per_second = self.ui_values.frame_gen_time
+                    .filter(|duration| !duration.is_zero())
+                    .map(|duration| statistics.total() as f64 / duration.as_secs_f64());
+                ui.label(rays_per_second.map_or("-".to_string(), |rays| format!("{rays:.0}")));
+                ui.end_row();
+            });
+        });
+    }
+    
+    fn display_frame_generation_time(&mut self, ui: &mut Ui) {
+        let (s, t) = match self.ui_values.frame_gen_time {
+            Some(duration) => {
+                let remaining_duration = self.ui_values.render_progress.remaining_time(duration)
+                    .unwrap_or(Duration::ZERO);
+                (format!("{:.3?}", duration), format!("{:.3?}", remaining_duration))
+            },
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        ui.label(format!("Time to generate image: {s}"));
+        ui.label(format!("Approximate time remaining: {t}"));
+    }
+    
+    /// Shortcut function to display various settings for the active camera. The settings can be 
+    /// changed and the updated values will be used in the rendering process. 
+    fn display_camera_settings(&mut self, ui: &mut Ui) {
+        let index = self.ui_values.active_camera;
+        let nbr_of_cameras = self.ui_values.ui_cameras.len();
+        let camera = &mut self.ui_values.ui_cameras[index];
+        
+        //name
+        ui.horizontal_top(|ui| {
+            let backup_name = &format!("Camera #{index}");
+            display_name_with_edit(ui, &mut camera.name, backup_name, &mut camera.editing_name);
+            ui.add_space(100.0);
+
+            if ui.button("Copy").on_hover_text(CAMERA_COPY_TOOLTIP).clicked() {
+                self.ui_values.after_ui_action = Some(AfterUIActions::CopyCamera(index));
+            }
+            let delete_button = egui::widgets::Button::new("Delete this camera").fill(Color32::LIGHT_RED);
+            if ui.add_enabled(nbr_of_cameras > 1, delete_button).clicked() {
+                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteCamera(index));
+            }
+        });
+        
+        //camera position
+        ui.horizontal_top(|ui| {
+            let mut pos_x_string = camera.pos_x.to_string();
+            let mut pos_y_string = camera.pos_y.to_string();
+            let mut pos_z_string = camera.pos_z.to_string();
+            ui.label("Camera Position: (x:").on_hover_text(CAMERA_POSITION_TOOLTIP);
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_x_string));
+            ui.label("y:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_y_string));
+            ui.label("z:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_z_string));
+            ui.label(")");
+
+            if pos_x_string.parse::<f32>().is_ok() {
+                camera.pos_x = pos_x_string.parse::<f32>().unwrap();
+            }
+            if pos_y_string.parse::<f32>().is_ok() {
+                camera.pos_y = pos_y_string.parse::<f32>().unwrap();
+            }
+            if pos_z_string.parse::<f32>().is_ok() {
+                camera.pos_z = pos_z_string.parse::<f32>().unwrap();
+            }
+        });
+        
+        //camera direction
+        ui.horizontal_top(|ui| {
+            let mut dir_x_string = camera.dir_x.to_string();
+            let mut dir_y_string = camera.dir_y.to_string();
+            let mut dir_z_string = camera.dir_z.to_string();
+
+            ui.label("Camera Direction: (x:").on_hover_text(CAMERA_DIRECTION_TOOLTIP);
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dir_x_string));
+            ui.label("y:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dir_y_string));
+            ui.label("z:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dir_z_string));
+            ui.label(")");
+
+            if dir_x_string.parse::<f32>().is_ok() {
+                camera.dir_x = dir_x_string.parse::<f32>().unwrap();
+            }
+            if dir_y_string.parse::<f32>().is_ok() {
+                camera.dir_y = dir_y_string.parse::<f32>().unwrap();
+            }
+            if dir_z_string.parse::<f32>().is_ok() {
+                camera.dir_z = dir_z_string.parse::<f32>().unwrap();
+            }
+        });
+
+        //camera up direction
+        ui.horizontal_top(|ui| {
+            let mut up_x_string = camera.up_x.to_string();
+            let mut up_y_string = camera.up_y.to_string();
+            let mut up_z_string = camera.up_z.to_string();
+
+            ui.label("Camera Up: (x:").on_hover_text(CAMERA_UP_TOOLTIP);
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut up_x_string));
+            ui.label("y:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut up_y_string));
+            ui.label("z:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut up_z_string));
+            ui.label(")");
+
+            if up_x_string.parse::<f32>().is_ok() {
+                camera.up_x = up_x_string.parse::<f32>().unwrap();
+            }
+            if up_y_string.parse::<f32>().is_ok() {
+                camera.up_y = up_y_string.parse::<f32>().unwrap();
+            }
+            if up_z_string.parse::<f32>().is_ok() {
+                camera.up_z = up_z_string.parse::<f32>().unwrap();
+            }
+        });
+        
+        //camera FOV
+        ui.horizontal_top(|ui| {
+            ui.label("Camera vertical FOV in degrees:").on_hover_text(CAMERA_FOV_TOOLTIP);
+            let mut fov_string = camera.fov_deg_y.to_string();
+
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut fov_string));
+
+            if fov_string.parse::<f32>().is_ok() {
+                camera.fov_deg_y = fov_string.parse::<f32>().unwrap();
+            }
+        });
+
+        //clipping planes
+        ui.horizontal_top(|ui| {
+            ui.label("Clipping:").on_hover_text(CAMERA_CLIPPING_TOOLTIP);
+            let mut near_enabled = camera.near_clip.is_some();
+            ui.checkbox(&mut near_enabled, "Near").on_hover_text(CAMERA_NEAR_CLIP_TOOLTIP);
+            match (near_enabled, &mut camera.near_clip) {
+                (false, near_clip) => *near_clip = None,
+                (true, None) => camera.near_clip = Some(CAMERA_NEAR_CLIP_DEFAULT),
+                (true, Some(near_clip)) => {
+                    ui.add(egui::DragValue::new(near_clip).range(0.0..=f32::MAX).speed(0.01))
+                        .on_hover_text(CAMERA_NEAR_CLIP_TOOLTIP);
+                }
+            }
+            
+            let mut far_enabled = camera.far_clip.is_some();
+            ui.checkbox(&mut far_enabled, "Far").on_hover_text(CAMERA_FAR_CLIP_TOOLTIP);
+            let near_clip = camera.near_clip.unwrap_or(0.0);
+            match (far_enabled, &mut camera.far_clip) {
+                (false, far_clip) => *far_clip = None,
+                (true, None) => camera.far_clip = Some(near_clip + CAMERA_FAR_CLIP_DEFAULT),
+                (true, Some(far_clip)) => {
+                    ui.add(egui::DragValue::new(far_clip).range(near_clip..=f32::MAX).speed(0.01))
+                        .on_hover_text(CAMERA_FAR_CLIP_TOOLTIP);
+                }
+            }
+        });
+
+        //lens model
+        ui.horizontal_top(|ui| {
+            ui.label("Thick lens:").on_hover_text(CAMERA_THICK_LENS_TOOLTIP);
+            ui.checkbox(&mut camera.thick_lens, "");
+        });
+        if camera.thick_lens {
+            ui.horizontal_top(|ui| {
+                let mut aperture_string = camera.aperture_radius.to_string();
+                let mut focus_string = camera.focus_distance.to_string();
+                let mut thickness_string = camera.lens_thickness.to_string();
+                ui.label("Aperture radius:").on_hover_text(CAMERA_APERTURE_RADIUS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut aperture_string));
+                ui.label("Focus distance:").on_hover_text(CAMERA_FOCUS_DISTANCE_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut focus_string));
+                ui.label("Lens thickness:").on_hover_text(CAMERA_LENS_THICKNESS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut thickness_string));
+
+                if let Ok(aperture) = aperture_string.parse::<f32>() {
+                    if aperture >= 0.0 {
+                        camera.aperture_radius = aperture;
+                    }
+                }
+                if let Ok(focus) = focus_string.parse::<f32>() {
+                    if focus > 0.0 {
+                        camera.focus_distance = focus;
+                    }
+                }
+                if let Ok(thickness) = thickness_string.parse::<f32>() {
+                    if thickness >= 0.0 {
+                        camera.lens_thickness = thickness;
+                    }
+                }
+            });
+            ui.horizontal_top(|ui| {
+                let mut refractive_index_string = camera.lens_refractive_index.to_string();
+                let mut abbe_string = camera.lens_abbe_number.to_string();
+                ui.label("Refractive index:").on_hover_text(CAMERA_LENS_REFRACTIVE_INDEX_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut refractive_index_string));
+                ui.label("Abbe number:").on_hover_text(CAMERA_LENS_ABBE_NUMBER_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut abbe_string));
+                ui.label("Wavelength bands:").on_hover_text(CAMERA_LENS_WAVELENGTH_BANDS_TOOLTIP);
+                ui.add(egui::Slider::new(&mut camera.lens_wavelength_bands, 1..=16));
+
+                if let Ok(refractive_index) = refractive_index_string.parse::<f32>() {
+                    if refractive_index > 1.0 {
+                        camera.lens_refractive_index = refractive_index;
+                    }
+                }
+                if let Ok(abbe_number) = abbe_string.parse::<f32>() {
+                    if abbe_number > 0.0 {
+                        camera.lens_abbe_number = abbe_number;
+                    }
+                }
+            });
+        }
+    }
+    
+    /// Shortcut function to display various settings for a single Light object. The settings can 
+    /// be changed and the updated values will be used in the rendering process. 
+    fn display_light_source_settings(&mut self, ui: &mut Ui, index: usize) { 
+        let light = &mut self.ui_values.ui_lights[index];
+        
+        //name
+        ui.horizontal_top(|ui| {
+            let backup_name = &format!("Light Source #{index}");
+            display_name_with_edit(ui, &mut light.name, backup_name, &mut light.editing_name);
+            ui.add_space(100.0);
+            
+            let delete_button = egui::widgets::Button::new("Delete this light source").fill(Color32::LIGHT_RED);
+            if ui.add(delete_button).clicked() {
+                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteLight(index));
+            }
+        });
+        
+        //light position
+        ui.horizontal_top(|ui| {
+            let mut pos_x_string = light.pos_x.to_string();
+            let mut pos_y_string = light.pos_y.to_string();
+            let mut pos_z_string = light.pos_z.to_string();
+            ui.label("Light Position: (x:").on_hover_text(LIGHT_SOURCE_TOOLTIP);
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_x_string));
+            ui.label("y:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_y_string));
+            ui.label("z:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_z_string));
+            ui.label(")");
+
+            if pos_x_string.parse::<f32>().is_ok() {
+                light.pos_x = pos_x_string.parse::<f32>().unwrap();
+            }
+            if pos_y_string.parse::<f32>().is_ok() {
+                light.pos_y = pos_y_string.parse::<f32>().unwrap();
+            }
+            if pos_z_string.parse::<f32>().is_ok() {
+                light.pos_z = pos_z_string.parse::<f32>().unwrap();
+            }
+        });
+
+        //light spectrum
+        ui.horizontal_top(|ui| {
+            let label_color = if !self.ui_values.spectra.contains(&light.spectrum) && is_time_even() {
+                Color32::RED
+            } else {
+                Color32::DARK_GRAY
+            };
+            ui.colored_label(label_color, "Spectrum").on_hover_text(LIGHT_SPECTRUM_TOOLTIP);
+
+            let borrow = light.spectrum.borrow();
+            let selected_text = borrow.to_string();
+            drop(borrow);
+            
+            Self::display_combobox_with_spectrum_list(
+                &mut self.ui_values.spectra,
+                ui, 
+                format!("light source {index} spectrum"),
+                selected_text,
+                LIGHT_SPECTRUM_TOOLTIP,
+                &mut light.spectrum,
+            )
+        });
+        
+        //group
+        ui.horizontal_top(|ui| {
+            ui.label("Group:").on_hover_text(LIGHT_GROUP_TOOLTIP);
+            display_combobox_with_group_list(&self.ui_values.ui_groups, ui, format!("light source {index} group"),
+                                             LIGHT_GROUP_TOOLTIP, &mut light.group, None);
+        });
+        
+        //light linking
+        ui.horizontal_top(|ui| {
+            ui.label("Light linking:").on_hover_text(LIGHT_LINKING_TOOLTIP);
+            ComboBox::new(format!("light source {index} linking"), "")
+                .selected_text(light.link_mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in LightLinkMode::ALL {
+                        ui.selectable_value(&mut light.link_mode, mode, mode.to_string());
+                    }
+                }).response.on_hover_text(LIGHT_LINKING_TOOLTIP);
+        });
+        if light.link_mode != LightLinkMode::All {
+            egui::CollapsingHeader::new(format!("Linked objects ({})", light.linked_objects.len()))
+                .id_salt(format!("light source {index} linked objects"))
+                .show(ui, |ui| {
+                    for object in &self.ui_values.ui_objects {
+                        let mut linked = light.linked_objects.contains(&object.id);
+                        if ui.checkbox(&mut linked, &object.name).on_hover_text(LIGHT_LINKED_OBJECTS_TOOLTIP).changed() {
+                            if linked {
+                                light.linked_objects.push(object.id);
+                            } else {
+                                light.linked_objects.retain(|id| *id != object.id);
+                            }
+                        }
+                    }
+                });
+        }
+    }
+    
+    /// Displays the settings of a single group of the scene hierarchy: its name, its translation 
+    /// and rotation and the group it is nested in. 
+    fn display_group_settings(&mut self, ui: &mut Ui, index: usize) {
+        let group_rc = self.ui_values.ui_groups[index].clone();
+        
+        {
+            let group = &mut *group_rc.borrow_mut();
+            
+            //name
+            ui.horizontal_top(|ui| {
+                let backup_name = &format!("Group #{index}");
+                display_name_with_edit(ui, &mut group.name, backup_name, &mut group.editing_name);
+                ui.add_space(100.0);
+                
+                let delete_button = egui::widgets::Button::new("Delete this group").fill(Color32::LIGHT_RED);
+                if ui.add(delete_button).on_hover_text(GROUP_DELETE_TOOLTIP).clicked() {
+                    self.ui_values.after_ui_action = Some(AfterUIActions::DeleteGroup(index));
+                }
+            });
+            
+            //translation and rotation
+            for (label, tooltip, values) in [
+                ("Group Position: (x:", GROUP_POSITION_TOOLTIP, [&mut group.pos_x, &mut group.pos_y, &mut group.pos_z]),
+                ("Group Rotation: (x:", GROUP_ROTATION_TOOLTIP, [&mut group.rot_x, &mut group.rot_y, &mut group.rot_z]),
+            ] {
+                ui.horizontal_top(|ui| {
+                    let [x, y, z] = values;
+                    let mut x_string = x.to_string();
+                    let mut y_string = y.to_string();
+                    let mut z_string = z.to_string();
+                    ui.label(label).on_hover_text(tooltip);
+                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut x_string));
+                    ui.label("y:");
+                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut y_string));
+                    ui.label("z:");
+                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut z_string));
+                    ui.label(")");
+                    
+                    if let Ok(new_x) = x_string.parse::<f32>() {
+                        *x = new_x;
+                    }
+                    if let Ok(new_y) = y_string.parse::<f32>() {
+                        *y = new_y;
+                    }
+                    if let Ok(new_z) = z_string.parse::<f32>() {
+                        *z = new_z;
+                    }
+                });
+            }
+        }
+        
+        //parent, the list borrows the other groups, so the group may not be borrowed meanwhile
+        ui.horizontal_top(|ui| {
+            ui.label("Parent Group:").on_hover_text(GROUP_PARENT_TOOLTIP);
+            let mut parent = group_rc.borrow().parent.clone();
+            display_combobox_with_group_list(&self.ui_values.ui_groups, ui, format!("group {index} parent"),
+                                             GROUP_PARENT_TOOLTIP, &mut parent, Some(&group_rc));
+            group_rc.borrow_mut().parent = parent;
+        });
+    }
+
+    /// Displays a [ComboBox] which lists all the available spectra. 
+    fn display_combobox_with_spectrum_list(spectra: &mut [Rc<RefCell<UISpectrum>>], ui: &mut Ui, id_salt: String,
+                                           selected_text: String, tool_tip: &str, current_spectrum: &mut Rc<RefCell<UISpectrum>>) {
+        ComboBox::new(id_salt, "")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for spectrum in spectra {
+                    ui.selectable_value(current_spectrum, spectrum.clone(), spectrum.borrow().to_string());
+                }
+            }).response.on_hover_text(tool_tip);
+    }
+
+    /// Displays a [ComboBox] which lists all the available materials. 
+    fn display_combobox_with_material_list(materials: &mut [Rc<RefCell<UIMaterial>>], ui: &mut Ui, id_salt: String,
+                                           selected_text: String, tool_tip: &str, current_material: &mut Rc<RefCell<UIMaterial>>) {
+        ComboBox::new(id_salt, "")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for material in materials {
+                    ui.selectable_value(current_material, material.clone(), material.borrow().to_string());
+                }
+        }).response.on_hover_text(tool_tip);
+    }
+    
+    /// Shortcut function to display the settings for a single Object in the scene. The settings 
+    /// can be changed and the updated values will be used in the rendering process. Each object is 
+    /// differentiated according to their type, and the respective settings will be displayed.
+    ///TODO the type changing logic in this function is a mess. 
+    fn display_objects_settings(&mut self, ui: &mut Ui, index: usize) {
+        let object = &mut self.ui_values.ui_objects[index];
+
+        //name
+        ui.horizontal_top(|ui| {
+            let backup_name = &format!("{object} #{index}");
+            display_name_with_edit(ui, &mut object.name, backup_name, &mut object.editing_name);
+            ui.add_space(30.0);
+            
+            display_object_type_combobox(ui, index, &mut object.ui_object_type);
+            ui.add_space(30.0);
+
+            let delete_button = egui::widgets::Button::new("Delete this object").fill(Color32::LIGHT_RED);
+            if ui.add(delete_button).clicked() {
+                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteObject(index));
+            }
+        });
+        
+        //object position
+        ui.horizontal_top(|ui| {
+            let mut pos_x_string = object.pos_x.to_string();
+            let mut pos_y_string = object.pos_y.to_string();
+            let mut pos_z_string = object.pos_z.to_string();
+            ui.label("Object Position: (x:").on_hover_text(OBJECT_POSITION_TOOLTIP);
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_x_string));
+            ui.label("y:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_y_string));
+            ui.label("z:");
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_z_string));
+            ui.label(")");
+
+            if pos_x_string.parse::<f32>().is_ok() {
+                object.pos_x = pos_x_string.parse::<f32>().unwrap();
+            }
+            if pos_y_string.parse::<f32>().is_ok() {
+                object.pos_y = pos_y_string.parse::<f32>().unwrap();
+            }
+            if pos_z_string.parse::<f32>().is_ok() {
+                object.pos_z = pos_z_string.parse::<f32>().unwrap();
+            }
+        });
+        
+        //type specific information
+        display_object_type_settings(ui, &index.to_string(), &mut object.ui_object_type);
+        
+        //material selection
+        ui.horizontal_top(|ui| {
+            let label_color = if !self.ui_values.materials.contains(&object.material) && is_time_even() {
+                Color32::RED
+            } else {
+                Color32::DARK_GRAY
+            };
+            ui.colored_label(label_color, "Material:").on_hover_text(OBJECT_MATERIAL_TOOLTIP);
+            
+            let selected_text = {
+                let borrow = object.material.borrow();
+                borrow.to_string()
             };
-            if !same {
-                object.ui_object_type = match selected {
-                    Type::PlainBox => UIObjectType::default_plain_box(),
-                    Type::Sphere => UIObjectType::default_sphere(),
-                    Type::RotatedBox => UIObjectType::default_rotated_box(),
+            
+            Self::display_combobox_with_material_list(
+                &mut self.ui_values.materials,
+                ui, 
+                format!("object {index} material"),
+                selected_text,
+                OBJECT_MATERIAL_TOOLTIP,
+                &mut object.material,
+            );
+        });
+        
+        //group
+        ui.horizontal_top(|ui| {
+            ui.label("Group:").on_hover_text(OBJECT_GROUP_TOOLTIP);
+            display_combobox_with_group_list(&self.ui_values.ui_groups, ui, format!("object {index} group"),
+                                             OBJECT_GROUP_TOOLTIP, &mut object.group, None);
+        });
+        
+        //emission
+        ui.horizontal_top(|ui| {
+            let mut emissive = object.emission.is_some();
+            ui.label("Emissive:").on_hover_text(OBJECT_EMISSION_TOOLTIP);
+            ui.checkbox(&mut emissive, "");
+            
+            if !emissive {
+                object.emission = None;
+            } else if object.emission.is_none() {
+                object.emission = self.ui_values.spectra.iter()
+                    .find(|s| s.borrow().spectrum_effect_type == SpectrumEffectType::Emissive)
+                    .or(self.ui_values.spectra.first())
+                    .cloned();
+            }
+            
+            if let Some(emission) = &mut object.emission {
+                let label_color = if !self.ui_values.spectra.contains(emission) && is_time_even() {
+                    Color32::RED
+                } else {
+                    Color32::DARK_GRAY
+                };
+                ui.colored_label(label_color, "Spectrum").on_hover_text(OBJECT_EMISSION_SPECTRUM_TOOLTIP);
+                
+                let selected_text = emission.borrow().to_string();
+                Self::display_combobox_with_spectrum_list(
+                    &mut self.ui_values.spectra,
+                    ui,
+                    format!("object {index} emission spectrum"),
+                    selected_text,
+                    OBJECT_EMISSION_SPECTRUM_TOOLTIP,
+                    emission,
+                );
+                
+                ui.checkbox(&mut object.emission_visible_to_camera, "Visible to camera")
+                    .on_hover_text(OBJECT_EMISSION_VISIBLE_TO_CAMERA_TOOLTIP);
+                ui.checkbox(&mut object.emission_visible_in_reflections, "Visible in reflections")
+                    .on_hover_text(OBJECT_EMISSION_VISIBLE_IN_REFLECTIONS_TOOLTIP);
+            }
+        });
+        
+        //visibility
+        ui.horizontal_top(|ui| {
+            ui.label("Seen by:").on_hover_text(OBJECT_VISIBILITY_TOOLTIP);
+            ui.checkbox(&mut object.visible_to_camera, "Camera")
+                .on_hover_text(OBJECT_VISIBLE_TO_CAMERA_TOOLTIP);
+            ui.checkbox(&mut object.visible_in_reflections, "Reflections")
+                .on_hover_text(OBJECT_VISIBLE_IN_REFLECTIONS_TOOLTIP);
+            ui.checkbox(&mut object.casts_shadows, "Shadows")
+                .on_hover_text(OBJECT_CASTS_SHADOWS_TOOLTIP);
+        });
+        
+        //texture
+        ui.horizontal_top(|ui| {
+            ui.label("Texture:").on_hover_text(OBJECT_TEXTURE_TOOLTIP);
+            match &object.texture {
+                Some(texture) => {
+                    let (width, height) = texture.get_resolution();
+                    let file_name = texture.get_path().file_name().unwrap_or_default().to_string_lossy();
+                    ui.label(format!("{file_name} ({width} x {height})"));
+                    if ui.button("Remove").clicked() {
+                        object.texture = None;
+                    }
+                }
+                None => {
+                    ui.label("None");
+                }
+            }
+            if ui.button("Load Image").on_hover_text(OBJECT_TEXTURE_TOOLTIP).clicked() {
+                let dialog = rfd::FileDialog::new()
+                    .add_filter("Image", &["png", "jpg", "jpeg", "bmp", "tiff", "tif"])
+                    .pick_file();
+                if let Some(path) = dialog {
+                    match self.texture_cache.get(&path) {
+                        Ok(texture) => object.texture = Some(texture),
+                        Err(e) => warn!("Error loading the texture: {}", e.error),
+                    }
+                }
+            }
+        });
+    }
+
+    /// Displays the settings which all spectra must have in common, such as the number of samples.
+    fn display_general_spectrum_settings(&mut self, ui: &mut Ui) {
+        //nbr of samples
+        ui.horizontal_top(|ui| {
+            let nbr_of_samples = &mut self.ui_values.spectrum_number_of_samples;
+            let mut nbr_of_samples_string = nbr_of_samples.to_string();
+            let mut final_nbr_of_samples = *nbr_of_samples;
+
+            ui.label("Number of samples in the spectra:").on_hover_text(SPECTRUM_NUMBER_OF_SAMPLES_TOOLTIP);
+            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut nbr_of_samples_string));
+
+            if nbr_of_samples_string.parse::<usize>().is_ok() {
+                let new_nbr_of_samples = nbr_of_samples_string.parse::<usize>().unwrap();
+                if new_nbr_of_samples > 1 && new_nbr_of_samples <= spectrum::NBR_OF_SAMPLES_MAX 
+                        && new_nbr_of_samples % 8 == 0 {
+                    final_nbr_of_samples = new_nbr_of_samples;
+                }
+            }
+
+            if ui.button("-").clicked() {
+                if *nbr_of_samples % 8 == 0 {
+                    if *nbr_of_samples == 8 {
+                        final_nbr_of_samples = 8;    //at least 8 samples have to be present
+                    } else {
+                        final_nbr_of_samples -= 8;   //subtract 8
+                    }
+                } else {
+                    final_nbr_of_samples = (*nbr_of_samples / 8 * 8).max(8)  //drop down to the nearest multiple of 8, at least 8
+                }
+            }
+
+            if ui.button("+").clicked() {
+                if *nbr_of_samples % 8 == 0 {
+                    final_nbr_of_samples += 8;   //add 8
+                } else {
+                    final_nbr_of_samples = (*nbr_of_samples / 8 + 1) * 8;    //go up to the nearest multiple of 8
+                }
+            }
+
+            if final_nbr_of_samples != *nbr_of_samples && final_nbr_of_samples <= spectrum::NBR_OF_SAMPLES_MAX {
+                self.ui_values.spectrum_number_of_samples = final_nbr_of_samples;
+                self.update_all_spectrum_sample_sizes(final_nbr_of_samples);
+            }
+        });
+
+        //range, the spectra are only generated again once a drag ends or a typed value is committed
+        ui.horizontal_top(|ui| {
+            let lower_bound = self.ui_values.spectrum_lower_bound;
+            let upper_bound = self.ui_values.spectrum_upper_bound;
+
+            ui.label("Spectrum range from:").on_hover_text(SPECTRUM_RANGE_TOOLTIP);
+            let lower_response = ui.add(egui::DragValue::new(&mut self.ui_values.spectrum_lower_bound)
+                .range(spectrum::EXTENDED_WAVELENGTH_LOWER_LIMIT..=upper_bound - SPECTRUM_RANGE_WIDTH_MIN)
+                .suffix(" nm"))
+                .on_hover_text(SPECTRUM_RANGE_TOOLTIP);
+            ui.label("to:");
+            let upper_response = ui.add(egui::DragValue::new(&mut self.ui_values.spectrum_upper_bound)
+                .range(lower_bound + SPECTRUM_RANGE_WIDTH_MIN..=spectrum::EXTENDED_WAVELENGTH_UPPER_LIMIT)
+                .suffix(" nm"))
+                .on_hover_text(SPECTRUM_RANGE_TOOLTIP);
+            
+            let mut committed = [lower_response, upper_response].iter()
+                .any(|response| response.drag_stopped() || (response.changed() && !response.dragged()));
+            let is_visible = lower_bound == spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND 
+                && upper_bound == spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND;
+            if ui.add_enabled(!is_visible, egui::Button::new("Visible"))
+                .on_hover_text(SPECTRUM_RANGE_VISIBLE_TOOLTIP).clicked() {
+                self.ui_values.spectrum_lower_bound = spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND;
+                self.ui_values.spectrum_upper_bound = spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND;
+                committed = true;
+            }
+            if committed {
+                self.update_spectrum();
+            }
+        });
+    }
+
+    /// Displays the simple settings for a single spectrum. Simple settings are the spectrum type or
+    /// the brightness factor. More complicated settings such as individual sample values go to the
+    /// dedicated settings on the right in
+    /// [display_spectrum_right_side](App::display_spectrum_right_side).
+    fn display_spectrum_settings(&mut self, ui: &mut Ui, index: usize) {
+        let ui_spectrum = &mut self.ui_values.spectra[index];
+        let mut ui_spectrum = ui_spectrum.borrow_mut();
+        
+        //name and delete button
+        ui.horizontal_top(|ui| {
+            //name
+            //moving out the bool since multiple mutable access are not allowed for Ref<_>. 
+            let mut editing_name = ui_spectrum.editing_name;
+            let backup_name = &format!("Spectrum {}", index);
+            display_name_with_edit(ui, &mut ui_spectrum.name, backup_name, &mut editing_name);
+            ui_spectrum.editing_name = editing_name;
+            
+            ui.add_space(80.0);
+
+            let delete_button = egui::widgets::Button::new("Delete this Spectrum").fill(Color32::LIGHT_RED);
+            if ui.add(delete_button).clicked() {
+                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteSpectrum(index));
+            }
+        });
+
+        //spectrum type
+        ui.horizontal_top(|ui| {
+            ui.label("Spectrum type:").on_hover_text(SPECTRUM_TYPE_TOOLTIP);
+            
+            let mut selected_type = ui_spectrum.spectrum_type;
+            ComboBox::new(format!("spectrum{}", index), "")   //the format is the ID salt, ensuring that each dropdown is distinct
+                .selected_text(selected_type.to_string())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected_type, UISpectrumType::Custom, format!("{}", UISpectrumType::Custom));
+                    ui.selectable_value(&mut selected_type, UISpectrumType::Solar(1.0), format!("{}", UISpectrumType::Solar(1.0)));
+                    ui.selectable_value(&mut selected_type, UISpectrumType::PlainReflective(1.0), format!("{}", UISpectrumType::PlainReflective(1.0)));
+                    ui.selectable_value(&mut selected_type, UISpectrumType::Temperature(1000.0, 1.0), format!("{}", UISpectrumType::Temperature(1.0, 1.0)));
+                    ui.selectable_value(&mut selected_type, UISpectrumType::ReflectiveRed(1.0), format!("{}", UISpectrumType::ReflectiveRed(1.0)));
+                    ui.selectable_value(&mut selected_type, UISpectrumType::ReflectiveGreen(1.0), format!("{}", UISpectrumType::ReflectiveGreen(1.0)));
+                    ui.selectable_value(&mut selected_type, UISpectrumType::ReflectiveBlue(1.0), format!("{}", UISpectrumType::ReflectiveBlue(1.0)));
+                    if ui.selectable_label(matches!(selected_type, UISpectrumType::ColorChecker(_)), 
+                                           UISpectrumType::ColorChecker(0).to_string()).clicked() 
+                        && !matches!(selected_type, UISpectrumType::ColorChecker(_)) {
+                        selected_type = UISpectrumType::ColorChecker(0);
+                    }
+                }).response.on_hover_text(SPECTRUM_TYPE_TOOLTIP);
+            
+            if selected_type != ui_spectrum.spectrum_type {
+                ui_spectrum.spectrum_type = selected_type;
+                match selected_type {
+                    UISpectrumType::Custom => {}
+                    UISpectrumType::Solar(factor) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_sunlight_spectrum(lower, upper, nbr_of_samples, factor);
+                    }
+                    UISpectrumType::PlainReflective(factor) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_singular_reflectance_factor(lower, upper, nbr_of_samples, factor);
+                    }
+                    UISpectrumType::Temperature(temp, factor) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_temperature_spectrum(lower, upper, temp, nbr_of_samples, factor); 
+                    }
+                    UISpectrumType::ReflectiveRed(factor) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_red(lower, upper, nbr_of_samples, factor);
+                    }
+                    UISpectrumType::ReflectiveGreen(factor) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_green(lower, upper, nbr_of_samples, factor);
+                    }
+                    UISpectrumType::ReflectiveBlue(factor) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_blue(lower, upper, nbr_of_samples, factor);
+                    }
+                    UISpectrumType::ColorChecker(patch) => {
+                        let lower = self.ui_values.spectrum_lower_bound;
+                        let upper = self.ui_values.spectrum_upper_bound;
+                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
+                        ui_spectrum.spectrum = Spectrum::new_color_checker_patch(patch, lower, upper, nbr_of_samples);
+                        ui_spectrum.spectrum_effect_type = SpectrumEffectType::Reflective;
+                    }
                 }
-            }
-            ui.add_space(30.0);
-
-            let delete_button = egui::widgets::Button::new("Delete this object").fill(Color32::LIGHT_RED);
-            if ui.add(delete_button).clicked() {
-                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteObject(index));
+                self.ui_values.after_ui_action = Some(AfterUIActions::UpdateSelectedSpectrum(index));
             }
         });
         
-        //object position
+        //spectrum reflectance
         ui.horizontal_top(|ui| {
-            let mut pos_x_string = object.pos_x.to_string();
-            let mut pos_y_string = object.pos_y.to_string();
-            let mut pos_z_string = object.pos_z.to_string();
-            ui.label("Object Position: (x:").on_hover_text(OBJECT_POSITION_TOOLTIP);
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_x_string));
-            ui.label("y:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_y_string));
-            ui.label("z:");
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut pos_z_string));
-            ui.label(")");
-
-            if pos_x_string.parse::<f32>().is_ok() {
-                object.pos_x = pos_x_string.parse::<f32>().unwrap();
-            }
-            if pos_y_string.parse::<f32>().is_ok() {
-                object.pos_y = pos_y_string.parse::<f32>().unwrap();
-            }
-            if pos_z_string.parse::<f32>().is_ok() {
-                object.pos_z = pos_z_string.parse::<f32>().unwrap();
+            ui.label("Behavior:").on_hover_text(SPECTRUM_EFFECT_TYPE_TOOLTIP);
+            
+            let mut selected_type = ui_spectrum.spectrum_effect_type;
+            ComboBox::new(format!("spectrum effect {}", index), "")
+                .selected_text(selected_type.to_string())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected_type, SpectrumEffectType::Emissive, format!("{}", SpectrumEffectType::Emissive));
+                    ui.selectable_value(&mut selected_type, SpectrumEffectType::Reflective, format!("{}", SpectrumEffectType::Reflective));
+                }).response.on_hover_text(SPECTRUM_EFFECT_TYPE_TOOLTIP);
+            
+            if selected_type != ui_spectrum.spectrum_effect_type {
+                ui_spectrum.spectrum_effect_type = selected_type;
+                self.ui_values.after_ui_action = Some(AfterUIActions::UpdateSelectedSpectrum(index));
             }
         });
-        
-        //type specific information
-        match object.ui_object_type {
-            UIObjectType::PlainBox(x_length, y_length, z_length) => {
-                //dimensions
+
+        //spectrum type sub settings
+        let mut changed = false;
+        match &mut ui_spectrum.spectrum_type {
+            UISpectrumType::Solar(factor) | UISpectrumType::PlainReflective(factor) => {
+                changed = display_factor(ui, factor) || changed;
+            }
+            UISpectrumType::Temperature(temp, factor) => {
+                //temperature
                 ui.horizontal_top(|ui| {
-                    let mut dim_x_string = x_length.to_string();
-                    let mut dim_y_string = y_length.to_string();
-                    let mut dim_z_string = z_length.to_string();
-                    ui.label("Object Dimensions: (x:").on_hover_text(OBJECT_PLAIN_BOX_DIMENSIONS_TOOLTIP);
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_x_string));
-                    ui.label("y:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_y_string));
-                    ui.label("z:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_z_string));
-                    ui.label(")");
+                    let mut temp_string = temp.to_string();
 
-                    if dim_x_string.parse::<f32>().is_ok() {
-                        let new_length_x = dim_x_string.parse::<f32>().unwrap();
-                        if new_length_x > 0.0 && new_length_x != x_length {
-                            object.ui_object_type = UIObjectType::PlainBox(new_length_x, y_length, z_length);
-                        }
-                    }
-                    if dim_y_string.parse::<f32>().is_ok() {
-                        let new_length_y = dim_y_string.parse::<f32>().unwrap();
-                        if new_length_y > 0.0 && new_length_y != y_length {
-                            object.ui_object_type = UIObjectType::PlainBox(x_length, new_length_y, z_length);
-                        }
-                    }
-                    if dim_z_string.parse::<f32>().is_ok() {
-                        let new_length_z = dim_z_string.parse::<f32>().unwrap();
-                        if new_length_z > 0.0 && new_length_z != z_length {
-                            object.ui_object_type = UIObjectType::PlainBox(x_length, y_length, new_length_z);
+                    ui.label("Black body radiation temperature:");
+                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut temp_string));
+                    ui.label("K");  //TODO add support for different temperature units
+
+                    if temp_string.parse::<f32>().is_ok() {
+                        let new_temp = temp_string.parse::<f32>().unwrap();
+                        if new_temp != *temp && new_temp > 0.0 {
+                            *temp = new_temp;
+                            changed = true;
                         }
                     }
                 });
-            }
-            UIObjectType::Sphere(radius) => {
-                //radius
+
+                //factor
+                changed = display_factor(ui, factor) || changed;
+                
+                //factor by illuminance
                 ui.horizontal_top(|ui| {
-                    let mut radius_string = radius.to_string();
-                    ui.label("Radius: ").on_hover_text(OBJECT_SPHERE_RADIUS_TOOLTIP);
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_string));
+                    ui.label("Illuminance:").on_hover_text(ILLUMINANCE_HELPER_TOOLTIP);
+                    let mut illuminance_string = self.ui_values.illuminance_helper_lux.to_string();
+                    ui.add_sized([60.0, 18.0], TextEdit::singleline(&mut illuminance_string));
+                    ui.label("lx at");
+                    let mut distance_string = self.ui_values.illuminance_helper_distance.to_string();
+                    ui.add_sized([60.0, 18.0], TextEdit::singleline(&mut distance_string));
+                    ui.label("m");
                     
-                    if radius_string.parse::<f32>().is_ok() {
-                        let new_radius = radius_string.parse::<f32>().unwrap();
-                        if new_radius > 0.0 {
-                            object.ui_object_type = UIObjectType::Sphere(new_radius);
-                        }
+                    if let Ok(illuminance) = illuminance_string.parse::<f32>() {
+                        self.ui_values.illuminance_helper_lux = illuminance.max(0.0);
+                    }
+                    if let Ok(distance) = distance_string.parse::<f32>() {
+                        self.ui_values.illuminance_helper_distance = distance.max(0.0);
+                    }
+                    
+                    if ui.button("Set factor").on_hover_text(ILLUMINANCE_HELPER_TOOLTIP).clicked() {
+                        *factor = spectrum::temperature_multiplier_for_illuminance(
+                            self.ui_values.spectrum_lower_bound,
+                            self.ui_values.spectrum_upper_bound,
+                            *temp,
+                            self.ui_values.spectrum_number_of_samples,
+                            self.ui_values.illuminance_helper_lux,
+                            self.ui_values.illuminance_helper_distance,
+                        );
+                        changed = true;
                     }
                 });
             }
-            UIObjectType::RotatedBox(x_length, y_length, z_length, 
-                                     x_rotation, y_rotation, z_rotation) => {
-                //dimensions
+            UISpectrumType::ReflectiveRed(factor) |
+            UISpectrumType::ReflectiveGreen(factor) |
+            UISpectrumType::ReflectiveBlue(factor) => {
+                //factor
+                changed = display_factor(ui, factor);
+            }
+            UISpectrumType::ColorChecker(patch) => {
                 ui.horizontal_top(|ui| {
-                    let mut dim_x_string = x_length.to_string();
-                    let mut dim_y_string = y_length.to_string();
-                    let mut dim_z_string = z_length.to_string();
-                    ui.label("Object Dimensions: (x:").on_hover_text(OBJECT_ROTATED_BOX_DIMENSIONS_TOOLTIP);
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_x_string));
-                    ui.label("y:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_y_string));
-                    ui.label("z:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_z_string));
-                    ui.label(")");
-
-                    if dim_x_string.parse::<f32>().is_ok() {
-                        let new_length_x = dim_x_string.parse::<f32>().unwrap();
-                        if new_length_x > 0.0 && new_length_x != x_length {
-                            object.ui_object_type = UIObjectType::RotatedBox(new_length_x, y_length, z_length, x_rotation, y_rotation, z_rotation);
-                        }
-                    }
-                    if dim_y_string.parse::<f32>().is_ok() {
-                        let new_length_y = dim_y_string.parse::<f32>().unwrap();
-                        if new_length_y > 0.0 && new_length_y != y_length {
-                            object.ui_object_type = UIObjectType::RotatedBox(x_length, new_length_y, z_length, x_rotation, y_rotation, z_rotation);
-                        }
-                    }
-                    if dim_z_string.parse::<f32>().is_ok() {
-                        let new_length_z = dim_z_string.parse::<f32>().unwrap();
-                        if new_length_z > 0.0 && new_length_z != z_length {
-                            object.ui_object_type = UIObjectType::RotatedBox(x_length, y_length, new_length_z, x_rotation, y_rotation, z_rotation);
-                        }
+                    ui.label("Patch:").on_hover_text(SPECTRUM_COLOR_CHECKER_TOOLTIP);
+                    let mut selected_patch = *patch;
+                    ComboBox::new(format!("spectrum color checker patch {index}"), "")
+                        .selected_text(format!("{} {}", *patch + 1, spectral_data::COLOR_CHECKER_PATCH_NAMES[*patch]))
+                        .show_ui(ui, |ui| {
+                            for (i, name) in spectral_data::COLOR_CHECKER_PATCH_NAMES.iter().enumerate() {
+                                ui.selectable_value(&mut selected_patch, i, format!("{} {name}", i + 1));
+                            }
+                        }).response.on_hover_text(SPECTRUM_COLOR_CHECKER_TOOLTIP);
+                    
+                    if selected_patch != *patch {
+                        *patch = selected_patch;
+                        changed = true;
                     }
                 });
-                
-                //rotation
+            }
+            UISpectrumType::Custom => {
                 ui.horizontal_top(|ui| {
-                    let mut rot_x_string = x_rotation.to_string();
-                    let mut rot_y_string = y_rotation.to_string();
-                    let mut rot_z_string = z_rotation.to_string();
-                    ui.label("Object Rotation: (x:").on_hover_text(OBJECT_ROTATED_BOX_ANGLES_TOOLTIP);
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_x_string));
-                    ui.label("y:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_y_string));
-                    ui.label("z:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_z_string));
-                    ui.label(")");
+                    ui.label("Adjustment:").on_hover_text(CUSTOM_SPECTRUM_FACTOR_ADJUST_TOOLTIP);
 
-                    if rot_x_string.parse::<f32>().is_ok() {
-                        let new_rotation_x = rot_x_string.parse::<f32>().unwrap();
-                        if new_rotation_x != x_rotation {
-                            object.ui_object_type = UIObjectType::RotatedBox(x_length, y_length, z_length, new_rotation_x, y_rotation, z_rotation);
-                        }
-                    }
-                    if rot_y_string.parse::<f32>().is_ok() {
-                        let new_rotation_y = rot_y_string.parse::<f32>().unwrap();
-                        if new_rotation_y != y_rotation {
-                            object.ui_object_type = UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, new_rotation_y, z_rotation);
-                        }
-                    }
-                    if rot_z_string.parse::<f32>().is_ok() {
-                        let new_rotation_z = rot_z_string.parse::<f32>().unwrap();
-                        if new_rotation_z != z_rotation {
-                            object.ui_object_type = UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, new_rotation_z);
-                        }
+                    ui.style_mut().spacing.slider_width = 200.0;
+                    let slider = egui::Slider::new(&mut ui_spectrum.adjust_custom_spectrum_slider, 0.01..=100.0).logarithmic(true);
+                    ui.add(slider);
+                    
+                    if ui.button("Apply").clicked() {
+                        let factor = ui_spectrum.adjust_custom_spectrum_slider;
+                        ui_spectrum.spectrum *= factor;
+                        changed = true; 
                     }
                 });
             }
         }
-        
-        //material selection
-        ui.horizontal_top(|ui| {
-            let label_color = if !self.ui_values.materials.contains(&object.material) && is_time_even() {
-                Color32::RED
-            } else {
-                Color32::DARK_GRAY
-            };
-            ui.colored_label(label_color, "Material:").on_hover_text(OBJECT_MATERIAL_TOOLTIP);
-            
-            let selected_text = {
-                let borrow = object.material.borrow();
-                borrow.to_string()
-            };
-            
-            Self::display_combobox_with_material_list(
-                &mut self.ui_values.materials,
-                ui, 
-                format!("object {index} material"),
-                selected_text,
-                OBJECT_MATERIAL_TOOLTIP,
-                &mut object.material,
-            );
-        });
+
+
+        drop(ui_spectrum);  //I just pray that this is future-proof
+        if changed {
+            self.update_spectrum()
+        }
     }
 
-    /// Displays the settings which all spectra must have in common, such as the number of samples.
-    fn display_general_spectrum_settings(&mut self, ui: &mut Ui) {
-        //nbr of samples
-        ui.horizontal_top(|ui| {
-            let nbr_of_samples = &mut self.ui_values.spectrum_number_of_samples;
-            let mut nbr_of_samples_string = nbr_of_samples.to_string();
-            let mut final_nbr_of_samples = *nbr_of_samples;
+    /// Displays the right side of spectrum settings. Here the user can preview the color of
+    /// the spectrum and each samples individual value.
+    fn display_spectrum_right_side(&mut self, ui: &mut Ui) {
+        match self.ui_values.selected_spectrum.as_mut() {
+            Some(selected) => {
+                let spectrum = &mut selected.spectrum;
+                let raw_rgb = spectrum.get_rgb_early();
+                let (r, g, b) = if self.ui_values.gamut_compression {gamut::compress(raw_rgb)} else {raw_rgb};
+                
+                ui.horizontal_top(|ui| {
+                    ui.colored_label(Color32::RED, "Any changes will not be applied unless saved. Selecting another spectrum will discard changes!");
+                    if ui.button("Save").clicked() {
+                        self.ui_values.after_ui_action = Some(AfterUIActions::SaveSelectedSpectrum(selected.selected_spectrum));
+                    }
+                });
+                
+                match selected.spectrum_effect_type {
+                    SpectrumEffectType::Emissive => {
+                        //color squares
+                        ui.horizontal_top(|ui| {
+                            //observed color
+                            ui.vertical(|ui| {
+                                let r_byte = (r.clamp(0.0, 1.0) * 255.0) as u8;
+                                let g_byte = (g.clamp(0.0, 1.0) * 255.0) as u8;
+                                let b_byte = (b.clamp(0.0, 1.0) * 255.0) as u8;
+                                let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                                let contrasting_text_color = if luminance < 0.5 { Color32::WHITE } else {Color32::BLACK};
+                
+                                egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
+                                    .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
+                                    .show(ui, |ui| {
+                                        ui.set_max_size(Vec2::new(200.0, 100.0));
+                                        ui.centered_and_justified(|ui| {
+                                            ui.colored_label(contrasting_text_color, format!("{r_byte:02X}{g_byte:02X}{b_byte:02X}"));
+                                        });
+                                    }).response.on_hover_text(OBSERVED_COLOR_TOOLTIP);
+                                ui.label("Observed Color").on_hover_text(OBSERVED_COLOR_TOOLTIP);
+                            });
+                
+                            //normalized color
+                            ui.vertical(|ui| {
+                                let max = r.max(g.max(b));
+                                let r_byte= (r / max * 255.0 + 0.5) as u8;
+                                let g_byte= (g / max * 255.0 + 0.5) as u8;
+                                let b_byte= (b / max * 255.0 + 0.5) as u8;
+                
+                                egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
+                                    .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
+                                    .show(ui, |ui| {
+                                        ui.set_max_size(Vec2::new(200.0, 100.0));
+                                        ui.centered_and_justified(|ui| {
+                                            ui.label(format!("{r_byte:02X}{g_byte:02X}{b_byte:02X}"));
+                                        });
+                                    }).response.on_hover_text(NORMALIZED_COLOR_TOOLTIP);
+                                ui.label("Normalized Color").on_hover_text(NORMALIZED_COLOR_TOOLTIP);
+                            });
+                        });
 
-            ui.label("Number of samples in the spectra:").on_hover_text(SPECTRUM_NUMBER_OF_SAMPLES_TOOLTIP);
-            ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut nbr_of_samples_string));
+                        ui.add_space(5.0);
 
-            if nbr_of_samples_string.parse::<usize>().is_ok() {
-                let new_nbr_of_samples = nbr_of_samples_string.parse::<usize>().unwrap();
-                if new_nbr_of_samples > 1 && new_nbr_of_samples <= spectrum::NBR_OF_SAMPLES_MAX 
-                        && new_nbr_of_samples % 8 == 0 {
-                    final_nbr_of_samples = new_nbr_of_samples;
-                }
-            }
+                        //radiance
+                        ui.horizontal_top(|ui| {
+                            ui.label(format!("Radiance of the spectrum: {}W/sr/m^2",
+                                             spectrum.get_radiance()))
+                                .on_hover_text(SPECTRUM_RADIANCE_TOOLTIP);
+                        });
 
-            if ui.button("-").clicked() {
-                if *nbr_of_samples % 8 == 0 {
-                    if *nbr_of_samples == 8 {
-                        final_nbr_of_samples = 8;    //at least 8 samples have to be present
-                    } else {
-                        final_nbr_of_samples -= 8;   //subtract 8
+                        let normalize_factor = r.max(g.max(b));
+                        let required_distance = normalize_factor.sqrt();
+                        ui.label(format!("Distance to an object required to achieve normalized color: {required_distance} units."));
+                        
+                        display_gamut_indicator(ui, raw_rgb, spectrum.get_xy_chromaticity());
                     }
-                } else {
-                    final_nbr_of_samples = (*nbr_of_samples / 8 * 8).max(8)  //drop down to the nearest multiple of 8, at least 8
-                }
-            }
+                    SpectrumEffectType::Reflective => {
+                        ui.horizontal_top(|ui| {
+                            ui.label("Use custom spectrum for base spectrum?")
+                                .on_hover_text(REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP);
+                            ui.checkbox(&mut self.ui_values.select_custom_reflective_base_spectrum, "")
+                                .on_hover_text(REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP);
+
+                            if self.ui_values.select_custom_reflective_base_spectrum {
+                                ui.add_space(10.0);
+
+                                ui.label("Normalize custom spectrum?")
+                                    .on_hover_text(REFLECTIVE_SPECTRUM_NORMALIZE_BASE_TOOLTIP);
+                                ui.checkbox(&mut self.ui_values.normalize_reflective_base_spectrum, "")
+                                    .on_hover_text(REFLECTIVE_SPECTRUM_NORMALIZE_BASE_TOOLTIP);
+                            }
+                        });
+                        
+                        let reflective_base = if self.ui_values.select_custom_reflective_base_spectrum {
+                            //user wants to use own spectrum
+                            let current_reflective_base_ui_spectrum = &mut self.ui_values.selected_reflective_base_spectrum;
+                            let selected_name;
+                            {   //block to implicitly drop borrow
+                                let borrow = current_reflective_base_ui_spectrum.borrow();
+                                selected_name = borrow.to_string();
+                            }
+                            ui.horizontal_top(|ui| {
+                                ui.label("Base spectrum which will be reflected by the selected spectrum.")
+                                    .on_hover_text(REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP);
+                                Self::display_combobox_with_spectrum_list(
+                                    &mut self.ui_values.spectra,
+                                    ui,
+                                    "reflective_spectrum_base_selector".to_string(),
+                                    selected_name,
+                                    REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP,
+                                    current_reflective_base_ui_spectrum
+                                );
+                            });
 
-            if ui.button("+").clicked() {
-                if *nbr_of_samples % 8 == 0 {
-                    final_nbr_of_samples += 8;   //add 8
-                } else {
-                    final_nbr_of_samples = (*nbr_of_samples / 8 + 1) * 8;    //go up to the nearest multiple of 8
-                }
-            }
 
-            if final_nbr_of_samples != *nbr_of_samples && final_nbr_of_samples <= spectrum::NBR_OF_SAMPLES_MAX {
-                self.ui_values.spectrum_number_of_samples = final_nbr_of_samples;
-                self.update_all_spectrum_sample_sizes(final_nbr_of_samples);
-            }
-        });
+                            let borrow = current_reflective_base_ui_spectrum.borrow();
+                            if self.ui_values.normalize_reflective_base_spectrum {
+                                borrow.spectrum.normalize()
+                            } else {
+                                borrow.spectrum
+                            }
+                        } else {
+                            //use normalized white spectrum
+                            self.ui_values.normalized_white_spectrum
+                        };
+                        
+                        //white reflected
+                        let reflected_spectrum = &*spectrum * &reflective_base;
+                        let raw_rgb = reflected_spectrum.get_rgb_early();
+                        let (r, g, b) = if self.ui_values.gamut_compression {gamut::compress(raw_rgb)} else {raw_rgb};
 
-        //range
-        ui.horizontal_top(|ui| {    //TODO implement non direct change
-            let lower_bound = &mut self.ui_values.spectrum_lower_bound;
-            let upper_bound = &mut self.ui_values.spectrum_upper_bound;
-            let mut lower_bound_string = lower_bound.to_string();
-            let mut upper_bound_string = upper_bound.to_string();
+                        ui.vertical(|ui| {
+                            let r_byte = (r.clamp(0.0, 1.0) * 255.0) as u8;
+                            let g_byte = (g.clamp(0.0, 1.0) * 255.0) as u8;
+                            let b_byte = (b.clamp(0.0, 1.0) * 255.0) as u8;
+                            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                            let contrasting_text_color = if luminance < 0.5 { Color32::WHITE } else {Color32::BLACK};
 
-            ui.label("Spectrum range from:").on_hover_text(SPECTRUM_RANGE_TOOLTIP);
-            //ui.add_sized([80.0, 18.0], egui::TextEdit::singleline(&mut lower_bound_string));  //uncomment to make wavelength bounds editable
-            ui.add_enabled(false,
-                           TextEdit::singleline(&mut lower_bound_string).desired_width(80.0))
-                .on_disabled_hover_text(SPECTRUM_WAVELENGTH_EDIT_NOT_SUPPORTED_TOOLTIP);
-            ui.label("nm to:");
-            //ui.add_sized([80.0, 18.0], egui::TextEdit::singleline(&mut upper_bound_string));
-            ui.add_enabled(false,
-                           TextEdit::singleline(&mut upper_bound_string).desired_width(80.0))
-                .on_disabled_hover_text(SPECTRUM_WAVELENGTH_EDIT_NOT_SUPPORTED_TOOLTIP);
-            ui.label("nm");
+                            egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
+                                .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
+                                .show(ui, |ui| {
+                                    ui.set_max_size(Vec2::new(200.0, 100.0));
+                                    ui.centered_and_justified(|ui| {
+                                        ui.colored_label(contrasting_text_color, format!("{r_byte:02X}{g_byte:02X}{b_byte:02X}"));
+                                    });
+                                }).response.on_hover_text(REFLECTED_COLOR_TOOLTIP);
+                            ui.label("Reflected Color").on_hover_text(REFLECTED_COLOR_TOOLTIP);
+                        });
+                        display_gamut_indicator(ui, raw_rgb, reflected_spectrum.get_xy_chromaticity());
 
-            if lower_bound_string.parse::<f32>().is_ok() {
-                let new_lower_bound = lower_bound_string.parse::<f32>().unwrap();
-                if 0.0 < new_lower_bound && new_lower_bound < *upper_bound {
-                    *lower_bound = new_lower_bound;
+                        //no color squares
+                        ui.label("Color Preview not (yet) available for reflective spectra.");
+                    }
                 }
+                ui.add_space(5.0);
+                
+                //samples
+                let editable = matches!(selected.ui_spectrum_type, UISpectrumType::Custom);
+                let slider_max = match selected.spectrum_effect_type {
+                    SpectrumEffectType::Emissive => {(selected.max * 2.0).max(0.01)},
+                    SpectrumEffectType::Reflective => 1.0,
+                };
+                let unit_label = if selected.spectrum_effect_type == SpectrumEffectType::Emissive 
+                    {"W/sr/m^2/nm"} else {""};
+                egui::ScrollArea::vertical().id_salt("right scroll area").show(ui, |ui| {
+                    for (wavelength, spectral_radiance) in 
+                            spectrum.get_wavelengths().iter().zip(spectrum.get_intensities_slice().iter_mut()) {
+                        
+                        //TODO make multiple sliders adjustable
+                        ui.horizontal_top(|ui| {
+                            ui.label(format!("{wavelength:.2}nm:"));
+                            ui.style_mut().spacing.slider_width = 300.0;
+                            ui.add_enabled(
+                                editable,
+                                egui::Slider::new(spectral_radiance, 0.0..=slider_max)
+                                    .fixed_decimals(3)
+                                    .step_by(0.001)
+                            ).on_disabled_hover_text(SPECTRUM_RIGHT_SLIDER_DISABLED_TOOLTIP);
+                            ui.label(unit_label);
+                        });
+                    }
+                });
             }
-            if upper_bound_string.parse::<f32>().is_ok() {
-                let new_upper_bound = upper_bound_string.parse::<f32>().unwrap();
-                if *lower_bound < new_upper_bound {
-                    *upper_bound = upper_bound_string.parse::<f32>().unwrap();
-                }
+            None => {
+                ui.label("Select a spectrum on the left to start editing...");
             }
-        });
-    }
+        }
 
-    /// Displays the simple settings for a single spectrum. Simple settings are the spectrum type or
-    /// the brightness factor. More complicated settings such as individual sample values go to the
-    /// dedicated settings on the right in
-    /// [display_spectrum_right_side](App::display_spectrum_right_side).
-    fn display_spectrum_settings(&mut self, ui: &mut Ui, index: usize) {
-        let ui_spectrum = &mut self.ui_values.spectra[index];
-        let mut ui_spectrum = ui_spectrum.borrow_mut();
+    }
+    
+    /// Displays the settings for a single material. 
+    fn display_material_settings(&mut self, ui: &mut Ui, index: usize) {
+        let ui_material_ref = self.ui_values.materials[index].clone();
+        let mut ui_material = ui_material_ref.borrow_mut();
         
         //name and delete button
         ui.horizontal_top(|ui| {
             //name
             //moving out the bool since multiple mutable access are not allowed for Ref<_>. 
-            let mut editing_name = ui_spectrum.editing_name;
+            let mut editing_name = ui_material.editing_name;
             let backup_name = &format!("Spectrum {}", index);
-            display_name_with_edit(ui, &mut ui_spectrum.name, backup_name, &mut editing_name);
-            ui_spectrum.editing_name = editing_name;
-            
+            display_name_with_edit(ui, &mut ui_material.name, backup_name, &mut editing_name);
+            ui_material.editing_name = editing_name;
+
             ui.add_space(80.0);
 
-            let delete_button = egui::widgets::Button::new("Delete this Spectrum").fill(Color32::LIGHT_RED);
+            let delete_button = egui::widgets::Button::new("Delete this Material").fill(Color32::LIGHT_RED);
             if ui.add(delete_button).clicked() {
-                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteSpectrum(index));
+                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteMaterial(index));
             }
         });
-
-        //spectrum type
+        
+        //shadow catcher, also for mixes
         ui.horizontal_top(|ui| {
-            ui.label("Spectrum type:").on_hover_text(SPECTRUM_TYPE_TOOLTIP);
-            
-            let mut selected_type = ui_spectrum.spectrum_type;
-            ComboBox::new(format!("spectrum{}", index), "")   //the format is the ID salt, ensuring that each dropdown is distinct
-                .selected_text(selected_type.to_string())
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut selected_type, UISpectrumType::Custom, format!("{}", UISpectrumType::Custom));
-                    ui.selectable_value(&mut selected_type, UISpectrumType::Solar(1.0), format!("{}", UISpectrumType::Solar(1.0)));
-                    ui.selectable_value(&mut selected_type, UISpectrumType::PlainReflective(1.0), format!("{}", UISpectrumType::PlainReflective(1.0)));
-                    ui.selectable_value(&mut selected_type, UISpectrumType::Temperature(1000.0, 1.0), format!("{}", UISpectrumType::Temperature(1.0, 1.0)));
-                    ui.selectable_value(&mut selected_type, UISpectrumType::ReflectiveRed(1.0), format!("{}", UISpectrumType::ReflectiveRed(1.0)));
-                    ui.selectable_value(&mut selected_type, UISpectrumType::ReflectiveGreen(1.0), format!("{}", UISpectrumType::ReflectiveGreen(1.0)));
-                    ui.selectable_value(&mut selected_type, UISpectrumType::ReflectiveBlue(1.0), format!("{}", UISpectrumType::ReflectiveBlue(1.0)));
-                }).response.on_hover_text(SPECTRUM_TYPE_TOOLTIP);
+            ui.checkbox(&mut ui_material.shadow_catcher, "Shadow catcher").on_hover_text(MATERIAL_SHADOW_CATCHER_TOOLTIP);
+        });
+        
+        //mix
+        ui.horizontal_top(|ui| {
+            let candidate = self.ui_values.materials.iter()
+                .find(|material| !mixes_in(material, &ui_material_ref))
+                .cloned();
+            let mut mixed = ui_material.mix.is_some();
+            ui.add_enabled(mixed || candidate.is_some(), egui::Checkbox::new(&mut mixed, "Mix of two materials"))
+                .on_hover_text(MATERIAL_MIX_TOOLTIP)
+                .on_disabled_hover_text(MATERIAL_MIX_DISABLED_TOOLTIP);
+            if !mixed {
+                ui_material.mix = None;
+            } else if let (None, Some(candidate)) = (&ui_material.mix, candidate) {
+                ui_material.mix = Some(UIMaterialMix {
+                    first: candidate.clone(),
+                    second: candidate,
+                    weight: MixWeight::Constant(0.5),
+                });
+            }
+        });
+        
+        if let Some(mix) = &mut ui_material.mix {
+            for (material, name) in [(&mut mix.first, "First"), (&mut mix.second, "Second")] {
+                ui.horizontal_top(|ui| {
+                    let label_color = if !self.ui_values.materials.contains(material) && is_time_even() {
+                        Color32::RED
+                    } else {
+                        Color32::DARK_GRAY
+                    };
+                    ui.colored_label(label_color, format!("{name} Material:")).on_hover_text(MATERIAL_MIX_MATERIALS_TOOLTIP);
+                    display_combobox_with_mix_material_list(
+                        &self.ui_values.materials,
+                        ui,
+                        format!("material {index} mix {name}"),
+                        MATERIAL_MIX_MATERIALS_TOOLTIP,
+                        material,
+                        &ui_material_ref,
+                    );
+                });
+            }
             
-            if selected_type != ui_spectrum.spectrum_type {
-                ui_spectrum.spectrum_type = selected_type;
-                match selected_type {
-                    UISpectrumType::Custom => {}
-                    UISpectrumType::Solar(factor) => {
-                        let lower = self.ui_values.spectrum_lower_bound;
-                        let upper = self.ui_values.spectrum_upper_bound;
-                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
-                        ui_spectrum.spectrum = Spectrum::new_sunlight_spectrum(lower, upper, nbr_of_samples, factor);
-                    }
-                    UISpectrumType::PlainReflective(factor) => {
-                        let lower = self.ui_values.spectrum_lower_bound;
-                        let upper = self.ui_values.spectrum_upper_bound;
-                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
-                        ui_spectrum.spectrum = Spectrum::new_singular_reflectance_factor(lower, upper, nbr_of_samples, factor);
-                    }
-                    UISpectrumType::Temperature(temp, factor) => {
-                        let lower = self.ui_values.spectrum_lower_bound;
-                        let upper = self.ui_values.spectrum_upper_bound;
-                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
-                        ui_spectrum.spectrum = Spectrum::new_temperature_spectrum(lower, upper, temp, nbr_of_samples, factor); 
-                    }
-                    UISpectrumType::ReflectiveRed(factor) => {
-                        let lower = self.ui_values.spectrum_lower_bound;
-                        let upper = self.ui_values.spectrum_upper_bound;
-                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
-                        ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_red(lower, upper, nbr_of_samples, factor);
-                    }
-                    UISpectrumType::ReflectiveGreen(factor) => {
-                        let lower = self.ui_values.spectrum_lower_bound;
-                        let upper = self.ui_values.spectrum_upper_bound;
-                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
-                        ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_green(lower, upper, nbr_of_samples, factor);
+            //weight
+            ui.horizontal_top(|ui| {
+                ui.label("Share of the second:").on_hover_text(MATERIAL_MIX_WEIGHT_TOOLTIP);
+                let candidates = [
+                    MixWeight::Constant(0.5), 
+                    MixWeight::Fresnel(1.5), 
+                    MixWeight::Pattern(Pattern::default_checker()),
+                    MixWeight::Pattern(Pattern::default_noise()),
+                    MixWeight::Pattern(Pattern::default_gradient()),
+                ];
+                let current = mix.weight;
+                ComboBox::new(format!("material {index} mix weight"), "")
+                    .selected_text(current.to_string())
+                    .show_ui(ui, |ui| {
+                        for candidate in candidates {
+                            let same_kind = match (current, candidate) {
+                                (MixWeight::Pattern(current), MixWeight::Pattern(candidate)) => 
+                                    std::mem::discriminant(&current) == std::mem::discriminant(&candidate),
+                                _ => std::mem::discriminant(&current) == std::mem::discriminant(&candidate),
+                            };
+                            if ui.selectable_label(same_kind, candidate.to_string()).clicked() && !same_kind {
+                                mix.weight = candidate;
+                            }
+                        }
+                    }).response.on_hover_text(MATERIAL_MIX_WEIGHT_TOOLTIP);
+                
+                match &mut mix.weight {
+                    MixWeight::Constant(share) => {
+                        ui.add(egui::Slider::new(share, 0.0..=1.0));
                     }
-                    UISpectrumType::ReflectiveBlue(factor) => {
-                        let lower = self.ui_values.spectrum_lower_bound;
-                        let upper = self.ui_values.spectrum_upper_bound;
-                        let nbr_of_samples = self.ui_values.spectrum_number_of_samples;
-                        ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_blue(lower, upper, nbr_of_samples, factor);
+                    MixWeight::Fresnel(refractive_index) => {
+                        ui.label("IOR:").on_hover_text(MATERIAL_MIX_FRESNEL_TOOLTIP);
+                        ui.add(egui::Slider::new(refractive_index, 1.0..=3.0));
                     }
+                    MixWeight::Pattern(_) => {}
                 }
-                self.ui_values.after_ui_action = Some(AfterUIActions::UpdateSelectedSpectrum(index));
+            });
+            if let MixWeight::Pattern(pattern) = &mut mix.weight {
+                display_pattern_settings(ui, pattern);
             }
-        });
+            
+            //the parameters of a mix are those of its materials
+            return;
+        }
         
-        //spectrum reflectance
+        //metallicness
         ui.horizontal_top(|ui| {
-            ui.label("Behavior:").on_hover_text(SPECTRUM_EFFECT_TYPE_TOOLTIP);
-            
-            let mut selected_type = ui_spectrum.spectrum_effect_type;
-            ComboBox::new(format!("spectrum effect {}", index), "")
-                .selected_text(selected_type.to_string())
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut selected_type, SpectrumEffectType::Emissive, format!("{}", SpectrumEffectType::Emissive));
-                    ui.selectable_value(&mut selected_type, SpectrumEffectType::Reflective, format!("{}", SpectrumEffectType::Reflective));
-                }).response.on_hover_text(SPECTRUM_EFFECT_TYPE_TOOLTIP);
-            
-            if selected_type != ui_spectrum.spectrum_effect_type {
-                ui_spectrum.spectrum_effect_type = selected_type;
-                self.ui_values.after_ui_action = Some(AfterUIActions::UpdateSelectedSpectrum(index));
-            }
+            ui.label("Metallicness:").on_hover_text(MATERIAL_METALLICNESS_TOOLTIP);
+            let slider = egui::Slider::new(&mut ui_material.metallicness, 0.0..=1.0);
+            ui.add(slider);
         });
 
-        //spectrum type sub settings
-        let mut changed = false;
-        match &mut ui_spectrum.spectrum_type {
-            UISpectrumType::Solar(factor) | UISpectrumType::PlainReflective(factor) => {
-                changed = display_factor(ui, factor) || changed;
+        //roughness
+        ui.horizontal_top(|ui| {
+            ui.label("Roughness:").on_hover_text(MATERIAL_ROUGHNESS_TOOLTIP);
+            let slider = egui::Slider::new(&mut ui_material.roughness, 0.0..=1.0);
+            ui.add(slider);
+            
+            let mut anisotropic = ui_material.bitangent_roughness.is_some();
+            ui.checkbox(&mut anisotropic, "Anisotropic").on_hover_text(MATERIAL_ANISOTROPIC_TOOLTIP);
+            if !anisotropic {
+                ui_material.bitangent_roughness = None;
+            } else if ui_material.bitangent_roughness.is_none() {
+                ui_material.bitangent_roughness = Some(ui_material.roughness);
             }
-            UISpectrumType::Temperature(temp, factor) => {
-                //temperature
-                ui.horizontal_top(|ui| {
-                    let mut temp_string = temp.to_string();
-
-                    ui.label("Black body radiation temperature:");
-                    ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut temp_string));
-                    ui.label("K");  //TODO add support for different temperature units
-
-                    if temp_string.parse::<f32>().is_ok() {
-                        let new_temp = temp_string.parse::<f32>().unwrap();
-                        if new_temp != *temp && new_temp > 0.0 {
-                            *temp = new_temp;
-                            changed = true;
+        });
+        
+        //anisotropic roughness
+        if let Some(bitangent_roughness) = ui_material.bitangent_roughness.as_mut() {
+            ui.horizontal_top(|ui| {
+                ui.label("Roughness across:").on_hover_text(MATERIAL_BITANGENT_ROUGHNESS_TOOLTIP);
+                let slider = egui::Slider::new(bitangent_roughness, 0.0..=1.0);
+                ui.add(slider);
+            });
+            ui.horizontal_top(|ui| {
+                ui.label("Tangent rotation:").on_hover_text(MATERIAL_TANGENT_ROTATION_TOOLTIP);
+                let slider = egui::Slider::new(&mut ui_material.tangent_rotation, 0.0..=std::f32::consts::PI);
+                ui.add(slider);
+            });
+        }
+        
+        //reflective spectrum
+        ui.horizontal_top(|ui| {
+            let label_color = if !self.ui_values.spectra.contains(&ui_material.spectrum) && is_time_even() {
+                Color32::RED
+            } else {
+                Color32::DARK_GRAY
+            };
+            ui.colored_label(label_color, "Reflecting Spectrum:").on_hover_text(MATERIAL_SPECTRUM_REFLECTING_TOOLTIP);
+            
+            let selected_text = {
+                let borrow = ui_material.spectrum.borrow();
+                borrow.to_string()
+            };
+        
+            Self::display_combobox_with_spectrum_list(
+                &mut self.ui_values.spectra,
+                ui,
+                format!("material reflecting {index} spectrum"),
+                selected_text,
+                MATERIAL_SPECTRUM_REFLECTING_TOOLTIP,
+                &mut ui_material.spectrum,
+            )
+        });
+        
+        //pattern
+        ui.horizontal_top(|ui| {
+            ui.label("Pattern:").on_hover_text(MATERIAL_PATTERN_TOOLTIP);
+            let current = ui_material.pattern.as_ref().map(|pattern| pattern.pattern);
+            let mut selected = current;
+            ComboBox::new(format!("material {index} pattern"), "")
+                .selected_text(current.map_or("None".to_string(), |pattern| pattern.to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected, None, "None");
+                    for candidate in [Pattern::default_checker(), Pattern::default_noise(), Pattern::default_gradient()] {
+                        let same_kind = current.is_some_and(|pattern| 
+                            std::mem::discriminant(&pattern) == std::mem::discriminant(&candidate));
+                        if ui.selectable_label(same_kind, candidate.to_string()).clicked() && !same_kind {
+                            selected = Some(candidate);
                         }
                     }
+                }).response.on_hover_text(MATERIAL_PATTERN_TOOLTIP);
+            
+            if selected != current {
+                ui_material.pattern = selected.map(|pattern| {
+                    //blend towards another spectrum than the own one if possible
+                    let spectrum = ui_material.pattern.as_ref().map(|pattern| pattern.spectrum.clone())
+                        .or_else(|| self.ui_values.spectra.iter()
+                            .find(|s| s.borrow().spectrum_effect_type == SpectrumEffectType::Reflective 
+                                && !Rc::ptr_eq(s, &ui_material.spectrum))
+                            .cloned())
+                        .unwrap_or_else(|| ui_material.spectrum.clone());
+                    UIPattern { pattern, spectrum }
                 });
-
-                //factor
-                changed = display_factor(ui, factor) || changed;
             }
-            UISpectrumType::ReflectiveRed(factor) |
-            UISpectrumType::ReflectiveGreen(factor) |
-            UISpectrumType::ReflectiveBlue(factor) => {
-                //factor
-                changed = display_factor(ui, factor);
+        });
+        
+        if let Some(ui_pattern) = &mut ui_material.pattern {
+            display_pattern_settings(ui, &mut ui_pattern.pattern);
+            
+            //second spectrum
+            ui.horizontal_top(|ui| {
+                let label_color = if !self.ui_values.spectra.contains(&ui_pattern.spectrum) && is_time_even() {
+                    Color32::RED
+                } else {
+                    Color32::DARK_GRAY
+                };
+                ui.colored_label(label_color, "Pattern Spectrum:").on_hover_text(MATERIAL_PATTERN_SPECTRUM_TOOLTIP);
+                
+                let selected_text = ui_pattern.spectrum.borrow().to_string();
+                Self::display_combobox_with_spectrum_list(
+                    &mut self.ui_values.spectra,
+                    ui,
+                    format!("material pattern {index} spectrum"),
+                    selected_text,
+                    MATERIAL_PATTERN_SPECTRUM_TOOLTIP,
+                    &mut ui_pattern.spectrum,
+                )
+            });
+        }
+        
+        //fluorescence
+        ui.horizontal_top(|ui| {
+            let mut fluorescent = ui_material.fluorescence.is_some();
+            ui.checkbox(&mut fluorescent, "Fluorescent").on_hover_text(MATERIAL_FLUORESCENCE_TOOLTIP);
+            if !fluorescent {
+                ui_material.fluorescence = None;
+            } else if ui_material.fluorescence.is_none() {
+                let find_spectrum = |effect_type: SpectrumEffectType| self.ui_values.spectra.iter()
+                    .find(|s| s.borrow().spectrum_effect_type == effect_type && !Rc::ptr_eq(s, &ui_material.spectrum))
+                    .cloned()
+                    .unwrap_or_else(|| ui_material.spectrum.clone());
+                ui_material.fluorescence = Some(UIFluorescence {
+                    excitation: find_spectrum(SpectrumEffectType::Reflective),
+                    emission: find_spectrum(SpectrumEffectType::Emissive),
+                    quantum_yield: 0.8,
+                });
             }
-            UISpectrumType::Custom => {
+            
+            if let Some(fluorescence) = &mut ui_material.fluorescence {
+                ui.label("Quantum yield:").on_hover_text(MATERIAL_FLUORESCENCE_YIELD_TOOLTIP);
+                ui.add(egui::Slider::new(&mut fluorescence.quantum_yield, 0.0..=1.0));
+            }
+        });
+        
+        if let Some(fluorescence) = &mut ui_material.fluorescence {
+            for (spectrum, name, tool_tip) in [
+                (&mut fluorescence.excitation, "Absorbed Spectrum:", MATERIAL_FLUORESCENCE_EXCITATION_TOOLTIP),
+                (&mut fluorescence.emission, "Re-emitted Spectrum:", MATERIAL_FLUORESCENCE_EMISSION_TOOLTIP),
+            ] {
                 ui.horizontal_top(|ui| {
-                    ui.label("Adjustment:").on_hover_text(CUSTOM_SPECTRUM_FACTOR_ADJUST_TOOLTIP);
-
-                    ui.style_mut().spacing.slider_width = 200.0;
-                    let slider = egui::Slider::new(&mut ui_spectrum.adjust_custom_spectrum_slider, 0.01..=100.0).logarithmic(true);
-                    ui.add(slider);
+                    let label_color = if !self.ui_values.spectra.contains(spectrum) && is_time_even() {
+                        Color32::RED
+                    } else {
+                        Color32::DARK_GRAY
+                    };
+                    ui.colored_label(label_color, name).on_hover_text(tool_tip);
                     
-                    if ui.button("Apply").clicked() {
-                        let factor = ui_spectrum.adjust_custom_spectrum_slider;
-                        ui_spectrum.spectrum *= factor;
-                        changed = true; 
-                    }
+                    let selected_text = spectrum.borrow().to_string();
+                    Self::display_combobox_with_spectrum_list(
+                        &mut self.ui_values.spectra,
+                        ui,
+                        format!("material {index} fluorescence {name}"),
+                        selected_text,
+                        tool_tip,
+                        &mut *spectrum,
+                    )
                 });
             }
         }
+    }
 
+    /// Displays the spectrum comparison: a selection of spectra on the left, which are plotted 
+    /// overlaid on the right. Optionally, the plot is normalized and the CIE D65 illuminant is 
+    /// drawn as a reference.
+    fn display_spectrum_comparison(&mut self, ui: &mut Ui) {
+        ui.horizontal_top(|ui| {
+            //left
+            ui.vertical(|ui| {
+                egui::ScrollArea::vertical().id_salt("spectrum comparison selection").show(ui, |ui| {
+                    ui.label("Spectra to compare:").on_hover_text(SPECTRUM_COMPARISON_TOOLTIP);
+                    for ui_spectrum in self.ui_values.spectra.iter() {
+                        let ui_spectrum = ui_spectrum.borrow();
+                        let mut selected = self.ui_values.compared_spectra.contains(&ui_spectrum.id);
+                        if ui.checkbox(&mut selected, ui_spectrum.to_string()).changed() {
+                            if selected {
+                                self.ui_values.compared_spectra.push(ui_spectrum.id);
+                            } else {
+                                self.ui_values.compared_spectra.retain(|id| *id != ui_spectrum.id);
+                            }
+                        }
+                    }
+                    ui.checkbox(&mut self.ui_values.compare_with_d65, "CIE D65 (reference)")
+                        .on_hover_text(SPECTRUM_COMPARISON_D65_TOOLTIP);
+                    
+                    ui.separator();
+                    ui.horizontal_top(|ui| {
+                        ui.selectable_value(&mut self.ui_values.comparison_normalized, false, "Absolute")
+                            .on_hover_text(SPECTRUM_COMPARISON_NORMALIZED_TOOLTIP);
+                        ui.selectable_value(&mut self.ui_values.comparison_normalized, true, "Normalized")
+                            .on_hover_text(SPECTRUM_COMPARISON_NORMALIZED_TOOLTIP);
+                    });
+                    ui.checkbox(&mut self.ui_values.comparison_show_samples, "Show samples")
+                        .on_hover_text(SPECTRUM_COMPARISON_SAMPLES_TOOLTIP);
+                });
+            });
 
-        drop(ui_spectrum);  //I just pray that this is future-proof
-        if changed {
-            self.update_spectrum()
-        }
+            //divider
+            ui.separator();
+
+            //right side
+            let normalized = self.ui_values.comparison_normalized;
+            let y_axis_label = if normalized {"Relative intensity"} else {"W/sr/m^2/nm (reflectance for reflective spectra)"};
+            egui_plot::Plot::new("spectrum_comparison_plot")
+                .legend(egui_plot::Legend::default())
+                .x_axis_label("Wavelength [nm]")
+                .y_axis_label(y_axis_label)
+                .show(ui, |plot_ui| {
+                    let mut colors = COMPARISON_PLOT_COLORS.iter().cycle();
+                    for ui_spectrum in self.ui_values.spectra.iter() {
+                        let ui_spectrum = ui_spectrum.borrow();
+                        if !self.ui_values.compared_spectra.contains(&ui_spectrum.id) {
+                            continue;
+                        }
+                        
+                        let max = ui_spectrum.spectrum.get_max();
+                        let scale = if normalized && max > 0.0 {1.0 / max} else {1.0};
+                        let points: Vec<[f64; 2]> = ui_spectrum.spectrum.iter()
+                            .map(|(wavelength, value)| [wavelength as f64, (value * scale) as f64])
+                            .collect();
+                        let color = *colors.next().unwrap();
+                        
+                        if self.ui_values.comparison_show_samples {
+                            plot_ui.points(egui_plot::Points::new(points.clone())
+                                .name(ui_spectrum.to_string()).color(color).radius(2.5));
+                        }
+                        plot_ui.line(egui_plot::Line::new(points).name(ui_spectrum.to_string()).color(color));
+                    }
+                    
+                    if self.ui_values.compare_with_d65 {
+                        //in absolute mode D65 is scaled to 1 at 560nm, as it has no absolute unit
+                        let scale = if normalized {1.0 / D65_RELATIVE_POWER_MAX} else {0.01};
+                        let points: Vec<[f64; 2]> = (0..=(spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND - spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND) as u32)
+                            .map(|i| {
+                                let wavelength = spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND + i as f32;
+                                [wavelength as f64, (spectral_data::get_d65_relative_power(wavelength) * scale) as f64]
+                            })
+                            .collect();
+                        plot_ui.line(egui_plot::Line::new(points).name("CIE D65").color(Color32::GRAY)
+                            .style(egui_plot::LineStyle::dashed_loose()));
+                    }
+                });
+        });
     }
 
-    /// Displays the right side of spectrum settings. Here the user can preview the color of
-    /// the spectrum and each samples individual value.
-    fn display_spectrum_right_side(&mut self, ui: &mut Ui) {
-        match self.ui_values.selected_spectrum.as_mut() {
-            Some(selected) => {
-                let spectrum = &mut selected.spectrum;
-                let (r, g, b) = spectrum.get_rgb_early();
-                
-                ui.horizontal_top(|ui| {
-                    ui.colored_label(Color32::RED, "Any changes will not be applied unless saved. Selecting another spectrum will discard changes!");
-                    if ui.button("Save").clicked() {
-                        self.ui_values.after_ui_action = Some(AfterUIActions::SaveSelectedSpectrum(selected.selected_spectrum));
-                    }
+    /// Displays the content of the given tab. 
+    fn display_tab_content(&mut self, ui: &mut Ui, tab: UiTab) {
+        let ctx = &ui.ctx().clone();
+        
+        match tab {
+            UiTab::Settings => {
+                self.display_width_text_edit_field(ui);
+                self.display_height_text_edit_field(ui);
+                self.display_render_scale_selection(ui);
+                self.display_nbr_of_threads_edit_field(ui);
+                self.display_nbr_of_iterations_edit_field(ui);
+                self.display_max_bounces_edit_field(ui);
+                self.display_bounce_kind_limits_edit_fields(ui);
+                self.display_sampler_selection(ui);
+                self.display_light_selection_settings(ui);
+                self.display_caustics_settings(ui);
+                self.display_irradiance_cache_settings(ui);
+                self.display_medium_settings(ui);
+                self.display_atmosphere_settings(ui);
+                self.display_background_settings(ui);
+                ui.vertical_centered(|ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.label("Transparent film:").on_hover_text(TRANSPARENT_FILM_TOOLTIP);
+                        ui.checkbox(&mut self.ui_values.transparent_film, "");
+                    });
                 });
-                
-                match selected.spectrum_effect_type {
-                    SpectrumEffectType::Emissive => {
-                        //color squares
+                self.display_firefly_filter_settings(ui);
+                self.display_color_response_settings(ui);
+                self.display_light_scale_settings(ui);
+                ui.vertical_centered(|ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.label("Record spectra:").on_hover_text(RECORD_SPECTRA_TOOLTIP);
+                        ui.checkbox(&mut self.ui_values.record_spectra, "");
+                    });
+                });
+                ui.vertical_centered(|ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.label("Light passes:").on_hover_text(LIGHT_PASSES_TOOLTIP);
+                        ui.checkbox(&mut self.ui_values.record_light_passes, "");
+                    });
+                });
+                ui.vertical_centered(|ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.label("Write checkpoints:").on_hover_text(WRITE_CHECKPOINTS_TOOLTIP);
+                        ui.checkbox(&mut self.ui_values.write_checkpoints, "");
+                    });
+                });
+                ui.vertical_centered(|ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.label("Render Workers:").on_hover_text(RENDER_WORKERS_TOOLTIP);
+                        ui.add(TextEdit::singleline(&mut self.ui_values.render_workers).hint_text("host:port, ..."))
+                            .on_hover_text(RENDER_WORKERS_TOOLTIP);
+                    });
+                });
+                ui.vertical_centered(|ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.label("Worker Token:").on_hover_text(RENDER_WORKER_TOKEN_TOOLTIP);
+                        ui.add(TextEdit::singleline(&mut self.ui_values.render_worker_token).password(true))
+                            .on_hover_text(RENDER_WORKER_TOKEN_TOOLTIP);
+                    });
+                });
+                self.display_remote_control_settings(ui);
+                self.display_path_dump_settings(ui);
+                self.display_advanced_settings(ui);
+            }
+            UiTab::Objects => {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.display_scene_check(ui);
+                    
+                    //camera settings
+                    ui.horizontal_top(|ui| {
+                        ui.label("Active Camera:").on_hover_text(CAMERA_ACTIVE_TOOLTIP);
+                        let selected_text = self.ui_values.ui_cameras[self.ui_values.active_camera].to_string();
+                        ComboBox::new("active_camera", "")
+                            .selected_text(selected_text)
+                            .show_ui(ui, |ui| {
+                                for (index, camera) in self.ui_values.ui_cameras.iter().enumerate() {
+                                    ui.selectable_value(&mut self.ui_values.active_camera, index, camera.to_string());
+                                }
+                            }).response.on_hover_text(CAMERA_ACTIVE_TOOLTIP);
+                    });
+                    egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
+                        self.display_camera_settings(ui);
+                    });
+                    ui.add_space(10.0);
+                    
+                    //Groups management
+                    ui.vertical_centered(|ui| {
                         ui.horizontal_top(|ui| {
-                            //observed color
-                            ui.vertical(|ui| {
-                                let r_byte = (r.clamp(0.0, 1.0) * 255.0) as u8;
-                                let g_byte = (g.clamp(0.0, 1.0) * 255.0) as u8;
-                                let b_byte = (b.clamp(0.0, 1.0) * 255.0) as u8;
-                                let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
-                                let contrasting_text_color = if luminance < 0.5 { Color32::WHITE } else {Color32::BLACK};
-                
-                                egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
-                                    .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
-                                    .show(ui, |ui| {
-                                        ui.set_max_size(Vec2::new(200.0, 100.0));
-                                        ui.centered_and_justified(|ui| {
-                                            ui.colored_label(contrasting_text_color, format!("{r_byte:02X}{g_byte:02X}{b_byte:02X}"));
-                                        });
-                                    }).response.on_hover_text(OBSERVED_COLOR_TOOLTIP);
-                                ui.label("Observed Color").on_hover_text(OBSERVED_COLOR_TOOLTIP);
-                            });
-                
-                            //normalized color
-                            ui.vertical(|ui| {
-                                let max = r.max(g.max(b));
-                                let r_byte= (r / max * 255.0 + 0.5) as u8;
-                                let g_byte= (g / max * 255.0 + 0.5) as u8;
-                                let b_byte= (b / max * 255.0 + 0.5) as u8;
-                
-                                egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
-                                    .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
-                                    .show(ui, |ui| {
-                                        ui.set_max_size(Vec2::new(200.0, 100.0));
-                                        ui.centered_and_justified(|ui| {
-                                            ui.label(format!("{r_byte:02X}{g_byte:02X}{b_byte:02X}"));
-                                        });
-                                    }).response.on_hover_text(NORMALIZED_COLOR_TOOLTIP);
-                                ui.label("Normalized Color").on_hover_text(NORMALIZED_COLOR_TOOLTIP);
-                            });
+                            ui.label("Groups:").on_hover_text(GROUP_TOOLTIP);
+                            ui.add_space(100.0);
+                            if ui.button("Add New Group").clicked() {
+                                let group = UIGroup::new("New Group".to_string());
+                                self.ui_values.ui_groups.push(Rc::new(RefCell::new(group)));
+                            }
                         });
-
-                        ui.add_space(5.0);
-
-                        //radiance
+                    });
+                    for index in 0..self.ui_values.ui_groups.len() {
+                        egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
+                            self.display_group_settings(ui, index);
+                        });
+                    }
+                    ui.add_space(10.0);
+                    
+                    //Light sources management
+                    ui.vertical_centered(|ui| {
                         ui.horizontal_top(|ui| {
-                            ui.label(format!("Radiance of the spectrum: {}W/sr/m^2",
-                                             spectrum.get_radiance()))
-                                .on_hover_text(SPECTRUM_RADIANCE_TOOLTIP);
+                            ui.label("Light Sources:");
+                            ui.add_space(100.0);
+                            if ui.button("Add New Light Source").clicked() {
+                                let spectrum = match self.ui_values.spectra.first() {
+                                    Some(spectrum) => spectrum.clone(),
+                                    None => {Rc::new(RefCell::new(UISpectrum::default()))}
+                                };
+                                let light = UILight::new(0.0, 0.0, 0.0, spectrum, "New Light Source".to_string());
+                                self.ui_values.ui_lights.push(light);
+                            }
+                        });
+                    });
+                    for index in 0..self.ui_values.ui_lights.len() {
+                        let hidden = self.ui_values.ui_lights[index].hidden;
+                        let color = if hidden {Color32::GRAY} else {Color32::LIGHT_GRAY};
+
+                        ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
+                            egui::Frame::NONE.fill(color).inner_margin(5.0).show(ui, |ui| {
+                                self.display_light_source_settings(ui, index);
+                            })
+                        }).response.context_menu(|ui| {
+                            if ui.button("Copy").clicked() {
+                                self.ui_values.after_ui_action = Some(AfterUIActions::CopyLight(index))
+                            }
+                            
+                            //adding actual size since button would wrap otherwise
+                            let hide_button_text = if hidden { "Show" } else { "Hide" };
+                            let button = egui::Button::new(hide_button_text).min_size([40.0, 0.0].into());
+                            if ui.add(button).clicked() {
+                            //if ui.button(hide_button_text).clicked() {
+                                self.ui_values.ui_lights[index].hidden = !hidden;
+                            }
                         });
-
-                        let normalize_factor = r.max(g.max(b));
-                        let required_distance = normalize_factor.sqrt();
-                        ui.label(format!("Distance to an object required to achieve normalized color: {required_distance} units."));
                     }
-                    SpectrumEffectType::Reflective => {
+                    ui.add_space(10.0);
+                    
+                    //Objects management
+                    ui.vertical_centered(|ui| {
                         ui.horizontal_top(|ui| {
-                            ui.label("Use custom spectrum for base spectrum?")
-                                .on_hover_text(REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP);
-                            ui.checkbox(&mut self.ui_values.select_custom_reflective_base_spectrum, "")
-                                .on_hover_text(REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP);
-
-                            if self.ui_values.select_custom_reflective_base_spectrum {
-                                ui.add_space(10.0);
-
-                                ui.label("Normalize custom spectrum?")
-                                    .on_hover_text(REFLECTIVE_SPECTRUM_NORMALIZE_BASE_TOOLTIP);
-                                ui.checkbox(&mut self.ui_values.normalize_reflective_base_spectrum, "")
-                                    .on_hover_text(REFLECTIVE_SPECTRUM_NORMALIZE_BASE_TOOLTIP);
+                            ui.label("Objects:");
+                            ui.add_space(100.0);
+                            if ui.button("Add New Object").clicked() {
+                                let object = UIObject::default(self);
+                                self.ui_values.ui_objects.push(object);
                             }
                         });
+                    });
+                    for index in 0..self.ui_values.ui_objects.len() {
+                        let hidden = self.ui_values.ui_objects[index].hidden;
+                        let color = if hidden {Color32::GRAY} else {Color32::LIGHT_GRAY};
                         
-                        let reflective_base = if self.ui_values.select_custom_reflective_base_spectrum {
-                            //user wants to use own spectrum
-                            let current_reflective_base_ui_spectrum = &mut self.ui_values.selected_reflective_base_spectrum;
-                            let selected_name;
-                            {   //block to implicitly drop borrow
-                                let borrow = current_reflective_base_ui_spectrum.borrow();
-                                selected_name = borrow.to_string();
-                            }
-                            ui.horizontal_top(|ui| {
-                                ui.label("Base spectrum which will be reflected by the selected spectrum.")
-                                    .on_hover_text(REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP);
-                                Self::display_combobox_with_spectrum_list(
-                                    &mut self.ui_values.spectra,
-                                    ui,
-                                    "reflective_spectrum_base_selector".to_string(),
-                                    selected_name,
-                                    REFLECTIVE_SPECTRUM_BASE_SELECTION_TOOLTIP,
-                                    current_reflective_base_ui_spectrum
-                                );
+                        ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
+                            egui::Frame::NONE.fill(color).inner_margin(5.0).show(ui, |ui| {
+                                self.display_objects_settings(ui, index);   //TODO ui setting for reflectivity
                             });
-
-
-                            let borrow = current_reflective_base_ui_spectrum.borrow();
-                            if self.ui_values.normalize_reflective_base_spectrum {
-                                borrow.spectrum.normalize()
-                            } else {
-                                borrow.spectrum
+                        }).response.context_menu(|ui| {
+                            if ui.button("Copy").clicked() {
+                                self.ui_values.after_ui_action = Some(AfterUIActions::CopyObject(index));
                             }
-                        } else {
-                            //use normalized white spectrum
-                            self.ui_values.normalized_white_spectrum
-                        };
-                        
-                        //white reflected
-                        let reflected_spectrum = &*spectrum * &reflective_base;
-                        let (r, g, b) = reflected_spectrum.get_rgb_early();
+                            
+                            //adding actual size since button would wrap otherwise
+                            let hide_button_text = if hidden { "Show" } else { "Hide" };
+                            let button = egui::Button::new(hide_button_text).min_size([40.0, 0.0].into());
+                            if ui.add(button).clicked() {
+                                self.ui_values.ui_objects[index].hidden = !hidden;
+                            }
+                        });
+                    }
+                });
+            }
+            UiTab::SpectrumComparison => {
+                self.display_spectrum_comparison(ui);
+            }
+            UiTab::SpectraAndMaterials => {
+                ui.horizontal_top(|ui| {
+                    //left
+                    ui.vertical(|ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
 
-                        ui.vertical(|ui| {
-                            let r_byte = (r.clamp(0.0, 1.0) * 255.0) as u8;
-                            let g_byte = (g.clamp(0.0, 1.0) * 255.0) as u8;
-                            let b_byte = (b.clamp(0.0, 1.0) * 255.0) as u8;
-                            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
-                            let contrasting_text_color = if luminance < 0.5 { Color32::WHITE } else {Color32::BLACK};
+                            ui.label("General Spectrum Settings:");
+                            egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
+                                self.display_general_spectrum_settings(ui);
+                            });
+                            ui.add_space(10.0);
 
-                            egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
-                                .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
-                                .show(ui, |ui| {
-                                    ui.set_max_size(Vec2::new(200.0, 100.0));
-                                    ui.centered_and_justified(|ui| {
-                                        ui.colored_label(contrasting_text_color, format!("{r_byte:02X}{g_byte:02X}{b_byte:02X}"));
+                            //name and add button: spectra
+                            ui.horizontal_top(|ui| {
+                                ui.label("Spectra:");
+                                ui.add_space(100.0);
+                                if ui.button("Add new Spectrum").clicked() {
+                                    let spectrum = UISpectrum::new(
+                                        "New Spectrum".to_string(),
+                                        UISpectrumType::Solar(0.001),
+                                        SpectrumEffectType::Emissive,
+                                        Spectrum::new_sunlight_spectrum(
+                                            self.ui_values.spectrum_lower_bound,
+                                            self.ui_values.spectrum_upper_bound,
+                                            self.ui_values.spectrum_number_of_samples,
+                                            0.001,
+                                        )
+                                    );
+                                    self.ui_values.spectra.push(
+                                        Rc::new(RefCell::new(spectrum))
+                                    );
+                                }
+                            });
+
+                            //individual spectra
+                            for index in 0..self.ui_values.spectra.len() {
+                                //determine color
+                                let mut color = Color32::LIGHT_GRAY;
+                                if let Some(selected_index) = &mut self.ui_values.selected_spectrum {
+                                    let selected_index = selected_index.selected_spectrum;
+                                    if selected_index == index {
+                                        color = Color32::LIGHT_BLUE;
+                                    }
+                                }
+
+                                //add actual spectrum UI elements
+                                let response =  ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
+                                    egui::Frame::NONE.fill(color).inner_margin(5.0).show(ui, |ui| {
+                                        self.display_spectrum_settings(ui, index);
                                     });
-                                }).response.on_hover_text(REFLECTED_COLOR_TOOLTIP);
-                            ui.label("Reflected Color").on_hover_text(REFLECTED_COLOR_TOOLTIP);
+                                }).response;
+                                if response.clicked()  {
+                                    self.update_selected_spectrum(index);
+                                };
+                                response.context_menu(|ui| {
+                                    if ui.button("Copy").clicked() {
+                                        self.ui_values.after_ui_action = Some(AfterUIActions::CopySpectrum(index));
+                                    }
+                                });
+                            }
+                            ui.add_space(10.0);
+                            
+                            //name and add button: materials
+                            ui.horizontal_top(|ui| {
+                                ui.label("Materials:");
+                                ui.add_space(100.0);
+                                if ui.button("Add new Material").clicked() {
+                                    let material = UIMaterial::default(self);
+                                    self.ui_values.materials.push(
+                                        Rc::new(RefCell::new(material))
+                                    );
+                                }
+                            });
+                            
+                            //individual materials
+                            for index in 0..self.ui_values.materials.len() {
+                                //add actual spectrum UI elements
+                                ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
+                                    egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
+                                        self.display_material_settings(ui, index);
+                                    });
+                                }).response.context_menu(|ui| {
+                                    if ui.button("Copy").clicked() {
+                                        self.ui_values.after_ui_action = Some(AfterUIActions::CopyMaterial(index));
+                                    }
+                                });
+                            }
                         });
+                    });
 
-                        //no color squares
-                        ui.label("Color Preview not (yet) available for reflective spectra.");
+                    //divider
+                    ui.separator();
+
+                    //right side
+                    ui.vertical(|ui| {
+                        self.display_spectrum_right_side(ui);
+                    });
+                });
+            }
+            UiTab::Display => {
+                //user information about rendering time
+                ui.horizontal_top(|ui| {
+                    self.display_start_render_button(ui);
+                    self.display_live_preview_checkbox(ui);
+                    self.display_camera_navigation_settings(ui);
+                    self.display_click_to_focus_checkbox(ui);
+                    self.display_pause_button(ui);
+                    self.display_abort_button(ui);
+                    self.display_denoise_button(ui);
+                    #[cfg(feature = "oidn")]
+                    self.display_oidn_buttons(ui);
+                    self.refresh_rendering_time();
+                    self.display_frame_generation_time(ui);
+                    egui::Frame::NONE.inner_margin(5.0).show(ui, |ui| {
+                        let progress = self.ui_values.render_progress;
+                        let frame_number = (progress.frames_done + 1).min(progress.nbr_of_frames);
+                        ui.add(egui::ProgressBar::new(progress.overall())
+                            .text(format!("Frame {frame_number}/{}: {:.0}%", progress.nbr_of_frames, progress.frame() * 100.0)))
+                            .on_hover_text(format!("Frame {} of {}, {:.0}% done\nTile {} of {}", 
+                                (progress.frames_done + 1).min(progress.nbr_of_frames), progress.nbr_of_frames, 
+                                progress.frame() * 100.0, progress.tiles_done, progress.nbr_of_tiles));
+                    });
+                    ui.label(format!("Rays traced: {}", self.ui_values.render_progress.rays_traced))
+                        .on_hover_text(DISPLAY_RAYS_TRACED_TOOLTIP);
+                    ui.checkbox(&mut self.ui_values.show_render_buckets, "Show buckets")
+                        .on_hover_text(DISPLAY_SHOW_BUCKETS_TOOLTIP);
+                    let tone_mapper = self.ui_values.tone_mapper;
+                    ComboBox::new("tone_mapper", "")
+                        .selected_text(tone_mapper.to_string())
+                        .show_ui(ui, |ui| {
+                            for mapper in [ToneMapper::Clip, ToneMapper::Reinhard, ToneMapper::Aces, ToneMapper::Filmic] {
+                                ui.selectable_value(&mut self.ui_values.tone_mapper, mapper, mapper.to_string());
+                            }
+                        }).response.on_hover_text(DISPLAY_TONE_MAPPER_TOOLTIP);
+                    let exposure_changed = ui.add(egui::Slider::new(&mut self.ui_values.exposure, -EXPOSURE_MAX..=EXPOSURE_MAX)
+                        .step_by(0.1).suffix(" EV")).on_hover_text(DISPLAY_EXPOSURE_TOOLTIP).changed();
+                    let bloom_changed = self.display_bloom_settings(ui);
+                    if self.ui_values.tone_mapper != tone_mapper || exposure_changed || bloom_changed {
+                        self.encode_last_frame(ctx);
                     }
+                    let displayed_buffer = self.ui_values.displayed_buffer;
+                    ComboBox::new("displayed_buffer", "")
+                        .selected_text(self.displayed_buffer_name(displayed_buffer))
+                        .show_ui(ui, |ui| {
+                            let light_passes = (0..self.image_light_passes.len()).map(DisplayedBuffer::LightPass);
+                            for buffer in [DisplayedBuffer::Image, DisplayedBuffer::NoiseHeatmap, DisplayedBuffer::Normals, 
+                                           DisplayedBuffer::Depth, DisplayedBuffer::Albedo, DisplayedBuffer::ObjectIds]
+                                .into_iter().chain(light_passes) {
+                                let name = self.displayed_buffer_name(buffer);
+                                ui.selectable_value(&mut self.ui_values.displayed_buffer, buffer, name);
+                            }
+                        }).response.on_hover_text(DISPLAY_BUFFER_TOOLTIP);
+                    let mut changed = self.ui_values.displayed_buffer != displayed_buffer;
+                    if self.ui_values.displayed_buffer == DisplayedBuffer::Depth {
+                        ui.label("Near:").on_hover_text(DISPLAY_DEPTH_RANGE_TOOLTIP);
+                        let far = self.ui_values.depth_far;
+                        changed |= ui.add(egui::DragValue::new(&mut self.ui_values.depth_near).speed(0.1).range(0.0..=far))
+                            .on_hover_text(DISPLAY_DEPTH_RANGE_TOOLTIP).changed();
+                        ui.label("Far:").on_hover_text(DISPLAY_DEPTH_RANGE_TOOLTIP);
+                        let near = self.ui_values.depth_near;
+                        changed |= ui.add(egui::DragValue::new(&mut self.ui_values.depth_far).speed(0.1).range(near..=f32::MAX))
+                            .on_hover_text(DISPLAY_DEPTH_RANGE_TOOLTIP).changed();
+                    }
+                    if changed {
+                        self.renew_texture_handle(ctx);
+                    }
+                });
+                self.display_noise_error(ui);
+                self.display_render_statistics(ui);
+                if self.display_post_effects(ui) {
+                    self.encode_last_frame(ctx);
+                }
+
+                if let Some(factor) = self.image_downsample_factor {
+                    ui.colored_label(Color32::DARK_RED, format!("The image exceeds the maximum texture size and \
+                        is displayed downsampled by a factor of {factor:.2}.")).on_hover_text(DISPLAY_DOWNSAMPLED_TOOLTIP);
                 }
-                ui.add_space(5.0);
                 
-                //samples
-                let editable = matches!(selected.ui_spectrum_type, UISpectrumType::Custom);
-                let slider_max = match selected.spectrum_effect_type {
-                    SpectrumEffectType::Emissive => {(selected.max * 2.0).max(0.01)},
-                    SpectrumEffectType::Reflective => 1.0,
-                };
-                let unit_label = if selected.spectrum_effect_type == SpectrumEffectType::Emissive 
-                    {"W/sr/m^2/nm"} else {""};
-                egui::ScrollArea::vertical().id_salt("right scroll area").show(ui, |ui| {
-                    for (wavelength, spectral_radiance) in 
-                            spectrum.get_wavelengths().iter().zip(spectrum.get_intensities_slice().iter_mut()) {
+                //full resolution inspection of a part of a downsampled image
+                if let Some(crop) = self.image_crop_texture.clone() {
+                    let mut open = true;
+                    egui::Window::new("Full Resolution Crop").open(&mut open).show(ctx, |ui| {
+                        ui.add(egui::Image::from_texture(&crop).fit_to_original_size(1.0));
+                    });
+                    if !open {
+                        self.image_crop_center = None;
+                        self.image_crop_texture = None;
+                    }
+                }
+
+                //image display frame
+                egui::Frame::NONE.fill(Color32::GRAY).show(ui, |ui| {
+                    if let Some(img) = self.image_eframe_texture.clone() {
+                        let window_dimensions = ctx.input(|i| i.viewport().outer_rect).unwrap();
+                        let x_ratio = window_dimensions.width() / self.ui_values.width as f32;
+                        let y_ratio = window_dimensions.height() / self.ui_values.height as f32;
+                        let lower_zoom_end = x_ratio.min(y_ratio).min(1.0);
+                        let upper_zoom_end = 10.0;
+
+                        let mut clicked_pixel = None;
+                        let mut hovered_pixel = None;
+                        let mut look_delta = Vec2::ZERO;
+                        let navigating = self.ui_values.camera_navigation && self.ui_values.live_preview;
+                        let downsample_factor = self.image_downsample_factor;
+                        egui::Scene::new()
+                                .zoom_range(lower_zoom_end..=upper_zoom_end)
+                                .show(ui, &mut self.ui_values.image_scene_rect, |ui| {
+                            //while navigating, dragging the image turns the camera instead of moving the view
+                            let sense = if navigating { Sense::click_and_drag() } else { Sense::click() };
+                            let image_response = ui.add(
+                                egui::Image::from_texture(&img).fit_to_original_size(1.0).sense(sense)
+                            ).on_hover_text(DISPLAY_IMAGE_TOOLTIP);
+                            let image_rect = image_response.rect;
+                            if navigating && image_response.dragged() {
+                                look_delta = image_response.drag_delta() / image_rect.height();
+                            }
+                            let to_pixel = |pointer: egui::Pos2| {
+                                let pixel = (pointer - image_rect.min) / image_rect.width() * img.size()[0] as f32 
+                                    * downsample_factor.unwrap_or(1.0);
+                                (pixel.x.max(0.0) as u32, pixel.y.max(0.0) as u32)
+                            };
+                            hovered_pixel = image_response.hover_pos().map(to_pixel);
+                            if image_response.clicked() {
+                                clicked_pixel = image_response.interact_pointer_pos().map(to_pixel);
+                            }
+                            
+                            //outlines of the parts of the image which are currently worked on
+                            if self.ui_values.show_render_buckets {
+                                //the buckets are in the coordinates of the render size, not of the output size
+                                let render_width = self.accumulation.lock().unwrap().as_ref()
+                                    .map_or(img.size()[0] as f32 * self.image_downsample_factor.unwrap_or(1.0), 
+                                            |accumulation| accumulation.get_width() as f32);
+                                let scale = image_rect.width() / render_width;
+                                let stroke = egui::Stroke::new(1.0, Color32::ORANGE);
+                                for bucket in self.active_buckets.lock().unwrap().iter() {
+                                    let min = image_rect.min + Vec2::new(bucket.x as f32, bucket.y as f32) * scale;
+                                    let size = Vec2::new(bucket.width as f32, bucket.height as f32) * scale;
+                                    ui.painter().rect_stroke(egui::Rect::from_min_size(min, size), 0.0, 
+                                                             stroke, egui::StrokeKind::Outside);
+                                }
+                            }
+                        }).response.context_menu(|ui| {
+                            if ui.button("Return to the image").clicked() {
+                                self.ui_values.image_scene_rect = egui::Rect::ZERO;
+                            }
+                        });
                         
-                        //TODO make multiple sliders adjustable
-                        ui.horizontal_top(|ui| {
-                            ui.label(format!("{wavelength:.2}nm:"));
-                            ui.style_mut().spacing.slider_width = 300.0;
-                            ui.add_enabled(
-                                editable,
-                                egui::Slider::new(spectral_radiance, 0.0..=slider_max)
-                                    .fixed_decimals(3)
-                                    .step_by(0.001)
-                            ).on_disabled_hover_text(SPECTRUM_RIGHT_SLIDER_DISABLED_TOOLTIP);
-                            ui.label(unit_label);
+                        if let Some(pixel) = clicked_pixel.filter(|_| self.click_to_focus_enabled()) {
+                            self.focus_at_pixel(pixel);
+                        } else if clicked_pixel.is_some() {
+                            self.probed_pixel = clicked_pixel;
+                            //a downsampled proxy can be clicked to inspect a part in full resolution
+                            if downsample_factor.is_some() {
+                                self.image_crop_center = clicked_pixel;
+                                self.renew_crop_texture_handle(ctx);
+                            }
+                        }
+                        self.display_pixel_probe(ctx, hovered_pixel);
+                        if navigating {
+                            self.navigate_camera(ctx, look_delta, hovered_pixel.is_some());
+                        }
+                    } else {
+                        ui.centered_and_justified(|ui| {
+                            self.display_start_render_button(ui);
                         });
                     }
                 });
             }
-            None => {
-                ui.label("Select a spectrum on the left to start editing...");
+            UiTab::Gallery => {
+                self.display_gallery(ui);
+            }
+            UiTab::Script => {
+                #[cfg(feature = "scripting")]
+                self.display_script_console(ui);
             }
         }
-
     }
     
-    /// Displays the settings for a single material. 
-    fn display_material_settings(&mut self, ui: &mut Ui, index: usize) {
-        let ui_material = &mut self.ui_values.materials[index];
-        let mut ui_material = ui_material.borrow_mut();
+    /// Displays the pixel probe for the hovered pixel or, while the cursor is not over the image, 
+    /// the last clicked one: its average linear RGB values and number of samples and, if spectra 
+    /// are recorded, its average spectrum as a plot together with its XYZ values. The values are 
+    /// those of the raw accumulation, unaffected by the firefly filter and the display settings. 
+    /// Does nothing until a pixel has been clicked. 
+    fn display_pixel_probe(&mut self, ctx: &egui::Context, hovered_pixel: Option<(u32, u32)>) {
+        let Some((x, y)) = hovered_pixel.or(self.probed_pixel).filter(|_| self.probed_pixel.is_some()) else {
+            return;
+        };
+        //a render of the gallery has no accumulation, the one of the last render does not belong to it
+        let (rgb, spectrum, records_spectra) = match (&*self.accumulation.lock().unwrap(), &self.image_float) {
+            (Some(accumulation), Some(image_float)) => {
+                //the accumulation has the render size, which differs from the output size by the render scale
+                let scale = accumulation.get_width() as f32 / image_float.get_width() as f32;
+                let (x, y) = ((x as f32 * scale) as u32, (y as f32 * scale) as u32);
+                (accumulation.pixel_rgb(x, y), accumulation.pixel_spectrum(x, y), accumulation.records_spectra())
+            },
+            _ => (None, None, false),
+        };
+        let observer = self.observer();
         
-        //name and delete button
-        ui.horizontal_top(|ui| {
-            //name
-            //moving out the bool since multiple mutable access are not allowed for Ref<_>. 
-            let mut editing_name = ui_material.editing_name;
-            let backup_name = &format!("Spectrum {}", index);
-            display_name_with_edit(ui, &mut ui_material.name, backup_name, &mut editing_name);
-            ui_material.editing_name = editing_name;
-
-            ui.add_space(80.0);
-
-            let delete_button = egui::widgets::Button::new("Delete this Material").fill(Color32::LIGHT_RED);
-            if ui.add(delete_button).clicked() {
-                self.ui_values.after_ui_action = Some(AfterUIActions::DeleteMaterial(index));
-            }
+        let mut open = true;
+        egui::Window::new("Pixel Probe").open(&mut open).default_width(320.0).show(ctx, |ui| {
+            ui.label(format!("Pixel: {x}, {y}")).on_hover_text(PIXEL_PROBE_TOOLTIP);
+            let Some(((r, g, b), samples)) = rgb else {
+                ui.label("No samples of this pixel.");
+                return;
+            };
+            ui.label(format!("Samples: {samples}"));
+            ui.label(format!("Linear RGB: {r:.4}, {g:.4}, {b:.4}"));
+            let Some(spectrum) = spectrum else {
+                if !records_spectra {
+                    ui.label("Enable \"Record spectra\" in the settings to see the spectrum of the pixels.");
+                }
+                return;
+            };
+            let xyz = spectrum.get_xyz_by_observer(observer);
+            ui.label(format!("XYZ: {:.4}, {:.4}, {:.4}", xyz.x, xyz.y, xyz.z));
+            let points: Vec<[f64; 2]> = spectrum.iter()
+                .map(|(wavelength, value)| [wavelength as f64, value as f64])
+                .collect();
+            egui_plot::Plot::new("pixel_probe_plot")
+                .height(PIXEL_PROBE_PLOT_HEIGHT)
+                .x_axis_label("Wavelength [nm]")
+                .y_axis_label("W/sr/m^2/nm")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(egui_plot::Line::new(points));
+                });
         });
-        
-        //metallicness
+        if !open {
+            self.probed_pixel = None;
+        }
+    }
+    
+    /// Displays the editor of the scripting tab, the button running the script against the scene 
+    /// and the text printed by its last run or the error it failed with, see [scripting::run]. 
+    #[cfg(feature = "scripting")]
+    fn display_script_console(&mut self, ui: &mut Ui) {
         ui.horizontal_top(|ui| {
-            ui.label("Metallicness:").on_hover_text(MATERIAL_METALLICNESS_TOOLTIP);
-            let slider = egui::Slider::new(&mut ui_material.metallicness, 0.0..=1.0);
-            ui.add(slider);
+            if ui.button("Run").on_hover_text(SCRIPT_RUN_TOOLTIP).clicked() {
+                let console = &mut self.script_console;
+                console.output = match scripting::run(&console.script, &mut self.ui_values, console.replace_scene) {
+                    Ok(output) => output,
+                    Err(e) => e.error,
+                };
+            }
+            ui.checkbox(&mut self.script_console.replace_scene, "Replace objects and lights")
+                .on_hover_text(SCRIPT_REPLACE_SCENE_TOOLTIP);
         });
-
-        //roughness
-        ui.horizontal_top(|ui| {
-            ui.label("Roughness:").on_hover_text(MATERIAL_ROUGHNESS_TOOLTIP);
-            let slider = egui::Slider::new(&mut ui_material.roughness, 0.0..=1.0);
-            ui.add(slider);
+        //the output keeps a fifth of the height, the editor takes the rest
+        let output_height = ui.available_height() / 5.0;
+        egui::ScrollArea::vertical().id_salt("script_editor").max_height(ui.available_height() - output_height)
+            .show(ui, |ui| {
+                ui.add(TextEdit::multiline(&mut self.script_console.script).code_editor()
+                    .desired_width(f32::INFINITY).desired_rows(20));
+            });
+        ui.separator();
+        egui::ScrollArea::vertical().id_salt("script_output").stick_to_bottom(true).show(ui, |ui| {
+            ui.label(egui::RichText::new(&self.script_console.output).monospace());
         });
+    }
+    
+    /// Displays the renders completed in this session, the latest first, each with a preview and 
+    /// the hash of its settings. A render can be shown in the display tab again and the settings 
+    /// it was rendered with can be restored. 
+    fn display_gallery(&mut self, ui: &mut Ui) {
+        let ctx = ui.ctx().clone();
+        if self.gallery.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label("Completed renders will be listed here.");
+            });
+            return;
+        }
         
-        //reflective spectrum
-        ui.horizontal_top(|ui| {
-            let label_color = if !self.ui_values.spectra.contains(&ui_material.spectrum) && is_time_even() {
-                Color32::RED
-            } else {
-                Color32::DARK_GRAY
-            };
-            ui.colored_label(label_color, "Reflecting Spectrum:").on_hover_text(MATERIAL_SPECTRUM_REFLECTING_TOOLTIP);
-            
-            let selected_text = {
-                let borrow = ui_material.spectrum.borrow();
-                borrow.to_string()
-            };
-        
-            Self::display_combobox_with_spectrum_list(
-                &mut self.ui_values.spectra,
-                ui,
-                format!("material reflecting {index} spectrum"),
-                selected_text,
-                MATERIAL_SPECTRUM_REFLECTING_TOOLTIP,
-                &mut ui_material.spectrum,
-            )
+        let mut shown_image = None;
+        let mut compared_image = None;
+        let mut restored_snapshot = None;
+        let can_compare = self.image_actual.is_some() && self.comparison_run.is_none();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (index, entry) in self.gallery.entries_mut().enumerate() {
+                ui.horizontal_top(|ui| {
+                    let thumbnail = entry.get_thumbnail(&ctx);
+                    ui.image((thumbnail.id(), thumbnail.size_vec2()));
+                    ui.vertical(|ui| {
+                        let (width, height) = entry.snapshot.get_size();
+                        ui.label(format!("Render #{}", entry.number));
+                        ui.label(format!("{width}×{height}, {} iterations, {:.3?}", 
+                            entry.snapshot.get_nbr_of_iterations(), entry.render_time));
+                        ui.label(format!("Settings hash: {:016x}", entry.snapshot.get_hash()))
+                            .on_hover_text(GALLERY_HASH_TOOLTIP);
+                        ui.horizontal(|ui| {
+                            if ui.button("Show").on_hover_text(GALLERY_SHOW_TOOLTIP).clicked() {
+                                shown_image = Some(entry.image.clone());
+                            }
+                            if ui.add_enabled(can_compare, egui::Button::new("Compare"))
+                                .on_hover_text(GALLERY_COMPARE_TOOLTIP).clicked() {
+                                compared_image = Some(entry.image.clone());
+                            }
+                            let enabled = !*self.currently_rendering.lock().unwrap();
+                            if ui.add_enabled(enabled, egui::Button::new("Restore settings from this render"))
+                                .on_hover_text(GALLERY_RESTORE_TOOLTIP).clicked() {
+                                restored_snapshot = Some(index);
+                            }
+                        });
+                    });
+                });
+                ui.separator();
+            }
         });
+        
+        if let Some(image) = shown_image {
+            self.image_actual = Some(image);
+            self.image_float = None;
+            self.image_oidn = None;
+            self.image_normals = None;
+            self.image_depth = None;
+            self.image_albedo = None;
+            self.image_ids = None;
+            self.image_light_passes.clear();
+            self.renew_texture_handle(&ctx);
+            self.tab_to_focus = Some(UiTab::Display);
+        }
+        if let Some(image) = compared_image {
+            self.start_comparison(move || Ok(image));
+        }
+        if let Some(index) = restored_snapshot {
+            if let Some(entry) = self.gallery.entries_mut().nth(index) {
+                entry.snapshot.restore(&mut self.ui_values);
+            }
+        }
     }
 
-    /// Displays a single tab for the UITabs up top.
-    fn display_tab_frame(&mut self, ui: &mut Ui, label: &str, color: Color32, tab: UiTab) {
-        if ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
-            egui::Frame::NONE.fill(color)
-                .outer_margin(0.0)
-                .inner_margin(5.0)
-                .show(ui, |ui| {
-                    let label = egui::Label::new(label)
-                        .selectable(false);
-                    ui.add(label);
-                });
-        }).response.clicked()  {
-            self.ui_values.tab = tab;
+    /// Compares the shown frame with a reference in the background, see [image_compare::compare]. 
+    /// A float reference is compared with the float colors of the render, any other with the 
+    /// displayed image, so that both went through the same exposure and tone mapping. 
+    fn start_comparison<F>(&mut self, load_reference: F) 
+    where F: FnOnce() -> Result<DynamicImage, String> + Send + 'static 
+    {
+        let Some(displayed) = self.image_actual.clone() else {
+            return;
         };
+        let float = self.shown_image_float().map(custom_image::CustomImage::get_color_image);
+        let transfer_function = self.transfer_function();
+        self.comparison_run = Some(BackgroundLoad::start("Comparing with the reference".to_string(), move |progress| {
+            let reference = load_reference().map_err(LoadError::Failed)?;
+            let image = match (&reference, float) {
+                (DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_), Some(float)) => float,
+                (DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_), None) => {
+                    return Err(LoadError::Failed("A float reference can only be compared with a render \
+                        of this session.".to_string()));
+                }
+                _ => displayed,
+            };
+            image_compare::compare(&image, &reference, transfer_function, progress)
+        }));
     }
-
+    
+    /// Displays the result of the last comparison with a reference: the RMSE, SSIM and FLIP 
+    /// values and the heatmap of the FLIP error, which can be saved. 
+    fn display_comparison(&mut self, ctx: &egui::Context) {
+        let Some((difference, heatmap)) = &self.comparison else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Image Comparison").open(&mut open).show(ctx, |ui| {
+            ui.label(format!("RMSE: {:.5}", difference.rmse)).on_hover_text(COMPARISON_RMSE_TOOLTIP);
+            ui.label(format!("SSIM: {:.4}", difference.ssim)).on_hover_text(COMPARISON_SSIM_TOOLTIP);
+            ui.label(format!("FLIP: {:.4}", difference.flip)).on_hover_text(COMPARISON_FLIP_TOOLTIP);
+            ui.add(egui::Image::from_texture(heatmap).max_width(COMPARISON_HEATMAP_WIDTH))
+                .on_hover_text(COMPARISON_HEATMAP_TOOLTIP);
+            if ui.button("Save Heatmap").clicked() {
+                let dialog = rfd::FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .set_file_name("flip.png")
+                    .save_file();
+                if let Some(Err(e)) = dialog.map(|path| difference.heatmap.save(path)) {
+                    warn!("Error saving the comparison heatmap: {:?}", e);
+                }
+            }
+        });
+        if !open {
+            self.comparison = None;
+        }
+    }
+    
     /// Takes the information from the UISpectrum at the given index, takes out all working
     /// information, stores it in the UISelectedSpectrum and displays these on the right and sight.
     fn update_selected_spectrum(&mut self, index: usize) {
@@ -1182,7 +3469,9 @@ impl App {
     }
 
     /// Iterates over all ui spectra. All non-custom Spectra are simply generated again with the new
-    /// sample size, for each custom spectrum [resample](Spectrum::resample) is called.
+    /// sample size and the current range, for each custom spectrum [resample](Spectrum::resample) is 
+    /// called. Custom spectra over a different range are instead interpolated onto the current 
+    /// range, wavelengths they did not cover are set to zero. 
     fn update_all_spectrum_sample_sizes(&mut self, nbr_of_samples: usize) {
         for ui_spectrum_ref in &mut self.ui_values.spectra {
             let mut ui_spectrum = ui_spectrum_ref.borrow_mut();
@@ -1191,7 +3480,12 @@ impl App {
             
             match ui_spectrum.spectrum_type {
                 UISpectrumType::Custom => {
-                    ui_spectrum.spectrum.resample(nbr_of_samples);
+                    if ui_spectrum.spectrum.get_range() != (lowest, highest) {
+                        let samples: Vec<(f32, f32)> = ui_spectrum.spectrum.iter().collect();
+                        ui_spectrum.spectrum = Spectrum::new_from_samples(&samples, lowest, highest, nbr_of_samples);
+                    } else {
+                        ui_spectrum.spectrum.resample(nbr_of_samples);
+                    }
                 }
                 UISpectrumType::Solar(factor) => {
                     ui_spectrum.spectrum = Spectrum::new_sunlight_spectrum(lowest, highest, nbr_of_samples, factor);
@@ -1211,6 +3505,9 @@ impl App {
                 UISpectrumType::ReflectiveBlue(factor) => {
                     ui_spectrum.spectrum = Spectrum::new_reflective_spectrum_blue(lowest, highest, nbr_of_samples, factor);
                 }
+                UISpectrumType::ColorChecker(patch) => {
+                    ui_spectrum.spectrum = Spectrum::new_color_checker_patch(patch, lowest, highest, nbr_of_samples);
+                }
             }
         }
         
@@ -1241,8 +3538,101 @@ impl App {
             .fill(Color32::LIGHT_RED);
         if ui.add_enabled(enabled, button)
             .on_hover_text(DISPLAY_ABORT_RENDERING_BUTTON_TOOLTIP).clicked() {
-                self.app_to_render_channel.as_mut().unwrap()
-                    .send(AppToRenderMessages::AbortRender).unwrap()
+                self.abort_render();
+        }
+    }
+    
+    /// Generates a button to pause the current rendering process, which turns into a button to 
+    /// resume it while paused. The button is disabled when nothing is being rendered.
+    fn display_pause_button(&mut self, ui: &mut Ui) {
+        let enabled = self.app_to_render_channel.is_some();
+        let paused = self.render_paused.load(Ordering::Relaxed);
+        let button = egui::Button::new(if paused { "Resume" } else { "Pause" });
+        if ui.add_enabled(enabled, button)
+            .on_hover_text(DISPLAY_PAUSE_RENDERING_BUTTON_TOOLTIP).clicked() {
+                self.render_paused.store(!paused, Ordering::Relaxed);
+        }
+    }
+    
+    /// Generates a button to denoise the last frame guided by its normals, albedos and depths, see 
+    /// [denoise::denoise]. The button is disabled while rendering and for frames without them. 
+    fn display_denoise_button(&mut self, ui: &mut Ui) {
+        let enabled = self.app_to_render_channel.is_none() && self.denoise_run.is_none() 
+            && self.image_float.is_some() && self.image_normals.is_some() 
+            && self.image_albedo.is_some() && self.image_depth.is_some();
+        if ui.add_enabled(enabled, egui::Button::new("Denoise"))
+            .on_hover_text(DISPLAY_DENOISE_BUTTON_TOOLTIP).clicked() {
+            if let (Some(image), Some(normals), Some(albedos), Some(depths)) = 
+                (&self.image_float, &self.image_normals, &self.image_albedo, &self.image_depth) {
+                let (image, normals, albedos, depths) = (image.clone(), normals.clone(), albedos.clone(), depths.clone());
+                self.denoise_run = Some(BackgroundLoad::start("Denoising".to_string(), move |progress| {
+                    denoise::denoise(&image, &normals, &albedos, &depths, progress)
+                }));
+            }
+        }
+    }
+    
+    /// Generates a button to denoise the last frame with Open Image Denoise, see [oidn::denoise], 
+    /// and a checkbox to switch between the denoised and the original frame. The button is 
+    /// disabled while rendering and for frames without normals and albedos. 
+    #[cfg(feature = "oidn")]
+    fn display_oidn_buttons(&mut self, ui: &mut Ui) {
+        let enabled = self.app_to_render_channel.is_none() && self.oidn_run.is_none() 
+            && self.image_float.is_some() && self.image_normals.is_some() && self.image_albedo.is_some();
+        if ui.add_enabled(enabled, egui::Button::new("OIDN"))
+            .on_hover_text(DISPLAY_OIDN_BUTTON_TOOLTIP).clicked() {
+            if let (Some(image), Some(normals), Some(albedos)) = 
+                (&self.image_float, &self.image_normals, &self.image_albedo) {
+                let (image, normals, albedos) = (image.clone(), normals.clone(), albedos.clone());
+                self.oidn_run = Some(BackgroundLoad::start("Open Image Denoise".to_string(), move |_| {
+                    oidn::denoise(&image, &normals, &albedos).map_err(|e| LoadError::Failed(e.error))
+                }));
+            }
+        }
+        let response = ui.add_enabled(self.image_oidn.is_some(), 
+                                      egui::Checkbox::new(&mut self.ui_values.show_oidn, "Show denoised"));
+        if response.on_hover_text(DISPLAY_SHOW_OIDN_TOOLTIP).changed() {
+            self.encode_last_frame(ui.ctx());
+        }
+    }
+    
+    /// Lets the running render stop after the rows currently rendered, a paused render is resumed 
+    /// for it. Returns false if nothing is being rendered. 
+    fn abort_render(&self) -> bool {
+        let Some(channel) = &self.app_to_render_channel else {
+            return false;
+        };
+        self.render_cancelled.store(true, Ordering::Relaxed);
+        self.render_paused.store(false, Ordering::Relaxed);
+        let _ = channel.send(AppToRenderMessages::AbortRender);
+        true
+    }
+    
+    /// Opens the watched scene file again if it changed on disk and renders it with at most 
+    /// [SCENE_WATCH_PREVIEW_ITERATIONS] iterations. A running render is aborted first. Files which 
+    /// can not be read, e.g. because the editor has not finished writing them, are skipped until 
+    /// they change again. 
+    fn poll_scene_watch(&mut self, ctx: &egui::Context) {
+        let Some(watch) = &mut self.scene_watch else {
+            return;
+        };
+        if watch.poll() {
+            match scene_file::read(watch.get_path(), &mut self.ui_values, &mut self.texture_cache) {
+                Ok(()) => {
+                    self.abort_render();
+                    self.scene_watch_render_pending = true;
+                }
+                Err(e) => warn!("Error opening the watched scene: {}", e.error),
+            }
+        }
+        ctx.request_repaint_after(scene_file::SCENE_WATCH_INTERVAL);
+        
+        if self.scene_watch_render_pending && !*self.currently_rendering.lock().unwrap() {
+            self.scene_watch_render_pending = false;
+            let nbr_of_iterations = self.ui_values.nbr_of_iterations;
+            self.ui_values.nbr_of_iterations = nbr_of_iterations.min(SCENE_WATCH_PREVIEW_ITERATIONS);
+            self.dispatch_render();
+            self.ui_values.nbr_of_iterations = nbr_of_iterations;
         }
     }
     
@@ -1250,12 +3640,203 @@ impl App {
     /// [check_render_legality](App::check_render_legality) returns false.
     fn display_start_render_button(&mut self, ui: &mut Ui) {
         let button_render =  egui::Button::new("Start generating image");
-        let enabled = self.check_render_legality(); //disable button when rendering would crash
+        //disable button when rendering would crash
+        let enabled = self.check_render_legality() && self.render_estimate_probe.is_none();
         if ui.add_enabled(enabled, button_render)
                 .on_disabled_hover_text(DISPLAY_START_RENDERING_BUTTON_DISABLED_TOOLTIP)
                 .clicked() {
+            self.start_render_estimate();
+        }
+    }
+    
+    /// Generates a checkbox to turn the live preview on and off, see 
+    /// [update_live_preview](App::update_live_preview). 
+    fn display_live_preview_checkbox(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.ui_values.live_preview, "Live preview")
+            .on_hover_text(DISPLAY_LIVE_PREVIEW_TOOLTIP);
+    }
+    
+    /// Generates a checkbox to turn the camera navigation on the image on and off together with the 
+    /// speed of the camera, see [navigate_camera](App::navigate_camera). The navigation relies on 
+    /// the live preview to show the moved camera. 
+    fn display_camera_navigation_settings(&mut self, ui: &mut Ui) {
+        let live_preview = self.ui_values.live_preview;
+        ui.add_enabled(live_preview, egui::Checkbox::new(&mut self.ui_values.camera_navigation, "Navigate"))
+            .on_hover_text(DISPLAY_CAMERA_NAVIGATION_TOOLTIP)
+            .on_disabled_hover_text(DISPLAY_CAMERA_NAVIGATION_DISABLED_TOOLTIP);
+        if live_preview && self.ui_values.camera_navigation {
+            ui.label("Speed:").on_hover_text(DISPLAY_CAMERA_NAVIGATION_SPEED_TOOLTIP);
+            ui.add(egui::DragValue::new(&mut self.ui_values.camera_navigation_speed).speed(0.1)
+                .range(0.01..=f32::MAX).suffix("/s"))
+                .on_hover_text(DISPLAY_CAMERA_NAVIGATION_SPEED_TOOLTIP);
+        }
+    }
+    
+    /// Generates a checkbox to focus the active camera on the surfaces clicked in the image instead 
+    /// of probing the pixels, see [focus_at_pixel](App::focus_at_pixel). Only cameras with a thick 
+    /// lens have a focus distance. 
+    fn display_click_to_focus_checkbox(&mut self, ui: &mut Ui) {
+        let thick_lens = self.ui_values.ui_cameras[self.ui_values.active_camera].thick_lens;
+        ui.add_enabled(thick_lens, egui::Checkbox::new(&mut self.ui_values.click_to_focus, "Click to focus"))
+            .on_hover_text(DISPLAY_CLICK_TO_FOCUS_TOOLTIP)
+            .on_disabled_hover_text(DISPLAY_CLICK_TO_FOCUS_DISABLED_TOOLTIP);
+    }
+    
+    /// Whether a click on the image focuses the active camera instead of probing the pixel. 
+    fn click_to_focus_enabled(&self) -> bool {
+        self.ui_values.click_to_focus && self.ui_values.ui_cameras[self.ui_values.active_camera].thick_lens
+    }
+    
+    /// Sets the focus distance of the active camera to the depth of the surface seen at the pixel 
+    /// of the output image, like tapping to focus on a phone. The camera ray through the pixel is 
+    /// traced in the current scene, see [shader::focus_distance_at]. The focus distance is kept 
+    /// where nothing is hit. 
+    fn focus_at_pixel(&mut self, (x, y): (u32, u32)) {
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+        if !self.check_render_legality() {
+            warn!("The scene can not be rendered in its current state, the focus distance is kept.");
+            return;
+        }
+        let uniforms = self.build_uniforms();
+        let dim = shader::Dimensions {width: self.ui_values.width, height: self.ui_values.height};
+        match shader::focus_distance_at(PixelPos {x, y}, dim, &uniforms) {
+            Some(distance) if distance > 0.0 => {
+                self.ui_values.ui_cameras[self.ui_values.active_camera].focus_distance = distance;
+            }
+            _ => info!("Nothing is seen at pixel {x}, {y}, the focus distance is kept."),
+        }
+    }
+    
+    /// Moves the active camera like in a first person game. While the cursor is over the image, 
+    /// W, A, S and D move it forward, left, backward and right, E and Q up and down along its up 
+    /// vector, faster while shift is held. The look delta is how far the image was dragged in 
+    /// parts of its height, dragging it by its whole height turns the camera by its field of view, 
+    /// sideways around the up vector and vertically around the right vector. The live preview 
+    /// picks up the changed camera and renders it again. 
+    fn navigate_camera(&mut self, ctx: &egui::Context, look_delta: Vec2, hovered: bool) {
+        let keys = [egui::Key::W, egui::Key::S, egui::Key::D, egui::Key::A, egui::Key::E, egui::Key::Q];
+        let (pressed, shift, dt) = ctx.input(|i| (keys.map(|key| i.key_down(key)), i.modifiers.shift, i.stable_dt));
+        let moving = hovered && !ctx.wants_keyboard_input() && pressed.contains(&true);
+        if !moving && look_delta == Vec2::ZERO {
+            return;
+        }
+        
+        let camera = &mut self.ui_values.ui_cameras[self.ui_values.active_camera];
+        let mut dir = Vector3::new(camera.dir_x, camera.dir_y, camera.dir_z).normalize();
+        let up = Vector3::new(camera.up_x, camera.up_y, camera.up_z).normalize();
+        //the right side of the image lies along up x dir, see the ray generation shader
+        let right = up.cross(&dir).normalize();
+        if dir.iter().any(|v| !v.is_finite()) || right.iter().any(|v| !v.is_finite()) {
+            return;
+        }
+        
+        if look_delta != Vec2::ZERO {
+            let fov = camera.fov_deg_y.to_radians();
+            let yaw = nalgebra::Rotation3::from_axis_angle(&nalgebra::Unit::new_normalize(up), look_delta.x * fov);
+            let pitch = nalgebra::Rotation3::from_axis_angle(&nalgebra::Unit::new_normalize(right), look_delta.y * fov);
+            let pitched = pitch * dir;
+            let margin = CAMERA_NAVIGATION_PITCH_MARGIN_DEG.to_radians();
+            if (margin..=std::f32::consts::PI - margin).contains(&pitched.angle(&up)) {
+                dir = pitched;
+            }
+            dir = yaw * dir;
+            (camera.dir_x, camera.dir_y, camera.dir_z) = (dir.x, dir.y, dir.z);
+        }
+        
+        if moving {
+            let speed = self.ui_values.camera_navigation_speed * if shift { CAMERA_NAVIGATION_FAST_FACTOR } else { 1.0 };
+            let axes = [dir, -dir, right, -right, up, -up];
+            let movement: Vector3<f32> = axes.iter().zip(pressed)
+                .filter(|(_, pressed)| *pressed)
+                .map(|(axis, _)| axis)
+                .sum();
+            let movement = movement * speed * dt;
+            camera.pos_x += movement.x;
+            camera.pos_y += movement.y;
+            camera.pos_z += movement.z;
+            //the keys are polled every frame while they are held
+            ctx.request_repaint();
+        }
+    }
+    
+    /// Keeps the render of the live preview in line with the scene: whenever the scene or the 
+    /// settings change, the running render is aborted and, once it has stopped, started again 
+    /// from an empty image, so that the frames accumulate for the current scene only. Unlike the 
+    /// start button, no estimate is made and the tabs are left as they are. Scenes which can not 
+    /// be rendered are skipped until they change again. 
+    fn update_live_preview(&mut self, ctx: &egui::Context) {
+        if !self.ui_values.live_preview {
+            self.live_preview_hash = None;
+            return;
+        }
+        let hash = RenderSnapshot::capture(&self.ui_values).get_hash();
+        if self.live_preview_hash == Some(hash) {
+            return;
+        }
+        if self.abort_render() || *self.currently_rendering.lock().unwrap() {
+            //the render finishes its rows first
+            ctx.request_repaint_after_secs(0.05);
+            return;
+        }
+        self.live_preview_hash = Some(hash);
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+        if self.check_render_legality() {
+            self.start_render(None);
+        }
+    }
+    
+    /// Estimates the memory and the time the render takes with a quick probe render in the 
+    /// background, see [render_estimate::estimate_render]. The render is dispatched once the probe 
+    /// is done, unless the estimate calls for a warning. 
+    fn start_render_estimate(&mut self) {
+        self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
+        if !self.check_render_legality() {
+            return;
+        }
+        
+        let uniforms = self.build_uniforms();
+        let (width, height) = self.ui_values.render_size();
+        let nbr_of_threads = self.ui_values.nbr_of_threads;
+        let nbr_of_light_passes = if self.ui_values.record_light_passes { uniforms.lights.len() } else { 0 };
+        let memory = render_estimate::framebuffer_memory(width, height, self.ui_values.record_spectra, 
+                                                         self.ui_values.spectrum_number_of_samples, nbr_of_light_passes);
+        self.render_estimate = None;
+        self.render_estimate_probe = Some(BackgroundLoad::start("Estimating the render time".to_string(), 
+            move |progress| render_estimate::estimate_render(uniforms, width, height, nbr_of_threads, memory, progress)));
+    }
+    
+    /// Warns about a render exceeding the memory or taking very long and lets the user start it 
+    /// anyway. 
+    fn display_render_estimate_warning(&mut self, ctx: &egui::Context) {
+        let Some(estimate) = self.render_estimate else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut start = false;
+        egui::Window::new("Render warning")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                for warning in estimate.warnings() {
+                    ui.colored_label(Color32::DARK_RED, warning);
+                }
+                ui.label(format!("Estimated memory: {}", render_estimate::format_bytes(estimate.memory)))
+                    .on_hover_text(RENDER_ESTIMATE_MEMORY_TOOLTIP);
+                ui.label(format!("Estimated time: {} per frame, {} in total", 
+                                 render_estimate::format_duration(estimate.frame_time), 
+                                 render_estimate::format_duration(estimate.total_time())))
+                    .on_hover_text(RENDER_ESTIMATE_TIME_TOOLTIP);
+                start = ui.button("Render anyway").clicked();
+            });
+        
+        if start {
             self.dispatch_render();
         }
+        if start || !open {
+            self.render_estimate = None;
+        }
     }
 
     /// Copies the first [UISpectrum] from the list which is of the [SpectrumEffectType::Reflective].
@@ -1276,86 +3857,239 @@ impl App {
     }
     
     /// A single frame render process. Takes the uniforms and mixes the image into the 
-    /// [CustomImage](custom_image::CustomImage) at the appropriate level. 
-    fn apply_shader2(img: &mut custom_image::CustomImage, uniforms: Arc<RaytracingUniforms>, thread_pool: &ThreadPool) {
+    /// [CustomImage](custom_image::CustomImage) at the appropriate level. The image is split into 
+    /// square tiles of [RENDER_TILE_SIZE](scene::RENDER_TILE_SIZE) pixels, which the thread pool 
+    /// renders in parallel into separate buffers. Work stealing keeps every thread busy, even if 
+    /// some parts of the image are much more expensive than others. While a worker renders a tile, its [RenderBucket] is 
+    /// listed in active_buckets. The raw samples are added to the [Accumulation], if there is one, 
+    /// and their AOVs to the image, if it records them. 
+    /// Every finished row of a tile is reported to the progress tracker and the UI. 
+    /// 
+    /// Some of the tiles are handed to the remote workers in proportion to their threads and 
+    /// rendered there at the same time. The tiles of a worker which fails are rendered locally 
+    /// instead and the worker is not used again. 
+    /// 
+    /// While the render is paused, the threads wait before starting their next tile. Tiles 
+    /// already started are finished, the remote workers finish the batches of tiles they were 
+    /// given and get no further ones. 
+    /// 
+    /// Once the render is cancelled, the threads stop after the row they are rendering and start 
+    /// no further tiles. The rows rendered up to then are still added to the image, whose pixels 
+    /// keep track of their own number of samples. The connections to the remote workers are shut 
+    /// down without waiting for their tiles in progress. 
+    #[allow(clippy::too_many_arguments)]
+    fn apply_shader2(img: &mut custom_image::CustomImage, uniforms: Arc<RaytracingUniforms>, thread_pool: &rayon::ThreadPool,
+                     workers: &mut Vec<RemoteWorker>, frame_number: u32, paused: &AtomicBool, cancelled: &AtomicBool,
+                     active_buckets: &Arc<Mutex<Vec<RenderBucket>>>, accumulation: &Arc<Mutex<Option<Accumulation>>>,
+                     progress: &ProgressTracker, action_list: &Arc<Mutex<Vec<AppActions>>>) {
         let width = img.get_width();
         let height = img.get_height();
+        let record_spectra = accumulation.lock().unwrap().as_ref().is_some_and(|a| a.records_spectra());
+        let record_aovs = img.records_aovs();
         
-        let (channel_sender, channel_receiver) = mpsc::channel::<(u32, Vec<f32>)>();
+        let tiles = RenderBucket::split(width, height);
         
-        for y in 0..height {
-            let sender = channel_sender.clone();
-            let uniforms = uniforms.clone();
-            
-            thread_pool.execute(move || {
-                let mut row = Vec::<f32>::with_capacity((width * 4) as usize);
-                
-                for x in 0..width {
-                    let (r, g, b) = 
-                        shader::ray_generation_shader(
-                            PixelPos{x, y}, 
-                            shader::Dimensions {width, height}, 
-                            &uniforms);
-                    
-                    row.push(r);
-                    row.push(g);
-                    row.push(b);
+        progress.start_frame(tiles.len() as u32, tiles.iter().map(|t| t.height).sum());
+        
+        //every tile goes to whoever has the fewest tiles per thread so far, index 0 is this machine
+        let mut threads = vec![thread_pool.current_num_threads()];
+        threads.extend(workers.iter().map(|w| w.get_threads()));
+        let mut assigned_tiles = vec![Vec::new(); threads.len()];
+        for bucket in tiles {
+            let index = (0..threads.len())
+                .min_by(|&a, &b| {
+                    let load_a = (assigned_tiles[a].len() + 1) as f32 / threads[a] as f32;
+                    let load_b = (assigned_tiles[b].len() + 1) as f32 / threads[b] as f32;
+                    load_a.total_cmp(&load_b)
+                })
+                .unwrap();
+            assigned_tiles[index].push(bucket);
+        }
+        let local_tiles = assigned_tiles.remove(0);
+        
+        let render_locally = |tiles: Vec<RenderBucket>| -> Vec<RenderedTile> { thread_pool.install(|| {
+            tiles.into_par_iter().filter_map(|bucket| {
+                while paused.load(Ordering::Relaxed) {
+                    thread::sleep(RENDER_PAUSE_POLL_INTERVAL);
                 }
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                active_buckets.lock().unwrap().push(bucket);
+                let mut spectra = record_spectra.then(Vec::new);
+                let mut aovs = TileAovs::default();
+                let (tile, alphas) = scene::render_tile(bucket, width, height, &uniforms, spectra.as_mut(), 
+                                                        record_aovs.then_some(&mut aovs), || {
+                    progress.finish_row(&shader::take_ray_statistics());
+                    let mut action_list = action_list.lock().unwrap();
+                    action_list.push(AppActions::RenderingProgressUpdate(progress.snapshot()));
+                    action_list.push(AppActions::RenderStatisticsUpdate(progress.statistics()));
+                    if cancelled.load(Ordering::Relaxed) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+                });
+                active_buckets.lock().unwrap().retain(|b| *b != bucket);
+                //a cancelled tile only covers the rows rendered before
+                let bucket = bucket.first_rows(alphas.len() as u32 / bucket.width);
+                if bucket.height == 0 {
+                    return None;
+                }
+                progress.finish_tile();
                 
-                sender.send((y, row)).unwrap();
-            })
-        }
+                if let Some(accumulation) = accumulation.lock().unwrap().as_mut() {
+                    accumulation.add_samples(bucket.x, bucket.y, bucket.width, &tile, spectra.as_deref());
+                }
+                #[cfg(feature = "profiling")]
+                profiler::flush_thread();
+                Some((bucket, tile, alphas, aovs))
+            }).collect()
+        })};
         
-        let mut done_rows = 0;
-        while done_rows < height { 
-            let (y, row) = channel_receiver.recv().expect("During the rendering process, a thread has terminated prematurely!");
-            let mut iter = row.into_iter();
-            let mut x = 0;
-            while let (Some(r), Some(g), Some(b)) = 
-                (iter.next(), iter.next(), iter.next()) {
-                let ratio = 1.0 / (uniforms.frame_id + 1) as f32;
-                img.blend_pixel(x, y as usize, &custom_image::Pixel { r, g, b, a: 1.0 }, ratio).unwrap();
-                x += 1;
+        let remote_tiles = Mutex::new(Vec::new());
+        let mut failed_workers = Vec::new();
+        let mut rendered_tiles = thread::scope(|scope| {
+            let handles: Vec<_> = workers.iter_mut().zip(&assigned_tiles).map(|(worker, tiles)| {
+                let remote_tiles = &remote_tiles;
+                scope.spawn(move || {
+                    let mut received = Vec::new();
+                    let result = worker.render_tiles(frame_number, tiles, paused, cancelled, |bucket, tile, alphas, aovs| {
+                        for _ in 0..bucket.height {
+                            progress.finish_row(&RayStatistics::new());
+                        }
+                        progress.finish_tile();
+                        action_list.lock().unwrap().push(AppActions::RenderingProgressUpdate(progress.snapshot()));
+                        if let Some(accumulation) = accumulation.lock().unwrap().as_mut() {
+                            accumulation.add_samples(bucket.x, bucket.y, bucket.width, &tile, None);
+                        }
+                        received.push(bucket);
+                        remote_tiles.lock().unwrap().push((bucket, tile, alphas, aovs));
+                    });
+                    result.map_err(|e| {
+                        warn!("Error rendering on worker {}, rendering its tiles locally: {}", worker.get_address(), e.error);
+                        tiles.iter().filter(|b| !received.contains(b)).copied().collect::<Vec<_>>()
+                    })
+                })
+            }).collect();
+            
+            let rendered_tiles = render_locally(local_tiles);
+            for (index, handle) in handles.into_iter().enumerate() {
+                if let Err(missing_tiles) = handle.join().unwrap() {
+                    failed_workers.push((index, missing_tiles));
+                }
             }
-            done_rows += 1;
+            rendered_tiles
+        });
+        
+        for (index, missing_tiles) in failed_workers.into_iter().rev() {
+            workers.remove(index);
+            rendered_tiles.extend(render_locally(missing_tiles));
+        }
+        rendered_tiles.extend(remote_tiles.into_inner().unwrap());
+        
+        for (bucket, tile, alphas, aovs) in rendered_tiles { 
+            scene::add_tile(img, bucket, &tile, &alphas, Some(&aovs));
         }
     }
 
     /// The overarching render process, best started in another thread. Calls
     /// [apply_shader2](App::apply_shader2) for each frame and gives the result to the main thread
-    /// to be displayed to the user.
-    fn render(mut image_float: custom_image::CustomImage, mut uniforms: RaytracingUniforms,
-              thread_pool: ThreadPool, nbr_of_iterations: u32, rendering:  Arc<Mutex<bool>>,
-              action_list: Arc<Mutex<Vec<AppActions>>>, receiver: Receiver<AppToRenderMessages>)
+    /// to be displayed to the user. If there is a worker setup, the workers are connected to first.
+    /// A resumed render starts at the given first frame, with the frames before already averaged 
+    /// into the image. If there is a checkpoint writer, a checkpoint is written regularly and when 
+    /// the render is aborted, and removed once the render completes. 
+    #[allow(clippy::too_many_arguments)]
+    fn render(mut image_float: custom_image::CustomImage, output_size: (u32, u32), mut uniforms: RaytracingUniforms,
+              thread_pool: rayon::ThreadPool, worker_setup: Option<WorkerSetup>, first_frame: u32, nbr_of_iterations: u32,
+              mut checkpoints: Option<CheckpointWriter>, rendering:  Arc<Mutex<bool>>, paused: Arc<AtomicBool>, cancelled: Arc<AtomicBool>,
+              action_list: Arc<Mutex<Vec<AppActions>>>, receiver: Receiver<AppToRenderMessages>,
+              active_buckets: Arc<Mutex<Vec<RenderBucket>>>, accumulation: Arc<Mutex<Option<Accumulation>>>)
     {
         {   //letting the ui know the render process has begun
             let mut mutex_guard = rendering.lock().unwrap();
             *mutex_guard = true;
         }
         let begin_time = Instant::now();
+        #[cfg(feature = "profiling")]
+        profiler::reset();
+        let progress = ProgressTracker::new(nbr_of_iterations, first_frame);
+        let mut workers = worker_setup.map(WorkerSetup::connect).unwrap_or_default();
+        let mut aborted = false;
+        //frames cut off by an abort count as rendered, their pixels keep their own sample counts
+        let mut frames_rendered = first_frame;
         
         //actual render process in a for loop
-        for frame_number in 0..nbr_of_iterations {
-            uniforms.frame_id = frame_number;
+        for frame_number in first_frame..nbr_of_iterations {
+            thread_pool.install(|| {
+                //the cache is built with the random values of frame 0, also when resuming
+                if frame_number == first_frame {
+                    uniforms.set_frame(0);
+                    uniforms.prepare_irradiance_cache(&shader::Dimensions {
+                        width: image_float.get_width(), 
+                        height: image_float.get_height(),
+                    });
+                }
+                if frame_number != uniforms.frame_id {
+                    uniforms.set_frame(frame_number);
+                }
+            });
             let uniforms_ref = Arc::new(uniforms.clone());
-            Self::apply_shader2(&mut image_float, uniforms_ref.clone(), &thread_pool);
+            Self::apply_shader2(&mut image_float, uniforms_ref.clone(), &thread_pool, &mut workers, frame_number, &paused, &cancelled,
+                                &active_buckets, &accumulation, &progress, &action_list);
+            frames_rendered = frame_number + 1;
+            if !cancelled.load(Ordering::Relaxed) {
+                progress.finish_frame();
+            }
             
             {   //take the custom image, convert it into a DynamicImage and send it to the main app
                 let mut action_list = action_list.lock().unwrap();
-                action_list.push(AppActions::FrameUpdate(image_float.clone().into()));
-                action_list.push(AppActions::RenderingProgressUpdate((
-                    frame_number + 1) as f32 / nbr_of_iterations as f32));
+                //a render at another scale is shown and saved at the size of the image
+                let mut display_image = image_float.resized(output_size.0, output_size.1);
+                if uniforms.gamut_compression {
+                    display_image.compress_gamut();
+                }
+                if let (Some(normals), Some(depth), Some(albedo), Some(ids)) = (display_image.get_normal_image(), 
+                    display_image.get_depth_image(), display_image.get_albedo_image(), display_image.get_id_image()) {
+                    let lights = display_image.get_light_pass_images();
+                    action_list.push(AppActions::AovsUpdate {normals, depth, albedo, ids, lights});
+                }
+                action_list.push(AppActions::FrameUpdate((&display_image).into(), display_image.without_aovs()));
+                action_list.push(AppActions::RenderingProgressUpdate(progress.snapshot()));
+                action_list.push(AppActions::RenderStatisticsUpdate(progress.statistics()));
+            }
+            if let Some(checkpoints) = &mut checkpoints {
+                checkpoints.frame_finished(frames_rendered, &image_float);
             }
 
+            //the flag is set before the abort message is sent, the frame may have been cut off
+            if cancelled.load(Ordering::Relaxed) {
+                aborted = true;
+                break;
+            }
             //check if any messages have been passed back
             if let Ok(message) = receiver.try_recv() {
                 match message {
                     AppToRenderMessages::AbortRender => {
+                        aborted = true;
                         break;  //simply jump out of loop to stop rendering
                     }
                 }
             }
         }
+        if let Some(mut checkpoints) = checkpoints {
+            if aborted {
+                checkpoints.write(frames_rendered, &image_float);
+            } else {
+                checkpoints.remove();
+            }
+        }
+
+        #[cfg(feature = "profiling")]
+        {   //writing the collected measurements next to the executable
+            let seconds = platform::SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            let base_path = std::path::PathBuf::from(format!("profile_{seconds}"));
+            match profiler::export_report(&base_path) {
+                Ok(_) => log::info!("Render profile written to {}.folded and {}.json", base_path.display(), base_path.display()),
+                Err(e) => warn!("Error writing render profile: {:?}", e),
+            }
+        }
 
         {   //letting the ui know the render process is finished
             let mut mutex_guard = rendering.lock().unwrap();
@@ -1374,6 +4108,36 @@ impl App {
     /// UI-side values, extracts the information such as the pure spectra necessary for rendering
     /// and passes these on to the next thread.
     fn dispatch_render(&mut self) {
+        self.start_render(None);
+        self.tab_to_focus = Some(UiTab::Display);
+    }
+    
+    /// Continues the render of the checkpoint, see [Checkpoint]. The scene of the checkpoint 
+    /// replaces the one in the UI, just like opening a scene file. 
+    fn resume_render(&mut self) {
+        let checkpoint = match Checkpoint::read(Path::new(CHECKPOINT_FILE_NAME)) {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                warn!("Error resuming the render: {}", e.error);
+                return;
+            }
+        };
+        if let Err(e) = scene_file::from_ron(&checkpoint.scene, &mut self.ui_values, &mut self.texture_cache) {
+            warn!("Error resuming the render, the scene of the checkpoint can not be opened: {}", e.error);
+            return;
+        }
+        if (checkpoint.image.get_width(), checkpoint.image.get_height()) != self.ui_values.render_size() {
+            warn!("Error resuming the render, the image of the checkpoint does not match its scene.");
+            return;
+        }
+        info!("Resuming the render after {} of {} frames", checkpoint.frames_done, self.ui_values.nbr_of_iterations);
+        self.start_render(Some(checkpoint));
+        self.tab_to_focus = Some(UiTab::Display);
+    }
+    
+    /// Starts the render of the scene in the UI, see [dispatch_render](App::dispatch_render). If a 
+    /// checkpoint is given, the render continues from it instead of starting with an empty image.
+    fn start_render(&mut self, checkpoint: Option<Checkpoint>) {
         self.update_all_spectrum_sample_sizes(self.ui_values.spectrum_number_of_samples);
         //TODO more safety checks?
         
@@ -1384,24 +4148,20 @@ impl App {
             return;
         }
         
-        let thread_pool = ThreadPool::new(self.ui_values.nbr_of_threads);
+        for warning in self.check_scene() {
+            warn!("Scene check: {warning}");
+        }
         
-        let example_spectrum = Spectrum::new_singular_reflectance_factor(
-            spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
-            spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
-            self.ui_values.spectrum_number_of_samples,
-            0.0,
-        );
-
-        let uniforms = RaytracingUniforms{
-            aabbs: Arc::new(self.ui_values.ui_objects.iter().filter(|o| !o.hidden).map(|o| o.into()).collect()),
-            lights: Arc::new(self.ui_values.ui_lights.iter().filter(|l| !l.hidden).map(|l| l.into()).collect()),
-            camera: shader::Camera::from(&self.ui_values.ui_camera),
-            frame_id: 0,
-            intended_frames_amount: self.ui_values.nbr_of_iterations,
-            example_spectrum,
-            max_bounces: self.ui_values.nbr_of_ray_bounces,
+        let thread_pool = match platform::render_thread_pool(self.ui_values.nbr_of_threads) {
+            Ok(thread_pool) => thread_pool,
+            Err(e) => {
+                error!("Could not create the render threads, aborting rendering: {e}");
+                return;
+            }
         };
+        let uniforms = self.build_uniforms();
+        let worker_setup = self.build_worker_setup();
+        self.render_snapshot = Some(RenderSnapshot::capture(&self.ui_values));
         
         //input validation
         let dependent = are_linear_dependent(&uniforms.camera.direction, &uniforms.camera.up);
@@ -1411,39 +4171,311 @@ impl App {
         }
         assert!(!dependent);
         
-        let image = custom_image::CustomImage::new(self.ui_values.width, self.ui_values.height);
+        let (width, height) = self.ui_values.render_size();
+        let output_size = (self.ui_values.width, self.ui_values.height);
+        let (mut image, first_frame) = match checkpoint {
+            Some(checkpoint) => (checkpoint.image, checkpoint.frames_done),
+            None => (custom_image::CustomImage::new(width, height), 0),
+        };
+        image.set_firefly_filter(self.ui_values.firefly_filter);
+        image.set_transfer_function(self.transfer_function());
+        image.set_tone_mapper(self.ui_values.tone_mapper);
+        image.set_exposure(self.ui_values.exposure);
+        image.set_bloom(self.ui_values.bloom);
+        image.set_post_effects(self.ui_values.post_effects.clone());
+        image.enable_aovs();
+        self.light_pass_names = self.ui_values.ui_lights.iter().filter(|l| !l.hidden).map(|l| l.name.clone()).collect();
         let nbr_of_iterations = self.ui_values.nbr_of_iterations;
+        let checkpoints = self.build_checkpoint_writer();
         let rendering = self.currently_rendering.clone();
+        self.render_paused.store(false, Ordering::Relaxed);
+        self.render_cancelled.store(false, Ordering::Relaxed);
+        self.ui_values.render_statistics = RayStatistics::new();
+        let paused = self.render_paused.clone();
+        let cancelled = self.render_cancelled.clone();
         let action_list = self.actions.clone();
+        let active_buckets = self.active_buckets.clone();
+        let example_spectrum = self.ui_values.record_spectra.then_some(&uniforms.example_spectrum);
+        *self.accumulation.lock().unwrap() = Some(Accumulation::new(width, height, example_spectrum));
+        let accumulation = self.accumulation.clone();
 
         let (sender, receiver) = mpsc::channel::<AppToRenderMessages>();
         self.app_to_render_channel = Some(sender);
         
-        self.ui_values.tab = UiTab::Display;
-        
-        thread::spawn(move || {
-            Self::render(image, uniforms, thread_pool, nbr_of_iterations, rendering, action_list, receiver);
+        platform::spawn(move || {
+            Self::render(image, output_size, uniforms, thread_pool, worker_setup, first_frame, nbr_of_iterations, checkpoints, 
+                         rendering, paused, cancelled, action_list, receiver, active_buckets, accumulation);
         });
     }
+    
+    /// Creates the writer of the checkpoints of the render if they are enabled in the settings. 
+    fn build_checkpoint_writer(&self) -> Option<CheckpointWriter> {
+        if !self.ui_values.write_checkpoints {
+            return None;
+        }
+        match scene_file::to_ron(&self.ui_values) {
+            Ok(scene) => Some(CheckpointWriter::new(PathBuf::from(CHECKPOINT_FILE_NAME), scene)),
+            Err(e) => {
+                warn!("Error preparing the checkpoints, rendering without them: {}", e.error);
+                None
+            }
+        }
+    }
+
+    /// Creates the setup for rendering on the workers listed in the settings, None if there are 
+    /// none or the render needs something the workers can not provide. 
+    fn build_worker_setup(&self) -> Option<WorkerSetup> {
+        if self.ui_values.render_workers.trim().is_empty() {
+            return None;
+        }
+        if self.ui_values.record_spectra || self.ui_values.record_light_passes || self.ui_values.transparent_film 
+            || self.ui_values.color_response == UIColorResponse::CustomSensor {
+            warn!("Recording spectra or light passes, transparent film and custom sensors are not supported by the \
+                   render workers, rendering locally.");
+            return None;
+        }
+        match scene_file::to_ron(&self.ui_values) {
+            Ok(scene) => WorkerSetup::new(&self.ui_values.render_workers, &self.ui_values.render_worker_token, scene),
+            Err(e) => {
+                warn!("Error preparing the scene for the render workers, rendering locally: {}", e.error);
+                None
+            }
+        }
+    }
+
+    /// Creates the uniforms for a render from the current UI values. All light sources and 
+    /// emissive objects are scaled by the light scale. 
+    fn build_uniforms(&self) -> RaytracingUniforms {
+        let lights = self.ui_values.ui_lights.iter().filter(|l| !l.hidden).map(|l| {
+            let mut light: shader::Light = (&hierarchy::world_light(l)).into();
+            light.scale_emission(self.ui_values.light_scale);
+            light
+        }).collect();
+        let objects = self.ui_values.ui_objects.iter().filter(|o| !o.hidden).map(|o| {
+            let mut aabb: shader::Aabb = (&hierarchy::world_object(o)).into();
+            aabb.scale_emission(self.ui_values.light_scale);
+            aabb
+        }).collect();
+        let scene = Scene {
+            objects,
+            lights,
+            camera: shader::Camera::from(&self.ui_values.ui_cameras[self.ui_values.active_camera]),
+            medium: self.ui_values.medium.as_ref().map(|medium| Arc::new(medium.into())),
+            atmosphere: self.ui_values.atmosphere.as_ref().map(|atmosphere| {
+                let mut atmosphere: Atmosphere = atmosphere.into();
+                atmosphere.scale_emission(self.ui_values.light_scale);
+                Arc::new(atmosphere)
+            }),
+            background: {
+                let mut background: Background = (&self.ui_values.background).into();
+                background.scale_emission(self.ui_values.light_scale);
+                background
+            },
+        };
+        
+        let uniforms = scene.build_uniforms(&self.build_render_settings(), self.bvh.take());
+        self.bvh.replace(Some(uniforms.bvh.clone()));
+        uniforms
+    }
+
+    /// Returns how the colors of renders are encoded into 8 and 16 bit images. 
+    fn transfer_function(&self) -> TransferFunction {
+        if self.ui_values.linear_output { TransferFunction::Linear } else { TransferFunction::Srgb }
+    }
+
+    /// Returns the standard observer the spectra are converted to colors with. 
+    fn observer(&self) -> Observer {
+        match self.ui_values.color_response {
+            UIColorResponse::CieObserver1931 => Observer::Cie1931,
+            _ => Observer::Cie1964,
+        }
+    }
+
+    /// Creates the settings for a render from the current UI values. 
+    fn build_render_settings(&self) -> RenderSettings {
+        RenderSettings {
+            width: self.ui_values.width,
+            height: self.ui_values.height,
+            nbr_of_iterations: self.ui_values.nbr_of_iterations,
+            max_bounces: self.ui_values.nbr_of_ray_bounces,
+            max_diffuse_bounces: self.ui_values.nbr_of_diffuse_bounces,
+            max_specular_bounces: self.ui_values.nbr_of_specular_bounces,
+            max_transmission_bounces: self.ui_values.nbr_of_transmission_bounces,
+            russian_roulette: self.ui_values.russian_roulette,
+            sampler_type: self.ui_values.sampler_type,
+            random_seed: self.ui_values.random_seed,
+            nbr_of_samples: self.ui_values.spectrum_number_of_samples,
+            spectrum_range: (self.ui_values.spectrum_lower_bound, self.ui_values.spectrum_upper_bound),
+            light_selection: self.ui_values.light_selection,
+            light_samples: self.ui_values.light_samples,
+            photon_count: self.ui_values.photon_count,
+            photon_radius: self.ui_values.photon_radius,
+            irradiance_cache_quality: self.ui_values.irradiance_cache_quality,
+            ray_offset: self.ui_values.ray_offset,
+            firefly_filter: self.ui_values.firefly_filter,
+            gamut_compression: self.ui_values.gamut_compression,
+            light_passes: self.ui_values.record_light_passes,
+            transparent_film: self.ui_values.transparent_film,
+            false_color: match self.ui_values.color_response {
+                UIColorResponse::FalseColor(false_color) => Some(false_color),
+                _ => None,
+            },
+            observer: self.observer(),
+            sensor_response: match self.ui_values.color_response {
+                UIColorResponse::CieObserver | UIColorResponse::CieObserver1931 | UIColorResponse::FalseColor(_) => None,
+                UIColorResponse::TypicalCmos => Some(Arc::new(SensorResponse::new_typical_cmos())),
+                UIColorResponse::CustomSensor => {
+                    if self.ui_values.custom_sensor_response.is_none() {
+                        warn!("No custom sensor response loaded, falling back to the CIE observer.");
+                    }
+                    self.ui_values.custom_sensor_response.clone()
+                }
+            },
+        }
+    }
+
+    /// Returns the name of the buffer for the selection on the display tab. Light passes are named 
+    /// after their lights. 
+    fn displayed_buffer_name(&self, buffer: DisplayedBuffer) -> String {
+        match buffer {
+            DisplayedBuffer::LightPass(index) if index < self.light_pass_names.len() => {
+                format!("Light: {}", self.light_pass_names[index])
+            }
+            buffer => buffer.to_string(),
+        }
+    }
+
+    /// Displays whether bright pixels of the render glow, together with the parameters of the glow. 
+    /// Returns whether any of them changed. 
+    fn display_bloom_settings(&mut self, ui: &mut Ui) -> bool {
+        let mut enabled = self.ui_values.bloom.is_some();
+        let mut changed = ui.checkbox(&mut enabled, "Bloom").on_hover_text(DISPLAY_BLOOM_TOOLTIP).changed();
+        if !enabled {
+            self.ui_values.bloom = None;
+        } else if self.ui_values.bloom.is_none() {
+            self.ui_values.bloom = Some(Bloom::default());
+        }
+        
+        if let Some(bloom) = &mut self.ui_values.bloom {
+            ui.label("Threshold:").on_hover_text(DISPLAY_BLOOM_THRESHOLD_TOOLTIP);
+            changed |= ui.add(egui::DragValue::new(&mut bloom.threshold).range(0.0..=f32::MAX).speed(0.05))
+                .on_hover_text(DISPLAY_BLOOM_THRESHOLD_TOOLTIP).changed();
+            ui.label("Intensity:").on_hover_text(DISPLAY_BLOOM_INTENSITY_TOOLTIP);
+            changed |= ui.add(egui::Slider::new(&mut bloom.intensity, 0.0..=1.0))
+                .on_hover_text(DISPLAY_BLOOM_INTENSITY_TOOLTIP).changed();
+            ui.label("Radius:").on_hover_text(DISPLAY_BLOOM_RADIUS_TOOLTIP);
+            changed |= ui.add(egui::Slider::new(&mut bloom.radius, 0.001..=0.1).logarithmic(true))
+                .on_hover_text(DISPLAY_BLOOM_RADIUS_TOOLTIP).changed();
+        }
+        changed
+    }
+
+    /// The float colors of the shown frame: the frame denoised by Open Image Denoise if it is 
+    /// shown, the rendered one otherwise. 
+    fn shown_image_float(&self) -> Option<&custom_image::CustomImage> {
+        match (&self.image_oidn, self.ui_values.show_oidn) {
+            (Some(image_oidn), true) => Some(image_oidn),
+            _ => self.image_float.as_ref(),
+        }
+    }
+    
+    /// Converts the float colors of the last frame again with the current bloom, exposure, tone 
+    /// mapper, post effects and transfer function and displays them. A running render picks the change up with its next frame. 
+    fn encode_last_frame(&mut self, ctx: &egui::Context) {
+        let transfer_function = self.transfer_function();
+        for image in [&mut self.image_float, &mut self.image_oidn].into_iter().flatten() {
+            image.set_transfer_function(transfer_function);
+            image.set_tone_mapper(self.ui_values.tone_mapper);
+            image.set_exposure(self.ui_values.exposure);
+            image.set_bloom(self.ui_values.bloom);
+            image.set_post_effects(self.ui_values.post_effects.clone());
+        }
+        if let Some(image) = self.shown_image_float() {
+            self.image_actual = Some(image.into());
+            self.renew_texture_handle(ctx);
+        }
+    }
 
     /// Takes the [DynamicImage] in [image_actual](App::image_actual) and generates an egui texture
-    /// handle from it. This is necessary to display the image to the user.
+    /// handle from it. This is necessary to display the image to the user. <br>
+    /// Should the image exceed the maximum texture size of the graphics backend, a downsampled 
+    /// proxy is displayed instead, parts of which can be inspected in full resolution. 
     fn renew_texture_handle(&mut self, ctx: &egui::Context) {
         if self.image_actual.is_none() {
             self.image_eframe_texture = None;
+            self.image_downsample_factor = None;
+            self.image_crop_center = None;
+            self.image_crop_texture = None;
             return;
         }
         
-        let img = self.image_actual.clone().unwrap();
-
-        let rgb_img = img.to_rgba8();
-        let size = [rgb_img.width() as usize, rgb_img.height() as usize];
-        let pixels = rgb_img.as_raw();
-        let color_image =
-            egui::ColorImage::from_rgba_unmultiplied(size, pixels);
+        let mut img = self.image_actual.clone().unwrap();
+        self.noise_error = None;
+        match self.ui_values.displayed_buffer {
+            DisplayedBuffer::Image => (),
+            DisplayedBuffer::NoiseHeatmap => {
+                if let Some(accumulation) = self.accumulation.lock().unwrap().as_ref() {
+                    img = accumulation.noise_heatmap();
+                    self.noise_error = accumulation.error_quantile(NOISE_ERROR_QUANTILE);
+                }
+            }
+            DisplayedBuffer::Normals => {
+                if let Some(normals) = &self.image_normals {
+                    img = custom_image::encode_normal_image(normals);
+                }
+            }
+            DisplayedBuffer::Depth => {
+                if let Some(depth) = &self.image_depth {
+                    img = custom_image::encode_depth_image(depth, self.ui_values.depth_near, self.ui_values.depth_far);
+                }
+            }
+            DisplayedBuffer::Albedo => {
+                if let Some(albedo) = &self.image_albedo {
+                    img = custom_image::encode_color_image(albedo, self.transfer_function());
+                }
+            }
+            DisplayedBuffer::ObjectIds => {
+                if let Some(ids) = &self.image_ids {
+                    img = ids.to_rgb8().into();
+                }
+            }
+            DisplayedBuffer::LightPass(index) => {
+                if let Some(light_pass) = self.image_light_passes.get(index) {
+                    img = custom_image::encode_color_image(light_pass, self.transfer_function());
+                }
+            }
+        }
+        
+        let max_side = ctx.input(|i| i.max_texture_side) as u32;
+        self.image_downsample_factor = None;
+        if img.width() > max_side || img.height() > max_side {
+            let full_width = img.width();
+            img = img.resize(max_side, max_side, image::imageops::FilterType::Triangle);
+            self.image_downsample_factor = Some(full_width as f32 / img.width() as f32);
+        }
 
         self.image_eframe_texture = Some(
-            ctx.load_texture("dynamic_image", color_image, egui::TextureOptions::default())
+            ctx.load_texture("dynamic_image", dynamic_image_to_color_image(&img), egui::TextureOptions::default())
+        );
+        self.renew_crop_texture_handle(ctx);
+    }
+
+    /// Cuts a [CROP_INSPECTION_SIZE] sized square around [image_crop_center](App::image_crop_center)
+    /// out of the full resolution image and generates an egui texture handle from it. 
+    fn renew_crop_texture_handle(&mut self, ctx: &egui::Context) {
+        let (Some(img), Some((center_x, center_y))) = (&self.image_actual, self.image_crop_center) else {
+            self.image_crop_texture = None;
+            return;
+        };
+        
+        let width = CROP_INSPECTION_SIZE.min(img.width());
+        let height = CROP_INSPECTION_SIZE.min(img.height());
+        let x = center_x.saturating_sub(width / 2).min(img.width() - width);
+        let y = center_y.saturating_sub(height / 2).min(img.height() - height);
+        let crop = img.crop_imm(x, y, width, height);
+        
+        self.image_crop_texture = Some(
+            ctx.load_texture("dynamic_image_crop", dynamic_image_to_color_image(&crop), egui::TextureOptions::NEAREST)
         );
     }
 
@@ -1453,6 +4485,9 @@ impl App {
         let lights_ok = self.check_lights_legality();
         let objects_ok = self.check_objects_legality();
         let materials_ok = self.check_materials_legality();
+        let medium_ok = self.check_medium_legality();
+        let atmosphere_ok = self.check_atmosphere_legality();
+        let background_ok = self.check_background_legality();
 
         let ui_sample_nbr = self.ui_values.spectrum_number_of_samples;
         let spectra_ok = self.ui_values.spectra.iter()
@@ -1460,7 +4495,29 @@ impl App {
 
         let not_currently_rendering = !*self.currently_rendering.lock().unwrap();
 
-        lights_ok && objects_ok && spectra_ok && materials_ok && not_currently_rendering
+        lights_ok && objects_ok && spectra_ok && materials_ok && medium_ok && atmosphere_ok 
+            && background_ok && not_currently_rendering
+    }
+
+    /// Checks if the spectra of the [UIMedium] are in the official list. 
+    fn check_medium_legality(&self) -> bool {
+        self.ui_values.medium.as_ref().is_none_or(|m| self.ui_values.spectra.contains(&m.absorption) 
+            && self.ui_values.spectra.contains(&m.scattering))
+    }
+
+    /// Checks if the sun spectrum of the [UIAtmosphere] is in the official list. 
+    fn check_atmosphere_legality(&self) -> bool {
+        self.ui_values.atmosphere.as_ref().is_none_or(|a| self.ui_values.spectra.contains(&a.sun_spectrum))
+    }
+
+    /// Checks if the spectra of the [UIBackground] are in the official list. 
+    fn check_background_legality(&self) -> bool {
+        match &self.ui_values.background {
+            UIBackground::Black | UIBackground::Transparent => true,
+            UIBackground::Spectrum(spectrum) => self.ui_values.spectra.contains(spectrum),
+            UIBackground::Gradient {top, bottom} => self.ui_values.spectra.contains(top) 
+                && self.ui_values.spectra.contains(bottom),
+        }
     }
 
     /// Checks if all [UILights](UILight) are in order. Returns false if the rendering process
@@ -1470,35 +4527,61 @@ impl App {
             .all(|l| self.ui_values.spectra.contains(&l.spectrum))
     }
 
-    /// Checks if all [UIObjects](UIObject) have materials which are in the official lists. 
+    /// Checks if all [UIObjects](UIObject) have materials and emission spectra which are in the 
+    /// official lists. 
     fn check_objects_legality(&self) -> bool {
         self.ui_values.ui_objects.iter()
-            .all(|o| self.ui_values.materials.contains(&o.material))
+            .all(|o| self.ui_values.materials.contains(&o.material) 
+                && o.emission.as_ref().is_none_or(|e| self.ui_values.spectra.contains(e)))
     }
     
-    /// Checks if all [UIMaterials](UIMaterial) have spectra in their materials, which are in the 
-    /// official lists. 
+    /// Checks if all [UIMaterials](UIMaterial) have spectra in their materials, patterns and 
+    /// fluorescence and mix materials, which are in the official lists. 
     fn check_materials_legality(&self) -> bool {
         self.ui_values.materials.iter()
-            .all(|o| self.ui_values.spectra.contains(&o.borrow().spectrum))
+            .all(|o| {
+                let material = o.borrow();
+                self.ui_values.spectra.contains(&material.spectrum)
+                    && material.pattern.as_ref().is_none_or(|p| self.ui_values.spectra.contains(&p.spectrum))
+                    && material.fluorescence.as_ref().is_none_or(|f| self.ui_values.spectra.contains(&f.excitation) 
+                        && self.ui_values.spectra.contains(&f.emission))
+                    && material.mix.as_ref().is_none_or(|m| self.ui_values.materials.contains(&m.first) 
+                        && self.ui_values.materials.contains(&m.second))
+            })
     }
 }
 
+/// A tile rendered in [App::apply_shader2]: its bucket, the RGB values, the alpha values and the 
+/// AOVs of its pixels. 
+type RenderedTile = (RenderBucket, Vec<f32>, Vec<f32>, TileAovs);
+
 /// Some threads, started by the UI, may need to write back to the main struct of the application
 /// but do not have a reference to it. They can instead submit an AppAction which describes their
 /// intent and the necessary data to complete these actions.
 enum AppActions {
     /// The rendering thread has completed an image, which can now be written back to the main
-    /// struct to be displayed for the user.
-    FrameUpdate(DynamicImage),
+    /// struct to be displayed for the user, together with its float colors to save them as HDR.
+    FrameUpdate(DynamicImage, custom_image::CustomImage),
+    
+    /// The rendering thread has completed a frame and sends the shading normals, the depth, the 
+    /// albedo, the object ids and the light passes of the image along with it, to be displayed 
+    /// and saved instead of the image on request. Sent before the [AppActions::FrameUpdate] of the 
+    /// same frame. 
+    AovsUpdate { normals: DynamicImage, depth: DynamicImage, albedo: DynamicImage, ids: DynamicImage, 
+                 lights: Vec<DynamicImage> },
     
     /// The rendering thread has completed the rendering process and reports back how long it took 
     /// exactly so that the UI may report it even if the ui did not update in a while. 
     TrueTimeUpdate(Duration),
     
-    /// The rendering thread has completed a step in rendering the image and now reports the 
-    /// current progress amount until it is finished, to be displayed in a progressbar. 
-    RenderingProgressUpdate(f32),
+    /// The rendering thread has completed a step in rendering the image, at least a row of a 
+    /// tile, and now reports the current progress until it is finished, to be displayed in a 
+    /// progressbar and used to estimate the remaining time. 
+    RenderingProgressUpdate(RenderProgress),
+    
+    /// The rendering thread reports the rays shot so far together with the progress, to be shown 
+    /// in the statistics of the display tab. 
+    RenderStatisticsUpdate(RayStatistics),
 
     /// The rendering thread has completed and its receiver is destroyed. Consequently, the app's
     /// sender is useless and should be destroyed as well.
@@ -1511,22 +4594,114 @@ enum AppActions {
 struct UIFields {
     width: u32,
     height: u32,
+    /// The resolution the image is rendered at relative to its width and height, see 
+    /// [render_size](UIFields::render_size). 
+    render_scale: RenderScale,
     frame_gen_time: Option<Duration>,
     nbr_of_iterations: u32,
     nbr_of_threads: usize,
     nbr_of_ray_bounces: u32,
-    tab: UiTab,
+    nbr_of_diffuse_bounces: u32,
+    nbr_of_specular_bounces: u32,
+    nbr_of_transmission_bounces: u32,
+    russian_roulette: bool,
+    sampler_type: SamplerType,
+    random_seed: u32,
+    light_selection: LightSelection,
+    /// The number of lights drawn at every hit, unless all lights are sampled.
+    light_samples: u32,
+    /// The number of caustic photons traced every frame, 0 if no photon map is used.
+    photon_count: u32,
+    photon_radius: f32,
+    /// The quality of the irradiance cache in range \[0; 1], None if no cache is used.
+    irradiance_cache_quality: Option<f32>,
+    /// How far above a surface new rays start, relative to the scale of the hit.
+    ray_offset: f32,
+    firefly_filter: FireflyFilter,
+    gamut_compression: bool,
+    show_render_buckets: bool,
+    /// What the display tab shows of the render. 
+    displayed_buffer: DisplayedBuffer,
+    /// How the colors of the render are compressed into the displayable range. 
+    tone_mapper: ToneMapper,
+    /// The stops the colors of the render are brightened by before tone mapping. 
+    exposure: f32,
+    /// The glow added around bright pixels of the render, None for no glow. 
+    bloom: Option<Bloom>,
+    /// The stylistic effects applied to the displayed and saved images after tone mapping, in order. 
+    post_effects: Vec<PostEffect>,
+    /// The distances shown white and black in the depth view. 
+    depth_near: f32,
+    depth_far: f32,
+    color_response: UIColorResponse,
+    custom_sensor_response: Option<Arc<SensorResponse>>,
     after_ui_action: Option<AfterUIActions>,
-    ui_camera: UICamera,
+    ui_cameras: Vec<UICamera>,
+    active_camera: usize,
     ui_lights: Vec<UILight>, 
     ui_objects: Vec<UIObject>,
-    progress_bar_progress: f32,
+    /// The nodes of the scene hierarchy, see [UIGroup].
+    ui_groups: Vec<Rc<RefCell<UIGroup>>>,
+    render_progress: RenderProgress,
+    /// The rays shot by the current or last render. 
+    render_statistics: RayStatistics,
     spectra: Vec<Rc<RefCell<UISpectrum>>>,
     materials: Vec<Rc<RefCell<UIMaterial>>>,
+    /// The fog filling the scene, None for a vacuum.
+    medium: Option<UIMedium>,
+    /// The sky and the sun surrounding the scene, None for a black void.
+    atmosphere: Option<UIAtmosphere>,
+    /// What rays leaving the scene see if there is no atmosphere.
+    background: UIBackground,
     spectrum_lower_bound: f32,
     spectrum_upper_bound: f32,
     spectrum_number_of_samples: usize,
     selected_spectrum: Option<UISelectedSpectrum>,
+    /// The ids of the [UISpectrum]s plotted in the spectrum comparison.
+    compared_spectra: Vec<u32>,
+    /// The illuminance in lux and the distance in meters used to calculate the factor of black 
+    /// body spectra. 
+    illuminance_helper_lux: f32,
+    illuminance_helper_distance: f32,
+    /// A multiplier applied to the spectra of all lights and emissive objects when rendering. The 
+    /// spectra themselves are left untouched, so that the scaling can be reverted. 
+    light_scale: f32,
+    /// Whether the spectra of all samples are summed up for the raw accumulation export. 
+    record_spectra: bool,
+    /// Whether the light of every light source is recorded in a pass of its own. 
+    record_light_passes: bool,
+    /// Whether pixels which see nothing of the scene are transparent in the saved images. 
+    transparent_film: bool,
+    /// Whether the 8 and 16 bit images keep the linear colors instead of applying the sRGB curve. 
+    linear_output: bool,
+    /// Whether the frame denoised by Open Image Denoise is shown instead of the rendered one. 
+    show_oidn: bool,
+    /// Whether the scene is rendered again whenever it changes, see 
+    /// [update_live_preview](App::update_live_preview). 
+    live_preview: bool,
+    /// Whether the active camera is moved with the keyboard and turned by dragging the image, see 
+    /// [navigate_camera](App::navigate_camera). 
+    camera_navigation: bool,
+    /// The distance the camera moves per second while navigating. 
+    camera_navigation_speed: f32,
+    /// Whether a click on the image focuses the active camera, see 
+    /// [focus_at_pixel](App::focus_at_pixel). 
+    click_to_focus: bool,
+    /// Whether the render is saved to a checkpoint regularly, so that it can be resumed. 
+    write_checkpoints: bool,
+    /// The comma separated addresses of other instances in worker mode which render a share of 
+    /// the tiles. 
+    render_workers: String,
+    /// The token the render workers were started with, sent along with the scene. Neither stored 
+    /// between sessions nor written into scene files. 
+    render_worker_token: String,
+    /// The port of localhost the remote control listens on, see [RemoteControl]. 
+    remote_control_port: u16,
+    /// The number of paths written to a file by the debug path dump. 
+    debug_path_count: u32,
+    compare_with_d65: bool,
+    comparison_normalized: bool,
+    comparison_show_samples: bool,
     image_scene_rect: egui::emath::Rect,
     normalized_white_spectrum: Spectrum,
     selected_reflective_base_spectrum: Rc<RefCell<UISpectrum>>,
@@ -1535,6 +4710,13 @@ struct UIFields {
 }
 
 impl UIFields {
+    /// Returns the size the image is rendered at, its width and height scaled by the render 
+    /// scale. The frames are resized to the width and height again. 
+    fn render_size(&self) -> (u32, u32) {
+        let factor = self.render_scale.factor();
+        (((self.width as f32 * factor).round() as u32).max(1), ((self.height as f32 * factor).round() as u32).max(1))
+    }
+    
     fn cornell_box(&mut self) {
         let spectrum = Spectrum::new_sunlight_spectrum(
             spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
@@ -1629,9 +4811,14 @@ impl UIFields {
 
         self.ui_lights = ui_lights;
         self.ui_objects = ui_objects;
+        self.ui_groups = Vec::new();
+        self.medium = None;
+        self.atmosphere = None;
+        self.background = UIBackground::Black;
         self.spectra = spectra;
         self.materials = materials;
-        self.ui_camera = UICamera::default();
+        self.ui_cameras = vec![UICamera::default()];
+        self.active_camera = 0;
     }
 }
 
@@ -1733,18 +4920,69 @@ impl Default for UIFields {
         Self {
             width: 600,
             height: 400,
+            render_scale: RenderScale::default(),
             frame_gen_time: None,
             nbr_of_iterations: NBR_OF_ITERATIONS_DEFAULT,
             nbr_of_threads: determine_optimal_thread_count(),
             nbr_of_ray_bounces: NEW_RAY_MAX_BOUNCES_DEFAULT,
-            tab: UiTab::Settings,
+            nbr_of_diffuse_bounces: NEW_RAY_MAX_DIFFUSE_BOUNCES_DEFAULT,
+            nbr_of_specular_bounces: NEW_RAY_MAX_SPECULAR_BOUNCES_DEFAULT,
+            nbr_of_transmission_bounces: NEW_RAY_MAX_TRANSMISSION_BOUNCES_DEFAULT,
+            russian_roulette: true,
+            sampler_type: SamplerType::Pcg,
+            random_seed: 0,
+            light_selection: LightSelection::All,
+            light_samples: 1,
+            photon_count: 0,
+            photon_radius: PHOTON_RADIUS_DEFAULT,
+            irradiance_cache_quality: None,
+            ray_offset: RAY_OFFSET_DEFAULT,
+            firefly_filter: FireflyFilter::Off,
+            gamut_compression: false,
+            show_render_buckets: true,
+            displayed_buffer: DisplayedBuffer::Image,
+            tone_mapper: ToneMapper::Clip,
+            exposure: 0.0,
+            bloom: None,
+            post_effects: Vec::new(),
+            depth_near: DEPTH_NEAR_DEFAULT,
+            depth_far: DEPTH_FAR_DEFAULT,
+            color_response: UIColorResponse::CieObserver,
+            custom_sensor_response: None,
             after_ui_action: None,
-            ui_camera: UICamera::default(),
+            ui_cameras: vec![UICamera::default()],
+            active_camera: 0,
             ui_lights,
             ui_objects,
-            progress_bar_progress: 0.0,
+            ui_groups: Vec::new(),
+            render_progress: RenderProgress::default(),
+            render_statistics: RayStatistics::default(),
             spectra,
             materials,
+            medium: None,
+            atmosphere: None,
+            background: UIBackground::Black,
+            compared_spectra: Vec::new(),
+            illuminance_helper_lux: 500.0,
+            illuminance_helper_distance: 2.0,
+            light_scale: 1.0,
+            record_spectra: false,
+            record_light_passes: false,
+            transparent_film: false,
+            linear_output: false,
+            show_oidn: false,
+            live_preview: false,
+            camera_navigation: false,
+            camera_navigation_speed: 1.0,
+            click_to_focus: false,
+            write_checkpoints: false,
+            render_workers: String::new(),
+            render_worker_token: String::new(),
+            remote_control_port: DEFAULT_REMOTE_CONTROL_PORT,
+            debug_path_count: 100,
+            compare_with_d65: false,
+            comparison_normalized: true,
+            comparison_show_samples: false,
             spectrum_lower_bound: spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
             spectrum_upper_bound: spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
             spectrum_number_of_samples: NBR_OF_SPECTRUM_SAMPLES_DEFAULT,
@@ -1758,6 +4996,59 @@ impl Default for UIFields {
     }
 }
 
+/// The render settings which are stored between sessions. The scene itself is not stored, it can 
+/// be saved into a scene file instead. 
+#[derive(PartialEq, Serialize, Deserialize)]
+struct StoredSettings {
+    width: u32,
+    height: u32,
+    nbr_of_threads: usize,
+    nbr_of_iterations: u32,
+    nbr_of_ray_bounces: u32,
+    nbr_of_diffuse_bounces: u32,
+    nbr_of_specular_bounces: u32,
+    nbr_of_transmission_bounces: u32,
+    spectrum_number_of_samples: usize,
+}
+
+impl StoredSettings {
+    /// Whether the settings are ones the UI could have produced, the stored file may have been 
+    /// edited by hand. 
+    fn is_valid(&self) -> bool {
+        self.width > 0 && self.height > 0 && self.nbr_of_threads > 0 && self.nbr_of_iterations > 0
+            && spectrum_file::check_nbr_of_samples(self.spectrum_number_of_samples).is_ok()
+    }
+    
+    /// Overwrites the settings of the UI values, except for the number of samples of the spectra, 
+    /// which requires the spectra to be resampled. 
+    fn apply(&self, ui_values: &mut UIFields) {
+        ui_values.width = self.width;
+        ui_values.height = self.height;
+        ui_values.nbr_of_threads = self.nbr_of_threads;
+        ui_values.nbr_of_iterations = self.nbr_of_iterations;
+        ui_values.nbr_of_ray_bounces = self.nbr_of_ray_bounces;
+        ui_values.nbr_of_diffuse_bounces = self.nbr_of_diffuse_bounces;
+        ui_values.nbr_of_specular_bounces = self.nbr_of_specular_bounces;
+        ui_values.nbr_of_transmission_bounces = self.nbr_of_transmission_bounces;
+    }
+}
+
+impl From<&UIFields> for StoredSettings {
+    fn from(value: &UIFields) -> Self {
+        StoredSettings {
+            width: value.width,
+            height: value.height,
+            nbr_of_threads: value.nbr_of_threads,
+            nbr_of_iterations: value.nbr_of_iterations,
+            nbr_of_ray_bounces: value.nbr_of_ray_bounces,
+            nbr_of_diffuse_bounces: value.nbr_of_diffuse_bounces,
+            nbr_of_specular_bounces: value.nbr_of_specular_bounces,
+            nbr_of_transmission_bounces: value.nbr_of_transmission_bounces,
+            spectrum_number_of_samples: value.spectrum_number_of_samples,
+        }
+    }
+}
+
 /// A struct dedicated to holding the currently selected spectrum. This struct allows for quick
 /// access to individual spectrum values and the spectrum itself to display each wavelength
 /// value and the final colors.
@@ -1841,7 +5132,7 @@ impl Display for UISpectrum {
 /// they portray the composition of light. Reflective spectra are not spectra per se, more are they 
 /// tables of percentages for how much a given wavelength is reflected. In the shader however, they 
 /// are the same datatype, therefore the UI does not discriminate on a type basis either.  
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 enum SpectrumEffectType {
     Emissive,
     Reflective,
@@ -1864,7 +5155,7 @@ impl Display for SpectrumEffectType {
 /// samples f. ex. each type is handled differently. For custom, each value is linearly interpolated 
 /// making the process quiet lossy. For every other type, the appropriate new [Spectrum] function is
 /// called and a new spectrum used instead. 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[derive(PartialEq)]
 enum UISpectrumType {
     Custom,
@@ -1875,6 +5166,8 @@ enum UISpectrumType {
     ReflectiveRed(f32),
     ReflectiveGreen(f32),
     ReflectiveBlue(f32),
+    ///Parameter = index of the ColorChecker patch, counted row by row from 0
+    ColorChecker(usize),
 }
 
 impl Display for UISpectrumType {
@@ -1887,6 +5180,7 @@ impl Display for UISpectrumType {
             UISpectrumType::ReflectiveRed(_) => write!(f, "Reflective red"),
             UISpectrumType::ReflectiveGreen(_) => write!(f, "Reflective green"),
             UISpectrumType::ReflectiveBlue(_) => write!(f, "Reflective blue"),
+            UISpectrumType::ColorChecker(_) => write!(f, "ColorChecker patch"),
         }
     }
 }
@@ -1911,6 +5205,71 @@ impl PartialEq for UISpectrum {
     }
 }
 
+/// A named node of the scene hierarchy. Its translation and rotation apply to all objects, lights 
+/// and groups assigned to it, on top of their own position and orientation. 
+#[derive(Debug)]
+struct UIGroup {
+    pos_x: f32,
+    pos_y: f32,
+    pos_z: f32,
+    ///The rotation about the three axes in radians, applied as euler-angles like those of the 
+    /// [rotated box](UIObjectType::RotatedBox). 
+    rot_x: f32,
+    rot_y: f32,
+    rot_z: f32,
+    ///The group this group is nested in, None for groups at the root of the scene.
+    parent: Option<Rc<RefCell<UIGroup>>>,
+    name: String,
+    id: u32,
+    editing_name: bool,
+}
+
+impl UIGroup {
+    fn new(name: String) -> Self {
+        UIGroup {
+            pos_x: 0.0,
+            pos_y: 0.0,
+            pos_z: 0.0,
+            rot_x: 0.0,
+            rot_y: 0.0,
+            rot_z: 0.0,
+            parent: None,
+            name,
+            id: get_id(),
+            editing_name: false,
+        }
+    }
+}
+
+impl Clone for UIGroup {
+    fn clone(&self) -> Self {
+        Self {
+            pos_x: self.pos_x,
+            pos_y: self.pos_y,
+            pos_z: self.pos_z,
+            rot_x: self.rot_x,
+            rot_y: self.rot_y,
+            rot_z: self.rot_z,
+            parent: self.parent.clone(),
+            name: self.name.clone(),
+            id: get_id(),
+            editing_name: false,
+        }
+    }
+}
+
+impl PartialEq for UIGroup {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Display for UIGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 /// This struct is a collection of values which can be assembled to a Light object. Coupled values
 /// such as position x, y and z are separated here to allow for easier manipulation by the ui. 
 #[derive(Debug)]
@@ -1919,6 +5278,12 @@ struct UILight {
     pos_y: f32,
     pos_z: f32,
     spectrum: Rc<RefCell<UISpectrum>>,
+    /// The group the position is relative to, None if it is given in world space.
+    group: Option<Rc<RefCell<UIGroup>>>,
+    /// Which objects the light illuminates, see [LightLinkMode].
+    link_mode: LightLinkMode,
+    /// The ids of the objects the link mode refers to.
+    linked_objects: Vec<u32>,
     name: String,
     editing_name: bool,
     hidden: bool,
@@ -1931,6 +5296,9 @@ impl UILight {
             pos_y,
             pos_z,
             spectrum,
+            group: None,
+            link_mode: LightLinkMode::All,
+            linked_objects: Vec::new(),
             name,
             editing_name: false,
             hidden: false,
@@ -1945,6 +5313,9 @@ impl Clone for UILight {
             pos_y: self.pos_y,
             pos_z: self.pos_z,
             spectrum: self.spectrum.clone(),
+            group: self.group.clone(),
+            link_mode: self.link_mode,
+            linked_objects: self.linked_objects.clone(),
             name: self.name.clone(),
             editing_name: false,
             hidden: self.hidden,
@@ -1954,7 +5325,11 @@ impl Clone for UILight {
 
 /// This struct is a collection of values which can be assembled to a Camera object. Coupled values
 /// such as position x, y and z are separated here to allow for easier manipulation by the ui. 
+#[derive(Serialize, Deserialize)]
 struct UICamera {
+    name: String,
+    #[serde(skip)]
+    editing_name: bool,
     pos_x: f32,
     pos_y: f32,
     pos_z: f32,
@@ -1965,11 +5340,26 @@ struct UICamera {
     up_y: f32,
     up_z: f32,
     fov_deg_y: f32,
+    thick_lens: bool,
+    aperture_radius: f32,
+    focus_distance: f32,
+    lens_thickness: f32,
+    lens_refractive_index: f32,
+    lens_abbe_number: f32,
+    lens_wavelength_bands: usize,
+    /// The distance along the view direction before which nothing is seen, None if nothing is 
+    /// clipped. 
+    near_clip: Option<f32>,
+    /// The distance along the view direction after which nothing is seen, None if nothing is 
+    /// clipped. 
+    far_clip: Option<f32>,
 }
 
 impl Default for UICamera {
     fn default() -> Self {
         Self {
+            name: "Main Camera".to_string(),
+            editing_name: false,
             pos_x: 0.0,
             pos_y: 0.0,
             pos_z: -2.0,
@@ -1980,6 +5370,31 @@ impl Default for UICamera {
             up_y: 1.0,
             up_z: 0.0,
             fov_deg_y: 60.0,
+            thick_lens: false,
+            aperture_radius: 0.05,
+            focus_distance: 3.0,
+            lens_thickness: 0.02,
+            lens_refractive_index: 1.5168,  //BK7 crown glass
+            lens_abbe_number: 64.17,
+            lens_wavelength_bands: 4,
+            near_clip: None,
+            far_clip: None,
+        }
+    }
+}
+
+impl Display for UICamera {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Clone for UICamera {
+    fn clone(&self) -> Self {
+        UICamera {
+            name: self.name.clone(),
+            editing_name: false,
+            ..*self
         }
     }
 }
@@ -1994,7 +5409,26 @@ struct UIObject {
     pos_z: f32,
     material: Rc<RefCell<UIMaterial>>,
     ui_object_type: UIObjectType,
+    /// The spectrum emitted by the surface of the object, None if the object does not glow.
+    emission: Option<Rc<RefCell<UISpectrum>>>,
+    emission_visible_to_camera: bool,
+    emission_visible_in_reflections: bool,
+    /// Whether camera rays hit the object. Unlike hiding, the object still appears in reflections 
+    /// and lights the scene. 
+    visible_to_camera: bool,
+    /// Whether rays reflected by other objects hit the object.
+    visible_in_reflections: bool,
+    /// Whether the object blocks the light of the light sources.
+    casts_shadows: bool,
+    /// The image which colors the surface of the object, None if the material alone does. 
+    texture: Option<Arc<Texture>>,
+    /// The group the position and orientation are relative to, None if they are given in world 
+    /// space.
+    group: Option<Rc<RefCell<UIGroup>>>,
     name: String,
+    /// Identifies the object for light linking. Copies in world space or in the gallery keep the 
+    /// id, duplicates made by the user get a new one. 
+    id: u32,
     editing_name: bool,
     hidden: bool,
 }
@@ -2007,7 +5441,16 @@ impl UIObject {
             pos_z,
             material, 
             ui_object_type,
+            emission: None,
+            emission_visible_to_camera: true,
+            emission_visible_in_reflections: true,
+            visible_to_camera: true,
+            visible_in_reflections: true,
+            casts_shadows: true,
+            texture: None,
+            group: None,
             name,
+            id: get_id(),
             editing_name: false,
             hidden: false,
         }
@@ -2031,11 +5474,31 @@ impl UIObject {
             pos_z: 0.0,
             material,
             ui_object_type: UIObjectType::PlainBox(2.0, 2.0, 2.0),
+            emission: None,
+            emission_visible_to_camera: true,
+            emission_visible_in_reflections: true,
+            visible_to_camera: true,
+            visible_in_reflections: true,
+            casts_shadows: true,
+            texture: None,
+            group: None,
             name: "New Object".to_string(),
+            id: get_id(),
             editing_name: false,
             hidden: false,
         }
     }
+    
+    /// Returns the color identifying the object in the object ID pass. Like a cryptomatte, it is 
+    /// derived from a hash of the name, so that it stays the same across sessions and scene files 
+    /// as long as the object keeps its name. Every channel is at least 0.2, which keeps objects 
+    /// apart from the black of pixels that see nothing. 
+    pub fn id_color(&self) -> Vector3<f32> {
+        //FNV-1a, which unlike the hasher of the standard library is guaranteed never to change
+        let hash = self.name.bytes().fold(0x811c9dc5_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193));
+        let [r, g, b, _] = hash.to_le_bytes();
+        Vector3::new(r, g, b).map(|channel| 0.2 + 0.8 * channel as f32 / 255.0)
+    }
 }
 
 impl Clone for UIObject {
@@ -2045,8 +5508,17 @@ impl Clone for UIObject {
             pos_y: self.pos_y,
             pos_z: self.pos_z,
             material: self.material.clone(),
-            ui_object_type: self.ui_object_type,
+            ui_object_type: self.ui_object_type.clone(),
+            emission: self.emission.clone(),
+            emission_visible_to_camera: self.emission_visible_to_camera,
+            emission_visible_in_reflections: self.emission_visible_in_reflections,
+            visible_to_camera: self.visible_to_camera,
+            visible_in_reflections: self.visible_in_reflections,
+            casts_shadows: self.casts_shadows,
+            texture: self.texture.clone(),
+            group: self.group.clone(),
             name: self.name.clone(),
+            id: self.id,
             editing_name: false,
             hidden: self.hidden,
         }
@@ -2059,6 +5531,12 @@ impl Display for UIObject {
             UIObjectType::PlainBox(_, _, _) => "Plain Box",
             UIObjectType::Sphere(_) => "Sphere",
             UIObjectType::RotatedBox(_, _, _, _, _, _) => "Rotated Box",
+            UIObjectType::Cylinder(_, _, _, _, _) => "Cylinder",
+            UIObjectType::Ellipsoid(_, _, _, _, _, _) => "Ellipsoid",
+            UIObjectType::Disc(_, _, _, _) => "Disc",
+            UIObjectType::Quad(_, _, _, _, _) => "Quad",
+            UIObjectType::Csg(_) => "CSG",
+            UIObjectType::Heightfield(_) => "Heightfield",
         };
         write!(f, "{}", s)
     }
@@ -2066,13 +5544,28 @@ impl Display for UIObject {
 
 /// An enum which differentiates the type of the [UIObjects](UIObject). Different types will be 
 /// assembled to different geometric shapes in the render process.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 enum UIObjectType {
     PlainBox(f32, f32, f32),
     Sphere(f32),
     ///The first three are its stretchedness towards the three principle axes, the other three 
     /// values are its rotation about the three axes. 
     RotatedBox(f32, f32, f32, f32, f32, f32),
+    ///The first two are its radius and height, the other three values are its rotation about the 
+    /// three axes. Unrotated, the axis of the cylinder points along the y axis. 
+    Cylinder(f32, f32, f32, f32, f32),
+    ///The first three are its radii along the three principle axes, the other three values are its 
+    /// rotation about the three axes. 
+    Ellipsoid(f32, f32, f32, f32, f32, f32),
+    ///The first value is its radius, the other three are the x, y and z components of its normal.
+    Disc(f32, f32, f32, f32),
+    ///The first two are its width and depth, the other three are the x, y and z components of its 
+    /// normal. Facing up, the width is measured along the x axis and the depth along the z axis.
+    Quad(f32, f32, f32, f32, f32),
+    ///Two objects combined by a boolean operation into a single one. 
+    Csg(Box<UICsg>),
+    ///A terrain surface, its position being the center of its base. 
+    Heightfield(Box<UIHeightfield>),
 }
 
 impl UIObjectType {
@@ -2087,12 +5580,109 @@ impl UIObjectType {
     fn default_rotated_box() -> Self {
         UIObjectType::RotatedBox(2.0, 2.0, 2.0, 0.0, 0.0, 0.0)
     }
+    
+    fn default_cylinder() -> Self {
+        UIObjectType::Cylinder(1.0, 2.0, 0.0, 0.0, 0.0)
+    }
+    
+    fn default_ellipsoid() -> Self {
+        UIObjectType::Ellipsoid(1.5, 1.0, 1.0, 0.0, 0.0, 0.0)
+    }
+    
+    fn default_disc() -> Self {
+        UIObjectType::Disc(1.0, 0.0, 1.0, 0.0)
+    }
+    
+    fn default_quad() -> Self {
+        UIObjectType::Quad(2.0, 2.0, 0.0, 1.0, 0.0)
+    }
+    
+    ///A box with a spherical hole. 
+    fn default_csg() -> Self {
+        UIObjectType::Csg(Box::new(UICsg {
+            operation: CsgOperation::Difference,
+            first: UICsgPart::new(UIObjectType::default_plain_box()),
+            second: UICsgPart::new(UIObjectType::Sphere(1.3)),
+        }))
+    }
+    
+    ///Rolling hills from noise. 
+    fn default_heightfield() -> Self {
+        let source = HeightfieldSource::Noise { seed: 1, frequency: 4.0, octaves: 5 };
+        let grid = HeightGrid::from_source(&source).expect("generating noise does not fail");
+        UIObjectType::Heightfield(Box::new(UIHeightfield {
+            width: 10.0,
+            depth: 10.0,
+            height: 2.0,
+            source,
+            grid: Arc::new(grid),
+        }))
+    }
+}
+
+/// The extents of a [heightfield](UIObjectType::Heightfield) and where its heights come from. 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "scene_file::HeightfieldEntry", try_from = "scene_file::HeightfieldEntry")]
+struct UIHeightfield {
+    ///The extent along the x axis.
+    width: f32,
+    ///The extent along the z axis.
+    depth: f32,
+    ///The height of the highest possible sample above the base.
+    height: f32,
+    source: HeightfieldSource,
+    ///The heights created from the source, recreated whenever the source changes.
+    grid: Arc<HeightGrid>,
+}
+
+/// The two parts of a [CSG object](UIObjectType::Csg) and how they are combined. 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UICsg {
+    operation: CsgOperation,
+    first: UICsgPart,
+    second: UICsgPart,
+}
+
+/// One part of a [CSG object](UIObjectType::Csg). Its position is relative to the position of the 
+/// CSG object and it takes on the material of the CSG object. Parts may be CSG objects themselves. 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UICsgPart {
+    offset_x: f32,
+    offset_y: f32,
+    offset_z: f32,
+    ui_object_type: UIObjectType,
+}
+
+impl UICsgPart {
+    fn new(ui_object_type: UIObjectType) -> Self {
+        UICsgPart {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            offset_z: 0.0,
+            ui_object_type,
+        }
+    }
 }
 
 struct UIMaterial {
     metallicness: f32,
     roughness: f32,
+    /// The roughness across the tangent, None if it equals the roughness along it (isotropic).
+    bitangent_roughness: Option<f32>,
+    /// The rotation of the tangent around the surface normal in radians. 
+    tangent_rotation: f32,
     spectrum: Rc<RefCell<UISpectrum>>,
+    /// The pattern blending the reflectance towards a second spectrum, None for a uniform surface.
+    pattern: Option<UIPattern>,
+    /// The re-emission of absorbed light at longer wavelengths, None for a material which is not 
+    /// fluorescent. 
+    fluorescence: Option<UIFluorescence>,
+    /// Whether the camera sees through the material, except for the shadows cast onto it. Used 
+    /// for compositing renders onto photographs. Applies to mixes as a whole. 
+    shadow_catcher: bool,
+    /// The two materials this material is a mix of, None for a material of its own. A mix ignores
+    /// all other parameters.
+    mix: Option<UIMaterialMix>,
     name: String,
     id: u32, 
     editing_name: bool,
@@ -2103,7 +5693,13 @@ impl UIMaterial {
         UIMaterial {
             metallicness,
             roughness,
+            bitangent_roughness: None,
+            tangent_rotation: 0.0,
             spectrum,
+            pattern: None,
+            fluorescence: None,
+            shadow_catcher: false,
+            mix: None,
             name,
             id: get_id(),
             editing_name: false,
@@ -2133,7 +5729,13 @@ impl UIMaterial {
         Self {
             metallicness: 0.0,
             roughness: 0.2,
+            bitangent_roughness: None,
+            tangent_rotation: 0.0,
             spectrum,
+            pattern: None,
+            fluorescence: None,
+            shadow_catcher: false,
+            mix: None,
             name: "New Material".to_string(),
             id: get_id(),
             editing_name: false,
@@ -2146,7 +5748,13 @@ impl Clone for UIMaterial {
         Self {
             metallicness: self.metallicness,
             roughness: self.roughness,
+            bitangent_roughness: self.bitangent_roughness,
+            tangent_rotation: self.tangent_rotation,
             spectrum: self.spectrum.clone(),
+            pattern: self.pattern.clone(),
+            fluorescence: self.fluorescence.clone(),
+            shadow_catcher: self.shadow_catcher,
+            mix: self.mix.clone(),
             name: self.name.clone(),
             id: get_id(),
             editing_name: false,
@@ -2154,6 +5762,78 @@ impl Clone for UIMaterial {
     }
 }
 
+/// A procedural pattern of a material together with the second spectrum it blends towards. 
+#[derive(Clone)]
+struct UIPattern {
+    pattern: Pattern,
+    spectrum: Rc<RefCell<UISpectrum>>,
+}
+
+/// A homogeneous medium such as fog filling the whole scene. The spectra give the absorption and 
+/// the scattering coefficient of each wavelength relative to the density, which is the 
+/// coefficient per unit of length a value of 1 stands for. 
+#[derive(Clone)]
+struct UIMedium {
+    absorption: Rc<RefCell<UISpectrum>>,
+    scattering: Rc<RefCell<UISpectrum>>,
+    density: f32,
+    /// Whether the light scattered towards the camera is gathered, making light beams visible.
+    in_scattering: bool,
+}
+
+/// The atmosphere of a planet like the earth around the scene, lit by the sun. The spectrum of 
+/// the sun is its irradiance at the top of the atmosphere. The sun is placed by its elevation above 
+/// the horizon and its azimuth, counted from the z axis towards the x axis. 
+#[derive(Clone)]
+struct UIAtmosphere {
+    sun_spectrum: Rc<RefCell<UISpectrum>>,
+    sun_elevation_deg: f32,
+    sun_azimuth_deg: f32,
+    /// The amount of aerosols relative to a clear day.
+    turbidity: f32,
+    /// The height of the scene above sea level in meters.
+    altitude: f32,
+}
+
+/// What rays leaving the scene without hitting anything see if there is no atmosphere. The spectra 
+/// give the radiance of the background, the gradient blends from the bottom straight down to the 
+/// top straight up. A transparent background is black but leaves the alpha of the image at 0. 
+#[derive(Clone)]
+enum UIBackground {
+    Black,
+    Spectrum(Rc<RefCell<UISpectrum>>),
+    Gradient {top: Rc<RefCell<UISpectrum>>, bottom: Rc<RefCell<UISpectrum>>},
+    Transparent,
+}
+
+impl Display for UIBackground {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UIBackground::Black => write!(f, "Black"),
+            UIBackground::Spectrum(_) => write!(f, "Spectrum"),
+            UIBackground::Gradient {..} => write!(f, "Gradient"),
+            UIBackground::Transparent => write!(f, "Transparent"),
+        }
+    }
+}
+
+/// The fluorescence of a material: the spectrum of the absorbed share which is re-emitted, the 
+/// shape of the re-emitted spectrum and the share of absorbed photons which are re-emitted. 
+#[derive(Clone)]
+struct UIFluorescence {
+    excitation: Rc<RefCell<UISpectrum>>,
+    emission: Rc<RefCell<UISpectrum>>,
+    quantum_yield: f32,
+}
+
+/// The two materials of a mixed material and how much of the second one shows. 
+#[derive(Clone)]
+struct UIMaterialMix {
+    first: Rc<RefCell<UIMaterial>>,
+    second: Rc<RefCell<UIMaterial>>,
+    weight: MixWeight,
+}
+
 impl PartialEq for UIMaterial {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -2166,18 +5846,180 @@ impl Display for UIMaterial {
     }
 }
 
-/// This enum differentiates which tab is currently displayed in the apps main content window.
+/// This enum describes how the rendered spectra are converted into RGB values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum UIColorResponse {
+    CieObserver,    //the human eye as described by the CIE 1964 10° standard observer
+    CieObserver1931,    //the human eye as described by the CIE 1931 2° standard observer
+    TypicalCmos,    //the built in approximation of a consumer camera sensor
+    CustomSensor,   //sensor curves loaded from a CSV file
+    FalseColor(FalseColor), //a single band of the spectra mapped through a color map
+}
+
+impl Display for UIColorResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UIColorResponse::CieObserver => write!(f, "CIE 1964 10° Observer"),
+            UIColorResponse::CieObserver1931 => write!(f, "CIE 1931 2° Observer"),
+            UIColorResponse::TypicalCmos => write!(f, "Typical CMOS Sensor"),
+            UIColorResponse::CustomSensor => write!(f, "Custom Sensor (CSV)"),
+            UIColorResponse::FalseColor(_) => write!(f, "False Color (Single Band)"),
+        }
+    }
+}
+
+/// The resolution an image is rendered at relative to its output size. Lower ones render quick 
+/// previews, which are enlarged to the output size, higher ones supersample the image, which is 
+/// averaged down to it. 
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+enum RenderScale {
+    Quarter,
+    Half,
+    #[default]
+    Full,
+    Double,
+}
+
+impl RenderScale {
+    const ALL: [RenderScale; 4] = [RenderScale::Quarter, RenderScale::Half, RenderScale::Full, RenderScale::Double];
+    
+    /// The factor the width and height are scaled by. 
+    fn factor(&self) -> f32 {
+        match self {
+            RenderScale::Quarter => 0.25,
+            RenderScale::Half => 0.5,
+            RenderScale::Full => 1.0,
+            RenderScale::Double => 2.0,
+        }
+    }
+}
+
+impl Display for RenderScale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0} %", self.factor() * 100.0)
+    }
+}
+
+/// The buffers of a render the display tab can show. 
 #[derive(Debug, Clone, Copy, PartialEq)]
+enum DisplayedBuffer {
+    Image,
+    /// The relative error of every pixel, see [Accumulation::noise_heatmap]. 
+    NoiseHeatmap,
+    /// The shading normal every pixel sees first, see [custom_image::encode_normal_image]. 
+    Normals,
+    /// The distance to what every pixel sees first, see [custom_image::encode_depth_image]. 
+    Depth,
+    /// The reflectance every pixel sees first, see [CustomImage::get_albedo_image](custom_image::CustomImage::get_albedo_image). 
+    Albedo,
+    /// The id color of the object every pixel sees first, see [UIObject::id_color]. 
+    ObjectIds,
+    /// The light of the light source with the given index, see 
+    /// [CustomImage::get_light_pass_images](custom_image::CustomImage::get_light_pass_images). 
+    LightPass(usize),
+}
+
+impl Display for DisplayedBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayedBuffer::Image => write!(f, "Image"),
+            DisplayedBuffer::NoiseHeatmap => write!(f, "Noise heatmap"),
+            DisplayedBuffer::Normals => write!(f, "Normals"),
+            DisplayedBuffer::Depth => write!(f, "Depth"),
+            DisplayedBuffer::Albedo => write!(f, "Albedo"),
+            DisplayedBuffer::ObjectIds => write!(f, "Object IDs"),
+            DisplayedBuffer::LightPass(index) => write!(f, "Light {}", index + 1),
+        }
+    }
+}
+
+/// This enum differentiates the tabs of the apps main content window. The tabs can be rearranged
+/// into dockable panels, allowing several of them to be visible at once.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum UiTab {
     Settings,   //pre render settings such as width, height or number of frames
     Objects,    //3D models and lights defined in the scene
     SpectraAndMaterials,    //reflectance and light spectra as well as object materials defined here
+    SpectrumComparison,     //several spectra plotted on top of each other
     Display,    //the screen ultimately displaying the result 
+    Gallery,    //the renders completed in this session together with their settings
+    Script,     //a script creating spectra, materials, objects and lights, only with the scripting feature
+}
+
+impl UiTab {
+    #[cfg(not(feature = "scripting"))]
+    const ALL: [UiTab; 6] = [UiTab::Settings, UiTab::Objects, UiTab::SpectraAndMaterials, 
+                             UiTab::SpectrumComparison, UiTab::Display, UiTab::Gallery];
+    #[cfg(feature = "scripting")]
+    const ALL: [UiTab; 7] = [UiTab::Settings, UiTab::Objects, UiTab::SpectraAndMaterials, 
+                             UiTab::SpectrumComparison, UiTab::Display, UiTab::Gallery, UiTab::Script];
+}
+
+/// Connects the [DockArea] of the main content window with the [App], whose tabs it displays.
+struct AppTabViewer<'a> {
+    app: &'a mut App,
+}
+
+impl TabViewer for AppTabViewer<'_> {
+    type Tab = UiTab;
+
+    fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+        match tab {
+            UiTab::Settings => "Settings".into(),
+            UiTab::Objects => {
+                //blinks red if the scene can not be rendered
+                if !(self.app.check_lights_legality() && self.app.check_objects_legality()) && is_time_even() {
+                    egui::RichText::new("Objects").color(Color32::RED).into()
+                } else {
+                    "Objects".into()
+                }
+            }
+            UiTab::SpectraAndMaterials => "Spectra and Materials".into(),
+            UiTab::SpectrumComparison => "Spectrum Comparison".into(),
+            UiTab::Display => "Display".into(),
+            UiTab::Gallery => "Gallery".into(),
+            UiTab::Script => "Script".into(),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, tab: &mut Self::Tab) {
+        self.app.display_tab_content(ui, *tab);
+    }
+
+    fn scroll_bars(&self, tab: &Self::Tab) -> [bool; 2] {
+        //all tabs but the settings bring their own scroll areas
+        match tab {
+            UiTab::Settings => [false, true],
+            _ => [false, false],
+        }
+    }
+}
+
+/// Returns the tab layout used on the first start: all tabs stacked in a single panel.
+fn default_dock_state() -> DockState<UiTab> {
+    DockState::new(UiTab::ALL.to_vec())
+}
+
+/// Adapts the stored tab layout of the last session to the tabs of this build. Tabs this build 
+/// does not have, like the script tab of a build with the scripting feature, are removed. Returns 
+/// None if the layout lacks tabs of this build, so that the default layout is used instead. 
+fn restore_dock_state(mut dock_state: DockState<UiTab>) -> Option<DockState<UiTab>> {
+    dock_state.retain_tabs(|tab| UiTab::ALL.contains(tab));
+    //layouts of older versions may lack tabs added since
+    UiTab::ALL.iter().all(|tab| dock_state.find_tab(tab).is_some()).then_some(dock_state)
+}
+
+/// The buttons of the tutorial window. 
+enum TutorialAction {
+    Render,
+    NextStep,
+    Restart,
 }
 
 /// This enum describes a number of actions which have to be taken after the UI is displayed such 
 /// as deleting objects. 
 enum AfterUIActions {
+    DeleteGroup(usize),
     DeleteLight(usize),
     DeleteObject(usize),
     SaveSelectedSpectrum(usize),
@@ -2188,6 +6030,8 @@ enum AfterUIActions {
     CopyObject(usize),
     DeleteMaterial(usize),
     CopyMaterial(usize),
+    CopyCamera(usize),
+    DeleteCamera(usize),
 }
 
 /// An enum to send messages from the UI thread over to the currently rendering thread.
@@ -2271,19 +6115,762 @@ fn display_name_with_edit(ui: &mut Ui, name: &mut String, backup: &String, editi
     display_edit_name_button(ui, editing);
 }
 
+/// Displays a [ComboBox] which lists all groups of the scene hierarchy and no group at all. The 
+/// excluded group and the groups nested in it are left out, so that a group can not become its 
+/// own parent. 
+fn display_combobox_with_group_list(groups: &[Rc<RefCell<UIGroup>>], ui: &mut Ui, id_salt: String, tool_tip: &str,
+                                    current_group: &mut Option<Rc<RefCell<UIGroup>>>, excluded: Option<&Rc<RefCell<UIGroup>>>) {
+    let selected_text = match current_group {
+        Some(group) => group.borrow().to_string(),
+        None => "None".to_string(),
+    };
+    ComboBox::new(id_salt, "")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(current_group, None, "None");
+            for group in groups {
+                if excluded.is_some_and(|excluded| hierarchy::is_nested_in(group, excluded)) {
+                    continue;
+                }
+                ui.selectable_value(current_group, Some(group.clone()), group.borrow().to_string());
+            }
+        }).response.on_hover_text(tool_tip);
+}
+
+/// Displays a [ComboBox] which lists the materials which may be part of the mixed material. 
+/// Materials which would mix in the mixed material itself are left out, no mix may contain itself. 
+fn display_combobox_with_mix_material_list(materials: &[Rc<RefCell<UIMaterial>>], ui: &mut Ui, id_salt: String, tool_tip: &str,
+                                           current_material: &mut Rc<RefCell<UIMaterial>>, mixed: &Rc<RefCell<UIMaterial>>) {
+    let selected_text = current_material.borrow().to_string();
+    ComboBox::new(id_salt, "")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            for material in materials {
+                if mixes_in(material, mixed) {
+                    continue;
+                }
+                ui.selectable_value(current_material, material.clone(), material.borrow().to_string());
+            }
+        }).response.on_hover_text(tool_tip);
+}
+
+/// Returns whether the candidate is the given material or contains it in its mix, at any depth. 
+/// The material itself is never borrowed, so it may be borrowed mutably while calling this. 
+fn mixes_in(candidate: &Rc<RefCell<UIMaterial>>, material: &Rc<RefCell<UIMaterial>>) -> bool {
+    if Rc::ptr_eq(candidate, material) {
+        return true;
+    }
+    match &candidate.borrow().mix {
+        Some(mix) => mixes_in(&mix.first, material) || mixes_in(&mix.second, material),
+        None => false,
+    }
+}
+
+/// Displays a [ComboBox] to choose the type of an object. Choosing another type replaces the 
+/// object type with the default of the chosen type. 
+fn display_object_type_combobox(ui: &mut Ui, id_salt: impl Hash, object_type: &mut UIObjectType) {
+    #[derive(PartialEq, Clone, Copy, Debug)]
+    enum Type {
+        PlainBox,
+        Sphere,
+        RotatedBox,
+        Cylinder,
+        Ellipsoid,
+        Disc,
+        Quad,
+        Csg,
+        Heightfield,
+    }
+    impl Display for Type {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let s = match self {
+                Type::PlainBox => "PlainBox",
+                Type::Sphere => "Sphere",
+                Type::RotatedBox => "RotatedBox",
+                Type::Cylinder => "Cylinder",
+                Type::Ellipsoid => "Ellipsoid",
+                Type::Disc => "Disc",
+                Type::Quad => "Quad",
+                Type::Csg => "CSG",
+                Type::Heightfield => "Heightfield",
+            };
+            write!(f, "{s}")
+        }
+    }
+    let mut selected = match *object_type {
+        UIObjectType::PlainBox(_, _, _) => Type::PlainBox,
+        UIObjectType::Sphere(_) => Type::Sphere,
+        UIObjectType::RotatedBox(_, _, _, _, _, _) => Type::RotatedBox,
+        UIObjectType::Cylinder(_, _, _, _, _) => Type::Cylinder,
+        UIObjectType::Ellipsoid(_, _, _, _, _, _) => Type::Ellipsoid,
+        UIObjectType::Disc(_, _, _, _) => Type::Disc,
+        UIObjectType::Quad(_, _, _, _, _) => Type::Quad,
+        UIObjectType::Csg(_) => Type::Csg,
+        UIObjectType::Heightfield(_) => Type::Heightfield,
+    };
+    ComboBox::new(id_salt, "Type")
+        .selected_text(format!("{}", selected))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut selected, Type::PlainBox, "Plain Box").on_hover_text(OBJECT_TYPE_PLAIN_BOX_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Sphere, "Sphere").on_hover_text(OBJECT_TYPE_SPHERE_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::RotatedBox, "Rotated Box").on_hover_text(OBJECT_TYPE_ROTATED_BOX_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Cylinder, "Cylinder").on_hover_text(OBJECT_TYPE_CYLINDER_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Ellipsoid, "Ellipsoid").on_hover_text(OBJECT_TYPE_ELLIPSOID_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Disc, "Disc").on_hover_text(OBJECT_TYPE_DISC_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Quad, "Quad").on_hover_text(OBJECT_TYPE_QUAD_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Csg, "CSG").on_hover_text(OBJECT_TYPE_CSG_TOOLTIP);
+            ui.selectable_value(&mut selected, Type::Heightfield, "Heightfield").on_hover_text(OBJECT_TYPE_HEIGHTFIELD_TOOLTIP);
+        }).response.on_hover_text(OBJECT_TYPE_TOOLTIP);
+    let same = selected == match *object_type {
+        UIObjectType::PlainBox(_, _, _) => Type::PlainBox,
+        UIObjectType::Sphere(_) => Type::Sphere,
+        UIObjectType::RotatedBox(_, _, _, _, _, _) => Type::RotatedBox,
+        UIObjectType::Cylinder(_, _, _, _, _) => Type::Cylinder,
+        UIObjectType::Ellipsoid(_, _, _, _, _, _) => Type::Ellipsoid,
+        UIObjectType::Disc(_, _, _, _) => Type::Disc,
+        UIObjectType::Quad(_, _, _, _, _) => Type::Quad,
+        UIObjectType::Csg(_) => Type::Csg,
+        UIObjectType::Heightfield(_) => Type::Heightfield,
+    };
+    if !same {
+        *object_type = match selected {
+            Type::PlainBox => UIObjectType::default_plain_box(),
+            Type::Sphere => UIObjectType::default_sphere(),
+            Type::RotatedBox => UIObjectType::default_rotated_box(),
+            Type::Cylinder => UIObjectType::default_cylinder(),
+            Type::Ellipsoid => UIObjectType::default_ellipsoid(),
+            Type::Disc => UIObjectType::default_disc(),
+            Type::Quad => UIObjectType::default_quad(),
+            Type::Csg => UIObjectType::default_csg(),
+            Type::Heightfield => UIObjectType::default_heightfield(),
+        }
+    }
+}
+
+/// Displays the settings specific to the given object type, such as the dimensions of a box. 
+/// The id salt has to be unique per displayed object type. 
+fn display_object_type_settings(ui: &mut Ui, id_salt: &str, object_type: &mut UIObjectType) {
+    match *object_type {
+        UIObjectType::PlainBox(x_length, y_length, z_length) => {
+            //dimensions
+            ui.horizontal_top(|ui| {
+                let mut dim_x_string = x_length.to_string();
+                let mut dim_y_string = y_length.to_string();
+                let mut dim_z_string = z_length.to_string();
+                ui.label("Object Dimensions: (x:").on_hover_text(OBJECT_PLAIN_BOX_DIMENSIONS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_x_string));
+                ui.label("y:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_y_string));
+                ui.label("z:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_z_string));
+                ui.label(")");
+
+                if dim_x_string.parse::<f32>().is_ok() {
+                    let new_length_x = dim_x_string.parse::<f32>().unwrap();
+                    if new_length_x > 0.0 && new_length_x != x_length {
+                        *object_type = UIObjectType::PlainBox(new_length_x, y_length, z_length);
+                    }
+                }
+                if dim_y_string.parse::<f32>().is_ok() {
+                    let new_length_y = dim_y_string.parse::<f32>().unwrap();
+                    if new_length_y > 0.0 && new_length_y != y_length {
+                        *object_type = UIObjectType::PlainBox(x_length, new_length_y, z_length);
+                    }
+                }
+                if dim_z_string.parse::<f32>().is_ok() {
+                    let new_length_z = dim_z_string.parse::<f32>().unwrap();
+                    if new_length_z > 0.0 && new_length_z != z_length {
+                        *object_type = UIObjectType::PlainBox(x_length, y_length, new_length_z);
+                    }
+                }
+            });
+        }
+        UIObjectType::Sphere(radius) => {
+            //radius
+            ui.horizontal_top(|ui| {
+                let mut radius_string = radius.to_string();
+                ui.label("Radius: ").on_hover_text(OBJECT_SPHERE_RADIUS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_string));
+
+                if radius_string.parse::<f32>().is_ok() {
+                    let new_radius = radius_string.parse::<f32>().unwrap();
+                    if new_radius > 0.0 {
+                        *object_type = UIObjectType::Sphere(new_radius);
+                    }
+                }
+            });
+        }
+        UIObjectType::RotatedBox(x_length, y_length, z_length, 
+                                 x_rotation, y_rotation, z_rotation) => {
+            //dimensions
+            ui.horizontal_top(|ui| {
+                let mut dim_x_string = x_length.to_string();
+                let mut dim_y_string = y_length.to_string();
+                let mut dim_z_string = z_length.to_string();
+                ui.label("Object Dimensions: (x:").on_hover_text(OBJECT_ROTATED_BOX_DIMENSIONS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_x_string));
+                ui.label("y:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_y_string));
+                ui.label("z:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut dim_z_string));
+                ui.label(")");
+
+                if dim_x_string.parse::<f32>().is_ok() {
+                    let new_length_x = dim_x_string.parse::<f32>().unwrap();
+                    if new_length_x > 0.0 && new_length_x != x_length {
+                        *object_type = UIObjectType::RotatedBox(new_length_x, y_length, z_length, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+                if dim_y_string.parse::<f32>().is_ok() {
+                    let new_length_y = dim_y_string.parse::<f32>().unwrap();
+                    if new_length_y > 0.0 && new_length_y != y_length {
+                        *object_type = UIObjectType::RotatedBox(x_length, new_length_y, z_length, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+                if dim_z_string.parse::<f32>().is_ok() {
+                    let new_length_z = dim_z_string.parse::<f32>().unwrap();
+                    if new_length_z > 0.0 && new_length_z != z_length {
+                        *object_type = UIObjectType::RotatedBox(x_length, y_length, new_length_z, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+            });
+
+            //rotation
+            ui.horizontal_top(|ui| {
+                let mut rot_x_string = x_rotation.to_string();
+                let mut rot_y_string = y_rotation.to_string();
+                let mut rot_z_string = z_rotation.to_string();
+                ui.label("Object Rotation: (x:").on_hover_text(OBJECT_ROTATED_BOX_ANGLES_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_x_string));
+                ui.label("y:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_y_string));
+                ui.label("z:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_z_string));
+                ui.label(")");
+
+                if rot_x_string.parse::<f32>().is_ok() {
+                    let new_rotation_x = rot_x_string.parse::<f32>().unwrap();
+                    if new_rotation_x != x_rotation {
+                        *object_type = UIObjectType::RotatedBox(x_length, y_length, z_length, new_rotation_x, y_rotation, z_rotation);
+                    }
+                }
+                if rot_y_string.parse::<f32>().is_ok() {
+                    let new_rotation_y = rot_y_string.parse::<f32>().unwrap();
+                    if new_rotation_y != y_rotation {
+                        *object_type = UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, new_rotation_y, z_rotation);
+                    }
+                }
+                if rot_z_string.parse::<f32>().is_ok() {
+                    let new_rotation_z = rot_z_string.parse::<f32>().unwrap();
+                    if new_rotation_z != z_rotation {
+                        *object_type = UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, new_rotation_z);
+                    }
+                }
+            });
+        }
+        UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+            //dimensions
+            ui.horizontal_top(|ui| {
+                let mut radius_string = radius.to_string();
+                let mut height_string = height.to_string();
+                ui.label("Radius: ").on_hover_text(OBJECT_CYLINDER_DIMENSIONS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_string));
+                ui.label("Height: ").on_hover_text(OBJECT_CYLINDER_DIMENSIONS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut height_string));
+
+                if radius_string.parse::<f32>().is_ok() {
+                    let new_radius = radius_string.parse::<f32>().unwrap();
+                    if new_radius > 0.0 && new_radius != radius {
+                        *object_type = UIObjectType::Cylinder(new_radius, height, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+                if height_string.parse::<f32>().is_ok() {
+                    let new_height = height_string.parse::<f32>().unwrap();
+                    if new_height > 0.0 && new_height != height {
+                        *object_type = UIObjectType::Cylinder(radius, new_height, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+            });
+
+            //rotation
+            ui.horizontal_top(|ui| {
+                let mut rot_x_string = x_rotation.to_string();
+                let mut rot_y_string = y_rotation.to_string();
+                let mut rot_z_string = z_rotation.to_string();
+                ui.label("Object Rotation: (x:").on_hover_text(OBJECT_CYLINDER_ANGLES_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_x_string));
+                ui.label("y:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_y_string));
+                ui.label("z:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_z_string));
+                ui.label(")");
+
+                if rot_x_string.parse::<f32>().is_ok() {
+                    let new_rotation_x = rot_x_string.parse::<f32>().unwrap();
+                    if new_rotation_x != x_rotation {
+                        *object_type = UIObjectType::Cylinder(radius, height, new_rotation_x, y_rotation, z_rotation);
+                    }
+                }
+                if rot_y_string.parse::<f32>().is_ok() {
+                    let new_rotation_y = rot_y_string.parse::<f32>().unwrap();
+                    if new_rotation_y != y_rotation {
+                        *object_type = UIObjectType::Cylinder(radius, height, x_rotation, new_rotation_y, z_rotation);
+                    }
+                }
+                if rot_z_string.parse::<f32>().is_ok() {
+                    let new_rotation_z = rot_z_string.parse::<f32>().unwrap();
+                    if new_rotation_z != z_rotation {
+                        *object_type = UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, new_rotation_z);
+                    }
+                }
+            });
+        }
+        UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+            //radii
+            ui.horizontal_top(|ui| {
+                let mut radius_x_string = x_radius.to_string();
+                let mut radius_y_string = y_radius.to_string();
+                let mut radius_z_string = z_radius.to_string();
+                ui.label("Radii: (x:").on_hover_text(OBJECT_ELLIPSOID_RADII_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_x_string));
+                ui.label("y:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_y_string));
+                ui.label("z:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_z_string));
+                ui.label(")");
+
+                if let Ok(new_radius_x) = radius_x_string.parse::<f32>() {
+                    if new_radius_x > 0.0 && new_radius_x != x_radius {
+                        *object_type = UIObjectType::Ellipsoid(new_radius_x, y_radius, z_radius, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+                if let Ok(new_radius_y) = radius_y_string.parse::<f32>() {
+                    if new_radius_y > 0.0 && new_radius_y != y_radius {
+                        *object_type = UIObjectType::Ellipsoid(x_radius, new_radius_y, z_radius, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+                if let Ok(new_radius_z) = radius_z_string.parse::<f32>() {
+                    if new_radius_z > 0.0 && new_radius_z != z_radius {
+                        *object_type = UIObjectType::Ellipsoid(x_radius, y_radius, new_radius_z, x_rotation, y_rotation, z_rotation);
+                    }
+                }
+            });
+
+            //rotation
+            ui.horizontal_top(|ui| {
+                let mut rot_x_string = x_rotation.to_string();
+                let mut rot_y_string = y_rotation.to_string();
+                let mut rot_z_string = z_rotation.to_string();
+                ui.label("Object Rotation: (x:").on_hover_text(OBJECT_ELLIPSOID_ANGLES_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_x_string));
+                ui.label("y:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_y_string));
+                ui.label("z:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut rot_z_string));
+                ui.label(")");
+
+                if let Ok(new_rotation_x) = rot_x_string.parse::<f32>() {
+                    if new_rotation_x != x_rotation {
+                        *object_type = UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, new_rotation_x, y_rotation, z_rotation);
+                    }
+                }
+                if let Ok(new_rotation_y) = rot_y_string.parse::<f32>() {
+                    if new_rotation_y != y_rotation {
+                        *object_type = UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, new_rotation_y, z_rotation);
+                    }
+                }
+                if let Ok(new_rotation_z) = rot_z_string.parse::<f32>() {
+                    if new_rotation_z != z_rotation {
+                        *object_type = UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, new_rotation_z);
+                    }
+                }
+            });
+        }
+        UIObjectType::Disc(radius, normal_x, normal_y, normal_z) => {
+            //radius
+            ui.horizontal_top(|ui| {
+                let mut radius_string = radius.to_string();
+                ui.label("Radius: ").on_hover_text(OBJECT_DISC_RADIUS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut radius_string));
+
+                if radius_string.parse::<f32>().is_ok() {
+                    let new_radius = radius_string.parse::<f32>().unwrap();
+                    if new_radius > 0.0 && new_radius != radius {
+                        *object_type = UIObjectType::Disc(new_radius, normal_x, normal_y, normal_z);
+                    }
+                }
+            });
+
+            //normal
+            if let Some((new_x, new_y, new_z)) = display_flat_object_normal(ui, (normal_x, normal_y, normal_z)) {
+                *object_type = UIObjectType::Disc(radius, new_x, new_y, new_z);
+            }
+        }
+        UIObjectType::Quad(width, depth, normal_x, normal_y, normal_z) => {
+            //extents
+            ui.horizontal_top(|ui| {
+                let mut width_string = width.to_string();
+                let mut depth_string = depth.to_string();
+                ui.label("Extents: (width:").on_hover_text(OBJECT_QUAD_EXTENTS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut width_string));
+                ui.label("depth:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut depth_string));
+                ui.label(")");
+
+                if width_string.parse::<f32>().is_ok() {
+                    let new_width = width_string.parse::<f32>().unwrap();
+                    if new_width > 0.0 && new_width != width {
+                        *object_type = UIObjectType::Quad(new_width, depth, normal_x, normal_y, normal_z);
+                    }
+                }
+                if depth_string.parse::<f32>().is_ok() {
+                    let new_depth = depth_string.parse::<f32>().unwrap();
+                    if new_depth > 0.0 && new_depth != depth {
+                        *object_type = UIObjectType::Quad(width, new_depth, normal_x, normal_y, normal_z);
+                    }
+                }
+            });
+
+            //normal
+            if let Some((new_x, new_y, new_z)) = display_flat_object_normal(ui, (normal_x, normal_y, normal_z)) {
+                *object_type = UIObjectType::Quad(width, depth, new_x, new_y, new_z);
+            }
+        }
+        UIObjectType::Csg(ref mut csg) => {
+            //operation
+            ComboBox::new(format!("{id_salt} csg operation"), "Operation")
+                .selected_text(format!("{}", csg.operation))
+                .show_ui(ui, |ui| {
+                    for operation in CsgOperation::ALL {
+                        ui.selectable_value(&mut csg.operation, operation, operation.to_string());
+                    }
+                }).response.on_hover_text(OBJECT_CSG_OPERATION_TOOLTIP);
+            
+            //the two parts, each with its own type and offset
+            for (name, part) in [("First part", &mut csg.first), ("Second part", &mut csg.second)] {
+                let part_salt = format!("{id_salt} {name}");
+                ui.collapsing(name, |ui| {
+                    display_object_type_combobox(ui, &part_salt, &mut part.ui_object_type);
+                    ui.horizontal_top(|ui| {
+                        let mut offset_x_string = part.offset_x.to_string();
+                        let mut offset_y_string = part.offset_y.to_string();
+                        let mut offset_z_string = part.offset_z.to_string();
+                        ui.label("Offset: (x:").on_hover_text(OBJECT_CSG_OFFSET_TOOLTIP);
+                        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut offset_x_string));
+                        ui.label("y:");
+                        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut offset_y_string));
+                        ui.label("z:");
+                        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut offset_z_string));
+                        ui.label(")");
+                        
+                        if let Ok(new_x) = offset_x_string.parse::<f32>() {
+                            part.offset_x = new_x;
+                        }
+                        if let Ok(new_y) = offset_y_string.parse::<f32>() {
+                            part.offset_y = new_y;
+                        }
+                        if let Ok(new_z) = offset_z_string.parse::<f32>() {
+                            part.offset_z = new_z;
+                        }
+                    });
+                    display_object_type_settings(ui, &part_salt, &mut part.ui_object_type);
+                });
+            }
+        }
+        UIObjectType::Heightfield(ref mut heightfield) => {
+            //extents
+            ui.horizontal_top(|ui| {
+                let mut width_string = heightfield.width.to_string();
+                let mut depth_string = heightfield.depth.to_string();
+                let mut height_string = heightfield.height.to_string();
+                ui.label("Extents: (width:").on_hover_text(OBJECT_HEIGHTFIELD_EXTENTS_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut width_string));
+                ui.label("depth:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut depth_string));
+                ui.label("height:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut height_string));
+                ui.label(")");
+                
+                for (string, value) in [(width_string, &mut heightfield.width), (depth_string, &mut heightfield.depth),
+                                        (height_string, &mut heightfield.height)] {
+                    if let Ok(new_value) = string.parse::<f32>() {
+                        if new_value > 0.0 {
+                            *value = new_value;
+                        }
+                    }
+                }
+            });
+            
+            //source of the heights
+            let mut new_source = None;
+            ui.horizontal_top(|ui| {
+                match heightfield.source {
+                    HeightfieldSource::Noise { seed, frequency, octaves } => {
+                        let mut seed_string = seed.to_string();
+                        let mut frequency_string = frequency.to_string();
+                        let mut octaves_string = octaves.to_string();
+                        ui.label("Noise: (seed:").on_hover_text(OBJECT_HEIGHTFIELD_NOISE_TOOLTIP);
+                        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut seed_string));
+                        ui.label("frequency:");
+                        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut frequency_string));
+                        ui.label("octaves:");
+                        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut octaves_string));
+                        ui.label(")");
+                        
+                        let new_seed = seed_string.parse::<u32>().unwrap_or(seed);
+                        let new_frequency = frequency_string.parse::<f32>().ok()
+                            .filter(|f| f.is_finite() && *f > 0.0).unwrap_or(frequency);
+                        let new_octaves = octaves_string.parse::<u32>().ok()
+                            .filter(|o| (1..=HEIGHTFIELD_OCTAVES_MAX).contains(o)).unwrap_or(octaves);
+                        if (new_seed, new_frequency, new_octaves) != (seed, frequency, octaves) {
+                            new_source = Some(HeightfieldSource::Noise {
+                                seed: new_seed, frequency: new_frequency, octaves: new_octaves });
+                        }
+                    }
+                    HeightfieldSource::Image(ref path) => {
+                        ui.label(format!("Image: {}", path.display())).on_hover_text(OBJECT_HEIGHTFIELD_IMAGE_TOOLTIP);
+                        if ui.button("Use Noise").clicked() {
+                            new_source = Some(HeightfieldSource::Noise { seed: 1, frequency: 4.0, octaves: 5 });
+                        }
+                    }
+                }
+                if ui.button("Load Image").on_hover_text(OBJECT_HEIGHTFIELD_IMAGE_TOOLTIP).clicked() {
+                    let dialog = rfd::FileDialog::new()
+                        .add_filter("Image", &["png", "jpg", "jpeg", "bmp", "tiff", "tif"])
+                        .pick_file();
+                    if let Some(path) = dialog {
+                        new_source = Some(HeightfieldSource::Image(path));
+                    }
+                }
+            });
+            if let Some(source) = new_source {
+                match HeightGrid::from_source(&source) {
+                    Ok(grid) => {
+                        heightfield.grid = Arc::new(grid);
+                        heightfield.source = source;
+                    }
+                    Err(e) => warn!("Error creating the heightfield: {}", e.error),
+                }
+            }
+        }
+    }
+}
+
+/// Displays the parameters of the pattern of a material. Only valid values are accepted, sizes 
+/// have to be positive. 
+fn display_pattern_settings(ui: &mut Ui, pattern: &mut Pattern) {
+    ui.horizontal_top(|ui| {
+        match *pattern {
+            Pattern::Checker { size } => {
+                let mut size_string = size.to_string();
+                ui.label("Checker: (size:").on_hover_text(MATERIAL_PATTERN_CHECKER_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut size_string));
+                ui.label(")");
+                
+                if let Ok(new_size) = size_string.parse::<f32>() {
+                    if new_size > 0.0 {
+                        *pattern = Pattern::Checker { size: new_size };
+                    }
+                }
+            }
+            Pattern::Noise { size, octaves, seed } => {
+                let mut size_string = size.to_string();
+                let mut octaves_string = octaves.to_string();
+                let mut seed_string = seed.to_string();
+                ui.label("Noise: (size:").on_hover_text(MATERIAL_PATTERN_NOISE_TOOLTIP);
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut size_string));
+                ui.label("octaves:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut octaves_string));
+                ui.label("seed:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut seed_string));
+                ui.label(")");
+                
+                let new_size = size_string.parse::<f32>().ok().filter(|size| *size > 0.0).unwrap_or(size);
+                let new_octaves = octaves_string.parse::<u32>().ok()
+                    .filter(|octaves| (1..=PATTERN_NOISE_OCTAVES_MAX).contains(octaves))
+                    .unwrap_or(octaves);
+                let new_seed = seed_string.parse::<u32>().unwrap_or(seed);
+                *pattern = Pattern::Noise { size: new_size, octaves: new_octaves, seed: new_seed };
+            }
+            Pattern::Gradient { axis, start, end } => {
+                let mut new_axis = axis;
+                let mut start_string = start.to_string();
+                let mut end_string = end.to_string();
+                ui.label("Gradient: (axis:").on_hover_text(MATERIAL_PATTERN_GRADIENT_TOOLTIP);
+                ComboBox::new(ui.next_auto_id(), "")
+                    .selected_text(axis.to_string())
+                    .width(40.0)
+                    .show_ui(ui, |ui| {
+                        for candidate in Axis::ALL {
+                            ui.selectable_value(&mut new_axis, candidate, candidate.to_string());
+                        }
+                    });
+                ui.label("start:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut start_string));
+                ui.label("end:");
+                ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut end_string));
+                ui.label(")");
+                
+                let new_start = start_string.parse::<f32>().unwrap_or(start);
+                let new_end = end_string.parse::<f32>().unwrap_or(end);
+                *pattern = Pattern::Gradient { axis: new_axis, start: new_start, end: new_end };
+            }
+        }
+    });
+}
+
+/// Displays the normal of a flat object in three text fields. Returns the new normal if it was 
+/// changed to another valid direction, a normal of length zero is not accepted. 
+fn display_flat_object_normal(ui: &mut Ui, (x, y, z): (f32, f32, f32)) -> Option<(f32, f32, f32)> {
+    ui.horizontal_top(|ui| {
+        let mut x_string = x.to_string();
+        let mut y_string = y.to_string();
+        let mut z_string = z.to_string();
+        ui.label("Normal: (x:").on_hover_text(OBJECT_FLAT_NORMAL_TOOLTIP);
+        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut x_string));
+        ui.label("y:");
+        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut y_string));
+        ui.label("z:");
+        ui.add_sized([80.0, 18.0], TextEdit::singleline(&mut z_string));
+        ui.label(")");
+
+        let new_normal = (x_string.parse::<f32>().unwrap_or(x), y_string.parse::<f32>().unwrap_or(y), 
+                          z_string.parse::<f32>().unwrap_or(z));
+        let valid = [new_normal.0, new_normal.1, new_normal.2].iter().all(|c| c.is_finite()) 
+            && new_normal != (0.0, 0.0, 0.0);
+        (valid && new_normal != (x, y, z)).then_some(new_normal)
+    }).inner
+}
+
+/// Converts a [DynamicImage] into an [egui::ColorImage] which can be loaded as a texture. 
+fn dynamic_image_to_color_image(img: &DynamicImage) -> egui::ColorImage {
+    let rgb_img = img.to_rgba8();
+    let size = [rgb_img.width() as usize, rgb_img.height() as usize];
+    egui::ColorImage::from_rgba_unmultiplied(size, rgb_img.as_raw())
+}
+
+/// Displays a warning badge if the given linear RGB color lies outside of the sRGB gamut, together 
+/// with the hard clipped and the gamut mapped color side by side. Below, the chromaticity of the 
+/// color is marked in a CIE xy diagram alongside the blackbody locus. 
+fn display_gamut_indicator(ui: &mut Ui, rgb: (f32, f32, f32), xy: Option<(f32, f32)>) {
+    let out_of_gamut = gamut::is_out_of_gamut(rgb);
+    if out_of_gamut {
+        ui.horizontal_top(|ui| {
+            ui.colored_label(Color32::DARK_RED, "⚠ Out of sRGB gamut").on_hover_text(OUT_OF_GAMUT_TOOLTIP);
+            
+            let clipped = gamut::clip(rgb);
+            let compressed = gamut::compress(rgb);
+            for (label, color, tool_tip) in [("Clipped", clipped, CLIPPED_COLOR_TOOLTIP), 
+                                             ("Gamut Mapped", compressed, GAMUT_MAPPED_COLOR_TOOLTIP)] {
+                //normalized to the brightest component to make dim colors comparable
+                let max = color.0.max(color.1).max(color.2).max(f32::EPSILON);
+                let r_byte = (color.0 / max * 255.0 + 0.5) as u8;
+                let g_byte = (color.1 / max * 255.0 + 0.5) as u8;
+                let b_byte = (color.2 / max * 255.0 + 0.5) as u8;
+                
+                ui.vertical(|ui| {
+                    egui::Frame::NONE.fill(Color32::from_rgb(r_byte, g_byte, b_byte))
+                        .stroke(egui::Stroke::new(1.0, Color32::LIGHT_GRAY))
+                        .show(ui, |ui| {
+                            ui.set_min_size(Vec2::new(60.0, 30.0));
+                        }).response.on_hover_text(tool_tip);
+                    ui.label(label).on_hover_text(tool_tip);
+                });
+            }
+        });
+    }
+    
+    //chromaticity diagram, x from 0 to 0.8 and y from 0 to 0.9
+    let (response, painter) = ui.allocate_painter(Vec2::new(160.0, 180.0), Sense::hover());
+    let rect = response.rect;
+    let to_screen = |(x, y): (f32, f32)| {
+        egui::pos2(rect.left() + x / 0.8 * rect.width(), rect.bottom() - y / 0.9 * rect.height())
+    };
+    
+    painter.rect_filled(rect, 0.0, Color32::from_gray(40));
+    let spectral_locus = spectrum::spectral_locus_xy().into_iter().map(to_screen).collect();
+    painter.add(egui::Shape::closed_line(spectral_locus, egui::Stroke::new(1.0, Color32::LIGHT_GRAY)));
+    let srgb_gamut = gamut::SRGB_PRIMARIES_XY.into_iter().map(to_screen).collect();
+    painter.add(egui::Shape::closed_line(srgb_gamut, egui::Stroke::new(1.0, Color32::LIGHT_BLUE)));
+    let planckian_locus = gamut::planckian_locus_xy().into_iter().map(to_screen).collect();
+    painter.add(egui::Shape::line(planckian_locus, egui::Stroke::new(1.0, Color32::ORANGE)));
+    if let Some(xy) = xy {
+        let color = if out_of_gamut {Color32::RED} else {Color32::WHITE};
+        painter.circle_filled(to_screen(xy), 3.0, color);
+    }
+    response.on_hover_text(CHROMATICITY_DIAGRAM_TOOLTIP);
+}
+
+/// Displays a small window showing the progress of the given [BackgroundLoad], together with a 
+/// button to cancel it. 
+fn display_loading_dialog<T: Send + 'static>(ctx: &egui::Context, load: &BackgroundLoad<T>) {
+    egui::Window::new("Loading")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, Vec2::new(-10.0, -10.0))
+        .show(ctx, |ui| {
+            ui.label(load.get_name());
+            ui.horizontal_top(|ui| {
+                ui.add(egui::ProgressBar::new(load.get_progress()).desired_width(200.0).show_percentage());
+                if ui.add_enabled(!load.is_cancelled(), egui::Button::new("Cancel")).clicked() {
+                    load.cancel();
+                }
+            });
+        });
+}
+
+/// Renders the scene with the given uniforms at a low resolution and returns the average 
+/// luminance of the accumulated image. The number of frames is limited to 
+/// [LIGHT_BALANCE_PROBE_FRAMES], which suffices for an estimate of the exposure. 
+fn probe_average_luminance(mut uniforms: RaytracingUniforms, width: u32, height: u32, progress: &LoadProgress) 
+    -> Result<f32, LoadError> {
+    let frames = LIGHT_BALANCE_PROBE_FRAMES.min(uniforms.intended_frames_amount.max(1));
+    uniforms.intended_frames_amount = frames;
+    
+    let mut luminance_sum = 0.0f64;
+    for frame_id in 0..frames {
+        uniforms.set_frame(frame_id);
+        for y in 0..height {
+            if progress.is_cancelled() {
+                return Err(LoadError::Cancelled);
+            }
+            for x in 0..width {
+                let (r, g, b, _) = shader::ray_generation_shader(
+                    PixelPos {x, y},
+                    shader::Dimensions {width, height},
+                    &uniforms,
+                );
+                let luminance = gamut::luminance((r, g, b));
+                if luminance.is_finite() {
+                    luminance_sum += luminance as f64;
+                }
+            }
+            progress.set((frame_id * height + y + 1) as f32 / (frames * height) as f32);
+        }
+    }
+    
+    Ok((luminance_sum / (frames as u64 * width as u64 * height as u64) as f64) as f32)
+}
+
 /// Returns true for one second, false for the next, then true again, etc. 
 fn is_time_even() -> bool {
-    std::time::SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() % 2 == 0
+    platform::SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() % 2 == 0
 }
 
-/// Takes a list of [AppActions] and removes all but the last [AppActions::FrameUpdate]. Having
-/// multiple frame updates will result in wasted work since all previous frames will be overwritten
-/// by the most recent frame update.
+/// Takes a list of [AppActions] and removes all but the last [AppActions::FrameUpdate], 
+/// [AppActions::AovsUpdate] and [AppActions::RenderingProgressUpdate]. Having multiple frame 
+/// updates will result in wasted work since all previous frames will be overwritten by the most 
+/// recent frame update.
 fn reduce_action_list(action_list: &mut Vec<AppActions>) {
+    //only the latest progress and statistics are relevant, the workers report them after every row
+    retain_last_action(action_list, |a| matches!(a, AppActions::RenderingProgressUpdate(_)));
+    retain_last_action(action_list, |a| matches!(a, AppActions::RenderStatisticsUpdate(_)));
+    retain_last_action(action_list, |a| matches!(a, AppActions::AovsUpdate {..}));
+    
     let mut nbr_of_frame_updates = 0;
 
     for action in action_list.iter() {
-        if let AppActions::FrameUpdate(_) = action {
+        if let AppActions::FrameUpdate(..) = action {
             nbr_of_frame_updates += 1;
         }
     }
@@ -2291,7 +6878,7 @@ fn reduce_action_list(action_list: &mut Vec<AppActions>) {
     if nbr_of_frame_updates > 1 {
         let mut found_last = false;
         for i in (0..action_list.len()).rev() {
-            if let AppActions::FrameUpdate(_) = action_list[i] {
+            if let AppActions::FrameUpdate(..) = action_list[i] {
                 if !found_last {
                     found_last = true;
                 } else {
@@ -2302,10 +6889,37 @@ fn reduce_action_list(action_list: &mut Vec<AppActions>) {
     }
 }
 
+/// Saves a float AOV image. Only OpenEXR keeps the float values, for the other formats the image is 
+/// encoded into 8 bit first. 
+fn save_aov_image(path: &Path, image: &DynamicImage, encode: impl FnOnce(&DynamicImage) -> DynamicImage) 
+    -> image::ImageResult<()> {
+    if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("exr")) {
+        image.save(path)
+    } else {
+        encode(image).save(path)
+    }
+}
+
+/// Removes all actions of the kind matched by the predicate except for the last one. 
+fn retain_last_action(action_list: &mut Vec<AppActions>, is_kind: impl Fn(&AppActions) -> bool) {
+    if let Some(last) = action_list.iter().rposition(&is_kind) {
+        let mut index = 0;
+        action_list.retain(|a| {
+            index += 1;
+            index - 1 == last || !is_kind(a)
+        });
+    }
+}
+
 //TODO undo redo stack for actions such as creating new elements or deleting old ones
 //TODO the entire UI could use an overhaul
 impl eframe::App for App {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) { //UI is defined here
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, DOCK_STATE_STORAGE_KEY, &self.dock_state);
+        self.store_settings(storage);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) { //UI is defined here
         //Top Menu bar (File, Edit, ...)
         TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             menu::bar(ui, |ui| {
@@ -2319,298 +6933,279 @@ impl eframe::App for App {
                             .add_filter("JPG", &["jpg"])
                             .add_filter("BMP", &["bmp"])
                             .add_filter("TIFF", &["tiff"])
+                            .add_filter("Radiance HDR", &["hdr"])
+                            .add_filter("OpenEXR", &["exr"])
+                            .set_file_name("image.png")
+                            .save_file();
+                        if let Some(path) = dialog {
+                            //the float formats are written from the float colors of the render
+                            let extension = path.extension().map(|extension| extension.to_ascii_lowercase())
+                                .filter(|extension| extension == "hdr" || extension == "exr");
+                            if let Some(extension) = extension {
+                                let result = match self.shown_image_float() {
+                                    Some(image_float) if extension == "hdr" => {
+                                        image_float.save_radiance_hdr(&path).map_err(|e| e.error)
+                                    }
+                                    Some(image_float) => image_float.get_color_image().save(&path).map_err(|e| e.to_string()),
+                                    None => Err("Only the colors of a render, not of a gallery entry, can be saved as HDR.".to_string()),
+                                };
+                                if let Err(e) = result {
+                                    warn!("Error saving image: {e}");
+                                }
+                            } else {
+                                let clone = self.image_actual.clone().unwrap();
+                                match clone.save(path) {
+                                    Ok(_) => (),
+                                    Err(e) => {warn!("Error saving image: {:?}", e);},
+                                }
+                            }
+                        }
+                    }
+                    if ui.add_enabled(self.image_float.is_some(), egui::Button::new("Save Image (16 Bit PNG)"))
+                        .on_hover_text(SAVE_IMAGE_16_BIT_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("PNG", &["png"])
                             .set_file_name("image.png")
                             .save_file();
                         if let Some(path) = dialog {
-                            let clone = self.image_actual.clone().unwrap();
-                            match clone.save(path) {
-                                Ok(_) => (),
-                                Err(e) => {warn!("Error saving image: {:?}", e);},
+                            let image = self.shown_image_float().map(custom_image::CustomImage::to_dithered_rgba16);
+                            if let Some(Err(e)) = image.map(|image| image.save(path)) {
+                                warn!("Error saving image: {:?}", e);
+                            }
+                        }
+                    }
+                    let has_accumulation = self.accumulation.lock().unwrap().is_some();
+                    let has_spectra = self.accumulation.lock().unwrap().as_ref().is_some_and(Accumulation::records_spectra);
+                    if ui.add_enabled(self.image_normals.is_some(), egui::Button::new("Save Normals"))
+                        .on_hover_text(SAVE_NORMALS_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("OpenEXR", &["exr"])
+                            .add_filter("PNG", &["png"])
+                            .add_filter("TIFF", &["tiff"])
+                            .set_file_name("normals.exr")
+                            .save_file();
+                        if let (Some(path), Some(normals)) = (dialog, &self.image_normals) {
+                            if let Err(e) = save_aov_image(&path, normals, custom_image::encode_normal_image) {
+                                warn!("Error saving the normals: {:?}", e);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(self.image_depth.is_some(), egui::Button::new("Save Depth"))
+                        .on_hover_text(SAVE_DEPTH_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("OpenEXR", &["exr"])
+                            .add_filter("PNG", &["png"])
+                            .add_filter("TIFF", &["tiff"])
+                            .set_file_name("depth.exr")
+                            .save_file();
+                        if let (Some(path), Some(depth)) = (dialog, &self.image_depth) {
+                            let (near, far) = (self.ui_values.depth_near, self.ui_values.depth_far);
+                            if let Err(e) = save_aov_image(&path, depth, |depth| custom_image::encode_depth_image(depth, near, far)) {
+                                warn!("Error saving the depth: {:?}", e);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(self.image_albedo.is_some(), egui::Button::new("Save Albedo"))
+                        .on_hover_text(SAVE_ALBEDO_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("OpenEXR", &["exr"])
+                            .add_filter("PNG", &["png"])
+                            .add_filter("TIFF", &["tiff"])
+                            .set_file_name("albedo.exr")
+                            .save_file();
+                        if let (Some(path), Some(albedo)) = (dialog, &self.image_albedo) {
+                            let transfer_function = self.transfer_function();
+                            if let Err(e) = save_aov_image(&path, albedo, |albedo| custom_image::encode_color_image(albedo, transfer_function)) {
+                                warn!("Error saving the albedo: {:?}", e);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(self.image_ids.is_some(), egui::Button::new("Save Object IDs"))
+                        .on_hover_text(SAVE_OBJECT_IDS_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("OpenEXR", &["exr"])
+                            .add_filter("PNG", &["png"])
+                            .add_filter("TIFF", &["tiff"])
+                            .set_file_name("object_ids.exr")
+                            .save_file();
+                        if let (Some(path), Some(ids)) = (dialog, &self.image_ids) {
+                            if let Err(e) = save_aov_image(&path, ids, |ids| ids.to_rgb8().into()) {
+                                warn!("Error saving the object ids: {:?}", e);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(!self.image_light_passes.is_empty(), egui::Button::new("Save Light Passes"))
+                        .on_hover_text(SAVE_LIGHT_PASSES_TOOLTIP).clicked() {
+                        
+                        if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                            for (i, light_pass) in self.image_light_passes.iter().enumerate() {
+                                if let Err(e) = light_pass.save(folder.join(format!("light_{}.exr", i + 1))) {
+                                    warn!("Error saving the light pass {}: {:?}", i + 1, e);
+                                }
+                            }
+                        }
+                    }
+                    if ui.add_enabled(has_accumulation, egui::Button::new("Save Noise Heatmap"))
+                        .on_hover_text(SAVE_NOISE_HEATMAP_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("PNG", &["png"])
+                            .set_file_name("noise.png")
+                            .save_file();
+                        if let Some(path) = dialog {
+                            let heatmap = self.accumulation.lock().unwrap().as_ref().map(Accumulation::noise_heatmap);
+                            if let Some(Err(e)) = heatmap.map(|heatmap| heatmap.save(path)) {
+                                warn!("Error saving the noise heatmap: {:?}", e);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(has_accumulation, egui::Button::new("Export Raw Accumulation"))
+                        .on_hover_text(EXPORT_ACCUMULATION_TOOLTIP).clicked() {
+                        
+                        if let Some(directory) = rfd::FileDialog::new().pick_folder() {
+                            //copied, so that the render thread is not blocked while writing
+                            let accumulation = self.accumulation.lock().unwrap().clone();
+                            if let Some(Err(e)) = accumulation.map(|a| a.export(&directory)) {
+                                warn!("Error exporting the accumulation: {}", e.error);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(has_spectra, egui::Button::new("Export Spectral Bands"))
+                        .on_hover_text(EXPORT_SPECTRAL_BANDS_TOOLTIP).clicked() {
+                        
+                        if let Some(directory) = rfd::FileDialog::new().pick_folder() {
+                            //copied, so that the render thread is not blocked while writing
+                            let accumulation = self.accumulation.lock().unwrap().clone();
+                            if let Some(Err(e)) = accumulation.map(|a| a.export_spectral_bands(&directory)) {
+                                warn!("Error exporting the spectral bands: {}", e.error);
+                            }
+                        }
+                    }
+                    if ui.add_enabled(has_spectra, egui::Button::new("Export Hyperspectral Cube"))
+                        .on_hover_text(EXPORT_HYPERSPECTRAL_CUBE_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("ENVI", &["dat"])
+                            .set_file_name("spectral_cube.dat")
+                            .save_file();
+                        if let Some(path) = dialog {
+                            let accumulation = self.accumulation.lock().unwrap().clone();
+                            if let Some(Err(e)) = accumulation.map(|a| a.export_envi(&path)) {
+                                warn!("Error exporting the hyperspectral cube: {}", e.error);
+                            }
+                        }
+                    }
+                    let can_compare = self.image_actual.is_some() && self.comparison_run.is_none();
+                    if ui.add_enabled(can_compare, egui::Button::new("Compare with Reference"))
+                        .on_hover_text(COMPARE_REFERENCE_TOOLTIP).clicked() {
+                        
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("Image", &["png", "jpg", "bmp", "tiff", "hdr", "exr"])
+                            .pick_file();
+                        if let Some(path) = dialog {
+                            self.start_comparison(move || image::open(path).map_err(|e| e.to_string()));
+                        }
+                    }
+                    if ui.button("Save Scene").on_hover_text(SCENE_SAVE_TOOLTIP).clicked() {
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("Scene", &["ron"])
+                            .set_file_name("scene.ron")
+                            .save_file();
+                        if let Some(path) = dialog {
+                            match scene_file::write(&path, &self.ui_values) {
+                                Ok(()) => self.scene_path = Some(path),
+                                Err(e) => warn!("Error saving the scene: {}", e.error),
+                            }
+                        }
+                    }
+                    if ui.button("Open Scene").on_hover_text(SCENE_OPEN_TOOLTIP).clicked() {
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("Scene", &["ron"])
+                            .pick_file();
+                        if let Some(path) = dialog {
+                            match scene_file::read(&path, &mut self.ui_values, &mut self.texture_cache) {
+                                Ok(()) => {
+                                    if self.scene_watch.is_some() {
+                                        self.scene_watch = Some(SceneWatch::new(path.clone()));
+                                    }
+                                    self.scene_path = Some(path);
+                                }
+                                Err(e) => warn!("Error opening the scene: {}", e.error),
+                            }
+                        }
+                    }
+                    let mut watching = self.scene_watch.is_some();
+                    if ui.add_enabled(self.scene_path.is_some(), egui::Checkbox::new(&mut watching, "Watch Scene File"))
+                        .on_hover_text(SCENE_WATCH_TOOLTIP).changed() {
+                        self.scene_watch = if watching { self.scene_path.clone().map(SceneWatch::new) } else { None };
+                        self.scene_watch_render_pending = false;
+                    }
+                    if ui.button("Export Scene as PBRT").on_hover_text(PBRT_EXPORT_TOOLTIP).clicked() {
+                        let dialog = rfd::FileDialog::new()
+                            .add_filter("PBRT", &["pbrt"])
+                            .set_file_name("scene.pbrt")
+                            .save_file();
+                        if let Some(path) = dialog {
+                            if let Err(e) = pbrt_export::write_pbrt(&path, &self.ui_values) {
+                                warn!("Error exporting the scene: {}", e.error);
                             }
                         }
                     }
                 });
                 ui.menu_button("Edit", |ui| {
                     self.display_start_render_button(ui);
-                    if ui.button("Reset Settings to default").clicked() {
+                    let can_resume = self.app_to_render_channel.is_none() && Path::new(CHECKPOINT_FILE_NAME).exists();
+                    if ui.add_enabled(can_resume, egui::Button::new("Resume Render"))
+                        .on_hover_text(RESUME_RENDER_TOOLTIP).clicked() {
+                        self.resume_render();
+                    }
+                    if ui.button("Reset Settings to default").on_hover_text(RESET_SETTINGS_TOOLTIP).clicked() {
                         self.ui_values = UIFields::default();
+                        if let Some(storage) = frame.storage_mut() {
+                            self.store_settings(storage);
+                            storage.flush();
+                        }
                     }
                     if ui.button("Cornell Box Preset").clicked() {
                         self.ui_values.cornell_box();
                     }
+                    if ui.button("Reset Layout").on_hover_text(RESET_LAYOUT_TOOLTIP).clicked() {
+                        self.dock_state = default_dock_state();
+                    }
                 });
                 ui.menu_button("Help", |ui| {
                     ui.label(HELP_MENU_LABEL);
+                    ui.separator();
+                    ui.label("Tutorials:");
+                    for tutorial in Tutorial::ALL {
+                        if ui.button(tutorial.to_string()).on_hover_text(tutorial.get_description()).clicked() {
+                            self.start_tutorial(tutorial);
+                            ui.close_menu();
+                        }
+                    }
                 })
             });
         });
         
-        //main content div. 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            //tab "buttons"
-            ui.vertical_centered(|ui| {
-                ui.horizontal_top(|ui| {
-                    let old_spacing = ui.style().spacing.clone();
-                    ui.style_mut().spacing.item_spacing.x = 0.0;
-                    ui.style_mut().spacing.item_spacing.y = 0.0;
-
-                    //settings
-                    let color = if self.ui_values.tab == UiTab::Settings {Color32::LIGHT_BLUE} else {Color32::LIGHT_GRAY};
-                    self.display_tab_frame(ui, "Settings", color, UiTab::Settings);
-
-                    //objects
-                    let mut color = if self.ui_values.tab == UiTab::Objects {Color32::LIGHT_BLUE} else {Color32::LIGHT_GRAY};
-                    if !(self.check_lights_legality() && self.check_objects_legality()) && is_time_even() {
-                        color = Color32::LIGHT_RED;
-                    }
-                    self.display_tab_frame(ui, "Objects", color, UiTab::Objects);
-
-                    //spectra and materials
-                    let color = if self.ui_values.tab == UiTab::SpectraAndMaterials {Color32::LIGHT_BLUE} else {Color32::LIGHT_GRAY};
-                    self.display_tab_frame(ui, "Spectra and Materials", color, UiTab::SpectraAndMaterials);
-
-                    //display
-                    let color = if self.ui_values.tab == UiTab::Display {Color32::LIGHT_BLUE} else {Color32::LIGHT_GRAY};
-                    self.display_tab_frame(ui, "Display", color, UiTab::Display);
-
-                    ui.style_mut().spacing = old_spacing;
-                });
-            });
-            
-            //a dividing line between category buttons and the main content
-            ui.add(egui::Separator::default().horizontal().grow(10.0));
-            
-            //content depending on the tab state 
-            match self.ui_values.tab {
-                UiTab::Settings => {
-                    self.display_width_text_edit_field(ui);
-                    self.display_height_text_edit_field(ui);
-                    self.display_nbr_of_threads_edit_field(ui);
-                    self.display_nbr_of_iterations_edit_field(ui);
-                    self.display_max_bounces_edit_field(ui);
-                }
-                UiTab::Objects => {
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        //camera settings
-                        ui.label("Camera:");
-                        egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
-                            self.display_camera_settings(ui);
-                        });
-                        ui.add_space(10.0);
-                        
-                        //Light sources management
-                        ui.vertical_centered(|ui| {
-                            ui.horizontal_top(|ui| {
-                                ui.label("Light Sources:");
-                                ui.add_space(100.0);
-                                if ui.button("Add New Light Source").clicked() {
-                                    let spectrum = match self.ui_values.spectra.first() {
-                                        Some(spectrum) => spectrum.clone(),
-                                        None => {Rc::new(RefCell::new(UISpectrum::default()))}
-                                    };
-                                    let light = UILight::new(0.0, 0.0, 0.0, spectrum, "New Light Source".to_string());
-                                    self.ui_values.ui_lights.push(light);
-                                }
-                            });
-                        });
-                        for index in 0..self.ui_values.ui_lights.len() {
-                            let hidden = self.ui_values.ui_lights[index].hidden;
-                            let color = if hidden {Color32::GRAY} else {Color32::LIGHT_GRAY};
-
-                            ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
-                                egui::Frame::NONE.fill(color).inner_margin(5.0).show(ui, |ui| {
-                                    self.display_light_source_settings(ui, index);
-                                })
-                            }).response.context_menu(|ui| {
-                                if ui.button("Copy").clicked() {
-                                    self.ui_values.after_ui_action = Some(AfterUIActions::CopyLight(index))
-                                }
-                                
-                                //adding actual size since button would wrap otherwise
-                                let hide_button_text = if hidden { "Show" } else { "Hide" };
-                                let button = egui::Button::new(hide_button_text).min_size([40.0, 0.0].into());
-                                if ui.add(button).clicked() {
-                                //if ui.button(hide_button_text).clicked() {
-                                    self.ui_values.ui_lights[index].hidden = !hidden;
-                                }
-                            });
-                        }
-                        ui.add_space(10.0);
-                        
-                        //Objects management
-                        ui.vertical_centered(|ui| {
-                            ui.horizontal_top(|ui| {
-                                ui.label("Objects:");
-                                ui.add_space(100.0);
-                                if ui.button("Add New Object").clicked() {
-                                    let object = UIObject::default(self);
-                                    self.ui_values.ui_objects.push(object);
-                                }
-                            });
-                        });
-                        for index in 0..self.ui_values.ui_objects.len() {
-                            let hidden = self.ui_values.ui_objects[index].hidden;
-                            let color = if hidden {Color32::GRAY} else {Color32::LIGHT_GRAY};
-                            
-                            ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
-                                egui::Frame::NONE.fill(color).inner_margin(5.0).show(ui, |ui| {
-                                    self.display_objects_settings(ui, index);   //TODO ui setting for reflectivity
-                                });
-                            }).response.context_menu(|ui| {
-                                if ui.button("Copy").clicked() {
-                                    self.ui_values.after_ui_action = Some(AfterUIActions::CopyObject(index));
-                                }
-                                
-                                //adding actual size since button would wrap otherwise
-                                let hide_button_text = if hidden { "Show" } else { "Hide" };
-                                let button = egui::Button::new(hide_button_text).min_size([40.0, 0.0].into());
-                                if ui.add(button).clicked() {
-                                    self.ui_values.ui_objects[index].hidden = !hidden;
-                                }
-                            });
-                        }
-                    });
-                }
-                UiTab::SpectraAndMaterials => {
-                    ui.horizontal_top(|ui| {
-                        //left
-                        ui.vertical(|ui| {
-                            egui::ScrollArea::vertical().show(ui, |ui| {
-
-                                ui.label("General Spectrum Settings:");
-                                egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
-                                    self.display_general_spectrum_settings(ui);
-                                });
-                                ui.add_space(10.0);
-
-                                //name and add button: spectra
-                                ui.horizontal_top(|ui| {
-                                    ui.label("Spectra:");
-                                    ui.add_space(100.0);
-                                    if ui.button("Add new Spectrum").clicked() {
-                                        let spectrum = UISpectrum::new(
-                                            "New Spectrum".to_string(),
-                                            UISpectrumType::Solar(0.001),
-                                            SpectrumEffectType::Emissive,
-                                            Spectrum::new_sunlight_spectrum(
-                                                self.ui_values.spectrum_lower_bound,
-                                                self.ui_values.spectrum_upper_bound,
-                                                self.ui_values.spectrum_number_of_samples,
-                                                0.001,
-                                            )
-                                        );
-                                        self.ui_values.spectra.push(
-                                            Rc::new(RefCell::new(spectrum))
-                                        );
-                                    }
-                                });
-
-                                //individual spectra
-                                for index in 0..self.ui_values.spectra.len() {
-                                    //determine color
-                                    let mut color = Color32::LIGHT_GRAY;
-                                    if let Some(selected_index) = &mut self.ui_values.selected_spectrum {
-                                        let selected_index = selected_index.selected_spectrum;
-                                        if selected_index == index {
-                                            color = Color32::LIGHT_BLUE;
-                                        }
-                                    }
-
-                                    //add actual spectrum UI elements
-                                    let response =  ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
-                                        egui::Frame::NONE.fill(color).inner_margin(5.0).show(ui, |ui| {
-                                            self.display_spectrum_settings(ui, index);
-                                        });
-                                    }).response;
-                                    if response.clicked()  {
-                                        self.update_selected_spectrum(index);
-                                    };
-                                    response.context_menu(|ui| {
-                                        if ui.button("Copy").clicked() {
-                                            self.ui_values.after_ui_action = Some(AfterUIActions::CopySpectrum(index));
-                                        }
-                                    });
-                                }
-                                ui.add_space(10.0);
-                                
-                                //name and add button: materials
-                                ui.horizontal_top(|ui| {
-                                    ui.label("Materials:");
-                                    ui.add_space(100.0);
-                                    if ui.button("Add new Material").clicked() {
-                                        let material = UIMaterial::default(self);
-                                        self.ui_values.materials.push(
-                                            Rc::new(RefCell::new(material))
-                                        );
-                                    }
-                                });
-                                
-                                //individual materials
-                                for index in 0..self.ui_values.materials.len() {
-                                    //add actual spectrum UI elements
-                                    ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
-                                        egui::Frame::NONE.fill(Color32::LIGHT_GRAY).inner_margin(5.0).show(ui, |ui| {
-                                            self.display_material_settings(ui, index);
-                                        });
-                                    }).response.context_menu(|ui| {
-                                        if ui.button("Copy").clicked() {
-                                            self.ui_values.after_ui_action = Some(AfterUIActions::CopyMaterial(index));
-                                        }
-                                    });
-                                }
-                            });
-                        });
-
-                        //divider
-                        ui.separator();
-
-                        //right side
-                        ui.vertical(|ui| {
-                            self.display_spectrum_right_side(ui);
-                        });
-                    });
-                }
-                UiTab::Display => {
-                    //user information about rendering time
-                    ui.horizontal_top(|ui| {
-                        self.display_start_render_button(ui);
-                        self.display_abort_button(ui);
-                        self.refresh_rendering_time();
-                        self.display_frame_generation_time(ui);
-                        egui::Frame::NONE.inner_margin(5.0).show(ui, |ui| {
-                            ui.add(egui::ProgressBar::new(self.ui_values.progress_bar_progress));
-                        });
-                    });
-
-                    //image display frame
-                    egui::Frame::NONE.fill(Color32::GRAY).show(ui, |ui| {
-                        if let Some(ref img) = self.image_eframe_texture {
-                            let window_dimensions = ctx.input(|i| i.viewport().outer_rect).unwrap();
-                            let x_ratio = window_dimensions.width() / self.ui_values.width as f32;
-                            let y_ratio = window_dimensions.height() / self.ui_values.height as f32;
-                            let lower_zoom_end = x_ratio.min(y_ratio).min(1.0);
-                            let upper_zoom_end = 10.0;
-
-                            egui::Scene::new()
-                                    .zoom_range(lower_zoom_end..=upper_zoom_end)
-                                    .show(ui, &mut self.ui_values.image_scene_rect, |ui| {
-                                ui.add(
-                                    egui::Image::from_texture(img).fit_to_original_size(1.0)
-                                ).on_hover_text(DISPLAY_IMAGE_TOOLTIP);
-                            }).response.context_menu(|ui| {
-                                if ui.button("Return to the image").clicked() {
-                                    self.ui_values.image_scene_rect = egui::Rect::ZERO;
-                                }
-                            });
-                        } else {
-                            ui.centered_and_justified(|ui| {
-                                self.display_start_render_button(ui);
-                            });
-                        }
-                    });
-                }
+        self.display_tutorial_window(ctx);
+        
+        //main content div, the tabs are arranged in dockable panels
+        let mut dock_state = std::mem::replace(&mut self.dock_state, DockState::new(Vec::new()));
+        if let Some(tab) = self.tab_to_focus.take() {
+            if let Some(location) = dock_state.find_tab(&tab) {
+                dock_state.set_active_tab(location);
             }
-        });
+        }
+        DockArea::new(&mut dock_state)
+            .show_close_buttons(false)
+            .show_leaf_close_all_buttons(false)
+            .show(ctx, &mut AppTabViewer { app: self });
+        self.dock_state = dock_state;
 
         /////////////////////////////////// UI IS DONE BY HERE /////////////////////////////////////
 
@@ -2618,6 +7213,21 @@ impl eframe::App for App {
         //elements with a button press. 
         if self.ui_values.after_ui_action.is_some() {
             match self.ui_values.after_ui_action.take().unwrap() {
+                AfterUIActions::DeleteGroup(index) => {
+                    //everything in the group moves up to its parent
+                    let group = self.ui_values.ui_groups.remove(index);
+                    let parent = group.borrow().parent.clone();
+                    let is_deleted = |g: &Option<Rc<RefCell<UIGroup>>>| g.as_ref().is_some_and(|g| Rc::ptr_eq(g, &group));
+                    for object in self.ui_values.ui_objects.iter_mut().filter(|o| is_deleted(&o.group)) {
+                        object.group = parent.clone();
+                    }
+                    for light in self.ui_values.ui_lights.iter_mut().filter(|l| is_deleted(&l.group)) {
+                        light.group = parent.clone();
+                    }
+                    for child in self.ui_values.ui_groups.iter().filter(|g| is_deleted(&g.borrow().parent)) {
+                        child.borrow_mut().parent = parent.clone();
+                    }
+                }
                 AfterUIActions::DeleteLight(index) => {
                     self.ui_values.ui_lights.remove(index);
                 }
@@ -2652,8 +7262,19 @@ impl eframe::App for App {
                 AfterUIActions::CopyObject(index) => {
                     let mut new_ui_object = self.ui_values.ui_objects[index].clone();
                     new_ui_object.name += COPIED_ELEMENT_NAME_INDICATOR;
+                    new_ui_object.id = get_id();
                     self.ui_values.ui_objects.insert(index + 1, new_ui_object);
                 }
+                AfterUIActions::CopyCamera(index) => {
+                    let mut new_ui_camera = self.ui_values.ui_cameras[index].clone();
+                    new_ui_camera.name += COPIED_ELEMENT_NAME_INDICATOR;
+                    self.ui_values.ui_cameras.insert(index + 1, new_ui_camera);
+                    self.ui_values.active_camera = index + 1;
+                }
+                AfterUIActions::DeleteCamera(index) => {
+                    self.ui_values.ui_cameras.remove(index);
+                    self.ui_values.active_camera = index.saturating_sub(1);
+                }
                 AfterUIActions::DeleteMaterial(index) => {
                     self.ui_values.materials.remove(index);
                 }
@@ -2666,6 +7287,156 @@ impl eframe::App for App {
         }
 
 
+        self.poll_scene_watch(ctx);
+        self.poll_remote_control();
+
+        //assets loaded in the background
+        if let Some(load) = &self.sensor_response_load {
+            match load.poll() {
+                Some(Ok(sensor)) => {
+                    self.ui_values.custom_sensor_response = Some(Arc::new(sensor));
+                    self.sensor_response_load = None;
+                }
+                Some(Err(LoadError::Cancelled)) => {
+                    self.sensor_response_load = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error loading sensor response: {e}");
+                    self.sensor_response_load = None;
+                }
+                None => {
+                    display_loading_dialog(ctx, load);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+
+        if let Some(probe) = &self.light_balance_probe {
+            match probe.poll() {
+                Some(Ok(average_luminance)) => {
+                    self.light_balance_result = Some(average_luminance);
+                    self.light_balance_probe = None;
+                }
+                Some(Err(LoadError::Cancelled)) => {
+                    self.light_balance_probe = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error during the light balance probe render: {e}");
+                    self.light_balance_probe = None;
+                }
+                None => {
+                    display_loading_dialog(ctx, probe);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+        self.display_light_balance_result(ctx);
+        self.update_live_preview(ctx);
+        if let Some(run) = &self.denoise_run {
+            match run.poll() {
+                Some(Ok(denoised)) => {
+                    self.denoise_run = None;
+                    self.image_float = Some(denoised);
+                    self.image_oidn = None;
+                    self.encode_last_frame(ctx);
+                }
+                Some(Err(LoadError::Cancelled)) => {
+                    self.denoise_run = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error denoising the image: {e}");
+                    self.denoise_run = None;
+                }
+                None => {
+                    display_loading_dialog(ctx, run);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+        if let Some(run) = &self.oidn_run {
+            match run.poll() {
+                Some(Ok(denoised)) => {
+                    self.oidn_run = None;
+                    self.image_oidn = Some(denoised);
+                    self.ui_values.show_oidn = true;
+                    self.encode_last_frame(ctx);
+                }
+                Some(Err(LoadError::Cancelled)) => {
+                    self.oidn_run = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error denoising the image with Open Image Denoise: {e}");
+                    self.oidn_run = None;
+                }
+                None => {
+                    display_loading_dialog(ctx, run);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+        if let Some(run) = &self.comparison_run {
+            match run.poll() {
+                Some(Ok(difference)) => {
+                    self.comparison_run = None;
+                    let heatmap = ctx.load_texture("comparison_heatmap", dynamic_image_to_color_image(&difference.heatmap), 
+                                                   egui::TextureOptions::default());
+                    self.comparison = Some((difference, heatmap));
+                }
+                Some(Err(LoadError::Cancelled)) => {
+                    self.comparison_run = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error comparing with the reference: {e}");
+                    self.comparison_run = None;
+                }
+                None => {
+                    display_loading_dialog(ctx, run);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+        self.display_comparison(ctx);
+        if let Some(probe) = &self.render_estimate_probe {
+            match probe.poll() {
+                Some(Ok(estimate)) => {
+                    self.render_estimate_probe = None;
+                    if estimate.warnings().is_empty() {
+                        self.dispatch_render();
+                    } else {
+                        self.render_estimate = Some(estimate);
+                    }
+                }
+                Some(Err(LoadError::Cancelled)) => {
+                    self.render_estimate_probe = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error estimating the render, rendering without an estimate: {e}");
+                    self.render_estimate_probe = None;
+                    self.dispatch_render();
+                }
+                None => {
+                    display_loading_dialog(ctx, probe);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+        self.display_render_estimate_warning(ctx);
+        if let Some(dump) = &self.path_dump {
+            match dump.poll() {
+                Some(Ok(())) | Some(Err(LoadError::Cancelled)) => {
+                    self.path_dump = None;
+                }
+                Some(Err(LoadError::Failed(e))) => {
+                    warn!("Error dumping debug paths: {e}");
+                    self.path_dump = None;
+                }
+                None => {
+                    display_loading_dialog(ctx, dump);
+                    ctx.request_repaint_after_secs(0.05);
+                }
+            }
+        }
+
         //Other frames may have finished work
         let mut separate_action_list;
         {   //block to drop the action list mutex guard
@@ -2678,15 +7449,34 @@ impl eframe::App for App {
         
         for action in separate_action_list {
             match action {
-                AppActions::FrameUpdate(image) => {
+                AppActions::AovsUpdate {normals, depth, albedo, ids, lights} => {
+                    self.image_normals = Some(normals);
+                    self.image_depth = Some(depth);
+                    self.image_albedo = Some(albedo);
+                    self.image_ids = Some(ids);
+                    self.image_light_passes = lights;
+                }
+                AppActions::FrameUpdate(image, image_float) => {
                     self.image_actual = Some(image);
+                    self.image_float = Some(image_float);
+                    self.image_oidn = None;
                     self.renew_texture_handle(ctx);
                 }
                 AppActions::TrueTimeUpdate(duration) => {
                     self.ui_values.frame_gen_time = Some(duration);
+                    //aborted renders are not kept
+                    let progress = self.ui_values.render_progress;
+                    if let (Some(snapshot), Some(image)) = (self.render_snapshot.take(), &self.image_actual) {
+                        if progress.frames_done == progress.nbr_of_frames {
+                            self.gallery.add(image.clone(), snapshot, duration);
+                        }
+                    }
                 }
                 AppActions::RenderingProgressUpdate(progress) => {
-                    self.ui_values.progress_bar_progress = progress;
+                    self.ui_values.render_progress = progress;
+                }
+                AppActions::RenderStatisticsUpdate(statistics) => {
+                    self.ui_values.render_statistics = statistics;
                 }
                 AppActions::DestroySender => {
                     self.app_to_render_channel = None;
@@ -2698,5 +7488,35 @@ impl eframe::App for App {
         //a request repaint call is cleared as soon as a frame is drawn, meaning this line does 
         // nothing as long as one continues moving their mouse
         ctx.request_repaint_after_secs(1.0);
+        
+        //the buckets move a lot faster than frames are finished
+        if self.ui_values.show_render_buckets && *self.currently_rendering.lock().unwrap() {
+            ctx.request_repaint_after_secs(0.1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_restore_dock_state_of_other_builds() {
+        //a layout saved by a build with the scripting feature, the script tab in a panel of its own
+        let mut dock_state = DockState::new(UiTab::ALL.iter().copied().filter(|tab| *tab != UiTab::Script).collect());
+        dock_state.main_surface_mut().split_right(egui_dock::NodeIndex::root(), 0.7, vec![UiTab::Script]);
+        let stored = ron::to_string(&dock_state).unwrap();
+        
+        let restored = restore_dock_state(ron::from_str(&stored).unwrap()).expect("The layout was not restored!");
+        for tab in UiTab::ALL {
+            assert!(restored.find_tab(&tab).is_some(), "The tab {tab:?} is missing from the restored layout!");
+        }
+        assert_eq!(restored.find_tab(&UiTab::Script).is_some(), UiTab::ALL.contains(&UiTab::Script), 
+                   "The script tab should only be kept by builds with the scripting feature!");
+        
+        //a layout lacking a tab of this build falls back to the default
+        let mut dock_state = default_dock_state();
+        dock_state.retain_tabs(|tab| *tab != UiTab::Gallery);
+        assert!(restore_dock_state(dock_state).is_none(), "A layout without the gallery tab was restored!");
     }
 }
@@ -0,0 +1,76 @@
+use std::f32::consts::PI;
+use crate::radiometry::{SpectralIrradiance, SpectralRadiance, SpectralReflectance};
+use crate::spectrum::Spectrum;
+
+/// A homogeneous participating medium filling the whole scene, such as fog or haze. Light 
+/// travelling through it is absorbed and scattered out of its way following the Beer-Lambert law. 
+/// Optionally, the light of the light sources scattered by the medium towards the camera is 
+/// gathered as well, which makes light beams visible. Scattering is isotropic. 
+#[derive(Clone, Debug)]
+pub struct Medium {
+    /// The scattering coefficient per unit of length.
+    scattering: Spectrum,
+    /// The sum of the absorption and the scattering coefficient per unit of length.
+    extinction: Spectrum,
+    /// The highest extinction coefficient of all wavelengths, used to sample scattering points.
+    max_extinction: f32,
+    in_scattering: bool,
+}
+
+impl Medium {
+    /// Returns the share of light of each wavelength which passes the given distance through the 
+    /// medium without being absorbed or scattered. 
+    pub fn transmittance(&self, distance: f32) -> SpectralReflectance {
+        let mut transmittance = self.extinction;
+        for value in transmittance.get_intensities_slice() {
+            //an infinite distance through a clear medium would otherwise be NaN
+            *value = if *value > 0.0 { (-*value * distance).exp() } else { 1.0 };
+        }
+        SpectralReflectance::new(transmittance)
+    }
+
+    /// Whether the light scattered towards the camera is gathered.
+    pub fn is_in_scattering(&self) -> bool {
+        self.in_scattering && self.max_extinction > 0.0
+    }
+
+    /// Draws the distance to a scattering point along a ray from the given random value in range 
+    /// \[0; 1), distributed like the free flight through the densest wavelength. Returns the 
+    /// distance together with its probability density, None if the medium is perfectly clear. 
+    pub fn sample_distance(&self, random: f32) -> Option<(f32, f32)> {
+        if self.max_extinction <= 0.0 {
+            return None;
+        }
+        let distance = -(1.0 - random).ln() / self.max_extinction;
+        Some((distance, self.max_extinction * (-self.max_extinction * distance).exp()))
+    }
+
+    /// The radiance scattered into any direction per unit of length, by a point of the medium 
+    /// receiving the given irradiance. Isotropic scattering spreads it over all 4π steradians.
+    pub fn scatter(&self, irradiance: &SpectralIrradiance) -> SpectralRadiance {
+        SpectralRadiance::new(&(&**irradiance * &self.scattering) / (4.0 * PI))
+    }
+}
+
+impl Medium {
+    /// Creates a medium from its absorption and scattering coefficients per unit of length, both 
+    /// multiplied by the density. Negative coefficients are taken as 0. With in-scattering, the 
+    /// light of the light sources scattered towards the camera is gathered. 
+    pub fn new(absorption: &Spectrum, scattering: &Spectrum, density: f32, in_scattering: bool) -> Self {
+        let mut absorption = *absorption;
+        absorption.max0();
+        absorption *= density;
+        let mut scattering = *scattering;
+        scattering.max0();
+        scattering *= density;
+        let mut extinction = absorption;
+        extinction += &scattering;
+        
+        Medium {
+            scattering,
+            extinction,
+            max_extinction: extinction.get_max(),
+            in_scattering,
+        }
+    }
+}
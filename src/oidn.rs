@@ -0,0 +1,131 @@
+//! Denoising with Intel Open Image Denoise, a neural network denoiser trained on path traced
+//! images. Only available if the crate is built with the `oidn` feature
+//! (`cargo build -r --features oidn`), which links against the OpenImageDenoise library. It has to
+//! be installed where the linker finds it, e.g. via `RUSTFLAGS="-L <oidn>/lib"`, and be found at
+//! runtime next to the executable or in the library path. <br>
+//! The library is bound via its C API directly, only the few calls needed to run the ray tracing
+//! filter on the CPU are declared.
+
+use std::ffi::{c_char, c_void, CStr};
+use image::DynamicImage;
+use crate::custom_image::CustomImage;
+
+const OIDN_DEVICE_TYPE_CPU: i32 = 1;
+const OIDN_FORMAT_FLOAT3: i32 = 3;
+const OIDN_ERROR_NONE: i32 = 0;
+
+#[link(name = "OpenImageDenoise")]
+extern "C" {
+    fn oidnNewDevice(device_type: i32) -> *mut c_void;
+    fn oidnCommitDevice(device: *mut c_void);
+    fn oidnGetDeviceError(device: *mut c_void, out_message: *mut *const c_char) -> i32;
+    fn oidnReleaseDevice(device: *mut c_void);
+    fn oidnNewFilter(device: *mut c_void, filter_type: *const c_char) -> *mut c_void;
+    fn oidnSetSharedFilterImage(filter: *mut c_void, name: *const c_char, data: *mut c_void, format: i32,
+                                width: usize, height: usize, byte_offset: usize, pixel_byte_stride: usize,
+                                row_byte_stride: usize);
+    fn oidnSetFilterBool(filter: *mut c_void, name: *const c_char, value: bool);
+    fn oidnCommitFilter(filter: *mut c_void);
+    fn oidnExecuteFilter(filter: *mut c_void);
+    fn oidnReleaseFilter(filter: *mut c_void);
+}
+
+/// A device of the library, released when dropped.
+struct Device(*mut c_void);
+
+impl Device {
+    /// Creates and commits a CPU device, which can work on the buffers of the image directly.
+    fn new() -> Result<Device, OidnError> {
+        let device = unsafe { oidnNewDevice(OIDN_DEVICE_TYPE_CPU) };
+        if device.is_null() {
+            return Err(OidnError{error: "Could not create an Open Image Denoise device!".to_string()});
+        }
+        let device = Device(device);
+        unsafe { oidnCommitDevice(device.0) };
+        device.check_error()?;
+        Ok(device)
+    }
+
+    /// Returns the first error the device ran into since the last check, if any.
+    fn check_error(&self) -> Result<(), OidnError> {
+        let mut message: *const c_char = std::ptr::null();
+        let code = unsafe { oidnGetDeviceError(self.0, &mut message) };
+        if code == OIDN_ERROR_NONE {
+            return Ok(());
+        }
+        let message = if message.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned()
+        };
+        Err(OidnError{error: format!("Open Image Denoise error {code}: {message}")})
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { oidnReleaseDevice(self.0) };
+    }
+}
+
+/// A filter of the library, released when dropped. Must be dropped before its device.
+struct Filter(*mut c_void);
+
+impl Drop for Filter {
+    fn drop(&mut self) {
+        unsafe { oidnReleaseFilter(self.0) };
+    }
+}
+
+/// Denoises the colors of the image with the ray tracing filter of Open Image Denoise, guided by
+/// the albedos and normals of what the pixels see first. The guides must be float images of the
+/// size of the image, as returned by [get_albedo_image](CustomImage::get_albedo_image) and
+/// [get_normal_image](CustomImage::get_normal_image). The colors are denoised as HDR, the alpha
+/// and the sample counts are kept.
+pub fn denoise(image: &CustomImage, normals: &DynamicImage, albedos: &DynamicImage) -> Result<CustomImage, OidnError> {
+    let (width, height) = (image.get_width(), image.get_height());
+    let normals = normals.to_rgb32f();
+    let albedos = albedos.to_rgb32f();
+    if normals.dimensions() != (width, height) || albedos.dimensions() != (width, height) {
+        return Err(OidnError{error: "The normals and albedos do not match the size of the image!".to_string()});
+    }
+    let mut normals = normals.into_raw();
+    let mut albedos = albedos.into_raw();
+    let mut colors = image.get_data().chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect::<Vec<f32>>();
+    let mut output = vec![0.0f32; colors.len()];
+
+    let device = Device::new()?;
+    let filter = Filter(unsafe { oidnNewFilter(device.0, c"RT".as_ptr()) });
+    if filter.0.is_null() {
+        device.check_error()?;
+        return Err(OidnError{error: "Could not create the Open Image Denoise filter!".to_string()});
+    }
+    let pixel_stride = 3 * size_of::<f32>();
+    for (name, buffer) in [(c"color", &mut colors), (c"albedo", &mut albedos), (c"normal", &mut normals),
+                           (c"output", &mut output)] {
+        unsafe {
+            oidnSetSharedFilterImage(filter.0, name.as_ptr(), buffer.as_mut_ptr() as *mut c_void, OIDN_FORMAT_FLOAT3,
+                                     width as usize, height as usize, 0, pixel_stride, pixel_stride * width as usize);
+        }
+    }
+    unsafe {
+        oidnSetFilterBool(filter.0, c"hdr".as_ptr(), true);
+        oidnCommitFilter(filter.0);
+        oidnExecuteFilter(filter.0);
+    }
+    device.check_error()?;
+    drop(filter);
+
+    let data = output.chunks_exact(3).zip(image.get_data().chunks_exact(4))
+        .flat_map(|(rgb, pixel)| [rgb[0], rgb[1], rgb[2], pixel[3]])
+        .collect();
+    CustomImage::new_from_samples(width, height, data, image.get_sample_counts().to_vec())
+        .map_err(|e| OidnError{error: e.error})
+}
+
+/// An error type used by the denoising with Open Image Denoise, specific details of the error are
+/// given in the error String.
+#[derive(Debug)]
+pub struct OidnError {
+    pub error: String,
+}
@@ -0,0 +1,339 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+use nalgebra::vector;
+use crate::{UIBackground, UIColorResponse, UIFields, UIHeightfield, UIMaterial, UIObjectType};
+use crate::hierarchy;
+use crate::shader::{flat_object_rotation, LightLinkMode, MixWeight};
+use crate::spectrum::{Spectrum, MAXIMUM_LUMINOUS_EFFICACY};
+
+/// The corners of a box centered at the origin with half lengths of 1, the index of a corner holds
+/// its sign along x, y and z in its three lowest bits.
+const BOX_CORNER_SIGNS: [(f32, f32, f32); 8] = [
+    (-1.0, -1.0, -1.0), (1.0, -1.0, -1.0), (-1.0, 1.0, -1.0), (1.0, 1.0, -1.0),
+    (-1.0, -1.0, 1.0), (1.0, -1.0, 1.0), (-1.0, 1.0, 1.0), (1.0, 1.0, 1.0),
+];
+/// Two triangles per side of the box, indexing [BOX_CORNER_SIGNS].
+const BOX_TRIANGLES: [u32; 36] = [
+    0, 2, 6, 0, 6, 4,   //-x
+    1, 5, 7, 1, 7, 3,   //+x
+    0, 4, 5, 0, 5, 1,   //-y
+    2, 3, 7, 2, 7, 6,   //+y
+    0, 1, 3, 0, 3, 2,   //-z
+    4, 6, 7, 4, 7, 5,   //+z
+];
+
+/// Writes the scene described by the UI values into the given file in the PBRT v4 scene format,
+/// so that renders can be cross-validated with an established spectral renderer. See [to_pbrt]
+/// for how the scene is mapped.
+pub fn write_pbrt(path: &Path, ui_values: &UIFields) -> Result<(), PbrtExportError> {
+    let image_name = path.with_extension("exr").file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "render.exr".to_string());
+    std::fs::write(path, to_pbrt(ui_values, &image_name))
+        .map_err(|e| PbrtExportError {error: format!("Could not write {}: {e}", path.display())})
+}
+
+/// Converts the scene into the PBRT v4 scene format. The mapping is as close as the two renderers
+/// allow:
+/// - Spectra are written as piecewise linear spectra with the same samples. PBRT normalizes
+///   emission spectra to a luminance of 1, the original brightness is restored via the scale.
+/// - Materials become a mix of a diffuse and a conductor material, the metallicness being the
+///   chance of the conductor, as in the hit shader. The roughness is passed on unchanged,
+///   although PBRT interprets it as microfacet roughness instead of a reflection cone. Anisotropic
+///   roughness becomes PBRT's u and v roughness, which follow the parametrization of the shapes
+///   instead of the tangent of the hit shader.
+/// - Mixed materials become PBRT mix materials of their two materials. Only a constant share is
+///   exported, Fresnel and pattern driven shares become an even mix.
+/// - Boxes, quads and heightfields become triangle meshes, spheres spheres, ellipsoids scaled
+///   spheres, discs discs, cylinders cylinders closed by two discs and glowing objects diffuse
+///   area lights. Quads are seen from both sides in PBRT. CSG objects have no counterpart and are left out.
+/// - Groups are resolved, objects and lights are written in world space.
+/// - The fog becomes a homogeneous medium around the camera and all objects, rendered with the
+///   volumetric path integrator. PBRT also gathers light scattered more than once. The atmosphere
+///   has no counterpart and is left out.
+/// - A background spectrum becomes an infinite light, gradients and transparency are left out.
+/// - Textures, material patterns and fluorescence are not exported, such surfaces only keep the
+///   reflecting spectrum of their material. Shadow catchers become ordinary surfaces.
+/// - Hidden objects and lights are left out. The thick lens is exported without dispersion,
+///   emission invisible to the camera, camera sensor curves, light linking and the visibility
+///   flags of objects have no counterpart.
+pub fn to_pbrt(ui_values: &UIFields, image_name: &str) -> String {
+    let mut pbrt = String::from("# Exported by the spectral raytracer\n\n");
+
+    //the medium surrounds the camera, so it has to be known before it
+    if let Some(medium) = &ui_values.medium {
+        let _ = writeln!(pbrt, "MakeNamedMedium \"fog\" \"string type\" \"homogeneous\" {} {} \"float scale\" [{}]",
+                         spectrum_parameter("sigma_a", &medium.absorption.borrow().spectrum),
+                         spectrum_parameter("sigma_s", &medium.scattering.borrow().spectrum), medium.density);
+        if !medium.in_scattering {
+            pbrt.push_str("# Light beams can not be turned off, PBRT always scatters light towards the camera\n");
+        }
+        pbrt.push_str("MediumInterface \"\" \"fog\"\n");
+    }
+
+    //camera, film and integrator
+    let camera = &ui_values.ui_cameras[ui_values.active_camera];
+    let _ = writeln!(pbrt, "LookAt {} {} {}  {} {} {}  {} {} {}",
+                     camera.pos_x, camera.pos_y, camera.pos_z,
+                     camera.pos_x + camera.dir_x, camera.pos_y + camera.dir_y, camera.pos_z + camera.dir_z,
+                     camera.up_x, camera.up_y, camera.up_z);
+    //PBRT's field of view spans the shorter side of the image
+    let aspect_ratio = ui_values.width as f32 / ui_values.height as f32;
+    let fov = if aspect_ratio >= 1.0 {
+        camera.fov_deg_y
+    } else {
+        2.0 * ((camera.fov_deg_y / 2.0).to_radians().tan() * aspect_ratio).atan().to_degrees()
+    };
+    let _ = write!(pbrt, "Camera \"perspective\" \"float fov\" [{fov}]");
+    if camera.thick_lens {
+        let _ = write!(pbrt, " \"float lensradius\" [{}] \"float focaldistance\" [{}]",
+                       camera.aperture_radius, camera.focus_distance);
+    }
+    pbrt.push('\n');
+    if camera.near_clip.is_some() || camera.far_clip.is_some() {
+        pbrt.push_str("# The clipping planes are not exported\n");
+    }
+    if !matches!(ui_values.color_response, UIColorResponse::CieObserver | UIColorResponse::CieObserver1931) {
+        pbrt.push_str("# The camera sensor response is not exported, PBRT uses the CIE observer\n");
+    }
+    let _ = writeln!(pbrt, "Film \"rgb\" \"integer xresolution\" [{}] \"integer yresolution\" [{}] \"string filename\" \"{}\"",
+                     ui_values.width, ui_values.height, escape(image_name));
+    let _ = writeln!(pbrt, "Sampler \"zsobol\" \"integer pixelsamples\" [{}]", ui_values.nbr_of_iterations);
+    let integrator = if ui_values.medium.is_some() { "volpath" } else { "path" };
+    let _ = writeln!(pbrt, "Integrator \"{integrator}\" \"integer maxdepth\" [{}]", ui_values.nbr_of_ray_bounces);
+    pbrt.push_str("\nWorldBegin\n\n");
+    if ui_values.medium.is_some() {
+        pbrt.push_str("MediumInterface \"\" \"fog\"\n\n");
+    }
+    if ui_values.atmosphere.is_some() {
+        pbrt.push_str("# The atmosphere and its sun are not exported\n\n");
+    } else {
+        match &ui_values.background {
+            UIBackground::Black => {}
+            UIBackground::Spectrum(spectrum) => {
+                let spectrum = spectrum.borrow().spectrum;
+                if let Some(scale) = emission_scale(&spectrum, ui_values.light_scale) {
+                    let _ = writeln!(pbrt, "LightSource \"infinite\" {} \"float scale\" [{scale}]\n",
+                                     spectrum_parameter("L", &spectrum));
+                }
+            }
+            UIBackground::Gradient {..} => pbrt.push_str("# The background gradient is not exported\n\n"),
+            UIBackground::Transparent => pbrt.push_str("# The transparency of the background is not exported\n\n"),
+        }
+    }
+
+    //materials
+    let mut written_materials = HashSet::new();
+    for material in &ui_values.materials {
+        write_material(&mut pbrt, &material.borrow(), &mut written_materials);
+    }
+
+    //light sources
+    for light in ui_values.ui_lights.iter().filter(|l| !l.hidden).map(hierarchy::world_light) {
+        let spectrum = light.spectrum.borrow().spectrum;
+        let _ = writeln!(pbrt, "# Light source \"{}\"", escape(&light.name));
+        if light.link_mode != LightLinkMode::All {
+            pbrt.push_str("# The light linking is not exported, it illuminates all objects\n");
+        }
+        match emission_scale(&spectrum, ui_values.light_scale) {
+            Some(scale) => {
+                let _ = writeln!(pbrt, "LightSource \"point\" \"point3 from\" [{} {} {}] {} \"float scale\" [{scale}]\n",
+                                 light.pos_x, light.pos_y, light.pos_z, spectrum_parameter("I", &spectrum));
+            }
+            None => pbrt.push_str("# Left out, it emits no visible light\n\n"),
+        }
+    }
+
+    //objects
+    for object in ui_values.ui_objects.iter().filter(|o| !o.hidden).map(hierarchy::world_object) {
+        let _ = writeln!(pbrt, "# Object \"{}\"", escape(&object.name));
+        pbrt.push_str("AttributeBegin\n");
+        let _ = writeln!(pbrt, "  Translate {} {} {}", object.pos_x, object.pos_y, object.pos_z);
+        let _ = writeln!(pbrt, "  NamedMaterial \"material_{}\"", object.material.borrow().id);
+        if let Some(texture) = &object.texture {
+            let _ = writeln!(pbrt, "  # The texture {} is not exported", escape(&texture.get_path().display().to_string()));
+        }
+        if !(object.visible_to_camera && object.visible_in_reflections && object.casts_shadows) {
+            pbrt.push_str("  # The visibility flags are not exported, all rays see the object\n");
+        }
+        if let Some(emission) = &object.emission {
+            let spectrum = emission.borrow().spectrum;
+            if let Some(scale) = emission_scale(&spectrum, ui_values.light_scale) {
+                let _ = writeln!(pbrt, "  AreaLightSource \"diffuse\" {} \"float scale\" [{scale}] \"bool twosided\" true",
+                                 spectrum_parameter("L", &spectrum));
+            }
+        }
+
+        match object.ui_object_type {
+            UIObjectType::Sphere(radius) => {
+                let _ = writeln!(pbrt, "  Shape \"sphere\" \"float radius\" [{radius}]");
+            }
+            UIObjectType::PlainBox(x_length, y_length, z_length) => {
+                pbrt.push_str(&box_shape(x_length, y_length, z_length));
+            }
+            UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, z_rotation) => {
+                //the euler angles are applied around x first, then y, then z
+                let _ = writeln!(pbrt, "  Rotate {} 0 0 1", z_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Rotate {} 0 1 0", y_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Rotate {} 1 0 0", x_rotation.to_degrees());
+                pbrt.push_str(&box_shape(x_length, y_length, z_length));
+            }
+            UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+                let _ = writeln!(pbrt, "  Rotate {} 0 0 1", z_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Rotate {} 0 1 0", y_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Rotate {} 1 0 0", x_rotation.to_degrees());
+                //PBRT's cylinders and discs lie along the z axis instead of the y axis
+                pbrt.push_str("  Rotate -90 1 0 0\n");
+                let _ = writeln!(pbrt, "  Shape \"cylinder\" \"float radius\" [{radius}] \"float zmin\" [{}] \"float zmax\" [{}]",
+                                 -height / 2.0, height / 2.0);
+                let _ = writeln!(pbrt, "  Shape \"disk\" \"float radius\" [{radius}] \"float height\" [{}]", height / 2.0);
+                let _ = writeln!(pbrt, "  Shape \"disk\" \"float radius\" [{radius}] \"float height\" [{}]", -height / 2.0);
+            }
+            UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+                let _ = writeln!(pbrt, "  Rotate {} 0 0 1", z_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Rotate {} 0 1 0", y_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Rotate {} 1 0 0", x_rotation.to_degrees());
+                let _ = writeln!(pbrt, "  Scale {x_radius} {y_radius} {z_radius}");
+                pbrt.push_str("  Shape \"sphere\" \"float radius\" [1]\n");
+            }
+            UIObjectType::Disc(radius, normal_x, normal_y, normal_z) => {
+                pbrt.push_str(&flat_object_rotate(normal_x, normal_y, normal_z));
+                //PBRT's discs face along the z axis instead of the y axis
+                pbrt.push_str("  Rotate -90 1 0 0\n");
+                let _ = writeln!(pbrt, "  Shape \"disk\" \"float radius\" [{radius}]");
+            }
+            UIObjectType::Quad(width, depth, normal_x, normal_y, normal_z) => {
+                pbrt.push_str(&flat_object_rotate(normal_x, normal_y, normal_z));
+                let (x, z) = (width / 2.0, depth / 2.0);
+                let _ = writeln!(pbrt, "  Shape \"trianglemesh\" \"point3 P\" [{} 0 {}  {x} 0 {}  {x} 0 {z}  {} 0 {z}] \"integer indices\" [0 2 1 0 3 2]",
+                                 -x, -z, -z, -x);
+            }
+            UIObjectType::Heightfield(ref heightfield) => {
+                pbrt.push_str(&heightfield_shape(heightfield));
+            }
+            UIObjectType::Csg(_) => {
+                pbrt.push_str("  # CSG objects have no counterpart in PBRT and are left out\n");
+            }
+        }
+        pbrt.push_str("AttributeEnd\n\n");
+    }
+
+    pbrt
+}
+
+/// Returns the scale which restores the brightness of the emission spectrum after PBRT normalized
+/// it to a luminance of 1, None if the spectrum has no luminance and can not be normalized.
+/// Writes the material unless it was written before. The materials of a mix are written first, 
+/// so that the mix can refer to them by name. 
+fn write_material(pbrt: &mut String, material: &UIMaterial, written_materials: &mut HashSet<u32>) {
+    let id = material.id;
+    if !written_materials.insert(id) {
+        return;
+    }
+    
+    if let Some(mix) = &material.mix {
+        write_material(pbrt, &mix.first.borrow(), written_materials);
+        write_material(pbrt, &mix.second.borrow(), written_materials);
+        let _ = writeln!(pbrt, "# Material \"{}\"", escape(&material.name));
+        let amount = match mix.weight {
+            MixWeight::Constant(share) => share,
+            weight => {
+                let _ = writeln!(pbrt, "# The {} share is not exported, both materials are mixed evenly", 
+                                 weight.to_string().to_lowercase());
+                0.5
+            }
+        };
+        let _ = writeln!(pbrt, "MakeNamedMaterial \"material_{id}\" \"string type\" \"mix\" \
+            \"string materials\" [\"material_{}\" \"material_{}\"] \"float amount\" [{amount}]\n",
+                         mix.first.borrow().id, mix.second.borrow().id);
+        return;
+    }
+    
+    let reflectance = spectrum_parameter("reflectance", &material.spectrum.borrow().spectrum);
+    let _ = writeln!(pbrt, "# Material \"{}\"", escape(&material.name));
+    if let Some(pattern) = &material.pattern {
+        let _ = writeln!(pbrt, "# The {} pattern is not exported", pattern.pattern.to_string().to_lowercase());
+    }
+    if material.fluorescence.is_some() {
+        pbrt.push_str("# The fluorescence is not exported\n");
+    }
+    if material.shadow_catcher {
+        pbrt.push_str("# The shadow catcher is exported as an ordinary surface\n");
+    }
+    let _ = writeln!(pbrt, "MakeNamedMaterial \"material_{id}_diffuse\" \"string type\" \"diffuse\" {reflectance}");
+    let roughness = match material.bitangent_roughness {
+        Some(bitangent_roughness) => format!("\"float uroughness\" [{}] \"float vroughness\" [{bitangent_roughness}]",
+                                             material.roughness),
+        None => format!("\"float roughness\" [{}]", material.roughness),
+    };
+    let _ = writeln!(pbrt, "MakeNamedMaterial \"material_{id}_specular\" \"string type\" \"conductor\" {reflectance} {roughness}");
+    let _ = writeln!(pbrt, "MakeNamedMaterial \"material_{id}\" \"string type\" \"mix\" \
+        \"string materials\" [\"material_{id}_diffuse\" \"material_{id}_specular\"] \"float amount\" [{}]\n",
+                     material.metallicness);
+}
+
+fn emission_scale(spectrum: &Spectrum, light_scale: f32) -> Option<f32> {
+    let photometric = spectrum.get_luminous_value() / MAXIMUM_LUMINOUS_EFFICACY;
+    (photometric > 0.0).then_some(photometric * light_scale)
+}
+
+/// Formats the spectrum as a piecewise linear PBRT spectrum parameter of the given name.
+fn spectrum_parameter(name: &str, spectrum: &Spectrum) -> String {
+    let values: Vec<String> = spectrum.iter().map(|(wavelength, intensity)| format!("{wavelength} {intensity}")).collect();
+    format!("\"spectrum {name}\" [{}]", values.join(" "))
+}
+
+/// Formats the rotation which turns the y axis onto the given normal as a PBRT rotation, see 
+/// [flat_object_rotation]. 
+fn flat_object_rotate(normal_x: f32, normal_y: f32, normal_z: f32) -> String {
+    match flat_object_rotation(&vector![normal_x, normal_y, normal_z]).axis_angle() {
+        Some((axis, angle)) => format!("  Rotate {} {} {} {}\n", angle.to_degrees(), axis.x, axis.y, axis.z),
+        None => String::new(),
+    }
+}
+
+/// Formats a box of the given lengths centered at the origin as a PBRT triangle mesh.
+fn box_shape(x_length: f32, y_length: f32, z_length: f32) -> String {
+    let points: Vec<String> = BOX_CORNER_SIGNS.iter()
+        .map(|(x, y, z)| format!("{} {} {}", x * x_length / 2.0, y * y_length / 2.0, z * z_length / 2.0))
+        .collect();
+    let indices: Vec<String> = BOX_TRIANGLES.iter().map(|i| i.to_string()).collect();
+    format!("  Shape \"trianglemesh\" \"point3 P\" [{}] \"integer indices\" [{}]\n", points.join("  "), indices.join(" "))
+}
+
+/// Formats the surface of the heightfield as a PBRT triangle mesh with the same triangles as the 
+/// renderer. 
+fn heightfield_shape(heightfield: &UIHeightfield) -> String {
+    let grid = &heightfield.grid;
+    let (resolution_x, resolution_z) = grid.get_resolution();
+    let dimensions = vector![heightfield.width, heightfield.height, heightfield.depth];
+    let mut points = Vec::with_capacity(resolution_x * resolution_z);
+    for z in 0..resolution_z {
+        for x in 0..resolution_x {
+            let point = grid.get_point(x, z, &dimensions);
+            points.push(format!("{} {} {}", point.x, point.y, point.z));
+        }
+    }
+    let mut indices = Vec::with_capacity(6 * (resolution_x - 1) * (resolution_z - 1));
+    for z in 0..resolution_z - 1 {
+        for x in 0..resolution_x - 1 {
+            let corner = |x: usize, z: usize| (z * resolution_x + x).to_string();
+            indices.extend([corner(x, z), corner(x, z + 1), corner(x + 1, z + 1)]);
+            indices.extend([corner(x, z), corner(x + 1, z + 1), corner(x + 1, z)]);
+        }
+    }
+    format!("  Shape \"trianglemesh\" \"point3 P\" [{}] \"integer indices\" [{}]\n", points.join("  "), indices.join(" "))
+}
+
+/// Escapes quotes and backslashes of names written into strings of the scene file.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// An error type used when exporting a scene. Specific details of the error are given in the
+/// error String.
+#[derive(Debug)]
+pub struct PbrtExportError {
+    pub error: String,
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use nalgebra::{Point3, Vector3};
+use crate::radiometry::{SpectralIrradiance, SpectralPower};
+use crate::spectrum::Spectrum;
+
+/// A photon which was traced from a light source and came to rest on a diffuse surface.
+pub struct Photon {
+    pub position: Point3<f32>,
+    /// The direction the photon travelled in when it arrived.
+    pub direction: Vector3<f32>,
+    pub power: SpectralPower,
+}
+
+/// The photons of one frame, sorted into a uniform grid with cells as large as the gather radius.
+/// The irradiance at a point is estimated from the power of all photons within the radius, so
+/// only the cell of the point and its 26 neighbours have to be searched.
+pub struct PhotonMap {
+    radius: f32,
+    cells: HashMap<(i32, i32, i32), Vec<Photon>>,
+}
+
+impl PhotonMap {
+    /// Sorts the photons into the grid. The radius has to be positive.
+    pub fn new(photons: Vec<Photon>, radius: f32) -> Self {
+        let mut cells: HashMap<(i32, i32, i32), Vec<Photon>> = HashMap::new();
+        for photon in photons {
+            cells.entry(cell_of(&photon.position, radius)).or_default().push(photon);
+        }
+        PhotonMap { radius, cells }
+    }
+
+    /// Estimates the irradiance arriving at the given point of a surface with the given normal, by
+    /// spreading the power of all photons within the gather radius over the disc of the radius.
+    /// Photons arriving from below the surface are left out, so that light does not leak through
+    /// thin objects.
+    pub fn irradiance_at(&self, point: &Point3<f32>, normal: &Vector3<f32>, example_spectrum: &Spectrum)
+        -> SpectralIrradiance {
+        let mut power = SpectralPower::zero(example_spectrum);
+        let (x, y, z) = cell_of(point, self.radius);
+        let radius_squared = self.radius * self.radius;
+        for cell in (x - 1..=x + 1).flat_map(|x| (y - 1..=y + 1).flat_map(move |y| (z - 1..=z + 1).map(move |z| (x, y, z)))) {
+            let Some(photons) = self.cells.get(&cell) else {
+                continue;
+            };
+            for photon in photons {
+                if (photon.position - point).magnitude_squared() <= radius_squared
+                    && photon.direction.dot(normal) < 0.0 {
+                    power += &photon.power;
+                }
+            }
+        }
+        power.irradiance_over(PI * radius_squared)
+    }
+}
+
+/// Returns the grid cell the point lies in.
+fn cell_of(point: &Point3<f32>, cell_size: f32) -> (i32, i32, i32) {
+    ((point.x / cell_size).floor() as i32, (point.y / cell_size).floor() as i32, (point.z / cell_size).floor() as i32)
+}
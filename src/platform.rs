@@ -0,0 +1,58 @@
+//! The threads and clocks of the app, which do not work the same on every target. Natively,
+//! threads are threads of the operating system and the clocks come from it as well. In the
+//! browser (wasm32), `std::thread::spawn` and the clocks of `std::time` panic: threads have to be
+//! web workers sharing the memory of the page and the time comes from `performance.now()`. All
+//! threads and clocks of the app go through this module, which picks the implementation by the
+//! target architecture. The clocks come from `web_time`, which falls back to `std::time` natively.
+//! <br>
+//! In the browser, the threads are taken from the global rayon pool. Its web workers have to be
+//! started by the page before the app, e.g. with `initThreadPool` of `wasm-bindgen-rayon`, with
+//! the module compiled with the `atomics` and `bulk-memory` target features so that they share
+//! its memory. The pool needs at least one worker more than render threads are set, one is taken
+//! by the render loop itself and one by each render thread for as long as the render runs.
+
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Runs the closure on a new thread which is not joined. 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn(f: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(f);
+}
+
+/// Runs the closure on a web worker of the global rayon pool. 
+#[cfg(target_arch = "wasm32")]
+pub fn spawn(f: impl FnOnce() + Send + 'static) {
+    rayon::spawn(f);
+}
+
+/// Builds the pool of threads the tiles of a frame are rendered on. 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_thread_pool(nbr_of_threads: usize) -> Result<rayon::ThreadPool, rayon::ThreadPoolBuildError> {
+    rayon::ThreadPoolBuilder::new().num_threads(nbr_of_threads).build()
+}
+
+/// Builds the pool of threads the tiles of a frame are rendered on. The browser can not start
+/// threads on its own, so each thread of the pool runs on a web worker of the global rayon pool
+/// until the pool is dropped. 
+#[cfg(target_arch = "wasm32")]
+pub fn render_thread_pool(nbr_of_threads: usize) -> Result<rayon::ThreadPool, rayon::ThreadPoolBuildError> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(nbr_of_threads)
+        .spawn_handler(|thread| {
+            rayon::spawn(move || thread.run());
+            Ok(())
+        })
+        .build()
+}
+
+/// Returns the memory available to new allocations in bytes, None if it is unknown. Only known on 
+/// Linux, where it is read from `/proc/meminfo`. 
+pub fn available_memory() -> Option<u64> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kibibytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kibibytes * 1024)
+}
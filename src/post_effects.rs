@@ -0,0 +1,133 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::sampler;
+
+/// The default parameters of the effects when they are added to the chain.
+pub const VIGNETTE_STRENGTH_DEFAULT: f32 = 0.3;
+pub const CHROMATIC_FRINGE_AMOUNT_DEFAULT: f32 = 0.003;
+pub const CONTRAST_DEFAULT: f32 = 1.2;
+pub const GRAIN_ISO_DEFAULT: f32 = 1600.0;
+
+/// The ISO speed the sensor of the grain is at its base sensitivity at. 
+const GRAIN_BASE_ISO: f32 = 100.0;
+/// The electrons a pixel of the sensor of the grain collects until it is white at the base ISO, 
+/// typical for a full frame camera. Higher ISO speeds amplify fewer electrons to white. 
+const GRAIN_FULL_WELL_ELECTRONS: f32 = 20000.0;
+/// The noise of the readout of the sensor in electrons, independent of the light. 
+const GRAIN_READ_NOISE_ELECTRONS: f32 = 3.0;
+/// Offsets the random numbers of the grain from the dither noise of the 16 bit conversion, which 
+/// hashes the same pixel positions. 
+const GRAIN_RANDOM_STREAM: u32 = 16;
+
+/// The linear value the contrast curve pivots around, middle gray.
+const CONTRAST_PIVOT: f32 = 0.18;
+
+/// A stylistic effect applied to the displayed and exported image after it is tone mapped, in the
+/// order of the chain the effects are in. Unlike the render itself, the effects are not physically
+/// based, they only imitate the look of real lenses and cameras, so the float colors of the render 
+/// are kept without them.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PostEffect {
+    /// Darkens the image towards its corners like the light fall off of a lens. The value is how
+    /// much the corners are darkened, in range \[0; 1].
+    Vignette(f32),
+    /// Spreads red outwards and blue inwards towards the borders, like the lateral chromatic
+    /// aberration of a simple lens. The value is how far the colors are apart in the corners, as a
+    /// fraction of the distance from the center.
+    ChromaticFringe(f32),
+    /// Steepens the tones around middle gray with values above 1 and flattens them below.
+    Contrast(f32),
+    /// Adds the noise of a camera sensor at the given ISO speed: the shot noise of the counted 
+    /// light, which grows with the square root of the brightness, and the constant read noise. The 
+    /// higher the ISO speed, the fewer electrons make up white and the stronger the noise. 
+    Grain(f32),
+}
+
+impl PostEffect {
+    /// All effects with their default parameters, in the order they are offered in.
+    pub const ALL: [PostEffect; 4] = [PostEffect::Vignette(VIGNETTE_STRENGTH_DEFAULT),
+                                      PostEffect::ChromaticFringe(CHROMATIC_FRINGE_AMOUNT_DEFAULT),
+                                      PostEffect::Contrast(CONTRAST_DEFAULT),
+                                      PostEffect::Grain(GRAIN_ISO_DEFAULT)];
+
+    /// Applies the effect to the r, g, b, a values of the pixels of an image with the given size.
+    /// The colors are expected in range \[0; 1] and stay within it, the alpha is kept.
+    pub fn apply(&self, width: u32, height: u32, colors: &mut [[f32; 4]]) {
+        let (width, height) = (width as usize, height as usize);
+        let center = (width as f32 / 2.0, height as f32 / 2.0);
+        let corner_distance = (center.0 * center.0 + center.1 * center.1).sqrt().max(f32::MIN_POSITIVE);
+        match *self {
+            PostEffect::Vignette(strength) => {
+                for (i, color) in colors.iter_mut().enumerate() {
+                    let (dx, dy) = ((i % width) as f32 + 0.5 - center.0, (i / width) as f32 + 0.5 - center.1);
+                    let distance_squared = (dx * dx + dy * dy) / (corner_distance * corner_distance);
+                    let factor = 1.0 - strength.clamp(0.0, 1.0) * distance_squared.min(1.0);
+                    (0..3).for_each(|c| color[c] *= factor);
+                }
+            }
+            PostEffect::ChromaticFringe(amount) => {
+                let source = colors.to_vec();
+                for (i, color) in colors.iter_mut().enumerate() {
+                    let (dx, dy) = ((i % width) as f32 + 0.5 - center.0, (i / width) as f32 + 0.5 - center.1);
+                    //red appears magnified, so it is looked up closer to the center, blue further out
+                    for (channel, scale) in [(0, 1.0 - amount / 2.0), (2, 1.0 + amount / 2.0)] {
+                        let (x, y) = (center.0 + dx * scale - 0.5, center.1 + dy * scale - 0.5);
+                        color[channel] = sample_bilinear(&source, width, height, x, y, channel);
+                    }
+                }
+            }
+            PostEffect::Contrast(contrast) => {
+                for color in colors.iter_mut() {
+                    for value in color.iter_mut().take(3) {
+                        *value = (CONTRAST_PIVOT * (*value / CONTRAST_PIVOT).max(0.0).powf(contrast)).min(1.0);
+                    }
+                }
+            }
+            PostEffect::Grain(iso) => {
+                let white_electrons = GRAIN_FULL_WELL_ELECTRONS * GRAIN_BASE_ISO / iso.max(GRAIN_BASE_ISO);
+                for (i, color) in colors.iter_mut().enumerate() {
+                    let (x, y) = ((i % width) as u32, (i / width) as u32);
+                    for (channel, value) in color.iter_mut().take(3).enumerate() {
+                        let electrons = *value * white_electrons;
+                        let deviation = (electrons + GRAIN_READ_NOISE_ELECTRONS.powi(2)).sqrt();
+                        let noise = standard_normal(x, y, GRAIN_RANDOM_STREAM + channel as u32);
+                        *value = ((electrons + noise * deviation) / white_electrons).clamp(0.0, 1.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Display for PostEffect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PostEffect::Vignette(_) => write!(f, "Vignette"),
+            PostEffect::ChromaticFringe(_) => write!(f, "Chromatic fringe"),
+            PostEffect::Contrast(_) => write!(f, "Contrast"),
+            PostEffect::Grain(_) => write!(f, "Grain"),
+        }
+    }
+}
+
+/// Returns a normally distributed random number with mean 0 and standard deviation 1, the same 
+/// for the same arguments, via the Box-Muller transform. 
+fn standard_normal(x: u32, y: u32, z: u32) -> f32 {
+    let (first, second, _) = sampler::random_pcg3d(x, y, z);
+    let radius = (-2.0 * (1.0 - first).max(f32::MIN_POSITIVE).ln()).sqrt();
+    radius * (std::f32::consts::TAU * second).cos()
+}
+
+/// Returns a channel of the colors at a position between the pixel centers, interpolated linearly
+/// between the four closest pixels. Positions outside the image take the closest border pixel.
+fn sample_bilinear(colors: &[[f32; 4]], width: usize, height: usize, x: f32, y: f32, channel: usize) -> f32 {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let value = |x: usize, y: usize| colors[y * width + x][channel];
+    let top = value(x0, y0) * (1.0 - fx) + value(x1, y0) * fx;
+    let bottom = value(x0, y1) * (1.0 - fx) + value(x1, y1) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
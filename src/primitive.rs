@@ -0,0 +1,39 @@
+//! The geometry of the shapes in a scene. The built-in shapes, from spheres to heightfields, 
+//! implement [Primitive] and the shaders reach them only through it. A type implementing it is 
+//! placed into a scene with [Aabb::new_custom](crate::shader::Aabb::new_custom), after which it is 
+//! lit, shaded, combined by CSG and accelerated by the bounding volume hierarchy like the built-in 
+//! shapes. This allows crates depending on the renderer to add shapes, e.g. a torus or a triangle 
+//! mesh, without touching the shaders. 
+
+use nalgebra::{Point3, Vector3};
+
+/// The geometry of a shape. All points and directions are given in world space, the directions
+/// of rays are normalized. Rays are tested against the bounds first, [Primitive::intersect] is
+/// only called for rays hitting them.
+pub trait Primitive: Send + Sync {
+    /// Returns the smallest and the largest corner of the axis aligned box enclosing the shape.
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>);
+
+    /// Returns the distance along the ray to the first point of the surface which is not behind
+    /// the origin of the ray, None if the ray misses the shape.
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32>;
+
+    /// Returns the normal of the surface at the given point on it, where it was hit by the given
+    /// ray, of unit length. The normals of closed shapes point outwards, those of flat shapes may
+    /// point to either side, e.g. towards the ray for shapes seen from both sides.
+    fn normal(&self, origin: &Point3<f32>, direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32>;
+
+    /// Returns the stretches of the ray inside the shape as pairs of the distances where the ray
+    /// enters and leaves it, sorted by distance and including stretches behind the origin of the
+    /// ray. Only needed for shapes used as a part of a CSG object, shapes enclosing no volume
+    /// return none, which is the default.
+    fn solid_intervals(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>) -> Vec<(f32, f32)> {
+        Vec::new()
+    }
+
+    /// Returns the texture coordinates in range \[0; 1] at the given point on the surface, None
+    /// if the shape is not textured, which is the default.
+    fn uv(&self, _point: &Point3<f32>, _normal: &Vector3<f32>) -> Option<(f32, f32)> {
+        None
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt::{Display, Formatter};
+use nalgebra::Point3;
+use serde::{Deserialize, Serialize};
+
+/// How much each octave of fractal noise contributes compared to the previous one.
+const NOISE_PERSISTENCE: f32 = 0.5;
+
+/// A pattern which blends the reflectance of a material between its own spectrum and a second
+/// one. Patterns are solid: they are evaluated at the hit point in world space, so objects look
+/// as if they were carved out of a patterned block.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Pattern {
+    /// A three dimensional checkerboard of cubes with the given edge length.
+    Checker { size: f32 },
+    /// Fractal value noise with features of roughly the given size. Every further octave adds
+    /// details of half the size and half the amplitude.
+    Noise { size: f32, octaves: u32, seed: u32 },
+    /// A linear transition along the axis, from only the material spectrum at the start to only
+    /// the second spectrum at the end.
+    Gradient { axis: Axis, start: f32, end: f32 },
+}
+
+impl Pattern {
+    pub fn default_checker() -> Self {
+        Pattern::Checker { size: 1.0 }
+    }
+
+    pub fn default_noise() -> Self {
+        Pattern::Noise { size: 1.0, octaves: 4, seed: 1 }
+    }
+
+    pub fn default_gradient() -> Self {
+        Pattern::Gradient { axis: Axis::Y, start: 0.0, end: 5.0 }
+    }
+
+    /// Returns the share of the second spectrum at the given point, in range \[0; 1].
+    pub fn evaluate(&self, point: &Point3<f32>) -> f32 {
+        match *self {
+            Pattern::Checker { size } => {
+                let cell = (point.x / size).floor() + (point.y / size).floor() + (point.z / size).floor();
+                if cell.rem_euclid(2.0) < 1.0 { 0.0 } else { 1.0 }
+            }
+            Pattern::Noise { size, octaves, seed } => {
+                //the sum of all octaves approaches twice the amplitude of the first
+                let noise = fractal_noise(seed, point.x / size, point.y / size, point.z / size, octaves.max(1));
+                (noise / (2.0 - NOISE_PERSISTENCE.powi(octaves.max(1) as i32 - 1))).clamp(0.0, 1.0)
+            }
+            Pattern::Gradient { axis, start, end } => {
+                let position = match axis {
+                    Axis::X => point.x,
+                    Axis::Y => point.y,
+                    Axis::Z => point.z,
+                };
+                if start == end {
+                    return if position < start { 0.0 } else { 1.0 };
+                }
+                ((position - start) / (end - start)).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Checker { .. } => write!(f, "Checker"),
+            Pattern::Noise { .. } => write!(f, "Noise"),
+            Pattern::Gradient { .. } => write!(f, "Gradient"),
+        }
+    }
+}
+
+/// One of the three axes of the world.
+#[derive(Clone, Copy, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+}
+
+impl Display for Axis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Axis::X => write!(f, "X"),
+            Axis::Y => write!(f, "Y"),
+            Axis::Z => write!(f, "Z"),
+        }
+    }
+}
+
+/// Sums octaves of value noise, each of twice the frequency and half the amplitude of the
+/// previous one. The result lies in range \[0; 2).
+pub fn fractal_noise(seed: u32, x: f32, y: f32, z: f32, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    for octave in 0..octaves {
+        sum += amplitude * value_noise(seed.wrapping_add(octave), x * frequency, y * frequency, z * frequency);
+        amplitude *= NOISE_PERSISTENCE;
+        frequency *= 2.0;
+    }
+    sum
+}
+
+/// Smoothly interpolates between random values at the integer lattice points.
+fn value_noise(seed: u32, x: f32, y: f32, z: f32) -> f32 {
+    let (x_floor, y_floor, z_floor) = (x.floor(), y.floor(), z.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (u, v, w) = (smooth(x - x_floor), smooth(y - y_floor), smooth(z - z_floor));
+    let (x_lattice, y_lattice, z_lattice) = (x_floor as i32, y_floor as i32, z_floor as i32);
+
+    //interpolates along x and z on one layer of the lattice
+    let layer = |y_lattice: i32| {
+        let value_00 = lattice_value(seed, x_lattice, y_lattice, z_lattice);
+        let value_10 = lattice_value(seed, x_lattice + 1, y_lattice, z_lattice);
+        let value_01 = lattice_value(seed, x_lattice, y_lattice, z_lattice + 1);
+        let value_11 = lattice_value(seed, x_lattice + 1, y_lattice, z_lattice + 1);
+        let near = value_00 + (value_10 - value_00) * u;
+        let far = value_01 + (value_11 - value_01) * u;
+        near + (far - near) * w
+    };
+    let bottom = layer(y_lattice);
+    if v == 0.0 {
+        return bottom;
+    }
+    bottom + (layer(y_lattice + 1) - bottom) * v
+}
+
+/// Hashes the lattice point into a pseudo random value in range \[0; 1).
+fn lattice_value(seed: u32, x: i32, y: i32, z: i32) -> f32 {
+    let mut hash = seed.wrapping_mul(0x9E37_79B9) ^ (x as u32).wrapping_mul(0x85EB_CA6B)
+        ^ (y as u32).wrapping_mul(0x27D4_EB2F) ^ (z as u32).wrapping_mul(0xC2B2_AE35);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7FEB_352D);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846C_A68B);
+    hash ^= hash >> 16;
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
@@ -0,0 +1,173 @@
+//! A tiny instrumenting profiler for the render process. It is only active if the crate is built
+//! with the `profiling` feature (`cargo build -r --features profiling`), otherwise the
+//! [profile_scope] macro expands to nothing and no overhead is added to the shaders. <br>
+//! Each instrumented scope records its time into a per thread table keyed by the stack of scopes
+//! it is nested in. The tables are flushed into one global table regularly, which is exported as
+//! folded stacks (readable by flamegraph.pl or inferno) and as a JSON summary after each render.
+
+/// Starts a profiled scope of the given [ProfileCategory] which lasts until the end of the
+/// enclosing block. Does nothing unless the `profiling` feature is enabled.
+#[cfg(feature = "profiling")]
+macro_rules! profile_scope {
+    ($category:ident) => {
+        let _profile_scope_guard = crate::profiler::ScopeGuard::new(crate::profiler::ProfileCategory::$category);
+    };
+}
+
+/// Starts a profiled scope of the given [ProfileCategory] which lasts until the end of the
+/// enclosing block. Does nothing unless the `profiling` feature is enabled.
+#[cfg(not(feature = "profiling"))]
+macro_rules! profile_scope {
+    ($category:ident) => {};
+}
+
+pub(crate) use profile_scope;
+
+#[cfg(feature = "profiling")]
+pub use implementation::*;
+
+#[cfg(feature = "profiling")]
+mod implementation {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fmt::Write as _;
+    use std::path::Path;
+    use std::sync::Mutex;
+    use crate::platform::Instant;
+
+    /// The stages of the render process which are distinguished by the profiler.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum ProfileCategory {
+        RayGeneration,
+        Intersection,
+        Shading,
+        SpectrumOps,
+    }
+
+    impl ProfileCategory {
+        fn name(&self) -> &'static str {
+            match self {
+                ProfileCategory::RayGeneration => "ray_generation",
+                ProfileCategory::Intersection => "intersection",
+                ProfileCategory::Shading => "shading",
+                ProfileCategory::SpectrumOps => "spectrum_ops",
+            }
+        }
+    }
+
+    /// The self time (time not spent in nested scopes) and the number of calls of one stack of
+    /// scopes.
+    #[derive(Clone, Copy, Default)]
+    struct StackEntry {
+        self_nanos: u64,
+        calls: u64,
+    }
+
+    /// The state of a single thread. The stack holds the currently open scopes together with the
+    /// time spent in their already closed children.
+    #[derive(Default)]
+    struct ThreadState {
+        stack: Vec<(ProfileCategory, u64)>,
+        table: HashMap<Vec<ProfileCategory>, StackEntry>,
+    }
+
+    thread_local! {
+        static THREAD_STATE: RefCell<ThreadState> = RefCell::new(ThreadState::default());
+    }
+
+    static GLOBAL_TABLE: Mutex<Option<HashMap<Vec<ProfileCategory>, StackEntry>>> = Mutex::new(None);
+
+    /// Guard which records the time between its creation and its drop. Created by the
+    /// [profile_scope](crate::profiler::profile_scope) macro.
+    pub struct ScopeGuard {
+        start: Instant,
+    }
+
+    impl ScopeGuard {
+        pub fn new(category: ProfileCategory) -> Self {
+            THREAD_STATE.with(|state| state.borrow_mut().stack.push((category, 0)));
+            ScopeGuard { start: Instant::now() }
+        }
+    }
+
+    impl Drop for ScopeGuard {
+        fn drop(&mut self) {
+            let elapsed = self.start.elapsed().as_nanos() as u64;
+            THREAD_STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                let key: Vec<ProfileCategory> = state.stack.iter().map(|(category, _)| *category).collect();
+                let (_, children_nanos) = state.stack.pop().unwrap();
+                if let Some(parent) = state.stack.last_mut() {
+                    parent.1 += elapsed;
+                }
+
+                let entry = state.table.entry(key).or_default();
+                entry.self_nanos += elapsed.saturating_sub(children_nanos);
+                entry.calls += 1;
+            });
+        }
+    }
+
+    /// Moves the measurements of the calling thread into the global table. Should be called by
+    /// every worker thread after finishing a unit of work.
+    pub fn flush_thread() {
+        let table = THREAD_STATE.with(|state| std::mem::take(&mut state.borrow_mut().table));
+        let mut global = GLOBAL_TABLE.lock().unwrap();
+        let global = global.get_or_insert_with(HashMap::new);
+        for (key, entry) in table {
+            let global_entry = global.entry(key).or_default();
+            global_entry.self_nanos += entry.self_nanos;
+            global_entry.calls += entry.calls;
+        }
+    }
+
+    /// Discards all measurements taken so far. Called at the beginning of a render.
+    pub fn reset() {
+        *GLOBAL_TABLE.lock().unwrap() = None;
+    }
+
+    /// Writes the collected measurements into two files next to the given base path:
+    /// `<base>.folded` holding folded stacks in microseconds for flamegraph tools and
+    /// `<base>.json` holding the self time and number of calls per stack and per category.
+    pub fn export_report(base_path: &Path) -> std::io::Result<()> {
+        let global = GLOBAL_TABLE.lock().unwrap();
+        let empty = HashMap::new();
+        let table = global.as_ref().unwrap_or(&empty);
+
+        let mut stacks: Vec<(&Vec<ProfileCategory>, &StackEntry)> = table.iter().collect();
+        stacks.sort_by_key(|(key, _)| key.iter().map(|c| c.name()).collect::<Vec<_>>());
+
+        let mut folded = String::new();
+        for (key, entry) in &stacks {
+            let names: Vec<&str> = key.iter().map(|c| c.name()).collect();
+            let _ = writeln!(folded, "{} {}", names.join(";"), entry.self_nanos / 1000);
+        }
+        std::fs::write(base_path.with_extension("folded"), folded)?;
+
+        let mut per_category: HashMap<ProfileCategory, StackEntry> = HashMap::new();
+        for (key, entry) in &stacks {
+            let category_entry = per_category.entry(*key.last().unwrap()).or_default();
+            category_entry.self_nanos += entry.self_nanos;
+            category_entry.calls += entry.calls;
+        }
+
+        let mut json = String::from("{\n  \"categories\": {\n");
+        let categories = [ProfileCategory::RayGeneration, ProfileCategory::Intersection,
+            ProfileCategory::Shading, ProfileCategory::SpectrumOps];
+        for (i, category) in categories.iter().enumerate() {
+            let entry = per_category.get(category).copied().unwrap_or_default();
+            let separator = if i + 1 < categories.len() { "," } else { "" };
+            let _ = writeln!(json, "    \"{}\": {{\"self_ms\": {:.3}, \"calls\": {}}}{separator}",
+                             category.name(), entry.self_nanos as f64 / 1e6, entry.calls);
+        }
+        json.push_str("  },\n  \"stacks\": [\n");
+        for (i, (key, entry)) in stacks.iter().enumerate() {
+            let names: Vec<String> = key.iter().map(|c| format!("\"{}\"", c.name())).collect();
+            let separator = if i + 1 < stacks.len() { "," } else { "" };
+            let _ = writeln!(json, "    {{\"stack\": [{}], \"self_ms\": {:.3}, \"calls\": {}}}{separator}",
+                             names.join(", "), entry.self_nanos as f64 / 1e6, entry.calls);
+        }
+        json.push_str("  ]\n}\n");
+        std::fs::write(base_path.with_extension("json"), json)
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use crate::shader::RayStatistics;
+
+/// A snapshot of the progress of a render process. The progress is reported hierarchically: the
+/// finished frames, the finished tiles of the current frame and the finished rows of the tiles
+/// currently being rendered.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RenderProgress {
+    pub frames_done: u32,
+    pub nbr_of_frames: u32,
+    /// The frames which were already done when the render was resumed, included in frames_done.
+    pub frames_resumed: u32,
+    pub tiles_done: u32,
+    pub nbr_of_tiles: u32,
+    /// The finished rows of all tiles of the current frame, those of finished tiles included.
+    pub rows_done: u32,
+    /// The rows of all tiles of the current frame, which equals the height of the image times the
+    /// number of tile columns.
+    pub nbr_of_rows: u32,
+    /// The rays shot since the render started, shadow rays included.
+    pub rays_traced: u64,
+}
+
+impl RenderProgress {
+    /// The progress of the current frame between 0 and 1, with the granularity of tile rows.
+    pub fn frame(&self) -> f32 {
+        if self.nbr_of_rows == 0 {
+            return 0.0;
+        }
+        self.rows_done as f32 / self.nbr_of_rows as f32
+    }
+
+    /// The progress of the whole render between 0 and 1. Partially finished frames count with the
+    /// share of their finished rows.
+    pub fn overall(&self) -> f32 {
+        if self.nbr_of_frames == 0 {
+            return 0.0;
+        }
+        ((self.frames_done as f32 + self.frame()) / self.nbr_of_frames as f32).min(1.0)
+    }
+
+    /// Estimates the time the render still takes from the given time it took so far, assuming
+    /// that the rest of the rows take as long as those rendered so far. The frames of a resumed
+    /// render which were done before took no time. Returns None before any progress was made.
+    pub fn remaining_time(&self, elapsed: Duration) -> Option<Duration> {
+        if self.nbr_of_frames == 0 {
+            return None;
+        }
+        let resumed = self.frames_resumed as f32 / self.nbr_of_frames as f32;
+        let done = self.overall() - resumed;
+        if done <= 0.0 {
+            return None;
+        }
+        Some(elapsed.mul_f32((1.0 - self.overall()) / done))
+    }
+}
+
+/// Collects the progress of a render process from the worker threads. Workers report every
+/// finished row and tile together with the rays it took, the render thread every frame.
+/// [ProgressTracker::snapshot] and [ProgressTracker::statistics] can be called from any thread.
+pub struct ProgressTracker {
+    nbr_of_frames: u32,
+    frames_resumed: u32,
+    frames_done: AtomicU32,
+    tiles_done: AtomicU32,
+    nbr_of_tiles: AtomicU32,
+    rows_done: AtomicU32,
+    nbr_of_rows: AtomicU32,
+    statistics: Mutex<RayStatistics>,
+}
+
+impl ProgressTracker {
+    /// Creates the tracker of a render of the given number of frames, of which the given number
+    /// is already done, e.g. when resuming a render.
+    pub fn new(nbr_of_frames: u32, frames_done: u32) -> Self {
+        ProgressTracker {
+            nbr_of_frames,
+            frames_resumed: frames_done,
+            frames_done: AtomicU32::new(frames_done),
+            tiles_done: AtomicU32::new(0),
+            nbr_of_tiles: AtomicU32::new(0),
+            rows_done: AtomicU32::new(0),
+            nbr_of_rows: AtomicU32::new(0),
+            statistics: Mutex::new(RayStatistics::new()),
+        }
+    }
+
+    /// Resets the tile and row counts for a new frame consisting of the given number of tiles and
+    /// tile rows.
+    pub fn start_frame(&self, nbr_of_tiles: u32, nbr_of_rows: u32) {
+        self.tiles_done.store(0, Ordering::Relaxed);
+        self.rows_done.store(0, Ordering::Relaxed);
+        self.nbr_of_tiles.store(nbr_of_tiles, Ordering::Relaxed);
+        self.nbr_of_rows.store(nbr_of_rows, Ordering::Relaxed);
+    }
+
+    /// Reports a finished row of a tile together with the rays it took.
+    pub fn finish_row(&self, statistics: &RayStatistics) {
+        self.rows_done.fetch_add(1, Ordering::Relaxed);
+        *self.statistics.lock().unwrap() += statistics;
+    }
+
+    pub fn finish_tile(&self) {
+        self.tiles_done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Reports a finished frame and clears the tile and row counts.
+    pub fn finish_frame(&self) {
+        self.frames_done.fetch_add(1, Ordering::Relaxed);
+        self.tiles_done.store(0, Ordering::Relaxed);
+        self.rows_done.store(0, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> RenderProgress {
+        RenderProgress {
+            frames_done: self.frames_done.load(Ordering::Relaxed),
+            nbr_of_frames: self.nbr_of_frames,
+            frames_resumed: self.frames_resumed,
+            tiles_done: self.tiles_done.load(Ordering::Relaxed),
+            nbr_of_tiles: self.nbr_of_tiles.load(Ordering::Relaxed),
+            rows_done: self.rows_done.load(Ordering::Relaxed),
+            nbr_of_rows: self.nbr_of_rows.load(Ordering::Relaxed),
+            rays_traced: self.statistics.lock().unwrap().total(),
+        }
+    }
+
+    /// Returns the rays shot since the render started. Rays of workers on other machines are not
+    /// included.
+    pub fn statistics(&self) -> RayStatistics {
+        *self.statistics.lock().unwrap()
+    }
+}
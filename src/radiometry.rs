@@ -0,0 +1,309 @@
+use std::f32::consts::PI;
+use std::ops::{AddAssign, Deref, Div, Mul, MulAssign};
+use std::sync::Arc;
+use crate::spectrum::Spectrum;
+
+/// Declares a newtype around [Spectrum] for one radiometric quantity. The wrapped spectrum can be
+/// read through Deref, but arithmetic is only implemented where it is physically meaningful, so
+/// that mixing up units in the shaders fails to compile.
+macro_rules! spectral_quantity {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name(Spectrum);
+
+        //not every quantity needs every constructor
+        #[allow(dead_code)]
+        impl $name {
+            pub fn new(spectrum: Spectrum) -> Self {
+                $name(spectrum)
+            }
+
+            /// Creates a quantity of zero with as many samples as the given spectrum.
+            pub fn zero(example_spectrum: &Spectrum) -> Self {
+                $name(Spectrum::new_equal_size_empty_spectrum(example_spectrum))
+            }
+        }
+
+        impl Deref for $name {
+            type Target = Spectrum;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl AddAssign<&$name> for $name {
+            fn add_assign(&mut self, rhs: &$name) {
+                self.0 += &rhs.0;
+            }
+        }
+
+        impl MulAssign<f32> for $name {
+            fn mul_assign(&mut self, rhs: f32) {
+                self.0 *= rhs;
+            }
+        }
+    };
+}
+
+spectral_quantity!(
+    /// Spectral radiance in W/sr/m^2/nm, the light travelling along a ray. This is what the camera
+    /// measures, what glowing surfaces emit and what the path tracer carries from hit to hit.
+    SpectralRadiance
+);
+
+spectral_quantity!(
+    /// Spectral intensity in W/sr/nm, the light a point light emits into each direction. Point
+    /// lights have no surface, so they cannot have a radiance.
+    SpectralIntensity
+);
+
+spectral_quantity!(
+    /// Spectral irradiance in W/m^2/nm, the light arriving at a surface from all directions
+    /// together.
+    SpectralIrradiance
+);
+
+spectral_quantity!(
+    /// The unitless share of each wavelength which is reflected, in range \[0; 1] for physical
+    /// materials. Products of reflectances, such as the throughput of a path, are reflectances
+    /// as well.
+    SpectralReflectance
+);
+
+spectral_quantity!(
+    /// Spectral radiant power in W/nm, the light carried by a photon of the photon map.
+    SpectralPower
+);
+
+impl SpectralRadiance {
+    pub fn into_spectrum(self) -> Spectrum {
+        self.0
+    }
+    
+    /// Sets all samples outside the given range to 0, see [Spectrum::keep_only_sample_range].
+    pub fn keep_only_sample_range(&mut self, first: usize, last: usize) {
+        self.0.keep_only_sample_range(first, last);
+    }
+}
+
+impl SpectralIntensity {
+    /// The total radiant power in W of a point light emitting this intensity evenly into all
+    /// 4π steradians.
+    pub fn get_power(&self) -> f32 {
+        self.0.get_radiance() * 4.0 * PI
+    }
+
+    /// The irradiance this intensity causes on a surface at the given squared distance, which the
+    /// light reaches with the given cosine to the surface normal (inverse square law).
+    pub fn irradiance_at(&self, distance_squared: f32, cos_incident: f32) -> SpectralIrradiance {
+        let mut irradiance = self.0;
+        irradiance *= cos_incident / distance_squared;
+        SpectralIrradiance(irradiance)
+    }
+
+    /// The spectral power of a point light emitting this intensity evenly into all 4π steradians.
+    pub fn get_spectral_power(&self) -> SpectralPower {
+        let mut power = self.0;
+        power *= 4.0 * PI;
+        SpectralPower(power)
+    }
+}
+
+impl SpectralPower {
+    /// The irradiance this power causes when it arrives evenly spread over the given area.
+    pub fn irradiance_over(&self, area: f32) -> SpectralIrradiance {
+        SpectralIrradiance(&self.0 / area)
+    }
+}
+
+impl MulAssign<&SpectralReflectance> for SpectralPower {
+    /// Attenuates the power, e.g. by the reflectance of a surface the photon bounced off.
+    fn mul_assign(&mut self, rhs: &SpectralReflectance) {
+        self.0 *= &rhs.0;
+    }
+}
+
+impl MulAssign<&SpectralReflectance> for SpectralIrradiance {
+    /// Attenuates the irradiance, e.g. by the transmittance of a medium the light passed.
+    fn mul_assign(&mut self, rhs: &SpectralReflectance) {
+        self.0 *= &rhs.0;
+    }
+}
+
+impl SpectralReflectance {
+    /// Creates a reflectance of 1 at all wavelengths with as many samples as the given spectrum,
+    /// the throughput of a path before its first bounce.
+    pub fn one(example_spectrum: &Spectrum) -> Self {
+        let (lowest, highest) = example_spectrum.get_range();
+        SpectralReflectance(Spectrum::new_singular_reflectance_factor(
+            lowest, highest, example_spectrum.get_nbr_of_samples(), 1.0))
+    }
+
+    /// The radiance a lambertian surface with this reflectance sends into every direction when
+    /// receiving the given irradiance. The BRDF of such a surface is the reflectance / π.
+    pub fn reflect_diffuse(&self, irradiance: &SpectralIrradiance) -> SpectralRadiance {
+        SpectralRadiance(&(&self.0 * &irradiance.0) / PI)
+    }
+}
+
+impl Mul<&SpectralRadiance> for &SpectralReflectance {
+    type Output = SpectralRadiance;
+
+    fn mul(self, rhs: &SpectralRadiance) -> Self::Output {
+        SpectralRadiance(&self.0 * &rhs.0)
+    }
+}
+
+impl MulAssign<&SpectralReflectance> for SpectralReflectance {
+    fn mul_assign(&mut self, rhs: &SpectralReflectance) {
+        self.0 *= &rhs.0;
+    }
+}
+
+impl Div<f32> for &SpectralReflectance {
+    type Output = SpectralReflectance;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        SpectralReflectance(&self.0 / rhs)
+    }
+}
+
+/// The fluorescence of a material: a share of the light absorbed at one wavelength is re-emitted
+/// evenly into all directions at longer wavelengths. Entry (o, i) of the matrix is the share of
+/// sample i which is re-emitted at sample o, only entries with o > i are used (Stokes shift).
+#[derive(Clone, Debug)]
+pub struct SpectralReemission {
+    nbr_of_samples: usize,
+    /// Row by row, the row being the sample of the re-emitted light.
+    matrix: Vec<f32>,
+}
+
+impl SpectralReemission {
+    /// Creates the re-emission of a fluorescent material. The excitation is the share of each
+    /// wavelength which is absorbed to be re-emitted, the emission only gives the shape of the
+    /// re-emitted spectrum and is normalized. Of the absorbed photons, the share given by the
+    /// quantum yield is re-emitted, each photon losing the energy of its longer wavelength.
+    /// Emission at wavelengths shorter than the absorbed one is left out.
+    pub fn new(excitation: &Spectrum, emission: &Spectrum, quantum_yield: f32) -> Self {
+        let nbr_of_samples = excitation.get_nbr_of_samples();
+        let wavelengths = excitation.get_wavelengths();
+        let emission_sum: f32 = emission.iter().map(|(_, value)| value.max(0.0)).sum();
+
+        let mut matrix = vec![0.0; nbr_of_samples * nbr_of_samples];
+        if emission_sum > 0.0 {
+            for out in 0..nbr_of_samples {
+                let emission_share = emission[out].max(0.0) / emission_sum;
+                for absorbed in 0..out {
+                    let photon_energy_ratio = wavelengths[absorbed] / wavelengths[out];
+                    matrix[out * nbr_of_samples + absorbed] = quantum_yield * excitation[absorbed].max(0.0)
+                        * emission_share * photon_energy_ratio;
+                }
+            }
+        }
+        SpectralReemission { nbr_of_samples, matrix }
+    }
+
+    /// The radiance a fluorescent surface with this re-emission sends into every direction when
+    /// receiving the given irradiance, the counterpart of [SpectralReflectance::reflect_diffuse].
+    pub fn reemit_diffuse(&self, irradiance: &SpectralIrradiance) -> SpectralRadiance {
+        let mut radiance = apply_matrix(&self.matrix, self.nbr_of_samples, &irradiance.0);
+        radiance /= PI;
+        SpectralRadiance(radiance)
+    }
+}
+
+/// The factor of the radiance gathered by a follow-up ray, or the product of these factors along
+/// a path. Usually a reflectance, which keeps every wavelength apart, but once a path crossed a
+/// fluorescent surface, light of one wavelength may arrive at the camera as light of another. The
+/// transfer then also holds a full matrix, added on top of the reflectance.
+#[derive(Clone, Debug)]
+pub struct SpectralTransfer {
+    reflectance: SpectralReflectance,
+    /// Row by row, the row being the sample of the outgoing light.
+    matrix: Option<Arc<Vec<f32>>>,
+}
+
+impl SpectralTransfer {
+    /// Creates the transfer of a single bounce, scaled by the given factor.
+    pub fn new(reflectance: SpectralReflectance, reemission: Option<&SpectralReemission>, factor: f32) -> Self {
+        let mut reflectance = reflectance;
+        reflectance *= factor;
+        let matrix = reemission.map(|reemission| {
+            Arc::new(reemission.matrix.iter().map(|value| value * factor).collect())
+        });
+        SpectralTransfer { reflectance, matrix }
+    }
+
+    /// The largest factor by which the light of any wavelength leaves the transfer, summed over
+    /// all wavelengths it ends up at.
+    pub fn get_max(&self) -> f32 {
+        let Some(matrix) = &self.matrix else {
+            return self.reflectance.get_max();
+        };
+        let nbr_of_samples = self.reflectance.get_nbr_of_samples();
+        (0..nbr_of_samples)
+            .map(|i| self.reflectance[i] + (0..nbr_of_samples).map(|o| matrix[o * nbr_of_samples + i]).sum::<f32>())
+            .fold(0.0, f32::max)
+    }
+}
+
+impl From<SpectralReflectance> for SpectralTransfer {
+    fn from(reflectance: SpectralReflectance) -> Self {
+        SpectralTransfer { reflectance, matrix: None }
+    }
+}
+
+impl Mul<&SpectralRadiance> for &SpectralTransfer {
+    type Output = SpectralRadiance;
+
+    fn mul(self, rhs: &SpectralRadiance) -> Self::Output {
+        let mut radiance = &self.reflectance * rhs;
+        if let Some(matrix) = &self.matrix {
+            radiance.0 += &apply_matrix(matrix, rhs.get_nbr_of_samples(), &rhs.0);
+        }
+        radiance
+    }
+}
+
+impl MulAssign<&SpectralTransfer> for SpectralTransfer {
+    /// Appends the transfer of the following bounce. With D the reflectances and M the matrices,
+    /// (D + M)(D' + M') = DD' + DM' + MD' + MM'.
+    fn mul_assign(&mut self, rhs: &SpectralTransfer) {
+        let n = self.reflectance.get_nbr_of_samples();
+        let matrix = match (&self.matrix, &rhs.matrix) {
+            (None, None) => None,
+            (own, following) => {
+                let mut matrix = vec![0.0; n * n];
+                for o in 0..n {
+                    for i in 0..n {
+                        let mut value = 0.0;
+                        if let Some(following) = following {
+                            value += self.reflectance[o] * following[o * n + i];
+                        }
+                        if let Some(own) = own {
+                            value += own[o * n + i] * rhs.reflectance[i];
+                            if let Some(following) = following {
+                                value += (0..n).map(|k| own[o * n + k] * following[k * n + i]).sum::<f32>();
+                            }
+                        }
+                        matrix[o * n + i] = value;
+                    }
+                }
+                Some(Arc::new(matrix))
+            }
+        };
+        self.reflectance *= &rhs.reflectance;
+        self.matrix = matrix;
+    }
+}
+
+/// Multiplies the square matrix, stored row by row, with the samples of the spectrum.
+fn apply_matrix(matrix: &[f32], nbr_of_samples: usize, spectrum: &Spectrum) -> Spectrum {
+    let mut result = Spectrum::new_equal_size_empty_spectrum(spectrum);
+    for (o, row) in matrix.chunks_exact(nbr_of_samples).enumerate() {
+        result[o] = row.iter().enumerate().map(|(i, value)| value * spectrum[i]).sum();
+    }
+    result
+}
@@ -0,0 +1,110 @@
+use std::fmt::Write;
+use std::path::Path;
+use crate::loader::{LoadError, LoadProgress};
+use crate::shader::{self, Dimensions, PathSegment, PixelPos, RaytracingUniforms};
+
+/// A path traced for the dump, together with the pixel it belongs to.
+struct PathRecord {
+    pixel: (u32, u32),
+    rgb: (f32, f32, f32),
+    segments: Vec<PathSegment>,
+}
+
+/// Traces the paths of the given number of pixels, spread evenly over the image, and writes every
+/// ray of them into the given file. Files ending in .csv are written as CSV with one line per ray,
+/// every other file as JSON. The object names have to be given in the order of the objects in the
+/// uniforms. Only the first frame is traced, so the same settings give the same paths.
+pub fn dump_paths(mut uniforms: RaytracingUniforms, width: u32, height: u32, nbr_of_paths: u32,
+                  object_names: &[String], path: &Path, progress: &LoadProgress) -> Result<(), LoadError> {
+    uniforms.set_frame(0);
+    let nbr_of_pixels = width as u64 * height as u64;
+    let nbr_of_paths = (nbr_of_paths as u64).min(nbr_of_pixels);
+
+    let mut records = Vec::with_capacity(nbr_of_paths as usize);
+    for i in 0..nbr_of_paths {
+        if progress.is_cancelled() {
+            return Err(LoadError::Cancelled);
+        }
+        let pixel_index = (i * nbr_of_pixels + nbr_of_pixels / 2) / nbr_of_paths;
+        let pixel = ((pixel_index % width as u64) as u32, (pixel_index / width as u64) as u32);
+
+        let (spectrum, segments) = shader::trace_path(
+            PixelPos {x: pixel.0, y: pixel.1},
+            Dimensions {width, height},
+            &uniforms,
+        );
+        records.push(PathRecord {pixel, rgb: shader::spectrum_to_rgb(&spectrum, &uniforms), segments});
+        progress.set((i + 1) as f32 / nbr_of_paths as f32);
+    }
+
+    let wavelengths = uniforms.example_spectrum.get_wavelengths();
+    let is_csv = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let content = if is_csv {
+        to_csv(&records, &wavelengths, object_names)
+    } else {
+        to_json(&records, &wavelengths, object_names)
+    };
+    std::fs::write(path, content)
+        .map_err(|e| LoadError::Failed(format!("Could not write {}: {e}", path.display())))
+}
+
+/// Writes the paths as JSON. The file holds the sampled wavelengths and a list of paths, each with
+/// its pixel, its final color and its rays in the order they were shot.
+fn to_json(records: &[PathRecord], wavelengths: &[f32], object_names: &[String]) -> String {
+    let list = |values: &mut dyn Iterator<Item = f32>| {
+        values.map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+    };
+
+    let mut json = format!("{{\n  \"wavelengths\": [{}],\n  \"paths\": [\n", list(&mut wavelengths.iter().copied()));
+    for (i, record) in records.iter().enumerate() {
+        let (r, g, b) = record.rgb;
+        let _ = writeln!(json, "    {{\"pixel\": [{}, {}], \"rgb\": [{r}, {g}, {b}], \"segments\": [",
+                         record.pixel.0, record.pixel.1);
+        for (j, segment) in record.segments.iter().enumerate() {
+            let (hit_object, hit_distance) = match segment.hit {
+                Some((index, distance)) => (format!("\"{}\"", escape_json(&object_names[index])), distance.to_string()),
+                None => ("null".to_string(), "null".to_string()),
+            };
+            let separator = if j + 1 < record.segments.len() { "," } else { "" };
+            let _ = writeln!(json, "      {{\"depth\": {}, \"kind\": \"{}\", \"origin\": [{}], \"direction\": [{}], \
+                \"hit_object\": {hit_object}, \"hit_distance\": {hit_distance}, \"spectrum\": [{}]}}{separator}",
+                segment.depth, segment.kind, list(&mut segment.origin.iter().copied()),
+                list(&mut segment.direction.iter().copied()), list(&mut segment.spectrum.iter().map(|(_, i)| i)));
+        }
+        let separator = if i + 1 < records.len() { "," } else { "" };
+        let _ = writeln!(json, "    ]}}{separator}");
+    }
+    json.push_str("  ]\n}\n");
+    json
+}
+
+/// Writes the paths as CSV with one line per ray. The spectrum takes one column per wavelength.
+fn to_csv(records: &[PathRecord], wavelengths: &[f32], object_names: &[String]) -> String {
+    let mut csv = String::from("path,pixel_x,pixel_y,depth,kind,origin_x,origin_y,origin_z,\
+        direction_x,direction_y,direction_z,hit_object,hit_distance");
+    for wavelength in wavelengths {
+        let _ = write!(csv, ",{wavelength}nm");
+    }
+    csv.push('\n');
+
+    for (i, record) in records.iter().enumerate() {
+        for segment in &record.segments {
+            let (hit_object, hit_distance) = match segment.hit {
+                Some((index, distance)) => (format!("\"{}\"", object_names[index].replace('"', "\"\"")), distance.to_string()),
+                None => (String::new(), String::new()),
+            };
+            let (o, d) = (segment.origin, segment.direction);
+            let _ = write!(csv, "{i},{},{},{},{},{},{},{},{},{},{},{hit_object},{hit_distance}",
+                           record.pixel.0, record.pixel.1, segment.depth, segment.kind, o.x, o.y, o.z, d.x, d.y, d.z);
+            for (_, intensity) in segment.spectrum.iter() {
+                let _ = write!(csv, ",{intensity}");
+            }
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+fn escape_json(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,391 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use eframe::egui;
+use image::{DynamicImage, ImageFormat};
+use log::{info, warn};
+use crate::platform;
+use crate::progress::RenderProgress;
+
+/// The port the remote control listens on if none is given.
+pub const DEFAULT_REMOTE_CONTROL_PORT: u16 = 7879;
+/// How often the server checks whether it should stop while no client is connected.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+/// How long a client may take to send its request.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The number of clients answered at the same time, further ones are turned away until one is
+/// done.
+const MAX_CLIENTS: usize = 8;
+/// The largest request line and headers accepted, together.
+const MAX_HEADER_BYTES: u64 = 16 * 1024;
+/// How long the server waits for the app to answer a request. The app answers in its next frame,
+/// which only takes long if the UI is blocked.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// The largest request body accepted, which is the content of a scene file. The body is only 
+/// stored as far as it arrives, not allocated for the length the client announces.
+const MAX_BODY_LENGTH: usize = 1 << 26;
+
+/// What a client of the remote control asks the app to do.
+pub enum RemoteCommand {
+    /// Starts a render of the given scene, given as the content of a scene file, or of the scene
+    /// currently shown in the UI if None.
+    Start { scene: Option<String> },
+    /// Aborts the running render.
+    Abort,
+    /// Asks whether the app renders and how far the render is.
+    Status,
+    /// Asks for the image currently displayed.
+    Image,
+}
+
+/// The answer of the app to a [RemoteCommand].
+pub enum RemoteReply {
+    Done,
+    Status(RemoteStatus),
+    /// The image currently displayed, None if nothing was rendered yet.
+    Image(Option<DynamicImage>),
+    /// The app could not do what it was asked to, for the given reason.
+    Failed(String),
+}
+
+/// The state of the render reported to clients.
+pub struct RemoteStatus {
+    pub rendering: bool,
+    pub progress: RenderProgress,
+    /// How long the current or last render took so far.
+    pub elapsed: Option<Duration>,
+}
+
+/// A command received by the server, which the app has to [answer](RemoteRequest::reply).
+pub struct RemoteRequest {
+    pub command: RemoteCommand,
+    reply: Sender<RemoteReply>,
+}
+
+impl RemoteRequest {
+    pub fn reply(self, reply: RemoteReply) {
+        //the client may have given up waiting already
+        let _ = self.reply.send(reply);
+    }
+}
+
+/// A small HTTP server on localhost through which other programs start and abort renders, query
+/// the progress and fetch the image, e.g. a render manager. The server runs on its own thread,
+/// reads every request on another one and hands the requests to the app, which answers them in
+/// [App::update](crate::App) without ever waiting for a client. The server stops when this is
+/// dropped.
+///
+/// Endpoints:
+/// - `GET /status`: the state of the render as JSON
+/// - `GET /image`: the image currently displayed as PNG
+/// - `POST /render`: starts a render, of the scene file sent as body if there is one
+/// - `POST /abort`: aborts the running render
+///
+/// Only requests addressed to 127.0.0.1 or localhost are answered and none sent by a web page, see 
+/// [check_local].
+pub struct RemoteControl {
+    requests: Receiver<RemoteRequest>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RemoteControl {
+    /// Starts listening on the given port of localhost. The context is repainted whenever a
+    /// request arrives, so that the app answers it right away.
+    pub fn start(port: u16, ctx: egui::Context) -> Result<Self, RemoteControlError> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| RemoteControlError {error: format!("Could not listen on port {port}: {e}")})?;
+        let (sender, requests) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stop_server = stop.clone();
+        platform::spawn(move || serve(listener, port, sender, ctx, stop_server));
+        info!("Remote control listening on http://127.0.0.1:{port}");
+        Ok(RemoteControl {requests, stop})
+    }
+
+    /// Returns the next request waiting for an answer, if any.
+    pub fn poll(&self) -> Option<RemoteRequest> {
+        self.requests.try_recv().ok()
+    }
+}
+
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Answers every client on a thread of its own until told to stop, so that a slow client does not
+/// hold up the others. At most [MAX_CLIENTS] are answered at the same time.
+fn serve(listener: TcpListener, port: u16, sender: Sender<RemoteRequest>, ctx: egui::Context, stop: Arc<AtomicBool>) {
+    let active_clients = Arc::new(AtomicUsize::new(0));
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((mut stream, _)) => {
+                if active_clients.fetch_add(1, Ordering::Relaxed) >= MAX_CLIENTS {
+                    active_clients.fetch_sub(1, Ordering::Relaxed);
+                    let _ = stream.set_nonblocking(false)
+                        .map(|_| Response::text(503, "Too many clients are connected.").write(&mut stream));
+                    continue;
+                }
+                let (sender, ctx, active_clients) = (sender.clone(), ctx.clone(), active_clients.clone());
+                platform::spawn(move || {
+                    if let Err(e) = handle_client(stream, port, &sender, &ctx) {
+                        warn!("Error answering a remote control request: {}", e.error);
+                    }
+                    active_clients.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(e) => warn!("Error accepting a remote control connection: {e}"),
+        }
+    }
+}
+
+/// Reads a single request, has the app answer it and writes the response.
+fn handle_client(stream: TcpStream, port: u16, sender: &Sender<RemoteRequest>, ctx: &egui::Context) 
+    -> Result<(), RemoteControlError> {
+    stream.set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(CLIENT_TIMEOUT)))
+        .map_err(|e| RemoteControlError {error: format!("Could not configure the connection: {e}")})?;
+    let mut writer = stream.try_clone()
+        .map_err(|e| RemoteControlError {error: format!("Could not configure the connection: {e}")})?;
+
+    let response = match read_request(stream) {
+        Ok(request) => match check_local(&request, port).and_then(|_| route(&request.method, &request.path, request.body)) {
+            Ok(command) => answer(command, sender, ctx),
+            Err(response) => response,
+        },
+        Err(e) => Response::text(400, &e.error),
+    };
+    response.write(&mut writer)
+}
+
+/// The parts of an HTTP request the server looks at.
+struct Request {
+    method: String,
+    path: String,
+    /// The value of the Host header, if any.
+    host: Option<String>,
+    /// Whether the request has an Origin header, which browsers add to requests of web pages.
+    has_origin: bool,
+    body: String,
+}
+
+/// Reads the method, the path, the headers of interest and the body of an HTTP request. The request
+/// line and the headers may take up at most [MAX_HEADER_BYTES], the body [MAX_BODY_LENGTH].
+fn read_request(stream: impl Read) -> Result<Request, RemoteControlError> {
+    let invalid = |e: std::io::Error| RemoteControlError {error: format!("Could not read the request: {e}")};
+    let too_long = || RemoteControlError {error: "The request line or the headers are too long!".to_string()};
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_HEADER_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line).map_err(invalid)?;
+    if !request_line.ends_with('\n') && head.limit() == 0 {
+        return Err(too_long());
+    }
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(RemoteControlError {error: "Invalid request line!".to_string()});
+    };
+
+    let mut content_length = 0;
+    let mut host = None;
+    let mut has_origin = false;
+    loop {
+        let mut header = String::new();
+        head.read_line(&mut header).map_err(invalid)?;
+        if !header.ends_with('\n') && head.limit() == 0 {
+            return Err(too_long());
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()
+                    .map_err(|_| RemoteControlError {error: "Invalid content length!".to_string()})?;
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                has_origin = true;
+            }
+        }
+    }
+    if content_length > MAX_BODY_LENGTH {
+        return Err(RemoteControlError {error: format!("The body of {content_length} bytes is too large!")});
+    }
+
+    let mut body = Vec::new();
+    reader.take(content_length as u64).read_to_end(&mut body).map_err(invalid)?;
+    if body.len() != content_length {
+        return Err(RemoteControlError {error: "The body is shorter than its content length!".to_string()});
+    }
+    let body = String::from_utf8(body)
+        .map_err(|_| RemoteControlError {error: "The body is not UTF-8!".to_string()})?;
+    //query parameters are not used
+    let path = path.split('?').next().unwrap_or_default().to_string();
+    Ok(Request {method: method.to_string(), path, host, has_origin, body})
+}
+
+/// Rejects requests which do not come from a program on this machine. Any web page open in a 
+/// browser may send requests to localhost, which carry an Origin header, and a page whose domain 
+/// is made to resolve to 127.0.0.1 (DNS rebinding) may even read the answers, but its requests 
+/// name that domain as Host. 
+fn check_local(request: &Request, port: u16) -> Result<(), Response> {
+    if request.has_origin {
+        return Err(Response::text(403, "Requests from web pages are not accepted."));
+    }
+    let is_local = request.host.as_deref().is_some_and(|host| {
+        host == format!("127.0.0.1:{port}") || host.eq_ignore_ascii_case(&format!("localhost:{port}"))
+    });
+    if !is_local {
+        return Err(Response::text(403, &format!("Only requests to 127.0.0.1:{port} or localhost:{port} are accepted.")));
+    }
+    Ok(())
+}
+
+/// Returns the command requested, or the response for requests which are not understood.
+fn route(method: &str, path: &str, body: String) -> Result<RemoteCommand, Response> {
+    let command = match path {
+        "/status" => (method == "GET").then_some(RemoteCommand::Status),
+        "/image" => (method == "GET").then_some(RemoteCommand::Image),
+        "/render" => (method == "POST").then(|| RemoteCommand::Start {
+            scene: (!body.trim().is_empty()).then_some(body),
+        }),
+        "/abort" => (method == "POST").then_some(RemoteCommand::Abort),
+        _ => return Err(Response::text(404, "Unknown endpoint, use /status, /image, /render or /abort.")),
+    };
+    command.ok_or_else(|| Response::text(405, "Method not allowed."))
+}
+
+/// Hands the command to the app and turns its answer into a response.
+fn answer(command: RemoteCommand, sender: &Sender<RemoteRequest>, ctx: &egui::Context) -> Response {
+    let (reply_sender, reply) = mpsc::channel();
+    if sender.send(RemoteRequest {command, reply: reply_sender}).is_err() {
+        return Response::text(503, "The app is shutting down.");
+    }
+    ctx.request_repaint();
+
+    match reply.recv_timeout(REPLY_TIMEOUT) {
+        Ok(RemoteReply::Done) => Response::text(200, "OK"),
+        Ok(RemoteReply::Status(status)) => Response::json(&status_json(&status)),
+        Ok(RemoteReply::Image(Some(image))) => {
+            let mut png = std::io::Cursor::new(Vec::new());
+            match image.write_to(&mut png, ImageFormat::Png) {
+                Ok(()) => Response {status: 200, content_type: "image/png", body: png.into_inner()},
+                Err(e) => Response::text(500, &format!("Could not encode the image: {e}")),
+            }
+        }
+        Ok(RemoteReply::Image(None)) => Response::text(404, "Nothing was rendered yet."),
+        Ok(RemoteReply::Failed(reason)) => Response::text(409, &reason),
+        Err(_) => Response::text(503, "The app did not answer in time."),
+    }
+}
+
+/// Formats the status as a JSON object. Durations are given in seconds, null if unknown.
+fn status_json(status: &RemoteStatus) -> String {
+    let progress = &status.progress;
+    let seconds = |duration: Option<Duration>| duration.map_or("null".to_string(), |d| d.as_secs_f32().to_string());
+    let remaining = status.elapsed.and_then(|elapsed| progress.remaining_time(elapsed));
+    format!("{{\"rendering\":{},\"progress\":{},\"frames_done\":{},\"nbr_of_frames\":{},\"rays_traced\":{},\
+             \"elapsed_seconds\":{},\"remaining_seconds\":{}}}\n",
+            status.rendering, progress.overall(), progress.frames_done, progress.nbr_of_frames,
+            progress.rays_traced, seconds(status.elapsed), seconds(remaining))
+}
+
+/// An HTTP response, the connection is closed after it.
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: u16, text: &str) -> Self {
+        Response {status, content_type: "text/plain; charset=utf-8", body: format!("{text}\n").into_bytes()}
+    }
+
+    fn json(json: &str) -> Self {
+        Response {status: 200, content_type: "application/json", body: json.as_bytes().to_vec()}
+    }
+
+    fn write(&self, stream: &mut TcpStream) -> Result<(), RemoteControlError> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        };
+        let header = format!("HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                             self.status, self.content_type, self.body.len());
+        stream.write_all(header.as_bytes())
+            .and_then(|_| stream.write_all(&self.body))
+            .map_err(|e| RemoteControlError {error: format!("Could not send the response: {e}")})
+    }
+}
+
+/// An error type used by the remote control. Specific details of the error are given in the error
+/// String.
+#[derive(Debug)]
+pub struct RemoteControlError {
+    pub error: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_only_local_requests_are_accepted() {
+        let request = |host: Option<&str>, has_origin: bool| Request {
+            method: "POST".to_string(),
+            path: "/render".to_string(),
+            host: host.map(str::to_string),
+            has_origin,
+            body: String::new(),
+        };
+        let port = DEFAULT_REMOTE_CONTROL_PORT;
+        assert!(check_local(&request(Some("127.0.0.1:7879"), false), port).is_ok());
+        assert!(check_local(&request(Some("LocalHost:7879"), false), port).is_ok());
+        for (host, has_origin) in [(Some("127.0.0.1:7879"), true), (Some("127.0.0.1:80"), false), 
+                                   (Some("attacker.example:7879"), false), (None, false)] {
+            assert!(check_local(&request(host, has_origin), port).is_err(), 
+                    "The request to {host:?} with origin {has_origin} was accepted!");
+        }
+    }
+
+    #[test]
+    fn test_read_request() {
+        let request = read_request("POST /render?x=1 HTTP/1.1\r\nHost: 127.0.0.1:7879\r\nContent-Length: 5\r\n\r\nscene".as_bytes()).unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/render"));
+        assert_eq!(request.host.as_deref(), Some("127.0.0.1:7879"));
+        assert!(!request.has_origin);
+        assert_eq!(request.body, "scene");
+    }
+
+    #[test]
+    fn test_oversized_requests_are_refused() {
+        let long_header = format!("GET /status HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEADER_BYTES as usize));
+        assert!(read_request(long_header.as_bytes()).is_err(), "The overlong header was accepted!");
+        let endless_line = format!("GET /{}", "a".repeat(MAX_HEADER_BYTES as usize));
+        assert!(read_request(endless_line.as_bytes()).is_err(), "The overlong request line was accepted!");
+        
+        let too_large = format!("POST /render HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_LENGTH + 1);
+        assert!(read_request(too_large.as_bytes()).is_err(), "The overlong body was accepted!");
+        //announcing a large body without sending it fails once the data ends
+        let truncated = format!("POST /render HTTP/1.1\r\nContent-Length: {MAX_BODY_LENGTH}\r\n\r\nscene");
+        assert!(read_request(truncated.as_bytes()).is_err(), "The truncated body was accepted!");
+    }
+}
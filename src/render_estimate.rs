@@ -0,0 +1,124 @@
+use std::time::Duration;
+use crate::loader::{LoadError, LoadProgress};
+use crate::platform::{self, Instant};
+use crate::shader::{self, Dimensions, PixelPos, RaytracingUniforms};
+
+/// The width of the probe render timing the scene, the height follows from the aspect ratio.
+const PROBE_WIDTH: u32 = 48;
+/// The frames of the probe render. The first frame also warms up the caches of the CPU, so more
+/// than one frame gives a steadier estimate.
+const PROBE_FRAMES: u32 = 2;
+/// Renders taking longer than this are only started after a warning.
+const RENDER_TIME_WARNING: Duration = Duration::from_secs(24 * 60 * 60);
+/// The bytes every pixel takes while rendering: the float image with its sample counts and its
+/// AOVs, the copy of it converted for display every frame, the 8 bit display image and its
+/// texture, the float images of the AOVs and the raw accumulation with its sample counts and the
+/// luminance sums the noise is estimated from.
+const BYTES_PER_PIXEL: u64 = 68 + 32 + 4 + 4 + 48 + 32;
+
+/// The resources a render is expected to take, estimated before it is started. The time is only
+/// a rough guess: the probe render neither uses the irradiance cache nor the full resolution.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderEstimate {
+    /// The memory taken by the image buffers in bytes.
+    pub memory: u64,
+    /// The memory available on this machine in bytes, None if it is unknown.
+    pub available_memory: Option<u64>,
+    pub frame_time: Duration,
+    pub nbr_of_frames: u32,
+}
+
+impl RenderEstimate {
+    pub fn total_time(&self) -> Duration {
+        self.frame_time.saturating_mul(self.nbr_of_frames)
+    }
+
+    /// Returns the reasons to think twice before starting the render, empty if there are none.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(available) = self.available_memory.filter(|&available| self.memory > available) {
+            warnings.push(format!("The image buffers take about {}, but only {} are available.",
+                                  format_bytes(self.memory), format_bytes(available)));
+        }
+        if self.total_time() > RENDER_TIME_WARNING {
+            warnings.push(format!("The render takes about {}.", format_duration(self.total_time())));
+        }
+        warnings
+    }
+}
+
+/// Returns the bytes the image buffers of a render of the given size take. If the spectra are
+/// recorded, every pixel also holds the sum of its spectra with the given number of samples, and
+/// for every light pass the sum of its colors and their float image.
+pub fn framebuffer_memory(width: u32, height: u32, record_spectra: bool, nbr_of_samples: usize,
+                          nbr_of_light_passes: usize) -> u64 {
+    let spectra = if record_spectra { nbr_of_samples as u64 * 4 } else { 0 };
+    let light_passes = nbr_of_light_passes as u64 * (12 + 12);
+    width as u64 * height as u64 * (BYTES_PER_PIXEL + spectra + light_passes)
+}
+
+/// Renders the scene at a low resolution on the calling thread and extrapolates the time a frame
+/// of the given size takes on the given number of threads. The memory is given by
+/// [framebuffer_memory].
+pub fn estimate_render(mut uniforms: RaytracingUniforms, width: u32, height: u32, nbr_of_threads: usize,
+                       memory: u64, progress: &LoadProgress) -> Result<RenderEstimate, LoadError> {
+    let nbr_of_frames = uniforms.intended_frames_amount;
+    let probe_width = PROBE_WIDTH.min(width).max(1);
+    let probe_height = ((probe_width as f32 * height as f32 / width as f32).round() as u32).max(1);
+
+    let start = Instant::now();
+    let mut photon_time = Duration::ZERO;
+    for frame_id in 0..PROBE_FRAMES {
+        let photon_start = Instant::now();
+        uniforms.set_frame(frame_id);
+        photon_time += photon_start.elapsed();
+        for y in 0..probe_height {
+            if progress.is_cancelled() {
+                return Err(LoadError::Cancelled);
+            }
+            for x in 0..probe_width {
+                shader::ray_generation_shader(
+                    PixelPos {x, y},
+                    Dimensions {width: probe_width, height: probe_height},
+                    &uniforms,
+                );
+            }
+            progress.set((frame_id * probe_height + y + 1) as f32 / (PROBE_FRAMES * probe_height) as f32);
+        }
+    }
+    let pixel_time = (start.elapsed() - photon_time) / PROBE_FRAMES;
+    let pixel_time_per_pixel = pixel_time.as_secs_f64() / (probe_width * probe_height) as f64;
+    let frame_time = pixel_time_per_pixel * width as f64 * height as f64 / nbr_of_threads.max(1) as f64
+        + (photon_time / PROBE_FRAMES).as_secs_f64();
+
+    Ok(RenderEstimate {
+        memory,
+        available_memory: platform::available_memory(),
+        frame_time: Duration::from_secs_f64(frame_time),
+        nbr_of_frames,
+    })
+}
+
+/// Formats a number of bytes with the largest binary unit that keeps the number above 1.
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["bytes", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", units[unit])
+}
+
+/// Formats a duration in days, hours or minutes, whichever fits best.
+pub fn format_duration(duration: Duration) -> String {
+    let hours = duration.as_secs_f64() / 3600.0;
+    if hours >= 48.0 {
+        format!("{:.1} days", hours / 24.0)
+    } else if hours >= 1.0 {
+        format!("{hours:.1} hours")
+    } else {
+        format!("{:.1} minutes", duration.as_secs_f64() / 60.0)
+    }
+}
@@ -0,0 +1,311 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::shader::{PixelPos, RaytracingUniforms};
+
+/// The direction numbers of the second Sobol dimension, generated by the primitive polynomial
+/// x + 1. The first dimension is simply the bit reversed index.
+const SOBOL_DIRECTIONS_DIMENSION_1: [u32; 32] = {
+    let mut directions = [0u32; 32];
+    directions[0] = 1 << 31;
+    let mut i = 1;
+    while i < 32 {
+        directions[i] = directions[i - 1] ^ (directions[i - 1] >> 1);
+        i += 1;
+    }
+    directions
+};
+
+/// The available strategies to generate the random numbers used in the shaders.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SamplerType {
+    /// Hashes the pixel position and frame number with PCG. Cheap, but the samples of consecutive
+    /// frames clump together, so the noise goes away slowly.
+    Pcg,
+    /// An Owen scrambled Sobol sequence. The samples of consecutive frames are evenly spread,
+    /// which converges noticeably faster at low frame counts.
+    Sobol,
+}
+
+impl Display for SamplerType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SamplerType::Pcg => write!(f, "PCG Hash"),
+            SamplerType::Sobol => write!(f, "Sobol (Owen scrambled)"),
+        }
+    }
+}
+
+/// The purposes random values are drawn for. Every purpose at every depth of a path is a
+/// dimension of its own, which gets an independent stream of random values. This way neither the
+/// bounces along a path nor the light samples at a single bounce share their random values.
+#[derive(Clone, Copy)]
+enum Dimension {
+    PixelOffset,
+    Lens { band: u32 },
+    BounceDirection { depth: u32 },
+    /// The choice of the reflection type as well as russian roulette.
+    BounceDecision { depth: u32 },
+    Light { depth: u32, light: u32 },
+    /// The choice between the two materials of a mixed material.
+    MaterialChoice { depth: u32 },
+    /// The scattering point within the medium and the light sampled from it.
+    Medium { depth: u32 },
+    /// The direction of a photon leaving the light or a surface.
+    PhotonDirection { depth: u32 },
+    /// The choice of the light emitting a photon, of the reflection type and of the material.
+    PhotonDecision { depth: u32 },
+    /// The jitter of a ray within its stratum of the hemisphere above a record of the irradiance cache.
+    IrradianceSample,
+}
+
+impl Dimension {
+    /// Hashes the dimension together with the seed into the key of its stream.
+    fn key(self, seed: u32) -> u32 {
+        let (purpose, a, b) = match self {
+            Dimension::PixelOffset => (0, 0, 0),
+            Dimension::Lens { band } => (1, band, 0),
+            Dimension::BounceDirection { depth } => (2, depth, 0),
+            Dimension::BounceDecision { depth } => (3, depth, 0),
+            Dimension::Light { depth, light } => (4, depth, light),
+            Dimension::MaterialChoice { depth } => (5, depth, 0),
+            Dimension::Medium { depth } => (6, depth, 0),
+            Dimension::PhotonDirection { depth } => (7, depth, 0),
+            Dimension::PhotonDecision { depth } => (8, depth, 0),
+            Dimension::IrradianceSample => (9, 0, 0),
+        };
+        let (key, _, _) = pcg3d(purpose, a, b);
+        pcg3d(key, seed, 0).0
+    }
+}
+
+/// Hands out the random numbers for a single pixel in a single frame. Each use, such as the
+/// position inside the pixel, the point on the lens or the direction of a bounce, has its own
+/// dimension, which keeps the sample points well distributed across frames. Changing the seed
+/// changes all streams at once, rendering the same scene with a different noise pattern.
+pub struct Sampler {
+    sampler_type: SamplerType,
+    pixel: PixelPos,
+    frame_id: u32,
+    intended_frames_amount: u32,
+    max_bounces: u32,
+    seed: u32,
+}
+
+impl Sampler {
+    /// Creates the sampler for the given pixel in the frame given by the uniforms.
+    pub fn new(uniforms: &RaytracingUniforms, pixel: PixelPos) -> Self {
+        Sampler {
+            sampler_type: uniforms.sampler_type,
+            pixel,
+            frame_id: uniforms.frame_id,
+            intended_frames_amount: uniforms.intended_frames_amount,
+            max_bounces: uniforms.max_bounces,
+            seed: uniforms.random_seed,
+        }
+    }
+
+    /// Returns the offset of the camera ray inside the pixel, both values in \[0; 1).
+    pub fn pixel_offset(&self) -> (f32, f32) {
+        match self.sampler_type {
+            SamplerType::Pcg => {
+                //the hammersley points are shifted per pixel, so that neighbouring pixels do not 
+                //sample the same spots
+                let (x, y) = hammersley(self.frame_id, self.intended_frames_amount);
+                let (shift_x, shift_y, _) = random_pcg3d(self.pixel.x, self.pixel.y,
+                                                         Dimension::PixelOffset.key(self.seed));
+                ((x + shift_x).fract(), (y + shift_y).fract())
+            }
+            SamplerType::Sobol => self.get_2d(Dimension::PixelOffset),
+        }
+    }
+
+    /// Returns two random values to pick a point on the lens aperture for the given wavelength
+    /// band.
+    pub fn lens(&self, band: u32) -> (f32, f32) {
+        self.get_2d(Dimension::Lens { band })
+    }
+
+    /// Returns three random values for the bounce of a ray with the given number of bounces left.
+    /// The first two are meant for the new direction, the third for choosing the type of
+    /// reflection.
+    pub fn bounce(&self, bounces_left: u32) -> (f32, f32, f32) {
+        let depth = self.max_bounces - bounces_left;
+        let (x, y) = self.get_2d(Dimension::BounceDirection { depth });
+        let (z, _) = self.get_2d(Dimension::BounceDecision { depth });
+        (x, y, z)
+    }
+
+    /// Returns the random value russian roulette uses to decide whether a ray with the given
+    /// number of bounces left is continued.
+    pub fn russian_roulette(&self, bounces_left: u32) -> f32 {
+        let depth = self.max_bounces - bounces_left;
+        self.get_2d(Dimension::BounceDecision { depth }).1
+    }
+
+    /// Returns two random values for the light sample with the given index at a surface hit by a 
+    /// ray with the given number of bounces left. Every light sample has its own stream.
+    pub fn light(&self, bounces_left: u32, light: u32) -> (f32, f32) {
+        let depth = self.max_bounces - bounces_left;
+        self.get_2d(Dimension::Light { depth, light })
+    }
+
+    /// Returns the random value which picks one of the materials of a mixed material hit by a ray 
+    /// with the given number of bounces left.
+    pub fn material_choice(&self, bounces_left: u32) -> f32 {
+        let depth = self.max_bounces - bounces_left;
+        self.get_2d(Dimension::MaterialChoice { depth }).0
+    }
+
+    /// Returns the two random values which pick the scattering point in the medium along a ray 
+    /// with the given number of bounces left and the light sampled from there.
+    pub fn medium(&self, bounces_left: u32) -> (f32, f32) {
+        let depth = self.max_bounces - bounces_left;
+        self.get_2d(Dimension::Medium { depth })
+    }
+
+    /// Returns four random values for a photon after the given number of bounces, where the pixel
+    /// of the sampler is the index of the photon. The first two are meant for the direction, the
+    /// others for the decisions: the light at the emission, the type of reflection and the
+    /// material at a bounce.
+    pub fn photon(&self, depth: u32) -> (f32, f32, f32, f32) {
+        let (x, y) = self.get_2d(Dimension::PhotonDirection { depth });
+        let (z, w) = self.get_2d(Dimension::PhotonDecision { depth });
+        (x, y, z, w)
+    }
+
+    /// Returns two random values which jitter a ray of the irradiance cache within its stratum, 
+    /// where the pixel of the sampler identifies the ray. 
+    pub fn irradiance_sample(&self) -> (f32, f32) {
+        self.get_2d(Dimension::IrradianceSample)
+    }
+
+    /// Returns two random values from the stream of the given dimension for the current frame.
+    fn get_2d(&self, dimension: Dimension) -> (f32, f32) {
+        let key = dimension.key(self.seed);
+        match self.sampler_type {
+            SamplerType::Pcg => {
+                let (x, y, _) = random_pcg3d(self.pixel.x, self.pixel.y, pcg3d(self.frame_id, key, 0).0);
+                (x, y)
+            }
+            SamplerType::Sobol => self.sobol_2d(key),
+        }
+    }
+
+    /// Returns the point of the 2D Sobol sequence for the current frame in the dimension with the
+    /// given key. The sequence is shuffled and Owen scrambled with a seed depending on the pixel
+    /// and the dimension, so that neither neighboring pixels nor different dimensions are
+    /// correlated.
+    /// <br>
+    /// Practical Hash-based Owen Scrambling, Brent Burley, 2020 <br>
+    /// http://www.jcgt.org/published/0009/04/01/
+    fn sobol_2d(&self, key: u32) -> (f32, f32) {
+        let (seed, seed_x, seed_y) = pcg3d(self.pixel.x, self.pixel.y, key);
+        let index = nested_uniform_scramble(self.frame_id, seed);
+        let x = nested_uniform_scramble(sobol(index, 0), seed_x);
+        let y = nested_uniform_scramble(sobol(index, 1), seed_y);
+        (to_unit_float(x), to_unit_float(y))
+    }
+}
+
+/// Returns the value of the given index in the given dimension (0 or 1) of the Sobol sequence, as
+/// a fixed point number with 32 fractional bits.
+fn sobol(index: u32, dimension: u32) -> u32 {
+    if dimension == 0 {
+        return index.reverse_bits();
+    }
+
+    let mut result = 0;
+    let mut index = index;
+    let mut bit = 0;
+    while index != 0 {
+        if index & 1 == 1 {
+            result ^= SOBOL_DIRECTIONS_DIMENSION_1[bit];
+        }
+        index >>= 1;
+        bit += 1;
+    }
+    result
+}
+
+/// A hash which only mixes bits towards the more significant bits, which makes it a random
+/// permutation of the binary tree of the lower bits. <br>
+/// Stratified Sampling for Stochastic Transparency, Laine and Karras, 2011
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
+
+/// Owen scrambles a fixed point number: every bit is flipped depending on the random permutation
+/// of all more significant bits.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// Converts a fixed point number with 32 fractional bits into a float in \[0; 1).
+fn to_unit_float(x: u32) -> f32 {
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
+
+// from http://holger.dammertz.org/stuff/notes_HammersleyOnHemisphere.html
+// Hacker's Delight, Henry S. Warren, 2001
+//adapted to be used in rust
+fn radical_inverse(mut bits: u32) -> f32 {
+    bits = bits.rotate_right(16);
+    bits = ((bits & 0x55555555) << 1) | ((bits & 0xAAAAAAAA) >> 1);
+    bits = ((bits & 0x33333333) << 2) | ((bits & 0xCCCCCCCC) >> 2);
+    bits = ((bits & 0x0F0F0F0F) << 4) | ((bits & 0xF0F0F0F0) >> 4);
+    bits = ((bits & 0x00FF00FF) << 8) | ((bits & 0xFF00FF00) >> 8);
+    (bits as f32) * 2.328_306_4e-10  // / 0x100000000
+}
+
+/// A hammersley sequence. Takes two numbers where the first should be strictly smaller than the
+/// second. Use it f.ex. this way: <br>
+/// ```let (x, y) = hammersley(current_frame, total_number_of_frames);```<br>
+/// For capital_n = 10 the sequence looks like this: <br><br>
+/// (0.05, 0.5), (0.15, 0.25), (0.25, 0.75), (0.35, 0.125), (0.45, 0.625), (0.55, 0.375)
+/// (0.65, 0.875), (0.75, 0.0625), (0.85, 0.5625), (0.95, 0.3125)
+fn hammersley(n: u32, capital_n: u32) -> (f32, f32) {
+    (
+        (n as f32 + 0.5) / capital_n as f32,
+        radical_inverse(n + 1),
+    )
+}
+
+/// Calculates three quasi random floats from unsigned integers. The integers can usually be: <br>
+/// x = pixel position x, <br>
+/// y = pixel position y, <br>
+/// z = frame number <br>
+/// The resulting three floats will probably be in range \[0; 1] <br>
+/// <br>
+/// Hash Functions for GPU Rendering, Jarzynski et al. <br>
+/// http://www.jcgt.org/published/0009/03/02/
+pub fn random_pcg3d(x: u32, y: u32, z: u32) -> (f32, f32, f32) {
+    let (x, y, z) = pcg3d(x, y, z);
+    let reciprocal = 1.0 / 0xffffffffu32 as f32;
+    (
+        x as f32 * reciprocal,
+        y as f32 * reciprocal,
+        z as f32 * reciprocal,
+    )
+}
+
+/// The integer part of [random_pcg3d], hashing three unsigned integers into three others.
+fn pcg3d(mut x: u32, mut y: u32, mut z: u32) -> (u32, u32, u32) {
+    x = x.wrapping_mul(1664525).wrapping_add(1013904223);
+    y = y.wrapping_mul(1664525).wrapping_add(1013904223);
+    z = z.wrapping_mul(1664525).wrapping_add(1013904223);
+    x = y.wrapping_mul(z).wrapping_add(x);
+    y = z.wrapping_mul(x).wrapping_add(y);
+    z = x.wrapping_mul(y).wrapping_add(z);
+    x ^= x >> 16;
+    y ^= y >> 16;
+    z ^= z >> 16;
+    x = y.wrapping_mul(z).wrapping_add(x);
+    y = z.wrapping_mul(x).wrapping_add(y);
+    z = x.wrapping_mul(y).wrapping_add(z);
+    (x, y, z)
+}
@@ -0,0 +1,535 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::atmosphere::Atmosphere;
+use crate::bvh::Bvh;
+use crate::custom_image::{CustomImage, FireflyFilter, Pixel};
+use crate::false_color::FalseColor;
+use crate::light_selection::{LightDistribution, LightSelection};
+use crate::medium::Medium;
+use crate::sampler::SamplerType;
+use crate::sensor::SensorResponse;
+use crate::shader::{self, Aabb, Background, Camera, Dimensions, Light, PixelPos, RaytracingUniforms};
+use crate::spectrum::{self, Observer, Spectrum};
+
+pub const NBR_OF_ITERATIONS_DEFAULT: u32 = 100;
+pub const NBR_OF_SPECTRUM_SAMPLES_DEFAULT: usize = 32;
+pub const NEW_RAY_MAX_BOUNCES_DEFAULT: u32 = 30;
+pub const NEW_RAY_MAX_DIFFUSE_BOUNCES_DEFAULT: u32 = 8;
+pub const NEW_RAY_MAX_SPECULAR_BOUNCES_DEFAULT: u32 = 30;
+pub const NEW_RAY_MAX_TRANSMISSION_BOUNCES_DEFAULT: u32 = 30;
+pub const PHOTON_RADIUS_DEFAULT: f32 = 0.05;
+/// The ray offset relative to the scale of the hit. About a hundred times the precision of f32,
+/// which leaves room for the imprecision of the intersection tests.
+pub const RAY_OFFSET_DEFAULT: f32 = 0.00001;
+/// The edge length in pixels of the square tiles the image is rendered in.
+pub const RENDER_TILE_SIZE: u32 = 32;
+
+/// Everything that is rendered: the objects, the point lights, the camera and what surrounds the
+/// scene. All spectra have to share the number of samples given in the [RenderSettings].
+#[derive(Clone)]
+pub struct Scene {
+    pub objects: Vec<Aabb>,
+    pub lights: Vec<Light>,
+    pub camera: Camera,
+    /// The medium filling the scene, None for a vacuum.
+    pub medium: Option<Arc<Medium>>,
+    /// The atmosphere surrounding the scene with its sun, None for the background.
+    pub atmosphere: Option<Arc<Atmosphere>>,
+    /// What rays leaving the scene see if there is no atmosphere.
+    pub background: Background,
+}
+
+/// How a [Scene] is rendered. The defaults are those of the app.
+#[derive(Clone)]
+pub struct RenderSettings {
+    pub width: u32,
+    pub height: u32,
+    /// The number of frames averaged into the image, every pixel gets one sample per frame.
+    pub nbr_of_iterations: u32,
+    pub max_bounces: u32,
+    pub max_diffuse_bounces: u32,
+    pub max_specular_bounces: u32,
+    pub max_transmission_bounces: u32,
+    /// Whether paths are ended at random once they carry little light, see [shader::trace_path].
+    pub russian_roulette: bool,
+    pub sampler_type: SamplerType,
+    /// Seeds all random number streams, a different seed gives a different noise pattern.
+    pub random_seed: u32,
+    /// The number of samples of every spectrum of the scene.
+    pub nbr_of_samples: usize,
+    /// The lowest and highest wavelength in nm every spectrum of the scene is sampled between.
+    pub spectrum_range: (f32, f32),
+    /// Which lights are sampled at a hit.
+    pub light_selection: LightSelection,
+    /// The number of lights drawn at every hit unless all lights are sampled.
+    pub light_samples: u32,
+    /// The number of caustic photons traced every frame, 0 to disable photon mapping.
+    pub photon_count: u32,
+    pub photon_radius: f32,
+    /// The quality of the irradiance cache in range \[0; 1], None if no cache is used.
+    pub irradiance_cache_quality: Option<f32>,
+    pub ray_offset: f32,
+    pub firefly_filter: FireflyFilter,
+    /// Whether out of gamut colors are desaturated instead of hard clipped.
+    pub gamut_compression: bool,
+    /// The camera sensor used to convert the spectra into RGB, the CIE observer if None.
+    pub sensor_response: Option<Arc<SensorResponse>>,
+    /// The CIE standard observer used unless there is a sensor response.
+    pub observer: Observer,
+    /// Renders a single band of the spectra through a color map instead of in RGB if given.
+    pub false_color: Option<FalseColor>,
+    /// Whether the contribution of every light source is recorded in a pass of its own.
+    pub light_passes: bool,
+    /// Whether pixels which see nothing of the scene are transparent, also in front of the 
+    /// background or the atmosphere. 
+    pub transparent_film: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 600,
+            height: 400,
+            nbr_of_iterations: NBR_OF_ITERATIONS_DEFAULT,
+            max_bounces: NEW_RAY_MAX_BOUNCES_DEFAULT,
+            max_diffuse_bounces: NEW_RAY_MAX_DIFFUSE_BOUNCES_DEFAULT,
+            max_specular_bounces: NEW_RAY_MAX_SPECULAR_BOUNCES_DEFAULT,
+            max_transmission_bounces: NEW_RAY_MAX_TRANSMISSION_BOUNCES_DEFAULT,
+            russian_roulette: true,
+            sampler_type: SamplerType::Pcg,
+            random_seed: 0,
+            nbr_of_samples: NBR_OF_SPECTRUM_SAMPLES_DEFAULT,
+            spectrum_range: (spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND, spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND),
+            light_selection: LightSelection::All,
+            light_samples: 1,
+            photon_count: 0,
+            photon_radius: PHOTON_RADIUS_DEFAULT,
+            irradiance_cache_quality: None,
+            ray_offset: RAY_OFFSET_DEFAULT,
+            firefly_filter: FireflyFilter::Off,
+            gamut_compression: false,
+            sensor_response: None,
+            observer: Observer::default(),
+            false_color: None,
+            light_passes: false,
+            transparent_film: false,
+        }
+    }
+}
+
+impl Scene {
+    /// Renders the scene on the current rayon thread pool, wrap the call in
+    /// [ThreadPool::install](rayon::ThreadPool::install) to choose the threads. Blocks until all
+    /// frames are rendered.
+    pub fn render(&self, settings: &RenderSettings) -> CustomImage {
+        let (width, height) = (settings.width, settings.height);
+        let mut uniforms = self.clone().build_uniforms(settings, None);
+        let mut image = CustomImage::new(width, height);
+        image.set_firefly_filter(settings.firefly_filter);
+        image.enable_aovs();
+
+        for frame in 0..settings.nbr_of_iterations {
+            uniforms.set_frame(frame);
+            if frame == 0 {
+                uniforms.prepare_irradiance_cache(&Dimensions {width, height});
+            }
+            let rendered_tiles: Vec<_> = RenderBucket::split(width, height).into_par_iter().map(|bucket| {
+                let mut aovs = TileAovs::default();
+                let (tile, alphas) = render_tile(bucket, width, height, &uniforms, None, Some(&mut aovs), 
+                                                 || ControlFlow::Continue(()));
+                (bucket, tile, alphas, aovs)
+            }).collect();
+            for (bucket, tile, alphas, aovs) in rendered_tiles {
+                add_tile(&mut image, bucket, &tile, &alphas, Some(&aovs));
+            }
+        }
+
+        if settings.gamut_compression {
+            image.compress_gamut();
+        }
+        image
+    }
+
+    /// Creates the uniforms of the first frame, see [RaytracingUniforms::set_frame]. The bounding
+    /// volume hierarchy of a previous render is refitted if the objects have the same number and
+    /// order, see [Bvh::update].
+    pub fn build_uniforms(self, settings: &RenderSettings, previous_bvh: Option<Arc<Bvh>>) -> RaytracingUniforms {
+        let light_distribution = match settings.light_selection {
+            LightSelection::All => None,
+            selection => Some(Arc::new(LightDistribution::new(&self.lights, selection))),
+        };
+        let (lowest_wavelength, highest_wavelength) = settings.spectrum_range;
+        let example_spectrum = Spectrum::new_singular_reflectance_factor(
+            lowest_wavelength,
+            highest_wavelength,
+            settings.nbr_of_samples,
+            0.0,
+        );
+        let bounds: Vec<_> = self.objects.iter().map(|aabb| aabb.get_bounds()).collect();
+
+        RaytracingUniforms {
+            aabbs: Arc::new(self.objects),
+            bvh: Bvh::update(previous_bvh, &bounds),
+            lights: Arc::new(self.lights),
+            camera: self.camera,
+            frame_id: 0,
+            intended_frames_amount: settings.nbr_of_iterations,
+            example_spectrum,
+            max_bounces: settings.max_bounces,
+            max_diffuse_bounces: settings.max_diffuse_bounces,
+            max_specular_bounces: settings.max_specular_bounces,
+            max_transmission_bounces: settings.max_transmission_bounces,
+            russian_roulette: settings.russian_roulette,
+            sampler_type: settings.sampler_type,
+            random_seed: settings.random_seed,
+            gamut_compression: settings.gamut_compression,
+            sensor_response: settings.sensor_response.clone(),
+            observer: settings.observer,
+            false_color: settings.false_color,
+            light_distribution,
+            light_samples: settings.light_samples,
+            medium: self.medium,
+            atmosphere: self.atmosphere,
+            background: self.background,
+            photon_count: settings.photon_count,
+            photon_radius: settings.photon_radius,
+            photon_map: None,
+            irradiance_cache_quality: settings.irradiance_cache_quality,
+            irradiance_cache: None,
+            ray_offset: settings.ray_offset,
+            record_light_passes: settings.light_passes,
+            transparent_film: settings.transparent_film,
+        }
+    }
+}
+
+/// A rectangular part of the image which is rendered by a single worker in one go. Coordinates
+/// and size are given in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RenderBucket {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderBucket {
+    /// Splits an image of the given size into tiles of [RENDER_TILE_SIZE] pixels, row by row. The
+    /// tiles at the right and bottom edge are cut to the image.
+    pub fn split(width: u32, height: u32) -> Vec<RenderBucket> {
+        let mut tiles = Vec::new();
+        for y in (0..height).step_by(RENDER_TILE_SIZE as usize) {
+            for x in (0..width).step_by(RENDER_TILE_SIZE as usize) {
+                tiles.push(RenderBucket {
+                    x,
+                    y,
+                    width: RENDER_TILE_SIZE.min(width - x),
+                    height: RENDER_TILE_SIZE.min(height - y),
+                });
+            }
+        }
+        tiles
+    }
+
+    /// Returns the part of the tile made up of its first rows, e.g. of a tile which was cut off
+    /// while rendering.
+    pub fn first_rows(&self, rows: u32) -> RenderBucket {
+        RenderBucket {height: rows.min(self.height), ..*self}
+    }
+}
+
+/// The arbitrary output variables of the pixels of a tile besides their colors, row by row, see 
+/// [shader::FirstHit]. 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TileAovs {
+    /// The x, y, z values of the shading normal each pixel sees first, zero where nothing is hit.
+    pub normals: Vec<f32>,
+    /// The distance from the camera to what each pixel sees first, infinite where nothing is hit.
+    pub depths: Vec<f32>,
+    /// The r, g, b values of the reflectance each pixel sees first, zero where nothing is hit.
+    pub albedos: Vec<f32>,
+    /// The r, g, b values of the id color of the object each pixel sees first, zero where nothing 
+    /// is hit.
+    pub id_colors: Vec<f32>,
+    /// The r, g, b values each light source contributes to each pixel, all lights of the first 
+    /// pixel before those of the second. Empty unless light passes are recorded.
+    pub light_colors: Vec<f32>,
+}
+
+impl TileAovs {
+    fn push(&mut self, first_hit: Option<shader::FirstHit>, light_spectra: &[Spectrum], uniforms: &RaytracingUniforms) {
+        for spectrum in light_spectra {
+            let (r, g, b) = shader::spectrum_to_rgb(spectrum, uniforms);
+            self.light_colors.extend_from_slice(&[r, g, b]);
+        }
+        match first_hit {
+            Some(hit) => {
+                self.normals.extend_from_slice(hit.normal.as_slice());
+                self.depths.push(hit.distance);
+                self.albedos.extend_from_slice(hit.albedo.as_slice());
+                self.id_colors.extend_from_slice(hit.id_color.as_slice());
+            }
+            None => {
+                self.normals.extend_from_slice(&[0.0; 3]);
+                self.depths.push(f32::INFINITY);
+                self.albedos.extend_from_slice(&[0.0; 3]);
+                self.id_colors.extend_from_slice(&[0.0; 3]);
+            }
+        }
+    }
+    
+    /// Returns the number of pixels covered. 
+    pub fn len(&self) -> usize {
+        self.depths.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.depths.is_empty()
+    }
+    
+    /// Returns the number of light sources with a light pass, 0 if none are recorded. 
+    pub fn nbr_of_light_passes(&self) -> usize {
+        if self.is_empty() { 0 } else { self.light_colors.len() / (self.len() * 3) }
+    }
+    
+    /// Returns the r, g, b values each light source contributes to the pixel with the given index. 
+    fn light_colors_of(&self, pixel: usize) -> &[f32] {
+        let stride = self.nbr_of_light_passes() * 3;
+        &self.light_colors[pixel * stride..(pixel + 1) * stride]
+    }
+}
+
+/// Renders the pixels of the tile of an image of the given width and height once. Returns the
+/// RGB values row by row and the alpha value of each pixel. If a list of spectra is given, the
+/// spectrum of each pixel is appended to it, and if AOVs are given, the AOVs of each pixel. The 
+/// callback is called after every row. If it breaks,
+/// the tile is cut off after that row, so that the values returned cover only the rows rendered
+/// up to then, see [RenderBucket::first_rows].
+pub fn render_tile(bucket: RenderBucket, width: u32, height: u32, uniforms: &RaytracingUniforms,
+                   mut spectra: Option<&mut Vec<Spectrum>>, mut aovs: Option<&mut TileAovs>,
+                   mut finish_row: impl FnMut() -> ControlFlow<()>) 
+    -> (Vec<f32>, Vec<f32>) {
+    let mut tile = Vec::<f32>::with_capacity((bucket.width * bucket.height * 3) as usize);
+    let mut alphas = Vec::<f32>::with_capacity((bucket.width * bucket.height) as usize);
+
+    for y in bucket.y..bucket.y + bucket.height {
+        for x in bucket.x..bucket.x + bucket.width {
+            let pos = PixelPos{x, y};
+            let dim = Dimensions {width, height};
+            let (spectrum, a, first_hit, light_spectra) = shader::spectral_ray_generation_shader(pos, dim, uniforms);
+            let (r, g, b) = shader::spectrum_to_rgb(&spectrum, uniforms);
+            if let Some(spectra) = &mut spectra {
+                spectra.push(spectrum);
+            }
+            if let Some(aovs) = &mut aovs {
+                aovs.push(first_hit, &light_spectra, uniforms);
+            }
+
+            tile.push(r);
+            tile.push(g);
+            tile.push(b);
+            alphas.push(a);
+        }
+        if finish_row().is_break() {
+            break;
+        }
+    }
+    (tile, alphas)
+}
+
+/// Adds the pixels of a tile rendered by [render_tile] to the image as one more sample each, 
+/// together with their AOVs if they were rendered and the image records them.
+pub fn add_tile(image: &mut CustomImage, bucket: RenderBucket, tile: &[f32], alphas: &[f32], aovs: Option<&TileAovs>) {
+    for (i, (pixel, &a)) in tile.chunks_exact(3).zip(alphas).enumerate() {
+        let x = bucket.x + i as u32 % bucket.width;
+        let y = bucket.y + i as u32 / bucket.width;
+        image.add_sample(x as usize, y as usize, &Pixel { r: pixel[0], g: pixel[1], b: pixel[2], a }).unwrap();
+        if let Some(aovs) = aovs.filter(|aovs| i < aovs.len()) {
+            let rgb = i * 3..i * 3 + 3;
+            image.add_aov_sample(x as usize, y as usize, &aovs.normals[rgb.clone()], aovs.depths[i], 
+                                 &aovs.albedos[rgb.clone()], &aovs.id_colors[rgb]).unwrap();
+            image.add_light_pass_sample(x as usize, y as usize, aovs.light_colors_of(i)).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+    use nalgebra::{point, vector, Point3, Vector3};
+    use crate::bsdf::{Bsdf, BsdfSample};
+    use crate::primitive::Primitive;
+    use crate::radiometry::{SpectralIntensity, SpectralRadiance, SpectralReflectance};
+    use crate::shader::{LensModel, Material};
+    use super::*;
+
+    #[test]
+    fn test_extended_range_render_keeps_visible_color() {
+        //a warm background fills the image, rendered over the visible and over an extended range
+        let render = |(lowest, highest): (f32, f32), nbr_of_samples: usize| {
+            let background = Spectrum::new_temperature_spectrum(lowest, highest, 3000.0, nbr_of_samples, 1.0);
+            let scene = Scene {
+                objects: Vec::new(),
+                lights: Vec::new(),
+                camera: Camera::new(point![0.0, 0.0, 0.0], vector![0.0, 0.0, -1.0], vector![0.0, 1.0, 0.0], 
+                                    60.0, LensModel::Pinhole),
+                medium: None,
+                atmosphere: None,
+                background: Background::Uniform(Arc::new(SpectralRadiance::new(background))),
+            };
+            let settings = RenderSettings {
+                width: 2,
+                height: 2,
+                nbr_of_iterations: 1,
+                nbr_of_samples,
+                spectrum_range: (lowest, highest),
+                ..Default::default()
+            };
+            let data = scene.render(&settings).get_data().to_vec();
+            (data[0], data[1], data[2])
+        };
+        let visible = render((spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND, spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND), 64);
+        let extended = render((300.0, 1000.0), 128);
+        let close_enough = |e: f32, a: f32| (1.0 - a / e).abs() < 0.05;
+        assert!(close_enough(visible.0, extended.0) && close_enough(visible.1, extended.1) 
+                    && close_enough(visible.2, extended.2), 
+                "Rendering over an extended range changed the color from {visible:?} to {extended:?}!");
+    }
+
+    /// A sphere implemented outside of the shaders, the way a crate depending on the renderer would.
+    struct PluginSphere {
+        center: Point3<f32>,
+        radius: f32,
+    }
+
+    impl Primitive for PluginSphere {
+        fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+            (self.center - Vector3::repeat(self.radius), self.center + Vector3::repeat(self.radius))
+        }
+
+        fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+            let oc = origin - self.center;
+            let b = oc.dot(direction);
+            let discriminant = b * b - (oc.dot(&oc) - self.radius * self.radius);
+            if discriminant < 0.0 {
+                return None;
+            }
+            [-b - discriminant.sqrt(), -b + discriminant.sqrt()].into_iter().find(|t| *t >= 0.0)
+        }
+
+        fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32> {
+            (point - self.center).normalize()
+        }
+    }
+
+    #[test]
+    fn test_custom_primitive_renders_like_built_in_shape() {
+        let settings = RenderSettings {width: 16, height: 16, nbr_of_iterations: 4, ..Default::default()};
+        let (lowest, highest) = settings.spectrum_range;
+        let spectrum = |factor: f32| Spectrum::new_singular_reflectance_factor(lowest, highest, settings.nbr_of_samples, factor);
+        let material = Material::new(SpectralReflectance::new(spectrum(0.8)), 0.0, 1.0);
+        let (center, radius) = (point![0.0, 0.0, -3.0], 1.0);
+        let render = |sphere: Aabb| {
+            let scene = Scene {
+                objects: vec![sphere],
+                lights: vec![Light::new(point![2.0, 2.0, 0.0], SpectralIntensity::new(spectrum(20.0)))],
+                camera: Camera::new(point![0.0, 0.0, 0.0], vector![0.0, 0.0, -1.0], vector![0.0, 1.0, 0.0], 
+                                    60.0, LensModel::Pinhole),
+                medium: None,
+                atmosphere: None,
+                background: Background::Uniform(Arc::new(SpectralRadiance::new(spectrum(0.1)))),
+            };
+            scene.render(&settings).get_data().to_vec()
+        };
+        let built_in = render(Aabb::new_sphere(&center, radius, material.clone()));
+        let custom = render(Aabb::new_custom(Arc::new(PluginSphere {center, radius}), material));
+        
+        //the center pixel sees the sphere, the corner the background
+        let pixel = |data: &[f32], x: usize, y: usize| data[(y * 16 + x) * 4];
+        assert!((pixel(&custom, 8, 8) - pixel(&custom, 0, 0)).abs() > 0.01, "The custom sphere was not rendered!");
+        let mean = |data: &[f32]| data.iter().sum::<f32>() / data.len() as f32;
+        assert!((mean(&built_in) - mean(&custom)).abs() < 0.01 * mean(&built_in), 
+                "The custom sphere rendered differently from the built-in one!");
+        for (index, (b, c)) in built_in.iter().zip(&custom).enumerate() {
+            assert!((b - c).abs() <= 0.02 * b.abs().max(0.05), 
+                    "Pixel {} differs between the built-in ({b}) and the custom sphere ({c})!", index / 4);
+        }
+    }
+
+    /// A diffuse BSDF reflecting the same share of the light at every wavelength, the way a crate
+    /// depending on the renderer would add one.
+    #[derive(Debug)]
+    struct ConstantDiffuse {
+        albedo: f32,
+    }
+
+    impl ConstantDiffuse {
+        fn value(&self, example_spectrum: &Spectrum) -> Spectrum {
+            let (lowest, highest) = example_spectrum.get_range();
+            Spectrum::new_singular_reflectance_factor(lowest, highest, example_spectrum.get_nbr_of_samples(), 
+                                                      self.albedo / PI)
+        }
+    }
+
+    impl Bsdf for ConstantDiffuse {
+        fn sample(&self, _outgoing: &Vector3<f32>, normal: &Vector3<f32>, random_x: f32, random_y: f32,
+                  example_spectrum: &Spectrum) -> Option<BsdfSample> {
+            //cosine weighted around the normal
+            let helper = if normal.x.abs() < 0.9 { Vector3::x() } else { Vector3::y() };
+            let tangent = normal.cross(&helper).normalize();
+            let bitangent = normal.cross(&tangent);
+            let (radius, angle) = (random_x.sqrt(), 2.0 * PI * random_y);
+            let direction = (tangent * radius * angle.cos() + bitangent * radius * angle.sin() 
+                + normal * (1.0 - random_x).max(0.0).sqrt()).normalize();
+            Some(BsdfSample {
+                pdf: self.pdf(&Vector3::zeros(), &direction, normal),
+                direction,
+                value: self.value(example_spectrum),
+                specular: false,
+            })
+        }
+
+        fn evaluate(&self, _outgoing: &Vector3<f32>, _incoming: &Vector3<f32>, _normal: &Vector3<f32>,
+                    example_spectrum: &Spectrum) -> Spectrum {
+            self.value(example_spectrum)
+        }
+
+        fn pdf(&self, _outgoing: &Vector3<f32>, incoming: &Vector3<f32>, normal: &Vector3<f32>) -> f32 {
+            incoming.dot(normal).max(0.0) / PI
+        }
+    }
+
+    #[test]
+    fn test_custom_bsdf_converges_to_albedo_times_environment() {
+        let settings = RenderSettings {width: 8, height: 8, nbr_of_iterations: 16, ..Default::default()};
+        let (lowest, highest) = settings.spectrum_range;
+        let spectrum = |factor: f32| Spectrum::new_singular_reflectance_factor(lowest, highest, settings.nbr_of_samples, factor);
+        let albedo = 0.6;
+        let render = |objects: Vec<Aabb>, background: f32| {
+            let scene = Scene {
+                objects,
+                lights: Vec::new(),
+                camera: Camera::new(point![0.0, 1.0, 0.0], vector![0.0, -1.0, 0.0], vector![0.0, 0.0, -1.0], 
+                                    60.0, LensModel::Pinhole),
+                medium: None,
+                atmosphere: None,
+                background: Background::Uniform(Arc::new(SpectralRadiance::new(spectrum(background)))),
+            };
+            scene.render(&settings).get_data().to_vec()
+        };
+        //a plane filling the image, seeing only the uniform environment above it
+        let mut material = Material::new(SpectralReflectance::new(spectrum(1.0)), 0.0, 1.0);
+        material.bsdf = Some(Arc::new(ConstantDiffuse {albedo}));
+        let plane = Aabb::new_quad(&Point3::origin(), 1000.0, 1000.0, &Vector3::y(), material);
+        let lit_plane = render(vec![plane], 0.5);
+        //the plane reflects the share albedo of the environment radiance in every direction
+        let expected = render(Vec::new(), 0.5 * albedo);
+        
+        for (index, (e, a)) in expected.iter().zip(&lit_plane).enumerate() {
+            assert!((e - a).abs() <= 0.05 * e.abs().max(0.05), 
+                    "Pixel {} of the plane is {a} instead of {e}!", index / 4);
+        }
+    }
+}
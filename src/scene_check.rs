@@ -0,0 +1,211 @@
+use nalgebra::{point, vector, Point3, Rotation3, Vector3};
+use crate::{UICsgPart, UILight, UIObject, UIObjectType};
+use crate::shader;
+use crate::spectrum::Spectrum;
+
+/// The tolerance in scene units within which two faces are considered coplanar.
+const COPLANAR_TOLERANCE: f32 = 0.0001;
+/// The share of an emitter's radiance below which the light reflected by an object is considered
+/// negligible. Also the reflectance below which an object is considered intentionally black.
+const SPECTRAL_MISMATCH_THRESHOLD: f32 = 0.01;
+
+/// The light of one emitter as reflected by one object, ignoring geometry.
+pub struct ReflectedLight {
+    pub object_name: String,
+    pub emitter_name: String,
+    /// The linear RGB color of the reflected spectrum, scaled so that its largest component is 1.
+    pub rgb: (f32, f32, f32),
+    /// The radiance of the reflected spectrum divided by the radiance of the emitted spectrum.
+    pub reflected_share: f32,
+}
+
+/// Analyzes the visible objects of the scene for problems which do not crash the renderer, but
+/// lead to missing objects or visual artifacts:
+/// - objects with a size of zero, negative or not finite dimensions
+/// - objects failing the [intersection self test](shader::intersection_self_test)
+/// - objects enclosing the camera, which then only sees their inside
+/// - boxes with exactly coplanar, overlapping faces, which speckle like z-fighting
+/// - objects reflecting almost none of the light of an emitter, e.g. blue objects under a red 
+///   light, which then render black
+///
+/// Returns one human-readable warning per problem found.
+pub fn check_scene(objects: &[UIObject], lights: &[UILight], camera_position: Point3<f32>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let visible: Vec<&UIObject> = objects.iter().filter(|o| !o.hidden).collect();
+
+    for object in &visible {
+        let position = point![object.pos_x, object.pos_y, object.pos_z];
+        if !has_valid_dimensions(&object.ui_object_type) || !position.coords.iter().all(|c| c.is_finite()) {
+            warnings.push(format!("\"{}\" has a size of zero, negative or invalid dimensions.", object.name));
+            continue;
+        }
+        if !shader::intersection_self_test(&shader::Aabb::from(*object)) {
+            warnings.push(format!("\"{}\" failed the intersection self test and may be missing in the render.", object.name));
+        }
+        if contains_point(&object.ui_object_type, camera_position - position) {
+            warnings.push(format!("\"{}\" encloses the camera, only its inside will be visible.", object.name));
+        }
+    }
+
+    for (i, first) in visible.iter().enumerate() {
+        for second in &visible[i + 1..] {
+            if let (Some(first_box), Some(second_box)) = (axis_aligned_bounds(first), axis_aligned_bounds(second)) {
+                if have_coplanar_overlapping_faces(first_box, second_box) {
+                    warnings.push(format!("\"{}\" and \"{}\" have coplanar overlapping faces, which will \
+                        render as speckles.", first.name, second.name));
+                }
+            }
+        }
+    }
+
+    for pair in reflected_light(objects, lights) {
+        if pair.reflected_share < SPECTRAL_MISMATCH_THRESHOLD {
+            warnings.push(format!("\"{}\" reflects only {:.2}% of the light of \"{}\" and will appear \
+                black under it.", pair.object_name, pair.reflected_share * 100.0, pair.emitter_name));
+        }
+    }
+
+    warnings
+}
+
+/// Multiplies the spectrum of every emitter, visible lights and glowing objects, with the 
+/// reflectance of every visible object. Objects with a (nearly) black material are skipped, as 
+/// they are meant to reflect nothing, as are emitters without any radiance. 
+pub fn reflected_light(objects: &[UIObject], lights: &[UILight]) -> Vec<ReflectedLight> {
+    let visible_objects: Vec<&UIObject> = objects.iter().filter(|o| !o.hidden).collect();
+    //glowing objects are remembered, so that they are not paired with themselves
+    let mut emitters: Vec<(&str, Spectrum, Option<&UIObject>)> = lights.iter()
+        .filter(|l| !l.hidden)
+        .map(|l| (l.name.as_str(), l.spectrum.borrow().spectrum, None))
+        .collect();
+    emitters.extend(visible_objects.iter()
+        .filter_map(|o| o.emission.as_ref().map(|e| (o.name.as_str(), e.borrow().spectrum, Some(*o)))));
+
+    let mut pairs = Vec::new();
+    for object in &visible_objects {
+        let reflectance = object.material.borrow().spectrum.borrow().spectrum;
+        if reflectance.get_max() < SPECTRAL_MISMATCH_THRESHOLD {
+            continue;
+        }
+        let reflectance_samples: Vec<(f32, f32)> = reflectance.iter().collect();
+
+        for (emitter_name, emitted, source) in &emitters {
+            let emitted_radiance = emitted.get_radiance();
+            if source.is_some_and(|source| std::ptr::eq(source, *object)) || emitted_radiance <= 0.0 {
+                continue;
+            }
+            //the reflectance is brought onto the samples of the emitter, they may differ
+            let (lowest, highest) = emitted.get_range();
+            let reflectance = Spectrum::new_from_samples(&reflectance_samples, lowest, highest, emitted.get_nbr_of_samples());
+            let reflected = emitted * &reflectance;
+
+            let (r, g, b) = reflected.get_rgb_early();
+            let max = r.max(g).max(b);
+            let rgb = if max > 0.0 { (r / max, g / max, b / max) } else { (0.0, 0.0, 0.0) };
+            pairs.push(ReflectedLight {
+                object_name: object.name.clone(),
+                emitter_name: emitter_name.to_string(),
+                rgb,
+                reflected_share: reflected.get_radiance() / emitted_radiance,
+            });
+        }
+    }
+    pairs
+}
+
+fn has_valid_dimensions(object_type: &UIObjectType) -> bool {
+    let is_valid_length = |length: f32| length.is_finite() && length > 0.0;
+    match *object_type {
+        UIObjectType::PlainBox(x, y, z) => [x, y, z].into_iter().all(is_valid_length),
+        UIObjectType::Sphere(radius) => is_valid_length(radius),
+        UIObjectType::RotatedBox(x, y, z, x_rotation, y_rotation, z_rotation) => {
+            [x, y, z].into_iter().all(is_valid_length)
+                && [x_rotation, y_rotation, z_rotation].iter().all(|r| r.is_finite())
+        }
+        UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+            [radius, height].into_iter().all(is_valid_length)
+                && [x_rotation, y_rotation, z_rotation].iter().all(|r| r.is_finite())
+        }
+        UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+            [x_radius, y_radius, z_radius].into_iter().all(is_valid_length)
+                && [x_rotation, y_rotation, z_rotation].iter().all(|r| r.is_finite())
+        }
+        UIObjectType::Disc(radius, normal_x, normal_y, normal_z) => {
+            is_valid_length(radius) && is_valid_normal(normal_x, normal_y, normal_z)
+        }
+        UIObjectType::Quad(width, depth, normal_x, normal_y, normal_z) => {
+            [width, depth].into_iter().all(is_valid_length) && is_valid_normal(normal_x, normal_y, normal_z)
+        }
+        UIObjectType::Csg(ref csg) => {
+            [&csg.first, &csg.second].iter().all(|part| {
+                [part.offset_x, part.offset_y, part.offset_z].iter().all(|o| o.is_finite())
+                    && has_valid_dimensions(&part.ui_object_type)
+            })
+        }
+        UIObjectType::Heightfield(ref heightfield) => {
+            [heightfield.width, heightfield.depth, heightfield.height].into_iter().all(is_valid_length)
+        }
+    }
+}
+
+fn is_valid_normal(x: f32, y: f32, z: f32) -> bool {
+    [x, y, z].iter().all(|c| c.is_finite()) && Vector3::new(x, y, z).norm() > 0.0
+}
+
+/// Returns true if the point, given relative to the position of the object, lies strictly inside 
+/// the object.
+fn contains_point(object_type: &UIObjectType, offset: Vector3<f32>) -> bool {
+    let inside_box = |offset: Vector3<f32>, x: f32, y: f32, z: f32| {
+        offset.x.abs() < x / 2.0 && offset.y.abs() < y / 2.0 && offset.z.abs() < z / 2.0
+    };
+    match *object_type {
+        UIObjectType::PlainBox(x, y, z) => inside_box(offset, x, y, z),
+        UIObjectType::Sphere(radius) => offset.norm() < radius,
+        UIObjectType::RotatedBox(x, y, z, x_rotation, y_rotation, z_rotation) => {
+            let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
+            inside_box(rotation.inverse() * offset, x, y, z)
+        }
+        UIObjectType::Cylinder(radius, height, x_rotation, y_rotation, z_rotation) => {
+            let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
+            let local = rotation.inverse() * offset;
+            local.y.abs() < height / 2.0 && local.x.hypot(local.z) < radius
+        }
+        UIObjectType::Ellipsoid(x_radius, y_radius, z_radius, x_rotation, y_rotation, z_rotation) => {
+            let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
+            (rotation.inverse() * offset).component_div(&Vector3::new(x_radius, y_radius, z_radius)).norm() < 1.0
+        }
+        //flat objects and heightfields have no inside
+        UIObjectType::Disc(..) | UIObjectType::Quad(..) | UIObjectType::Heightfield(..) => false,
+        UIObjectType::Csg(ref csg) => {
+            let part_contains = |part: &UICsgPart| {
+                contains_point(&part.ui_object_type, offset - vector![part.offset_x, part.offset_y, part.offset_z])
+            };
+            csg.operation.contains(part_contains(&csg.first), part_contains(&csg.second))
+        }
+    }
+}
+
+/// Returns the smallest and largest corner of the object if it is an axis aligned box.
+fn axis_aligned_bounds(object: &UIObject) -> Option<(Point3<f32>, Point3<f32>)> {
+    match object.ui_object_type {
+        UIObjectType::PlainBox(x, y, z) => {
+            let center = point![object.pos_x, object.pos_y, object.pos_z];
+            let half = Vector3::new(x, y, z) / 2.0;
+            Some((center - half, center + half))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the two boxes have faces facing the same way which lie in the same plane and
+/// overlap in an area. Faces touching back to back, like a box standing on the floor, are fine.
+fn have_coplanar_overlapping_faces(first: (Point3<f32>, Point3<f32>), second: (Point3<f32>, Point3<f32>)) -> bool {
+    (0..3).any(|axis| {
+        let coplanar = (first.0[axis] - second.0[axis]).abs() < COPLANAR_TOLERANCE
+            || (first.1[axis] - second.1[axis]).abs() < COPLANAR_TOLERANCE;
+        let overlapping = (0..3).filter(|other| *other != axis).all(|other| {
+            first.1[other].min(second.1[other]) - first.0[other].max(second.0[other]) > COPLANAR_TOLERANCE
+        });
+        coplanar && overlapping
+    })
+}
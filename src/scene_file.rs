@@ -0,0 +1,738 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use crate::{RenderScale, SpectrumEffectType, SPECTRUM_RANGE_WIDTH_MIN, UIAtmosphere, UIBackground, UICamera, UIColorResponse, UIFields, UIFluorescence,
+            UIGroup, UIHeightfield, UILight, UIMaterial, UIMaterialMix, UIMedium, UIObject, UIObjectType, UIPattern,
+            UISpectrum, UISpectrumType};
+use crate::custom_image::FireflyFilter;
+use crate::heightfield::{HeightGrid, HeightfieldSource};
+use crate::light_selection::LightSelection;
+use crate::platform::Instant;
+use crate::procedural::Pattern;
+use crate::sampler::SamplerType;
+use crate::shader::{LightLinkMode, MixWeight};
+use crate::spectrum::{self, Spectrum, NBR_OF_SAMPLES_MAX};
+use crate::spectrum_file;
+use crate::texture::TextureCache;
+
+/// The version of the format written into every scene file. Files of other versions are rejected.
+const SCENE_FILE_VERSION: u32 = 1;
+/// How often a watched scene file is checked for changes.
+pub const SCENE_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Writes the settings and the scene of the UI values into the given file as RON. Spectra,
+/// materials and groups are written once and referred to by their id, so that elements sharing
+/// them still share them after reading the file. Textures and heightfield images are referred to
+/// by their path, the curves of a custom camera sensor are not written.
+pub fn write(path: &Path, ui_values: &UIFields) -> Result<(), SceneFileError> {
+    std::fs::write(path, to_ron(ui_values)?)
+        .map_err(|e| SceneFileError {error: format!("Could not write {}: {e}", path.display())})
+}
+
+/// Returns the content of the scene file of the UI values, see [write].
+pub fn to_ron(ui_values: &UIFields) -> Result<String, SceneFileError> {
+    ron::ser::to_string_pretty(&SceneFile::from(ui_values), ron::ser::PrettyConfig::default())
+        .map_err(|e| SceneFileError {error: format!("Could not serialize the scene: {e}")})
+}
+
+/// Reads the scene file at the given path, see [write], and overwrites the settings and the scene
+/// of the UI values with it. The UI values are left untouched if the file can not be read.
+/// Selections referring to the replaced spectra are cleared.
+pub fn read(path: &Path, ui_values: &mut UIFields, texture_cache: &mut TextureCache) -> Result<(), SceneFileError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SceneFileError {error: format!("Could not read {}: {e}", path.display())})?;
+    from_ron(&content, ui_values, texture_cache)
+        .map_err(|e| SceneFileError {error: format!("Could not open {}: {}", path.display(), e.error)})
+}
+
+/// Overwrites the settings and the scene of the UI values with the content of a scene file, see
+/// [read].
+pub fn from_ron(content: &str, ui_values: &mut UIFields, texture_cache: &mut TextureCache) -> Result<(), SceneFileError> {
+    let file: SceneFile = ron::from_str(content)
+        .map_err(|e| SceneFileError {error: format!("Could not parse the scene: {e}")})?;
+    if file.version != SCENE_FILE_VERSION {
+        return Err(SceneFileError {
+            error: format!("Scene files of version {} are not supported, expected version {SCENE_FILE_VERSION}!", file.version)
+        });
+    }
+    file.restore(ui_values, texture_cache)
+}
+
+/// The settings and the scene as they are written into the file. Elements shared by several
+/// others are referred to by the id they had when the file was written.
+#[derive(Serialize, Deserialize)]
+struct SceneFile {
+    version: u32,
+    settings: SettingsEntry,
+    spectra: Vec<SpectrumEntry>,
+    materials: Vec<MaterialEntry>,
+    groups: Vec<GroupEntry>,
+    cameras: Vec<UICamera>,
+    active_camera: usize,
+    lights: Vec<LightEntry>,
+    objects: Vec<ObjectEntry>,
+    medium: Option<MediumEntry>,
+    atmosphere: Option<AtmosphereEntry>,
+    background: BackgroundEntry,
+}
+
+/// The render settings, the same ones a render in the gallery can be restored with.
+#[derive(Serialize, Deserialize)]
+struct SettingsEntry {
+    width: u32,
+    height: u32,
+    render_scale: RenderScale,
+    nbr_of_iterations: u32,
+    nbr_of_ray_bounces: u32,
+    nbr_of_diffuse_bounces: u32,
+    nbr_of_specular_bounces: u32,
+    nbr_of_transmission_bounces: u32,
+    russian_roulette: bool,
+    sampler_type: SamplerType,
+    random_seed: u32,
+    light_selection: LightSelection,
+    light_samples: u32,
+    photon_count: u32,
+    photon_radius: f32,
+    irradiance_cache_quality: Option<f32>,
+    ray_offset: f32,
+    firefly_filter: FireflyFilter,
+    gamut_compression: bool,
+    color_response: UIColorResponse,
+    light_scale: f32,
+    spectrum_lower_bound: f32,
+    spectrum_upper_bound: f32,
+    spectrum_number_of_samples: usize,
+}
+
+impl SettingsEntry {
+    /// Checks the settings which the UI keeps in bounds, but a hand edited or received file may 
+    /// not, and which would make the render panic. 
+    fn check(&self) -> Result<(), SceneFileError> {
+        let invalid = |error: String| Err(SceneFileError {error: format!("The settings are invalid: {error}")});
+        if self.width == 0 || self.height == 0 {
+            return invalid(format!("The image size {}x{} is empty!", self.width, self.height));
+        }
+        if self.nbr_of_iterations == 0 {
+            return invalid("At least one iteration has to be rendered!".to_string());
+        }
+        if let Err(e) = spectrum_file::check_nbr_of_samples(self.spectrum_number_of_samples) {
+            return invalid(e.error);
+        }
+        let (lower, upper) = (self.spectrum_lower_bound, self.spectrum_upper_bound);
+        if !(spectrum::EXTENDED_WAVELENGTH_LOWER_LIMIT..=spectrum::EXTENDED_WAVELENGTH_UPPER_LIMIT).contains(&lower) 
+            || !(spectrum::EXTENDED_WAVELENGTH_LOWER_LIMIT..=spectrum::EXTENDED_WAVELENGTH_UPPER_LIMIT).contains(&upper) 
+            || upper - lower < SPECTRUM_RANGE_WIDTH_MIN {
+            return invalid(format!("The spectrum range from {lower} nm to {upper} nm must lie between {} nm and {} nm \
+                and span at least {SPECTRUM_RANGE_WIDTH_MIN} nm!", spectrum::EXTENDED_WAVELENGTH_LOWER_LIMIT, 
+                spectrum::EXTENDED_WAVELENGTH_UPPER_LIMIT));
+        }
+        Ok(())
+    }
+}
+
+/// A spectrum with its equidistant samples. Spectra and materials which are only referred to, but
+/// were removed from the lists of the UI, are written as well, but not listed again when read.
+#[derive(Serialize, Deserialize)]
+struct SpectrumEntry {
+    id: u32,
+    name: String,
+    listed: bool,
+    spectrum_type: UISpectrumType,
+    spectrum_effect_type: SpectrumEffectType,
+    lowest_wavelength: f32,
+    highest_wavelength: f32,
+    intensities: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MaterialEntry {
+    id: u32,
+    name: String,
+    listed: bool,
+    metallicness: f32,
+    roughness: f32,
+    bitangent_roughness: Option<f32>,
+    tangent_rotation: f32,
+    spectrum: u32,
+    pattern: Option<(Pattern, u32)>,
+    /// The ids of the excitation and the emission spectrum and the quantum yield.
+    fluorescence: Option<(u32, u32, f32)>,
+    shadow_catcher: bool,
+    /// The ids of the first and the second material and the weight.
+    mix: Option<(u32, u32, MixWeight)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GroupEntry {
+    id: u32,
+    name: String,
+    position: (f32, f32, f32),
+    rotation: (f32, f32, f32),
+    parent: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LightEntry {
+    name: String,
+    position: (f32, f32, f32),
+    spectrum: u32,
+    group: Option<u32>,
+    link_mode: LightLinkMode,
+    linked_objects: Vec<u32>,
+    hidden: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ObjectEntry {
+    /// The id the light linking refers to.
+    id: u32,
+    name: String,
+    position: (f32, f32, f32),
+    material: u32,
+    ui_object_type: UIObjectType,
+    emission: Option<u32>,
+    emission_visible_to_camera: bool,
+    emission_visible_in_reflections: bool,
+    visible_to_camera: bool,
+    visible_in_reflections: bool,
+    casts_shadows: bool,
+    /// The path of the image the texture is read from.
+    texture: Option<PathBuf>,
+    group: Option<u32>,
+    hidden: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MediumEntry {
+    absorption: u32,
+    scattering: u32,
+    density: f32,
+    in_scattering: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AtmosphereEntry {
+    sun_spectrum: u32,
+    sun_elevation_deg: f32,
+    sun_azimuth_deg: f32,
+    turbidity: f32,
+    altitude: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+enum BackgroundEntry {
+    Black,
+    Spectrum(u32),
+    Gradient {top: u32, bottom: u32},
+    Transparent,
+}
+
+/// A [heightfield](UIHeightfield) without its heights, which are created from the source again
+/// when the file is read.
+#[derive(Serialize, Deserialize)]
+pub struct HeightfieldEntry {
+    width: f32,
+    depth: f32,
+    height: f32,
+    source: HeightfieldSource,
+}
+
+impl From<UIHeightfield> for HeightfieldEntry {
+    fn from(value: UIHeightfield) -> Self {
+        HeightfieldEntry {
+            width: value.width,
+            depth: value.depth,
+            height: value.height,
+            source: value.source,
+        }
+    }
+}
+
+impl TryFrom<HeightfieldEntry> for UIHeightfield {
+    type Error = String;
+
+    fn try_from(value: HeightfieldEntry) -> Result<Self, Self::Error> {
+        let grid = HeightGrid::from_source(&value.source).map_err(|e| e.error)?;
+        Ok(UIHeightfield {
+            width: value.width,
+            depth: value.depth,
+            height: value.height,
+            source: value.source,
+            grid: Arc::new(grid),
+        })
+    }
+}
+
+/// Collects the spectra and materials while the scene is written, including the ones which are
+/// only referred to. Each of them is written once.
+#[derive(Default)]
+struct Collector {
+    spectra: Vec<SpectrumEntry>,
+    materials: Vec<MaterialEntry>,
+}
+
+impl Collector {
+    /// Writes the spectrum unless it was written before and returns its id.
+    fn spectrum(&mut self, spectrum: &Rc<RefCell<UISpectrum>>, listed: bool) -> u32 {
+        let spectrum = spectrum.borrow();
+        if !self.spectra.iter().any(|entry| entry.id == spectrum.id) {
+            let (lowest_wavelength, highest_wavelength) = spectrum.spectrum.get_range();
+            self.spectra.push(SpectrumEntry {
+                id: spectrum.id,
+                name: spectrum.name.clone(),
+                listed,
+                spectrum_type: spectrum.spectrum_type,
+                spectrum_effect_type: spectrum.spectrum_effect_type,
+                lowest_wavelength,
+                highest_wavelength,
+                intensities: spectrum.spectrum.iter().map(|(_, intensity)| intensity).collect(),
+            });
+        }
+        spectrum.id
+    }
+
+    /// Writes the material together with its spectra and the materials it is a mix of, unless it
+    /// was written before, and returns its id.
+    fn material(&mut self, material: &Rc<RefCell<UIMaterial>>, listed: bool) -> u32 {
+        let material = material.borrow();
+        if self.materials.iter().any(|entry| entry.id == material.id) {
+            return material.id;
+        }
+        //pushed before the mix is resolved, so that mixes referring back to it end
+        self.materials.push(MaterialEntry {
+            id: material.id,
+            name: material.name.clone(),
+            listed,
+            metallicness: material.metallicness,
+            roughness: material.roughness,
+            bitangent_roughness: material.bitangent_roughness,
+            tangent_rotation: material.tangent_rotation,
+            spectrum: 0,
+            pattern: None,
+            fluorescence: None,
+            shadow_catcher: material.shadow_catcher,
+            mix: None,
+        });
+        let index = self.materials.len() - 1;
+        self.materials[index].spectrum = self.spectrum(&material.spectrum, false);
+        self.materials[index].pattern = material.pattern.as_ref()
+            .map(|pattern| (pattern.pattern, self.spectrum(&pattern.spectrum, false)));
+        self.materials[index].fluorescence = material.fluorescence.as_ref()
+            .map(|f| (self.spectrum(&f.excitation, false), self.spectrum(&f.emission, false), f.quantum_yield));
+        self.materials[index].mix = material.mix.as_ref()
+            .map(|mix| (self.material(&mix.first, false), self.material(&mix.second, false), mix.weight));
+        material.id
+    }
+}
+
+impl From<&UIFields> for SceneFile {
+    fn from(ui_values: &UIFields) -> Self {
+        let mut collector = Collector::default();
+        //the listed ones first, so that they are not taken for ones which are only referred to
+        for spectrum in &ui_values.spectra {
+            collector.spectrum(spectrum, true);
+        }
+        for material in &ui_values.materials {
+            collector.material(material, true);
+        }
+
+        let group_id = |group: &Option<Rc<RefCell<UIGroup>>>| group.as_ref().map(|group| group.borrow().id);
+        let groups = ui_values.ui_groups.iter().map(|group| {
+            let group = group.borrow();
+            GroupEntry {
+                id: group.id,
+                name: group.name.clone(),
+                position: (group.pos_x, group.pos_y, group.pos_z),
+                rotation: (group.rot_x, group.rot_y, group.rot_z),
+                parent: group_id(&group.parent),
+            }
+        }).collect();
+        let lights = ui_values.ui_lights.iter().map(|light| LightEntry {
+            name: light.name.clone(),
+            position: (light.pos_x, light.pos_y, light.pos_z),
+            spectrum: collector.spectrum(&light.spectrum, false),
+            group: group_id(&light.group),
+            link_mode: light.link_mode,
+            linked_objects: light.linked_objects.clone(),
+            hidden: light.hidden,
+        }).collect();
+        let objects = ui_values.ui_objects.iter().map(|object| ObjectEntry {
+            id: object.id,
+            name: object.name.clone(),
+            position: (object.pos_x, object.pos_y, object.pos_z),
+            material: collector.material(&object.material, false),
+            ui_object_type: object.ui_object_type.clone(),
+            emission: object.emission.as_ref().map(|emission| collector.spectrum(emission, false)),
+            emission_visible_to_camera: object.emission_visible_to_camera,
+            emission_visible_in_reflections: object.emission_visible_in_reflections,
+            visible_to_camera: object.visible_to_camera,
+            visible_in_reflections: object.visible_in_reflections,
+            casts_shadows: object.casts_shadows,
+            texture: object.texture.as_ref().map(|texture| texture.get_path().to_path_buf()),
+            group: group_id(&object.group),
+            hidden: object.hidden,
+        }).collect();
+        let medium = ui_values.medium.as_ref().map(|medium| MediumEntry {
+            absorption: collector.spectrum(&medium.absorption, false),
+            scattering: collector.spectrum(&medium.scattering, false),
+            density: medium.density,
+            in_scattering: medium.in_scattering,
+        });
+        let atmosphere = ui_values.atmosphere.as_ref().map(|atmosphere| AtmosphereEntry {
+            sun_spectrum: collector.spectrum(&atmosphere.sun_spectrum, false),
+            sun_elevation_deg: atmosphere.sun_elevation_deg,
+            sun_azimuth_deg: atmosphere.sun_azimuth_deg,
+            turbidity: atmosphere.turbidity,
+            altitude: atmosphere.altitude,
+        });
+        let background = match &ui_values.background {
+            UIBackground::Black => BackgroundEntry::Black,
+            UIBackground::Spectrum(spectrum) => BackgroundEntry::Spectrum(collector.spectrum(spectrum, false)),
+            UIBackground::Gradient {top, bottom} => BackgroundEntry::Gradient {
+                top: collector.spectrum(top, false),
+                bottom: collector.spectrum(bottom, false),
+            },
+            UIBackground::Transparent => BackgroundEntry::Transparent,
+        };
+
+        SceneFile {
+            version: SCENE_FILE_VERSION,
+            settings: SettingsEntry {
+                width: ui_values.width,
+                height: ui_values.height,
+                render_scale: ui_values.render_scale,
+                nbr_of_iterations: ui_values.nbr_of_iterations,
+                nbr_of_ray_bounces: ui_values.nbr_of_ray_bounces,
+                nbr_of_diffuse_bounces: ui_values.nbr_of_diffuse_bounces,
+                nbr_of_specular_bounces: ui_values.nbr_of_specular_bounces,
+                nbr_of_transmission_bounces: ui_values.nbr_of_transmission_bounces,
+                russian_roulette: ui_values.russian_roulette,
+                sampler_type: ui_values.sampler_type,
+                random_seed: ui_values.random_seed,
+                light_selection: ui_values.light_selection,
+                light_samples: ui_values.light_samples,
+                photon_count: ui_values.photon_count,
+                photon_radius: ui_values.photon_radius,
+                irradiance_cache_quality: ui_values.irradiance_cache_quality,
+                ray_offset: ui_values.ray_offset,
+                firefly_filter: ui_values.firefly_filter,
+                gamut_compression: ui_values.gamut_compression,
+                color_response: ui_values.color_response,
+                light_scale: ui_values.light_scale,
+                spectrum_lower_bound: ui_values.spectrum_lower_bound,
+                spectrum_upper_bound: ui_values.spectrum_upper_bound,
+                spectrum_number_of_samples: ui_values.spectrum_number_of_samples,
+            },
+            spectra: collector.spectra,
+            materials: collector.materials,
+            groups,
+            cameras: ui_values.ui_cameras.clone(),
+            active_camera: ui_values.active_camera,
+            lights,
+            objects,
+            medium,
+            atmosphere,
+            background,
+        }
+    }
+}
+
+/// Looks up the element the file refers to by the given id.
+fn resolve<T>(elements: &HashMap<u32, Rc<RefCell<T>>>, id: u32, kind: &str) -> Result<Rc<RefCell<T>>, SceneFileError> {
+    elements.get(&id).cloned()
+        .ok_or_else(|| SceneFileError {error: format!("The scene file refers to the unknown {kind} {id}!")})
+}
+
+impl SceneFile {
+    /// Rebuilds the scene and overwrites the settings and the scene of the UI values with it. All
+    /// elements get new ids, the light linking is adjusted to the new ids of the objects.
+    fn restore(self, ui_values: &mut UIFields, texture_cache: &mut TextureCache) -> Result<(), SceneFileError> {
+        if self.cameras.is_empty() || self.active_camera >= self.cameras.len() {
+            return Err(SceneFileError {error: "The scene file holds no active camera!".to_string()});
+        }
+        self.settings.check()?;
+
+        let mut spectra = HashMap::new();
+        let mut listed_spectra = Vec::new();
+        for entry in self.spectra {
+            let nbr_of_samples = spectrum_file::check_nbr_of_samples(entry.intensities.len())
+                .map_err(|e| SceneFileError {error: format!("The spectrum \"{}\" is invalid: {}", entry.name, e.error)})?;
+            let mut intensities = [0f32; NBR_OF_SAMPLES_MAX];
+            intensities[0..nbr_of_samples].copy_from_slice(&entry.intensities);
+            let spectrum = Spectrum::new_from_list(&intensities, entry.lowest_wavelength, entry.highest_wavelength,
+                                                   nbr_of_samples);
+            let spectrum = Rc::new(RefCell::new(UISpectrum::new(entry.name, entry.spectrum_type,
+                                                                entry.spectrum_effect_type, spectrum)));
+            if entry.listed {
+                listed_spectra.push(spectrum.clone());
+            }
+            spectra.insert(entry.id, spectrum);
+        }
+        let spectrum = |id: u32| resolve(&spectra, id, "spectrum");
+
+        //the mixes are resolved once all materials exist, as they may refer to later ones
+        let mut materials = HashMap::new();
+        let mut listed_materials = Vec::new();
+        for entry in &self.materials {
+            let mut material = UIMaterial::new(entry.metallicness, entry.roughness, spectrum(entry.spectrum)?,
+                                               entry.name.clone());
+            material.bitangent_roughness = entry.bitangent_roughness;
+            material.tangent_rotation = entry.tangent_rotation;
+            material.pattern = match entry.pattern {
+                Some((pattern, id)) => Some(UIPattern {pattern, spectrum: spectrum(id)?}),
+                None => None,
+            };
+            material.fluorescence = match entry.fluorescence {
+                Some((excitation, emission, quantum_yield)) => Some(UIFluorescence {
+                    excitation: spectrum(excitation)?,
+                    emission: spectrum(emission)?,
+                    quantum_yield,
+                }),
+                None => None,
+            };
+            material.shadow_catcher = entry.shadow_catcher;
+            let material = Rc::new(RefCell::new(material));
+            if entry.listed {
+                listed_materials.push(material.clone());
+            }
+            materials.insert(entry.id, material);
+        }
+        for entry in &self.materials {
+            if let Some((first, second, weight)) = entry.mix {
+                let mix = UIMaterialMix {
+                    first: resolve(&materials, first, "material")?,
+                    second: resolve(&materials, second, "material")?,
+                    weight,
+                };
+                materials[&entry.id].borrow_mut().mix = Some(mix);
+            }
+        }
+        let material = |id: u32| resolve(&materials, id, "material");
+
+        //as with the mixes, the parents are resolved once all groups exist
+        let mut groups = HashMap::new();
+        let mut listed_groups = Vec::new();
+        for entry in &self.groups {
+            let mut group = UIGroup::new(entry.name.clone());
+            (group.pos_x, group.pos_y, group.pos_z) = entry.position;
+            (group.rot_x, group.rot_y, group.rot_z) = entry.rotation;
+            let group = Rc::new(RefCell::new(group));
+            listed_groups.push(group.clone());
+            groups.insert(entry.id, group);
+        }
+        for entry in &self.groups {
+            if let Some(parent) = entry.parent {
+                groups[&entry.id].borrow_mut().parent = Some(resolve(&groups, parent, "group")?);
+            }
+        }
+        let group = |id: Option<u32>| id.map(|id| resolve(&groups, id, "group")).transpose();
+
+        let mut object_ids = HashMap::new();
+        let mut objects = Vec::with_capacity(self.objects.len());
+        for entry in self.objects {
+            let (pos_x, pos_y, pos_z) = entry.position;
+            let mut object = UIObject::new(pos_x, pos_y, pos_z, material(entry.material)?, entry.ui_object_type,
+                                           entry.name);
+            object.emission = entry.emission.map(spectrum).transpose()?;
+            object.emission_visible_to_camera = entry.emission_visible_to_camera;
+            object.emission_visible_in_reflections = entry.emission_visible_in_reflections;
+            object.visible_to_camera = entry.visible_to_camera;
+            object.visible_in_reflections = entry.visible_in_reflections;
+            object.casts_shadows = entry.casts_shadows;
+            object.texture = match entry.texture {
+                Some(path) => Some(texture_cache.get(&path).map_err(|e| SceneFileError {error: e.error})?),
+                None => None,
+            };
+            object.group = group(entry.group)?;
+            object.hidden = entry.hidden;
+            object_ids.insert(entry.id, object.id);
+            objects.push(object);
+        }
+
+        let mut lights = Vec::with_capacity(self.lights.len());
+        for entry in self.lights {
+            let (pos_x, pos_y, pos_z) = entry.position;
+            let mut light = UILight::new(pos_x, pos_y, pos_z, spectrum(entry.spectrum)?, entry.name);
+            light.group = group(entry.group)?;
+            light.link_mode = entry.link_mode;
+            light.linked_objects = entry.linked_objects.iter().filter_map(|id| object_ids.get(id).copied()).collect();
+            light.hidden = entry.hidden;
+            lights.push(light);
+        }
+
+        let medium = match self.medium {
+            Some(medium) => Some(UIMedium {
+                absorption: spectrum(medium.absorption)?,
+                scattering: spectrum(medium.scattering)?,
+                density: medium.density,
+                in_scattering: medium.in_scattering,
+            }),
+            None => None,
+        };
+        let atmosphere = match self.atmosphere {
+            Some(atmosphere) => Some(UIAtmosphere {
+                sun_spectrum: spectrum(atmosphere.sun_spectrum)?,
+                sun_elevation_deg: atmosphere.sun_elevation_deg,
+                sun_azimuth_deg: atmosphere.sun_azimuth_deg,
+                turbidity: atmosphere.turbidity,
+                altitude: atmosphere.altitude,
+            }),
+            None => None,
+        };
+        let background = match self.background {
+            BackgroundEntry::Black => UIBackground::Black,
+            BackgroundEntry::Spectrum(id) => UIBackground::Spectrum(spectrum(id)?),
+            BackgroundEntry::Gradient {top, bottom} => UIBackground::Gradient {
+                top: spectrum(top)?,
+                bottom: spectrum(bottom)?,
+            },
+            BackgroundEntry::Transparent => UIBackground::Transparent,
+        };
+
+        let settings = self.settings;
+        ui_values.width = settings.width;
+        ui_values.height = settings.height;
+        ui_values.render_scale = settings.render_scale;
+        ui_values.nbr_of_iterations = settings.nbr_of_iterations;
+        ui_values.nbr_of_ray_bounces = settings.nbr_of_ray_bounces;
+        ui_values.nbr_of_diffuse_bounces = settings.nbr_of_diffuse_bounces;
+        ui_values.nbr_of_specular_bounces = settings.nbr_of_specular_bounces;
+        ui_values.nbr_of_transmission_bounces = settings.nbr_of_transmission_bounces;
+        ui_values.russian_roulette = settings.russian_roulette;
+        ui_values.sampler_type = settings.sampler_type;
+        ui_values.random_seed = settings.random_seed;
+        ui_values.light_selection = settings.light_selection;
+        ui_values.light_samples = settings.light_samples;
+        ui_values.photon_count = settings.photon_count;
+        ui_values.photon_radius = settings.photon_radius;
+        ui_values.irradiance_cache_quality = settings.irradiance_cache_quality;
+        ui_values.ray_offset = settings.ray_offset;
+        ui_values.firefly_filter = settings.firefly_filter;
+        ui_values.gamut_compression = settings.gamut_compression;
+        ui_values.color_response = settings.color_response;
+        ui_values.light_scale = settings.light_scale;
+        ui_values.spectrum_lower_bound = settings.spectrum_lower_bound;
+        ui_values.spectrum_upper_bound = settings.spectrum_upper_bound;
+        ui_values.spectrum_number_of_samples = settings.spectrum_number_of_samples;
+
+        ui_values.spectra = listed_spectra;
+        ui_values.materials = listed_materials;
+        ui_values.ui_cameras = self.cameras;
+        ui_values.active_camera = self.active_camera;
+        ui_values.ui_lights = lights;
+        ui_values.ui_objects = objects;
+        ui_values.ui_groups = listed_groups;
+        ui_values.medium = medium;
+        ui_values.atmosphere = atmosphere;
+        ui_values.background = background;
+        ui_values.selected_spectrum = None;
+        ui_values.compared_spectra.clear();
+        Ok(())
+    }
+}
+
+/// Watches a scene file on disk by polling the time it was last modified, so that scenes edited in
+/// a text editor can be rendered again whenever they are saved.
+pub struct SceneWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl SceneWatch {
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modification_time(&path);
+        SceneWatch {
+            path,
+            modified,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the file was modified since the last call. The file is checked at most
+    /// every [SCENE_WATCH_INTERVAL], a file which is missing for a moment while an editor saves it
+    /// is not taken for a change.
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.elapsed() < SCENE_WATCH_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        match modification_time(&self.path) {
+            Some(modified) if Some(modified) != self.modified => {
+                self.modified = Some(modified);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// An error type used when writing or reading scene files. Specific details of the error are
+/// given in the error String.
+#[derive(Debug)]
+pub struct SceneFileError {
+    pub error: String,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::App;
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let app = App::new(None);
+        let written = to_ron(&app.ui_values).unwrap();
+        let mut restored = App::new(None);
+        from_ron(&written, &mut restored.ui_values, &mut restored.texture_cache).unwrap();
+        let rewritten = to_ron(&restored.ui_values).unwrap();
+        
+        //the restored elements get new ids, which have to replace the old ones consistently
+        assert_eq!(written.lines().count(), rewritten.lines().count(), "The scene changed when it was read again!");
+        let mut ids = HashMap::new();
+        for (line, reread_line) in written.lines().zip(rewritten.lines()) {
+            if line == reread_line {
+                continue;
+            }
+            let (key, id) = line.trim().split_once(": ").unwrap_or_default();
+            let (reread_key, reread_id) = reread_line.trim().split_once(": ").unwrap_or_default();
+            assert!(key == reread_key && ["id", "spectrum", "material"].contains(&key), 
+                    "The line \"{line}\" changed to \"{reread_line}\" when the scene was read again!");
+            assert_eq!(*ids.entry(id).or_insert(reread_id), reread_id, "The id {id} was not replaced consistently!");
+        }
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let mut app = App::new(None);
+        let written = to_ron(&app.ui_values).unwrap();
+        let samples = format!("spectrum_number_of_samples: {}", app.ui_values.spectrum_number_of_samples);
+        let replacements = [
+            (samples.clone(), "spectrum_number_of_samples: 12".to_string()),
+            (samples, "spectrum_number_of_samples: 256".to_string()),
+            (format!("width: {},", app.ui_values.width), "width: 0,".to_string()),
+            ("spectrum_upper_bound: 780.0".to_string(), "spectrum_upper_bound: 385.0".to_string()),
+            ("spectrum_lower_bound: 380.0".to_string(), "spectrum_lower_bound: 10.0".to_string()),
+        ];
+        for (original, replacement) in replacements {
+            assert!(written.contains(&original), "The scene holds no \"{original}\"!");
+            let edited = written.replacen(&original, &replacement, 1);
+            assert!(from_ron(&edited, &mut app.ui_values, &mut app.texture_cache).is_err(), 
+                    "The scene with \"{replacement}\" was accepted!");
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use rhai::{Dynamic, Engine, EvalAltResult};
+use crate::{SpectrumEffectType, UIFields, UILight, UIMaterial, UIObject, UIObjectType, UISpectrum, UISpectrumType};
+use crate::spectrum::Spectrum;
+
+/// The number of operations after which a script is stopped, so that an endless loop does not
+/// freeze the app.
+const SCRIPT_OPERATIONS_MAX: u64 = 50_000_000;
+/// The number of patches of the ColorChecker chart.
+const COLOR_CHECKER_PATCHES: i64 = 24;
+/// The script shown when the scripting tab is opened for the first time.
+const EXAMPLE_SCRIPT: &str = r#"// 100 spheres in a spiral, lit from above
+let white = reflective("Script White", 0.8);
+let red = reflective_rgb("Script Red", 0.8, 0.1, 0.1);
+let floor = material("Script Floor", white, 0.0, 0.8);
+let glossy = material("Script Glossy Red", red, 0.0, 0.2);
+
+cuboid("Floor", 0.0, -0.1, 0.0, 20.0, 0.2, 20.0, floor);
+for i in 0..100 {
+    let angle = i * 0.3;
+    let radius = 0.5 + i * 0.06;
+    sphere(`Sphere ${i}`, radius * angle.cos(), 0.3 + i * 0.02, radius * angle.sin(), 0.25, glossy);
+}
+light("Sun", 0.0, 10.0, 0.0, black_body("Script Light", 5500.0, 1.0));
+print("Spiral created.");
+"#;
+
+type SpectrumRef = Rc<RefCell<UISpectrum>>;
+type MaterialRef = Rc<RefCell<UIMaterial>>;
+
+/// The state of the scripting tab: the script which is edited and the output of its last run.
+pub struct ScriptConsole {
+    pub script: String,
+    /// Whether the objects and lights of the scene are removed before the script runs, so that
+    /// running it again does not duplicate them.
+    pub replace_scene: bool,
+    /// The text printed by the last run of the script, followed by its error, if any.
+    pub output: String,
+}
+
+impl Default for ScriptConsole {
+    fn default() -> Self {
+        ScriptConsole {
+            script: EXAMPLE_SCRIPT.to_string(),
+            replace_scene: true,
+            output: String::new(),
+        }
+    }
+}
+
+/// The scene a script builds. Changes to spectra and materials which already exist are collected
+/// and only made once the script ran without errors, as are the new objects and lights.
+struct ScriptScene {
+    lowest_wavelength: f32,
+    highest_wavelength: f32,
+    nbr_of_samples: usize,
+    /// The listed spectra and materials, followed by the ones created by the script.
+    spectra: Vec<SpectrumRef>,
+    materials: Vec<MaterialRef>,
+    spectrum_updates: Vec<(SpectrumRef, UISpectrumType, SpectrumEffectType, Spectrum)>,
+    /// The metallicness, roughness and spectrum of updated materials.
+    material_updates: Vec<(MaterialRef, f32, f32, SpectrumRef)>,
+    objects: Vec<UIObject>,
+    lights: Vec<UILight>,
+    output: String,
+}
+
+impl ScriptScene {
+    /// Adds a spectrum, or updates the one with the same name.
+    fn spectrum(&mut self, name: &str, spectrum_type: UISpectrumType, effect_type: SpectrumEffectType,
+                spectrum: Spectrum) -> SpectrumRef {
+        if let Some(existing) = self.spectra.iter().find(|s| s.borrow().name == name) {
+            self.spectrum_updates.push((existing.clone(), spectrum_type, effect_type, spectrum));
+            return existing.clone();
+        }
+        let spectrum = Rc::new(RefCell::new(UISpectrum::new(name.to_string(), spectrum_type, effect_type, spectrum)));
+        self.spectra.push(spectrum.clone());
+        spectrum
+    }
+
+    /// Adds a material, or updates the one with the same name.
+    fn material(&mut self, name: &str, spectrum: SpectrumRef, metallicness: f32, roughness: f32) -> MaterialRef {
+        if let Some(existing) = self.materials.iter().find(|m| m.borrow().name == name) {
+            self.material_updates.push((existing.clone(), metallicness, roughness, spectrum));
+            return existing.clone();
+        }
+        let material = Rc::new(RefCell::new(UIMaterial::new(metallicness, roughness, spectrum, name.to_string())));
+        self.materials.push(material.clone());
+        material
+    }
+}
+
+/// Runs the script against the scene of the UI values and returns the text it printed. The
+/// script creates spectra, materials, objects and lights with the functions described in the
+/// README. Spectra and materials are identified by their name: creating one with the name of an
+/// existing one updates the existing one. If the scene is replaced, its objects and lights are
+/// removed first. The UI values are left untouched if the script fails.
+pub fn run(script: &str, ui_values: &mut UIFields, replace_scene: bool) -> Result<String, ScriptError> {
+    let scene = Rc::new(RefCell::new(ScriptScene {
+        lowest_wavelength: ui_values.spectrum_lower_bound,
+        highest_wavelength: ui_values.spectrum_upper_bound,
+        nbr_of_samples: ui_values.spectrum_number_of_samples,
+        spectra: ui_values.spectra.clone(),
+        materials: ui_values.materials.clone(),
+        spectrum_updates: Vec::new(),
+        material_updates: Vec::new(),
+        objects: Vec::new(),
+        lights: Vec::new(),
+        output: String::new(),
+    }));
+    let mut engine = Engine::new();
+    engine.set_max_operations(SCRIPT_OPERATIONS_MAX);
+    register_scene_functions(&mut engine, &scene);
+    let result = engine.run(script);
+    drop(engine);
+    let scene = Rc::into_inner(scene).expect("only the dropped engine shared the scene").into_inner();
+    if let Err(e) = result {
+        return Err(ScriptError {error: format!("{}{e}", scene.output)});
+    }
+
+    for (spectrum, spectrum_type, effect_type, values) in scene.spectrum_updates {
+        let mut spectrum = spectrum.borrow_mut();
+        spectrum.spectrum_type = spectrum_type;
+        spectrum.spectrum_effect_type = effect_type;
+        spectrum.spectrum = values;
+    }
+    for (material, metallicness, roughness, spectrum) in scene.material_updates {
+        let mut material = material.borrow_mut();
+        material.metallicness = metallicness;
+        material.roughness = roughness;
+        material.spectrum = spectrum;
+    }
+    ui_values.spectra = scene.spectra;
+    ui_values.materials = scene.materials;
+    if replace_scene {
+        ui_values.ui_objects.clear();
+        ui_values.ui_lights.clear();
+    }
+    ui_values.ui_objects.extend(scene.objects);
+    ui_values.ui_lights.extend(scene.lights);
+    Ok(scene.output)
+}
+
+/// Converts a number of a script into a float, scripts may give integers as well. 
+fn number(value: Dynamic) -> Result<f32, Box<EvalAltResult>> {
+    match value.as_float() {
+        Ok(value) => Ok(value as f32),
+        Err(_) => value.as_int().map(|value| value as f32)
+            .map_err(|type_name| format!("Expected a number, but got {type_name}!").into()),
+    }
+}
+
+/// Makes the functions creating the elements of the scene available to scripts and collects the
+/// text they print.
+fn register_scene_functions(engine: &mut Engine, scene: &Rc<RefCell<ScriptScene>>) {
+    engine.register_type_with_name::<SpectrumRef>("Spectrum");
+    engine.register_type_with_name::<MaterialRef>("Material");
+
+    let shared = scene.clone();
+    engine.on_print(move |text| {
+        let mut scene = shared.borrow_mut();
+        scene.output.push_str(text);
+        scene.output.push('\n');
+    });
+
+    let shared = scene.clone();
+    engine.register_fn("reflective", move |name: &str, factor: Dynamic| -> Result<SpectrumRef, Box<EvalAltResult>> {
+        let factor = number(factor)?;
+        let mut scene = shared.borrow_mut();
+        let spectrum = Spectrum::new_singular_reflectance_factor(scene.lowest_wavelength, scene.highest_wavelength,
+                                                                 scene.nbr_of_samples, factor);
+        Ok(scene.spectrum(name, UISpectrumType::PlainReflective(factor), SpectrumEffectType::Reflective, spectrum))
+    });
+    let shared = scene.clone();
+    engine.register_fn("reflective_rgb", move |name: &str, r: Dynamic, g: Dynamic, b: Dynamic| 
+        -> Result<SpectrumRef, Box<EvalAltResult>> {
+        let rgb = (number(r)?, number(g)?, number(b)?);
+        if ![rgb.0, rgb.1, rgb.2].iter().all(|c| (0.0..=1.0).contains(c)) {
+            return Err(format!("The color {rgb:?} of \"{name}\" is not in range [0; 1]!").into());
+        }
+        let mut scene = shared.borrow_mut();
+        let spectrum = Spectrum::new_from_rgb_reflectance(rgb, scene.lowest_wavelength, scene.highest_wavelength,
+                                                          scene.nbr_of_samples);
+        Ok(scene.spectrum(name, UISpectrumType::Custom, SpectrumEffectType::Reflective, spectrum))
+    });
+    let shared = scene.clone();
+    engine.register_fn("color_checker", move |name: &str, patch: i64| -> Result<SpectrumRef, Box<EvalAltResult>> {
+        if !(0..COLOR_CHECKER_PATCHES).contains(&patch) {
+            return Err(format!("The ColorChecker has no patch {patch}, they are numbered from 0 to {}!", 
+                               COLOR_CHECKER_PATCHES - 1).into());
+        }
+        let mut scene = shared.borrow_mut();
+        let spectrum = Spectrum::new_color_checker_patch(patch as usize, scene.lowest_wavelength, 
+                                                         scene.highest_wavelength, scene.nbr_of_samples);
+        Ok(scene.spectrum(name, UISpectrumType::ColorChecker(patch as usize), SpectrumEffectType::Reflective, spectrum))
+    });
+    let shared = scene.clone();
+    engine.register_fn("black_body", move |name: &str, temperature: Dynamic, factor: Dynamic| 
+        -> Result<SpectrumRef, Box<EvalAltResult>> {
+        let (temperature, factor) = (number(temperature)?, number(factor)?);
+        if temperature <= 0.0 {
+            return Err(format!("The temperature {temperature} K of \"{name}\" is not positive!").into());
+        }
+        let mut scene = shared.borrow_mut();
+        let spectrum = Spectrum::new_temperature_spectrum(scene.lowest_wavelength, scene.highest_wavelength, 
+                                                          temperature, scene.nbr_of_samples, factor);
+        Ok(scene.spectrum(name, UISpectrumType::Temperature(temperature, factor), SpectrumEffectType::Emissive, spectrum))
+    });
+    let shared = scene.clone();
+    engine.register_fn("sunlight", move |name: &str, factor: Dynamic| -> Result<SpectrumRef, Box<EvalAltResult>> {
+        let factor = number(factor)?;
+        let mut scene = shared.borrow_mut();
+        let spectrum = Spectrum::new_sunlight_spectrum(scene.lowest_wavelength, scene.highest_wavelength, 
+                                                       scene.nbr_of_samples, factor);
+        Ok(scene.spectrum(name, UISpectrumType::Solar(factor), SpectrumEffectType::Emissive, spectrum))
+    });
+
+    let shared = scene.clone();
+    engine.register_fn("material", move |name: &str, spectrum: SpectrumRef, metallicness: Dynamic, roughness: Dynamic| 
+        -> Result<MaterialRef, Box<EvalAltResult>> {
+        let (metallicness, roughness) = (number(metallicness)?, number(roughness)?);
+        if !(0.0..=1.0).contains(&metallicness) || !(0.0..=1.0).contains(&roughness) {
+            return Err(format!("The metallicness and roughness of \"{name}\" have to be in range [0; 1]!").into());
+        }
+        Ok(shared.borrow_mut().material(name, spectrum, metallicness, roughness))
+    });
+
+    let shared = scene.clone();
+    engine.register_fn("sphere", move |name: &str, x: Dynamic, y: Dynamic, z: Dynamic, radius: Dynamic, 
+                                       material: MaterialRef| -> Result<(), Box<EvalAltResult>> {
+        let object_type = UIObjectType::Sphere(number(radius)?);
+        let object = UIObject::new(number(x)?, number(y)?, number(z)?, material, object_type, name.to_string());
+        shared.borrow_mut().objects.push(object);
+        Ok(())
+    });
+    let shared = scene.clone();
+    engine.register_fn("cuboid", move |name: &str, x: Dynamic, y: Dynamic, z: Dynamic, width: Dynamic, height: Dynamic, 
+                                       depth: Dynamic, material: MaterialRef| -> Result<(), Box<EvalAltResult>> {
+        let object_type = UIObjectType::PlainBox(number(width)?, number(height)?, number(depth)?);
+        let object = UIObject::new(number(x)?, number(y)?, number(z)?, material, object_type, name.to_string());
+        shared.borrow_mut().objects.push(object);
+        Ok(())
+    });
+    let shared = scene.clone();
+    engine.register_fn("light", move |name: &str, x: Dynamic, y: Dynamic, z: Dynamic, spectrum: SpectrumRef| 
+        -> Result<(), Box<EvalAltResult>> {
+        let light = UILight::new(number(x)?, number(y)?, number(z)?, spectrum, name.to_string());
+        shared.borrow_mut().lights.push(light);
+        Ok(())
+    });
+}
+
+/// An error type used when a script fails. The error String holds the text the script printed
+/// before it failed, followed by the error and where in the script it occurred.
+#[derive(Debug)]
+pub struct ScriptError {
+    pub error: String,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::App;
+    use super::*;
+
+    #[test]
+    fn test_example_script() {
+        let mut app = App::new(None);
+        let output = run(EXAMPLE_SCRIPT, &mut app.ui_values, true).unwrap();
+        assert_eq!(output, "Spiral created.\n");
+        assert_eq!(app.ui_values.ui_objects.len(), 101, "The floor and 100 spheres should have been created!");
+        assert_eq!(app.ui_values.ui_lights.len(), 1);
+        assert!(app.check_objects_legality() && app.check_lights_legality() && app.check_materials_legality(), 
+                "The script created elements which are not listed!");
+        
+        //running it again replaces the objects and reuses the spectra and materials
+        let (nbr_of_spectra, nbr_of_materials) = (app.ui_values.spectra.len(), app.ui_values.materials.len());
+        run(EXAMPLE_SCRIPT, &mut app.ui_values, true).unwrap();
+        assert_eq!(app.ui_values.ui_objects.len(), 101);
+        assert_eq!(app.ui_values.spectra.len(), nbr_of_spectra, "The spectra were created a second time!");
+        assert_eq!(app.ui_values.materials.len(), nbr_of_materials, "The materials were created a second time!");
+        
+        run(EXAMPLE_SCRIPT, &mut app.ui_values, false).unwrap();
+        assert_eq!(app.ui_values.ui_objects.len(), 202);
+    }
+
+    #[test]
+    fn test_existing_spectrum_is_updated() {
+        let mut app = App::new(None);
+        let name = app.ui_values.spectra[0].borrow().name.clone();
+        run(&format!("reflective(\"{name}\", 0.25);"), &mut app.ui_values, false).unwrap();
+        let spectrum = app.ui_values.spectra[0].borrow();
+        assert_eq!(spectrum.spectrum_type, UISpectrumType::PlainReflective(0.25));
+        assert!(spectrum.spectrum.iter().all(|(_, intensity)| intensity == 0.25));
+    }
+
+    #[test]
+    fn test_failing_script_changes_nothing() {
+        let mut app = App::new(None);
+        let name = app.ui_values.spectra[0].borrow().name.clone();
+        let original: Vec<(f32, f32)> = app.ui_values.spectra[0].borrow().spectrum.iter().collect();
+        let (nbr_of_spectra, nbr_of_objects) = (app.ui_values.spectra.len(), app.ui_values.ui_objects.len());
+        let script = format!("let white = reflective(\"{name}\", 0.25);
+                              sphere(\"Sphere\", 0, 0, 0, 1, material(\"Script Material\", white, 0, 0));
+                              print(\"before the error\");
+                              sphere(\"Sphere\", 0, 0, 0, \"large\", material(\"Script Material\", white, 0, 0));");
+        let error = run(&script, &mut app.ui_values, true).unwrap_err().error;
+        assert!(error.starts_with("before the error\n"), "The printed text is missing in \"{error}\"!");
+        assert!(error.contains("line 4"), "The error \"{error}\" does not tell where it occurred!");
+        assert_eq!(app.ui_values.spectra.len(), nbr_of_spectra);
+        assert_eq!(app.ui_values.ui_objects.len(), nbr_of_objects);
+        assert_eq!(app.ui_values.spectra[0].borrow().spectrum.iter().collect::<Vec<_>>(), original, "The spectrum was changed by the failing script!");
+    }
+
+    #[test]
+    fn test_endless_script_is_stopped() {
+        let mut app = App::new(None);
+        assert!(run("loop { }", &mut app.ui_values, false).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::Path;
+use crate::loader::{LoadError, LoadProgress};
+use crate::spectrum;
+
+/// Number of bytes read from a CSV file at once, between reads the progress is reported.
+const CSV_READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Distance in nanometers between two samples of the internal response table.
+const TABLE_STEP: f32 = 5.0;
+/// Number of samples of the internal response table, covering the visible range in TABLE_STEP steps.
+const TABLE_LENGTH: usize = ((spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND
+    - spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND) / TABLE_STEP) as usize + 1;
+
+/// Peak wavelength, standard deviation and relative height of the gaussian red, green and blue
+/// channels of [SensorResponse::new_typical_cmos].
+const TYPICAL_CMOS_CHANNELS: [(f32, f32, f32); 3] = [
+    (600.0, 35.0, 0.9),     //red
+    (530.0, 40.0, 1.0),     //green
+    (460.0, 28.0, 0.8),     //blue
+];
+
+/// The spectral sensitivity of the red, green and blue channel of a camera sensor. It can be used
+/// instead of the CIE standard observer to convert a [Spectrum](spectrum::Spectrum) into RGB
+/// values, emulating how a specific physical camera would capture the scene. <br>
+/// Internally, the curves are resampled into a table at 5 nm intervals from 380 nm to 780 nm.
+/// Each channel is scaled such that a spectrum of equal energy results in the same value as the
+/// luminance of the CIE observer would, meaning the sensor is white balanced to equal energy white.
+#[derive(Clone, Debug)]
+pub struct SensorResponse {
+    name: String,
+    table: [(f32, f32, f32); TABLE_LENGTH],
+}
+
+impl SensorResponse {
+    /// Creates a new SensorResponse from a list of (wavelength, red, green, blue) samples. The
+    /// wavelengths must be in nanometers and strictly ascending. Between the samples the response
+    /// is linearly interpolated, outside of them it is taken to be 0. <br>
+    /// Returns a SensorResponseError if fewer than two samples are given, the wavelengths are not
+    /// ascending, any value is negative or a channel has no sensitivity in the visible range.
+    pub fn new_from_samples(name: String, samples: &[(f32, f32, f32, f32)]) -> Result<Self, SensorResponseError> {
+        if samples.len() < 2 {
+            return Err(SensorResponseError {error: "At least two samples are required!".to_string()});
+        }
+        if samples.windows(2).any(|w| w[0].0 >= w[1].0) {
+            return Err(SensorResponseError {error: "The wavelengths must be strictly ascending!".to_string()});
+        }
+        if samples.iter().any(|s| s.1 < 0.0 || s.2 < 0.0 || s.3 < 0.0) {
+            return Err(SensorResponseError {error: "The sensitivities must not be negative!".to_string()});
+        }
+
+        let mut table = [(0.0, 0.0, 0.0); TABLE_LENGTH];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let wavelength = spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND + i as f32 * TABLE_STEP;
+            *entry = interpolate_samples(samples, wavelength);
+        }
+
+        let sums = table.iter().fold((0.0, 0.0, 0.0), |acc, x| (acc.0 + x.0, acc.1 + x.1, acc.2 + x.2));
+        if sums.0 <= 0.0 || sums.1 <= 0.0 || sums.2 <= 0.0 {
+            return Err(SensorResponseError {error: "Every channel must be sensitive somewhere between 380 nm and 780 nm!".to_string()});
+        }
+        let luminance_sum = spectrum::cie_luminance_table_sum();
+        for entry in table.iter_mut() {
+            entry.0 *= luminance_sum / sums.0;
+            entry.1 *= luminance_sum / sums.1;
+            entry.2 *= luminance_sum / sums.2;
+        }
+
+        Ok(SensorResponse { name, table })
+    }
+
+    /// Creates the response of a typical consumer CMOS sensor behind an infrared cut filter,
+    /// approximated by one gaussian curve per channel.
+    pub fn new_typical_cmos() -> Self {
+        let samples: Vec<(f32, f32, f32, f32)> = (0..TABLE_LENGTH).map(|i| {
+            let wavelength = spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND + i as f32 * TABLE_STEP;
+            let [r, g, b] = TYPICAL_CMOS_CHANNELS.map(|(peak, deviation, height)| {
+                height * (-0.5 * ((wavelength - peak) / deviation).powi(2)).exp()
+            });
+            (wavelength, r, g, b)
+        }).collect();
+
+        Self::new_from_samples("Typical CMOS sensor".to_string(), &samples)
+            .expect("The built in sensor curves are valid")
+    }
+
+    /// Reads a SensorResponse from a CSV file. Each line must hold four comma separated values:
+    /// the wavelength in nanometers followed by the red, green and blue sensitivity. Empty lines,
+    /// lines starting with '#' and a leading header line are ignored. <br>
+    /// Meant to be run as a [BackgroundLoad](crate::loader::BackgroundLoad), the progress is 
+    /// reported to and cancellation is checked on the given [LoadProgress]. 
+    /// Returns a [LoadError] if the file can not be read or its content is invalid.
+    pub fn new_from_csv_file(path: &Path, progress: &LoadProgress) -> Result<Self, LoadError> {
+        let read_error = |e: std::io::Error| LoadError::Failed(format!("Could not read {}: {e}", path.display()));
+        let mut file = std::fs::File::open(path).map_err(read_error)?;
+        let file_size = file.metadata().map_err(read_error)?.len().max(1) as usize;
+
+        //reading makes up the first half of the progress, parsing the second
+        let mut bytes = Vec::with_capacity(file_size);
+        let mut chunk = vec![0u8; CSV_READ_CHUNK_SIZE];
+        loop {
+            if progress.is_cancelled() {
+                return Err(LoadError::Cancelled);
+            }
+            let read = file.read(&mut chunk).map_err(read_error)?;
+            if read == 0 {
+                break;
+            }
+            bytes.extend_from_slice(&chunk[..read]);
+            progress.set(bytes.len() as f32 / file_size as f32 * 0.5);
+        }
+        let content = String::from_utf8(bytes)
+            .map_err(|_| LoadError::Failed(format!("{} is not a text file!", path.display())))?;
+
+        let nbr_of_lines = content.lines().count().max(1);
+        let mut samples = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            if line_number % 1024 == 0 {
+                if progress.is_cancelled() {
+                    return Err(LoadError::Cancelled);
+                }
+                progress.set(0.5 + line_number as f32 / nbr_of_lines as f32 * 0.5);
+            }
+            
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let values: Result<Vec<f32>, _> = line.split(',').map(|v| v.trim().parse::<f32>()).collect();
+            match values {
+                Ok(values) if values.len() == 4 => samples.push((values[0], values[1], values[2], values[3])),
+                Err(_) if samples.is_empty() => continue,    //header line
+                _ => return Err(LoadError::Failed(
+                    format!("Line {} is not of the form 'wavelength,red,green,blue'!", line_number + 1)
+                )),
+            }
+        }
+
+        let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or("Custom sensor".to_string());
+        let sensor = Self::new_from_samples(name, &samples).map_err(|e| LoadError::Failed(e.error))?;
+        progress.set(1.0);
+        Ok(sensor)
+    }
+
+    /// Returns the sensitivity of the red, green and blue channel at the given wavelength in
+    /// nanometers. Between the table samples the values are linearly interpolated.
+    pub fn response_at(&self, wavelength: f32) -> (f32, f32, f32) {
+        if !(spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND..=spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND).contains(&wavelength) {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let position = (wavelength - spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND) / TABLE_STEP;
+        let index_lower = (position as usize).min(TABLE_LENGTH - 1);
+        let index_upper = (index_lower + 1).min(TABLE_LENGTH - 1);
+        let fract = position - index_lower as f32;
+        let lower = self.table[index_lower];
+        let upper = self.table[index_upper];
+
+        (
+            lower.0 + (upper.0 - lower.0) * fract,
+            lower.1 + (upper.1 - lower.1) * fract,
+            lower.2 + (upper.2 - lower.2) * fract,
+        )
+    }
+}
+
+impl Display for SensorResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// An error type used by the SensorResponse struct to communicate issues with the supplied curves.
+/// Specific details of the error are given in the error String.
+#[derive(Debug)]
+pub struct SensorResponseError {
+    pub error: String,
+}
+
+/// Linearly interpolates the given ascending (wavelength, red, green, blue) samples at the given
+/// wavelength. Returns 0 for wavelengths outside of the samples.
+fn interpolate_samples(samples: &[(f32, f32, f32, f32)], wavelength: f32) -> (f32, f32, f32) {
+    let upper = samples.partition_point(|s| s.0 < wavelength);
+    if upper == samples.len() || (upper == 0 && samples[0].0 > wavelength) {
+        return (0.0, 0.0, 0.0);
+    }
+    if samples[upper].0 == wavelength {
+        return (samples[upper].1, samples[upper].2, samples[upper].3);
+    }
+
+    let (lower, upper) = (samples[upper - 1], samples[upper]);
+    let fract = (wavelength - lower.0) / (upper.0 - lower.0);
+    (
+        lower.1 + (upper.1 - lower.1) * fract,
+        lower.2 + (upper.2 - lower.2) * fract,
+        lower.3 + (upper.3 - lower.3) * fract,
+    )
+}
@@ -1,18 +1,81 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::f32::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::ops::{AddAssign, ControlFlow};
 use std::sync::Arc;
-use nalgebra::{point, vector, Const, OMatrix, OPoint, Point3, Rotation3, Vector3};
-use crate::{UICamera, UILight, UIMaterial, UIObject, UIObjectType};
-use crate::spectrum::Spectrum;
+use nalgebra::{point, vector, Point3, Rotation3, Unit, Vector3};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::atmosphere::Atmosphere;
+use crate::bsdf::Bsdf;
+use crate::bvh::Bvh;
+use crate::csg::{self, Boundary, CsgOperation};
+use crate::false_color::FalseColor;
+use crate::heightfield::HeightGrid;
+use crate::irradiance_cache::{IrradianceCache, IrradianceRecord};
+use crate::light_selection::{LightDistribution, LightSelection};
+use crate::medium::Medium;
+use crate::photon_map::{Photon, PhotonMap};
+use crate::primitive::Primitive;
+use crate::procedural::Pattern;
+use crate::profiler::profile_scope;
+use crate::radiometry::{SpectralIntensity, SpectralIrradiance, SpectralRadiance, SpectralReemission, SpectralReflectance, 
+                        SpectralTransfer};
+use crate::sampler::{Sampler, SamplerType};
+use crate::sensor::SensorResponse;
+use crate::spectrum::{Observer, Spectrum};
+use crate::texture::Texture;
 
-pub(crate) const F32_DELTA: f32 = 0.00001;
-const NEW_RAY_POSITION_OFFSET_DISTANCE: f32 = 0.00001;
+pub const F32_DELTA: f32 = 0.00001;
 
-/// The distance a ray has to travel at least when being reflected via specular reflection. If the
-/// normal is not perpendicular to the surface, a high roughness value may result in rays being
-/// shot into the same object directly adjacent. Therefore, any ray shorter than this is being
-/// discarded.
-const SPECULAR_REFLECTION_HIGH_ROUGHNESS_MINIMUM_RAY_DISTANCE: f32 = 0.0001;
+/// Wavelengths of the Fraunhofer lines which define the refractive index and Abbe number of glass.
+const FRAUNHOFER_D_LINE_NM: f32 = 587.6;
+const FRAUNHOFER_F_LINE_NM: f32 = 486.1;
+const FRAUNHOFER_C_LINE_NM: f32 = 656.3;
+/// The smallest scale the ray offset is relative to, see [ray_offset]. Keeps rays leaving hits 
+/// right at the origin from starting on the surface. 
+const RAY_OFFSET_MIN_SCALE: f32 = 0.001;
 
+/// The distance the bounding box of a flat object extends in front of and behind its surface. A 
+/// bounding box without thickness would never be hit. 
+const FLAT_OBJECT_BOUNDS_PADDING: f32 = 0.0001;
+
+/// The distance a ray has to travel at least when being reflected via specular reflection, as a 
+/// multiple of the ray offset at its origin. If the normal is not perpendicular to the surface, a 
+/// high roughness value may result in rays being shot into the same object directly adjacent. 
+/// Therefore, any ray shorter than this is being discarded.
+const SPECULAR_REFLECTION_HIGH_ROUGHNESS_MINIMUM_RAY_DISTANCE_FACTOR: f32 = 10.0;
+
+/// The number of bounces a path has to survive before russian roulette may terminate it. The first
+/// few bounces carry most of the energy, terminating them early would only add noise.
+const RUSSIAN_ROULETTE_MIN_DEPTH: u32 = 3;
+
+/// The lowest survival probability russian roulette will use. Paths with a throughput below this
+/// value are not terminated even more aggressively, which would otherwise produce fireflies.
+const RUSSIAN_ROULETTE_MIN_SURVIVAL_PROBABILITY: f32 = 0.05;
+/// The records of the irradiance cache are first placed every this many pixels, then ever more 
+/// densely where they are needed. 
+const IRRADIANCE_CACHE_SPACING_COARSEST: u32 = 32;
+/// A record is valid at least up to twice the distance to the neighbouring records at the finest 
+/// spacing, so that they overlap, and at most 64 times as far. Without a lower limit, records in 
+/// corners would only cover their own pixel, without an upper one, records in the open would 
+/// cover everything. 
+const IRRADIANCE_CACHE_RADIUS_MIN_FACTOR: f32 = 2.0;
+const IRRADIANCE_CACHE_RADIUS_MAX_FACTOR: f32 = 64.0;
+
+/// The deepest nesting of mixed materials which is followed. Deeper materials are treated as if 
+/// they were no mix. 
+pub const MATERIAL_MIX_DEPTH_MAX: usize = 16;
+
+
+thread_local! {
+    /// The segments of the path currently traced on this thread, only Some while a path is traced 
+    /// by [trace_path]. 
+    static PATH_RECORDER: RefCell<Option<Vec<PathSegment>>> = const { RefCell::new(None) };
+    /// The rays shot on this thread since the last call of [take_ray_statistics]. 
+    static RAY_STATISTICS: Cell<RayStatistics> = const { Cell::new(RayStatistics::new()) };
+}
 
 /// The position of the pixel on the screen. (0, 0) is the top left. 
 #[derive(Copy, Clone)]
@@ -27,49 +90,224 @@ pub struct Dimensions {
     pub height: u32,
 }
 
+/// One ray of a path recorded by [trace_path]. Shadow rays are not recorded. 
+pub struct PathSegment {
+    /// The number of bounces before this ray, 0 for rays shot from the camera.
+    pub depth: u32,
+    /// "camera", "diffuse", "specular" or "transmission", the kind of bounce which spawned the ray.
+    pub kind: &'static str,
+    pub origin: Point3<f32>,
+    pub direction: Vector3<f32>,
+    /// The index of the hit object in the objects of the uniforms and the distance to it, None 
+    /// if the ray missed.
+    pub hit: Option<(usize, f32)>,
+    /// The radiance carried back along the ray towards the camera.
+    pub spectrum: Spectrum,
+}
+
 /// The struct holds the uniform data which is constant per frame. This includes things as the 
 /// information about light sources or objects in the scene. 
 #[derive(Clone)]
 pub struct RaytracingUniforms {
-    pub(crate) aabbs: Arc<Vec<Aabb>>,
-    pub(crate) lights: Arc<Vec<Light>>,
-    pub(crate) camera: Camera,
-    pub(crate) frame_id: u32,
-    pub(crate) intended_frames_amount: u32,
-    pub(crate) example_spectrum: Spectrum,
-    pub(crate) max_bounces: u32,
+    pub aabbs: Arc<Vec<Aabb>>,
+    /// The hierarchy over the bounding boxes of the aabbs, in the same order.
+    pub bvh: Arc<Bvh>,
+    pub lights: Arc<Vec<Light>>,
+    pub camera: Camera,
+    pub frame_id: u32,
+    pub intended_frames_amount: u32,
+    pub example_spectrum: Spectrum,
+    pub max_bounces: u32,
+    pub max_diffuse_bounces: u32,
+    pub max_specular_bounces: u32,
+    pub max_transmission_bounces: u32,
+    pub russian_roulette: bool,
+    pub sampler_type: SamplerType,
+    /// Seeds all random number streams, a different seed gives a different noise pattern.
+    pub random_seed: u32,
+    /// Whether out of gamut colors in the final image are desaturated instead of hard clipped.
+    pub gamut_compression: bool,
+    /// The camera sensor used to convert the spectra into RGB. The CIE observer is used if None.
+    pub sensor_response: Option<Arc<SensorResponse>>,
+    /// The CIE standard observer the spectra are converted with if there is no sensor response. 
+    pub observer: Observer,
+    /// Maps a single band of the spectra onto a color map instead of converting them into RGB, 
+    /// takes precedence over the sensor response. 
+    pub false_color: Option<FalseColor>,
+    /// The distribution the lights sampled at a hit are drawn from. Every light is sampled at 
+    /// every hit if None. 
+    pub light_distribution: Option<Arc<LightDistribution>>,
+    /// The number of lights drawn at every hit if a light distribution is given.
+    pub light_samples: u32,
+    /// The medium filling the scene, None for a vacuum.
+    pub medium: Option<Arc<Medium>>,
+    /// The atmosphere surrounding the scene with its sun, None for a black void.
+    pub atmosphere: Option<Arc<Atmosphere>>,
+    /// What rays leaving the scene see if there is no atmosphere.
+    pub background: Background,
+    /// The number of photons traced from the light sources every frame for the caustics, 0 if 
+    /// caustics are only found by the path tracer. 
+    pub photon_count: u32,
+    /// The radius around a hit within which photons are gathered. 
+    pub photon_radius: f32,
+    /// The caustic photons of the current frame, see [RaytracingUniforms::set_frame].
+    pub photon_map: Option<Arc<PhotonMap>>,
+    /// The quality of the irradiance cache in range \[0; 1], None if no cache is used.
+    pub irradiance_cache_quality: Option<f32>,
+    /// The cached indirect irradiance, see [RaytracingUniforms::prepare_irradiance_cache].
+    pub irradiance_cache: Option<Arc<IrradianceCache>>,
+    /// How far above a surface the rays leaving it start, relative to the scale of the hit, see 
+    /// [ray_offset]. 
+    pub ray_offset: f32,
+    /// Whether the light sampled from every light source is gathered separately as well, see 
+    /// [spectral_ray_generation_shader]. 
+    pub record_light_passes: bool,
+    /// Whether the camera sees through everything outside the scene, whatever the background or 
+    /// the atmosphere, whose light still reaches the objects, see [Ray::alpha]. 
+    pub transparent_film: bool,
+}
+
+impl RaytracingUniforms {
+    /// Prepares the uniforms for rendering the given frame. The caustic photons are traced anew 
+    /// for every frame, so that averaging the frames averages many photon maps as well. 
+    pub fn set_frame(&mut self, frame_id: u32) {
+        self.frame_id = frame_id;
+        self.photon_map = trace_photons(self).map(Arc::new);
+    }
+
+    /// Builds the irradiance cache for images of the given dimensions if a quality is set. The 
+    /// cache is built once per render, with the random values of the current frame. 
+    pub fn prepare_irradiance_cache(&mut self, dim: &Dimensions) {
+        self.irradiance_cache = None;
+        if let Some(quality) = self.irradiance_cache_quality {
+            self.irradiance_cache = Some(Arc::new(build_irradiance_cache(dim, quality, self)));
+        }
+    }
 }
 
 /// The struct representing the ray that is shot through the scene. It contains information about
-/// the origin and direction as well as returned information such as color (intensity). 
+/// the origin and direction as well as returned information such as the radiance arriving along it. 
 struct Ray {
     origin: Point3<f32>,
     direction: Vector3<f32>,
     hit: bool,
-    spectrum: Spectrum,
+    spectrum: SpectralRadiance,
     skip_hit_shader: bool,
     max_bounces: u32,
+    diffuse_bounces_left: u32,
+    specular_bounces_left: u32,
+    transmission_bounces_left: u32,
+    /// The largest share of light the path up to this ray can still carry back to the camera. Used
+    /// by russian roulette to decide how likely the path is to be continued.
+    throughput: f32,
+    /// The kind of bounce which spawned this ray, None for rays shot from the camera.
+    bounce_kind: Option<BounceKind>,
     original_pixel_pos: PixelPos,
     hit_distance: f32,
     max_hit_distance: f32,
+    /// The opacity of the pixel along a camera ray, below 1 if it hits a shadow catcher and 0 if 
+    /// it sees a transparent background or film. Written by [submit_ray]. 
+    alpha: f32,
+    /// The shading normal at the first hit of the path, zero if nothing is hit. Written by 
+    /// [submit_ray]. 
+    normal: Vector3<f32>,
+    /// The reflectance at the first hit of the path as linear RGB, see [surface_albedo]. Zero if 
+    /// nothing is hit. Written by [submit_ray]. 
+    albedo: Vector3<f32>,
+    /// The id color of the object of the first hit of the path, zero if nothing is hit. Written by 
+    /// [submit_ray]. 
+    id_color: Vector3<f32>,
+    /// The radiance arriving along the ray which each light source of the uniforms contributes 
+    /// via the direct light at the hits of the path, empty unless light passes are recorded. 
+    /// Written by [submit_ray]. 
+    light_radiances: Vec<SpectralRadiance>,
 }
 impl Ray {
     /// Creates a new standard Ray with default values for the values which will be written to in 
-    /// the shaders. 
-    fn new(origin: Point3<f32>, direction: Vector3<f32>, max_bounces: u32,
+    /// the shaders. The per material bounce limits are taken from the uniforms.
+    fn new(origin: Point3<f32>, direction: Vector3<f32>, uniforms: &RaytracingUniforms,
            original_pixel_pos: PixelPos, example_spectrum: &Spectrum) -> Ray {
         Ray {
             origin,
             direction: direction.normalize(),
             hit: false,
-            spectrum: Spectrum::new_equal_size_empty_spectrum(example_spectrum),
+            spectrum: SpectralRadiance::zero(example_spectrum),
             skip_hit_shader: false,
-            max_bounces,
+            max_bounces: uniforms.max_bounces,
+            diffuse_bounces_left: uniforms.max_diffuse_bounces,
+            specular_bounces_left: uniforms.max_specular_bounces,
+            transmission_bounces_left: uniforms.max_transmission_bounces,
+            throughput: 1.0,
+            bounce_kind: None,
             original_pixel_pos,
             hit_distance: 0.0,
             max_hit_distance: f32::INFINITY,
+            alpha: 1.0,
+            normal: Vector3::zeros(),
+            albedo: Vector3::zeros(),
+            id_color: Vector3::zeros(),
+            light_radiances: Vec::new(),
+        }
+    }
+
+    /// Creates the follow-up ray of this ray after a bounce. The remaining bounces are decreased
+    /// by one, for the given kind of bounce additionally the remaining bounces of that kind. The
+    /// throughput is multiplied with the given factor.
+    fn new_bounce(&self, origin: Point3<f32>, direction: Vector3<f32>, kind: BounceKind, throughput_factor: f32) -> Ray {
+        let (mut diffuse_bounces_left, mut specular_bounces_left, mut transmission_bounces_left) = 
+            (self.diffuse_bounces_left, self.specular_bounces_left, self.transmission_bounces_left);
+        match kind {
+            BounceKind::Diffuse => diffuse_bounces_left -= 1,
+            BounceKind::Specular => specular_bounces_left -= 1,
+            BounceKind::Transmission => transmission_bounces_left -= 1,
+        }
+
+        Ray {
+            origin,
+            direction: direction.normalize(),
+            hit: false,
+            spectrum: SpectralRadiance::zero(&self.spectrum),
+            skip_hit_shader: false,
+            max_bounces: self.max_bounces - 1,
+            diffuse_bounces_left,
+            specular_bounces_left,
+            transmission_bounces_left,
+            throughput: self.throughput * throughput_factor,
+            bounce_kind: Some(kind),
+            original_pixel_pos: self.original_pixel_pos,
+            hit_distance: 0.0,
+            max_hit_distance: f32::INFINITY,
+            alpha: 1.0,
+            normal: Vector3::zeros(),
+            albedo: Vector3::zeros(),
+            id_color: Vector3::zeros(),
+            light_radiances: Vec::new(),
+        }
+    }
+
+    /// Returns true if the ray hits the object at all: shadow rays only objects casting shadows, 
+    /// camera rays only objects visible to the camera and all others only objects visible in 
+    /// reflections. 
+    fn sees(&self, aabb: &Aabb) -> bool {
+        if self.skip_hit_shader {
+            aabb.visibility.casts_shadows
+        } else if self.bounce_kind.is_none() {
+            aabb.visibility.to_camera
+        } else {
+            aabb.visibility.in_reflections
         }
     }
+
+    /// Returns true if this ray may spawn another ray of the given kind, respecting the overall
+    /// bounce limit as well as the limit of the specific kind.
+    fn can_bounce(&self, kind: BounceKind) -> bool {
+        let kind_bounces_left = match kind {
+            BounceKind::Diffuse => self.diffuse_bounces_left,
+            BounceKind::Specular => self.specular_bounces_left,
+            BounceKind::Transmission => self.transmission_bounces_left,
+        };
+        self.max_bounces > 1 && kind_bounces_left > 0
+    }
     
     /// Creates a new shadow ray. Shadow rays are rays which terminate upon hitting anything and 
     /// can thus be used to determine if an unobstructed line to another point exists. The 
@@ -82,35 +320,116 @@ impl Ray {
             origin, 
             direction,
             hit: false,
-            spectrum: Spectrum::new_equal_size_empty_spectrum(example_spectrum),    //TODO maybe refactor this out
+            spectrum: SpectralRadiance::zero(example_spectrum),    //TODO maybe refactor this out
             skip_hit_shader: true,
             max_bounces: 2, //technically unnecessary
+            diffuse_bounces_left: 0,
+            specular_bounces_left: 0,
+            transmission_bounces_left: 0,
+            throughput: 1.0,
+            bounce_kind: None,
             original_pixel_pos: PixelPos {x:0, y:0},    //dummy value
             hit_distance: 0.0,
             max_hit_distance,
+            alpha: 1.0,
+            normal: Vector3::zeros(),
+            albedo: Vector3::zeros(),
+            id_color: Vector3::zeros(),
+            light_radiances: Vec::new(),
         }
     }
 }
 
+/// The kind of bounce a ray performs upon hitting a surface. Each kind has its own depth limit, 
+/// since deep diffuse chains contribute far less to the image than deep specular chains and light 
+/// passing through glass needs two bounces per pane. No material refracts light yet, so 
+/// transmission bounces are only limited, never taken. 
+#[derive(Clone, Copy, PartialEq)]
+enum BounceKind {
+    Diffuse,
+    Specular,
+    /// Light passing through the surface into or out of an object, refracted or not. 
+    #[allow(dead_code)]
+    Transmission,
+}
+
 /// AABBs (Axis Aligned Bounding Box) are structures defined by their smallest and largest Point of 
 /// a cuboid. These structs hold an Enum which differentiates their content, for example a sphere 
 /// (AABBType::Sphere) can be mathematically defined by its center and radius, both of which can be 
 /// calculated from the two given points of the AABB. 
-pub(crate) struct Aabb {
+#[derive(Clone)]
+pub struct Aabb {
     min: Point3<f32>,
     max: Point3<f32>,
     aabb_type: AABBType,
     material: Material,
+    emission: Option<Emission>,
+    /// The image which modulates the reflectance of the surface, if any. Only spheres and boxes 
+    /// have texture coordinates, other objects ignore it. 
+    texture: Option<Arc<Texture>>,
+    /// The id of the object this was built from, which light linking refers to. 0 if it was not 
+    /// built from an object of the scene. 
+    object_id: u32,
+    /// The color the object has in the object ID pass, see [FirstHit::id_color]. Black if it was 
+    /// not built from an object of the scene. 
+    id_color: Vector3<f32>,
+    /// Which rays see the object, see [Ray::sees]. 
+    visibility: ObjectVisibility,
 }
 impl Aabb {
+    /// Returns the smallest and the largest corner of the axis aligned box enclosing the object. 
+    pub fn get_bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        (self.min, self.max)
+    }
+    
+    /// Multiplies the radiance emitted by the object, if any, with the given factor. 
+    pub fn scale_emission(&mut self, factor: f32) {
+        if let Some(emission) = &mut self.emission {
+            emission.spectrum *= factor;
+        }
+    }
+    
+    /// Lets the surface of the object emit the given radiance. Camera rays and rays reflected by 
+    /// other objects can be kept from seeing the emission, it lights the scene nonetheless. 
+    pub fn set_emission(&mut self, spectrum: SpectralRadiance, visible_to_camera: bool, visible_in_reflections: bool) {
+        self.emission = Some(Emission {spectrum, visible_to_camera, visible_in_reflections});
+    }
+    
+    pub fn set_texture(&mut self, texture: Option<Arc<Texture>>) {
+        self.texture = texture;
+    }
+    
+    /// Sets the id light linking refers to the object by, see [Light::set_link]. 
+    pub fn set_object_id(&mut self, object_id: u32) {
+        self.object_id = object_id;
+    }
+    
+    /// Sets the color the object has in the object ID pass. 
+    pub fn set_id_color(&mut self, id_color: Vector3<f32>) {
+        self.id_color = id_color;
+    }
+    
+    /// Restricts which kinds of rays hit the object. Objects are seen by all rays by default. 
+    pub fn set_visibility(&mut self, to_camera: bool, in_reflections: bool, casts_shadows: bool) {
+        self.visibility = ObjectVisibility {to_camera, in_reflections, casts_shadows};
+    }
+    
     /// Creates a new sphere object with given center point and radius, as well as given material.
     /// The sphere is a mathematically perfect sphere and not a polygon approximation.
     pub fn new_sphere(center: &Point3<f32>, radius: f32, material: Material) -> Aabb {
+        let sphere = Sphere {center: *center, radius};
+        let (min, max) = sphere.bounds();
+        
         Aabb {
-            min: point![center.x - radius, center.y - radius, center.z - radius],
-            max: point![center.x + radius, center.y + radius, center.z + radius],
-            aabb_type: AABBType::Sphere,
+            min,
+            max,
+            aabb_type: AABBType::Sphere(sphere),
             material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
         }
     }
 
@@ -121,20 +440,307 @@ impl Aabb {
         let x_half = x_length / 2.0;
         let y_half = y_length / 2.0;
         let z_half = z_length / 2.0;
-        Aabb {
+        let plain_box = PlainBox {
             min: point![center.x - x_half, center.y - y_half, center.z - z_half],
             max: point![center.x + x_half, center.y + y_half, center.z + z_half],
-            aabb_type: AABBType::PlainBox,
+        };
+        let (min, max) = plain_box.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::PlainBox(plain_box),
             material, 
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
         }
     }
 
     /// Creates a new box, analogous to an AABB, which can however be rotated in any way. No longer
     /// has skipped intersection check bonus of the AABB.
     pub fn new_rotated_box(center: &Point3<f32>, x_length: f32, y_length: f32, z_length: f32, rotation: Rotation3<f32>, material: Material) -> Aabb {
-        let x_half = x_length / 2.0;
-        let y_half = y_length / 2.0;
-        let z_half = z_length / 2.0;
+        let rotated_box = RotatedBox {center: *center, dimensions: vector![x_length, y_length, z_length], rotation};
+        let (min, max) = rotated_box.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::RotatedBox(rotated_box),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new cylinder closed by two flat caps. Before the rotation is applied, the axis of 
+    /// the cylinder points along the y axis and the caps lie half the height above and below the 
+    /// center. 
+    pub fn new_cylinder(center: &Point3<f32>, radius: f32, height: f32, rotation: Rotation3<f32>, material: Material) -> Aabb {
+        let cylinder = Cylinder {center: *center, radius, height, rotation};
+        let (min, max) = cylinder.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Cylinder(cylinder),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new ellipsoid, a sphere stretched to the given radius along each of its three 
+    /// axes before the rotation is applied. 
+    pub fn new_ellipsoid(center: &Point3<f32>, radii: Vector3<f32>, rotation: Rotation3<f32>, material: Material) -> Aabb {
+        let ellipsoid = Ellipsoid {center: *center, radii, rotation};
+        let (min, max) = ellipsoid.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Ellipsoid(ellipsoid),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new flat disc with the given center, radius and normal. The disc is seen from both 
+    /// sides. 
+    pub fn new_disc(center: &Point3<f32>, radius: f32, normal: &Vector3<f32>, material: Material) -> Aabb {
+        let disc = Disc {center: *center, radius, rotation: flat_object_rotation(normal)};
+        let (min, max) = disc.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Disc(disc),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new flat rectangle with the given center, width, depth and normal. Before being 
+    /// turned towards the normal, the quad faces up along the y axis with its width along the x 
+    /// axis and its depth along the z axis. The quad is single-sided, it is invisible from behind. 
+    pub fn new_quad(center: &Point3<f32>, width: f32, depth: f32, normal: &Vector3<f32>, material: Material) -> Aabb {
+        let quad = Quad {center: *center, width, depth, rotation: flat_object_rotation(normal)};
+        let (min, max) = quad.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Quad(quad),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new heightfield whose base is centered at the given position. The grid spans the 
+    /// width along the x axis and the depth along the z axis, its highest possible sample lying 
+    /// the given height above the base. 
+    pub fn new_heightfield(base: &Point3<f32>, width: f32, depth: f32, height: f32, grid: Arc<HeightGrid>, 
+                           material: Material) -> Aabb {
+        let heightfield = Heightfield {base: *base, dimensions: vector![width, height, depth], grid};
+        let (min, max) = heightfield.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Heightfield(heightfield),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new object combining the two given objects with the given boolean operation. 
+    /// The materials of the two objects are not used. 
+    pub fn new_csg(operation: CsgOperation, first: Aabb, second: Aabb, material: Material) -> Aabb {
+        let (min, max) = match operation {
+            CsgOperation::Union => (first.min.inf(&second.min), first.max.sup(&second.max)),
+            CsgOperation::Intersection => (first.min.sup(&second.min), first.max.inf(&second.max)),
+            CsgOperation::Difference => (first.min, first.max),
+        };
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Csg(operation, Box::new(first), Box::new(second)),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+    
+    /// Creates a new object of a shape which is not built in, see [Primitive]. 
+    pub fn new_custom(primitive: Arc<dyn Primitive>, material: Material) -> Aabb {
+        let (min, max) = primitive.bounds();
+        
+        Aabb {
+            min,
+            max,
+            aabb_type: AABBType::Custom(primitive),
+            material,
+            emission: None,
+            texture: None,
+            object_id: 0,
+            id_color: Vector3::zeros(),
+            visibility: ObjectVisibility::VISIBLE,
+        }
+    }
+}
+#[derive(Clone)]
+enum AABBType {
+    PlainBox(PlainBox),
+    Sphere(Sphere),
+    RotatedBox(RotatedBox),
+    Cylinder(Cylinder),
+    Ellipsoid(Ellipsoid),
+    Disc(Disc),
+    Quad(Quad),
+    /// Two objects combined by a boolean operation, see [csg]. 
+    Csg(CsgOperation, Box<Aabb>, Box<Aabb>),
+    Heightfield(Heightfield),
+    /// A shape which is not built in. 
+    Custom(Arc<dyn Primitive>),
+}
+
+impl AABBType {
+    /// Returns the shape of the object, None for CSG objects, whose surface is made of the 
+    /// surfaces of their parts. 
+    fn primitive(&self) -> Option<&dyn Primitive> {
+        match self {
+            AABBType::PlainBox(shape) => Some(shape),
+            AABBType::Sphere(shape) => Some(shape),
+            AABBType::RotatedBox(shape) => Some(shape),
+            AABBType::Cylinder(shape) => Some(shape),
+            AABBType::Ellipsoid(shape) => Some(shape),
+            AABBType::Disc(shape) => Some(shape),
+            AABBType::Quad(shape) => Some(shape),
+            AABBType::Csg(..) => None,
+            AABBType::Heightfield(shape) => Some(shape),
+            AABBType::Custom(primitive) => Some(primitive.as_ref()),
+        }
+    }
+}
+
+/// A box whose sides are aligned with the axes, given by its smallest and largest corner. 
+#[derive(Clone)]
+struct PlainBox {
+    min: Point3<f32>,
+    max: Point3<f32>,
+}
+
+impl Primitive for PlainBox {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        (self.min, self.max)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        ray_aabb_intersection(origin, direction, &self.min, &self.max).and_then(first_in_front)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32> {
+        plain_box_normal_calculation(&self.min, &self.max, point)
+    }
+
+    fn solid_intervals(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Vec<(f32, f32)> {
+        ray_aabb_intersection(origin, direction, &self.min, &self.max).into_iter().collect()
+    }
+
+    fn uv(&self, point: &Point3<f32>, normal: &Vector3<f32>) -> Option<(f32, f32)> {
+        Some(box_uv(&(point - self.min).component_div(&(self.max - self.min)), normal))
+    }
+}
+
+/// A mathematically perfect sphere and not a polygon approximation. 
+#[derive(Clone)]
+struct Sphere {
+    center: Point3<f32>,
+    radius: f32,
+}
+
+impl Sphere {
+    /// Returns where the ray enters and leaves the sphere, None if it misses it. 
+    fn interval(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<(f32, f32)> {
+        match ray_sphere_intersection(origin, direction, &self.center, self.radius) {
+            SphereIntersection::NoIntersection => None,
+            SphereIntersection::OneIntersection(t) => Some((t, t)),
+            SphereIntersection::TwoIntersections(t_1, t_2) => Some((t_1.min(t_2), t_1.max(t_2))),
+        }
+    }
+}
+
+impl Primitive for Sphere {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        let extent = Vector3::repeat(self.radius);
+        (self.center - extent, self.center + extent)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        self.interval(origin, direction).and_then(first_in_front)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32> {
+        (point - self.center).normalize()
+    }
+
+    fn solid_intervals(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Vec<(f32, f32)> {
+        self.interval(origin, direction).into_iter().collect()
+    }
+
+    /// Maps the sphere like a globe, u running around the y axis and v from the bottom to the top 
+    /// pole. 
+    fn uv(&self, point: &Point3<f32>, _normal: &Vector3<f32>) -> Option<(f32, f32)> {
+        let direction = (point - self.center).normalize();
+        let u = 0.5 + direction.x.atan2(direction.z) / (2.0 * PI);
+        let v = 0.5 + direction.y.clamp(-1.0, 1.0).asin() / PI;
+        Some((u, v))
+    }
+}
+
+/// A box which can be rotated in any way, given by its center, its lengths along its own axes and 
+/// its rotation. 
+#[derive(Clone)]
+struct RotatedBox {
+    center: Point3<f32>,
+    dimensions: Vector3<f32>,
+    rotation: Rotation3<f32>,
+}
+
+impl Primitive for RotatedBox {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        let (center, rotation) = (self.center, self.rotation);
+        let x_half = self.dimensions.x / 2.0;
+        let y_half = self.dimensions.y / 2.0;
+        let z_half = self.dimensions.z / 2.0;
         
         //calculate the 8 points of the cube
         let point_mmm = center + rotation * vector![-x_half, -y_half, -z_half];
@@ -154,344 +760,1673 @@ impl Aabb {
         let z_min = point_mmm.z.min(point_mmp.z).min(point_mpm.z).min(point_mpp.z).min(point_pmm.z).min(point_pmp.z).min(point_ppm.z).min(point_ppp.z);
         let z_max = point_mmm.z.max(point_mmp.z).max(point_mpm.z).max(point_mpp.z).max(point_pmm.z).max(point_pmp.z).max(point_ppm.z).max(point_ppp.z);
         
-        let min = point![x_min, y_min, z_min];
-        let max = point![x_max, y_max, z_max];
+        (point![x_min, y_min, z_min], point![x_max, y_max, z_max])
+    }
 
-        Aabb {
-            min, 
-            max,
-            aabb_type: AABBType::RotatedBox(*center, vector![x_length, y_length, z_length], rotation),
-            material,
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        ray_oriented_box_intersection(origin, direction, &self.center, &self.dimensions, &self.rotation)
+            .and_then(first_in_front)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32> {
+        rotated_box_normal_calculation(&self.center, &self.dimensions, &self.rotation, point)
+    }
+
+    fn solid_intervals(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Vec<(f32, f32)> {
+        ray_oriented_box_intersection(origin, direction, &self.center, &self.dimensions, &self.rotation)
+            .into_iter().collect()
+    }
+
+    fn uv(&self, point: &Point3<f32>, normal: &Vector3<f32>) -> Option<(f32, f32)> {
+        //position on the box in range [0; 1] and normal, both in the space of the unrotated box
+        let inv_rotation = self.rotation.inverse();
+        let local_point = (inv_rotation * (point - self.center)).component_div(&self.dimensions);
+        Some(box_uv(&local_point.add_scalar(0.5), &(inv_rotation * normal)))
+    }
+}
+
+/// A cylinder closed by two flat caps, given by its center, radius, height and rotation. 
+#[derive(Clone)]
+struct Cylinder {
+    center: Point3<f32>,
+    radius: f32,
+    height: f32,
+    rotation: Rotation3<f32>,
+}
+
+impl Primitive for Cylinder {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        //each cap is a disc, which extends along an axis by its radius times the sine of the angle 
+        //between the axis and the cylinder axis
+        let axis = self.rotation * Vector3::y();
+        let extent = axis.map(|component| {
+            component.abs() * self.height / 2.0 + self.radius * (1.0 - component * component).max(0.0).sqrt()
+        });
+        (self.center - extent, self.center + extent)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        ray_cylinder_intersection(origin, direction, &self.center, self.radius, self.height, &self.rotation)
+            .and_then(first_in_front)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32> {
+        cylinder_normal_calculation(&self.center, self.radius, self.height, &self.rotation, point)
+    }
+
+    fn solid_intervals(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Vec<(f32, f32)> {
+        ray_cylinder_intersection(origin, direction, &self.center, self.radius, self.height, &self.rotation)
+            .into_iter().collect()
+    }
+}
+
+/// An ellipsoid, given by its center, the radii along its three axes and its rotation. 
+#[derive(Clone)]
+struct Ellipsoid {
+    center: Point3<f32>,
+    radii: Vector3<f32>,
+    rotation: Rotation3<f32>,
+}
+
+impl Primitive for Ellipsoid {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        //the ellipsoid extends along a world axis by the length of that row of the rotation matrix 
+        //scaled by the radii
+        let matrix = self.rotation.matrix();
+        let extent = Vector3::from_fn(|i, _| {
+            (0..3).map(|j| (matrix[(i, j)] * self.radii[j]).powi(2)).sum::<f32>().sqrt()
+        });
+        (self.center - extent, self.center + extent)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        ray_ellipsoid_intersection(origin, direction, &self.center, &self.radii, &self.rotation)
+            .and_then(first_in_front)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, point: &Point3<f32>) -> Vector3<f32> {
+        ellipsoid_normal_calculation(&self.center, &self.radii, &self.rotation, point)
+    }
+
+    fn solid_intervals(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Vec<(f32, f32)> {
+        ray_ellipsoid_intersection(origin, direction, &self.center, &self.radii, &self.rotation)
+            .into_iter().collect()
+    }
+}
+
+/// A flat disc seen from both sides, given by its center, radius and rotation. Unrotated, it faces 
+/// along the y axis. 
+#[derive(Clone)]
+struct Disc {
+    center: Point3<f32>,
+    radius: f32,
+    rotation: Rotation3<f32>,
+}
+
+impl Primitive for Disc {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        //the disc extends along an axis by its radius times the sine of the angle between the axis 
+        //and the normal
+        let normal = self.rotation * Vector3::y();
+        let extent = normal.map(|component| {
+            self.radius * (1.0 - component * component).max(0.0).sqrt() + FLAT_OBJECT_BOUNDS_PADDING
+        });
+        (self.center - extent, self.center + extent)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        let (t, local_point) = ray_flat_object_intersection(origin, direction, &self.center, &self.rotation, false)?;
+        (local_point.x * local_point.x + local_point.z * local_point.z <= self.radius * self.radius).then_some(t)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, direction: &Vector3<f32>, _point: &Point3<f32>) -> Vector3<f32> {
+        //the disc is seen from both sides, its normal has to face the incoming ray
+        let normal = self.rotation * Vector3::y();
+        if normal.dot(direction) > 0.0 { -normal } else { normal }
+    }
+}
+
+/// A flat rectangle seen only from the front, given by its center, width, depth and rotation. 
+/// Unrotated, it faces along the y axis with its width along the x axis and its depth along the z 
+/// axis. 
+#[derive(Clone)]
+struct Quad {
+    center: Point3<f32>,
+    width: f32,
+    depth: f32,
+    rotation: Rotation3<f32>,
+}
+
+impl Primitive for Quad {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        let half_width = self.rotation * vector![self.width / 2.0, 0.0, 0.0];
+        let half_depth = self.rotation * vector![0.0, 0.0, self.depth / 2.0];
+        let extent = half_width.abs() + half_depth.abs() 
+            + Vector3::repeat(FLAT_OBJECT_BOUNDS_PADDING);
+        (self.center - extent, self.center + extent)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        let (t, local_point) = ray_flat_object_intersection(origin, direction, &self.center, &self.rotation, true)?;
+        (local_point.x.abs() <= self.width / 2.0 && local_point.z.abs() <= self.depth / 2.0).then_some(t)
+    }
+
+    fn normal(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, _point: &Point3<f32>) -> Vector3<f32> {
+        self.rotation * Vector3::y()
+    }
+}
+
+/// A heightfield seen from both sides, given by the center of its base, its width, height and 
+/// depth and its heights. 
+#[derive(Clone)]
+struct Heightfield {
+    base: Point3<f32>,
+    dimensions: Vector3<f32>,
+    grid: Arc<HeightGrid>,
+}
+
+impl Primitive for Heightfield {
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        let padding = Vector3::repeat(FLAT_OBJECT_BOUNDS_PADDING);
+        let min = self.base - vector![self.dimensions.x / 2.0, 0.0, self.dimensions.z / 2.0] - padding;
+        let max = self.base + vector![self.dimensions.x / 2.0, self.dimensions.y * self.grid.get_highest(), 
+                                      self.dimensions.z / 2.0] + padding;
+        (min, max)
+    }
+
+    fn intersect(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        ray_heightfield_intersection(origin, direction, &self.base, &self.dimensions, &self.grid).map(|(t, _)| t)
+    }
+
+    fn normal(&self, origin: &Point3<f32>, direction: &Vector3<f32>, _point: &Point3<f32>) -> Vector3<f32> {
+        //the normal is only known where the grid was hit, so the surface is found once more. The 
+        //heightfield is seen from both sides, its normal has to face the incoming ray
+        match ray_heightfield_intersection(origin, direction, &self.base, &self.dimensions, &self.grid) {
+            Some((_, normal)) if normal.dot(direction) > 0.0 => -normal,
+            Some((_, normal)) => normal,
+            None => -direction,
         }
     }
 }
-enum AABBType {
-    PlainBox,
-    Sphere,
-    RotatedBox(Point3<f32>, Vector3<f32>, Rotation3<f32>),
+
+/// The light emitted by the surface of an object. Which rays see the emission can be restricted, 
+/// allowing for example light panels which light the scene but are invisible to the camera.
+#[derive(Clone)]
+struct Emission {
+    spectrum: SpectralRadiance,
+    visible_to_camera: bool,
+    visible_in_reflections: bool,
+}
+
+/// Which kinds of rays an object is hit by. Hiding an object from some of them allows lighting 
+/// tricks, e.g. a bounce card which lights the scene but does not show up in the image. 
+#[derive(Clone, Copy, Debug)]
+struct ObjectVisibility {
+    to_camera: bool,
+    /// Whether rays reflected by other objects hit the object, which also decides whether it 
+    /// passes light on to other objects. 
+    in_reflections: bool,
+    /// Whether shadow rays and caustic photons are stopped by the object.
+    casts_shadows: bool,
+}
+
+impl ObjectVisibility {
+    const VISIBLE: ObjectVisibility = ObjectVisibility {to_camera: true, in_reflections: true, casts_shadows: true};
+}
+
+/// A point light. Having no surface, it emits an intensity instead of a radiance. 
+#[derive(Clone)]
+pub struct Light {
+    position: Point3<f32>,
+    spectrum: SpectralIntensity,
+    /// Which objects the light illuminates directly. 
+    link_mode: LightLinkMode,
+    /// The ids of the objects the link mode refers to. 
+    linked_objects: HashSet<u32>,
+}
+impl Light {
+    pub fn new(position: Point3<f32>, spectrum: SpectralIntensity) -> Light {
+        Light {
+            position,
+            spectrum,
+            link_mode: LightLinkMode::All,
+            linked_objects: HashSet::new(),
+        }
+    }
+    
+    /// Whether the light illuminates the object with the given id, see [LightLinkMode]. 
+    pub fn illuminates(&self, object_id: u32) -> bool {
+        match self.link_mode {
+            LightLinkMode::All => true,
+            LightLinkMode::Include => self.linked_objects.contains(&object_id),
+            LightLinkMode::Exclude => !self.linked_objects.contains(&object_id),
+        }
+    }
+    
+    /// Returns the radiant power of the light, used to weight it when choosing lights at random. 
+    pub fn get_power(&self) -> f32 {
+        self.spectrum.get_power()
+    }
+    
+    /// Multiplies the emitted intensity with the given factor. 
+    pub fn scale_emission(&mut self, factor: f32) {
+        self.spectrum *= factor;
+    }
+    
+    /// Restricts the objects the light illuminates directly to or excludes the objects with the 
+    /// given ids, see [Aabb::set_object_id]. 
+    pub fn set_link(&mut self, link_mode: LightLinkMode, linked_objects: HashSet<u32>) {
+        self.link_mode = link_mode;
+        self.linked_objects = linked_objects;
+    }
+}
+
+/// Which objects a light source illuminates directly. Linking lights to objects allows e.g. a 
+/// fill light which does not show up in a mirror. Light reflected by other objects still reaches 
+/// all objects. 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LightLinkMode {
+    #[default]
+    All,
+    /// Only the linked objects are lit.
+    Include,
+    /// All objects but the linked ones are lit.
+    Exclude,
+}
+
+impl LightLinkMode {
+    pub const ALL: [LightLinkMode; 3] = [LightLinkMode::All, LightLinkMode::Include, LightLinkMode::Exclude];
+}
+
+impl Display for LightLinkMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LightLinkMode::All => write!(f, "All objects"),
+            LightLinkMode::Include => write!(f, "Only linked objects"),
+            LightLinkMode::Exclude => write!(f, "All but linked objects"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub position: Point3<f32>,
+    pub direction: Vector3<f32>,
+    pub up: Vector3<f32>,
+    pub fov_y_deg: f32,
+    pub lens: LensModel,
+    /// The distance along the view direction before which the camera sees nothing, 0 if nothing 
+    /// is clipped. 
+    pub near_clip: f32,
+    /// The distance along the view direction after which the camera sees nothing, infinite if 
+    /// nothing is clipped. 
+    pub far_clip: f32,
+}
+
+impl Camera {
+    pub fn new(position: Point3<f32>, direction: Vector3<f32>, up: Vector3<f32>, fov_y_deg: f32, lens: LensModel) -> Camera {
+        Camera {
+            position, 
+            direction, 
+            up,
+            fov_y_deg,
+            lens,
+            near_clip: 0.0,
+            far_clip: f32::INFINITY,
+        }
+    }
+    
+    /// Restricts the camera ray to the part between the clipping planes. The ray starts at the 
+    /// near plane, so that the objects in front of it are not seen, and hits nothing behind the 
+    /// far plane. Only the camera rays are clipped, the clipped objects still cast shadows and 
+    /// show up in reflections. 
+    fn clip(&self, ray: &mut Ray) {
+        let forward = self.direction.normalize();
+        let cos_view = ray.direction.dot(&forward);
+        if cos_view <= 0.0 {
+            return;
+        }
+        let depth = (ray.origin - self.position).dot(&forward);
+        let near_distance = ((self.near_clip - depth) / cos_view).max(0.0);
+        ray.origin += ray.direction * near_distance;
+        ray.max_hit_distance = (self.far_clip - depth) / cos_view - near_distance;
+    }
+}
+
+/// The optical model used to generate the camera rays. 
+#[derive(Clone, Copy)]
+pub enum LensModel {
+    /// An ideal pinhole camera, everything is in focus and no aberrations occur.
+    Pinhole,
+    /// A simple glass lens whose refractive index depends on the wavelength, see [ThickLens].
+    ThickLens(ThickLens),
+}
+
+/// A simplified thick lens. The two principal planes of the lens are separated by the thickness,
+/// rays enter through a disc shaped aperture and converge on the focal plane. Since the
+/// refractive index of the glass depends on the wavelength, so do the focus distance and the
+/// magnification, resulting in longitudinal and lateral chromatic aberration (purple fringing).
+/// The spectrum is split into bands and one ray per band is traced.
+#[derive(Clone, Copy)]
+pub struct ThickLens {
+    pub aperture_radius: f32,
+    pub focus_distance: f32,
+    pub thickness: f32,
+    /// The refractive index at the sodium d-line (587.6 nm).
+    pub refractive_index: f32,
+    /// The Abbe number of the glass. Lower values mean stronger dispersion.
+    pub abbe_number: f32,
+    pub nbr_of_wavelength_bands: usize,
+}
+
+impl ThickLens {
+    /// Calculates the refractive index at the given wavelength via Cauchy's equation, with the
+    /// coefficients chosen to match the refractive index and the Abbe number of the lens.
+    fn refractive_index_at(&self, wavelength_nm: f32) -> f32 {
+        let (d, f, c) = (FRAUNHOFER_D_LINE_NM / 1000.0, FRAUNHOFER_F_LINE_NM / 1000.0, FRAUNHOFER_C_LINE_NM / 1000.0);
+        let b = (self.refractive_index - 1.0) / (self.abbe_number * (1.0 / (f * f) - 1.0 / (c * c)));
+        let a = self.refractive_index - b / (d * d);
+        let wavelength_um = wavelength_nm / 1000.0;
+        a + b / (wavelength_um * wavelength_um)
+    }
+
+    /// The factor by which the focal length at the given wavelength differs from the focal length
+    /// at the d-line. According to the lensmaker's equation, the focal length is inversely 
+    /// proportional to n - 1. 
+    fn focal_length_scale(&self, wavelength_nm: f32) -> f32 {
+        (self.refractive_index - 1.0) / (self.refractive_index_at(wavelength_nm) - 1.0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub reflective_spectrum: SpectralReflectance,
+    /// How much the surface reflects like a mirror instead of diffusely, in range \[0; 1]. 
+    pub metallicness: f32,
+    /// The roughness along the tangent of the surface, see [surface_tangent]. 
+    pub roughness: f32,
+    /// The roughness across the tangent, equal to the roughness for isotropic materials. 
+    pub bitangent_roughness: f32,
+    pub tangent_rotation: f32,
+    /// The pattern blending the reflectance towards the second spectrum, if any. 
+    pub pattern: Option<(Pattern, SpectralReflectance)>,
+    /// The re-emission of light at longer wavelengths, if the material is fluorescent. 
+    pub fluorescence: Option<Arc<SpectralReemission>>,
+    /// The two materials this material is a mix of, if any. All other parameters are unused then.
+    pub mix: Option<Arc<MaterialMix>>,
+    /// Whether the camera sees through the surface, except for the shadows cast onto it, see 
+    /// [shadow_catcher_alpha]. Reflections and bounce light treat it like any other surface. 
+    pub shadow_catcher: bool,
+    /// The shading model replacing the reflectance, pattern, fluorescence, metallicness and 
+    /// roughness of the material, if any. See [crate::bsdf]. 
+    pub bsdf: Option<Arc<dyn Bsdf>>,
+}
+
+impl Material {
+    /// Creates an isotropic material without pattern, fluorescence or mix. 
+    pub fn new(reflective_spectrum: SpectralReflectance, metallicness: f32, roughness: f32) -> Material {
+        Material {
+            reflective_spectrum,
+            metallicness,
+            roughness,
+            bitangent_roughness: roughness,
+            tangent_rotation: 0.0,
+            pattern: None,
+            fluorescence: None,
+            mix: None,
+            shadow_catcher: false,
+            bsdf: None,
+        }
+    }
+}
+
+/// Two materials mixed into one. Each hit is shaded with one of the two, picked at random with 
+/// the share of the second material as its chance. 
+#[derive(Debug)]
+pub struct MaterialMix {
+    pub first: Material,
+    pub second: Material,
+    pub weight: MixWeight,
+}
+
+/// How the share of the second material of a mix is determined. 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MixWeight {
+    /// The same share everywhere, in range \[0; 1]. 
+    Constant(f32),
+    /// The share follows the pattern at the hit point, see [Pattern::evaluate]. 
+    Pattern(Pattern),
+    /// The share is the Fresnel reflectance of a dielectric with the given index of refraction, 
+    /// after Schlick's approximation. The second material shows at grazing angles, like a coat. 
+    Fresnel(f32),
+}
+
+impl MixWeight {
+    /// Returns the share of the second material where a ray of the given direction hits the 
+    /// surface with the given normal at the given point, in range \[0; 1]. 
+    pub fn evaluate(&self, point: &Point3<f32>, direction: &Vector3<f32>, normal: &Vector3<f32>) -> f32 {
+        match *self {
+            MixWeight::Constant(share) => share.clamp(0.0, 1.0),
+            MixWeight::Pattern(pattern) => pattern.evaluate(point),
+            MixWeight::Fresnel(refractive_index) => {
+                let reflectance_0 = ((refractive_index - 1.0) / (refractive_index + 1.0)).powi(2);
+                let cos_theta = direction.normalize().dot(normal).abs().min(1.0);
+                reflectance_0 + (1.0 - reflectance_0) * (1.0 - cos_theta).powi(5)
+            }
+        }
+    }
+}
+
+impl Display for MixWeight {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MixWeight::Constant(_) => write!(f, "Constant"),
+            MixWeight::Pattern(pattern) => write!(f, "{pattern}"),
+            MixWeight::Fresnel(_) => write!(f, "Fresnel"),
+        }
+    }
+}
+
+/// Picks the material a hit is shaded with. Mixed materials pick one of their two materials at 
+/// random until a material which is no mix is reached. The random value in range \[0; 1) is 
+/// rescaled after each choice, so that it can be used again for nested mixes. 
+fn choose_material<'a>(material: &'a Material, point: &Point3<f32>, direction: &Vector3<f32>, 
+                       normal: &Vector3<f32>, mut random: f32) -> &'a Material {
+    let mut material = material;
+    while let Some(mix) = &material.mix {
+        let share = mix.weight.evaluate(point, direction, normal);
+        if random < share {
+            random /= share;
+            material = &mix.second;
+        } else {
+            random = (random - share) / (1.0 - share);
+            material = &mix.first;
+        }
+    }
+    material
+}
+
+/// What the camera sees first along the ray of a pixel, for the arbitrary output variables of the 
+/// render besides the color. 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FirstHit {
+    /// The shading normal at the hit. 
+    pub normal: Vector3<f32>,
+    /// The distance from the position of the camera to the hit. 
+    pub distance: f32,
+    /// The reflectance of the surface hit as linear RGB, white being 1 in every channel. 
+    pub albedo: Vector3<f32>,
+    /// The color identifying the object hit in the object ID pass. 
+    pub id_color: Vector3<f32>,
+}
+
+/// The ray generation shader. Returns the linear RGB color of the pixel and its opacity, which is 
+/// below 1 where shadow catchers are seen. 
+pub fn ray_generation_shader(pos: PixelPos, dim: Dimensions, uniforms: &RaytracingUniforms) -> (f32, f32, f32, f32) {
+    let (spectrum, alpha, _, _) = spectral_ray_generation_shader(pos, dim, uniforms);
+    let (r, g, b) = spectrum_to_rgb(&spectrum, uniforms);
+    (r, g, b, alpha)
+}
+
+/// Converts a spectrum gathered by [spectral_ray_generation_shader] into linear RGB, using the 
+/// false color mapping or the sensor response of the uniforms if there is one, the observer of the 
+/// uniforms otherwise. 
+pub fn spectrum_to_rgb(spectrum: &Spectrum, uniforms: &RaytracingUniforms) -> (f32, f32, f32) {
+    if let Some(false_color) = &uniforms.false_color {
+        return false_color.to_rgb(spectrum);
+    }
+    match &uniforms.sensor_response {
+        Some(sensor) => spectrum.get_rgb_by_sensor(sensor),
+        None => spectrum.get_rgb_by_observer(uniforms.observer),
+    }
+}
+
+/// The ray generation shader, returning the spectrum arriving at the pixel instead of its color, 
+/// together with the opacity of the pixel and what the camera sees first, None where nothing is 
+/// hit. With a thick lens, the first hit is the one seen through the first wavelength band. <br/>
+/// If light passes are recorded, the last value holds the share of the spectrum each light source 
+/// of the uniforms contributes via the direct light at the hits of the path, in the order of the 
+/// lights. It is empty otherwise. 
+pub fn spectral_ray_generation_shader(pos: PixelPos, dim: Dimensions, uniforms: &RaytracingUniforms) 
+    -> (Spectrum, f32, Option<FirstHit>, Vec<Spectrum>) {
+    profile_scope!(RayGeneration);
+    let x = pos.x as f32;
+    let y = pos.y as f32;
+    let width = dim.width as f32;
+    let height = dim.height as f32;
+    let aspect_ratio = width / height;
+    let fov_half_rad = (uniforms.camera.fov_y_deg / 2.0) / 180.0 * PI;
+    let focal_distance = 1.0 / fov_half_rad.tan();
+    
+    let sampler = Sampler::new(uniforms, pos);
+    let (pixel_offset_x, pixel_offset_y) = sampler.pixel_offset();
+    
+    let y = -(((y + pixel_offset_y) / height) * 2.0 - 1.0);
+    let x = (((x + pixel_offset_x) / width) * 2.0 - 1.0) * aspect_ratio;
+    
+    let up = uniforms.camera.up.normalize();
+    let forward = uniforms.camera.direction.normalize();
+    let right = forward.cross(&up).normalize(); //forward x up  
+    let true_up = right.cross(&forward);
+
+    match uniforms.camera.lens {
+        LensModel::Pinhole => {
+            let dir = forward * focal_distance - right * x + true_up * y;   //no idea why the - but it works correct this way
+            let dir = dir.normalize();
+
+            let mut ray = Ray::new(uniforms.camera.position, dir, uniforms, pos, &uniforms.example_spectrum);
+            uniforms.camera.clip(&mut ray);
+            submit_ray(&mut ray, uniforms);
+            let first_hit = first_hit(&ray, uniforms);
+            let light_spectra = ray.light_radiances.into_iter().map(SpectralRadiance::into_spectrum).collect();
+            (ray.spectrum.into_spectrum(), ray.alpha, first_hit, light_spectra)
+        }
+        LensModel::ThickLens(lens) => {
+            let mut spectrum = SpectralRadiance::zero(&uniforms.example_spectrum);
+            let mut alpha_sum = 0.0;
+            let mut band_first_hit = None;
+            let mut light_radiances: Vec<SpectralRadiance> = Vec::new();
+            let wavelengths = spectrum.get_wavelengths();
+            let nbr_of_samples = wavelengths.len();
+            let nbr_of_bands = lens.nbr_of_wavelength_bands.clamp(1, nbr_of_samples);
+            let front_principal_plane = uniforms.camera.position + forward * lens.thickness;
+
+            for band in 0..nbr_of_bands {
+                let first_sample = band * nbr_of_samples / nbr_of_bands;
+                let last_sample = (band + 1) * nbr_of_samples / nbr_of_bands;
+                let band_wavelength = (wavelengths[first_sample] + wavelengths[last_sample - 1]) / 2.0;
+                let scale = lens.focal_length_scale(band_wavelength);
+
+                //the chief ray through the center of the lens, magnified according to the wavelength
+                let chief_dir = forward * focal_distance - right * (x * scale) + true_up * (y * scale);
+                let focus_point = front_principal_plane 
+                    + chief_dir * (lens.focus_distance * scale / chief_dir.dot(&forward));
+
+                //sample the aperture
+                let (random_x, random_y) = sampler.lens(band as u32);
+                let (disc_x, disc_y) = concentric_disc_sample(random_x, random_y);
+                let lens_point = front_principal_plane 
+                    + (right * disc_x + true_up * disc_y) * lens.aperture_radius;
+
+                let mut ray = Ray::new(lens_point, focus_point - lens_point, uniforms, pos, &uniforms.example_spectrum);
+                uniforms.camera.clip(&mut ray);
+                submit_ray(&mut ray, uniforms);
+                ray.spectrum.keep_only_sample_range(first_sample, last_sample);
+                spectrum += &ray.spectrum;
+                alpha_sum += ray.alpha;
+                for light_radiance in &mut ray.light_radiances {
+                    light_radiance.keep_only_sample_range(first_sample, last_sample);
+                }
+                if band == 0 {
+                    band_first_hit = first_hit(&ray, uniforms);
+                    light_radiances = ray.light_radiances;
+                } else {
+                    for (sum, light_radiance) in light_radiances.iter_mut().zip(&ray.light_radiances) {
+                        *sum += light_radiance;
+                    }
+                }
+            }
+            let light_spectra = light_radiances.into_iter().map(SpectralRadiance::into_spectrum).collect();
+            (spectrum.into_spectrum(), alpha_sum / nbr_of_bands as f32, band_first_hit, light_spectra)
+        }
+    }
+    //random_pcg3d(pos.x, pos.y, uniforms.frame_id)
+    //TODO dead center in the middle sphere is a big fat aliasing circle
+}
+
+/// Returns what the camera ray, after [submit_ray], hit first, None if it hit nothing. 
+fn first_hit(ray: &Ray, uniforms: &RaytracingUniforms) -> Option<FirstHit> {
+    ray.hit.then(|| FirstHit {
+        normal: ray.normal,
+        distance: (ray.origin + ray.direction * ray.hit_distance - uniforms.camera.position).norm(),
+        albedo: ray.albedo,
+        id_color: ray.id_color,
+    })
+}
+
+/// The intersection shader.
+fn intersection_shader(ray: &Ray, aabb: &Aabb) -> Option<f32> {
+    match aabb.aabb_type.primitive() {
+        Some(primitive) => primitive.intersect(&ray.origin, &ray.direction).filter(|t| *t >= 0.0),
+        None => first_csg_boundary(ray, aabb).map(|boundary| boundary.t),
+    }
+}
+
+/// The closest hit shader. Returns the radiance leaving the hit point towards the origin of the 
+/// ray which does not depend on the rest of the path: the radiance emitted by the surface and the 
+/// light of the light sources reflected or re-emitted by it. If the path is continued, the 
+/// follow-up ray is returned as well, together with the transfer the radiance gathered by it has 
+/// to be multiplied with. <br/>
+/// The share of the returned radiance each light source contributes is added to the light 
+/// radiances at the index of the light, unless they are empty. 
+fn hit_shader(ray: &Ray, aabb: &Aabb, ray_intersection_length: f32, uniforms: &RaytracingUniforms, 
+              light_radiances: &mut [SpectralRadiance]) -> (SpectralRadiance, Option<(Ray, SpectralTransfer)>) {
+    profile_scope!(Shading);
+    
+    //determining position and normal of the hit
+    let intersection_point = ray.origin + ray.direction * ray_intersection_length;
+    let normal = surface_normal(ray, aabb, &intersection_point);
+    let random_material = Sampler::new(uniforms, ray.original_pixel_pos).material_choice(ray.max_bounces);
+    let material = choose_material(&aabb.material, &intersection_point, &ray.direction, &normal, random_material);
+    let reflectance = surface_reflectance(aabb, material, &intersection_point, &normal, &ray.spectrum);
+
+    //a new ray is shot slightly above the hit position because of floating point imprecision in 
+    //order not to intersect at the hit position
+    let new_shot_rays_pos = intersection_point 
+        + normal * ray_offset(&intersection_point, ray_intersection_length, uniforms);
+    
+    if let Some(bsdf) = &material.bsdf {
+        let mut spectrum = bsdf_direct_light(ray, aabb, bsdf.as_ref(), &normal, &new_shot_rays_pos, uniforms, 
+                                             light_radiances);
+        add_emission(&mut spectrum, ray, aabb);
+        return (spectrum, bsdf_bounce(ray, bsdf.as_ref(), &normal, new_shot_rays_pos, uniforms));
+    }
+    
+    //calculating how much light of the light sources hits this point
+    let mut received_irradiance = SpectralIrradiance::zero(&ray.spectrum);
+    //the radiance the point reflects and re-emits when lit with the given irradiance
+    let reflect = |irradiance: &SpectralIrradiance| {
+        let mut spectrum = reflectance.reflect_diffuse(irradiance);
+        if let Some(fluorescence) = &material.fluorescence {
+            spectrum += &fluorescence.reemit_diffuse(irradiance);
+        }
+        spectrum
+    };
+    let mut next_ray = None;
+
+    //get deterministic random values 
+    let (random_x, random_y, random_z) = 
+        Sampler::new(uniforms, ray.original_pixel_pos).bounce(ray.max_bounces);
+    
+    if random_z < material.metallicness {
+        //specular reflection
+
+        if let Some(survival_probability) = continue_path(ray, BounceKind::Specular, uniforms) {
+            let direction = specular_direction(&ray.direction, &normal, material, random_x, random_y);
+            let new_ray = ray.new_bounce(new_shot_rays_pos, direction, BounceKind::Specular,
+                                         reflectance.get_max());
+            next_ray = Some((new_ray, SpectralTransfer::new(reflectance, None, 1.0 / survival_probability)));
+        }
+
+        //TODO direct contributions
+        //metallic rays cannot detect light sources, the photon map provides the caustics instead
+    } else {
+        //diffuse reflection
+
+        //direct light contributions via light sources
+        //important: ONLY HERE is the light intensity divided by distance squared, reflected rays
+        // carry radiance, which does not fall off with distance. 
+        let mut sample_light = |index: usize, weight: f32| {
+            let light = &uniforms.lights[index];
+            if !light.illuminates(aabb.object_id) {
+                return;
+            }
+            if let Some(mut irradiance) = light_irradiance(light, &new_shot_rays_pos, Some(&normal), &ray.spectrum, uniforms) {
+                irradiance *= weight;
+                received_irradiance += &irradiance;
+                if let Some(light_radiance) = light_radiances.get_mut(index) {
+                    *light_radiance += &reflect(&irradiance);
+                }
+            }
+        };
+        match &uniforms.light_distribution {
+            None => (0..uniforms.lights.len()).for_each(|index| sample_light(index, 1.0)),
+            Some(distribution) => {
+                //each drawn light stands in for all the others, weighted by how likely it was drawn
+                let sampler = Sampler::new(uniforms, ray.original_pixel_pos);
+                for i in 0..uniforms.light_samples {
+                    let (random, _) = sampler.light(ray.max_bounces, i);
+                    if let Some((index, probability)) = distribution.sample(random) {
+                        sample_light(index, 1.0 / (probability * uniforms.light_samples as f32));
+                    }
+                }
+            }
+        }
+        //light of the light sources which reached this point via specular reflections (caustics)
+        if let Some(photon_map) = &uniforms.photon_map {
+            received_irradiance += &photon_map.irradiance_at(&intersection_point, &normal, &ray.spectrum);
+        }
+        if let Some(atmosphere) = &uniforms.atmosphere {
+            if let Some(irradiance) = sun_irradiance(atmosphere, &new_shot_rays_pos, Some(&normal), &ray.spectrum, uniforms) {
+                received_irradiance += &irradiance;
+            }
+        }
+
+        //indirect light contribution, interpolated from the irradiance cache at the first diffuse 
+        //hit of the path where possible
+        let cached_irradiance = uniforms.irradiance_cache.as_ref()
+            .filter(|_| ray.diffuse_bounces_left == uniforms.max_diffuse_bounces)
+            .and_then(|cache| cache.irradiance_at(&intersection_point, &normal, &ray.spectrum));
+        if let Some(irradiance) = cached_irradiance {
+            received_irradiance += &irradiance;
+        } else if let Some(survival_probability) = continue_path(ray, BounceKind::Diffuse, uniforms) {
+            //diffuse - random - light ray bounces
+            let new_direction = global_space_random_bounce_direction(random_x, random_y, &normal);  //importance sampling of a sphere, therefore no direction correction necessary later
+            //fluorescence re-emits evenly into all directions, just like the diffuse reflection
+            let transfer = SpectralTransfer::new(reflectance, material.fluorescence.as_deref(), 
+                                                 1.0 / survival_probability);
+            let new_ray = ray.new_bounce(new_shot_rays_pos, new_direction, BounceKind::Diffuse,
+                                         transfer.get_max() * survival_probability);
+            //no direction correction here
+            next_ray = Some((new_ray, transfer));
+        }
+    }
+    
+    let mut spectrum = reflect(&received_irradiance);
+    add_emission(&mut spectrum, ray, aabb);
+    
+    (spectrum, next_ray)
+}
+
+/// Adds the light emitted by the surface itself, if it is visible to the ray. 
+fn add_emission(spectrum: &mut SpectralRadiance, ray: &Ray, aabb: &Aabb) {
+    if let Some(emission) = &aabb.emission {
+        let visible = match ray.bounce_kind {
+            None => emission.visible_to_camera,
+            Some(_) => emission.visible_in_reflections,
+        };
+        if visible {
+            *spectrum += &emission.spectrum;
+        }
+    }
 }
 
-impl From<&UIObject> for Aabb {
-    fn from(value: &UIObject) -> Self {
-        let pos = point![value.pos_x, value.pos_y, value.pos_z];
-        match value.ui_object_type {
-            UIObjectType::PlainBox(x_length, y_length, z_length) => {
-                Aabb::new_box(&pos, x_length, y_length, z_length, (&*value.material.borrow()).into())
+/// Returns the radiance a surface shaded by the BSDF reflects towards the origin of the ray from 
+/// the light sources and the sun. Unlike for the built-in materials, the caustics of the photon 
+/// map and the irradiance cache are not used, as they only hold the irradiance without the 
+/// directions it arrives from. The share of each light source is added to the light radiances 
+/// like in [hit_shader]. 
+fn bsdf_direct_light(ray: &Ray, aabb: &Aabb, bsdf: &dyn Bsdf, normal: &Vector3<f32>, point: &Point3<f32>, 
+                     uniforms: &RaytracingUniforms, light_radiances: &mut [SpectralRadiance]) -> SpectralRadiance {
+    let outgoing = -ray.direction;
+    let mut radiance = SpectralRadiance::zero(&ray.spectrum);
+    let reflect = |irradiance: &SpectralIrradiance, incoming: &Vector3<f32>, weight: f32| {
+        let mut reflected = &bsdf.evaluate(&outgoing, incoming, normal, &ray.spectrum) * &**irradiance;
+        reflected *= weight;
+        SpectralRadiance::new(reflected)
+    };
+    
+    let mut sample_light = |index: usize, weight: f32| {
+        let light = &uniforms.lights[index];
+        if !light.illuminates(aabb.object_id) {
+            return;
+        }
+        if let Some(irradiance) = light_irradiance(light, point, Some(normal), &ray.spectrum, uniforms) {
+            let reflected = reflect(&irradiance, &(light.position - point).normalize(), weight);
+            radiance += &reflected;
+            if let Some(light_radiance) = light_radiances.get_mut(index) {
+                *light_radiance += &reflected;
             }
-            UIObjectType::Sphere(radius) => {
-                Aabb::new_sphere(&pos, radius, (&*value.material.borrow()).into())
+        }
+    };
+    match &uniforms.light_distribution {
+        None => (0..uniforms.lights.len()).for_each(|index| sample_light(index, 1.0)),
+        Some(distribution) => {
+            let sampler = Sampler::new(uniforms, ray.original_pixel_pos);
+            for i in 0..uniforms.light_samples {
+                let (random, _) = sampler.light(ray.max_bounces, i);
+                if let Some((index, probability)) = distribution.sample(random) {
+                    sample_light(index, 1.0 / (probability * uniforms.light_samples as f32));
+                }
             }
-            UIObjectType::RotatedBox(x_length, y_length, z_length, x_rotation, y_rotation, z_rotation) => {
-                let rotation = Rotation3::from_euler_angles(x_rotation, y_rotation, z_rotation);
-                Aabb::new_rotated_box(&pos, x_length, y_length, z_length, rotation, (&*value.material.borrow()).into())
+        }
+    }
+    if let Some(atmosphere) = &uniforms.atmosphere {
+        if let Some(irradiance) = sun_irradiance(atmosphere, point, Some(normal), &ray.spectrum, uniforms) {
+            radiance += &reflect(&irradiance, atmosphere.get_sun_direction(), 1.0);
+        }
+    }
+    radiance
+}
+
+/// Continues the path into a direction drawn from the BSDF. Returns the follow-up ray and its 
+/// transfer, None if the BSDF absorbs the light or the path ends. 
+fn bsdf_bounce(ray: &Ray, bsdf: &dyn Bsdf, normal: &Vector3<f32>, origin: Point3<f32>, uniforms: &RaytracingUniforms) 
+    -> Option<(Ray, SpectralTransfer)> {
+    let (random_x, random_y, _) = Sampler::new(uniforms, ray.original_pixel_pos).bounce(ray.max_bounces);
+    let sample = bsdf.sample(&-ray.direction, normal, random_x, random_y, &ray.spectrum)?;
+    let (kind, factor) = if sample.specular {
+        (BounceKind::Specular, 1.0)
+    } else {
+        //the estimate of the light arriving from all directions, weighted by the cosine
+        let cos_incident = sample.direction.dot(normal).max(0.0);
+        if sample.pdf <= 0.0 || cos_incident <= 0.0 {
+            return None;
+        }
+        (BounceKind::Diffuse, cos_incident / sample.pdf)
+    };
+    let survival_probability = continue_path(ray, kind, uniforms)?;
+    
+    let transfer = SpectralTransfer::new(SpectralReflectance::new(sample.value), None, factor / survival_probability);
+    let new_ray = ray.new_bounce(origin, sample.direction, kind, transfer.get_max() * survival_probability);
+    Some((new_ray, transfer))
+}
+
+/// Returns the direction a ray arriving in the given direction leaves a surface with the given 
+/// normal and material in, when reflected specularly. Rough materials scatter the reflection 
+/// around the mirrored direction, using the two random values. 
+fn specular_direction(direction: &Vector3<f32>, normal: &Vector3<f32>, material: &Material, random_x: f32, 
+                      random_y: f32) -> Vector3<f32> {
+    let reflected_direction = reflect_vec(direction, normal);
+    let (roughness, bitangent_roughness) = (material.roughness, material.bitangent_roughness);
+    if roughness.max(bitangent_roughness) < 0.001 {
+        reflected_direction
+    } else if roughness == bitangent_roughness {
+        sample_in_cone(&reflected_direction, roughness, random_x, random_y)
+    } else {
+        let tangent = surface_tangent(normal, material.tangent_rotation);
+        sample_in_elliptical_cone(&reflected_direction, &tangent, roughness, bitangent_roughness, 
+                                  random_x, random_y)
+    }
+}
+
+/// Traces the photons of the current frame from the light sources and keeps those which reached 
+/// a diffuse surface via at least one specular reflection. These are the caustics the path tracer 
+/// cannot find, as its rays never hit the point lights. Returns None if no photons are traced. 
+/// The photons are traced in parallel on the current thread pool. 
+pub fn trace_photons(uniforms: &RaytracingUniforms) -> Option<PhotonMap> {
+    if uniforms.photon_count == 0 || uniforms.photon_radius <= 0.0 || uniforms.lights.is_empty() {
+        return None;
+    }
+    
+    //photons are emitted by the lights in proportion to their power
+    let distribution = LightDistribution::new(&uniforms.lights, LightSelection::Power);
+    let photons = (0..uniforms.photon_count).into_par_iter()
+        .filter_map(|index| trace_photon(index, &distribution, uniforms))
+        .collect();
+    Some(PhotonMap::new(photons, uniforms.photon_radius))
+}
+
+/// Traces the photon with the given index from a light drawn from the distribution. Returns the 
+/// photon where it hits a diffuse surface, if it was reflected specularly before. 
+fn trace_photon(index: u32, distribution: &LightDistribution, uniforms: &RaytracingUniforms) -> Option<Photon> {
+    profile_scope!(Shading);
+    let pixel = PixelPos {x: index, y: 0};
+    let sampler = Sampler::new(uniforms, pixel);
+    let (random_x, random_y, random_light, _) = sampler.photon(0);
+    let (light_index, probability) = distribution.sample(random_light)?;
+    let light = &uniforms.lights[light_index];
+    let mut power = light.spectrum.get_spectral_power();
+    power *= 1.0 / (probability * uniforms.photon_count as f32);
+    
+    //uniform direction on the sphere
+    let z = 1.0 - 2.0 * random_x;
+    let phi = 2.0 * PI * random_y;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let mut ray = Ray::new(light.position, vector![r * phi.cos(), r * phi.sin(), z], uniforms, pixel, 
+                           &uniforms.example_spectrum);
+    
+    for depth in 1..=uniforms.max_specular_bounces + 1 {
+        count_ray(|statistics| statistics.photon_rays += 1);
+        //photons carry the light of the lights, so they pass objects which cast no shadows
+        let (aabb, t) = closest_hit_where(&ray, uniforms, |aabb| aabb.visibility.casts_shadows)?;
+        //like the specular reflections of the path tracer, photons hitting the adjacent surface again are discarded
+        if depth > 1 && t <= SPECULAR_REFLECTION_HIGH_ROUGHNESS_MINIMUM_RAY_DISTANCE_FACTOR 
+            * ray_offset(&ray.origin, 0.0, uniforms) {
+            return None;
+        }
+        if let Some(medium) = &uniforms.medium {
+            power *= &medium.transmittance(t);
+        }
+        let point = ray.origin + ray.direction * t;
+        let normal = surface_normal(&ray, aabb, &point);
+        let (random_x, random_y, random_z, random_material) = sampler.photon(depth);
+        let material = choose_material(&aabb.material, &point, &ray.direction, &normal, random_material);
+        if material.bsdf.is_some() {
+            //surfaces with a custom shading model neither use nor reflect photons
+            return None;
+        }
+        
+        if random_z >= material.metallicness {
+            //only photons which were reflected specularly are caustics, the rest is path traced. 
+            //Linked lights only leave photons on the objects they illuminate. 
+            let is_caustic = depth > 1 && light.illuminates(aabb.object_id);
+            return is_caustic.then_some(Photon {position: point, direction: ray.direction, power});
+        }
+        power *= &surface_reflectance(aabb, material, &point, &normal, &uniforms.example_spectrum);
+        let direction = specular_direction(&ray.direction, &normal, material, random_x, random_y);
+        ray = Ray::new(point + normal * ray_offset(&point, t, uniforms), direction, uniforms, pixel, 
+                       &uniforms.example_spectrum);
+    }
+    None
+}
+
+/// Builds the irradiance cache for images of the given dimensions. The records are placed at the 
+/// first hits of the rays through the centers of every few pixels, first on a coarse grid of 
+/// pixels, then on ever finer ones wherever the records so far do not suffice. This way, the 
+/// records are dense only where the irradiance changes quickly, like in corners. The irradiance 
+/// of a record is gathered by stratified rays into the hemisphere, each continuing as an ordinary 
+/// path. <br>
+/// Higher qualities in range \[0; 1] allow finer grids, use more rays per record and allow less 
+/// error when interpolating. The records of each grid are computed in parallel on the current 
+/// thread pool. 
+pub fn build_irradiance_cache(dim: &Dimensions, quality: f32, uniforms: &RaytracingUniforms) -> IrradianceCache {
+    let quality = quality.clamp(0.0, 1.0);
+    let finest_spacing = 1 << (3.0 - 2.0 * quality).round() as u32;
+    let strata = (4.0 + 12.0 * quality).round() as u32;
+    let max_error = 0.5 - 0.4 * quality;
+    
+    let mut cache = IrradianceCache::new(max_error);
+    let mut spacing = IRRADIANCE_CACHE_SPACING_COARSEST;
+    while spacing >= finest_spacing {
+        let pixels: Vec<PixelPos> = (spacing / 2..dim.height).step_by(spacing as usize)
+            .flat_map(|y| (spacing / 2..dim.width).step_by(spacing as usize).map(move |x| PixelPos {x, y}))
+            .collect();
+        let records = pixels.into_par_iter()
+            .filter_map(|pos| irradiance_record(pos, dim, finest_spacing, strata, &cache, uniforms))
+            .collect();
+        cache.insert(records);
+        spacing /= 2;
+    }
+    cache
+}
+
+/// Computes the record of the irradiance cache at the first hit of the ray through the center of 
+/// the given pixel, using strata x strata rays. Returns None if nothing or a pure mirror is hit, 
+/// or if the cache already covers the hit. 
+fn irradiance_record(pos: PixelPos, dim: &Dimensions, finest_spacing: u32, strata: u32, cache: &IrradianceCache,
+                     uniforms: &RaytracingUniforms) -> Option<IrradianceRecord> {
+    //the ray of an ideal pinhole camera, see spectral_ray_generation_shader
+    let (width, height) = (dim.width as f32, dim.height as f32);
+    let fov_half_tan = (uniforms.camera.fov_y_deg.to_radians() / 2.0).tan();
+    let y = -(((pos.y as f32 + 0.5) / height) * 2.0 - 1.0);
+    let x = (((pos.x as f32 + 0.5) / width) * 2.0 - 1.0) * width / height;
+    let forward = uniforms.camera.direction.normalize();
+    let right = forward.cross(&uniforms.camera.up.normalize()).normalize();
+    let true_up = right.cross(&forward);
+    let direction = forward / fov_half_tan - right * x + true_up * y;
+    let mut camera_ray = Ray::new(uniforms.camera.position, direction, uniforms, pos, &uniforms.example_spectrum);
+    uniforms.camera.clip(&mut camera_ray);
+    
+    count_ray(|statistics| statistics.camera_rays += 1);
+    let (aabb, t) = closest_hit(&camera_ray, uniforms)?;
+    if (aabb.material.mix.is_none() && aabb.material.metallicness >= 1.0) 
+        || !camera_ray.can_bounce(BounceKind::Diffuse) {
+        return None;
+    }
+    let point = camera_ray.origin + camera_ray.direction * t;
+    let normal = surface_normal(&camera_ray, aabb, &point);
+    if cache.is_covered(&point, &normal) {
+        return None;
+    }
+    
+    let mut irradiance = SpectralIrradiance::zero(&uniforms.example_spectrum);
+    let mut inverse_distance_sum = 0.0;
+    let nbr_of_rays = strata * strata;
+    for i in 0..nbr_of_rays {
+        //every ray has its own random streams, the pixel is only used as their key
+        let sample_pos = PixelPos {x: pos.x * nbr_of_rays + i, y: pos.y};
+        let (jitter_x, jitter_y) = Sampler::new(uniforms, sample_pos).irradiance_sample();
+        let random_x = ((i % strata) as f32 + jitter_x) / strata as f32;
+        let random_y = ((i / strata) as f32 + jitter_y) / strata as f32;
+        let direction = global_space_random_bounce_direction(random_x, random_y, &normal);
+        let mut ray = camera_ray.new_bounce(point + normal * ray_offset(&point, t, uniforms), direction, 
+                                            BounceKind::Diffuse, 1.0);
+        ray.original_pixel_pos = sample_pos;
+        submit_ray(&mut ray, uniforms);
+        irradiance += &SpectralIrradiance::new(ray.spectrum.into_spectrum());
+        if ray.hit {
+            inverse_distance_sum += 1.0 / ray.hit_distance.max(F32_DELTA);
+        }
+    }
+    //the rays are distributed by the cosine, so the irradiance is π times their mean radiance
+    irradiance *= PI / nbr_of_rays as f32;
+    
+    //the distance between neighbouring records on the surface, via the size of a pixel at the hit
+    let camera_distance = (point - uniforms.camera.position).magnitude();
+    let record_distance = finest_spacing as f32 * camera_distance * 2.0 * fov_half_tan / height;
+    let radius_min = IRRADIANCE_CACHE_RADIUS_MIN_FACTOR * record_distance / cache.get_max_error();
+    let mean_distance = (nbr_of_rays as f32 / inverse_distance_sum)
+        .clamp(radius_min, radius_min * IRRADIANCE_CACHE_RADIUS_MAX_FACTOR / IRRADIANCE_CACHE_RADIUS_MIN_FACTOR);
+    Some(IrradianceRecord {position: point, normal, irradiance, mean_distance})
+}
+
+/// Returns the irradiance the light causes at the given point, None if the light is occluded. A 
+/// surface with the given normal receives the light by the cosine of the incoming angle, a point 
+/// in the medium without normal from all directions alike. The medium attenuates the light on its 
+/// way. 
+fn light_irradiance(light: &Light, point: &Point3<f32>, normal: Option<&Vector3<f32>>, example_spectrum: &Spectrum,
+                    uniforms: &RaytracingUniforms) -> Option<SpectralIrradiance> {
+    let direction = light.position - point;
+    let distance = direction.magnitude();
+    let direction_norm = direction.normalize();
+    let shadow_ray = Ray::new_shadow_ray(*point, direction_norm, distance, example_spectrum);
+    if is_occluded(&shadow_ray, uniforms) {
+        return None;
+    }
+    
+    //the intensity of the light becomes an irradiance on the surface, which depends on the 
+    //distance and the incoming ray angle, but not on the outgoing one
+    let cos_incident = normal.map_or(1.0, |normal| direction_norm.dot(normal).max(0.0));
+    let mut irradiance = light.spectrum.irradiance_at(direction.magnitude_squared(), cos_incident);
+    if let Some(medium) = &uniforms.medium {
+        irradiance *= &medium.transmittance(distance);
+    }
+    Some(irradiance)
+}
+
+/// Returns the irradiance the sun of the atmosphere causes at the given point, None if the sun is 
+/// occluded or below the surface. The sunlight arrives in parallel rays, see [light_irradiance] 
+/// for the rest. 
+fn sun_irradiance(atmosphere: &Atmosphere, point: &Point3<f32>, normal: Option<&Vector3<f32>>, example_spectrum: &Spectrum,
+                  uniforms: &RaytracingUniforms) -> Option<SpectralIrradiance> {
+    let direction = atmosphere.get_sun_direction();
+    let cos_incident = normal.map_or(1.0, |normal| direction.dot(normal).max(0.0));
+    if cos_incident <= 0.0 {
+        return None;
+    }
+    let shadow_ray = Ray::new_shadow_ray(*point, *direction, f32::INFINITY, example_spectrum);
+    if is_occluded(&shadow_ray, uniforms) {
+        return None;
+    }
+    
+    let mut irradiance = *atmosphere.get_ground_sun_irradiance();
+    irradiance *= cos_incident;
+    if let Some(medium) = &uniforms.medium {
+        irradiance *= &medium.transmittance(f32::INFINITY);
+    }
+    Some(irradiance)
+}
+
+/// Estimates the light of the light sources which the medium scatters towards the origin of the 
+/// ray along the given distance. A single scattering point is drawn, scattering of light which 
+/// was scattered before is left out. 
+fn medium_in_scattering(ray: &Ray, medium: &Medium, distance: f32, uniforms: &RaytracingUniforms) -> SpectralRadiance {
+    let (random_distance, random_light) = Sampler::new(uniforms, ray.original_pixel_pos).medium(ray.max_bounces);
+    let Some((scatter_distance, probability_density)) = medium.sample_distance(random_distance)
+        .filter(|(scatter_distance, _)| *scatter_distance < distance) else {
+        return SpectralRadiance::zero(&ray.spectrum);
+    };
+    let point = ray.origin + ray.direction * scatter_distance;
+    
+    let mut received_irradiance = SpectralIrradiance::zero(&ray.spectrum);
+    let mut sample_light = |light: &Light, weight: f32| {
+        if let Some(mut irradiance) = light_irradiance(light, &point, None, &ray.spectrum, uniforms) {
+            irradiance *= weight;
+            received_irradiance += &irradiance;
+        }
+    };
+    match &uniforms.light_distribution {
+        None => uniforms.lights.iter().for_each(|light| sample_light(light, 1.0)),
+        Some(distribution) => {
+            if let Some((index, probability)) = distribution.sample(random_light) {
+                sample_light(&uniforms.lights[index], 1.0 / probability);
             }
         }
     }
+    
+    let mut radiance = &medium.transmittance(scatter_distance) * &medium.scatter(&received_irradiance);
+    radiance *= 1.0 / probability_density;
+    radiance
 }
 
-pub (crate) struct Light {
-    position: Point3<f32>,
-    spectrum: Spectrum,
-}
-impl Light {
-    pub fn new(position: Point3<f32>, spectrum: Spectrum) -> Light {
-        Light {
-            position,
-            spectrum,
-        }
+/// Returns the reflectance of the object at the given point on its surface, when shaded with the 
+/// given material. The pattern of the material blends between its two spectra, then, where the 
+/// object has a texture, the result is multiplied with the spectrum of the texel color. 
+fn surface_reflectance(aabb: &Aabb, material: &Material, intersection_point: &Point3<f32>, normal: &Vector3<f32>, 
+                       example_spectrum: &Spectrum) -> SpectralReflectance {
+    let mut reflectance = material.reflective_spectrum;
+    if let Some((pattern, second_spectrum)) = &material.pattern {
+        let share = pattern.evaluate(intersection_point);
+        let mut second_share = *second_spectrum;
+        second_share *= share;
+        reflectance *= 1.0 - share;
+        reflectance += &second_share;
     }
+    
+    let Some(texture) = &aabb.texture else {
+        return reflectance;
+    };
+    let Some((u, v)) = surface_uv(aabb, intersection_point, normal) else {
+        return reflectance;
+    };
+    
+    let (lowest, highest) = example_spectrum.get_range();
+    let texel = Spectrum::new_from_rgb_reflectance(texture.sample(u, v), lowest, highest, 
+                                                   example_spectrum.get_nbr_of_samples());
+    reflectance *= &SpectralReflectance::new(texel);
+    reflectance
+}
+
+/// Returns the reflectance the ray sees where it hits the object after the given distance as 
+/// linear RGB, the material chosen and textured like in [hit_shader]. Each channel is divided by 
+/// that of a perfect white reflector, so that white is 1 in every channel. 
+fn surface_albedo(ray: &Ray, aabb: &Aabb, ray_intersection_length: f32, normal: &Vector3<f32>, 
+                  uniforms: &RaytracingUniforms) -> Vector3<f32> {
+    let intersection_point = ray.origin + ray.direction * ray_intersection_length;
+    let random_material = Sampler::new(uniforms, ray.original_pixel_pos).material_choice(ray.max_bounces);
+    let material = choose_material(&aabb.material, &intersection_point, &ray.direction, normal, random_material);
+    let reflectance = surface_reflectance(aabb, material, &intersection_point, normal, &ray.spectrum);
+    
+    let (r, g, b) = spectrum_to_rgb(&reflectance, uniforms);
+    let (white_r, white_g, white_b) = spectrum_to_rgb(&SpectralReflectance::one(&ray.spectrum), uniforms);
+    let channel = |value: f32, white: f32| if white > 0.0 { (value / white).max(0.0) } else { 0.0 };
+    vector![channel(r, white_r), channel(g, white_g), channel(b, white_b)]
+}
+
+/// Calculates the texture coordinates of the object at the given point on its surface, None if 
+/// the object has none, see [Primitive::uv]. 
+fn surface_uv(aabb: &Aabb, intersection_point: &Point3<f32>, normal: &Vector3<f32>) -> Option<(f32, f32)> {
+    aabb.aabb_type.primitive()?.uv(intersection_point, normal)
 }
 
-impl From<&UILight> for Light {
-    fn from(value: &UILight) -> Self {
-        Light::new(point![value.pos_x, value.pos_y, value.pos_z], 
-                   value.spectrum.borrow().spectrum)
+/// Calculates the texture coordinates on a box from the given point on it in range [0; 1] and the 
+/// normal there, both in the space of the unrotated box. Every side of the box shows the whole 
+/// texture, stretched to its size. 
+fn box_uv(local_point: &Vector3<f32>, local_normal: &Vector3<f32>) -> (f32, f32) {
+    let (x, y, z) = (local_normal.x.abs(), local_normal.y.abs(), local_normal.z.abs());
+    if x >= y && x >= z {
+        (local_point.z, local_point.y)
+    } else if y >= z {
+        (local_point.x, local_point.z)
+    } else {
+        (local_point.x, local_point.y)
     }
 }
 
-#[derive(Clone, Copy)]
-pub (crate) struct Camera {
-    pub position: Point3<f32>,
-    pub direction: Vector3<f32>,
-    pub up: Vector3<f32>,
-    pub fov_y_deg: f32,
+/// Calculates the normal of the object at the given point on its surface, where it was hit by the 
+/// given ray. 
+fn surface_normal(ray: &Ray, aabb: &Aabb, intersection_point: &Point3<f32>) -> Vector3<f32> {
+    let Some(primitive) = aabb.aabb_type.primitive() else {
+        //the intersection shader only returns the distance, the surface hit is found once more
+        return match first_csg_boundary(ray, aabb) {
+            Some(boundary) => {
+                let normal = surface_normal(ray, boundary.surface, intersection_point);
+                if boundary.flipped { -normal } else { normal }
+            }
+            None => -ray.direction,
+        };
+    };
+    primitive.normal(&ray.origin, &ray.direction, intersection_point)
 }
 
-impl Camera {
-    pub fn new(position: Point3<f32>, direction: Vector3<f32>, up: Vector3<f32>, fov_y_deg: f32) -> Camera {
-        Camera {
-            position, 
-            direction, 
-            up,
-            fov_y_deg,
-        }
+/// Decides whether the path of the given ray is continued with a bounce of the given kind. Returns
+/// None if the bounce limits are exhausted or russian roulette terminated the path. Otherwise, the
+/// probability with which the path survived is returned, the light gathered by the continued path
+/// has to be divided by it to keep the image unbiased.
+fn continue_path(ray: &Ray, kind: BounceKind, uniforms: &RaytracingUniforms) -> Option<f32> {
+    if !ray.can_bounce(kind) {
+        return None;
+    }
+
+    let depth = uniforms.max_bounces - ray.max_bounces;
+    if !uniforms.russian_roulette || depth < RUSSIAN_ROULETTE_MIN_DEPTH {
+        return Some(1.0);
+    }
+
+    let survival_probability = ray.throughput.clamp(RUSSIAN_ROULETTE_MIN_SURVIVAL_PROBABILITY, 1.0);
+    let random = Sampler::new(uniforms, ray.original_pixel_pos).russian_roulette(ray.max_bounces);
+    if random < survival_probability {
+        Some(survival_probability)
+    } else {
+        None
     }
 }
 
-impl From<&UICamera> for Camera {
-    fn from(ui_camera: &UICamera) -> Self {
-        Camera::new(
-            point![
-                    ui_camera.pos_x, 
-                    ui_camera.pos_y, 
-                    ui_camera.pos_z
-                ],
-            vector![
-                    ui_camera.dir_x, 
-                    ui_camera.dir_y, 
-                    ui_camera.dir_z
-                ],
-            vector![
-                ui_camera.up_x,
-                ui_camera.up_y,
-                ui_camera.up_z,
-            ],
-            ui_camera.fov_deg_y)
+/// The miss shader. It is called on a submitted ray if this ray does ultimately not hit anything 
+/// and returns the light arriving from the surroundings. <br/>
+/// This is the sky of the atmosphere if there is one, otherwise the background. 
+fn miss_shader(ray: &Ray, uniforms: &RaytracingUniforms) -> SpectralRadiance {
+    match &uniforms.atmosphere {
+        Some(atmosphere) => atmosphere.sky_radiance(&ray.direction),
+        None => uniforms.background.radiance(&ray.direction, &ray.spectrum),
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Material {
-    reflective_spectrum: Spectrum,
-    metallicness: f32,
-    roughness: f32,
+/// What rays leaving the scene without hitting anything see, unless an atmosphere provides the 
+/// sky. 
+#[derive(Clone, Debug)]
+pub enum Background {
+    Black,
+    /// The same radiance from every direction.
+    Uniform(Arc<SpectralRadiance>),
+    /// The radiance blends from the bottom straight down to the top straight up.
+    Gradient {top: Arc<SpectralRadiance>, bottom: Arc<SpectralRadiance>},
+    /// Black, but the camera sees through it, see [Ray::alpha].
+    Transparent,
 }
 
-impl From<&UIMaterial> for Material {
-    fn from(value: &UIMaterial) -> Self {
-        Self {
-            reflective_spectrum: (&*value.spectrum.borrow()).into(),
-            metallicness: value.metallicness,
-            roughness: value.roughness,
+impl Background {
+    /// Returns the radiance arriving from the given direction. 
+    fn radiance(&self, direction: &Vector3<f32>, example_spectrum: &Spectrum) -> SpectralRadiance {
+        match self {
+            Background::Black | Background::Transparent => SpectralRadiance::zero(example_spectrum),
+            Background::Uniform(radiance) => **radiance,
+            Background::Gradient {top, bottom} => {
+                let share_of_top = ((direction.normalize().y + 1.0) / 2.0).clamp(0.0, 1.0);
+                let mut radiance = **top;
+                radiance *= share_of_top;
+                let mut bottom = **bottom;
+                bottom *= 1.0 - share_of_top;
+                radiance += &bottom;
+                radiance
+            }
+        }
+    }
+    
+    /// Multiplies the radiance of the background with the given factor. 
+    pub fn scale_emission(&mut self, factor: f32) {
+        match self {
+            Background::Black | Background::Transparent => {}
+            Background::Uniform(radiance) => *Arc::make_mut(radiance) *= factor,
+            Background::Gradient {top, bottom} => {
+                *Arc::make_mut(top) *= factor;
+                *Arc::make_mut(bottom) *= factor;
+            }
         }
     }
 }
 
-/// The ray generation shader. 
-pub fn ray_generation_shader(pos: PixelPos, dim: Dimensions, uniforms: &RaytracingUniforms) -> (f32, f32, f32) {
-    let x = pos.x as f32;
-    let y = pos.y as f32;
-    let width = dim.width as f32;
-    let height = dim.height as f32;
-    let aspect_ratio = width / height;
-    let fov_half_rad = (uniforms.camera.fov_y_deg / 2.0) / 180.0 * PI;
-    let focal_distance = 1.0 / fov_half_rad.tan();
-    
-    let (pixel_offset_x, pixel_offset_y) = 
-        hammersley(uniforms.frame_id, uniforms.intended_frames_amount);
+/// The heart of the raytracing engine, here the rays are actually shot and tracked through the 
+/// scene. The path of the submitted ray is followed bounce by bounce in a loop: at every hit the 
+/// hit shader returns the light leaving the surface and the follow-up ray. The light is weighted 
+/// with the throughput, the product of all reflectances along the path so far, and summed up. 
+/// After this function returns, the result can be read from the submitted ray. <br/>
+/// Shadow rays only check whether anything is hit, see [is_occluded]. 
+fn submit_ray(ray: &mut Ray, uniforms: &RaytracingUniforms) {
+    if ray.skip_hit_shader {
+        ray.hit = is_occluded(ray, uniforms);
+        return;
+    }
     
-    let y = -(((y + pixel_offset_y) / height) * 2.0 - 1.0);
-    let x = (((x + pixel_offset_x) / width) * 2.0 - 1.0) * aspect_ratio;
+    let mut radiance = SpectralRadiance::zero(&ray.spectrum);
+    let mut throughput = SpectralTransfer::from(SpectralReflectance::one(&ray.spectrum));
+    //the index of each recorded segment, the radiance leaving its hit point and the factor of the 
+    //radiance of the following segment, see trace_path
+    let mut recorded_segments: Vec<(usize, SpectralRadiance, SpectralTransfer)> = Vec::new();
+    let mut first_hit = None;
+    let mut alpha = 1.0;
+    //the radiance each light source contributes along the whole path and at the current hit
+    let nbr_of_light_passes = if uniforms.record_light_passes { uniforms.lights.len() } else { 0 };
+    let mut light_radiances = vec![SpectralRadiance::zero(&ray.spectrum); nbr_of_light_passes];
+    let mut hit_light_radiances = light_radiances.clone();
     
-    let up = uniforms.camera.up.normalize();
-    let forward = uniforms.camera.direction.normalize();
-    let right = forward.cross(&up).normalize(); //forward x up  
-    let true_up = right.cross(&forward);
-    let dir = forward * focal_distance - right * x + true_up * y;   //no idea why the - but it works correct this way
-    let dir = dir.normalize();
-
-    let mut ray = Ray::new(uniforms.camera.position, dir, uniforms.max_bounces, pos, &uniforms.example_spectrum);
-    submit_ray(&mut ray, uniforms);
-
-    ray.spectrum.get_rgb_early()
-    //random_pcg3d(pos.x, pos.y, uniforms.frame_id)
-    //TODO dead center in the middle sphere is a big fat aliasing circle
-}
-
-/// The intersection shader.
-fn intersection_shader(ray: &Ray, aabb: &Aabb) -> Option<f32> {
-    match aabb.aabb_type {
-        AABBType::Sphere => {
-            let sphere_pos = (aabb.min + aabb.max.coords) * 0.5;
-            let radius = aabb.max.x - sphere_pos.x;
-            match ray_sphere_intersection(ray, &sphere_pos, radius) {
-                SphereIntersection::NoIntersection => None,
-                SphereIntersection::OneIntersection(t) => {
-                    if t >= 0.0 {
-                        Some(t)
-                    } else {
-                        None
-                    }
-                },
-                SphereIntersection::TwoIntersections(t_1, t_2) => {
-                    let min = t_1.min(t_2);
-                    let max = t_1.max(t_2);
-                    if min >= 0.0{
-                        Some(min)
-                    } else if max >= 0.0 {
-                        Some(max)
-                    } else {
-                        None
-                    }
-                },
+    let mut next_ray: Option<Ray> = None;
+    loop {
+        let current = next_ray.as_ref().unwrap_or(ray);
+        match current.bounce_kind {
+            None => count_ray(|statistics| statistics.camera_rays += 1),
+            Some(_) => count_ray(|statistics| statistics.bounce_rays += 1),
+        }
+        let hit = closest_hit(current, uniforms);
+        let recorded_segment = start_path_segment(current, hit.as_ref(), uniforms);
+        if first_hit.is_none() {
+            first_hit = Some(hit.map(|(aabb, t)| {
+                let normal = surface_normal(current, aabb, &(current.origin + current.direction * t));
+                (t, normal, surface_albedo(current, aabb, t, &normal, uniforms), aabb.id_color)
+            }));
+        }
+        
+        //the medium scatters light towards the ray and attenuates the light arriving along it
+        if let Some(medium) = &uniforms.medium {
+            let distance = hit.map_or(f32::INFINITY, |(_, t)| t);
+            if medium.is_in_scattering() {
+                radiance += &(&throughput * &medium_in_scattering(current, medium, distance, uniforms));
             }
+            throughput *= &SpectralTransfer::from(medium.transmittance(distance));
         }
-        AABBType::PlainBox => {
-            let (t1, t2) = ray_aabb_intersection(&ray.origin, &ray.direction, &aabb.min, &aabb.max).unwrap();
-            //at least one value is guaranteed to be positive
-            let min = t1.min(t2);
-            if min >= 0.0 {
-                Some(min)
-            } else {
-                Some(t1.max(t2))
+        
+        let (spectrum, next) = match hit {
+            //specular reflections which hit the adjacent surface again are discarded
+            Some((_, t)) if current.bounce_kind == Some(BounceKind::Specular) 
+                && t <= SPECULAR_REFLECTION_HIGH_ROUGHNESS_MINIMUM_RAY_DISTANCE_FACTOR 
+                    * ray_offset(&current.origin, 0.0, uniforms) => {
+                (SpectralRadiance::zero(&radiance), None)
+            }
+            //the camera sees through shadow catchers, only the shadows on them remain
+            Some((aabb, t)) if current.bounce_kind.is_none() && aabb.material.shadow_catcher => {
+                alpha = shadow_catcher_alpha(current, aabb, t, uniforms);
+                (SpectralRadiance::zero(&radiance), None)
             }
-            //no None since ray_aabb already hit and this is the same once again
-        }
-        AABBType::RotatedBox(pos, dim, rotation) => {
-            if let Some((t1, t2)) = ray_oriented_box_intersection(&ray.origin, &ray.direction, &pos, &dim, &rotation) {
-                let min = t1.min(t2);
-                let max = t1.max(t2);
-                
-                if min >= 0.0 {
-                    Some(min)
-                } else if max >= 0.0 {
-                    Some(max)
+            Some((aabb, t)) => {
+                hit_light_radiances.iter_mut().for_each(|light_radiance| *light_radiance = SpectralRadiance::zero(&radiance));
+                let shaded = hit_shader(current, aabb, t, uniforms, &mut hit_light_radiances);
+                for (sum, light_radiance) in light_radiances.iter_mut().zip(&hit_light_radiances) {
+                    *sum += &(&throughput * light_radiance);
+                }
+                shaded
+            }
+            None => {
+                //the camera sees through a transparent background or film, the colors are 
+                //premultiplied by the alpha, so the background must not show through at edges
+                let transparent_background = uniforms.atmosphere.is_none() 
+                    && matches!(uniforms.background, Background::Transparent);
+                if current.bounce_kind.is_none() && (uniforms.transparent_film || transparent_background) {
+                    alpha = 0.0;
+                    (SpectralRadiance::zero(&radiance), None)
                 } else {
-                    None
+                    (miss_shader(current, uniforms), None)
                 }
-            } else {
-                None
             }
+        };
+        radiance += &(&throughput * &spectrum);
+        
+        if let Some(index) = recorded_segment {
+            let factor = next.as_ref().map_or_else(|| SpectralReflectance::zero(&radiance).into(), |(_, f)| f.clone());
+            recorded_segments.push((index, spectrum, factor));
+        }
+        match next {
+            Some((new_ray, factor)) => {
+                throughput *= &factor;
+                next_ray = Some(new_ray);
+            }
+            None => break,
         }
     }
+    
+    if let Some(Some((t, normal, albedo, id_color))) = first_hit {
+        ray.hit = true;
+        ray.hit_distance = t;
+        ray.normal = normal;
+        ray.albedo = albedo;
+        ray.id_color = id_color;
+    }
+    ray.spectrum = radiance;
+    ray.alpha = alpha;
+    ray.light_radiances = light_radiances;
+    finish_path_segments(&recorded_segments);
 }
 
-/// The closest hit shader.
-fn hit_shader(ray: &mut Ray, aabb: &Aabb, ray_intersection_length: f32, uniforms: &RaytracingUniforms) {
-    ray.hit = true;
-    ray.hit_distance = ray_intersection_length;
-    
-    //determining position and normal of the hit
+/// Returns the opacity of a shadow catcher where the ray hits it after the given distance: the 
+/// share of the illuminance of the light sources and the sun which other objects keep from the 
+/// point. A point in full light is transparent, a point in full shadow opaque. 
+fn shadow_catcher_alpha(ray: &Ray, aabb: &Aabb, ray_intersection_length: f32, uniforms: &RaytracingUniforms) -> f32 {
     let intersection_point = ray.origin + ray.direction * ray_intersection_length;
-    let normal= match aabb.aabb_type {
-        AABBType::PlainBox => {
-            plain_box_normal_calculation(aabb, intersection_point)
-        }
-        AABBType::Sphere => {
-            let sphere_pos = (aabb.min + aabb.max.coords) * 0.5;
-            //let radius = aabb.max.x - sphere_pos.x;
-            (intersection_point - sphere_pos).normalize()
+    let normal = surface_normal(ray, aabb, &intersection_point);
+    let new_shot_rays_pos = intersection_point 
+        + normal * ray_offset(&intersection_point, ray_intersection_length, uniforms);
+    
+    let mut illuminance = 0.0;
+    let mut shadowed_illuminance = 0.0;
+    let mut add_light = |direction: Vector3<f32>, distance: f32, light_illuminance: f32| {
+        if light_illuminance <= 0.0 {
+            return;
         }
-        AABBType::RotatedBox(pos, dim, rotation) => {
-            rotated_box_normal_calculation(&pos, &dim, &rotation, &intersection_point)
+        illuminance += light_illuminance;
+        let shadow_ray = Ray::new_shadow_ray(new_shot_rays_pos, direction, distance, &ray.spectrum);
+        if is_occluded(&shadow_ray, uniforms) {
+            shadowed_illuminance += light_illuminance;
         }
     };
+    for light in uniforms.lights.iter().filter(|light| light.illuminates(aabb.object_id)) {
+        let direction = light.position - new_shot_rays_pos;
+        let distance = direction.magnitude();
+        let cos_incident = (direction / distance).dot(&normal).max(0.0);
+        let irradiance = light.spectrum.irradiance_at(distance * distance, cos_incident);
+        add_light(direction / distance, distance, irradiance.get_luminous_value());
+    }
+    if let Some(atmosphere) = &uniforms.atmosphere {
+        let direction = *atmosphere.get_sun_direction();
+        let cos_incident = direction.dot(&normal).max(0.0);
+        add_light(direction, f32::INFINITY, atmosphere.get_ground_sun_irradiance().get_luminous_value() * cos_incident);
+    }
+    
+    if illuminance > 0.0 {
+        shadowed_illuminance / illuminance
+    } else {
+        0.0
+    }
+}
 
-    //a new ray is shot slightly above the hit position because of floating point imprecision in 
-    //order not to intersect at the hit position
-    let new_shot_rays_pos = intersection_point + normal * NEW_RAY_POSITION_OFFSET_DISTANCE;
+/// Returns how far above a surface the rays leaving the given hit point start, so that they do not 
+/// hit the surface again due to floating point imprecision. The imprecision of the hit point grows 
+/// with its distance from the world origin and with the distance the ray travelled to it, so the 
+/// offset is relative to the larger of both. A fixed offset would cause shadow acne in large scenes 
+/// and let light leak through thin objects in tiny ones. 
+fn ray_offset(point: &Point3<f32>, ray_distance: f32, uniforms: &RaytracingUniforms) -> f32 {
+    let scale = point.coords.amax().max(ray_distance).max(RAY_OFFSET_MIN_SCALE);
+    uniforms.ray_offset * scale
+}
+
+/// The numbers of rays shot, by the purpose of the rays. 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RayStatistics {
+    /// The first rays of the paths, shot from the camera or from the points the irradiance cache 
+    /// gathers light at. 
+    pub camera_rays: u64,
+    /// The rays continuing the paths after a reflection. 
+    pub bounce_rays: u64,
+    /// The rays testing whether a light is visible. 
+    pub shadow_rays: u64,
+    /// The rays of the photons of the photon map. 
+    pub photon_rays: u64,
+}
+
+impl RayStatistics {
+    pub const fn new() -> Self {
+        RayStatistics {camera_rays: 0, bounce_rays: 0, shadow_rays: 0, photon_rays: 0}
+    }
     
+    /// Returns the number of all rays. 
+    pub fn total(&self) -> u64 {
+        self.camera_rays + self.bounce_rays + self.shadow_rays + self.photon_rays
+    }
     
-    //calculating how much light hits this point
-    let mut received_spectrum = Spectrum::new_equal_size_empty_spectrum(&ray.spectrum);
+    /// Returns the average number of bounces of a path, 0 if no path was traced. 
+    pub fn average_bounces(&self) -> f32 {
+        if self.camera_rays == 0 {
+            return 0.0;
+        }
+        self.bounce_rays as f32 / self.camera_rays as f32
+    }
+}
 
-    //get deterministic random values 
-    let (random_x, random_y, random_z) = 
-        random_pcg3d(ray.original_pixel_pos.x, ray.original_pixel_pos.y, 
-                     uniforms.frame_id + ray.max_bounces);
-    
-    if random_z < aabb.material.metallicness {
-        //specular reflection
+impl AddAssign<&RayStatistics> for RayStatistics {
+    fn add_assign(&mut self, rhs: &RayStatistics) {
+        self.camera_rays += rhs.camera_rays;
+        self.bounce_rays += rhs.bounce_rays;
+        self.shadow_rays += rhs.shadow_rays;
+        self.photon_rays += rhs.photon_rays;
+    }
+}
 
-        if ray.max_bounces > 1 {
-            let reflected_direction = reflect_vec(&ray.direction, &normal);
-            let direction = if aabb.material.roughness < 0.001 {
-                reflected_direction
-            } else {
-                sample_in_cone(&reflected_direction, aabb.material.roughness, random_x, random_y)
-            };
-            let mut new_ray = Ray::new(new_shot_rays_pos, direction, 
-                                       ray.max_bounces - 1, ray.original_pixel_pos, &ray.spectrum);
-            submit_ray(&mut new_ray, uniforms);
+/// Counts a ray shot on this thread in the statistics, see [RayStatistics]. 
+fn count_ray(count: impl FnOnce(&mut RayStatistics)) {
+    RAY_STATISTICS.with(|statistics| {
+        let mut updated = statistics.get();
+        count(&mut updated);
+        statistics.set(updated);
+    });
+}
 
-            if new_ray.hit_distance > SPECULAR_REFLECTION_HIGH_ROUGHNESS_MINIMUM_RAY_DISTANCE {
-                received_spectrum += &new_ray.spectrum;
-            }
-        }
+/// Returns the rays shot on the calling thread since the last call and resets the counts. 
+pub fn take_ray_statistics() -> RayStatistics {
+    RAY_STATISTICS.with(|statistics| statistics.replace(RayStatistics::new()))
+}
 
-        //TODO direct contributions
-        //TODO metallic rays cannot yet detect light sources
-    } else {
-        //diffuse reflection
+/// Returns the object closest to the origin of the ray which the ray hits within its maximum hit 
+/// distance, together with the distance to it. Objects the ray does not see are passed through, 
+/// see [Ray::sees]. 
+fn closest_hit<'a>(ray: &Ray, uniforms: &'a RaytracingUniforms) -> Option<(&'a Aabb, f32)> {
+    closest_hit_where(ray, uniforms, |aabb| ray.sees(aabb))
+}
 
-        //direct light contributions via light sources
-        //important: ONLY HERE is the light intensity divided by distance squared, reflected rays
-        // have already paid the square tax. 
-        for light in uniforms.lights.iter() {
-            let direction = light.position - new_shot_rays_pos;
-            let distance = direction.magnitude();
-            let direction_norm = direction.normalize();
-            let mut shadow_ray = Ray::new_shadow_ray(new_shot_rays_pos, direction_norm, distance, &ray.spectrum);
-            submit_ray(&mut shadow_ray, uniforms);
-            
-            if !shadow_ray.hit {
-                //adjust strength for distance from light source
-                let mut adjusted = &light.spectrum / direction.magnitude_squared();
-                
-                //adjust for incoming ray angle
-                adjusted *= shadow_ray.direction.normalize().dot(&normal).max(0.0);
-                
-                //adjust for outgoing ray angle
-                adjusted *= (-ray.direction).dot(&normal).max(0.0);
-                
-                received_spectrum += &adjusted;
+/// Returns the object closest to the origin of the ray among those for which is_visible returns 
+/// true, like [closest_hit]. 
+fn closest_hit_where<'a>(ray: &Ray, uniforms: &'a RaytracingUniforms, is_visible: impl Fn(&Aabb) -> bool) 
+    -> Option<(&'a Aabb, f32)> {
+    profile_scope!(Intersection);
+    let mut closest: Option<(&Aabb, f32)> = None;
+    uniforms.bvh.traverse(&ray.origin, &ray.direction, ray.max_hit_distance, |index| {
+        let aabb = &uniforms.aabbs[index];
+        if !is_visible(aabb) {
+            return ControlFlow::Continue(closest.map_or(f32::INFINITY, |(_, t)| t));
+        }
+        let hit = ray_aabb_intersection(&ray.origin, &ray.direction, &aabb.min, &aabb.max)
+            .and_then(|_| intersection_shader(ray, aabb))
+            .filter(|t| *t > 0.0 && *t <= ray.max_hit_distance);
+        if let Some(t) = hit {
+            if closest.is_none_or(|(_, closest_t)| t < closest_t) {
+                closest = Some((aabb, t));
             }
         }
+        ControlFlow::Continue(closest.map_or(f32::INFINITY, |(_, t)| t))
+    });
+    closest
+}
 
-        //indirect light contribution (diffuse - random - light ray bounces)
-        if ray.max_bounces > 1 {
-            let new_direction = global_space_random_bounce_direction(random_x, random_y, &normal);  //importance sampling of a sphere, therefore no direction correction necessary later
-            let mut new_ray = Ray::new(intersection_point, new_direction,
-                                   ray.max_bounces - 1, ray.original_pixel_pos, &ray.spectrum);
-            submit_ray(&mut new_ray, uniforms);
-
-            new_ray.spectrum.max0();
-            //no direction correction here
-            received_spectrum += &new_ray.spectrum; 
-        }
-    }
-    
-    ray.spectrum = &aabb.material.reflective_spectrum * &received_spectrum;
+/// The occlusion query of shadow rays. Returns true as soon as any object casting shadows is hit 
+/// closer than the maximum hit distance of the ray, without searching for the closest hit. 
+fn is_occluded(ray: &Ray, uniforms: &RaytracingUniforms) -> bool {
+    profile_scope!(Intersection);
+    count_ray(|statistics| statistics.shadow_rays += 1);
+    let mut occluded = false;
+    uniforms.bvh.traverse(&ray.origin, &ray.direction, ray.max_hit_distance, |index| {
+        let aabb = &uniforms.aabbs[index];
+        occluded = aabb.visibility.casts_shadows && ray_aabb_intersection(&ray.origin, &ray.direction, &aabb.min, &aabb.max)
+            .and_then(|_| intersection_shader(ray, aabb))
+            .is_some_and(|t| t > 0.0 && t <= ray.max_hit_distance);
+        if occluded { ControlFlow::Break(()) } else { ControlFlow::Continue(f32::INFINITY) }
+    });
+    occluded
 }
 
-/// The miss shader. It is called on a submitted ray if this ray does ultimately not hit anything. 
-/// <br/>
-/// Here it does nothing but set the intensity/color to 0 (black) and set the hit flag to false. 
-fn miss_shader(ray: &mut Ray, _uniforms: &RaytracingUniforms) {
-    ray.spectrum = Spectrum::new_equal_size_empty_spectrum(&ray.spectrum);  //TODO make sky blue perhaps or give user choice
-    ray.hit = false;
+/// Records the given ray if a path is traced by [trace_path] on this thread and returns the index 
+/// of its segment. The spectrum of the segment is filled in after shading. Recording before 
+/// shading keeps the segments in the order the rays were shot. 
+fn start_path_segment(ray: &Ray, hit: Option<&(&Aabb, f32)>, uniforms: &RaytracingUniforms) -> Option<usize> {
+    PATH_RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        let segments = recorder.as_mut()?;
+        let hit = hit.map(|(aabb, t)| {
+            (uniforms.aabbs.iter().position(|a| std::ptr::eq(a, *aabb)).unwrap(), *t)
+        });
+        segments.push(PathSegment {
+            depth: uniforms.max_bounces - ray.max_bounces,
+            kind: match ray.bounce_kind {
+                None => "camera",
+                Some(BounceKind::Diffuse) => "diffuse",
+                Some(BounceKind::Specular) => "specular",
+                Some(BounceKind::Transmission) => "transmission",
+            },
+            origin: ray.origin,
+            direction: ray.direction,
+            hit,
+            spectrum: Spectrum::new_equal_size_empty_spectrum(&ray.spectrum),
+        });
+        Some(segments.len() - 1)
+    })
 }
 
-/// The heart of the raytracing engine, here the rays are actually shot and tracked through the 
-/// scene. After all collisions have been determined, the appropriate shaders are called, which
-/// mutate the ray and after this function returns, the result can be read from the submitted ray. 
-fn submit_ray(ray: &mut Ray, uniforms: &RaytracingUniforms) {
-    let mut intersections: Vec<(&Aabb, f32)> = Vec::new();
-    
-    for aabb in uniforms.aabbs.iter() {
-        if let Some((_t_min, _t_max)) = ray_aabb_intersection(&ray.origin, &ray.direction, &aabb.min, &aabb.max) {
-            if let Some(t) = intersection_shader(ray, aabb) {
-                if t > 0.0 {
-                    intersections.push((aabb, t));
+/// Fills in the spectra of the segments recorded while tracing a path. The radiance carried along 
+/// a segment is the radiance leaving its hit point plus the radiance of the following segment, 
+/// weighted with the factor of the bounce. 
+fn finish_path_segments(recorded_segments: &[(usize, SpectralRadiance, SpectralTransfer)]) {
+    if recorded_segments.is_empty() {
+        return;
+    }
+    PATH_RECORDER.with(|recorder| {
+        if let Some(segments) = recorder.borrow_mut().as_mut() {
+            let mut following: Option<SpectralRadiance> = None;
+            for (index, spectrum, factor) in recorded_segments.iter().rev() {
+                let mut carried = *spectrum;
+                if let Some(following) = &following {
+                    carried += &(factor * following);
                 }
+                segments[*index].spectrum = carried.into_spectrum();
+                following = Some(carried);
             }
         }
-    }
+    });
+}
+
+/// Traces the path of the given pixel like [spectral_ray_generation_shader] while recording every 
+/// ray of it. Returns the spectrum arriving at the pixel and the recorded segments. 
+pub fn trace_path(pos: PixelPos, dim: Dimensions, uniforms: &RaytracingUniforms) -> (Spectrum, Vec<PathSegment>) {
+    PATH_RECORDER.with(|recorder| *recorder.borrow_mut() = Some(Vec::new()));
+    let (spectrum, _, _, _) = spectral_ray_generation_shader(pos, dim, uniforms);
+    let segments = PATH_RECORDER.with(|recorder| recorder.borrow_mut().take()).unwrap_or_default();
+    (spectrum, segments)
+}
+
+/// Returns the focus distance which brings the surface seen through the center of the pixel into 
+/// focus, the distance of the surface from the front principal plane of the lens along the view 
+/// direction, see [ThickLens]. The chief ray through the center of the lens is traced like a 
+/// camera ray for the d-line, without shading. None if it hits nothing. 
+pub fn focus_distance_at(pos: PixelPos, dim: Dimensions, uniforms: &RaytracingUniforms) -> Option<f32> {
+    let aspect_ratio = dim.width as f32 / dim.height as f32;
+    let focal_distance = 1.0 / (uniforms.camera.fov_y_deg.to_radians() / 2.0).tan();
+    let y = -(((pos.y as f32 + 0.5) / dim.height as f32) * 2.0 - 1.0);
+    let x = (((pos.x as f32 + 0.5) / dim.width as f32) * 2.0 - 1.0) * aspect_ratio;
     
-    intersections.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    let up = uniforms.camera.up.normalize();
+    let forward = uniforms.camera.direction.normalize();
+    let right = forward.cross(&up).normalize();
+    let true_up = right.cross(&forward);
+    let thickness = match uniforms.camera.lens {
+        LensModel::Pinhole => 0.0,
+        LensModel::ThickLens(lens) => lens.thickness,
+    };
+    let front_principal_plane = uniforms.camera.position + forward * thickness;
     
-    if let Some((aabb, t)) = intersections.first() {
-        if t <= &ray.max_hit_distance {
-            if !ray.skip_hit_shader {
-                hit_shader(ray, aabb, *t, uniforms);
-            } else {
-                ray.hit = true;
-            }
+    let chief_dir = forward * focal_distance - right * x + true_up * y;
+    let mut ray = Ray::new(front_principal_plane, chief_dir, uniforms, pos, &uniforms.example_spectrum);
+    uniforms.camera.clip(&mut ray);
+    let (_, t) = closest_hit(&ray, uniforms)?;
+    Some((ray.origin + ray.direction * t - front_principal_plane).dot(&forward))
+}
+
+/// Shoots a ray at the center of the object from each of the six axis directions and returns true 
+/// if all of them hit its surface in front of the center. Flat objects are only shot at along 
+/// their normal from the sides they are visible from. Objects failing the test are degenerate 
+/// and will be missing or broken in the render. 
+pub fn intersection_self_test(aabb: &Aabb) -> bool {
+    let center = aabb.min + (aabb.max - aabb.min) * 0.5;
+    let distance = (aabb.max - aabb.min).norm() + 1.0;
+    if !distance.is_finite() {
+        return false;
+    }
+    let example_spectrum = Spectrum::new_singular_reflectance_factor(
+        crate::spectrum::VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+        crate::spectrum::VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+        8,
+        0.0,
+    );
+    
+    let directions = match aabb.aabb_type {
+        //the combined object may have a hole along any of the axes, such as a tube, so only its 
+        //parts are tested
+        AABBType::Csg(_, ref first, ref second) => {
+            return intersection_self_test(first) && intersection_self_test(second);
         }
+        AABBType::Disc(ref disc) => vec![disc.rotation * Vector3::y(), disc.rotation * -Vector3::y()],
+        //the heightfield is open below and at its sides
+        AABBType::Heightfield(..) => vec![-Vector3::y()],
+        AABBType::Quad(ref quad) => vec![quad.rotation * -Vector3::y()],
+        _ => vec![Vector3::x(), -Vector3::x(), Vector3::y(), -Vector3::y(), Vector3::z(), -Vector3::z()],
+    };
+    directions.iter().all(|direction| {
+        let ray = Ray::new_shadow_ray(center - direction * distance, *direction, distance, &example_spectrum);
+        //the surface of flat objects lies at the center, which is only hit up to rounding errors
+        ray_aabb_intersection(&ray.origin, &ray.direction, &aabb.min, &aabb.max)
+            .and_then(|_| intersection_shader(&ray, aabb))
+            .is_some_and(|t| t > 0.0 && t <= distance * (1.0 + F32_DELTA))
+    })
+}
 
-    } else {
-        miss_shader(ray, uniforms);
+/// Returns the stretches of the ray inside the object, from where the ray enters the object to 
+/// where it leaves it, sorted by distance. Unlike the intersection shader, stretches behind the 
+/// origin of the ray are returned as well, which CSG objects need to know whether the ray starts 
+/// inside one of their parts. Flat objects enclose no volume and have no stretches. 
+fn ray_solid_intervals<'a>(ray: &Ray, aabb: &'a Aabb) -> Vec<(Boundary<&'a Aabb>, Boundary<&'a Aabb>)> {
+    if let AABBType::Csg(operation, first, second) = &aabb.aabb_type {
+        return csg::combine(*operation, &ray_solid_intervals(ray, first), &ray_solid_intervals(ray, second));
     }
+    
+    let boundary = |t| Boundary {t, surface: aabb, flipped: false};
+    aabb.aabb_type.primitive()
+        .map(|primitive| primitive.solid_intervals(&ray.origin, &ray.direction))
+        .unwrap_or_default().into_iter()
+        .map(|(t1, t2)| (boundary(t1), boundary(t2)))
+        .collect()
+}
+
+/// Returns the first boundary of the CSG object in front of the origin of the ray, None if the ray 
+/// misses it. 
+fn first_csg_boundary<'a>(ray: &Ray, aabb: &'a Aabb) -> Option<Boundary<&'a Aabb>> {
+    ray_solid_intervals(ray, aabb).into_iter()
+        .flat_map(|(enter, leave)| [enter, leave])
+        .find(|boundary| boundary.t >= 0.0)
 }
 
 /// An enum to differentiate between the possible cases of a ray-sphere-intersection. The ray can
@@ -505,10 +2440,11 @@ enum SphereIntersection {
 
 /// Calculates the intersection between a ray and a sphere. The intersection points are returned as
 /// scalars for the direction of the ray. 
-fn ray_sphere_intersection(ray: &Ray, sphere_pos: &Point3<f32>, sphere_rad: f32) -> SphereIntersection {
-    let oc = ray.origin - sphere_pos;
-    let a = ray.direction.dot(&ray.direction);
-    let b = 2.0 * oc.dot(&ray.direction);
+fn ray_sphere_intersection(ray_origin: &Point3<f32>, ray_direction: &Vector3<f32>, sphere_pos: &Point3<f32>, 
+                           sphere_rad: f32) -> SphereIntersection {
+    let oc = ray_origin - sphere_pos;
+    let a = ray_direction.dot(ray_direction);
+    let b = 2.0 * oc.dot(ray_direction);
     let c = oc.dot(&oc) - sphere_rad * sphere_rad;
     
     let discriminant = b * b - 4.0 * a * c;
@@ -578,25 +2514,188 @@ fn ray_oriented_box_intersection(ray_origin: &Point3<f32>, ray_direction: &Vecto
     )
 }
 
+/// Calculates the potential intersections of a ray and a capped cylinder. Returns the lengths of 
+/// the ray upon entering and leaving the cylinder, the smaller one first, iff the ray intersects 
+/// it, else None. 
+fn ray_cylinder_intersection(ray_origin: &Point3<f32>, ray_direction: &Vector3<f32>, position: &Point3<f32>,
+                             radius: f32, height: f32, rotation: &Rotation3<f32>) -> Option<(f32, f32)> {
+    //in local space, the axis of the cylinder is the y axis
+    let inv_rotation = rotation.inverse();
+    let origin = inv_rotation * (ray_origin - position);
+    let direction = inv_rotation * ray_direction;
+    let half_height = height / 2.0;
+    
+    //the ray has to be between the two caps...
+    let (cap_t_min, cap_t_max) = if direction.y == 0.0 {
+        if origin.y.abs() > half_height {
+            return None;
+        }
+        (f32::NEG_INFINITY, f32::INFINITY)
+    } else {
+        let t1 = (-half_height - origin.y) / direction.y;
+        let t2 = (half_height - origin.y) / direction.y;
+        (t1.min(t2), t1.max(t2))
+    };
+    
+    //...and inside the infinite cylinder around the axis
+    let a = direction.x * direction.x + direction.z * direction.z;
+    let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+    let c = origin.x * origin.x + origin.z * origin.z - radius * radius;
+    let (side_t_min, side_t_max) = if a == 0.0 {
+        //parallel to the axis
+        if c > 0.0 {
+            return None;
+        }
+        (f32::NEG_INFINITY, f32::INFINITY)
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let discriminant_sqrt = discriminant.sqrt();
+        ((-b - discriminant_sqrt) / (2.0 * a), (-b + discriminant_sqrt) / (2.0 * a))
+    };
+    
+    let t_min = cap_t_min.max(side_t_min);
+    let t_max = cap_t_max.min(side_t_max);
+    if t_max <= t_min || t_max < 0.0 {
+        return None;
+    }
+    Some((t_min, t_max))
+}
+
+/// Calculates the potential intersections of a ray and an ellipsoid. The ray is transformed into 
+/// the space in which the ellipsoid is the unit sphere, which keeps the lengths of the ray. Returns 
+/// the lengths of the ray upon entering and leaving the ellipsoid, the smaller one first, iff the 
+/// ray intersects it, else None. 
+fn ray_ellipsoid_intersection(ray_origin: &Point3<f32>, ray_direction: &Vector3<f32>, position: &Point3<f32>,
+                              radii: &Vector3<f32>, rotation: &Rotation3<f32>) -> Option<(f32, f32)> {
+    let inv_rotation = rotation.inverse();
+    let origin = (inv_rotation * (ray_origin - position)).component_div(radii);
+    let direction = (inv_rotation * ray_direction).component_div(radii);
+    
+    let a = direction.dot(&direction);
+    let b = 2.0 * origin.dot(&direction);
+    let c = origin.dot(&origin) - 1.0;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    
+    let discriminant_sqrt = discriminant.sqrt();
+    let t_min = (-b - discriminant_sqrt) / (2.0 * a);
+    let t_max = (-b + discriminant_sqrt) / (2.0 * a);
+    if t_max < 0.0 {
+        return None;
+    }
+    Some((t_min, t_max))
+}
+
+/// Calculates the intersection of a ray and the plane of a flat object. Returns the length of the 
+/// ray upon hitting the plane together with the hit point in the local space of the object, in 
+/// which the plane is spanned by the x and z axis. If the object is single-sided, rays hitting it 
+/// from behind miss. 
+fn ray_flat_object_intersection(ray_origin: &Point3<f32>, ray_direction: &Vector3<f32>, position: &Point3<f32>,
+                                rotation: &Rotation3<f32>, single_sided: bool) -> Option<(f32, Point3<f32>)> {
+    let normal = rotation * Vector3::y();
+    let denominator = ray_direction.dot(&normal);
+    if denominator == 0.0 || (single_sided && denominator > 0.0) {
+        return None;
+    }
+    
+    let t = (position - ray_origin).dot(&normal) / denominator;
+    if t < 0.0 {
+        return None;
+    }
+    let local_point = rotation.inverse() * (ray_origin + ray_direction * t - position);
+    Some((t, local_point.into()))
+}
+
+/// Calculates the first intersection of a ray and a heightfield, see [HeightGrid::intersect]. 
+/// Returns the length of the ray upon hitting the surface and the upwards normal of the surface 
+/// there, None if the ray misses it. 
+fn ray_heightfield_intersection(ray_origin: &Point3<f32>, ray_direction: &Vector3<f32>, base: &Point3<f32>, 
+                                dimensions: &Vector3<f32>, grid: &HeightGrid) -> Option<(f32, Vector3<f32>)> {
+    let local_origin = Point3::from(ray_origin - base);
+    let half = vector![dimensions.x / 2.0, 0.0, dimensions.z / 2.0];
+    let padding = Vector3::repeat(FLAT_OBJECT_BOUNDS_PADDING);
+    let point_min = Point3::from(-half - padding);
+    let point_max = Point3::from(half + vector![0.0, dimensions.y * grid.get_highest(), 0.0] + padding);
+    let (t_enter, t_exit) = ray_aabb_intersection(&local_origin, ray_direction, &point_min, &point_max)?;
+    grid.intersect(&local_origin, ray_direction, dimensions, t_enter.max(0.0), t_exit)
+}
+
+/// Returns the first of the distances where a ray enters and leaves a solid object which is not 
+/// behind the origin of the ray, None if the object lies behind it. 
+fn first_in_front((t_enter, t_leave): (f32, f32)) -> Option<f32> {
+    if t_enter >= 0.0 {
+        Some(t_enter)
+    } else if t_leave >= 0.0 {
+        Some(t_leave)
+    } else {
+        None
+    }
+}
+
+/// Returns the rotation which turns the y axis onto the given normal, used to place flat objects. 
+/// A normal of length zero is treated as the y axis. 
+pub fn flat_object_rotation(normal: &Vector3<f32>) -> Rotation3<f32> {
+    if normal.norm() == 0.0 {
+        return Rotation3::identity();
+    }
+    //there is no unique rotation onto the opposite direction, any half turn will do
+    Rotation3::rotation_between(&Vector3::y(), normal)
+        .unwrap_or_else(|| Rotation3::from_axis_angle(&Vector3::x_axis(), PI))
+}
+
+/// Calculates the normal for a given hit on a capped cylinder. 
+fn cylinder_normal_calculation(pos: &Point3<f32>, radius: f32, height: f32, rotation: &Rotation3<f32>,
+                               intersection_point: &Point3<f32>) -> Vector3<f32> {
+    //transform hit point into cylinder local space
+    let local_point = rotation.inverse() * (intersection_point - pos);
+    let distance_from_axis = (local_point.x * local_point.x + local_point.z * local_point.z).sqrt();
+    
+    //whichever surface the hit point lies closer to was hit
+    let distance_cap = (height / 2.0 - local_point.y.abs()).abs();
+    let distance_side = (radius - distance_from_axis).abs();
+    let normal_local = if distance_cap < distance_side || distance_from_axis == 0.0 {
+        vector![0.0, local_point.y.signum(), 0.0]
+    } else {
+        vector![local_point.x / distance_from_axis, 0.0, local_point.z / distance_from_axis]
+    };
+    
+    rotation * normal_local
+}
+
 /// Calculate the normal for a given hit on a plain box. 
-fn plain_box_normal_calculation(aabb: &Aabb, intersection_point: OPoint<f32, Const<3>>) -> OMatrix<f32, Const<3>, Const<1>> {
-    let x = if (intersection_point.x - aabb.min.x).abs() < F32_DELTA {
+/// Calculates the normal of an ellipsoid at the given point on its surface. Normals do not follow 
+/// the stretching of the unit sphere, but its inverse: the normal of the unit sphere is divided by 
+/// the radii instead of being multiplied with them. 
+fn ellipsoid_normal_calculation(pos: &Point3<f32>, radii: &Vector3<f32>, rotation: &Rotation3<f32>,
+                                intersection_point: &Point3<f32>) -> Vector3<f32> {
+    let local_point = rotation.inverse() * (intersection_point - pos);
+    let local_normal = local_point.component_div(&radii.component_mul(radii));
+    (rotation * local_normal).normalize()
+}
+
+fn plain_box_normal_calculation(min: &Point3<f32>, max: &Point3<f32>, intersection_point: &Point3<f32>) -> Vector3<f32> {
+    let x = if (intersection_point.x - min.x).abs() < F32_DELTA {
         -1.0
-    } else if (intersection_point.x - aabb.max.x).abs() < F32_DELTA {
+    } else if (intersection_point.x - max.x).abs() < F32_DELTA {
         1.0
     } else {
         0.0
     };
-    let y = if (intersection_point.y - aabb.min.y).abs() < F32_DELTA {
+    let y = if (intersection_point.y - min.y).abs() < F32_DELTA {
         -1.0
-    } else if (intersection_point.y - aabb.max.y).abs() < F32_DELTA {
+    } else if (intersection_point.y - max.y).abs() < F32_DELTA {
         1.0
     } else {
         0.0
     };
-    let z = if (intersection_point.z - aabb.min.z).abs() < F32_DELTA {
+    let z = if (intersection_point.z - min.z).abs() < F32_DELTA {
         -1.0
-    } else if (intersection_point.z - aabb.max.z).abs() < F32_DELTA {
+    } else if (intersection_point.z - max.z).abs() < F32_DELTA {
         1.0
     } else {
         0.0
@@ -649,59 +2748,22 @@ pub fn rotated_box_normal_calculation(pos: &Point3<f32>, dim: &Vector3<f32>, rot
     rotation * normal_local
 }
 
-// from http://holger.dammertz.org/stuff/notes_HammersleyOnHemisphere.html
-// Hacker's Delight, Henry S. Warren, 2001
-//adapted to be used in rust
-fn radical_inverse(mut bits: u32) -> f32 {
-    bits = bits.rotate_right(16);
-    bits = ((bits & 0x55555555) << 1) | ((bits & 0xAAAAAAAA) >> 1);
-    bits = ((bits & 0x33333333) << 2) | ((bits & 0xCCCCCCCC) >> 2);
-    bits = ((bits & 0x0F0F0F0F) << 4) | ((bits & 0xF0F0F0F0) >> 4);
-    bits = ((bits & 0x00FF00FF) << 8) | ((bits & 0xFF00FF00) >> 8);
-    (bits as f32) * 2.328_306_4e-10  // / 0x100000000
-}
-
-/// A hammersley sequence. Takes two numbers where the first should be strictly smaller than the 
-/// second. Use it f.ex. this way: <br>
-/// ```let (x, y) = hammersley(current_frame, total_number_of_frames);```<br>
-/// For capital_n = 10 the sequence looks like this: <br><br>
-/// (0.05, 0.5), (0.15, 0.25), (0.25, 0.75), (0.35, 0.125), (0.45, 0.625), (0.55, 0.375)
-/// (0.65, 0.875), (0.75, 0.0625), (0.85, 0.5625), (0.95, 0.3125)
-fn hammersley(n: u32, capital_n: u32) -> (f32, f32) {
-    (
-        (n as f32 + 0.5) / capital_n as f32,
-        radical_inverse(n + 1),
-    )
-}
+/// Maps two random variables in range \[0; 1] uniformly unto the unit disc, preserving the 
+/// stratification of the input. Returns the x and y coordinate on the disc. <br>
+/// A Low Distortion Map Between Disk and Square, Shirley and Chiu, 1997
+fn concentric_disc_sample(random_x: f32, random_y: f32) -> (f32, f32) {
+    let offset_x = 2.0 * random_x - 1.0;
+    let offset_y = 2.0 * random_y - 1.0;
+    if offset_x == 0.0 && offset_y == 0.0 {
+        return (0.0, 0.0);
+    }
 
-/// Calculates three quasi random floats from unsigned integers. The integers can usually be: <br>
-/// x = pixel position x, <br>
-/// y = pixel position y, <br>
-/// z = frame number <br>
-/// The resulting three floats will probably be in range \[0; 1] <br>
-/// <br>
-/// Hash Functions for GPU Rendering, Jarzynski et al. <br>
-/// http://www.jcgt.org/published/0009/03/02/
-fn random_pcg3d(mut x: u32, mut y: u32, mut z: u32) -> (f32, f32, f32) {
-    x = x.wrapping_mul(1664525).wrapping_add(1013904223);
-    y = y.wrapping_mul(1664525).wrapping_add(1013904223);
-    z = z.wrapping_mul(1664525).wrapping_add(1013904223);
-    x = y.wrapping_mul(z).wrapping_add(x);
-    y = z.wrapping_mul(x).wrapping_add(y);
-    z = x.wrapping_mul(y).wrapping_add(z);
-    x ^= x >> 16;
-    y ^= y >> 16;
-    z ^= z >> 16;
-    x = y.wrapping_mul(z).wrapping_add(x);
-    y = z.wrapping_mul(x).wrapping_add(y);
-    z = x.wrapping_mul(y).wrapping_add(z);
-
-    let reciprocal = 1.0 / 0xffffffffu32 as f32;
-    (
-        x as f32 * reciprocal,
-        y as f32 * reciprocal,
-        z as f32 * reciprocal,
-    )
+    let (radius, theta) = if offset_x.abs() > offset_y.abs() {
+        (offset_x, std::f32::consts::FRAC_PI_4 * (offset_y / offset_x))
+    } else {
+        (offset_y, std::f32::consts::FRAC_PI_2 - std::f32::consts::FRAC_PI_4 * (offset_x / offset_y))
+    };
+    (radius * theta.cos(), radius * theta.sin())
 }
 
 /// Reflects a vector incident about the given normal (which must be normalized for correct results).
@@ -728,6 +2790,42 @@ fn global_space_random_bounce_direction(random_x: f32, random_y: f32, normal: &V
     Rotation3::face_towards(normal, &up) * local_direction
 }
 
+/// Returns the tangent of the surface with the given normal, along which the roughness of 
+/// anisotropic materials applies. The tangent follows the world x axis projected onto the surface 
+/// (the z axis for surfaces facing along x), turned around the normal by the given rotation. 
+fn surface_tangent(normal: &Vector3<f32>, rotation: f32) -> Vector3<f32> {
+    let reference = if normal.x.abs() < 0.9 { Vector3::x() } else { Vector3::z() };
+    let tangent = (reference - normal * normal.dot(&reference)).normalize();
+    Rotation3::from_axis_angle(&Unit::new_normalize(*normal), rotation) * tangent
+}
+
+/// Works like [sample_in_cone], but the cone is elliptical: it opens by the roughness along the 
+/// given tangent and by the bitangent roughness across it. Highlights stretch along the rougher 
+/// direction, like those of brushed metal. 
+fn sample_in_elliptical_cone(original_direction: &Vector3<f32>, tangent: &Vector3<f32>, roughness: f32, 
+                             bitangent_roughness: f32, random_x: f32, random_y: f32) -> Vector3<f32> {
+    let w = original_direction.normalize();
+    //the tangent is brought perpendicular to the direction the cone opens around
+    let mut u = tangent - w * w.dot(tangent);
+    if u.magnitude_squared() < 1e-8 {
+        u = w.cross(&if w.z.abs() < 0.999 {Vector3::z()} else {Vector3::x()});
+    }
+    let u = u.normalize();
+    let v = w.cross(&u);
+    
+    //the roughness in the direction of phi lies on the ellipse spanned by the two roughnesses
+    let phi = 2.0 * PI * random_y;
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    let denominator = ((bitangent_roughness * cos_phi).powi(2) + (roughness * sin_phi).powi(2)).sqrt();
+    let directional_roughness = roughness * bitangent_roughness / denominator.max(f32::EPSILON);
+    
+    let theta_max = directional_roughness * directional_roughness * std::f32::consts::FRAC_PI_2;
+    let cos_theta = (1.0 - random_x) + random_x * theta_max.cos();
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+    (u * sin_theta * cos_phi + v * sin_theta * sin_phi + w * cos_theta).normalize()
+}
+
 /// Generates a vector, pointing roughly in the same direction as the given original direction,
 /// based on the roughness provided. For a roughness of 0 nothing changes, a roughness of 1
 /// produces a random direction within the semicircle of the original direction. <br>
@@ -752,4 +2850,92 @@ fn sample_in_cone(original_direction: &Vector3<f32>, roughness: f32, random_x: f
     let u = v.cross(&w);
 
     (u * local_direction.x + v * local_direction.y + w * local_direction.z).normalize()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{RenderSettings, Scene};
+    use super::*;
+
+    /// A small linear congruential generator, so that the rays are the same on every run.
+    struct Random(u64);
+
+    impl Random {
+        /// Returns the next number in range \[low; high).
+        fn next(&mut self, low: f32, high: f32) -> f32 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            low + (self.0 >> 40) as f32 / (1u64 << 24) as f32 * (high - low)
+        }
+
+        fn vector(&mut self, low: f32, high: f32) -> Vector3<f32> {
+            vector![self.next(low, high), self.next(low, high), self.next(low, high)]
+        }
+    }
+
+    fn relative_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-4 * a.abs().max(1.0)
+    }
+
+    /// Compares the distances and normals of spheres and quads reached through [Primitive] with 
+    /// the calculations the shaders did for them before, for rays from all around them.
+    #[test]
+    fn test_built_in_shapes_hit_like_before_dispatch() {
+        let settings = RenderSettings::default();
+        let (lowest, highest) = settings.spectrum_range;
+        let spectrum = Spectrum::new_singular_reflectance_factor(lowest, highest, settings.nbr_of_samples, 0.5);
+        let material = Material::new(SpectralReflectance::new(spectrum), 0.0, 1.0);
+        let uniforms = Scene {
+            objects: Vec::new(),
+            lights: Vec::new(),
+            camera: Camera::new(Point3::origin(), -Vector3::z(), Vector3::y(), 60.0, LensModel::Pinhole),
+            medium: None,
+            atmosphere: None,
+            background: Background::Uniform(Arc::new(SpectralRadiance::zero(&spectrum))),
+        }.build_uniforms(&settings, None);
+        
+        let (center, radius) = (point![0.5, -0.25, -3.0], 1.2);
+        let sphere = Aabb::new_sphere(&center, radius, material.clone());
+        let (quad_center, width, depth, quad_normal) = (point![-0.5, 0.5, -2.0], 3.0, 2.0, vector![0.2, 1.0, 0.1]);
+        let quad = Aabb::new_quad(&quad_center, width, depth, &quad_normal, material);
+        let rotation = flat_object_rotation(&quad_normal);
+        
+        let mut random = Random(7);
+        let (mut sphere_hits, mut quad_hits) = (0, 0);
+        for _ in 0..2000 {
+            let origin = Point3::from(random.vector(-5.0, 5.0));
+            let target = Point3::from(random.vector(-2.0, 2.0)) + center.coords * 0.5;
+            let ray = Ray::new(origin, target - origin, &uniforms, PixelPos {x: 0, y: 0}, &spectrum);
+            
+            //the sphere was given by its bounds, the closest distance in front of the origin was hit
+            let sphere_center = (sphere.min + sphere.max.coords) * 0.5;
+            let expected = match ray_sphere_intersection(&ray.origin, &ray.direction, &sphere_center, sphere.max.x - sphere_center.x) {
+                SphereIntersection::NoIntersection => None,
+                SphereIntersection::OneIntersection(t) => (t >= 0.0).then_some(t),
+                SphereIntersection::TwoIntersections(t_1, t_2) => [t_1.min(t_2), t_1.max(t_2)].into_iter().find(|t| *t >= 0.0),
+            };
+            let hit = intersection_shader(&ray, &sphere);
+            assert_eq!(hit.is_some(), expected.is_some(), "The ray from {origin} hit the sphere differently!");
+            if let (Some(t), Some(expected)) = (hit, expected) {
+                assert!(relative_eq(t, expected), "The ray from {origin} hit the sphere at {t} instead of {expected}!");
+                let point = ray.origin + ray.direction * t;
+                let normal = surface_normal(&ray, &sphere, &point);
+                let expected_normal = (point - sphere_center).normalize();
+                assert!((normal - expected_normal).norm() < 1e-4, "The sphere normal changed from {expected_normal} to {normal}!");
+                sphere_hits += 1;
+            }
+            
+            //the quad was hit within its width and depth from the front only
+            let expected = ray_flat_object_intersection(&ray.origin, &ray.direction, &quad_center, &rotation, true)
+                .and_then(|(t, local)| (local.x.abs() <= width / 2.0 && local.z.abs() <= depth / 2.0).then_some(t));
+            let hit = intersection_shader(&ray, &quad);
+            assert_eq!(hit.is_some(), expected.is_some(), "The ray from {origin} hit the quad differently!");
+            if let (Some(t), Some(expected)) = (hit, expected) {
+                assert!(relative_eq(t, expected), "The ray from {origin} hit the quad at {t} instead of {expected}!");
+                let normal = surface_normal(&ray, &quad, &(ray.origin + ray.direction * t));
+                assert!((normal - rotation * Vector3::y()).norm() < 1e-6, "The quad normal changed to {normal}!");
+                quad_hits += 1;
+            }
+        }
+        assert!(sphere_hits > 100 && quad_hits > 100, "Too few rays hit, {sphere_hits} the sphere and {quad_hits} the quad!");
+    }
+}
@@ -25,6 +25,190 @@ pub fn get_sunlight_intensity(wavelength: f32) -> f32 {
     lower_value * fract + upper_value * fract_inv
 }
 
+/// Returns the relative spectral power of the CIE standard illuminant D65 at the given wavelength
+/// in nanometers, normalized to 100 at 560nm. The values are linearly interpolated from the table
+/// [D65_SPECTRUM], outside of its range 0 is returned.
+pub fn get_d65_relative_power(wavelength: f32) -> f32 {
+    if !(D65_SPECTRUM_LOWEST_WAVELENGTH..=D65_SPECTRUM_HIGHEST_WAVELENGTH).contains(&wavelength) {
+        return 0.0;
+    }
+
+    let position = (wavelength - D65_SPECTRUM_LOWEST_WAVELENGTH) / D65_SPECTRUM_STEP;
+    let lower_index = (position as usize).min(D65_SPECTRUM.len() - 1);
+    let upper_index = (lower_index + 1).min(D65_SPECTRUM.len() - 1);
+    let fract = position - lower_index as f32;
+
+    D65_SPECTRUM[lower_index] * (1.0 - fract) + D65_SPECTRUM[upper_index] * fract
+}
+
+const D65_SPECTRUM_LOWEST_WAVELENGTH: f32 = 380.0;
+const D65_SPECTRUM_HIGHEST_WAVELENGTH: f32 = 780.0;
+const D65_SPECTRUM_STEP: f32 = 10.0;
+
+/// The relative spectral power distribution of the CIE standard illuminant D65 from 380nm to 780nm
+/// in 10nm steps, as published in CIE 15:2004.
+const D65_SPECTRUM: [f32; 41] = [
+    49.9755, 54.6482, 82.7549, 91.486, 93.4318, 86.6823, 104.865, 117.008, 117.812, 114.861,
+    115.923, 108.811, 109.354, 107.802, 104.790, 107.689, 104.405, 104.046, 100.000, 96.3342,
+    95.788, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146, 82.2778,
+    78.2842, 69.7213, 71.6091, 74.349, 61.604, 69.8856, 75.087, 63.5927, 46.4182, 66.8054,
+    63.3828,
+];
+
+/// Returns the reflectance at the given wavelength in nanometers of a smooth spectrum which
+/// appears in the given linear RGB color, following Smits' "An RGB to Spectrum Conversion for
+/// Reflectances" (1999). The spectrum is a sum of the white basis spectrum for the smallest
+/// component and of the two basis spectra closest to the color for the rest. The tables are
+/// linearly interpolated between their bin centers and clamped at their ends.
+pub fn get_rgb_uplift_reflectance((r, g, b): (f32, f32, f32), wavelength: f32) -> f32 {
+    let basis = |table: &[f32; 10]| {
+        let position = ((wavelength - RGB_UPLIFT_LOWEST_WAVELENGTH) / RGB_UPLIFT_STEP - 0.5)
+            .clamp(0.0, (table.len() - 1) as f32);
+        let lower_index = (position as usize).min(table.len() - 2);
+        let fract = position - lower_index as f32;
+        table[lower_index] * (1.0 - fract) + table[lower_index + 1] * fract
+    };
+
+    let reflectance = if r <= g && r <= b {
+        r * basis(&RGB_UPLIFT_WHITE) + if g <= b {
+            (g - r) * basis(&RGB_UPLIFT_CYAN) + (b - g) * basis(&RGB_UPLIFT_BLUE)
+        } else {
+            (b - r) * basis(&RGB_UPLIFT_CYAN) + (g - b) * basis(&RGB_UPLIFT_GREEN)
+        }
+    } else if g <= r && g <= b {
+        g * basis(&RGB_UPLIFT_WHITE) + if r <= b {
+            (r - g) * basis(&RGB_UPLIFT_MAGENTA) + (b - r) * basis(&RGB_UPLIFT_BLUE)
+        } else {
+            (b - g) * basis(&RGB_UPLIFT_MAGENTA) + (r - b) * basis(&RGB_UPLIFT_RED)
+        }
+    } else {
+        b * basis(&RGB_UPLIFT_WHITE) + if r <= g {
+            (r - b) * basis(&RGB_UPLIFT_YELLOW) + (g - r) * basis(&RGB_UPLIFT_GREEN)
+        } else {
+            (g - b) * basis(&RGB_UPLIFT_YELLOW) + (r - g) * basis(&RGB_UPLIFT_RED)
+        }
+    };
+    reflectance.clamp(0.0, 1.0)
+}
+
+/// The number of patches of the ColorChecker chart.
+pub const COLOR_CHECKER_PATCH_COUNT: usize = 24;
+
+/// The names of the patches of the ColorChecker chart, row by row from the top left patch.
+pub const COLOR_CHECKER_PATCH_NAMES: [&str; COLOR_CHECKER_PATCH_COUNT] = [
+    "Dark skin", "Light skin", "Blue sky", "Foliage", "Blue flower", "Bluish green",
+    "Orange", "Purplish blue", "Moderate red", "Purple", "Yellow green", "Orange yellow",
+    "Blue", "Green", "Red", "Yellow", "Magenta", "Cyan",
+    "White 9.5", "Neutral 8", "Neutral 6.5", "Neutral 5", "Neutral 3.5", "Black 2",
+];
+
+/// Returns the reflectance of the given patch of the ColorChecker chart at the given wavelength in
+/// nanometers. The values are linearly interpolated from the measured spectra of the table
+/// [COLOR_CHECKER_REFLECTANCE], outside of its range the values at its ends are returned.
+pub fn get_color_checker_reflectance(patch: usize, wavelength: f32) -> f32 {
+    let table = &COLOR_CHECKER_REFLECTANCE[patch];
+    let position = ((wavelength - COLOR_CHECKER_LOWEST_WAVELENGTH) / COLOR_CHECKER_STEP)
+        .clamp(0.0, (table.len() - 1) as f32);
+    let lower_index = (position as usize).min(table.len() - 2);
+    let fract = position - lower_index as f32;
+
+    table[lower_index] * (1.0 - fract) + table[lower_index + 1] * fract
+}
+
+const COLOR_CHECKER_LOWEST_WAVELENGTH: f32 = 380.0;
+const COLOR_CHECKER_STEP: f32 = 10.0;
+
+/// The spectral reflectances of the ColorChecker patches from 380nm to 730nm in 10nm steps, row by
+/// row from the top left patch, as averaged over measurements of many charts and published by
+/// BabelColor.
+#[allow(clippy::approx_constant)]    //measured values which happen to be close to 1/π
+const COLOR_CHECKER_REFLECTANCE: [[f32; 36]; COLOR_CHECKER_PATCH_COUNT] = [
+    //dark skin
+    [0.055, 0.058, 0.061, 0.062, 0.062, 0.062, 0.062, 0.062, 0.062, 0.062, 0.062, 0.063, 0.065, 0.070, 0.076, 0.079, 0.081, 0.084,
+     0.091, 0.103, 0.119, 0.134, 0.143, 0.147, 0.151, 0.158, 0.168, 0.179, 0.188, 0.190, 0.186, 0.181, 0.182, 0.187, 0.196, 0.209],
+    //light skin
+    [0.117, 0.143, 0.175, 0.191, 0.196, 0.199, 0.204, 0.213, 0.228, 0.251, 0.280, 0.309, 0.329, 0.333, 0.315, 0.286, 0.273, 0.276,
+     0.277, 0.289, 0.339, 0.420, 0.488, 0.525, 0.546, 0.562, 0.578, 0.595, 0.612, 0.625, 0.638, 0.656, 0.678, 0.700, 0.717, 0.734],
+    //blue sky
+    [0.130, 0.177, 0.251, 0.306, 0.324, 0.330, 0.333, 0.335, 0.339, 0.338, 0.330, 0.316, 0.295, 0.267, 0.236, 0.206, 0.180, 0.160,
+     0.144, 0.131, 0.122, 0.116, 0.111, 0.107, 0.104, 0.101, 0.099, 0.097, 0.096, 0.096, 0.096, 0.097, 0.099, 0.101, 0.104, 0.109],
+    //foliage
+    [0.051, 0.054, 0.056, 0.057, 0.058, 0.059, 0.060, 0.061, 0.062, 0.063, 0.065, 0.067, 0.075, 0.101, 0.145, 0.178, 0.184, 0.170,
+     0.149, 0.133, 0.122, 0.115, 0.109, 0.105, 0.104, 0.106, 0.109, 0.112, 0.114, 0.114, 0.112, 0.112, 0.115, 0.120, 0.125, 0.130],
+    //blue flower
+    [0.144, 0.198, 0.294, 0.375, 0.408, 0.421, 0.426, 0.426, 0.419, 0.403, 0.379, 0.346, 0.311, 0.281, 0.254, 0.229, 0.214, 0.208,
+     0.202, 0.194, 0.193, 0.200, 0.214, 0.230, 0.241, 0.254, 0.279, 0.313, 0.348, 0.366, 0.372, 0.374, 0.378, 0.384, 0.389, 0.392],
+    //bluish green
+    [0.136, 0.179, 0.247, 0.297, 0.320, 0.337, 0.355, 0.381, 0.419, 0.466, 0.510, 0.546, 0.567, 0.574, 0.569, 0.551, 0.524, 0.488,
+     0.445, 0.400, 0.350, 0.299, 0.252, 0.221, 0.204, 0.196, 0.191, 0.188, 0.191, 0.199, 0.212, 0.223, 0.232, 0.233, 0.229, 0.229],
+    //orange
+    [0.054, 0.054, 0.053, 0.054, 0.054, 0.055, 0.055, 0.055, 0.056, 0.057, 0.058, 0.061, 0.068, 0.089, 0.125, 0.154, 0.174, 0.199,
+     0.248, 0.335, 0.444, 0.538, 0.587, 0.595, 0.591, 0.587, 0.584, 0.584, 0.590, 0.603, 0.620, 0.639, 0.655, 0.663, 0.663, 0.667],
+    //purplish blue
+    [0.122, 0.164, 0.229, 0.286, 0.327, 0.361, 0.388, 0.400, 0.392, 0.362, 0.316, 0.260, 0.209, 0.168, 0.138, 0.117, 0.104, 0.096,
+     0.090, 0.086, 0.084, 0.084, 0.084, 0.084, 0.084, 0.085, 0.090, 0.098, 0.109, 0.123, 0.143, 0.169, 0.205, 0.244, 0.287, 0.332],
+    //moderate red
+    [0.096, 0.115, 0.131, 0.135, 0.133, 0.132, 0.130, 0.128, 0.125, 0.120, 0.115, 0.110, 0.105, 0.100, 0.095, 0.093, 0.092, 0.093,
+     0.096, 0.108, 0.156, 0.265, 0.399, 0.500, 0.556, 0.579, 0.588, 0.591, 0.593, 0.594, 0.598, 0.602, 0.607, 0.609, 0.609, 0.610],
+    //purple
+    [0.092, 0.116, 0.146, 0.169, 0.178, 0.173, 0.158, 0.139, 0.119, 0.101, 0.087, 0.075, 0.066, 0.060, 0.056, 0.053, 0.051, 0.051,
+     0.052, 0.052, 0.051, 0.052, 0.058, 0.073, 0.096, 0.119, 0.141, 0.166, 0.194, 0.227, 0.265, 0.309, 0.355, 0.396, 0.436, 0.478],
+    //yellow green
+    [0.061, 0.061, 0.062, 0.063, 0.064, 0.066, 0.069, 0.075, 0.085, 0.105, 0.139, 0.192, 0.271, 0.376, 0.476, 0.531, 0.549, 0.546,
+     0.528, 0.504, 0.471, 0.428, 0.381, 0.347, 0.327, 0.318, 0.312, 0.310, 0.314, 0.327, 0.345, 0.363, 0.376, 0.381, 0.378, 0.379],
+    //orange yellow
+    [0.063, 0.063, 0.063, 0.064, 0.064, 0.064, 0.065, 0.066, 0.067, 0.068, 0.071, 0.076, 0.087, 0.125, 0.206, 0.305, 0.383, 0.431,
+     0.469, 0.518, 0.568, 0.607, 0.628, 0.637, 0.640, 0.642, 0.645, 0.648, 0.651, 0.653, 0.657, 0.664, 0.673, 0.680, 0.684, 0.688],
+    //blue
+    [0.066, 0.079, 0.102, 0.146, 0.200, 0.244, 0.282, 0.309, 0.308, 0.278, 0.231, 0.178, 0.130, 0.094, 0.070, 0.054, 0.046, 0.042,
+     0.039, 0.038, 0.038, 0.038, 0.038, 0.039, 0.039, 0.040, 0.041, 0.042, 0.044, 0.045, 0.046, 0.046, 0.048, 0.052, 0.057, 0.065],
+    //green
+    [0.052, 0.053, 0.054, 0.055, 0.057, 0.059, 0.061, 0.066, 0.075, 0.093, 0.125, 0.178, 0.246, 0.307, 0.337, 0.334, 0.317, 0.293,
+     0.262, 0.230, 0.198, 0.165, 0.135, 0.115, 0.104, 0.098, 0.094, 0.092, 0.093, 0.097, 0.102, 0.108, 0.113, 0.115, 0.114, 0.114],
+    //red
+    [0.050, 0.049, 0.048, 0.047, 0.047, 0.047, 0.047, 0.047, 0.046, 0.045, 0.044, 0.044, 0.045, 0.046, 0.047, 0.048, 0.049, 0.050,
+     0.054, 0.060, 0.072, 0.104, 0.178, 0.312, 0.467, 0.581, 0.644, 0.675, 0.690, 0.698, 0.706, 0.715, 0.724, 0.730, 0.734, 0.738],
+    //yellow
+    [0.058, 0.054, 0.052, 0.052, 0.053, 0.054, 0.056, 0.059, 0.067, 0.081, 0.107, 0.152, 0.225, 0.336, 0.462, 0.559, 0.616, 0.650,
+     0.672, 0.694, 0.710, 0.723, 0.731, 0.739, 0.746, 0.752, 0.758, 0.764, 0.769, 0.771, 0.776, 0.782, 0.790, 0.796, 0.799, 0.804],
+    //magenta
+    [0.145, 0.195, 0.283, 0.346, 0.362, 0.354, 0.334, 0.306, 0.276, 0.248, 0.218, 0.190, 0.168, 0.149, 0.127, 0.107, 0.100, 0.102,
+     0.104, 0.109, 0.137, 0.200, 0.290, 0.400, 0.516, 0.615, 0.687, 0.732, 0.760, 0.774, 0.783, 0.793, 0.803, 0.812, 0.817, 0.825],
+    //cyan
+    [0.108, 0.141, 0.192, 0.236, 0.261, 0.286, 0.317, 0.353, 0.390, 0.426, 0.446, 0.437, 0.403, 0.350, 0.289, 0.226, 0.163, 0.115,
+     0.085, 0.069, 0.060, 0.055, 0.053, 0.051, 0.050, 0.050, 0.051, 0.052, 0.053, 0.054, 0.055, 0.056, 0.057, 0.058, 0.059, 0.061],
+    //white 9.5
+    [0.189, 0.255, 0.423, 0.660, 0.811, 0.862, 0.877, 0.884, 0.891, 0.896, 0.899, 0.904, 0.907, 0.909, 0.911, 0.910, 0.911, 0.914,
+     0.913, 0.916, 0.915, 0.916, 0.914, 0.915, 0.918, 0.919, 0.921, 0.923, 0.924, 0.922, 0.922, 0.925, 0.927, 0.930, 0.930, 0.933],
+    //neutral 8
+    [0.171, 0.232, 0.365, 0.507, 0.567, 0.583, 0.588, 0.590, 0.591, 0.590, 0.588, 0.588, 0.589, 0.589, 0.591, 0.590, 0.590, 0.590,
+     0.589, 0.591, 0.590, 0.590, 0.587, 0.585, 0.583, 0.580, 0.578, 0.576, 0.574, 0.572, 0.571, 0.569, 0.568, 0.568, 0.566, 0.566],
+    //neutral 6.5
+    [0.144, 0.192, 0.272, 0.331, 0.350, 0.357, 0.361, 0.363, 0.363, 0.361, 0.359, 0.358, 0.358, 0.359, 0.360, 0.360, 0.361, 0.361,
+     0.360, 0.360, 0.361, 0.361, 0.360, 0.358, 0.356, 0.354, 0.352, 0.350, 0.349, 0.348, 0.348, 0.348, 0.349, 0.351, 0.354, 0.356],
+    //neutral 5
+    [0.105, 0.126, 0.148, 0.167, 0.174, 0.176, 0.178, 0.179, 0.180, 0.179, 0.178, 0.178, 0.178, 0.178, 0.179, 0.179, 0.180, 0.180,
+     0.181, 0.181, 0.182, 0.182, 0.181, 0.180, 0.179, 0.178, 0.177, 0.176, 0.175, 0.175, 0.175, 0.174, 0.174, 0.174, 0.174, 0.175],
+    //neutral 3.5
+    [0.068, 0.074, 0.080, 0.084, 0.087, 0.088, 0.089, 0.089, 0.090, 0.090, 0.090, 0.090, 0.090, 0.090, 0.090, 0.090, 0.090, 0.090,
+     0.090, 0.090, 0.090, 0.090, 0.090, 0.090, 0.089, 0.089, 0.088, 0.088, 0.087, 0.087, 0.087, 0.087, 0.087, 0.087, 0.087, 0.087],
+    //black 2
+    [0.031, 0.032, 0.032, 0.033, 0.033, 0.033, 0.033, 0.033, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032,
+     0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.032, 0.033],
+];
+
+const RGB_UPLIFT_LOWEST_WAVELENGTH: f32 = 380.0;
+const RGB_UPLIFT_STEP: f32 = 34.0;
+
+/// The basis spectra of the RGB uplifting, ten bins of 34nm each from 380nm to 720nm.
+const RGB_UPLIFT_WHITE: [f32; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const RGB_UPLIFT_CYAN: [f32; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const RGB_UPLIFT_MAGENTA: [f32; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const RGB_UPLIFT_YELLOW: [f32; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const RGB_UPLIFT_RED: [f32; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const RGB_UPLIFT_GREEN: [f32; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const RGB_UPLIFT_BLUE: [f32; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
 /// The sunlight spectrum based on data found in Solar_Spectrum_Data.txt. <br>
 /// Each element represents the intensity of a given wavelength in W/m^2/nm. The wavelength of 
 /// element n is n + 1 nanometers (element 0 is wavelength 1nm, element 399 is wavelength 400nm). 
@@ -1,11 +1,21 @@
 use std::ops::{AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign};
 use nalgebra::{Matrix3, Vector3};
-use crate::{SpectrumEffectType, UISpectrum};
+use crate::profiler::profile_scope;
+use crate::sensor::SensorResponse;
+use crate::spectral_data;
 
 pub const VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND: f32 = 380.0;
 pub const VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND: f32 = 780.0;
+/// The widest range the spectra can be extended to, from the ultraviolet into the near infrared. 
+/// Outside the visible range the CIE observer and the camera sensors do not respond, such light 
+/// only becomes visible through fluorescence or a false color render. 
+pub const EXTENDED_WAVELENGTH_LOWER_LIMIT: f32 = 200.0;
+pub const EXTENDED_WAVELENGTH_UPPER_LIMIT: f32 = 2500.0;
 
 pub const NBR_OF_SAMPLES_MAX: usize = 128;
+/// The luminous efficacy of monochromatic light at 555nm in lumen per watt, which converts 
+/// radiometric into photometric units. 
+pub const MAXIMUM_LUMINOUS_EFFICACY: f32 = 683.0;
 
 /// A matrix which can be multiplied unto a [vec3](Vector3) to change the color space from XYZ to 
 /// linear sRGB. To get to real sRGB, gamma correction has to be performed. 
@@ -15,8 +25,64 @@ const XYZ_TO_RGB_MATRIX: Matrix3<f32> = Matrix3::new(
     0.0134474, -0.1183897,  1.0154096,
 );
 
+/// The standard observers of the CIE, the color matching functions of the human eye the spectra 
+/// are converted into XYZ with. 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Observer {
+    /// The CIE 1931 2° standard observer, for colors seen under a small angle of view. sRGB and 
+    /// most colorimetric data are defined with it. 
+    Cie1931,
+    /// The CIE 1964 10° supplementary standard observer, for larger colored areas. 
+    #[default]
+    Cie1964,
+}
+
+impl Observer {
+    /// Returns the lookup table of the color matching functions of the observer. 
+    fn table(&self) -> &'static [(f32, f32, f32); 81] {
+        match self {
+            Observer::Cie1931 => &CIE_1931_XYZ_TABLE,
+            Observer::Cie1964 => &WAVELENGTH_TO_XYZ_TABLE,
+        }
+    }
+    
+    /// Computes the color in the XYZ colorspace of a given light wavelength in nanometers as seen 
+    /// by the observer, see [wavelength_to_XYZ]. 
+    pub fn wavelength_to_xyz(&self, wavelength: f32) -> (f32, f32, f32) {
+        //filter out non-visible light, including NaN
+        if !(VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND..=VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND).contains(&wavelength) {
+            return (0.0, 0.0, 0.0);
+        }
+        let table = self.table();
+
+        //wavelength can be immediately cast to table lookup
+        if wavelength % 5.0 == 0.0 {
+            let index = (wavelength as usize - 380) / 5;
+            return table[index];
+        }
+
+        //linear interpolation between two closest values
+        let w_adjusted = (wavelength - 380.0) / 5.0;
+        let index_lower = w_adjusted as usize;
+        let index_upper = index_lower + 1;
+        
+        let value_lower = table[index_lower];
+        let value_upper = table[index_upper];
+        let fract = w_adjusted.fract();
+        let fract_inv = 1.0 - fract;
+
+        (
+            value_lower.0 * fract + value_upper.0 * fract_inv,
+            value_lower.1 * fract + value_upper.1 * fract_inv,
+            value_lower.2 * fract + value_upper.2 * fract_inv,
+        )
+    }
+}
+
 /// The Spectrum is a datatype designed to hold a spectrum of visible and non-visible wavelengths, 
-/// together with their spectral radiance's. It supports various methods of creation to emulate 
+/// together with one value per wavelength. The unit of the values depends on what the spectrum 
+/// describes, the shaders wrap it into the radiometric quantities of the radiometry module to 
+/// keep them apart. It supports various methods of creation to emulate 
 /// realistic light sources, as well as allows typical mathematical operations to be performed on 
 /// it, allowing for easy use in the shaders. It essentially replaces the r, g, b f32 triplet in 
 /// closest-hit-shader calculations. <br>
@@ -121,6 +187,43 @@ impl Spectrum {
         Self::new_from_list(&arr, lowest_wavelength, highest_wavelength, nbr_of_samples)
     }
     
+    /// Creates a new Spectrum with the given range and number of samples from a list of 
+    /// (wavelength, intensity) samples with ascending wavelengths, such as measured data. Between 
+    /// the given samples the intensity is linearly interpolated, outside of them it is 0. 
+    pub fn new_from_samples(samples: &[(f32, f32)], lowest_wavelength: f32, highest_wavelength: f32, nbr_of_samples: usize) -> Self {
+        let mut arr = [0f32; NBR_OF_SAMPLES_MAX];
+        let step = (highest_wavelength - lowest_wavelength) / (nbr_of_samples - 1) as f32;
+        for (i, elem) in arr[0..nbr_of_samples].iter_mut().enumerate() {
+            *elem = interpolate_samples(samples, lowest_wavelength + step * i as f32);
+        }
+
+        Self::new_from_list(&arr, lowest_wavelength, highest_wavelength, nbr_of_samples)
+    }
+    
+    /// Creates a new reflective Spectrum which appears in the given linear RGB color, see
+    /// [spectral_data::get_rgb_uplift_reflectance]. Components are expected in range \[0; 1].
+    pub fn new_from_rgb_reflectance(rgb: (f32, f32, f32), lowest_wavelength: f32, highest_wavelength: f32, nbr_of_samples: usize) -> Self {
+        let mut arr = [0f32; NBR_OF_SAMPLES_MAX];
+        let step = (highest_wavelength - lowest_wavelength) / (nbr_of_samples - 1) as f32;
+        for (i, elem) in arr[0..nbr_of_samples].iter_mut().enumerate() {
+            *elem = spectral_data::get_rgb_uplift_reflectance(rgb, lowest_wavelength + step * i as f32);
+        }
+
+        Self::new_from_list(&arr, lowest_wavelength, highest_wavelength, nbr_of_samples)
+    }
+    
+    /// Creates a new reflective Spectrum of the given patch of the ColorChecker chart, see 
+    /// [spectral_data::get_color_checker_reflectance]. The patches are numbered from 0 row by row. 
+    pub fn new_color_checker_patch(patch: usize, lowest_wavelength: f32, highest_wavelength: f32, nbr_of_samples: usize) -> Self {
+        let mut arr = [0f32; NBR_OF_SAMPLES_MAX];
+        let step = (highest_wavelength - lowest_wavelength) / (nbr_of_samples - 1) as f32;
+        for (i, elem) in arr[0..nbr_of_samples].iter_mut().enumerate() {
+            *elem = spectral_data::get_color_checker_reflectance(patch, lowest_wavelength + step * i as f32);
+        }
+
+        Self::new_from_list(&arr, lowest_wavelength, highest_wavelength, nbr_of_samples)
+    }
+    
     pub fn new_normalized_white(lowest_wavelength: f32, highest_wavelength: f32, nbr_of_samples: usize) -> Self {
         let mut unnormalized_white = Spectrum::new_sunlight_spectrum(
             lowest_wavelength,
@@ -220,6 +323,16 @@ impl Spectrum {
         }
     }
     
+    /// Sets every sample outside the index range \[first; last) to zero. Used to restrict a 
+    /// spectrum to a band of wavelengths.
+    pub fn keep_only_sample_range(&mut self, first: usize, last: usize) {
+        for (i, intensity) in self.intensities[0..self.nbr_of_samples].iter_mut().enumerate() {
+            if i < first || i >= last {
+                *intensity = 0.0;
+            }
+        }
+    }
+    
     /// Modifies the inner intensities to each be at most 1.0. 
     pub fn min1(&mut self) {
         assert_eq!(self.nbr_of_samples % 8, 0);
@@ -236,15 +349,37 @@ impl Spectrum {
     /// and then convert this to RGB. RGB is taken to be Adobes sRGB. <br>
     /// See https://stackoverflow.com/a/51639077 (saved website can be seen in ../research_materials )
     pub fn get_rgb_early(&self) -> (f32, f32, f32) {
+        self.get_rgb_by_observer(Observer::default())
+    }
+    
+    /// Works like [Spectrum::get_rgb_early], but converts the spectrum into XYZ with the given 
+    /// standard observer. 
+    pub fn get_rgb_by_observer(&self, observer: Observer) -> (f32, f32, f32) {
+        profile_scope!(SpectrumOps);
+        let rgb: Vector3<f32> = XYZ_TO_RGB_MATRIX * self.get_xyz_by_observer(observer);
+        //gamma_correction(&mut rgb);
+        rgb.in2()
+    }
+
+    /// Takes the spectrum and converts it into the XYZ color space via the CIE lookup table of the 
+    /// default observer. 
+    pub fn get_xyz(&self) -> Vector3<f32> {
+        self.get_xyz_by_observer(Observer::default())
+    }
+    
+    /// Takes the spectrum and converts it into the XYZ color space via the lookup table of the 
+    /// given standard observer. 
+    pub fn get_xyz_by_observer(&self, observer: Observer) -> Vector3<f32> {
         match self.spectrum_type {
             SpectrumType::EquidistantSamples(min, max) => {
                 let mut xyz_values: Vec<Vector3<f32>> = Vec::with_capacity(self.nbr_of_samples);
                 let sample_distance = (max - min) / (self.nbr_of_samples - 1) as f32;
+                let weight = visible_sample_weight(sample_distance);
             
                 let mut wavelength = min;
                 while wavelength <= max {
-                    let xyz = wavelength_to_XYZ(wavelength).in2();
-                    xyz_values.push(xyz / self.nbr_of_samples as f32);
+                    let xyz = observer.wavelength_to_xyz(wavelength).in2();
+                    xyz_values.push(xyz * weight);
                     wavelength += sample_distance;
                 }
             
@@ -252,9 +387,50 @@ impl Spectrum {
                     *xyz *= self.intensities[i];
                 }
             
-                let fin = xyz_values.into_iter().fold(Vector3::new(0.0, 0.0, 0.0), |acc, x| acc + x);
-                let rgb: Vector3<f32> = XYZ_TO_RGB_MATRIX * fin;
-                //gamma_correction(&mut rgb);
+                xyz_values.into_iter().fold(Vector3::new(0.0, 0.0, 0.0), |acc, x| acc + x)
+            }
+        }
+    }
+
+    /// Returns the chromaticity coordinates (x, y) of the spectrum as seen by the CIE observer, 
+    /// or None if the spectrum is not visible at all. 
+    pub fn get_xy_chromaticity(&self) -> Option<(f32, f32)> {
+        let xyz = self.get_xyz();
+        let sum = xyz.x + xyz.y + xyz.z;
+        if sum <= 0.0 {
+            return None;
+        }
+        Some((xyz.x / sum, xyz.y / sum))
+    }
+    
+    /// Estimates the correlated color temperature in kelvin, the temperature of the black body 
+    /// whose color comes closest to the color of the spectrum. Returns None if the spectrum is not 
+    /// visible at all. The approximation is only meaningful for light sources of roughly white 
+    /// color, between about 2000 K and 12500 K. <br>
+    /// Correlated Color Temperature as an Explicit Function of Chromaticity Coordinates, 
+    /// McCamy, 1992
+    pub fn get_correlated_color_temperature(&self) -> Option<f32> {
+        let (x, y) = self.get_xy_chromaticity()?;
+        let n = (x - 0.3320) / (0.1858 - y);
+        Some(449.0 * n.powi(3) + 3525.0 * n.powi(2) + 6823.3 * n + 5520.33)
+    }
+    
+    /// Takes the spectrum and converts it into RGB values as seen by the given camera sensor. <br>
+    /// Works like [Spectrum::get_rgb_early], but integrates the spectrum against the sensitivity
+    /// curves of the sensor instead of the CIE observer. The resulting raw camera RGB is used
+    /// directly as linear RGB, no color space conversion is performed.
+    pub fn get_rgb_by_sensor(&self, sensor: &SensorResponse) -> (f32, f32, f32) {
+        profile_scope!(SpectrumOps);
+        match self.spectrum_type {
+            SpectrumType::EquidistantSamples(min, max) => {
+                let sample_distance = (max - min) / (self.nbr_of_samples - 1) as f32;
+                let weight = visible_sample_weight(sample_distance);
+
+                let mut rgb = Vector3::new(0.0, 0.0, 0.0);
+                for (i, intensity) in self.intensities[0..self.nbr_of_samples].iter().enumerate() {
+                    let response: Vector3<f32> = sensor.response_at(min + i as f32 * sample_distance).in2();
+                    rgb += response * (intensity * weight);
+                }
                 rgb.in2()
             }
         }
@@ -273,6 +449,11 @@ impl Spectrum {
     pub fn get_nbr_of_samples(&self) -> usize {
         self.nbr_of_samples
     }
+
+    /// Returns the largest intensity among all samples.
+    pub fn get_max(&self) -> f32 {
+        self.intensities[0..self.nbr_of_samples].iter().fold(f32::NEG_INFINITY, |acc, elem| acc.max(*elem))
+    }
     
     /// Takes the given bounds as the new lower and upper bound, adjusting the samples accordingly. 
     /// //TODO if sampling out of old bounds, nearest neighbor ?
@@ -364,6 +545,17 @@ impl Spectrum {
             .fold(0f32, |acc, elem| acc + elem) 
     }
     
+    /// Calculates the photometric counterpart of the spectrum by weighting it with the luminosity
+    /// function of the CIE observer. Depending on what the spectrum describes, this is the 
+    /// illuminance in lux (lm/m^2) for spectral irradiance, the luminous intensity in candela for 
+    /// spectral intensity (W/sr/nm) or the luminance in cd/m^2 for spectral radiance. 
+    pub fn get_luminous_value(&self) -> f32 {
+        let iter = self.iter();
+        let step = iter.step;
+        iter.map(|(wavelength, intensity)| intensity * wavelength_to_XYZ(wavelength).1 * step)
+            .fold(0f32, |acc, elem| acc + elem) * MAXIMUM_LUMINOUS_EFFICACY
+    }
+    
     /// Normalizes the given spectrum. <br>
     /// The definition of normalizing a spectrum is: Adjusting its values in a way that the overall 
     /// shape of the distribution remains the same, but the resulting RGB values will be in range 
@@ -378,6 +570,7 @@ impl Spectrum {
 
 impl AddAssign<&Spectrum> for Spectrum {
     fn add_assign(&mut self, rhs: &Spectrum) {  //TODO using assert_unchecked for arithmetic saves about 2%
+        profile_scope!(SpectrumOps);
         assert_eq!(self.nbr_of_samples, rhs.nbr_of_samples);
         assert_eq!(self.nbr_of_samples % 8, 0);
 
@@ -420,6 +613,7 @@ impl Mul<&Spectrum> for &Spectrum {
     type Output = Spectrum;
     
     fn mul(self, rhs: &Spectrum) -> Self::Output {
+        profile_scope!(SpectrumOps);
         assert_eq!(self.nbr_of_samples, rhs.nbr_of_samples);
         assert_eq!(self.nbr_of_samples % 8, 0);
 
@@ -483,16 +677,6 @@ impl DivAssign<f32> for &mut Spectrum {
     }
 }
 
-impl From<&UISpectrum> for Spectrum {
-    fn from(value: &UISpectrum) -> Self {
-        let mut inner_spectrum = value.spectrum;
-        if value.spectrum_effect_type == SpectrumEffectType::Reflective {
-            inner_spectrum.min1();
-        }
-        inner_spectrum
-    }
-}
-
 pub struct SpectrumIterator<'a> {
     spectrum: &'a Spectrum,
     index: usize,
@@ -563,9 +747,11 @@ const SPEED_OF_LIGHT: f64 = 299_792_458_f64; // m/s
 const PLANCK_CONSTANT: f64 = 6.62607015e-34;
 const BOLTZMANN_CONSTANT: f64 = 1.380649e-23;
 
-///                 2hc^2           1       
-///     B_l(l, T) = ----- * ------------------
-///                  l^5    e^(hc/l*T*k_B) - 1 
+/// ```text
+///             2hc^2           1       
+/// B_l(l, T) = ----- * ------------------
+///              l^5    e^(hc/l*T*k_B) - 1 
+/// ```
 /// l = lambda = Wavelength                         <br>
 /// h = Planck constant                             <br>
 /// c = speed of light in a vacuum                  <br>
@@ -593,6 +779,25 @@ fn black_body_radiation(wavelength_nm: f64, temperature_k: f64) -> f64 {
     (hc22 / l5) * (1.0 / big_denominator)  * 1e-9   //*1e-9 = to /nanometer
 }
 
+/// Calculates the multiplier of a black body spectrum (see [Spectrum::new_temperature_spectrum]) 
+/// with the given temperature, range and number of samples, such that a point light emitting it 
+/// produces the given illuminance in lux on a surface facing it at the given distance in meters 
+/// (scene units). Returns 0 if the spectrum has no visible part. <br>
+/// A point light emitting the spectrum as its intensity I illuminates the surface with I / d^2, 
+/// therefore the multiplier is the illuminance * d^2 over the luminous intensity of the 
+/// unscaled spectrum. 
+pub fn temperature_multiplier_for_illuminance(lowest_wavelength: f32, highest_wavelength: f32, 
+                                              temp_in_kelvin: f32, nbr_of_samples: usize, 
+                                              illuminance: f32, distance: f32) -> f32 {
+    let unscaled = Spectrum::new_temperature_spectrum(
+        lowest_wavelength, highest_wavelength, temp_in_kelvin, nbr_of_samples, 1.0);
+    let luminous_intensity = unscaled.get_luminous_value();
+    if luminous_intensity <= 0.0 {
+        return 0.0;
+    }
+    illuminance * distance * distance / luminous_intensity
+}
+
 /// Takes a slice, halves its size, rounds the length up to a multiple of 8 and then linearly 
 /// interpolates each value for the new list with the calculated length. 
 fn collapse_list_to_half(list: &[f32]) -> Vec<f32> {
@@ -637,6 +842,32 @@ fn linear_interpolate_halved(original_values: &[f32], target_length: usize) -> V
     result
 }
 
+/// Linearly interpolates the given ascending (wavelength, intensity) samples at the given 
+/// wavelength. Returns 0 for wavelengths outside of the samples. 
+fn interpolate_samples(samples: &[(f32, f32)], wavelength: f32) -> f32 {
+    let upper = samples.partition_point(|s| s.0 < wavelength);
+    if upper == samples.len() || (upper == 0 && samples[0].0 > wavelength) {
+        return 0.0;
+    }
+    if samples[upper].0 == wavelength {
+        return samples[upper].1;
+    }
+
+    let (lower, upper) = (samples[upper - 1], samples[upper]);
+    let fract = (wavelength - lower.0) / (upper.0 - lower.0);
+    lower.1 + (upper.1 - lower.1) * fract
+}
+
+/// Returns the weight of a sample with the given distance to its neighbours when adding up the 
+/// response of the CIE observer or a sensor to a spectrum. Every sample stands for a band as wide 
+/// as its distance, so the visible range carries the same total weight however far the spectrum 
+/// extends beyond it. For a spectrum over exactly the visible range this is 1 over the number of 
+/// samples. 
+fn visible_sample_weight(sample_distance: f32) -> f32 {
+    let visible_width = VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND - VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND;
+    sample_distance / (visible_width + sample_distance)
+}
+
 /// Takes a slice and pads it with zeroes to an array of 128. If the slice is longer, truncates 
 /// instead. 
 fn slice_to_array_128(input: &[f32]) -> [f32; NBR_OF_SAMPLES_MAX] {
@@ -646,45 +877,38 @@ fn slice_to_array_128(input: &[f32]) -> [f32; NBR_OF_SAMPLES_MAX] {
     output
 }
 
-/// Computes the color in the XYZ colorspace of a given light wavelength. The wavelength unit must 
-/// be nanometers. If no precise sample exists for the given wavelength, it is instead linearly
-/// interpolated. 
-//magical values here come from const WAVELENGTH_TO_XYZ_TABLE
+/// Computes the color in the XYZ colorspace of a given light wavelength as seen by the default 
+/// observer. The wavelength unit must be nanometers. If no precise sample exists for the given 
+/// wavelength, it is instead linearly interpolated. Ultraviolet and infrared wavelengths outside 
+/// the table are invisible, (0, 0, 0). 
 #[allow(non_snake_case)]    //allowing non snake case because color space XYZ != color space xyz
 fn wavelength_to_XYZ(wavelength: f32) -> (f32, f32, f32) {
-    //filter out non-visible light
-    if !(380.0..=780.0).contains(&wavelength) {
-        return (0.0, 0.0, 0.0);
-    }
-
-    //wavelength can be immediately cast to table lookup
-    if wavelength % 5.0 == 0.0 {
-        let index = (wavelength as usize - 380) / 5;
-        return WAVELENGTH_TO_XYZ_TABLE[index];
-    }
+    Observer::default().wavelength_to_xyz(wavelength)
+}
 
-    //linear interpolation between two closest values
-    let w_adjusted = (wavelength - 380.0) / 5.0;
-    let index_lower = w_adjusted as usize;
-    let index_upper = index_lower + 1;
-    
-    let value_lower = WAVELENGTH_TO_XYZ_TABLE[index_lower];
-    let value_upper = WAVELENGTH_TO_XYZ_TABLE[index_upper];
-    let fract = w_adjusted.fract();
-    let fract_inv = 1.0 - fract;
 
-    (
-        value_lower.0 * fract + value_upper.0 * fract_inv,
-        value_lower.1 * fract + value_upper.1 * fract_inv,
-        value_lower.2 * fract + value_upper.2 * fract_inv,
-    )
+/// Returns the chromaticity coordinates (x, y) of every wavelength in the CIE lookup table, which
+/// together outline the spectral locus, the border of all visible colors. 
+pub fn spectral_locus_xy() -> Vec<(f32, f32)> {
+    WAVELENGTH_TO_XYZ_TABLE.iter()
+        .filter(|xyz| xyz.0 + xyz.1 + xyz.2 > 0.0)
+        .map(|xyz| {
+            let sum = xyz.0 + xyz.1 + xyz.2;
+            (xyz.0 / sum, xyz.1 / sum)
+        }).collect()
 }
 
+/// Returns the sum of the luminance (Y) column of the CIE lookup table. Used to bring other color
+/// matching functions, such as a [SensorResponse], to a comparable brightness.
+pub fn cie_luminance_table_sum() -> f32 {
+    WAVELENGTH_TO_XYZ_TABLE.iter().map(|xyz| xyz.1).sum()
+}
 
-/// A lookup table to convert color in terms of a light wavelength to the XYZ color space. The table
-/// contains samples at 5-nanometer intervals. The smallest available sample is 380 nm, and the
-/// largest available sample is 780 nm. Anything beyond can be taken as (0, 0, 0).
-//CHANGES HERE MUST BE REFLECTED IN fn wavelength_to_XYZ !
+/// A lookup table to convert color in terms of a light wavelength to the XYZ color space, the color
+/// matching functions of the CIE 1964 10° observer. The table contains samples at 5-nanometer 
+/// intervals. The smallest available sample is 380 nm, and the largest available sample is 780 nm. 
+/// Anything beyond can be taken as (0, 0, 0).
+//CHANGES HERE MUST BE REFLECTED IN fn Observer::wavelength_to_xyz !
 const WAVELENGTH_TO_XYZ_TABLE: [(f32, f32, f32); 81] = [
     (0.00016, 0.000017, 0.000705),      //380nm
     (0.000662, 0.000072, 0.002928),     //385nm
@@ -769,6 +993,92 @@ const WAVELENGTH_TO_XYZ_TABLE: [(f32, f32, f32); 81] = [
     (0.000033, 0.000013, 0.000000),     //780nm
 ];
 
+/// The color matching functions of the CIE 1931 2° observer, sampled like the 
+/// [WAVELENGTH_TO_XYZ_TABLE]. 
+const CIE_1931_XYZ_TABLE: [(f32, f32, f32); 81] = [
+    (0.001368, 0.000039, 0.006450),     //380nm
+    (0.002236, 0.000064, 0.010550),     //385nm
+    (0.004243, 0.000120, 0.020050),     //390nm
+    (0.007650, 0.000217, 0.036210),     //...
+    (0.014310, 0.000396, 0.067850),     //400nm
+    (0.023190, 0.000640, 0.110200),
+    (0.043510, 0.001210, 0.207400),
+    (0.077630, 0.002180, 0.371300),
+    (0.134380, 0.004000, 0.645600),
+    (0.214770, 0.007300, 1.03905),
+    (0.283900, 0.011600, 1.3856),
+    (0.328500, 0.016840, 1.62296),
+    (0.348280, 0.023000, 1.74706),
+    (0.348060, 0.029800, 1.7826),
+    (0.336200, 0.038000, 1.77211),     //450nm
+    (0.318700, 0.048000, 1.7441),
+    (0.290800, 0.060000, 1.6692),
+    (0.251100, 0.073900, 1.5281),
+    (0.195360, 0.090980, 1.28764),
+    (0.142100, 0.112600, 1.0419),
+    (0.095640, 0.139020, 0.812950),
+    (0.057950, 0.169300, 0.616200),
+    (0.032010, 0.208020, 0.465180),
+    (0.014700, 0.258600, 0.353300),
+    (0.004900, 0.323000, 0.272000),     //500nm
+    (0.002400, 0.407300, 0.212300),
+    (0.009300, 0.503000, 0.158200),
+    (0.029100, 0.608200, 0.111700),
+    (0.063270, 0.710000, 0.078250),
+    (0.109600, 0.793200, 0.057250),
+    (0.165500, 0.862000, 0.042160),
+    (0.225750, 0.914850, 0.029840),
+    (0.290400, 0.954000, 0.020300),
+    (0.359700, 0.980300, 0.013400),
+    (0.433450, 0.994950, 0.008750),     //550nm
+    (0.512050, 1.000000, 0.005750),
+    (0.594500, 0.995000, 0.003900),
+    (0.678400, 0.978600, 0.002750),
+    (0.762100, 0.952000, 0.002100),
+    (0.842500, 0.915400, 0.001800),
+    (0.916300, 0.870000, 0.001650),
+    (0.978600, 0.816300, 0.001400),
+    (1.0263, 0.757000, 0.001100),
+    (1.0567, 0.694900, 0.001000),
+    (1.0622, 0.631000, 0.000800),     //600nm
+    (1.0456, 0.566800, 0.000600),
+    (1.0026, 0.503000, 0.000340),
+    (0.938400, 0.441200, 0.000240),
+    (0.854450, 0.381000, 0.000190),
+    (0.751400, 0.321000, 0.000100),
+    (0.642400, 0.265000, 0.000050),
+    (0.541900, 0.217000, 0.000030),
+    (0.447900, 0.175000, 0.000020),
+    (0.360800, 0.138200, 0.000010),
+    (0.283500, 0.107000, 0.000000),     //650nm
+    (0.218700, 0.081600, 0.000000),
+    (0.164900, 0.061000, 0.000000),
+    (0.121200, 0.044580, 0.000000),
+    (0.087400, 0.032000, 0.000000),
+    (0.063600, 0.023200, 0.000000),
+    (0.046770, 0.017000, 0.000000),
+    (0.032900, 0.011920, 0.000000),
+    (0.022700, 0.008210, 0.000000),
+    (0.015840, 0.005723, 0.000000),
+    (0.011359, 0.004102, 0.000000),     //700nm
+    (0.008111, 0.002929, 0.000000),
+    (0.005790, 0.002091, 0.000000),
+    (0.004109, 0.001484, 0.000000),
+    (0.002899, 0.001047, 0.000000),
+    (0.002049, 0.000740, 0.000000),
+    (0.001440, 0.000520, 0.000000),
+    (0.001000, 0.000361, 0.000000),
+    (0.000690, 0.000249, 0.000000),
+    (0.000476, 0.000172, 0.000000),
+    (0.000332, 0.000120, 0.000000),     //750nm
+    (0.000235, 0.000085, 0.000000),
+    (0.000166, 0.000060, 0.000000),
+    (0.000117, 0.000042, 0.000000),
+    (0.000083, 0.000030, 0.000000),
+    (0.000059, 0.000021, 0.000000),
+    (0.000042, 0.000015, 0.000000),     //780nm
+];
+
 #[cfg(test)]
 mod test {
     use crate::shader::F32_DELTA;
@@ -828,6 +1138,113 @@ mod test {
         
         //TODO more useful tests as soon as the current one passes :,(  
     }
+
+    #[test]
+    fn test_spectrum_to_rgb_by_sensor() {
+        //the sensor is white balanced to equal energy, so a flat spectrum has to be greyscale
+        let sensor = SensorResponse::new_typical_cmos();
+        let flat = Spectrum::new_singular_reflectance_factor(
+            VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+            VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+            128,
+            1.0,
+        );
+        let (r, g, b) = flat.get_rgb_by_sensor(&sensor);
+        assert!((1.0 - r / g).abs() < 0.02, "Red ({r}) and green ({g}) too different to be greyscale!");
+        assert!((1.0 - b / g).abs() < 0.02, "Blue ({b}) and green ({g}) too different to be greyscale!");
+
+        //a narrow band at 450nm is mostly seen by the blue channel
+        let mut blue_light = Spectrum::new_equal_size_empty_spectrum(&flat);
+        let wavelengths = blue_light.get_wavelengths();
+        let index = wavelengths.iter().position(|w| *w >= 450.0).unwrap();
+        blue_light[index] = 1.0;
+        let (r, g, b) = blue_light.get_rgb_by_sensor(&sensor);
+        assert!(b > g && g > r, "Expected blue ({b}) > green ({g}) > red ({r}) for 450nm light!");
+    }
+    
+    #[test]
+    fn test_extended_range_keeps_visible_color() {
+        //light outside the visible range must neither add to nor dilute the visible color
+        let visible = Spectrum::new_singular_reflectance_factor(
+            VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+            VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+            64,
+            1.0,
+        );
+        let extended = Spectrum::new_singular_reflectance_factor(300.0, 1000.0, 128, 1.0);
+        let sensor = SensorResponse::new_typical_cmos();
+        for (expected, actual) in [(visible.get_rgb_early(), extended.get_rgb_early()), 
+                                   (visible.get_rgb_by_sensor(&sensor), extended.get_rgb_by_sensor(&sensor))] {
+            let close_enough = |e: f32, a: f32| (1.0 - a / e).abs() < 0.05;
+            assert!(close_enough(expected.0, actual.0) && close_enough(expected.1, actual.1) 
+                        && close_enough(expected.2, actual.2), 
+                    "Extending the range changed the color from {expected:?} to {actual:?}!");
+        }
+    }
+    
+    #[test]
+    fn test_observers_equal_energy_white() {
+        //both observers are normalized so that equal energy light is seen as x = y = 1/3
+        let flat = Spectrum::new_singular_reflectance_factor(
+            VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+            VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+            128,
+            1.0,
+        );
+        for observer in [Observer::Cie1931, Observer::Cie1964] {
+            let xyz = flat.get_xyz_by_observer(observer);
+            let sum = xyz.x + xyz.y + xyz.z;
+            let (x, y) = (xyz.x / sum, xyz.y / sum);
+            assert!((x - 1.0 / 3.0).abs() < 0.002 && (y - 1.0 / 3.0).abs() < 0.002, 
+                    "Equal energy white is at ({x}, {y}) for the {observer:?} observer!");
+        }
+        
+        //the two observers see a narrow band differently
+        let mut cyan = Spectrum::new_equal_size_empty_spectrum(&flat);
+        let index = cyan.get_wavelengths().iter().position(|w| *w >= 490.0).unwrap();
+        cyan[index] = 1.0;
+        assert_ne!(cyan.get_rgb_by_observer(Observer::Cie1931), cyan.get_rgb_by_observer(Observer::Cie1964));
+    }
+    
+    #[test]
+    fn test_spectrum_from_samples() {
+        let samples = [(400.0, 1.0), (500.0, 3.0), (600.0, 2.0)];
+        let spectrum = Spectrum::new_from_samples(&samples, 300.0, 700.0, 16);
+        for (wavelength, intensity) in spectrum.iter() {
+            let expected = if !(400.0..=600.0).contains(&wavelength) {
+                0.0
+            } else if wavelength <= 500.0 {
+                1.0 + (wavelength - 400.0) / 100.0 * 2.0
+            } else {
+                3.0 - (wavelength - 500.0) / 100.0
+            };
+            assert!((intensity - expected).abs() < F32_DELTA, 
+                    "Expected {expected} at {wavelength}nm, got {intensity}!");
+        }
+    }
+    
+    #[test]
+    fn test_luminous_value() {
+        //a flat spectrum of 1 W/nm corresponds to 683 lumen times the integral of the luminosity
+        //function, the table is sampled every 5nm
+        let flat = Spectrum::new_singular_reflectance_factor(
+            VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND,
+            VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND,
+            128,
+            1.0,
+        );
+        let expected = 683.0 * cie_luminance_table_sum() * 5.0;
+        let luminous = flat.get_luminous_value();
+        assert!((luminous / expected - 1.0).abs() < 0.02, "Expected about {expected}, got {luminous}!");
+        
+        //a black body scaled by the calculated multiplier has to produce the requested illuminance
+        let multiplier = temperature_multiplier_for_illuminance(
+            VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND, VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND, 3000.0, 64, 500.0, 2.0);
+        let light = Spectrum::new_temperature_spectrum(
+            VISIBLE_LIGHT_WAVELENGTH_LOWER_BOUND, VISIBLE_LIGHT_WAVELENGTH_UPPER_BOUND, 3000.0, 64, multiplier);
+        let illuminance = light.get_luminous_value() / (2.0 * 2.0);
+        assert!((illuminance / 500.0 - 1.0).abs() < 0.001, "Expected 500 lux, got {illuminance}!");
+    }
     
     #[test]
     fn test_black_body_calculation() {
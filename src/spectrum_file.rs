@@ -0,0 +1,148 @@
+use std::path::Path;
+use crate::spectrum::{Spectrum, NBR_OF_SAMPLES_MAX};
+
+/// The first line of every file in the internal spectrum format.
+const SPECTRUM_FILE_HEADER: &str = "# spectral-raytracer spectrum";
+
+/// Reads a spectrum from the given file. Files ending in .csv are read as CSV (see
+/// [parse_csv]), every other file in the internal format (see [parse_internal]). When reading a
+/// CSV file, the samples are resampled to the given number of samples, or to as many samples as
+/// the file holds if None is given.
+pub fn read(path: &Path, nbr_of_samples: Option<usize>) -> Result<Spectrum, SpectrumFileError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SpectrumFileError {error: format!("Could not read {}: {e}", path.display())})?;
+
+    if is_csv(path) {
+        parse_csv(&content, nbr_of_samples)
+    } else {
+        parse_internal(&content)
+    }
+}
+
+/// Writes the spectrum to the given file. Files ending in .csv are written as CSV, every other
+/// file in the internal format.
+pub fn write(path: &Path, spectrum: &Spectrum) -> Result<(), SpectrumFileError> {
+    let content = if is_csv(path) { to_csv(spectrum) } else { to_internal(spectrum) };
+    std::fs::write(path, content)
+        .map_err(|e| SpectrumFileError {error: format!("Could not write {}: {e}", path.display())})
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+/// Parses a spectrum from CSV. Each line must hold two comma separated values: the wavelength in
+/// nanometers and the intensity. The wavelengths must be strictly ascending but do not have to be
+/// equidistant. Empty lines, lines starting with '#' and a leading header line are ignored. <br>
+/// The resulting spectrum covers the range of the wavelengths in the file and holds the given
+/// number of samples. If None is given, the number of lines is used, rounded up to a multiple of
+/// 8 and limited to [NBR_OF_SAMPLES_MAX].
+pub fn parse_csv(content: &str, nbr_of_samples: Option<usize>) -> Result<Spectrum, SpectrumFileError> {
+    let mut samples = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let values: Result<Vec<f32>, _> = line.split(',').map(|v| v.trim().parse::<f32>()).collect();
+        match values {
+            Ok(values) if values.len() == 2 => samples.push((values[0], values[1])),
+            Err(_) if samples.is_empty() => continue,    //header line
+            _ => return Err(SpectrumFileError {
+                error: format!("Line {} is not of the form 'wavelength,intensity'!", line_number + 1)
+            }),
+        }
+    }
+
+    if samples.len() < 2 {
+        return Err(SpectrumFileError {error: "At least two samples are required!".to_string()});
+    }
+    if samples.windows(2).any(|w| w[0].0 >= w[1].0) {
+        return Err(SpectrumFileError {error: "The wavelengths must be strictly ascending!".to_string()});
+    }
+
+    let nbr_of_samples = match nbr_of_samples {
+        Some(nbr_of_samples) => check_nbr_of_samples(nbr_of_samples)?,
+        None => samples.len().div_ceil(8).clamp(1, NBR_OF_SAMPLES_MAX / 8) * 8,
+    };
+    let lowest = samples[0].0;
+    let highest = samples[samples.len() - 1].0;
+    Ok(Spectrum::new_from_samples(&samples, lowest, highest, nbr_of_samples))
+}
+
+/// Writes the spectrum as CSV with a header line followed by one 'wavelength,intensity' line per
+/// sample.
+pub fn to_csv(spectrum: &Spectrum) -> String {
+    let mut csv = String::from("wavelength,intensity\n");
+    for (wavelength, intensity) in spectrum.iter() {
+        csv += &format!("{wavelength},{intensity}\n");
+    }
+    csv
+}
+
+/// Parses a spectrum from the internal format. After the header line, the format holds a line
+/// with the lowest wavelength, the highest wavelength and the number of samples, separated by
+/// spaces. It is followed by one intensity per line. The samples are equidistant, so that the
+/// spectrum is restored exactly.
+pub fn parse_internal(content: &str) -> Result<Spectrum, SpectrumFileError> {
+    let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next() != Some(SPECTRUM_FILE_HEADER) {
+        return Err(SpectrumFileError {error: "The file is not a spectrum file, the header is missing!".to_string()});
+    }
+
+    let range_error = || SpectrumFileError {
+        error: "The second line must be of the form 'lowest_wavelength highest_wavelength number_of_samples'!".to_string()
+    };
+    let range: Vec<&str> = lines.next().ok_or_else(range_error)?.split_whitespace().collect();
+    if range.len() != 3 {
+        return Err(range_error());
+    }
+    let lowest = range[0].parse::<f32>().map_err(|_| range_error())?;
+    let highest = range[1].parse::<f32>().map_err(|_| range_error())?;
+    let nbr_of_samples = check_nbr_of_samples(range[2].parse::<usize>().map_err(|_| range_error())?)?;
+    if lowest >= highest {
+        return Err(SpectrumFileError {error: "The lowest wavelength must be smaller than the highest!".to_string()});
+    }
+
+    let intensities: Result<Vec<f32>, _> = lines.map(|line| line.parse::<f32>()).collect();
+    let intensities = intensities
+        .map_err(|_| SpectrumFileError {error: "Every intensity must be a number!".to_string()})?;
+    if intensities.len() != nbr_of_samples {
+        return Err(SpectrumFileError {
+            error: format!("Expected {nbr_of_samples} intensities, found {}!", intensities.len())
+        });
+    }
+
+    let mut arr = [0f32; NBR_OF_SAMPLES_MAX];
+    arr[0..nbr_of_samples].copy_from_slice(&intensities);
+    Ok(Spectrum::new_from_list(&arr, lowest, highest, nbr_of_samples))
+}
+
+/// Writes the spectrum in the internal format, see [parse_internal].
+pub fn to_internal(spectrum: &Spectrum) -> String {
+    let (lowest, highest) = spectrum.get_range();
+    let mut content = format!("{SPECTRUM_FILE_HEADER}\n{lowest} {highest} {}\n", spectrum.get_nbr_of_samples());
+    for (_, intensity) in spectrum.iter() {
+        content += &format!("{intensity}\n");
+    }
+    content
+}
+
+/// Returns the given number of samples if a spectrum can hold it, meaning it is a multiple of 8
+/// between 8 and [NBR_OF_SAMPLES_MAX].
+pub fn check_nbr_of_samples(nbr_of_samples: usize) -> Result<usize, SpectrumFileError> {
+    if nbr_of_samples == 0 || !nbr_of_samples.is_multiple_of(8) || nbr_of_samples > NBR_OF_SAMPLES_MAX {
+        return Err(SpectrumFileError {
+            error: format!("The number of samples must be a multiple of 8 between 8 and {NBR_OF_SAMPLES_MAX}, got {nbr_of_samples}!")
+        });
+    }
+    Ok(nbr_of_samples)
+}
+
+/// An error type used when reading or writing spectrum files. Specific details of the error are
+/// given in the error String.
+#[derive(Debug)]
+pub struct SpectrumFileError {
+    pub error: String,
+}
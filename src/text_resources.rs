@@ -1,6 +1,10 @@
 // settings 
 pub const IMAGE_WIDTH_TOOLTIP: &str = "The width of the image in pixels.";
 pub const IMAGE_HEIGHT_TOOLTIP: &str = "The height of the image in pixels.";
+pub const RENDER_SCALE_TOOLTIP: &str = "Renders at a fraction or a multiple of the width and \
+    height while the image keeps its size. At 25 % or 50 % a quick preview with a sixteenth or a \
+    quarter of the pixels is enlarged to the size of the image, at 200 % four pixels are rendered \
+    and averaged for every pixel of the image, smoothing its edges.";
 pub const NUMBER_OF_PARALLEL_THREADS_TOOLTIP: &str  = "The number of parallel threads computing the \
     image at any given time. The default value fully utilizes the CPU. If the computer is to be \
     used otherwise during the duration of the rendering process, it is recommended to reduce \
@@ -14,9 +18,71 @@ pub const MAX_BOUNCES_TOOLTIP: &str = "The maximum number of rays that will be t
     other objects. 2 means the new hit object shoots one additional ray, etc. Lower \
     numbers mean better performance, but light will spread less and the image will look darker. \
     Use 30 as a default.";
+pub const MAX_DIFFUSE_BOUNCES_TOOLTIP: &str = "The maximum number of diffuse bounces a path may \
+    take, independent of the overall maximum. Light scattered diffusely many times contributes \
+    little to the image, so this limit can be considerably lower than the specular one. 0 disables \
+    indirect diffuse light entirely.";
+pub const MAX_SPECULAR_BOUNCES_TOOLTIP: &str = "The maximum number of specular (mirror-like) \
+    bounces a path may take, independent of the overall maximum. Mirrors facing each other need \
+    high values here to look correct.";
+pub const MAX_TRANSMISSION_BOUNCES_TOOLTIP: &str = "The maximum number of times a path may pass \
+    through a surface into or out of an object, independent of the overall maximum. Every pane of \
+    glass takes two. No material refracts light yet, so this has no effect for now.";
+pub const RUSSIAN_ROULETTE_TOOLTIP: &str = "Randomly terminates paths which can only carry little \
+    light back to the camera, based on the reflectance of the surfaces hit so far. Surviving paths \
+    are brightened accordingly, the image stays correct on average but renders faster.";
+pub const SAMPLER_TOOLTIP: &str = "How the random numbers for pixel positions, lens samples and \
+    bounce directions are generated. The Sobol sampler spreads the samples of consecutive frames \
+    more evenly, giving less noise with few iterations than the PCG hash.";
+pub const RANDOM_SEED_TOOLTIP: &str = "Seeds the random numbers of the sampler. Rendering the same \
+    scene with a different seed gives a different, independent noise pattern.";
+pub const LIGHT_SELECTION_TOOLTIP: &str = "Which light sources are sampled for the direct light at \
+    every hit. Sampling all lights is exact but slow in scenes with many lights. Drawing a few \
+    lights at random is much faster and correct on average, at the cost of more noise. Power \
+    weighting draws bright lights more often, which gives less noise than drawing uniformly.";
+pub const LIGHT_SAMPLES_TOOLTIP: &str = "How many lights are drawn at every hit. More lights give \
+    less noise per iteration but take longer.";
+pub const FIREFLY_FILTER_TOOLTIP: &str = "Suppresses fireflies, single very bright pixels caused by \
+    rare light paths, which take very long to average out. Clamp limits every sample to a fixed \
+    luminance, outlier rejection limits it relative to the brightness the pixel has so far. Both \
+    slightly darken the image in exchange for much less noise.";
+pub const FIREFLY_CLAMP_TOOLTIP: &str = "Samples brighter than this luminance are darkened to it. \
+    A luminance of 1 is displayed as white.";
+pub const FIREFLY_OUTLIER_TOOLTIP: &str = "Samples more than this many times brighter than the \
+    pixel so far are darkened to that limit. Smaller values remove more fireflies but also more \
+    legitimate light.";
+pub const COLOR_RESPONSE_TOOLTIP: &str = "How the rendered spectra are converted into colors. The CIE \
+    observers show the scene as seen by the human eye: the 1964 10° observer describes larger \
+    colored areas, the 1931 2° observer small ones and is the basis of most colorimetric data, so \
+    choose it to compare colors with measured values. A camera sensor instead uses the red, green \
+    and blue sensitivity curves of that sensor, previewing how the camera would capture the scene. \
+    False color shows the scene as seen at a single wavelength, mapping its intensity onto a color \
+    map to visualize how materials behave at that wavelength.";
+pub const FALSE_COLOR_WAVELENGTH_TOOLTIP: &str = "The wavelength the scene is viewed at in nanometers.";
+pub const FALSE_COLOR_BANDWIDTH_TOOLTIP: &str = "The width of the band around the wavelength in \
+    nanometers, the intensities of all spectrum samples within it are averaged. If no sample falls \
+    into the band, the one closest to the wavelength is used.";
+pub const FALSE_COLOR_MAXIMUM_TOOLTIP: &str = "The intensity shown at the top of the color map, \
+    brighter parts of the scene are clipped to it.";
+pub const FALSE_COLOR_MAP_TOOLTIP: &str = "The colors the intensities from zero up to the maximum are \
+    mapped onto. Viridis and inferno keep differences visible across the whole range.";
+pub const LINEAR_OUTPUT_TOOLTIP: &str = "Displays and saves the 8 and 16 bit images with the linear \
+    colors of the render, only clamped to [0; 1], instead of encoding them with the sRGB curve \
+    screens and image viewers expect. The linear image appears too dark and contrasty, but keeps \
+    the raw values for further processing. OpenEXR and Radiance HDR files are always linear.";
+pub const GAMUT_COMPRESSION_TOOLTIP: &str = "Colors too saturated for the sRGB color space are \
+    desaturated until they fit, keeping their hue and brightness. Otherwise, the negative color \
+    components are simply cut off, which shifts the hue.";
+pub const SENSOR_RESPONSE_CSV_TOOLTIP: &str = "Load the sensitivity curves of a camera sensor. Each \
+    line of the file holds the wavelength in nanometers followed by the red, green and blue \
+    sensitivity, separated by commas.";
 
 
 // objects
+pub const CAMERA_ACTIVE_TOOLTIP: &str = "The camera from whose viewpoint the scene is rendered. \
+    Its settings are shown below.";
+pub const CAMERA_COPY_TOOLTIP: &str = "Adds a copy of this camera and makes it the active one, \
+    allowing another viewpoint to be set up.";
 pub const CAMERA_POSITION_TOOLTIP: &str = "The position of the camera in the scene."; 
 pub const CAMERA_DIRECTION_TOOLTIP: &str = "The direction in which the camera looks. In the default \
     scene, positive X is to the right, positive Y is upwards and positive Z looks into the screen.";
@@ -24,6 +90,25 @@ pub const CAMERA_UP_TOOLTIP: &str = "The direction which the camera considers to
     this value allows for tilted cameras.";
 pub const CAMERA_FOV_TOOLTIP: &str = "The vertical FOV of the camera. The horizontal FOV is \
     dependent on the vertical FOV and the aspect ratio."; 
+pub const CAMERA_THICK_LENS_TOOLTIP: &str = "Simulate a simple glass lens instead of a perfect \
+    pinhole. The lens has a limited depth of field, and since the refractive index of glass depends \
+    on the wavelength, each color is focused slightly differently, resulting in colored fringes \
+    (chromatic aberration). Each wavelength band requires its own ray, so rendering takes longer.";
+pub const CAMERA_APERTURE_RADIUS_TOOLTIP: &str = "The radius of the lens opening. Larger values \
+    result in a shallower depth of field. 0 keeps everything in focus.";
+pub const CAMERA_FOCUS_DISTANCE_TOOLTIP: &str = "The distance from the camera at which objects \
+    appear sharp, for yellow light (587.6 nm). With \"Click to focus\" checked on the display tab, \
+    it is set by clicking an object in the image.";
+pub const CAMERA_LENS_THICKNESS_TOOLTIP: &str = "The distance between the two principal planes of \
+    the lens. Rays leave the lens this far in front of the camera position.";
+pub const CAMERA_LENS_REFRACTIVE_INDEX_TOOLTIP: &str = "The refractive index of the lens glass for \
+    yellow light (587.6 nm). Typical crown glass has 1.52, flint glass about 1.62.";
+pub const CAMERA_LENS_ABBE_NUMBER_TOOLTIP: &str = "The Abbe number of the lens glass, describing \
+    how strongly the refractive index varies with the wavelength. Lower values mean stronger \
+    chromatic aberration. Crown glass has about 64, flint glass about 36.";
+pub const CAMERA_LENS_WAVELENGTH_BANDS_TOOLTIP: &str = "The number of bands the spectrum is split \
+    into. Each band is refracted separately by the lens. More bands produce smoother color fringes \
+    but take proportionally longer to render.";
 pub const LIGHT_SOURCE_TOOLTIP: &str = "The position of the light source in the scene.";
 pub const OBJECT_TYPE_TOOLTIP: &str = "The type of the object. The type determines its shape and \
     collision detection speed. Having many complex types may drastically lower rendering speed."; 
@@ -36,6 +121,14 @@ pub const LIGHT_SPECTRUM_TOOLTIP: &str = "The spectrum emitted by this light sou
     spectra can be adjusted in their respective tab.";
 pub const OBJECT_MATERIAL_TOOLTIP: &str = "The material of the object. This describes the way the \
     object will look like when rendered.";
+pub const OBJECT_EMISSION_TOOLTIP: &str = "Whether the surface of the object glows by itself, \
+    turning it into an area light such as a light panel.";
+pub const OBJECT_EMISSION_SPECTRUM_TOOLTIP: &str = "The spectrum emitted by the surface of the \
+    object. Each sample is the radiance emitted at this wavelength.";
+pub const OBJECT_EMISSION_VISIBLE_TO_CAMERA_TOOLTIP: &str = "Whether the camera directly sees the \
+    glow of the object. Disable to hide a light panel from view while it still lights the scene.";
+pub const OBJECT_EMISSION_VISIBLE_IN_REFLECTIONS_TOOLTIP: &str = "Whether the glow of the object \
+    is seen by light bouncing off other surfaces, for example in reflections or as indirect light.";
 pub const OBJECT_TYPE_PLAIN_BOX_TOOLTIP: &str = "The simplest shape, a simple box. This box can be \
     stretched and moved. It can, however, not be rotated, it is always axis-aligned. This shape is \
     the fastest to compute.";
@@ -47,6 +140,49 @@ pub const OBJECT_ROTATED_BOX_DIMENSIONS_TOOLTIP: &str = "The width, height and d
     box. Important: The dimensions are defined on a non-rotated box, only after will it be rotated.";
 pub const OBJECT_ROTATED_BOX_ANGLES_TOOLTIP: &str = "The rotation angles around the X, Y and Z \
     axis. The angles are in radians. The three angles are treated as euler-angles.";
+pub const OBJECT_TYPE_CYLINDER_TOOLTIP: &str = "A cylinder closed by two flat caps, for example for \
+    columns, tubes or light fixtures. It can be rotated as well as stretched and moved.";
+pub const OBJECT_CYLINDER_DIMENSIONS_TOOLTIP: &str = "The radius of the caps and the distance \
+    between them. Unrotated, the cylinder stands upright along the Y axis.";
+pub const OBJECT_TYPE_DISC_TOOLTIP: &str = "A flat, round disc without thickness, seen from both \
+    sides. Useful for round light panels or floors.";
+pub const OBJECT_TYPE_QUAD_TOOLTIP: &str = "A flat rectangle without thickness. It is single-sided, \
+    only visible from the side its normal points to. Useful as a light panel, floor or backdrop.";
+pub const OBJECT_TYPE_CSG_TOOLTIP: &str = "Two objects combined into a single one by a boolean \
+    operation (constructive solid geometry), for example a box with a spherical hole. Both parts \
+    take on the material of the CSG object. Flat objects enclose no volume and cannot be part of \
+    it.";
+pub const OBJECT_TYPE_HEIGHTFIELD_TOOLTIP: &str = "A terrain surface whose heights come from a \
+    grayscale image or from procedural noise. Its position is the center of its base. The surface \
+    is open below and at its sides and is seen from both sides.";
+pub const OBJECT_HEIGHTFIELD_EXTENTS_TOOLTIP: &str = "The width along the X axis, the depth along the \
+    Z axis and the height of the highest possible point above the base.";
+pub const OBJECT_HEIGHTFIELD_NOISE_TOOLTIP: &str = "The heights are generated from fractal noise. \
+    The seed chooses the terrain, the frequency is about the number of hills across the surface and \
+    every further octave adds finer details. At most 8 octaves are supported.";
+pub const OBJECT_HEIGHTFIELD_IMAGE_TOOLTIP: &str = "Loads the heights from an image seen from above, \
+    black being the base and white the full height. Color images are converted to grayscale and \
+    images larger than 1024 pixels are scaled down. The image is read once when loading it.";
+pub const OBJECT_CSG_OPERATION_TOOLTIP: &str = "How the two parts are combined. Union keeps \
+    everything inside either part, Intersection only what is inside both and Difference everything \
+    inside the first part which is not inside the second one.";
+pub const OBJECT_CSG_OFFSET_TOOLTIP: &str = "The position of the part relative to the position of \
+    the CSG object.";
+pub const OBJECT_DISC_RADIUS_TOOLTIP: &str = "The radius of the disc.";
+pub const OBJECT_QUAD_EXTENTS_TOOLTIP: &str = "The width and depth of the quad. Facing up, the width \
+    is measured along the X axis and the depth along the Z axis, before the quad is turned towards \
+    its normal.";
+pub const OBJECT_FLAT_NORMAL_TOOLTIP: &str = "The direction the surface faces. The length of the \
+    normal does not matter, but it may not be zero.";
+pub const OBJECT_TYPE_ELLIPSOID_TOOLTIP: &str = "A sphere stretched along its three axes, with a \
+    separate radius for each. Squashed spheres quickly fake many organic shapes such as pebbles, \
+    leaves or eggs.";
+pub const OBJECT_ELLIPSOID_RADII_TOOLTIP: &str = "The radii of the ellipsoid along its X, Y and Z \
+    axis, before it is rotated.";
+pub const OBJECT_ELLIPSOID_ANGLES_TOOLTIP: &str = "The rotation angles of the ellipsoid around the \
+    X, Y and Z axis. The angles are in radians. The three angles are treated as euler-angles.";
+pub const OBJECT_CYLINDER_ANGLES_TOOLTIP: &str = "The rotation angles of the cylinder around the X, \
+    Y and Z axis. The angles are in radians. The three angles are treated as euler-angles.";
 
 
 //spectra and materials
@@ -54,7 +190,13 @@ pub const SPECTRUM_NUMBER_OF_SAMPLES_TOOLTIP: &str = "The number of samples used
     Spectrum. Higher numbers mean clearer images and more accurate numbers but also higher \
     computing times. Multiples of 8 are most cost-efficient.";
 pub const SPECTRUM_RANGE_TOOLTIP: &str = "The lower and upper bound of the spectrum. The default \
-    values are the range of visible light.";
+    values are the range of visible light. Extending the range, e.g. to 300 nm - 1000 nm, also \
+    traces ultraviolet and near infrared light, which the eye and the camera sensors do not see \
+    but which can become visible through fluorescence or in a false color render. The samples are \
+    spread over the whole range, so more of them are needed for the same visible detail. Custom \
+    spectra are zero where they had no values before.";
+pub const SPECTRUM_RANGE_VISIBLE_TOOLTIP: &str = "Resets the range to the visible light from 380 nm \
+    to 780 nm.";
 pub const OBSERVED_COLOR_TOOLTIP: &str = "The color of the spectrum when looking directly at it. \
     Welding sparks and lightning flashes are not true white, but they are so bright that they \
     subjectively appear white. If this light source is bright enough, any color can appear white \
@@ -64,6 +206,15 @@ pub const NORMALIZED_COLOR_TOOLTIP: &str = "The color of the spectrum when it is
     distant object.";
 pub const REFLECTED_COLOR_TOOLTIP: &str = "The color of an (approximately) white spectrum when it \
     is reflected by a surface with this reflective spectrum.";
+pub const OUT_OF_GAMUT_TOOLTIP: &str = "This color is too saturated to be displayed in the sRGB \
+    color space, at least one of its color components is negative.";
+pub const CLIPPED_COLOR_TOOLTIP: &str = "The color with its negative components cut off, as it is \
+    displayed without gamut compression.";
+pub const GAMUT_MAPPED_COLOR_TOOLTIP: &str = "The color desaturated just enough to fit into the sRGB \
+    color space, as it is displayed with gamut compression.";
+pub const CHROMATICITY_DIAGRAM_TOOLTIP: &str = "The CIE xy chromaticity diagram. The grey outline \
+    contains all visible colors, the blue triangle all colors displayable in sRGB and the orange \
+    line the colors of black bodies of different temperatures. The dot marks this color.";
 pub const SPECTRUM_TYPE_TOOLTIP: &str = "The preliminary type of the spectrum. The type \
     determines the initial shape of the spectrum. The type can be changed to custom, which \
     allows for direct editing of the samples. \nBeware: In opposition to all other types, changing \
@@ -71,16 +222,16 @@ pub const SPECTRUM_TYPE_TOOLTIP: &str = "The preliminary type of the spectrum. T
 pub const SPECTRUM_EFFECT_TYPE_TOOLTIP: &str = "The way the spectrum is intended to behave. \
     There are two primary ways:\n\
     1. Emitting: The spectrum is a light source. Use this for light sources. The values can take \
-    any form, typically larger than 1 in many places.\n\
+    any form, typically larger than 1 in many places. Light sources are points and emit the \
+    values as spectral intensity (W/sr/nm), glowing objects emit them as spectral radiance \
+    (W/sr/m^2/nm) from every point of their surface.\n\
     2. Reflecting: The spectrum is not emitted. Instead it describes the share of each wavelength \
     that is reflected. Under white light, a reflecting spectrum with only 0.5 as its values will \
     appear as a medium gray. Reflection values must be in range [0; 1].";
 pub const SPECTRUM_RADIANCE_TOOLTIP: &str = "The radiance of the spectrum. The higher the number, \
     the greater the energy that is emitted. Can be thought of as the apparent brightness of the \
     spectrum. Note however that smaller wavelengths carry more energy, thereby skewing the numbers \
-    a bit.";
-pub const SPECTRUM_WAVELENGTH_EDIT_NOT_SUPPORTED_TOOLTIP: &str = "Editing the wavelength is not \
-    yet supported. Currently, only the entire visible spectrum can be used";
+    a bit. Used by a light source, the same number is its radiant intensity in W/sr.";
 pub const SPECTRUM_RIGHT_SLIDER_DISABLED_TOOLTIP: &str = "Editing spectra is not allowed unless \
     their type has been changed to custom. After a spectrum is converted to custom, the number of \
     samples should no longer be changed.";
@@ -100,6 +251,14 @@ pub const MATERIAL_ROUGHNESS_TOOLTIP: &str = "The roughness of a material. \
     It determines how perfect of a mirror, how polished, a metallic object is. Lower values make \
     a sharper mirror, higher values make it more blurry. A value of 0 skips the blurriness \
     computation, making the render faster.";
+pub const MATERIAL_ANISOTROPIC_TOOLTIP: &str = "Whether the material is rougher in one direction \
+    than in the other, like brushed metal. The roughness then applies along the tangent of the \
+    surface, a second roughness across it.";
+pub const MATERIAL_BITANGENT_ROUGHNESS_TOOLTIP: &str = "The roughness across the tangent of the \
+    surface. Reflections stretch along the rougher of the two directions.";
+pub const MATERIAL_TANGENT_ROTATION_TOOLTIP: &str = "The rotation of the tangent around the surface \
+    normal in radians. Without rotation, the tangent follows the X axis of the world as seen on \
+    the surface, or the Z axis on surfaces facing along X.";
 pub const MATERIAL_SPECTRUM_REFLECTING_TOOLTIP: &str = "The spectrum reflected by the material. Each \
     sample value is the share of this wavelength that is reflected. A spectrum of only 1 will \
     fully reflect every wavelength, essentially a perfectly white body.";
@@ -108,18 +267,428 @@ pub const MATERIAL_SPECTRUM_REFLECTING_TOOLTIP: &str = "The spectrum reflected b
 //display
 pub const DISPLAY_START_RENDERING_BUTTON_DISABLED_TOOLTIP: &str = "Cannot start rendering right \
     now. Maybe some lights or objects have illegal spectra assigned or you are already rendering \
-    something?";
+    something or estimating the render time?";
 pub const DISPLAY_ABORT_RENDERING_BUTTON_TOOLTIP: &str = "Aborts the current rendering process. \
-    The current frame will be finished, which may take a few more seconds.";
+    The rows being rendered are finished and the rest of the frame is left out, the pixels \
+    already rendered in the frame are kept. Render workers on other machines finish their tiles \
+    of the frame.";
+pub const DISPLAY_PAUSE_RENDERING_BUTTON_TOOLTIP: &str = "Pauses the current rendering process to \
+    free the CPU, the tiles being rendered are finished first. Resuming continues with the same \
+    image, no progress is lost. Render workers on other machines finish their tiles of the frame.";
+pub const DISPLAY_DENOISE_BUTTON_TOOLTIP: &str = "Removes the remaining noise of the last frame \
+    with an edge aware filter guided by the normals, albedos and depths of the render, so that a \
+    usable image comes out of far fewer frames. Flat areas are smoothed while the edges of \
+    objects, textures and sharp shadows are kept. The denoised image replaces the displayed and \
+    saved one until the next render.";
+pub const DISPLAY_LIVE_PREVIEW_TOOLTIP: &str = "Renders the scene continuously and starts over \
+    whenever the camera, an object or any other part of the scene or the render settings changes, \
+    so that the scene can be laid out while watching the image instead of typing coordinates and \
+    waiting for renders. Lower the resolution or the number of bounces for faster feedback. A \
+    running render is aborted when the preview is turned on.";
+pub const DISPLAY_CAMERA_NAVIGATION_TOOLTIP: &str = "Moves the active camera with the keyboard \
+    while the cursor is over the image: W, A, S and D move it forward, left, backward and right, E \
+    and Q up and down, holding shift moves it faster. Dragging the image turns the camera, \
+    scrolling still zooms the view. The live preview renders the moved camera again.";
+pub const DISPLAY_CAMERA_NAVIGATION_DISABLED_TOOLTIP: &str = "The navigation needs the live \
+    preview to show the moved camera.";
+pub const DISPLAY_CAMERA_NAVIGATION_SPEED_TOOLTIP: &str = "The distance the camera moves per \
+    second while a key is held.";
+pub const DISPLAY_CLICK_TO_FOCUS_TOOLTIP: &str = "Clicking the image sets the focus distance of \
+    the active camera to the depth of the object seen at the clicked pixel instead of probing its \
+    spectrum. With the live preview running, the refocused image is rendered right away.";
+pub const DISPLAY_CLICK_TO_FOCUS_DISABLED_TOOLTIP: &str = "Only a camera with a thick lens has a \
+    focus distance, enable it in the camera settings.";
+#[cfg(feature = "oidn")]
+pub const DISPLAY_OIDN_BUTTON_TOOLTIP: &str = "Denoises the last frame with Intel Open Image \
+    Denoise, a neural network trained on path traced images, guided by the normals and albedos of \
+    the render. It usually keeps more detail than the Denoise button, but needs the library \
+    installed.";
+#[cfg(feature = "oidn")]
+pub const DISPLAY_SHOW_OIDN_TOOLTIP: &str = "Switches between the frame denoised by Open Image \
+    Denoise and the rendered one, for comparing them. The shown one is also the one saved. The \
+    denoised frame is discarded with the next render.";
+pub const DISPLAY_SHOW_BUCKETS_TOOLTIP: &str = "Outlines the parts of the image which are \
+    currently being rendered, showing where the workers are.";
+pub const DISPLAY_TONE_MAPPER_TOOLTIP: &str = "How colors brighter than the screen can show are \
+    compressed. \"Clip\" cuts them off, so bright lights become flat white blobs. \"Reinhard\" \
+    compresses them gently keeping their hue, \"ACES\" and \"Filmic\" follow the response of \
+    film with a soft roll off into white. Applies to the displayed image and the saved 8 and 16 \
+    bit images, the OpenEXR and Radiance HDR files keep the untouched colors.";
+pub const DISPLAY_BLOOM_TOOLTIP: &str = "Lets very bright pixels glow into their surroundings, \
+    like the glare of a camera lens or an eye, instead of ending as flat white discs. The glow is \
+    taken from the float colors and shown without rendering anew. Applies to the saved 8 and 16 \
+    bit images as well, not to the OpenEXR and Radiance HDR files.";
+pub const DISPLAY_BLOOM_THRESHOLD_TOOLTIP: &str = "The luminance above which pixels glow, before \
+    the exposure is applied. Only the light above it is spread, 1 lets only what is brighter than \
+    white glow.";
+pub const DISPLAY_BLOOM_INTENSITY_TOOLTIP: &str = "The share of the light above the threshold \
+    which is added around the pixel as glow.";
+pub const DISPLAY_BLOOM_RADIUS_TOOLTIP: &str = "How far the glow reaches, relative to the larger \
+    side of the image. A faint haze reaches sixteen times as far.";
+pub const POST_EFFECTS_TOOLTIP: &str = "Stylistic effects applied one after the other to the \
+    displayed and saved 8 and 16 bit images after tone mapping. They imitate the look of real \
+    lenses and cameras without being physically based, so the OpenEXR and Radiance HDR files and the float \
+    colors of the render are kept without them.";
+pub const POST_EFFECT_VIGNETTE_TOOLTIP: &str = "Darkens the image towards its corners like the \
+    light fall off of a lens, by how much the corners are darkened.";
+pub const POST_EFFECT_CHROMATIC_FRINGE_TOOLTIP: &str = "Spreads red outwards and blue inwards \
+    towards the borders, like the colored fringes of a simple lens. The value is how far the \
+    colors are apart in the corners, relative to the distance from the center.";
+pub const POST_EFFECT_CONTRAST_TOOLTIP: &str = "Steepens the tones around middle gray with values \
+    above 1, so that shadows become darker and highlights brighter, and flattens them below 1.";
+pub const POST_EFFECT_GRAIN_TOOLTIP: &str = "Adds the noise of a camera sensor at this ISO speed \
+    for a photographic look: the shot noise of the counted light, strongest in the midtones, and \
+    a faint read noise in the shadows. The higher the ISO speed, the coarser the noise. It is \
+    added to the displayed and saved images only, the render itself stays clean.";
+pub const DISPLAY_EXPOSURE_TOOLTIP: &str = "Brightens or darkens the image before tone mapping, \
+    every stop (EV) doubles the brightness. Raise it to inspect the details in the shadows of a \
+    finished render, the image is shown again from its float colors without rendering anew. \
+    Applies to the saved 8 and 16 bit images as well, not to the OpenEXR and Radiance HDR files.";
+pub const DISPLAY_DOWNSAMPLED_TOOLTIP: &str = "The graphics card cannot display an image this \
+    large at once. The saved image keeps the full resolution. Click on a point of the image to \
+    inspect the area around it in full resolution.";
 pub const DISPLAY_IMAGE_TOOLTIP: &str = "Hold and drag the image to move it. Alternatively, use \
     the mouse scroll wheel to move up and down. Hold down [shift] and scroll to move left and \
-    right. Hold down [ctrl] and scroll to zoom in and out.";
+    right. Hold down [ctrl] and scroll to zoom in and out. Click a pixel to probe its \
+    spectrum.";
+pub const PIXEL_PROBE_TOOLTIP: &str = "The pixel under the cursor or, while the cursor is not \
+    over the image, the one clicked last. The values are the averages of the raw samples of the \
+    pixel, before the firefly filter, the exposure and the tone mapping. The XYZ values are \
+    computed from the spectrum with the standard observer of the color response, the RGB values \
+    are those of the color response itself.";
 
 
 //other stuff
 pub const EDIT_BUTTON_PENCIL_EMOJI: &str = "✏";
+pub const RESET_LAYOUT_TOOLTIP: &str = "Puts all tabs back into a single panel. Tabs can be \
+    dragged by their title to split the window and view several of them at once.";
+pub const SPECTRUM_COMPARISON_TOOLTIP: &str = "Select the spectra which are plotted on top of each \
+    other, e.g. to compare a custom light source with a reference or to check how well a spectrum \
+    survived resampling.";
+pub const SPECTRUM_COMPARISON_D65_TOOLTIP: &str = "Adds the CIE standard illuminant D65, average \
+    daylight, as a reference. In the absolute view it is scaled to 1 at 560nm.";
+pub const SPECTRUM_COMPARISON_NORMALIZED_TOOLTIP: &str = "Absolute plots the spectra as they are. \
+    Normalized scales each spectrum to a maximum of 1, comparing only their shapes.";
+pub const SPECTRUM_COMPARISON_SAMPLES_TOOLTIP: &str = "Marks the individual samples of each \
+    spectrum, showing where the curve is interpolated.";
+pub const ILLUMINANCE_HELPER_TOOLTIP: &str = "Sets the brightness factor such that a point light \
+    with this spectrum illuminates a surface facing it at the given distance with the given \
+    illuminance. One scene unit is taken to be one meter. For reference, an office is lit with \
+    about 500 lx, an overcast day gives about 1000 lx.";
+pub const LIGHT_SCALE_TOOLTIP: &str = "A multiplier applied to all light sources and emissive \
+    objects when rendering. The spectra themselves are not changed, a light scale of 1 renders \
+    them as they are.";
+pub const LIGHT_AUTO_BALANCE_TOOLTIP: &str = "Renders a small preview of the scene to measure its \
+    average luminance and proposes a light scale which brings it to a middle gray of 0.18.";
+pub const LIGHT_SCALE_RESET_TOOLTIP: &str = "Resets the light scale to 1, rendering all lights \
+    with their original spectra.";
+pub const RECORD_SPECTRA_TOOLTIP: &str = "Additionally sums up the spectrum of every sample \
+    during rendering, so that it is included in the raw accumulation export and shown by the \
+    pixel probe of the display tab. This takes one float \
+    per spectrum sample and pixel of memory.";
+pub const EXPORT_SPECTRAL_BANDS_TOOLTIP: &str = "Writes one grayscale OpenEXR image per sampled \
+    wavelength of the current render into a folder, holding the average spectral radiance of every \
+    pixel at that wavelength. Only available if spectra are recorded.";
+pub const EXPORT_HYPERSPECTRAL_CUBE_TOOLTIP: &str = "Writes the average spectrum of every pixel of \
+    the current render as a hyperspectral cube in the ENVI format, a .dat file with the data and a \
+    .hdr file with the wavelengths, for remote sensing and colorimetry tools. Only available if \
+    spectra are recorded.";
+pub const LIGHT_PASSES_TOOLTIP: &str = "Additionally records the light of every light source in a \
+    pass of its own, so that the lighting can be rebalanced in post-production without rendering \
+    again. The light of glowing objects, the sun and the background, caustics and the indirect \
+    light of the irradiance cache are not part of any pass. Takes 24 bytes per light and pixel of \
+    memory and is not supported by render workers.";
+pub const EXPORT_ACCUMULATION_TOOLTIP: &str = "Writes the raw sums of all samples per pixel, the \
+    number of samples per pixel and, if recorded, the summed spectra of the current render into a \
+    folder as NumPy .npy files. This is possible while rendering as well.";
+pub const SCENE_CHECK_TOOLTIP: &str = "The visible objects are checked for problems which do not \
+    stop the rendering process but lead to missing objects or artifacts: objects without size, \
+    objects the intersection test can not hit, objects enclosing the camera, boxes with \
+    overlapping faces in the same plane and objects reflecting almost none of a light.";
+pub const REFLECTED_COLORS_TOOLTIP: &str = "The emitted spectrum of every light source and \
+    glowing object multiplied with the reflectance of every object, normalized to full \
+    brightness. Objects which reflect almost nothing of a light appear black under it, e.g. blue \
+    objects under a red light.";
+pub const PATH_DUMP_TOOLTIP: &str = "Traces the paths of this many pixels, spread evenly over \
+    the image, and writes every ray of them to a JSON or CSV file: origin, direction, the hit \
+    object and the spectrum carried back along the ray, tagged with the sampled wavelengths. \
+    Useful to debug changes to the renderer or to illustrate path tracing.";
+pub const PBRT_EXPORT_TOOLTIP: &str = "Writes the scene in the PBRT v4 format to compare the \
+    render with an established spectral renderer. Spectra are exported as they are, materials \
+    and the camera as closely as PBRT allows.";
+pub const DISPLAY_RAYS_TRACED_TOOLTIP: &str = "The number of rays shot since the render started, \
+    shadow rays included.";
+
+pub const GALLERY_HASH_TOOLTIP: &str = "A fingerprint of everything influencing the image: the \
+    settings, the active camera and the visible lights and objects with their spectra and \
+    materials. Renders with the same hash give the same image.";
+pub const GALLERY_SHOW_TOOLTIP: &str = "Shows the render in the display tab again, from where it \
+    can be saved.";
+pub const GALLERY_COMPARE_TOOLTIP: &str = "Compares the image shown in the display tab with this \
+    render, see \"File\" -> \"Compare with Reference\". Both have to be of the same size.";
+pub const GALLERY_RESTORE_TOOLTIP: &str = "Replaces the current settings and the whole scene with \
+    the ones this render was started with, so that it can be reproduced or varied. Unsaved changes \
+    to the current scene are lost.";
+
+#[cfg(feature = "scripting")]
+pub const SCRIPT_RUN_TOOLTIP: &str = "Runs the Rhai script, which adds the spectra, materials, \
+    objects and lights it creates to the scene. Spectra and materials with the name of an existing \
+    one update it instead. Nothing is changed if the script fails.";
+#[cfg(feature = "scripting")]
+pub const SCRIPT_REPLACE_SCENE_TOOLTIP: &str = "Removes all objects and lights before the script \
+    runs, so that running it again replaces what it created the last time instead of adding it a \
+    second time. Spectra, materials and cameras are kept.";
+
+pub const TUTORIAL_NEXT_STEP_TOOLTIP: &str = "Applies the changes of the next step on top of the \
+    current settings.";
+pub const TUTORIAL_RESTART_TOOLTIP: &str = "Loads the scene of the tutorial again and returns to its \
+    first step. Changes made to the scene are lost.";
+
 pub const EDIT_BUTTON_TOOLTIP: &str = "Change the name of this element.";
-pub const HELP_MENU_LABEL: &str = "For a simple tutorial, see the README.md file. For explanations \
-    what the different settings do, hover over them for a short period of time to see their \
-    tooltips.";
-pub const COPIED_ELEMENT_NAME_INDICATOR: &str = " (copy)";
\ No newline at end of file
+pub const HELP_MENU_LABEL: &str = "For a simple tutorial, see the README.md file or follow one of \
+    the guided scenes below. For explanations what the different settings do, hover over them for \
+    a short period of time to see their tooltips.";
+pub const COPIED_ELEMENT_NAME_INDICATOR: &str = " (copy)";
+pub const GROUP_TOOLTIP: &str = "Groups are named nodes of the scene hierarchy. Their position and \
+    rotation apply to all objects, lights and groups assigned to them, so that a whole arrangement \
+    can be moved at once.";
+pub const GROUP_POSITION_TOOLTIP: &str = "The translation of the group. The positions of everything \
+    in the group are relative to it.";
+pub const GROUP_ROTATION_TOOLTIP: &str = "The rotation of the group around the X, Y and Z axis in \
+    radians, treated as euler-angles. Everything in the group turns around the position of the \
+    group. Quads lose any twist around their normal and heightfields can not be rotated.";
+pub const GROUP_PARENT_TOOLTIP: &str = "The group this group is nested in. Its translation and \
+    rotation are applied after those of this group. A group can not be nested in itself or in a \
+    group nested in it.";
+pub const GROUP_DELETE_TOOLTIP: &str = "Deletes the group. Everything in it moves up to its parent \
+    group, keeping its own position relative to the new group.";
+pub const OBJECT_GROUP_TOOLTIP: &str = "The group the object belongs to. Its position and rotation \
+    are then relative to the group.";
+pub const LIGHT_GROUP_TOOLTIP: &str = "The group the light source belongs to. Its position is then \
+    relative to the group.";
+pub const OBJECT_TEXTURE_TOOLTIP: &str = "An image which colors the surface of the object. The \
+    reflectance of the material is multiplied with a smooth spectrum of the color of each texel, \
+    so a white texture leaves the material unchanged. Spheres are wrapped like a globe, every side \
+    of a box shows the whole image. Other object types ignore the texture.";
+pub const MATERIAL_PATTERN_TOOLTIP: &str = "A procedural pattern which blends the reflectance of the \
+    material between its reflecting spectrum and a second spectrum. The pattern is evaluated at the \
+    hit point in world space, as if objects were carved out of a patterned block.";
+pub const MATERIAL_PATTERN_SPECTRUM_TOOLTIP: &str = "The spectrum the pattern blends the reflectance \
+    towards. Where the pattern is fully on, the material reflects like this spectrum.";
+pub const MATERIAL_PATTERN_CHECKER_TOOLTIP: &str = "A three dimensional checkerboard of cubes with \
+    the given edge length, alternating between the two spectra.";
+pub const MATERIAL_PATTERN_NOISE_TOOLTIP: &str = "Fractal value noise. The size is the size of the \
+    coarsest features, every further octave adds details of half the size and half the strength. \
+    Different seeds give different noise.";
+pub const MATERIAL_PATTERN_GRADIENT_TOOLTIP: &str = "A linear transition along the chosen axis of the \
+    world. Before the start, the material spectrum is used, after the end the pattern spectrum.";
+pub const SPECTRUM_COLOR_CHECKER_TOOLTIP: &str = "One of the 24 patches of the ColorChecker chart, \
+    numbered row by row from the top left. The reflectance is the average measured spectrum of the \
+    patch, so it also behaves like the real chart under lights other than daylight.";
+pub const MATERIAL_MIX_TOOLTIP: &str = "Makes this material a mix of two other materials. Every \
+    hit is shaded with one of the two, chosen at random by the share of the second material. All \
+    other settings of this material are ignored while it is a mix.";
+pub const MATERIAL_MIX_DISABLED_TOOLTIP: &str = "There is no other material to mix.";
+pub const MATERIAL_MIX_MATERIALS_TOOLTIP: &str = "The materials which are mixed. Materials which \
+    contain this material in their own mix can not be chosen.";
+pub const MATERIAL_MIX_WEIGHT_TOOLTIP: &str = "How much of the second material shows. Constant uses \
+    the same share everywhere, a pattern varies the share over the surface and Fresnel shows the \
+    second material at grazing angles, like a clear coat on top of the first.";
+pub const MATERIAL_MIX_FRESNEL_TOOLTIP: &str = "The index of refraction of the coat. Higher values \
+    show more of the second material, also when looking straight at the surface.";
+pub const MATERIAL_FLUORESCENCE_TOOLTIP: &str = "Makes the material fluorescent, like highlighters or \
+    optical brighteners: a part of the light it absorbs is re-emitted at longer wavelengths, evenly \
+    into all directions. The absorbed light should not be reflected as well, the reflecting \
+    spectrum and the absorbed spectrum should add up to at most 1 at every wavelength.";
+pub const MATERIAL_FLUORESCENCE_YIELD_TOOLTIP: &str = "The share of absorbed photons which are \
+    re-emitted. Each re-emitted photon carries less energy than the absorbed one, as its wavelength \
+    is longer.";
+pub const MATERIAL_FLUORESCENCE_EXCITATION_TOOLTIP: &str = "The share of each wavelength which is \
+    absorbed to be re-emitted, usually short wavelengths like ultraviolet and blue light.";
+pub const MATERIAL_FLUORESCENCE_EMISSION_TOOLTIP: &str = "The shape of the re-emitted spectrum, only \
+    its relative values matter. Light is only re-emitted at wavelengths longer than the absorbed one.";
+pub const MEDIUM_TOOLTIP: &str = "Fills the whole scene with a homogeneous medium like fog or haze. \
+    Light travelling through it is absorbed and scattered out of its way, distant objects fade. \
+    Rendering takes longer, as every segment of every path is attenuated.";
+pub const MEDIUM_DISABLED_TOOLTIP: &str = "The fog needs spectra for its absorption and scattering, \
+    add a spectrum first.";
+pub const MEDIUM_DENSITY_TOOLTIP: &str = "The absorption and scattering coefficient per unit of \
+    length a spectrum value of 1 stands for. At a density of 1, light of such a wavelength is \
+    weakened to about a third over a distance of 1.";
+pub const MEDIUM_IN_SCATTERING_TOOLTIP: &str = "Gathers the light of the light sources which the fog \
+    scatters towards the camera, so that beams of light and the glow around lights become visible. \
+    Only light scattered once is gathered.";
+pub const MEDIUM_ABSORPTION_TOOLTIP: &str = "How strongly the fog absorbs each wavelength, relative to \
+    the density. Absorbed light is lost.";
+pub const MEDIUM_SCATTERING_TOOLTIP: &str = "How strongly the fog scatters each wavelength, relative \
+    to the density. Scattered light is taken out of its way, but may reach the camera from the side \
+    if light beams are enabled. Scattering short wavelengths more gives a blueish haze.";
+pub const ATMOSPHERE_TOOLTIP: &str = "Surrounds the scene with the atmosphere of an earth-like planet \
+    lit by the sun. The sky is the sunlight scattered by air molecules, which scatter short \
+    wavelengths far more, and by aerosols, computed for every wavelength. A low sun shines through \
+    much more air, which turns it and the sky around it red. The sun also lights the scene itself.";
+pub const ATMOSPHERE_DISABLED_TOOLTIP: &str = "The sun needs a spectrum, add a spectrum first.";
+pub const ATMOSPHERE_SUN_POSITION_TOOLTIP: &str = "The elevation of the sun above the horizon and its \
+    azimuth in degrees, counted from the z axis towards the x axis. Below an elevation of 0, the sun \
+    has set and only the twilight remains.";
+pub const ATMOSPHERE_TURBIDITY_TOOLTIP: &str = "The amount of aerosols like dust and water droplets \
+    relative to a clear day. More aerosols make the sky hazy and whitish and the glow around the sun \
+    larger.";
+pub const ATMOSPHERE_ALTITUDE_TOOLTIP: &str = "The height of the scene above sea level in meters. The \
+    sky gets darker with less air above.";
+pub const ATMOSPHERE_SUN_SPECTRUM_TOOLTIP: &str = "The irradiance of the sun above the atmosphere, \
+    before any of it is scattered. The light scale applies to it like to all light sources.";
+pub const PHOTON_COUNT_TOOLTIP: &str = "How many photons are traced from the lights every iteration to \
+    find the caustics, the light focused onto diffuse surfaces by metallic reflections. The path \
+    tracer can hardly find these, as its rays never hit the lights. 0 turns the photon map off.";
+pub const PHOTON_RADIUS_TOOLTIP: &str = "The radius around every diffuse hit within which photons are \
+    gathered. Larger radii give less noisy but blurrier caustics.";
+pub const IRRADIANCE_CACHE_TOOLTIP: &str = "Computes the indirect light of diffuse surfaces only at \
+    sparse points before the first iteration and interpolates in between, instead of tracing a \
+    bounce at every hit. Much faster for mostly diffuse scenes, but the interpolation can leave \
+    blotches, which do not average out over the iterations.";
+pub const IRRADIANCE_CACHE_QUALITY_TOOLTIP: &str = "Higher qualities place the points of the cache \
+    more densely, trace more rays from each and interpolate less far. This takes longer before the \
+    first iteration, but gives fewer blotches.";
+pub const LIGHT_LINKING_TOOLTIP: &str = "Which objects the light source illuminates. Unlinked objects \
+    receive no direct light and no caustics from it, but still see it in reflections and receive \
+    the light bounced off other objects.";
+pub const LIGHT_LINKED_OBJECTS_TOOLTIP: &str = "Whether the object is linked to the light source. \
+    Depending on the mode, only linked objects are lit, or all but the linked ones.";
+pub const MATERIAL_SHADOW_CATCHER_TOOLTIP: &str = "The camera sees through surfaces of this material, \
+    except for the shadows other objects cast onto them. Place a shadow catcher where the ground of \
+    a photograph would be to composite the render onto it, then save the image as PNG to keep the \
+    transparency. Reflections and bounce light still see the surface.";
+pub const OBJECT_VISIBILITY_TOOLTIP: &str = "Which rays hit the object. Leaving some out allows \
+    lighting tricks, like a bounce card which lights the scene without showing up in the image. \
+    Hiding the object leaves all of them out.";
+pub const OBJECT_VISIBLE_TO_CAMERA_TOOLTIP: &str = "Whether the camera sees the object. If not, it \
+    sees whatever lies behind it instead.";
+pub const OBJECT_VISIBLE_IN_REFLECTIONS_TOOLTIP: &str = "Whether the object shows up in the \
+    reflections of other objects. If not, it also passes no bounce light on to them.";
+pub const OBJECT_CASTS_SHADOWS_TOOLTIP: &str = "Whether the object blocks the light of the light \
+    sources and the sun, casting shadows and focusing caustics.";
+pub const RAY_OFFSET_TOOLTIP: &str = "How far above a surface the rays leaving it start, so that they do \
+    not hit the surface again due to floating point imprecision. The distance is relative to how far \
+    the hit lies from the origin and from the start of the ray. Increase it if surfaces show dark \
+    speckles (shadow acne), decrease it if light leaks through thin objects or into corners.";
+pub const RAY_OFFSET_RESET_TOOLTIP: &str = "Resets the ray offset to its default.";
+pub const CAMERA_CLIPPING_TOOLTIP: &str = "Cuts away everything closer than the near plane or farther \
+    than the far plane, e.g. to look into a closed room through one of its walls. Clipped objects \
+    are not seen, but everything else still is, including their light and shadows. The distances \
+    are measured along the view direction.";
+pub const CAMERA_NEAR_CLIP_TOOLTIP: &str = "The camera sees nothing closer than this distance.";
+pub const CAMERA_FAR_CLIP_TOOLTIP: &str = "The camera sees nothing farther than this distance, the \
+    sky or background shows instead.";
+pub const BACKGROUND_TOOLTIP: &str = "What rays leaving the scene without hitting anything see: black, \
+    the radiance of a spectrum from every direction, a vertical gradient between two spectra or \
+    nothing at all. A transparent background is black, but the camera sees through it, so the \
+    saved PNG can be laid over another image, e.g. together with shadow catchers. The atmosphere \
+    replaces the background.";
+pub const TRANSPARENT_FILM_TOOLTIP: &str = "Makes every pixel which sees nothing of the scene \
+    transparent, while the background or the atmosphere still light the objects and show in \
+    reflections. Saved as PNG or OpenEXR, the rendered objects can then be composited over other \
+    imagery.";
+pub const BACKGROUND_SPECTRUM_TOOLTIP: &str = "The radiance arriving from every direction. The light \
+    scale applies to it like to all light sources.";
+pub const BACKGROUND_GRADIENT_TOOLTIP: &str = "The radiance arriving from straight up and from \
+    straight down, in between both are blended. The light scale applies to them like to all light \
+    sources.";
+pub const SCENE_SAVE_TOOLTIP: &str = "Writes the scene with its spectra, materials, cameras and the \
+    render settings into a file. Textures and heightfield images are stored by their path, the \
+    curves of a custom camera sensor are not stored.";
+pub const SCENE_OPEN_TOOLTIP: &str = "Replaces the scene and the render settings with those of a \
+    saved scene file.";
+pub const RESET_SETTINGS_TOOLTIP: &str = "Resets the settings and the scene to their defaults. The \
+    resolution, threads, iterations, bounces and number of samples are no longer restored on the \
+    next start.";
+pub const SCENE_WATCH_TOOLTIP: &str = "Opens the scene file opened or saved last again whenever it \
+    changes on disk and renders it with a few iterations, so that the scene can be edited in a \
+    text editor with live feedback. A running render is aborted.";
+pub const RENDER_WORKERS_TOOLTIP: &str = "The addresses of other machines running this app with the \
+    'worker' subcommand, separated by commas, for example 192.168.0.12:7878. The scene is sent to \
+    them at the start of a render and they render a share of the tiles of every frame, in \
+    proportion to their threads. Textures and heightfield images have to exist at the same paths \
+    on the workers. Workers are not used when recording spectra or with a custom sensor.";
+pub const RENDER_WORKER_TOKEN_TOOLTIP: &str = "The token the render workers were started with \
+    ('worker --token TOKEN'). Workers refuse to render for a master sending another token. It is \
+    sent unencrypted, so workers should only be reachable through a trusted network.";
+pub const REMOTE_CONTROL_TOOLTIP: &str = "Lets other programs on this machine control the app through \
+    HTTP on the given port: GET /status returns the progress as JSON, GET /image the displayed \
+    image as PNG, POST /render starts a render (of the scene file sent as body, if any) and POST \
+    /abort aborts it. Only connections from localhost are accepted.";
+pub const WRITE_CHECKPOINTS_TOOLTIP: &str = "Saves the render to the file 'render.checkpoint' in the \
+    working directory every minute and when it is aborted, so that it can be continued with \
+    'Edit' -> 'Resume Render' after a crash or shutdown. The file is removed once the render \
+    completes.";
+pub const RESUME_RENDER_TOOLTIP: &str = "Opens the scene of the last checkpoint and continues its \
+    render from the frame it was saved at, instead of starting over. Only available if \
+    checkpoints were written, see 'Write checkpoints' in the settings.";
+pub const STATISTICS_CAMERA_RAYS_TOOLTIP: &str = "The rays starting the paths, one per pixel and \
+    frame, plus those gathering light for the irradiance cache.";
+pub const STATISTICS_BOUNCE_RAYS_TOOLTIP: &str = "The rays continuing the paths after they were \
+    reflected or refracted at a surface or scattered in a medium.";
+pub const STATISTICS_SHADOW_RAYS_TOOLTIP: &str = "The rays testing whether a light source or the \
+    sun is visible from a hit.";
+pub const STATISTICS_PHOTON_RAYS_TOOLTIP: &str = "The rays of the caustic photons traced from the \
+    light sources, 0 unless photon mapping is enabled.";
+pub const STATISTICS_AVERAGE_BOUNCES_TOOLTIP: &str = "The average number of bounces of a path, \
+    which depends on the maximum bounces, russian roulette and how much light the materials \
+    absorb.";
+pub const STATISTICS_RAYS_PER_SECOND_TOOLTIP: &str = "All rays shot divided by the time the render \
+    took so far. Rays of distributed workers are not counted.";
+pub const RENDER_ESTIMATE_MEMORY_TOOLTIP: &str = "The memory taken by the image buffers while \
+    rendering, which grows with the resolution and, if the spectra are recorded, with the number \
+    of spectrum samples. The scene itself is not included.";
+pub const RENDER_ESTIMATE_TIME_TOOLTIP: &str = "Extrapolated from a quick render at a low \
+    resolution without the irradiance cache, so it is only a rough guess.";
+pub const DISPLAY_BUFFER_TOOLTIP: &str = "What is shown of the render. \"Noise heatmap\" shows how \
+    noisy every pixel still is: dark blue below 0.1% relative error, through green and yellow to \
+    red above 10%. The error is estimated from the variance of the luminance of the samples of a \
+    pixel, gray pixels have too few samples to tell. \"Normals\" shows the shading normal each \
+    pixel sees first, with x, y, z as red, green, blue. \"Depth\" shows the distance to what each \
+    pixel sees first, from white at the near to black at the far distance. \"Albedo\" shows the \
+    reflectance of what each pixel sees first, without any lighting. \"Object IDs\" shows every \
+    object in a color of its own, derived from its name. If light passes are recorded, each of \
+    them can be shown as well.";
+pub const DISPLAY_DEPTH_RANGE_TOOLTIP: &str = "The distances from the camera shown white and black \
+    in the depth view, also used when saving the depth in an 8 bit format.";
+pub const DISPLAY_NOISE_ERROR_TOOLTIP: &str = "The noise of a pixel halves with every quadrupling \
+    of its samples, from which the remaining frames are extrapolated.";
+pub const COMPARE_REFERENCE_TOOLTIP: &str = "Measures how much the shown image differs from a \
+    reference image of the same size, e.g. a render with many more samples or one without an \
+    optimization. An OpenEXR or Radiance HDR reference is compared with the float colors of the \
+    render, any other format with the displayed image, which is exposed and tone mapped like a \
+    saved PNG.";
+pub const COMPARISON_RMSE_TOOLTIP: &str = "The root mean square error of the linear colors. Noise \
+    makes it fall with more samples, a bias keeps it from falling further.";
+pub const COMPARISON_SSIM_TOOLTIP: &str = "The structural similarity of the brightness of the \
+    images, 1 if they are equal and lower the more their local structure differs.";
+pub const COMPARISON_FLIP_TOOLTIP: &str = "The mean FLIP error, which estimates how noticeable the \
+    differences are to someone flipping between the images on a monitor, from 0 for no visible \
+    difference to 1.";
+pub const COMPARISON_HEATMAP_TOOLTIP: &str = "The FLIP error of every pixel, from black for no \
+    visible difference over purple and orange to light yellow for the largest.";
+pub const SAVE_NOISE_HEATMAP_TOOLTIP: &str = "Saves the noise heatmap of the last render as an \
+    image, see the \"Noise heatmap\" view of the display tab.";
+pub const SAVE_IMAGE_16_BIT_TOOLTIP: &str = "Saves the image as a PNG with 16 bits per channel \
+    instead of 8, converted from the float colors of the render with a little dither noise, so \
+    that subtle gradients do not band when the image is edited further. For the colors above \
+    white as well, save it as .hdr instead.";
+pub const SAVE_NORMALS_TOOLTIP: &str = "Saves the shading normals the camera sees first, e.g. as \
+    an auxiliary image for external denoisers or for compositing. OpenEXR keeps the x, y, z \
+    values in range [-1; 1] as floats, the other formats store them mapped to [0; 255].";
+pub const SAVE_DEPTH_TOOLTIP: &str = "Saves the distance from the camera to what each pixel sees \
+    first, e.g. for depth of field or fog in compositing. OpenEXR keeps the distances as floats, \
+    with infinity where nothing is hit, the other formats store them normalized to the near and \
+    far distance of the depth view.";
+pub const SAVE_ALBEDO_TOOLTIP: &str = "Saves the reflectance of what each pixel sees first as \
+    linear RGB, the auxiliary image external denoisers expect along with the normals. OpenEXR \
+    keeps the values as floats, the other formats store them as 8 bit.";
+pub const SAVE_OBJECT_IDS_TOOLTIP: &str = "Saves every object the camera sees in a color of its \
+    own, for masking individual objects in post-production. The color is derived from the name \
+    of the object and stays the same as long as the name does. Pixels shared by several objects \
+    mix their colors by coverage.";
+pub const SAVE_LIGHT_PASSES_TOOLTIP: &str = "Saves the light pass of every light source as an \
+    OpenEXR file into the chosen folder, numbered in the order of the lights. Scaling the passes \
+    and adding them up rebalances the lighting by the light sources.";
\ No newline at end of file
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+/// The maximum width and height of textures, larger images are scaled down to keep the memory in
+/// check. Textures are seldom seen closely enough for this to be noticeable.
+const TEXTURE_RESOLUTION_MAX: u32 = 4096;
+
+/// An image which colors the surface of objects. The texels are stored in linear RGB, the sRGB
+/// gamma of the image file is removed when it is read.
+#[derive(Debug)]
+pub struct Texture {
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    /// The texels row by row from the top of the image, x changing fastest.
+    texels: Vec<(f32, f32, f32)>,
+}
+
+impl Texture {
+    /// Reads the texture from the image at the given path.
+    pub fn from_image(path: &Path) -> Result<Self, TextureError> {
+        let mut image = image::open(path)
+            .map_err(|e| TextureError {error: format!("Could not read {}: {e}", path.display())})?;
+        if image.width() == 0 || image.height() == 0 {
+            return Err(TextureError {error: "The image is empty!".to_string()});
+        }
+        if image.width().max(image.height()) > TEXTURE_RESOLUTION_MAX {
+            image = image.resize(TEXTURE_RESOLUTION_MAX, TEXTURE_RESOLUTION_MAX,
+                                 image::imageops::FilterType::Triangle);
+        }
+
+        let rgb = image.to_rgb32f();
+        let texels = rgb.pixels()
+            .map(|pixel| (srgb_to_linear(pixel.0[0]), srgb_to_linear(pixel.0[1]), srgb_to_linear(pixel.0[2])))
+            .collect();
+        Ok(Texture {
+            path: path.to_path_buf(),
+            width: rgb.width() as usize,
+            height: rgb.height() as usize,
+            texels,
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the width and the height of the texture in texels.
+    pub fn get_resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns the linear RGB color of the texture at the given texture coordinates, bilinearly
+    /// interpolated between the four nearest texels. u runs from the left to the right edge of the
+    /// image, v from its bottom to its top edge. Coordinates outside of \[0; 1] repeat the texture.
+    pub fn sample(&self, u: f32, v: f32) -> (f32, f32, f32) {
+        //texel centers lie at half texel offsets
+        let x = u.rem_euclid(1.0) * self.width as f32 - 0.5;
+        let y = (1.0 - v.rem_euclid(1.0)) * self.height as f32 - 0.5;
+        let (x_floor, y_floor) = (x.floor(), y.floor());
+        let (x_frac, y_frac) = (x - x_floor, y - y_floor);
+
+        let texel = |x: f32, y: f32| {
+            let x = (x as isize).rem_euclid(self.width as isize) as usize;
+            let y = (y as isize).rem_euclid(self.height as isize) as usize;
+            self.texels[y * self.width + x]
+        };
+        let lerp = |a: (f32, f32, f32), b: (f32, f32, f32), t: f32| {
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+        };
+        let top = lerp(texel(x_floor, y_floor), texel(x_floor + 1.0, y_floor), x_frac);
+        let bottom = lerp(texel(x_floor, y_floor + 1.0), texel(x_floor + 1.0, y_floor + 1.0), x_frac);
+        lerp(top, bottom, y_frac)
+    }
+}
+
+/// Hands out the textures of image files, reading each file only once while a texture of it is
+/// in use. The cache only keeps weak references, textures no object uses anymore are freed.
+#[derive(Default)]
+pub struct TextureCache {
+    textures: HashMap<PathBuf, Weak<Texture>>,
+}
+
+impl TextureCache {
+    /// Returns the texture of the image at the given path, reading the image if no texture of it
+    /// is in use yet.
+    pub fn get(&mut self, path: &Path) -> Result<Arc<Texture>, TextureError> {
+        if let Some(texture) = self.textures.get(path).and_then(Weak::upgrade) {
+            return Ok(texture);
+        }
+
+        let texture = Arc::new(Texture::from_image(path)?);
+        self.textures.retain(|_, texture| texture.strong_count() > 0);
+        self.textures.insert(path.to_path_buf(), Arc::downgrade(&texture));
+        Ok(texture)
+    }
+}
+
+/// An error type used when reading a texture. Specific details of the error are given in the
+/// error String.
+#[derive(Debug)]
+pub struct TextureError {
+    pub error: String,
+}
+
+/// Removes the sRGB gamma from a color channel in range \[0; 1].
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
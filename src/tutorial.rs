@@ -0,0 +1,415 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use crate::{SpectrumEffectType, UIBackground, UICamera, UIColorResponse, UIFields, UILight, UIMaterial, UIObject, UIObjectType,
+            UISpectrum, UISpectrumType};
+use crate::custom_image::FireflyFilter;
+use crate::light_selection::LightSelection;
+use crate::sampler::SamplerType;
+use crate::spectrum::Spectrum;
+
+/// The guided scenes offered in the help menu. Each one loads a scene and then walks through a
+/// number of steps, each of which explains a feature and enables it in the settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tutorial {
+    WindowLitRoom,
+    NightScene,
+    Dispersion,
+}
+
+impl Tutorial {
+    pub const ALL: [Tutorial; 3] = [Tutorial::WindowLitRoom, Tutorial::NightScene, Tutorial::Dispersion];
+
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            Tutorial::WindowLitRoom => "A room lit by the sun through a single window. Learn about \
+                indirect light, bounces and noise.",
+            Tutorial::NightScene => "A street lamp at night under a cold moon. Learn about light \
+                temperatures, glowing objects and scenes with many lights.",
+            Tutorial::Dispersion => "A bright light seen through a glass lens. Learn about the thick \
+                lens, depth of field and how glass splits light into its colors.",
+        }
+    }
+
+    fn get_steps(&self) -> &'static [TutorialStep] {
+        match self {
+            Tutorial::WindowLitRoom => &WINDOW_LIT_ROOM_STEPS,
+            Tutorial::NightScene => &NIGHT_SCENE_STEPS,
+            Tutorial::Dispersion => &DISPERSION_STEPS,
+        }
+    }
+
+    /// Replaces the scene of the UI values with the scene of the tutorial. The spectra are only
+    /// created with their type and have to be generated afterwards.
+    fn load_scene(&self, ui_values: &mut UIFields) {
+        match self {
+            Tutorial::WindowLitRoom => window_lit_room(ui_values),
+            Tutorial::NightScene => night_scene(ui_values),
+            Tutorial::Dispersion => dispersion_scene(ui_values),
+        }
+        ui_values.selected_spectrum = None;
+        ui_values.compared_spectra.clear();
+    }
+}
+
+impl Display for Tutorial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tutorial::WindowLitRoom => write!(f, "Window-lit room"),
+            Tutorial::NightScene => write!(f, "Night scene with mixed light temperatures"),
+            Tutorial::Dispersion => write!(f, "Dispersion in a glass lens"),
+        }
+    }
+}
+
+/// A single step of a [Tutorial]. Its changes are applied on top of those of all previous steps.
+struct TutorialStep {
+    title: &'static str,
+    text: &'static str,
+    apply: fn(&mut UIFields),
+}
+
+/// The tutorial the user currently follows and how far they got.
+pub struct TutorialProgress {
+    tutorial: Tutorial,
+    step: usize,
+}
+
+impl TutorialProgress {
+    /// Loads the scene of the tutorial and applies its first step.
+    pub fn start(tutorial: Tutorial, ui_values: &mut UIFields) -> Self {
+        tutorial.load_scene(ui_values);
+        (tutorial.get_steps()[0].apply)(ui_values);
+        TutorialProgress {tutorial, step: 0}
+    }
+
+    /// Applies the next step, if there is one.
+    pub fn next(&mut self, ui_values: &mut UIFields) {
+        if !self.is_last_step() {
+            self.step += 1;
+            (self.tutorial.get_steps()[self.step].apply)(ui_values);
+        }
+    }
+
+    pub fn get_tutorial(&self) -> Tutorial {
+        self.tutorial
+    }
+
+    /// Returns the index of the current step and the number of steps.
+    pub fn get_step(&self) -> (usize, usize) {
+        (self.step, self.tutorial.get_steps().len())
+    }
+
+    pub fn get_title(&self) -> &'static str {
+        self.tutorial.get_steps()[self.step].title
+    }
+
+    pub fn get_text(&self) -> &'static str {
+        self.tutorial.get_steps()[self.step].text
+    }
+
+    pub fn is_last_step(&self) -> bool {
+        self.step + 1 == self.tutorial.get_steps().len()
+    }
+}
+
+const WINDOW_LIT_ROOM_STEPS: [TutorialStep; 4] = [
+    TutorialStep {
+        title: "Direct light only",
+        text: "The sun shines through the window onto the floor. There is no sky light and no \
+            portal, all light entering the room has to come through the window. \n\n\
+            Rays are only allowed a single bounce, so only the spot hit by the sun directly is lit. \
+            Start the render and look at the result in the display tab.",
+        apply: |ui_values| {
+            ui_values.nbr_of_iterations = 20;
+            ui_values.nbr_of_ray_bounces = 1;
+            ui_values.nbr_of_diffuse_bounces = 1;
+            ui_values.russian_roulette = false;
+        },
+    },
+    TutorialStep {
+        title: "Indirect light",
+        text: "Real light does not stop at the first surface. The sunlit spot on the floor lights \
+            the walls, which light the ceiling and so on. \n\n\
+            The number of bounces is now raised to 5. Render again: the room is filled with soft \
+            indirect light, while the rays mostly end in the dark corners.",
+        apply: |ui_values| {
+            ui_values.nbr_of_ray_bounces = 5;
+            ui_values.nbr_of_diffuse_bounces = 5;
+        },
+    },
+    TutorialStep {
+        title: "Less noise",
+        text: "Indirect light is noisy, as every pixel has to find the small sunlit spot by chance. \
+            More iterations average the noise away. The Sobol sampler spreads the samples of \
+            consecutive iterations more evenly and russian roulette stops paths which carry little \
+            light, which makes more bounces affordable. \n\n\
+            All three are now enabled, together with 10 bounces and 100 iterations.",
+        apply: |ui_values| {
+            ui_values.nbr_of_iterations = 100;
+            ui_values.nbr_of_ray_bounces = 10;
+            ui_values.nbr_of_diffuse_bounces = 10;
+            ui_values.russian_roulette = true;
+            ui_values.sampler_type = SamplerType::Sobol;
+        },
+    },
+    TutorialStep {
+        title: "Fireflies and exposure",
+        text: "Single very bright pixels, fireflies, appear where a rare path found the sun after \
+            many bounces. The firefly filter now rejects such outliers. \n\n\
+            If the room is too dark or too bright, open the settings tab and let the light \
+            scale be balanced automatically, the spectra themselves stay unchanged.",
+        apply: |ui_values| {
+            ui_values.firefly_filter = FireflyFilter::OutlierRejection(10.0);
+        },
+    },
+];
+
+const NIGHT_SCENE_STEPS: [TutorialStep; 4] = [
+    TutorialStep {
+        title: "A warm street lamp",
+        text: "A street lamp lights the scene at night. Its light is the black body radiation of \
+            2700 K, like an incandescent bulb, which looks orange. \n\n\
+            Start the render to see the lamp on its own.",
+        apply: |ui_values| {
+            ui_values.nbr_of_iterations = 30;
+            ui_values.nbr_of_ray_bounces = 4;
+            ui_values.nbr_of_diffuse_bounces = 4;
+            set_hidden(ui_values, &["Moon", "Shop sign", "Shop sign light"], true);
+        },
+    },
+    TutorialStep {
+        title: "Mixed light temperatures",
+        text: "The moon is now visible. Moonlight is reflected sunlight, but at night the eye \
+            perceives it as cold blue, which is mimicked by 8000 K. \n\n\
+            Render again: surfaces facing the lamp are warm, those only lit by the moon are cold, \
+            and the shadows of each light are tinted by the other one.",
+        apply: |ui_values| {
+            set_hidden(ui_values, &["Moon"], false);
+        },
+    },
+    TutorialStep {
+        title: "Glowing objects",
+        text: "A shop sign is added. It is a glowing object, its surface emits light which can be \
+            seen by the camera, as can the glowing bulb of the lamp. A light source directly in \
+            front of the sign lights its surroundings. \n\n\
+            Glowing objects and light sources can be edited in the objects tab.",
+        apply: |ui_values| {
+            set_hidden(ui_values, &["Shop sign", "Shop sign light"], false);
+        },
+    },
+    TutorialStep {
+        title: "Many lights and camera sensors",
+        text: "Every light is sampled at every hit, which gets slow with many lights. Power weighted \
+            light selection draws a single light per hit instead, bright ones more often. \n\n\
+            The colors are now converted with the response of a typical camera sensor instead of \
+            the human eye. Compare the result with the previous render in the gallery.",
+        apply: |ui_values| {
+            ui_values.light_selection = LightSelection::Power;
+            ui_values.light_samples = 1;
+            ui_values.color_response = UIColorResponse::TypicalCmos;
+        },
+    },
+];
+
+const DISPERSION_STEPS: [TutorialStep; 3] = [
+    TutorialStep {
+        title: "A pinhole camera",
+        text: "A small bright light hangs in front of a row of white spheres. The scene is seen \
+            through a pinhole camera, which is always perfectly sharp. \n\n\
+            Objects cannot refract light yet, so the prism of this tutorial is the glass lens of \
+            the camera. Start the render to see the scene without it.",
+        apply: |ui_values| {
+            ui_values.nbr_of_iterations = 50;
+            ui_values.ui_cameras[ui_values.active_camera].thick_lens = false;
+        },
+    },
+    TutorialStep {
+        title: "A thick lens",
+        text: "The camera now looks through a thick glass lens with an open aperture. Only objects \
+            at the focus distance are sharp, the light behind them is blurred into a disc. \n\n\
+            The lens is made of crown glass, which hardly disperses light, so the blur stays \
+            white.",
+        apply: |ui_values| {
+            let camera = &mut ui_values.ui_cameras[ui_values.active_camera];
+            camera.thick_lens = true;
+            camera.aperture_radius = 0.15;
+            camera.focus_distance = 2.0;
+            camera.lens_refractive_index = 1.5168;
+            camera.lens_abbe_number = 64.17;
+            camera.lens_wavelength_bands = 4;
+        },
+    },
+    TutorialStep {
+        title: "Dispersion",
+        text: "Glass refracts short wavelengths more strongly than long ones, the lower its Abbe \
+            number the stronger. The lens is now made of an extremely dispersive glass and traced \
+            in 8 wavelength bands. \n\n\
+            Render again: each band is focused at a slightly different distance, the blurred light \
+            gets colored fringes, just like white light split by a prism.",
+        apply: |ui_values| {
+            let camera = &mut ui_values.ui_cameras[ui_values.active_camera];
+            camera.lens_refractive_index = 1.75;
+            camera.lens_abbe_number = 15.0;
+            camera.lens_wavelength_bands = 8;
+        },
+    },
+];
+
+/// Hides or shows all lights and objects with one of the given names.
+fn set_hidden(ui_values: &mut UIFields, names: &[&str], hidden: bool) {
+    for light in ui_values.ui_lights.iter_mut().filter(|l| names.contains(&l.name.as_str())) {
+        light.hidden = hidden;
+    }
+    for object in ui_values.ui_objects.iter_mut().filter(|o| names.contains(&o.name.as_str())) {
+        object.hidden = hidden;
+    }
+}
+
+/// Creates a spectrum of the given type. The samples are only a placeholder, they are generated
+/// from the type once the scene is loaded.
+fn new_spectrum(name: &str, spectrum_type: UISpectrumType, effect_type: SpectrumEffectType) -> Rc<RefCell<UISpectrum>> {
+    let placeholder = Spectrum::new_equal_size_empty_spectrum(&UISpectrum::default().spectrum);
+    Rc::new(RefCell::new(UISpectrum::new(name.to_string(), spectrum_type, effect_type, placeholder)))
+}
+
+fn new_material(name: &str, metallicness: f32, roughness: f32, spectrum: &Rc<RefCell<UISpectrum>>) -> Rc<RefCell<UIMaterial>> {
+    Rc::new(RefCell::new(UIMaterial::new(metallicness, roughness, spectrum.clone(), name.to_string())))
+}
+
+/// Adds a box with the given center and lengths.
+fn new_box(name: &str, center: (f32, f32, f32), lengths: (f32, f32, f32), material: &Rc<RefCell<UIMaterial>>) -> UIObject {
+    UIObject::new(center.0, center.1, center.2, material.clone(),
+                  UIObjectType::PlainBox(lengths.0, lengths.1, lengths.2), name.to_string())
+}
+
+/// A closed room of 4 × 2.5 × 4 units with a window in the right wall. The sun stands high up
+/// to the right, so that it shines through the window onto the floor.
+fn window_lit_room(ui_values: &mut UIFields) {
+    let sun = new_spectrum("Sunlight", UISpectrumType::Solar(100.0), SpectrumEffectType::Emissive);
+    let white = new_spectrum("White paint", UISpectrumType::PlainReflective(0.8), SpectrumEffectType::Reflective);
+    let floor = new_spectrum("Wooden floor", UISpectrumType::ReflectiveRed(0.6), SpectrumEffectType::Reflective);
+    let green = new_spectrum("Green", UISpectrumType::ReflectiveGreen(0.8), SpectrumEffectType::Reflective);
+
+    let wall_material = new_material("Wall paint", 0.0, 0.0, &white);
+    let floor_material = new_material("Floor", 0.0, 0.0, &floor);
+    let ball_material = new_material("Green plastic", 0.0, 0.0, &green);
+
+    ui_values.ui_lights = vec![
+        UILight::new(700.0, 700.0, 0.0, sun.clone(), "Sun".to_string()),
+    ];
+    ui_values.ui_objects = vec![
+        new_box("Floor", (0.0, -1.1, 0.0), (4.4, 0.2, 4.4), &floor_material),
+        new_box("Ceiling", (0.0, 1.6, 0.0), (4.4, 0.2, 4.4), &wall_material),
+        new_box("Left wall", (-2.1, 0.25, 0.0), (0.2, 2.5, 4.4), &wall_material),
+        new_box("Back wall", (0.0, 0.25, 2.1), (4.0, 2.5, 0.2), &wall_material),
+        new_box("Front wall", (0.0, 0.25, -2.1), (4.0, 2.5, 0.2), &wall_material),
+        //the right wall is built around the window opening from y 0 to 1 and z -0.5 to 0.5
+        new_box("Wall below window", (2.1, -0.5, 0.0), (0.2, 1.0, 4.4), &wall_material),
+        new_box("Wall above window", (2.1, 1.25, 0.0), (0.2, 0.5, 4.4), &wall_material),
+        new_box("Wall left of window", (2.1, 0.5, -1.35), (0.2, 1.0, 1.7), &wall_material),
+        new_box("Wall right of window", (2.1, 0.5, 1.35), (0.2, 1.0, 1.7), &wall_material),
+        UIObject::new(-0.8, -0.6, 0.8, ball_material.clone(), UIObjectType::Sphere(0.4), "Ball".to_string()),
+    ];
+    ui_values.spectra = vec![sun, white, floor, green];
+    ui_values.materials = vec![wall_material, floor_material, ball_material];
+    ui_values.ui_groups = Vec::new();
+    ui_values.medium = None;
+    ui_values.atmosphere = None;
+    ui_values.background = UIBackground::Black;
+    ui_values.ui_cameras = vec![UICamera {
+        name: "Room camera".to_string(),
+        pos_x: -0.5,
+        pos_y: 0.3,
+        pos_z: -1.8,
+        dir_x: 0.3,
+        dir_y: -0.25,
+        dir_z: 1.0,
+        fov_deg_y: 70.0,
+        ..UICamera::default()
+    }];
+    ui_values.active_camera = 0;
+}
+
+/// A street with a lamp, a cold moon high above and a glowing shop sign with a light in front of
+/// it. The moon and the sign are enabled by the later steps of the tutorial.
+fn night_scene(ui_values: &mut UIFields) {
+    let lamp = new_spectrum("Incandescent 2700 K", UISpectrumType::Temperature(2700.0, 0.05), SpectrumEffectType::Emissive);
+    let moon = new_spectrum("Moonlight 8000 K", UISpectrumType::Temperature(8000.0, 5.0), SpectrumEffectType::Emissive);
+    let sign = new_spectrum("Cold LED 8000 K", UISpectrumType::Temperature(8000.0, 0.00003), SpectrumEffectType::Emissive);
+    let grey = new_spectrum("Asphalt", UISpectrumType::PlainReflective(0.3), SpectrumEffectType::Reflective);
+    let white = new_spectrum("Plaster", UISpectrumType::PlainReflective(0.8), SpectrumEffectType::Reflective);
+    let dark = new_spectrum("Dark paint", UISpectrumType::PlainReflective(0.1), SpectrumEffectType::Reflective);
+
+    let street_material = new_material("Street", 0.0, 0.0, &grey);
+    let wall_material = new_material("House wall", 0.0, 0.0, &white);
+    let pole_material = new_material("Lamp pole", 0.6, 0.3, &dark);
+
+    let mut bulb = UIObject::new(0.0, 1.1, 0.5, wall_material.clone(), UIObjectType::Sphere(0.1), "Lamp bulb".to_string());
+    bulb.emission = Some(lamp.clone());
+    let mut shop_sign = new_box("Shop sign", (-1.5, 0.8, 2.0), (1.0, 0.3, 0.05), &wall_material);
+    shop_sign.emission = Some(sign.clone());
+
+    ui_values.ui_lights = vec![
+        //between the pole and the bulb, so that neither casts a shadow
+        UILight::new(0.0, 0.95, 0.5, lamp.clone(), "Street lamp".to_string()),
+        UILight::new(-300.0, 900.0, -300.0, moon.clone(), "Moon".to_string()),
+        UILight::new(-1.5, 0.8, 1.7, sign.clone(), "Shop sign light".to_string()),
+    ];
+    ui_values.ui_objects = vec![
+        new_box("Street", (0.0, -1.1, 1.0), (20.0, 0.2, 10.0), &street_material),
+        new_box("House", (0.0, 1.0, 3.0), (8.0, 4.0, 1.8), &wall_material),
+        new_box("Lamp pole", (0.0, -0.05, 0.5), (0.06, 1.9, 0.06), &pole_material),
+        bulb,
+        shop_sign,
+        UIObject::new(1.2, -0.6, 1.0, wall_material.clone(), UIObjectType::Sphere(0.4), "Ball".to_string()),
+    ];
+    ui_values.spectra = vec![lamp, moon, sign, grey, white, dark];
+    ui_values.materials = vec![street_material, wall_material, pole_material];
+    ui_values.ui_groups = Vec::new();
+    ui_values.medium = None;
+    ui_values.atmosphere = None;
+    ui_values.background = UIBackground::Black;
+    ui_values.ui_cameras = vec![UICamera {
+        name: "Street camera".to_string(),
+        pos_y: 0.2,
+        pos_z: -3.0,
+        dir_y: -0.1,
+        ..UICamera::default()
+    }];
+    ui_values.active_camera = 0;
+}
+
+/// A small, very bright light in front of a row of white spheres in an otherwise black scene,
+/// which makes the color fringes of a dispersive lens easy to see.
+fn dispersion_scene(ui_values: &mut UIFields) {
+    let light = new_spectrum("Daylight", UISpectrumType::Solar(0.0002), SpectrumEffectType::Emissive);
+    let glow = new_spectrum("Bright glow", UISpectrumType::Solar(0.001), SpectrumEffectType::Emissive);
+    let white = new_spectrum("White", UISpectrumType::PlainReflective(0.9), SpectrumEffectType::Reflective);
+    let white_material = new_material("White plastic", 0.0, 0.0, &white);
+
+    let mut glowing_sphere = UIObject::new(0.8, 0.5, 5.0, white_material.clone(), UIObjectType::Sphere(0.05), "Glowing sphere".to_string());
+    glowing_sphere.emission = Some(glow.clone());
+    let mut objects = vec![glowing_sphere];
+    for i in 0..5 {
+        let x = -1.0 + 0.5 * i as f32;
+        objects.push(UIObject::new(x, -0.3, 1.0 + 0.5 * i as f32, white_material.clone(),
+                                   UIObjectType::Sphere(0.15), format!("Sphere {}", i + 1)));
+    }
+
+    ui_values.ui_lights = vec![
+        UILight::new(0.0, 1.0, 0.0, light.clone(), "Light".to_string()),
+    ];
+    ui_values.ui_objects = objects;
+    ui_values.spectra = vec![light, glow, white];
+    ui_values.materials = vec![white_material];
+    ui_values.ui_groups = Vec::new();
+    ui_values.medium = None;
+    ui_values.atmosphere = None;
+    ui_values.background = UIBackground::Black;
+    ui_values.ui_cameras = vec![UICamera {
+        name: "Lens camera".to_string(),
+        ..UICamera::default()
+    }];
+    ui_values.active_camera = 0;
+}